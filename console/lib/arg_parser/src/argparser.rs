@@ -1,9 +1,13 @@
-use std::{env, collections::HashSet};
+use std::{collections::{HashMap, HashSet}, sync::Arc};
+
+#[cfg(test)]
+mod tests;
 
 ///
 ///An enum representing a command-line
 ///argument
 ///
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Arg {
     ///
     ///An argument whose presence
@@ -19,6 +23,26 @@ pub enum Arg {
 }
 
 impl Arg {
+    ///
+    ///The key of the argument
+    ///
+    pub fn key(&self) -> &str {
+        match self {
+            Arg::Flag(k) => k,
+            Arg::Pair(k, _) => k
+        }
+    }
+
+    ///
+    ///The same argument, under a different key
+    ///
+    pub fn with_key(self, key: &str) -> Self {
+        match self {
+            Arg::Flag(_) => Arg::Flag(String::from(key)),
+            Arg::Pair(_, v) => Arg::Pair(String::from(key), v)
+        }
+    }
+
     pub fn to_key_value_pair(&self) -> (String, String) {
         match self {
             Arg::Flag(k) => (k.to_string(), true.to_string()),
@@ -27,6 +51,356 @@ impl Arg {
     }
 }
 
+///
+///A check on the value of an argument, returning a
+///description of the problem if it's invalid
+///
+#[derive(Clone)]
+pub struct Validator(Arc<ValidatorFn>);
+
+type ValidatorFn = dyn Fn(&str) -> Result<(), String> + Send + Sync;
+
+impl Validator {
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        (self.0)(value)
+    }
+}
+
+impl std::fmt::Debug for Validator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Validator")
+    }
+}
+
+///
+///Declaration of an argument, used to validate parsed
+///arguments against
+///
+#[derive(Clone, Debug)]
+pub struct ArgSpec {
+    ///
+    ///The key of the argument
+    ///
+    key: String,
+    ///
+    ///Whether parsing fails if the argument is absent
+    ///
+    required: bool,
+    ///
+    ///The value given to the argument if it's absent
+    ///
+    default: Option<String>,
+    ///
+    ///What the argument does
+    ///
+    description: String,
+    ///
+    ///Placeholder naming the type of the argument's
+    ///value, or None if the argument is a flag
+    ///
+    value: Option<String>,
+    ///
+    ///Other keys the argument may be given by
+    ///
+    aliases: Vec<String>,
+    ///
+    ///The values the argument accepts. Only
+    ///enforced without a validator, in which
+    ///case they're documentation
+    ///
+    choices: Vec<String>,
+    ///
+    ///Check on the argument's value
+    ///
+    validator: Option<Validator>,
+    ///
+    ///Whether the argument may be given more
+    ///than once
+    ///
+    multiple: bool
+}
+
+impl ArgSpec {
+    ///
+    ///Create a new declaration of an optional argument
+    ///with the given key, and no default
+    ///
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: String::from(key),
+            required: false,
+            default: None,
+            description: String::new(),
+            value: None,
+            aliases: Vec::new(),
+            choices: Vec::new(),
+            validator: None,
+            multiple: false
+        }
+    }
+
+    ///
+    ///Set whether the argument is required and return
+    ///self
+    ///
+    pub fn with_required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    ///
+    ///Set the value given to the argument if it's absent
+    ///and return self
+    ///
+    pub fn with_default(mut self, default: &str) -> Self {
+        self.default = Some(String::from(default));
+        self
+    }
+
+    ///
+    ///Set the description of the argument and return
+    ///self
+    ///
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = String::from(description);
+        self
+    }
+
+    ///
+    ///Set the placeholder naming the type of the argument's
+    ///value and return self
+    ///
+    pub fn with_value(mut self, value: &str) -> Self {
+        self.value = Some(String::from(value));
+        self
+    }
+
+    ///
+    ///Add another key the argument may be given by and
+    ///return self
+    ///
+    pub fn with_alias(mut self, alias: &str) -> Self {
+        self.aliases.push(String::from(alias));
+        self
+    }
+
+    ///
+    ///Set the values the argument accepts, ignoring ASCII
+    ///case, and return self
+    ///
+    pub fn with_choices<T: AsRef<str>, I: IntoIterator<Item = T>>(mut self, choices: I) -> Self {
+        self.choices = choices.into_iter().map(|choice| String::from(choice.as_ref())).collect();
+        self
+    }
+
+    ///
+    ///Set a check on the argument's value and return self.
+    ///The choices are then only listed in help, so they may
+    ///describe values, i.e. <hex color>
+    ///
+    pub fn with_validator<F: Fn(&str) -> Result<(), String> + Send + Sync + 'static>(mut self, validator: F) -> Self {
+        self.validator = Some(Validator(Arc::new(validator)));
+        self
+    }
+
+    ///
+    ///Set whether the argument may be given more than once
+    ///and return self
+    ///
+    pub fn with_multiple(mut self, multiple: bool) -> Self {
+        self.multiple = multiple;
+        self
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn is_multiple(&self) -> bool {
+        self.multiple
+    }
+
+    ///
+    ///Check the value given to the argument against its
+    ///validator, or its choices
+    ///
+    pub fn check(&self, value: &str, settings: &ParseArgsSettings) -> Result<(), String> {
+        match &self.validator {
+            Some(validator) => validator.validate(value)
+                .map_err(|err| format!("Invalid value for argument '{}{}': {err}", settings.prefix, self.key)),
+            None if self.choices.is_empty() || self.choices.iter().any(|choice| choice.eq_ignore_ascii_case(value)) => Ok(()),
+            None => Err(format!("Argument '{}{}' must be one of {}, but was '{value}'.", settings.prefix, self.key, self.choices.join(", ")))
+        }
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    pub fn choices(&self) -> &[String] {
+        &self.choices
+    }
+
+    ///
+    ///Whether the argument is given by the key, or
+    ///one of its aliases
+    ///
+    pub fn matches(&self, key: &str) -> bool {
+        self.key == key || self.aliases.iter().any(|alias| alias == key)
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    pub fn default(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+impl From<&str> for ArgSpec {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+///
+///A subcommand, selected by the first argument, along
+///with the arguments it accepts
+///
+#[derive(Clone, Debug)]
+pub struct Subcommand {
+    ///
+    ///The name which selects the subcommand. Matched
+    ///ignoring ASCII case
+    ///
+    name: String,
+    ///
+    ///What the subcommand does
+    ///
+    description: String,
+    ///
+    ///The arguments the subcommand accepts
+    ///
+    arguments: Vec<ArgSpec>
+}
+
+impl Subcommand {
+    ///
+    ///Create a new subcommand with the given name, which
+    ///accepts no arguments
+    ///
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            description: String::new(),
+            arguments: Vec::new()
+        }
+    }
+
+    ///
+    ///Set the description of the subcommand and return
+    ///self
+    ///
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = String::from(description);
+        self
+    }
+
+    ///
+    ///Add an argument the subcommand accepts and return
+    ///self
+    ///
+    pub fn with_argument<T: Into<ArgSpec>>(mut self, argument: T) -> Self {
+        self.arguments.push(argument.into());
+        self
+    }
+
+    ///
+    ///Add several arguments the subcommand accepts and
+    ///return self
+    ///
+    pub fn with_arguments<T: Into<ArgSpec>, I: IntoIterator<Item = T>>(mut self, arguments: I) -> Self {
+        self.arguments.extend(arguments.into_iter().map(|argument| argument.into()));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    ///
+    ///Whether the subcommand accepts an argument with
+    ///the given key
+    ///
+    pub fn accepts(&self, key: &str) -> bool {
+        self.arguments.iter().any(|argument| argument.matches(key))
+    }
+
+    pub fn arguments(&self) -> &[ArgSpec] {
+        &self.arguments
+    }
+
+    ///
+    ///Check arguments parsed with the given settings against
+    ///the subcommand's declarations, filling in defaults. Used
+    ///when the subcommand wasn't chosen by the first argument
+    ///
+    pub fn validate(&self, args: Vec<Arg>, settings: &ParseArgsSettings) -> Result<Vec<Arg>, Vec<String>> {
+        apply_schema(args, &self.arguments, settings, Some(&self.name))
+    }
+}
+
+///
+///The subcommand selected by a collection of command
+///line arguments, and the arguments following it
+///
+pub struct SubcommandMatch {
+    ///
+    ///The name of the subcommand, as it was defined, or
+    ///None if no subcommand was given
+    ///
+    pub name: Option<String>,
+    ///
+    ///The arguments following the subcommand
+    ///
+    pub args: Vec<Arg>
+}
+
+///
+///The values of parsed arguments, by key. Arguments
+///which may be repeated have a value for each time
+///they were given
+///
+pub type ArgValues = HashMap<String, Vec<String>>;
+
+///
+///Group parsed arguments by key, keeping the order
+///repeated arguments were given in
+///
+pub fn collect_values(args: &[Arg]) -> ArgValues {
+    let mut values = ArgValues::new();
+
+    for arg in args {
+        let (key, value) = arg.to_key_value_pair();
+        values.entry(key).or_default().push(value);
+    }
+
+    values
+}
+
 ///
 ///Settings to modify the execution of parse_args
 ///
@@ -39,7 +413,24 @@ pub struct ParseArgsSettings {
     ///The value separating keys and values
     ///of arguments
     ///
-    delimiter: String
+    delimiter: String,
+    ///
+    ///The arguments which may be given, if
+    ///declared
+    ///
+    arguments: Option<Vec<ArgSpec>>,
+    ///
+    ///The key of an argument which asks for
+    ///help, in whose presence required arguments
+    ///may be omitted
+    ///
+    help_key: Option<String>,
+    ///
+    ///Arguments read from a config file, used
+    ///where the same key isn't given on the
+    ///command line
+    ///
+    config: Option<Vec<Arg>>
 }
 
 impl Default for ParseArgsSettings {
@@ -56,7 +447,10 @@ impl ParseArgsSettings {
     pub fn new() -> Self {
         Self {
             prefix: String::from(""),
-            delimiter: String::from("=")
+            delimiter: String::from("="),
+            arguments: None,
+            help_key: None,
+            config: None
         }
     }
 
@@ -67,7 +461,10 @@ impl ParseArgsSettings {
     pub fn init(prefix: String, delimiter: String) -> Self {
         Self {
             prefix,
-            delimiter
+            delimiter,
+            arguments: None,
+            help_key: None,
+            config: None
         }
     }
 
@@ -89,19 +486,70 @@ impl ParseArgsSettings {
         self
     }
 
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn delimiter(&self) -> &str {
+        &self.delimiter
+    }
+
+    ///
+    ///Declare an argument on given instance of ParseArgsSettings
+    ///and return self. Once any argument is declared, parse_args
+    ///rejects keys which weren't
+    ///
+    pub fn with_argument<T: Into<ArgSpec>>(&mut self, argument: T) -> &Self {
+        self.arguments.get_or_insert_with(Vec::new).push(argument.into());
+        self
+    }
+
+    ///
+    ///Set the arguments read from a config file on given instance
+    ///of ParseArgsSettings and return self. Arguments given on the
+    ///command line override those with the same key, and the
+    ///help argument is only taken from the command line
+    ///
+    pub fn with_config(&mut self, config: Vec<Arg>) -> &Self {
+        self.config = Some(config);
+        self
+    }
+
+    ///
+    ///Set the key of the help argument on given instance of
+    ///ParseArgsSettings and return self
+    ///
+    pub fn with_help_key(&mut self, help_key: String) -> &Self {
+        self.help_key = Some(help_key);
+        self
+    }
+
+    ///
+    ///Whether the key is that of the help argument
+    ///
+    fn is_help_key(&self, key: &str) -> bool {
+        self.help_key.as_deref() == Some(key)
+    }
+
 }
 
 impl Clone for ParseArgsSettings {
     fn clone(&self) -> Self {
         Self {
             prefix: self.prefix.to_string(),
-            delimiter: self.delimiter.to_string()
+            delimiter: self.delimiter.to_string(),
+            arguments: self.arguments.clone(),
+            help_key: self.help_key.clone(),
+            config: self.config.clone()
         }
     }
 
     fn clone_from(&mut self, source: &Self) {
         self.prefix = source.prefix.to_string();
         self.delimiter = source.delimiter.to_string();
+        self.arguments.clone_from(&source.arguments);
+        self.help_key.clone_from(&source.help_key);
+        self.config.clone_from(&source.config);
     }
 }
 
@@ -109,11 +557,176 @@ impl Clone for ParseArgsSettings {
 ///Create a vector of Arg from a collection
 ///of command line arguments.
 ///
-pub fn parse_args_with_opts(args: env::Args, settings: ParseArgsSettings) -> Result<Vec<Arg>, Vec<String>> {
+pub fn parse_args_with_opts<I: IntoIterator<Item = String>>(args: I, settings: ParseArgsSettings) -> Result<Vec<Arg>, Vec<String>> {
+    //Skip the program name
+    parse_declared(args.into_iter().skip(1), &settings)
+}
+
+///
+///Parse arguments which don't include the program name, and
+///check them against those declared on the settings, if any
+///
+fn parse_declared<I: Iterator<Item = String>>(tokens: I, settings: &ParseArgsSettings) -> Result<Vec<Arg>, Vec<String>> {
+    let mut parsed = parse_tokens(tokens, settings)?;
+
+    match &settings.arguments {
+        Some(schema) => apply_schema(parsed, schema, settings, None),
+        None => {
+            check_duplicates(&parsed, |_| false).map_err(|err| vec![err])?;
+
+            if let Some(config) = &settings.config {
+                let from_config: Vec<Arg> = config.iter()
+                    .filter(|arg| !settings.is_help_key(arg.key()))
+                    .filter(|arg| !parsed.iter().any(|given| given.key() == arg.key()))
+                    .cloned()
+                    .collect();
+
+                parsed.extend(from_config);
+            }
+
+            Ok(parsed)
+        }
+    }
+}
+
+///
+///Check parsed arguments against their declarations, rejecting
+///unknown keys and missing required arguments, and appending
+///an argument for each absent one with a default
+///
+fn apply_schema(args: Vec<Arg>, schema: &[ArgSpec], settings: &ParseArgsSettings, command: Option<&str>) -> Result<Vec<Arg>, Vec<String>> {
+    let for_command = command.map_or(String::new(), |command| format!(" for command '{command}'"));
+
+    let mut errors: Vec<String> = args.iter()
+        .map(|arg| arg.key())
+        .filter(|key| !schema.iter().any(|spec| spec.matches(key)))
+        .map(|key| format!("Unknown argument '{}{key}'{for_command}.", settings.prefix))
+        .collect();
+
+    //Refer to arguments given by an alias by their key
+    let mut args: Vec<Arg> = args.into_iter()
+        .map(|arg| match schema.iter().find(|spec| spec.key != arg.key() && spec.matches(arg.key())) {
+            Some(spec) => arg.with_key(&spec.key),
+            None => arg
+        })
+        .collect();
+
+    //Fill in arguments from the config file which weren't given, ignoring those meant for other commands.
+    //Help is only asked for on the command line, or a config file asking for it would stop every command
+    if let Some(config) = &settings.config {
+        let from_config: Vec<Arg> = config.iter()
+            .filter_map(|arg| schema.iter()
+                .find(|spec| spec.matches(arg.key()))
+                .map(|spec| arg.clone().with_key(&spec.key)))
+            .filter(|arg| !settings.is_help_key(arg.key()))
+            .filter(|arg| !args.iter().any(|given| given.key() == arg.key()))
+            .collect();
+
+        args.extend(from_config);
+    }
+
+    if let Err(err) = check_duplicates(&args, |key| schema.iter().any(|spec| spec.multiple && spec.key == key)) {
+        errors.push(err);
+    }
+
+    let present = |key: &str| args.iter().any(|arg| arg.key() == key);
+
+    //Asking for help shouldn't require arguments which only matter to running the command
+    let help_requested = settings.help_key.as_deref().is_some_and(present);
+
+    if !help_requested {
+        errors.extend(schema.iter()
+            .filter(|spec| spec.required && !present(&spec.key))
+            .map(|spec| format!("Missing required argument '{}{}'{for_command}.", settings.prefix, spec.key)));
+    }
+
+    //Flags have no value to check
+    for arg in &args {
+        if let (Arg::Pair(_, value), Some(spec)) = (arg, schema.iter().find(|spec| spec.key == arg.key())) {
+            if let Err(err) = spec.check(value, settings) {
+                errors.push(err);
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let defaults: Vec<Arg> = schema.iter()
+        .filter(|spec| !present(&spec.key))
+        .filter_map(|spec| spec.default.as_ref().map(|default| Arg::Pair(spec.key.to_string(), default.to_string())))
+        .collect();
+
+    args.extend(defaults);
+
+    Ok(args)
+}
+
+///
+///Make sure no key appears more than once, except for those
+///which may be repeated
+///
+fn check_duplicates<F: Fn(&str) -> bool>(parsed: &[Arg], repeatable: F) -> Result<(), String> {
+    //Get a vector of indices with duplicate argument keys
+    let find_dupes = |args: &[Arg]| {
+        //Collection of keys already recorded
+        let mut seen: HashSet<&String> = HashSet::new();
+
+        //Collection of indices which have a duplicate key
+        let mut dupes: Vec<usize> = Vec::new();
+
+        //Check each argument sequentially for duplicates
+        for (i, arg) in args.iter().enumerate() {
+            //Get the key from the argument
+            let key = match arg {
+                Arg::Flag(key) => key,
+                Arg::Pair(key, _) => key
+            };
+
+            //If the key has been previously seen, mark the index as a duplicate, unless it may be repeated
+            if seen.contains(key) {
+                if !repeatable(key) {
+                    dupes.push(i);
+                }
+            }
+            //Otherwise, record the key so any future appearances will be marked as duplicates
+            else {
+                seen.insert(key);
+            }
+        }
+
+        dupes
+    };
+
+    let dupes = find_dupes(parsed);
+
+    //If there are any duplicates, return an error
+    if !dupes.is_empty() {
+
+        let dupe_args: Vec<&str> = parsed.iter()
+        .enumerate()
+        .filter(|(i, _)| dupes.contains(i))
+        .map(|(_, arg)| match arg {
+            Arg::Flag(key) => key.as_str(),
+            Arg::Pair(key, _) => key.as_str()
+        }).collect();
+
+        return Err(format!("Cannot have duplicate argument keys! ({})", dupe_args.join(", ")));
+    }
+
+    Ok(())
+}
+
+///
+///Create a vector of Arg from arguments which
+///don't include the program name
+///
+fn parse_tokens<I: Iterator<Item = String>>(tokens: I, settings: &ParseArgsSettings) -> Result<Vec<Arg>, Vec<String>> {
     let mut contains_errors: bool = false;
 
     //Try to parse each argument into an Arg
-    let parsed_or_errs: Vec<Result<Arg, &str>> = args.map(|a| {
+    let parsed_or_errs: Vec<Result<Arg, &str>> = tokens.map(|a| {
         //Split each argument on delimiter (default '=') from settings
         let split: Vec<&str> = a.split(settings.delimiter.as_str()).collect();
 
@@ -202,65 +815,136 @@ pub fn parse_args_with_opts(args: env::Args, settings: ParseArgsSettings) -> Res
         parsed.push(arg.unwrap());
     }
 
-    //Get a vector of indices with duplicate argument keys
-    let find_dupes = |args: &Vec<Arg>| {
-        //Collection of keys already recorded
-        let mut seen: HashSet<&String> = HashSet::new();
+    Ok(parsed)
+}
 
-        //Collection of indices which have a duplicate key
-        let mut dupes: Vec<usize> = Vec::new();
+///
+///Find the value of the argument with the given key, without
+///parsing or validating the others. For arguments which decide
+///how the rest are parsed, such as the path of a config file
+///
+pub fn find_value<I: IntoIterator<Item = String>>(args: I, settings: &ParseArgsSettings, key: &str) -> Option<String> {
+    //Skip the program name
+    args.into_iter().skip(1).find_map(|arg| {
+        let arg = arg.trim();
+        let (arg_key, value) = arg.strip_prefix(settings.prefix.as_str())?
+            .split_once(settings.delimiter.as_str())?;
+
+        (arg_key.trim() == key).then(|| String::from(value.trim()))
+    })
+}
 
-        //Check each argument sequentially for duplicates
-        for (i, arg) in args.iter().enumerate() {
-            //Get the key from the argument
-            let key = match arg {
-                Arg::Flag(key) => key,
-                Arg::Pair(key, _) => key
-            };
+///
+///Read arguments from the contents of a config file. Each
+///line is a key, optionally followed by = and a value, which
+///may be quoted. Blank lines and lines starting with # are
+///skipped
+///
+pub fn parse_config(text: &str) -> Result<Vec<Arg>, Vec<String>> {
+    let mut errors: Vec<String> = Vec::new();
+    let mut parsed: Vec<Arg> = Vec::new();
 
-            //If the key has been previously seen, mark the index as a duplicate
-            if seen.contains(key) {
-                dupes.push(i);
-            }
-            //Otherwise, record the key so any future appearances will be marked as duplicates
-            else {
-                seen.insert(key);
-            }
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
         }
 
-        dupes
-    };
+        //Split on the first '=', like arguments on the command line
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), Some(value.trim())),
+            None => (line, None)
+        };
 
-    let dupes = find_dupes(&parsed);
+        if key.is_empty() {
+            errors.push(format!("Line {}: Argument key cannot be empty!", i + 1));
+            continue;
+        }
 
-    //If there are any duplicates, return an error
-    if !dupes.is_empty() {
+        parsed.push(match value {
+            //Allow values to be quoted, as in TOML
+            Some(value) => {
+                let unquoted = ['"', '\'']
+                    .iter()
+                    .find_map(|quote| value.strip_prefix(*quote).and_then(|v| v.strip_suffix(*quote)))
+                    .unwrap_or(value);
 
-        let dupe_args: Vec<&str> = parsed.iter()
-        .enumerate()
-        .filter(|(i, _)| dupes.contains(i))
-        .map(|(_, arg)| match arg {
-            Arg::Flag(key) => key.as_str(),
-            Arg::Pair(key, _) => key.as_str()
-        }).collect();
+                Arg::Pair(String::from(key), String::from(unquoted))
+            },
+            None => Arg::Flag(String::from(key))
+        });
+    }
 
-        return Err(vec![format!("Cannot have duplicate argument keys! ({})", dupe_args.join(", "))]);
+    if !errors.is_empty() {
+        return Err(errors);
     }
 
     Ok(parsed)
 }
 
 ///
-/// Calls 
-/// ```
-/// parse_args_with_opts
-/// ```
-/// with default settings.
+/// Calls [`parse_args_with_opts`] with default settings.
 /// 
-pub fn parse_args(args: env::Args) -> Result<Vec<Arg>, Vec<String>> {
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Vec<Arg>, Vec<String>> {
     parse_args_with_opts(args, ParseArgsSettings::new())
 }
 
+///
+///Split a collection of command line arguments into a
+///subcommand and its arguments. The first argument after
+///the program name selects the subcommand if it isn't
+///prefixed like the others (with an empty prefix, it always
+///does). The remaining arguments are then parsed and
+///validated against the subcommand's declarations.
+///
+///If no subcommand is given, the name of the returned match
+///is None and its arguments are only checked against those
+///declared on the settings, if any. Without declarations,
+///repeated keys are left for Subcommand::validate to check.
+///
+pub fn parse_subcommand_with_opts<I: IntoIterator<Item = String>>(args: I, settings: ParseArgsSettings, subcommands: &[Subcommand]) -> Result<SubcommandMatch, Vec<String>> {
+    //Skip the program name
+    let mut tokens = args.into_iter().skip(1).peekable();
+
+    let is_positional = tokens.peek()
+        .is_some_and(|token| settings.prefix.is_empty() || !token.trim().starts_with(settings.prefix.as_str()));
+
+    //Without a subcommand, every token is an argument
+    if !is_positional {
+        let args = parse_tokens(tokens, &settings)?;
+
+        return Ok(SubcommandMatch {
+            name: None,
+            args: match &settings.arguments {
+                Some(schema) => apply_schema(args, schema, &settings, None)?,
+                None => args
+            }
+        });
+    }
+
+    let token = tokens.next().unwrap_or_default();
+    let token = token.trim();
+
+    let subcommand = subcommands.iter()
+        .find(|subcommand| subcommand.name.eq_ignore_ascii_case(token))
+        .ok_or_else(|| vec![format!("Unknown command '{token}'.")])?;
+
+    let args = subcommand.validate(parse_tokens(tokens, &settings)?, &settings)?;
+
+    Ok(SubcommandMatch {
+        name: Some(subcommand.name.to_string()),
+        args
+    })
+}
+
+///
+/// Calls [`parse_subcommand_with_opts`] with default settings.
+/// 
+pub fn parse_subcommand<I: IntoIterator<Item = String>>(args: I, subcommands: &[Subcommand]) -> Result<SubcommandMatch, Vec<String>> {
+    parse_subcommand_with_opts(args, ParseArgsSettings::new(), subcommands)
+}
+
 impl std::fmt::Display for Arg {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -0,0 +1,200 @@
+use super::*;
+
+fn tokens(args: &[&str]) -> Vec<String> {
+    std::iter::once("program").chain(args.iter().copied()).map(String::from).collect()
+}
+
+fn settings() -> ParseArgsSettings {
+    ParseArgsSettings::init(String::from("/"), String::from(":"))
+}
+
+fn subcommands() -> Vec<Subcommand> {
+    vec![
+        Subcommand::new("view").with_arguments(["path", "width"]),
+        Subcommand::new("info").with_argument("path")
+    ]
+}
+
+#[test]
+fn subcommand_selects_schema() -> Result<(), String> {
+    let matched = parse_subcommand_with_opts(tokens(&["VIEW", "/path:a.bmp", "/width:8"]), settings(), &subcommands())
+        .map_err(|err| err.join(", "))?;
+
+    assert_eq!(Some(String::from("view")), matched.name);
+    assert_eq!(vec![
+        Arg::Pair(String::from("path"), String::from("a.bmp")),
+        Arg::Pair(String::from("width"), String::from("8"))
+    ], matched.args);
+
+    Ok(())
+}
+
+#[test]
+fn subcommand_rejects_unknown() -> Result<(), String> {
+    let unknown_command = parse_subcommand_with_opts(tokens(&["draw", "/path:a.bmp"]), settings(), &subcommands());
+    assert_eq!(Err(vec![String::from("Unknown command 'draw'.")]), unknown_command.map(|matched| matched.name));
+
+    let unknown_argument = parse_subcommand_with_opts(tokens(&["info", "/path:a.bmp", "/width:8"]), settings(), &subcommands());
+    assert_eq!(Err(vec![String::from("Unknown argument '/width' for command 'info'.")]), unknown_argument.map(|matched| matched.name));
+
+    Ok(())
+}
+
+#[test]
+fn no_subcommand() -> Result<(), String> {
+    let matched = parse_subcommand_with_opts(tokens(&["/path:a.bmp", "/anything"]), settings(), &subcommands())
+        .map_err(|err| err.join(", "))?;
+
+    assert_eq!(None, matched.name);
+    assert_eq!(vec![
+        Arg::Pair(String::from("path"), String::from("a.bmp")),
+        Arg::Flag(String::from("anything"))
+    ], matched.args);
+
+    Ok(())
+}
+
+#[test]
+fn schema_required_and_defaults() -> Result<(), String> {
+    let mut settings = settings();
+    settings.with_argument(ArgSpec::new("path").with_required(true));
+    settings.with_argument(ArgSpec::new("mode").with_default("fast"));
+    settings.with_help_key(String::from("help"));
+    settings.with_argument("help");
+
+    let parsed = parse_args_with_opts(tokens(&["/path:a.bmp"]), settings.clone())
+        .map_err(|err| err.join(", "))?;
+
+    assert_eq!(vec![
+        Arg::Pair(String::from("path"), String::from("a.bmp")),
+        Arg::Pair(String::from("mode"), String::from("fast"))
+    ], parsed);
+
+    let missing = parse_args_with_opts(tokens(&["/mode:slow", "/width:8"]), settings.clone());
+    assert_eq!(Err(vec![
+        String::from("Unknown argument '/width'."),
+        String::from("Missing required argument '/path'.")
+    ]), missing);
+
+    //Required arguments may be left out when asking for help
+    let help = parse_args_with_opts(tokens(&["/help"]), settings)
+        .map_err(|err| err.join(", "))?;
+    assert_eq!(Arg::Flag(String::from("help")), help[0]);
+
+    Ok(())
+}
+
+#[test]
+fn schema_aliases() -> Result<(), String> {
+    let subcommands = vec![Subcommand::new("view").with_argument(ArgSpec::new("help").with_alias("?"))];
+
+    let matched = parse_subcommand_with_opts(tokens(&["view", "/?"]), settings(), &subcommands)
+        .map_err(|err| err.join(", "))?;
+    assert_eq!(vec![Arg::Flag(String::from("help"))], matched.args);
+
+    let both = parse_subcommand_with_opts(tokens(&["view", "/?", "/help"]), settings(), &subcommands);
+    assert_eq!(Err(vec![String::from("Cannot have duplicate argument keys! (help)")]), both.map(|matched| matched.args));
+
+    Ok(())
+}
+
+#[test]
+fn repeated_arguments() -> Result<(), String> {
+    let subcommands = vec![Subcommand::new("montage")
+        .with_argument(ArgSpec::new("path").with_multiple(true))
+        .with_argument("columns")];
+
+    let matched = parse_subcommand_with_opts(tokens(&["montage", "/path:a.bmp", "/path:b.bmp", "/columns:2"]), settings(), &subcommands)
+        .map_err(|err| err.join(", "))?;
+
+    let values = collect_values(&matched.args);
+    assert_eq!(Some(&vec![String::from("a.bmp"), String::from("b.bmp")]), values.get("path"));
+
+    let repeated = parse_subcommand_with_opts(tokens(&["montage", "/columns:2", "/columns:3"]), settings(), &subcommands);
+    assert_eq!(Err(vec![String::from("Cannot have duplicate argument keys! (columns)")]), repeated.map(|matched| matched.args));
+
+    //Without declarations, any repeated key is an error
+    let undeclared = parse_args_with_opts(tokens(&["/path:a.bmp", "/path:b.bmp"]), settings());
+    assert_eq!(Err(vec![String::from("Cannot have duplicate argument keys! (path)")]), undeclared);
+
+    Ok(())
+}
+
+#[test]
+fn config_under_cli() -> Result<(), String> {
+    let config = parse_config("# Preferences\ncharset = \"ascii\"\ncolor=never\n\nverbose\nunused=1\n")
+        .map_err(|err| err.join(", "))?;
+
+    assert_eq!(vec![
+        Arg::Pair(String::from("charset"), String::from("ascii")),
+        Arg::Pair(String::from("color"), String::from("never")),
+        Arg::Flag(String::from("verbose")),
+        Arg::Pair(String::from("unused"), String::from("1"))
+    ], config);
+
+    assert_eq!(Some(String::from("prefs.cfg")), find_value(tokens(&["view", "/path:a.bmp", "/config:prefs.cfg"]), &settings(), "config"));
+
+    let mut settings = settings();
+    settings.with_config(config);
+
+    let subcommands = vec![Subcommand::new("view").with_arguments(["path", "charset", "color"])];
+
+    let matched = parse_subcommand_with_opts(tokens(&["view", "/path:a.bmp", "/color:always"]), settings, &subcommands)
+        .map_err(|err| err.join(", "))?;
+
+    //The command line wins over the config file, and keys the command doesn't accept are ignored
+    assert_eq!(vec![
+        Arg::Pair(String::from("path"), String::from("a.bmp")),
+        Arg::Pair(String::from("color"), String::from("always")),
+        Arg::Pair(String::from("charset"), String::from("ascii"))
+    ], matched.args);
+
+    assert_eq!(Err(vec![String::from("Line 2: Argument key cannot be empty!")]), parse_config("a=1\n=2"));
+
+    Ok(())
+}
+
+#[test]
+fn config_cannot_ask_for_help() -> Result<(), String> {
+    let mut settings = settings();
+    settings.with_help_key(String::from("help"));
+    settings.with_config(parse_config("help\ncolor=never").map_err(|err| err.join(", "))?);
+
+    let subcommands = vec![Subcommand::new("view").with_arguments([ArgSpec::new("path").with_required(true), ArgSpec::from("color"), ArgSpec::from("help")])];
+
+    let matched = parse_subcommand_with_opts(tokens(&["view", "/path:a.bmp"]), settings.clone(), &subcommands)
+        .map_err(|err| err.join(", "))?;
+
+    assert_eq!(vec![
+        Arg::Pair(String::from("path"), String::from("a.bmp")),
+        Arg::Pair(String::from("color"), String::from("never"))
+    ], matched.args);
+
+    //Nor excuse missing arguments, as help on the command line does
+    assert!(parse_subcommand_with_opts(tokens(&["view"]), settings.clone(), &subcommands).is_err());
+    assert!(parse_subcommand_with_opts(tokens(&["view", "/help"]), settings, &subcommands).is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn schema_choices_and_validators() -> Result<(), String> {
+    let subcommands = vec![Subcommand::new("view")
+        .with_argument(ArgSpec::new("output").with_choices(["file", "draw"]))
+        .with_argument(ArgSpec::new("width").with_validator(|value| value.parse::<u32>()
+            .map(|_| ())
+            .map_err(|_| String::from("expected a whole number.")))
+        )];
+
+    let matched = parse_subcommand_with_opts(tokens(&["view", "/output:FILE", "/width:8"]), settings(), &subcommands)
+        .map_err(|err| err.join(", "))?;
+    assert_eq!(2, matched.args.len());
+
+    let invalid = parse_subcommand_with_opts(tokens(&["view", "/output:fiel", "/width:wide"]), settings(), &subcommands);
+    assert_eq!(Err(vec![
+        String::from("Argument '/output' must be one of file, draw, but was 'fiel'."),
+        String::from("Invalid value for argument '/width': expected a whole number.")
+    ]), invalid.map(|matched| matched.args));
+
+    Ok(())
+}
\ No newline at end of file
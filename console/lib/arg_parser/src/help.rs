@@ -0,0 +1,76 @@
+use crate::argparser::{ArgSpec, ParseArgsSettings, Subcommand};
+
+#[cfg(test)]
+mod tests;
+
+///
+///How an argument is written on the command line,
+///including its aliases
+///
+pub fn usage(argument: &ArgSpec, settings: &ParseArgsSettings) -> String {
+    let value = argument.value()
+        .map_or(String::new(), |value| format!("{}<{value}>", settings.delimiter()));
+
+    std::iter::once(argument.key())
+        .chain(argument.aliases().iter().map(|alias| alias.as_str()))
+        .map(|key| format!("{}{key}{value}", settings.prefix()))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+///
+///List the arguments with their descriptions, aligned
+///in a column, along with whether they're required, the
+///values they accept and their defaults
+///
+pub fn help_arguments(arguments: &[ArgSpec], settings: &ParseArgsSettings) -> String {
+    let width = arguments.iter()
+        .map(|argument| usage(argument, settings).len())
+        .max()
+        .unwrap_or(0);
+
+    let mut text = String::new();
+
+    for argument in arguments {
+        let required = if argument.is_required() { " Required." } else { "" };
+        let multiple = if argument.is_multiple() { " May be repeated." } else { "" };
+        text.push_str(&format!("  {: <width$}  {}{required}{multiple}\n", usage(argument, settings), argument.description()));
+
+        if !argument.choices().is_empty() {
+            text.push_str(&format!("  {: <width$}    Values: {}\n", "", argument.choices().join(", ")));
+        }
+
+        if let Some(default) = argument.default() {
+            text.push_str(&format!("  {: <width$}    Default: {default}\n", ""));
+        }
+    }
+
+    text
+}
+
+impl Subcommand {
+    ///
+    ///One line summary of how the subcommand is invoked
+    ///
+    pub fn usage(&self, program: &str) -> String {
+        format!("Usage: {program} {} [arguments]", self.name())
+    }
+
+    ///
+    ///Full help text for the subcommand, generated from
+    ///its declarations
+    ///
+    pub fn help(&self, program: &str, settings: &ParseArgsSettings) -> String {
+        let mut text = format!("{}\n", self.usage(program));
+
+        if !self.description().is_empty() {
+            text.push_str(&format!("\n{}\n", self.description()));
+        }
+
+        if !self.arguments().is_empty() {
+            text.push_str(&format!("\nArguments:\n{}", help_arguments(self.arguments(), settings)));
+        }
+
+        text
+    }
+}
\ No newline at end of file
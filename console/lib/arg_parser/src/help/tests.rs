@@ -0,0 +1,27 @@
+use super::*;
+
+#[test]
+fn subcommand_help() -> Result<(), String> {
+    let settings = ParseArgsSettings::init(String::from("/"), String::from(":"));
+
+    let subcommand = Subcommand::new("view")
+        .with_description("Draw an image.")
+        .with_argument(ArgSpec::new("path").with_value("path").with_required(true).with_description("Image to draw."))
+        .with_argument(ArgSpec::new("dither").with_value("mode").with_choices(["none", "ordered"]).with_default("none").with_description("How to dither."))
+        .with_argument(ArgSpec::new("help").with_alias("?").with_description("Print help."));
+
+    let expected = "Usage: console view [arguments]\n\
+        \n\
+        Draw an image.\n\
+        \n\
+        Arguments:\n  \
+        /path:<path>    Image to draw. Required.\n  \
+        /dither:<mode>  How to dither.\n  \
+        \x20                 Values: none, ordered\n  \
+        \x20                 Default: none\n  \
+        /help, /?       Print help.\n";
+
+    assert_eq!(expected, subcommand.help("console", &settings));
+
+    Ok(())
+}
\ No newline at end of file
@@ -1,4 +1,6 @@
 pub mod argparser;
+pub mod typed;
+pub mod help;
 
 // #[cfg(test)]
 // mod test {
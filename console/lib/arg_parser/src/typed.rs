@@ -0,0 +1,132 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::argparser::ArgValues;
+
+#[cfg(test)]
+mod tests;
+
+///
+///A type whose values can be named by a command-line
+///argument, such as a mode or a format
+///
+pub trait ArgEnum: Sized + Copy + 'static {
+    ///
+    ///The name of each value, and the value it names.
+    ///Names are matched ignoring ASCII case, and several
+    ///names may share a value
+    ///
+    fn variants() -> &'static [(&'static str, Self)];
+}
+
+///
+///Typed access to the values of parsed arguments. Each
+///getter returns None if the argument is absent, and an
+///error naming the argument and the expected type if its
+///value can't be parsed
+///
+pub trait TypedArgs {
+    ///
+    ///The raw value of the argument with the given key
+    ///
+    fn value(&self, key: &str) -> Option<&str>;
+
+    ///
+    ///Every raw value of the argument with the given key,
+    ///for arguments which may be repeated
+    ///
+    fn all_values(&self, key: &str) -> Vec<&str> {
+        self.value(key).into_iter().collect()
+    }
+
+    ///
+    ///Every value of the argument with the given key, in
+    ///the order they were given
+    ///
+    fn get_all(&self, key: &str) -> Vec<String> {
+        self.all_values(key).into_iter().map(String::from).collect()
+    }
+
+    ///
+    ///Parse the value of the argument with the given key,
+    ///describing the type as expected in the error
+    ///
+    fn get_parsed<T: FromStr>(&self, key: &str, expected: &str) -> Result<Option<T>, String> {
+        self.value(key)
+            .map(|value| value.trim().parse::<T>()
+                .map_err(|_| format!("Argument '{key}' must be {expected}, but was '{value}'.")))
+            .transpose()
+    }
+
+    fn get_u8(&self, key: &str) -> Result<Option<u8>, String> {
+        self.get_parsed(key, "an integer from 0 to 255")
+    }
+
+    fn get_u16(&self, key: &str) -> Result<Option<u16>, String> {
+        self.get_parsed(key, "a positive integer")
+    }
+
+    fn get_u32(&self, key: &str) -> Result<Option<u32>, String> {
+        self.get_parsed(key, "a positive integer")
+    }
+
+    fn get_u64(&self, key: &str) -> Result<Option<u64>, String> {
+        self.get_parsed(key, "a positive integer")
+    }
+
+    fn get_usize(&self, key: &str) -> Result<Option<usize>, String> {
+        self.get_parsed(key, "a positive integer")
+    }
+
+    fn get_f64(&self, key: &str) -> Result<Option<f64>, String> {
+        self.get_parsed(key, "a number")
+    }
+
+    ///
+    ///Whether the flag with the given key was set. Flags
+    ///may also be given an explicit value, i.e. log=false,
+    ///and are false when absent
+    ///
+    fn get_bool(&self, key: &str) -> Result<bool, String> {
+        match self.value(key).map(|value| value.trim()) {
+            None => Ok(false),
+            Some(value) if value.eq_ignore_ascii_case("true") => Ok(true),
+            Some(value) if value.eq_ignore_ascii_case("false") => Ok(false),
+            Some(value) => Err(format!("Argument '{key}' must be true or false, but was '{value}'."))
+        }
+    }
+
+    ///
+    ///Find the value named by the argument with the given
+    ///key
+    ///
+    fn get_enum<T: ArgEnum>(&self, key: &str) -> Result<Option<T>, String> {
+        self.value(key)
+            .map(|value| T::variants().iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(value.trim()))
+                .map(|(_, variant)| *variant)
+                .ok_or_else(|| {
+                    let names: Vec<&str> = T::variants().iter().map(|(name, _)| *name).collect();
+                    format!("Argument '{key}' must be one of {}, but was '{value}'.", names.join(", "))
+                }))
+            .transpose()
+    }
+}
+
+impl TypedArgs for HashMap<String, String> {
+    fn value(&self, key: &str) -> Option<&str> {
+        self.get(key).map(|value| value.as_str())
+    }
+}
+
+impl TypedArgs for ArgValues {
+    fn value(&self, key: &str) -> Option<&str> {
+        self.get(key)
+            .and_then(|values| values.first())
+            .map(|value| value.as_str())
+    }
+
+    fn all_values(&self, key: &str) -> Vec<&str> {
+        self.get(key)
+            .map_or(Vec::new(), |values| values.iter().map(|value| value.as_str()).collect())
+    }
+}
\ No newline at end of file
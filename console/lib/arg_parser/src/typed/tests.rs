@@ -0,0 +1,55 @@
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Fast,
+    Slow
+}
+
+impl ArgEnum for Mode {
+    fn variants() -> &'static [(&'static str, Self)] {
+        &[("fast", Mode::Fast), ("quick", Mode::Fast), ("slow", Mode::Slow)]
+    }
+}
+
+fn arguments(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn typed_values() -> Result<(), String> {
+    let args = arguments(&[("width", "80"), ("log", "true"), ("quiet", "FALSE"), ("mode", "Quick")]);
+
+    assert_eq!(Some(80), args.get_u32("width")?);
+    assert_eq!(None, args.get_u32("height")?);
+    assert!(args.get_bool("log")?);
+    assert!(!args.get_bool("quiet")?);
+    assert!(!args.get_bool("missing")?);
+    assert_eq!(Some(Mode::Fast), args.get_enum::<Mode>("mode")?);
+
+    Ok(())
+}
+
+#[test]
+fn typed_errors() -> Result<(), String> {
+    let args = arguments(&[("width", "-3"), ("tolerance", "300"), ("log", "maybe"), ("mode", "medium")]);
+
+    assert_eq!(Err(String::from("Argument 'width' must be a positive integer, but was '-3'.")), args.get_u32("width"));
+    assert_eq!(Err(String::from("Argument 'tolerance' must be an integer from 0 to 255, but was '300'.")), args.get_u8("tolerance"));
+    assert_eq!(Err(String::from("Argument 'log' must be true or false, but was 'maybe'.")), args.get_bool("log"));
+    assert_eq!(Err(String::from("Argument 'mode' must be one of fast, quick, slow, but was 'medium'.")), args.get_enum::<Mode>("mode"));
+
+    Ok(())
+}
+
+#[test]
+fn repeated_values() -> Result<(), String> {
+    let mut args = ArgValues::new();
+    args.insert(String::from("path"), vec![String::from("a.bmp"), String::from("b.bmp")]);
+
+    assert_eq!(Some("a.bmp"), args.value("path"));
+    assert_eq!(vec![String::from("a.bmp"), String::from("b.bmp")], args.get_all("path"));
+    assert!(args.get_all("other").is_empty());
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,24 @@
+///
+/// The set of colors the console is able to display
+///
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ColorMode {
+    ///
+    /// 24-bit color
+    ///
+    TrueColor,
+    ///
+    /// The xterm 256-color palette
+    ///
+    Ansi256,
+    ///
+    /// The 16 basic ANSI colors
+    ///
+    #[default]
+    Ansi16,
+    ///
+    /// No color at all. Pixels are drawn with denser
+    /// characters for brighter colors instead.
+    ///
+    Monochrome
+}
\ No newline at end of file
@@ -0,0 +1,1698 @@
+pub mod view;
+pub mod convert;
+pub mod hex;
+pub mod info;
+pub mod play;
+pub mod slideshow;
+pub mod montage;
+pub mod histogram;
+pub mod diff;
+pub mod formats;
+pub mod generate;
+pub mod pick;
+pub mod strip;
+pub mod validate;
+pub mod compare;
+pub mod transform;
+
+#[cfg(test)]
+mod tests;
+
+use std::{fs::File, io::{self, IsTerminal, Read, Write}, path::{Path, PathBuf}};
+use parse_args::{argparser::{self, Arg, ArgSpec, ArgValues, ParseArgsSettings, Subcommand}, typed::{ArgEnum, TypedArgs}};
+use rs_image::{error::ImageError, image::{Image, format::{self, ImageFormat, codec::{self, LoadOptions}, bitmap::{Bitmap, DecodeOptions}}}};
+
+use crate::constants::{args, commands, env, exit_codes};
+
+///
+/// A subcommand of the console tool
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Command {
+    ///
+    /// Draw an image in the console
+    ///
+    View,
+    ///
+    /// Re-encode an image to a file
+    ///
+    Convert,
+    ///
+    /// Print the annotated bytes of an image
+    ///
+    Hex,
+    ///
+    /// Print the metadata of an image
+    ///
+    Info,
+    ///
+    /// Play an animation in the console
+    ///
+    Play,
+    ///
+    /// Show each image in a directory in turn
+    ///
+    Slideshow,
+    ///
+    /// Lay out several images in a labeled grid
+    ///
+    Montage,
+    ///
+    /// Chart the distribution of each channel of an image
+    ///
+    Histogram,
+    ///
+    /// Compare two images
+    ///
+    Diff,
+    ///
+    /// List the formats images can be read from
+    ///
+    Formats,
+    ///
+    /// Write a standard test image
+    ///
+    Generate,
+    ///
+    /// Print the values of a pixel of an image
+    ///
+    Pick,
+    ///
+    /// Rewrite an image without its metadata
+    ///
+    Strip,
+    ///
+    /// Check images against their format
+    ///
+    Validate,
+    ///
+    /// Compare two images by one measure, for scripts
+    ///
+    Compare,
+    ///
+    /// Print usage information
+    ///
+    Help
+}
+
+///
+/// How a command prints its analysis
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ReportFormat {
+    ///
+    /// Aligned, human-readable text
+    ///
+    #[default]
+    Text,
+    ///
+    /// A single JSON object
+    ///
+    Json
+}
+
+impl ArgEnum for ReportFormat {
+    fn variants() -> &'static [(&'static str, Self)] {
+        &[
+            (args::values::report_format::TEXT, ReportFormat::Text),
+            (args::values::report_format::JSON, ReportFormat::Json)
+        ]
+    }
+}
+
+///
+/// Value of the legacy /output argument, which chose what
+/// to do before there were subcommands
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputType {
+    Draw,
+    File,
+    Hex,
+    Play,
+    Slideshow
+}
+
+impl ArgEnum for OutputType {
+    fn variants() -> &'static [(&'static str, Self)] {
+        &[
+            (args::values::output_type::OUTPUT, OutputType::Draw),
+            (args::values::output_type::DRAW, OutputType::Draw),
+            (args::values::output_type::FILE, OutputType::File),
+            (args::values::output_type::HEX, OutputType::Hex),
+            (args::values::output_type::PLAY, OutputType::Play),
+            (args::values::output_type::SLIDESHOW, OutputType::Slideshow)
+        ]
+    }
+}
+
+impl From<OutputType> for Command {
+    fn from(value: OutputType) -> Self {
+        match value {
+            OutputType::Draw => Command::View,
+            OutputType::File => Command::Convert,
+            OutputType::Hex => Command::Hex,
+            OutputType::Play => Command::Play,
+            OutputType::Slideshow => Command::Slideshow
+        }
+    }
+}
+
+///
+/// When to color output
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorWhen {
+    ///
+    /// Only when stdout is a terminal and NO_COLOR isn't set
+    ///
+    Auto,
+    Always,
+    Never
+}
+
+impl ArgEnum for ColorWhen {
+    fn variants() -> &'static [(&'static str, Self)] {
+        &[
+            (args::values::color::AUTO, ColorWhen::Auto),
+            (args::values::color::ALWAYS, ColorWhen::Always),
+            (args::values::color::NEVER, ColorWhen::Never)
+        ]
+    }
+}
+
+///
+/// Check on an argument's value, returning why it is invalid
+///
+pub type ArgumentValidator = fn(&str) -> Result<(), String>;
+
+///
+/// Description of a command line argument, used to
+/// validate arguments and generate help text
+///
+pub struct ArgumentDefinition {
+    ///
+    /// The key of the argument
+    ///
+    pub key: &'static str,
+    ///
+    /// Other keys the argument may be given by
+    ///
+    pub aliases: &'static [&'static str],
+    ///
+    /// Placeholder for the argument's value, if it
+    /// takes one
+    ///
+    pub value: Option<&'static str>,
+    ///
+    /// The values the argument accepts, if limited
+    ///
+    pub choices: &'static [&'static str],
+    ///
+    /// Whether the argument must be present
+    ///
+    pub required: bool,
+    ///
+    /// Whether the argument may be given more than once
+    ///
+    pub multiple: bool,
+    ///
+    /// The value the argument takes when absent, if any
+    ///
+    pub default: Option<&'static str>,
+    ///
+    /// Check on the argument's value. Without one, the
+    /// choices are the only values accepted
+    ///
+    pub validator: Option<ArgumentValidator>,
+    ///
+    /// What the argument does
+    ///
+    pub description: &'static str
+}
+
+///
+/// Description of a subcommand, and the arguments
+/// it accepts
+///
+pub struct CommandDefinition {
+    pub command: Command,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub arguments: &'static [&'static ArgumentDefinition]
+}
+
+///
+/// An error from running a command, along with the
+/// exit code the process should return
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandError {
+    pub exit_code: u8,
+    pub message: String
+}
+
+impl CommandError {
+    ///
+    /// The command was invoked incorrectly
+    ///
+    pub fn usage(message: String) -> Self {
+        Self {
+            exit_code: exit_codes::USAGE,
+            message
+        }
+    }
+
+    ///
+    /// The command was invoked correctly, but failed
+    ///
+    pub fn failure(message: String) -> Self {
+        Self {
+            exit_code: exit_codes::FAILURE,
+            message
+        }
+    }
+
+    ///
+    /// The images compared differed by more than allowed
+    ///
+    pub fn different(message: String) -> Self {
+        Self {
+            exit_code: exit_codes::DIFFERENT,
+            message
+        }
+    }
+
+    ///
+    /// The images checked broke their format
+    ///
+    pub fn invalid(message: String) -> Self {
+        Self {
+            exit_code: exit_codes::INVALID,
+            message
+        }
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(value: String) -> Self {
+        Self::failure(value)
+    }
+}
+
+impl From<ImageError> for CommandError {
+    fn from(value: ImageError) -> Self {
+        Self::failure(value.to_string())
+    }
+}
+
+pub const ARG_HELP: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::HELP,
+    aliases: &["?"],
+    value: None,
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Print help for the command."
+};
+
+pub const ARG_PATH: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::FILE_PATH,
+    aliases: &[],
+    value: Some("file"),
+    choices: &[],
+    required: true,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Path of the image to read, or - to read from stdin."
+};
+
+pub const ARG_RAW_ORIENTATION: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::RAW_ORIENTATION,
+    aliases: &[],
+    value: None,
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Keep the image as it was stored, rather than turning it upright by the orientation in its metadata."
+};
+
+pub const ARG_OUTPUT_PATH: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::OUTPUT_PATH,
+    aliases: &[],
+    value: Some("file"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Path to write the image to, or - to write to stdout. Defaults to a timestamped file in the output directory."
+};
+
+pub const ARG_OUTPUT_DIRECTORY: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::OUTPUT_DIRECTORY,
+    aliases: &[],
+    value: Some("dir"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Directory to write to when no output path is given. Defaults to output/<format>."
+};
+
+pub const ARG_CONVERT_PATHS: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::FILE_PATH,
+    aliases: &[],
+    value: Some("files"),
+    choices: &[],
+    required: true,
+    multiple: true,
+    default: None,
+    validator: None,
+    description: "Path of the image to read, or - to read from stdin. Comma separated images, or a directory of images, are each converted into the output directory."
+};
+
+pub const ARG_JOBS: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::JOBS,
+    aliases: &[],
+    value: Some("count"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Number of files to convert at once. Defaults to the number of CPUs."
+};
+
+pub const ARG_NO_TRUECOLOR: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::FORCE_DISABLE_TRUECOLOR,
+    aliases: &[],
+    value: None,
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Don't use truecolor, even if the console supports it."
+};
+
+pub const ARG_DITHER: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::DITHER,
+    aliases: &[],
+    value: Some("mode"),
+    choices: &[
+        args::values::dither::NONE,
+        args::values::dither::ORDERED,
+        args::values::dither::FLOYD_STEINBERG
+    ],
+    required: false,
+    multiple: false,
+    default: Some(args::values::dither::NONE),
+    validator: None,
+    description: "How to dither the image when the console has a limited palette."
+};
+
+pub const ARG_ROWS: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::ROWS,
+    aliases: &[],
+    value: Some("rows"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Rows to draw before pausing for Enter, like a pager. 0 never pauses. Defaults to the terminal's height when reading input from it."
+};
+
+pub const ARG_WIDTH: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::WIDTH,
+    aliases: &[],
+    value: Some("pixels"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Width to draw the image at. Defaults to fitting the terminal."
+};
+
+pub const ARG_HEIGHT: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::HEIGHT,
+    aliases: &[],
+    value: Some("pixels"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Height to draw the image at. Defaults to keeping the image's proportions."
+};
+
+pub const ARG_BACKGROUND: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::BACKGROUND,
+    aliases: &[args::keys::BACKGROUND_LONG],
+    value: Some("color"),
+    choices: &[
+        "<hex color>",
+        args::values::background::CHECKER
+    ],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: Some(view::validate_background),
+    description: "What to draw behind transparent pixels."
+};
+
+pub const ARG_FORMAT: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::FORMAT,
+    aliases: &[],
+    value: Some("format"),
+    choices: ImageFormat::WRITABLE_EXTENSIONS,
+    required: false,
+    multiple: false,
+    default: None,
+    validator: Some(convert::validate_format),
+    description: "Format to convert to. Defaults to the extension of the output path, or bmp."
+};
+
+pub const ARG_BIT_DEPTH: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::BIT_DEPTH,
+    aliases: &[],
+    value: Some("bits"),
+    choices: &["1", "4", "8", "24", "32"],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Bit depth to convert to. Defaults to the bit depth of the source."
+};
+
+pub const ARG_COMPRESSION: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::COMPRESSION,
+    aliases: &[],
+    value: Some("method"),
+    choices: &[
+        args::values::compression::NONE,
+        args::values::compression::RLE
+    ],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Compression to use, where rle needs a bit depth of 4 or 8. Defaults to the compression of the source."
+};
+
+pub const ARG_RESIZE: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::RESIZE,
+    aliases: &[],
+    value: Some("WxH"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: Some(transform::validate_size),
+    description: "Scale the image to the given size."
+};
+
+pub const ARG_CROP: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::CROP,
+    aliases: &[],
+    value: Some("X,Y,WxH"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: Some(transform::validate_region),
+    description: "Crop the image to the given region. Applied before resizing."
+};
+
+pub const ARG_ROTATE: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::ROTATE,
+    aliases: &[],
+    value: Some("degrees"),
+    choices: &[
+        args::values::rotate::DEGREES_90,
+        args::values::rotate::DEGREES_180,
+        args::values::rotate::DEGREES_270
+    ],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Rotate the image clockwise."
+};
+
+pub const ARG_FLIP: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::FLIP,
+    aliases: &[],
+    value: Some("axis"),
+    choices: &[
+        args::values::flip::HORIZONTAL,
+        args::values::flip::VERTICAL
+    ],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Mirror the image horizontally or vertically."
+};
+
+pub const ARG_FILTER: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::FILTER,
+    aliases: &[],
+    value: Some("filters"),
+    choices: &[
+        args::values::filter::GRAYSCALE,
+        args::values::filter::INVERT,
+        args::values::filter::AUTO_CONTRAST,
+        args::values::filter::AUTO_LEVELS,
+        args::values::filter::STIPPLE,
+        "blur=<radius>",
+        "brightness=<amount>",
+        "hatch=<spacing>",
+        "kuwahara=<radius>",
+        "anisotropic-kuwahara=<radius>"
+    ],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: Some(transform::validate_filters),
+    description: "Comma separated filters to apply in order, after any geometric transforms."
+};
+
+pub const ARG_LUT: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::LUT,
+    aliases: &[],
+    value: Some("path"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Path of a .cube 3D color lookup table to apply, after any filters."
+};
+
+pub const ARG_RECIPE: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::RECIPE,
+    aliases: &[],
+    value: Some("path"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Path of a JSON recipe of edits to apply, after any LUT."
+};
+
+pub const ARG_FRAMES_PATH: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::FILE_PATH,
+    aliases: &[],
+    value: Some("dir"),
+    choices: &[],
+    required: true,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Directory of images to play as frames, in file name order, an animated gif, or a single image."
+};
+
+pub const ARG_DELAY: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::DELAY,
+    aliases: &[],
+    value: Some("ms"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Delay between frames, for sources that don't specify one. Defaults to 100."
+};
+
+pub const ARG_LOOP: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::LOOP,
+    aliases: &[],
+    value: Some("count"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Number of times to play the animation. Defaults to that of a gif, or otherwise 0, looping until interrupted."
+};
+
+pub const ARG_SLIDESHOW_PATH: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::FILE_PATH,
+    aliases: &[],
+    value: Some("dir"),
+    choices: &[],
+    required: true,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Directory of images to show, in file name order."
+};
+
+pub const ARG_INTERVAL: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::INTERVAL,
+    aliases: &[],
+    value: Some("seconds"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Time to show each image for. Defaults to 5. Type n or an empty line for the next image, p for the previous, or q to quit."
+};
+
+pub const ARG_SLIDESHOW_LOOP: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::LOOP,
+    aliases: &[],
+    value: Some("count"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Number of times to show every image. Defaults to 0, looping until interrupted."
+};
+
+pub const ARG_MONTAGE_PATHS: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::FILE_PATH,
+    aliases: &[],
+    value: Some("files"),
+    choices: &[],
+    required: true,
+    multiple: true,
+    default: None,
+    validator: None,
+    description: "Comma separated images to include, or a directory of images."
+};
+
+pub const ARG_COLUMNS: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::COLUMNS,
+    aliases: &[],
+    value: Some("count"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Number of images per row. Defaults to making the grid roughly square."
+};
+
+pub const ARG_THUMBNAIL_SIZE: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::THUMBNAIL_SIZE,
+    aliases: &[],
+    value: Some("pixels"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Size of the square each image is scaled to fit. Defaults to 64."
+};
+
+pub const ARG_MONTAGE_OUTPUT_PATH: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::OUTPUT_PATH,
+    aliases: &[],
+    value: Some("file"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Path to write the montage to, or - to write to stdout. Defaults to drawing it in the console."
+};
+
+pub const ARG_LOG_SCALE: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::LOG_SCALE,
+    aliases: &[],
+    value: None,
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Scale bars logarithmically, so small counts stay visible."
+};
+
+pub const ARG_STATS: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::STATS,
+    aliases: &[],
+    value: None,
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Also decode the pixels, and report the range, mean, spread and median of each channel."
+};
+
+pub const ARG_BINS: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::BINS,
+    aliases: &[],
+    value: Some("count"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Number of bars in each chart, up to 256. Defaults to 64, or fewer to fit the terminal."
+};
+
+pub const ARG_CHART_HEIGHT: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::HEIGHT,
+    aliases: &[],
+    value: Some("lines"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Height of each chart. Defaults to 8."
+};
+
+pub const ARG_DIFF_PATHS: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::FILE_PATH,
+    aliases: &[],
+    value: Some("file"),
+    choices: &[],
+    required: true,
+    multiple: true,
+    default: None,
+    validator: None,
+    description: "Path of the image to compare, or - to read from stdin. Give twice to compare two images without /other."
+};
+
+pub const ARG_OTHER_PATH: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::OTHER_PATH,
+    aliases: &[],
+    value: Some("file"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Path of the image to compare against."
+};
+
+pub const ARG_THRESHOLD: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::THRESHOLD,
+    aliases: &[],
+    value: Some("percent"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Percentage of pixels allowed to differ before exiting with code 3. Defaults to 0."
+};
+
+pub const ARG_MODE: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::MODE,
+    aliases: &[],
+    value: Some("mode"),
+    choices: &[
+        args::values::compare_mode::EXACT,
+        args::values::compare_mode::COUNT,
+        args::values::compare_mode::PSNR,
+        args::values::compare_mode::SSIM,
+        args::values::compare_mode::DELTA_E
+    ],
+    required: false,
+    multiple: false,
+    default: Some(args::values::compare_mode::EXACT),
+    validator: None,
+    description: "What to measure: exact requires identical pixels, count at most threshold differing pixels, and psnr or ssim at least threshold."
+};
+
+pub const ARG_COMPARE_THRESHOLD: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::THRESHOLD,
+    aliases: &[],
+    value: Some("amount"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Limit the measure must meet. Defaults to 0 pixels for count, 40 dB for psnr and 0.99 for ssim."
+};
+
+pub const ARG_TOLERANCE: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::TOLERANCE,
+    aliases: &[],
+    value: Some("amount"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "How much a channel may differ, from 0 to 255, before the pixel counts as different. Defaults to 0."
+};
+
+pub const ARG_SHOW: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::SHOW,
+    aliases: &[],
+    value: None,
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Draw the highlighted differences in the console."
+};
+
+pub const ARG_DIFF_OUTPUT_PATH: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::OUTPUT_PATH,
+    aliases: &[],
+    value: Some("file"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Path to write the highlighted differences to."
+};
+
+pub const ARG_RAW: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::RAW,
+    aliases: &[],
+    value: None,
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Print a plain hexdump with ASCII, instead of bytes annotated with their meaning."
+};
+
+pub const ARG_OFFSETS: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::OFFSETS,
+    aliases: &[],
+    value: None,
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Show the offset of each line in the file. Always shown in a plain hexdump."
+};
+
+pub const ARG_BYTES_PER_LINE: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::BYTES_PER_LINE,
+    aliases: &[],
+    value: Some("count"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Bytes per line. Defaults to 16 for a plain hexdump, or one pixel per line."
+};
+
+pub const ARG_SECTION: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::SECTION,
+    aliases: &[],
+    value: Some("sections"),
+    choices: &[
+        args::values::section::HEADER,
+        args::values::section::PALETTE,
+        args::values::section::PIXELS
+    ],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: Some(hex::validate_sections),
+    description: "Comma separated sections of the file to print. Defaults to all of them."
+};
+
+pub const ARG_PATTERN: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::PATTERN,
+    aliases: &[],
+    value: Some("pattern"),
+    choices: &[
+        args::values::pattern::SOLID,
+        args::values::pattern::GRADIENT,
+        args::values::pattern::BARS,
+        args::values::pattern::CHECKERBOARD,
+        args::values::pattern::NOISE
+    ],
+    required: true,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Test pattern to generate."
+};
+
+pub const ARG_SIZE: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::SIZE,
+    aliases: &[],
+    value: Some("WxH"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: Some(transform::validate_size),
+    description: "Size of the image. Defaults to 256x256."
+};
+
+pub const ARG_COLORS: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::COLORS,
+    aliases: &[],
+    value: Some("hex,hex"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: Some(generate::validate_colors),
+    description: "Color of a solid image, or the two colors of a gradient or checkerboard. Defaults to black, then white."
+};
+
+pub const ARG_CELL: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::CELL,
+    aliases: &[],
+    value: Some("pixels"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Size of each square of a checkerboard. Defaults to 16."
+};
+
+pub const ARG_SEED: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::SEED,
+    aliases: &[],
+    value: Some("number"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Seed of the noise; the same seed always generates the same image. Defaults to 0."
+};
+
+pub const ARG_VERTICAL: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::VERTICAL,
+    aliases: &[],
+    value: None,
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Blend a gradient from top to bottom, rather than left to right."
+};
+
+pub const ARG_GENERATE_OUTPUT_PATH: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::OUTPUT_PATH,
+    aliases: &[],
+    value: Some("file"),
+    choices: &[],
+    required: true,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Path to write the bitmap to, or - to write to stdout."
+};
+
+pub const ARG_GENERATE_BIT_DEPTH: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::BIT_DEPTH,
+    aliases: &[],
+    value: Some("bits"),
+    choices: &["1", "4", "8", "24", "32"],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Bit depth of the bitmap. At 8 bits or fewer, colors are mapped to a standard palette. Defaults to 24."
+};
+
+pub const ARG_AT: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::AT,
+    aliases: &[],
+    value: Some("x,y"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: Some(pick::validate_position),
+    description: "Position of the pixel to pick. Without it, the image is drawn with a crosshair, moved by lines typed on stdin."
+};
+
+pub const ARG_STRIP_OUTPUT_PATH: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::OUTPUT_PATH,
+    aliases: &[],
+    value: Some("file"),
+    choices: &[],
+    required: true,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Path to write the stripped image to, or - to write to stdout."
+};
+
+pub const ARG_VALIDATE_PATHS: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::FILE_PATH,
+    aliases: &[],
+    value: Some("files"),
+    choices: &[],
+    required: true,
+    multiple: true,
+    default: None,
+    validator: None,
+    description: "Comma separated bitmaps to check, or a directory of bitmaps, or - to read one from stdin."
+};
+
+pub const ARG_REPORT_FORMAT: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::FORMAT,
+    aliases: &[],
+    value: Some("format"),
+    choices: &[
+        args::values::report_format::TEXT,
+        args::values::report_format::JSON
+    ],
+    required: false,
+    multiple: false,
+    default: Some(args::values::report_format::TEXT),
+    validator: None,
+    description: "Format to print the results in."
+};
+
+pub const ARG_OUTPUT_TYPE: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::OUTPUT_TYPE,
+    aliases: &[],
+    value: Some("type"),
+    choices: &[
+        args::values::output_type::OUTPUT,
+        args::values::output_type::DRAW,
+        args::values::output_type::FILE,
+        args::values::output_type::HEX,
+        args::values::output_type::PLAY,
+        args::values::output_type::SLIDESHOW
+    ],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "What to do, when no command is given. Superseded by commands."
+};
+
+pub const ARG_VERBOSE: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::VERBOSE,
+    aliases: &[],
+    value: None,
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Log details to stderr, such as how long each stage took."
+};
+
+pub const ARG_QUIET: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::QUIET,
+    aliases: &[],
+    value: None,
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Only log errors."
+};
+
+pub const ARG_CHARSET: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::CHARSET,
+    aliases: &[],
+    value: Some("charset"),
+    choices: &[
+        args::values::charset::BLOCKS,
+        args::values::charset::SHADES,
+        args::values::charset::ASCII,
+        args::values::charset::DOTS,
+        "<custom>"
+    ],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: Some(view::validate_charset),
+    description: "Characters to draw pixels with, from most to least opaque. Custom sets are comma separated strings \
+        of equal length, or a single string of one character per level. Defaults to blocks."
+};
+
+pub const ARG_CONFIG: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::CONFIG,
+    aliases: &[],
+    value: Some("file"),
+    choices: &[],
+    required: false,
+    multiple: false,
+    default: None,
+    validator: None,
+    description: "Read default arguments from a file of key=value lines, such as charset=ascii. Arguments given on the command line take precedence."
+};
+
+pub const ARG_COLOR: ArgumentDefinition = ArgumentDefinition {
+    key: args::keys::COLOR,
+    aliases: &[],
+    value: Some("when"),
+    choices: &[
+        args::values::color::AUTO,
+        args::values::color::ALWAYS,
+        args::values::color::NEVER
+    ],
+    required: false,
+    multiple: false,
+    default: Some(args::values::color::AUTO),
+    validator: None,
+    description: "When to use color. auto uses color if stdout is a terminal and NO_COLOR isn't set."
+};
+
+///
+/// Arguments accepted by every command
+///
+pub const GLOBAL_ARGUMENTS: &[&ArgumentDefinition] = &[&ARG_VERBOSE, &ARG_QUIET, &ARG_COLOR, &ARG_CONFIG];
+
+///
+/// All subcommands the tool accepts
+///
+pub const COMMANDS: &[CommandDefinition] = &[
+    CommandDefinition {
+        command: Command::View,
+        name: commands::VIEW,
+        description: "Draw an image in the console.",
+        arguments: &[&ARG_PATH, &ARG_NO_TRUECOLOR, &ARG_DITHER, &ARG_WIDTH, &ARG_HEIGHT, &ARG_ROWS, &ARG_BACKGROUND, &ARG_CHARSET, &ARG_RAW_ORIENTATION,
+            &ARG_CROP, &ARG_RESIZE, &ARG_ROTATE, &ARG_FLIP, &ARG_FILTER, &ARG_LUT, &ARG_RECIPE, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Convert,
+        name: commands::CONVERT,
+        description: "Re-encode an image to a file.",
+        arguments: &[&ARG_CONVERT_PATHS, &ARG_OUTPUT_PATH, &ARG_OUTPUT_DIRECTORY, &ARG_FORMAT, &ARG_BIT_DEPTH, &ARG_COMPRESSION, &ARG_BACKGROUND, &ARG_JOBS, &ARG_RAW_ORIENTATION,
+            &ARG_CROP, &ARG_RESIZE, &ARG_ROTATE, &ARG_FLIP, &ARG_FILTER, &ARG_LUT, &ARG_RECIPE, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Hex,
+        name: commands::HEX,
+        description: "Print the annotated bytes of an image.",
+        arguments: &[&ARG_PATH, &ARG_RAW, &ARG_OFFSETS, &ARG_BYTES_PER_LINE, &ARG_SECTION, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Info,
+        name: commands::INFO,
+        description: "Print the metadata of an image.",
+        arguments: &[&ARG_PATH, &ARG_REPORT_FORMAT, &ARG_STATS, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Play,
+        name: commands::PLAY,
+        description: "Play an animation in the console.",
+        arguments: &[&ARG_FRAMES_PATH, &ARG_DELAY, &ARG_LOOP, &ARG_NO_TRUECOLOR, &ARG_DITHER, &ARG_WIDTH, &ARG_HEIGHT, &ARG_BACKGROUND, &ARG_CHARSET,
+            &ARG_CROP, &ARG_RESIZE, &ARG_ROTATE, &ARG_FLIP, &ARG_FILTER, &ARG_LUT, &ARG_RECIPE, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Slideshow,
+        name: commands::SLIDESHOW,
+        description: "Show each image in a directory in turn.",
+        arguments: &[&ARG_SLIDESHOW_PATH, &ARG_INTERVAL, &ARG_SLIDESHOW_LOOP, &ARG_NO_TRUECOLOR, &ARG_DITHER, &ARG_WIDTH, &ARG_HEIGHT, &ARG_BACKGROUND, &ARG_CHARSET,
+            &ARG_CROP, &ARG_RESIZE, &ARG_ROTATE, &ARG_FLIP, &ARG_FILTER, &ARG_LUT, &ARG_RECIPE, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Montage,
+        name: commands::MONTAGE,
+        description: "Lay out several images in a labeled grid.",
+        arguments: &[&ARG_MONTAGE_PATHS, &ARG_COLUMNS, &ARG_THUMBNAIL_SIZE, &ARG_MONTAGE_OUTPUT_PATH,
+            &ARG_NO_TRUECOLOR, &ARG_DITHER, &ARG_WIDTH, &ARG_HEIGHT, &ARG_BACKGROUND, &ARG_CHARSET, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Histogram,
+        name: commands::HISTOGRAM,
+        description: "Chart the distribution of each channel of an image.",
+        arguments: &[&ARG_PATH, &ARG_LOG_SCALE, &ARG_BINS, &ARG_CHART_HEIGHT, &ARG_REPORT_FORMAT, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Diff,
+        name: commands::DIFF,
+        description: "Compare two images, exiting with code 3 if they differ.",
+        arguments: &[&ARG_DIFF_PATHS, &ARG_OTHER_PATH, &ARG_THRESHOLD, &ARG_TOLERANCE, &ARG_DIFF_OUTPUT_PATH, &ARG_SHOW, &ARG_REPORT_FORMAT,
+            &ARG_NO_TRUECOLOR, &ARG_WIDTH, &ARG_HEIGHT, &ARG_CHARSET, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Formats,
+        name: commands::FORMATS,
+        description: "List the formats images can be read from, including those added by plugins.",
+        arguments: &[&ARG_REPORT_FORMAT, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Generate,
+        name: commands::GENERATE,
+        description: "Write a standard test image, such as color bars or a gradient.",
+        arguments: &[&ARG_PATTERN, &ARG_SIZE, &ARG_COLORS, &ARG_CELL, &ARG_SEED, &ARG_VERTICAL, &ARG_GENERATE_OUTPUT_PATH, &ARG_GENERATE_BIT_DEPTH, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Pick,
+        name: commands::PICK,
+        description: "Print a pixel's color as ARGB, hex, HSV and Lab, with the nearest named and ANSI colors.",
+        arguments: &[&ARG_PATH, &ARG_AT, &ARG_REPORT_FORMAT, &ARG_NO_TRUECOLOR, &ARG_WIDTH, &ARG_HEIGHT, &ARG_BACKGROUND, &ARG_CHARSET, &ARG_RAW_ORIENTATION, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Strip,
+        name: commands::STRIP,
+        description: "Rewrite an image without its metadata: a bitmap without resolution, reserved fields, color profiles or trailing bytes.",
+        arguments: &[&ARG_PATH, &ARG_STRIP_OUTPUT_PATH, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Validate,
+        name: commands::VALIDATE,
+        description: "Check bitmaps against the format, exiting with code 4 if any break it.",
+        arguments: &[&ARG_VALIDATE_PATHS, &ARG_REPORT_FORMAT, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Compare,
+        name: commands::COMPARE,
+        description: "Compare two images by one measure, printing a one-line summary and exiting with code 1 if they fail.",
+        arguments: &[&ARG_DIFF_PATHS, &ARG_OTHER_PATH, &ARG_MODE, &ARG_COMPARE_THRESHOLD, &ARG_TOLERANCE, &ARG_HELP]
+    },
+    CommandDefinition {
+        command: Command::Help,
+        name: commands::HELP,
+        description: "Print usage information.",
+        arguments: &[]
+    }
+];
+
+impl From<&ArgumentDefinition> for ArgSpec {
+    fn from(value: &ArgumentDefinition) -> Self {
+        let mut spec = ArgSpec::new(value.key)
+            .with_required(value.required)
+            .with_multiple(value.multiple)
+            .with_description(value.description)
+            .with_choices(value.choices);
+
+        for alias in value.aliases {
+            spec = spec.with_alias(alias);
+        }
+
+        if let Some(placeholder) = value.value {
+            spec = spec.with_value(placeholder);
+        }
+
+        if let Some(default) = value.default {
+            spec = spec.with_default(default);
+        }
+
+        if let Some(validator) = value.validator {
+            spec = spec.with_validator(validator);
+        }
+
+        spec
+    }
+}
+
+impl Command {
+    ///
+    /// Find the command with the given name
+    ///
+    pub fn from_name(name: &str) -> Option<Command> {
+        COMMANDS.iter()
+            .find(|definition| definition.name.eq_ignore_ascii_case(name))
+            .map(|definition| definition.command)
+    }
+
+    ///
+    /// Argument parser schemas for every command
+    ///
+    pub fn subcommands() -> Vec<Subcommand> {
+        COMMANDS.iter()
+            .map(|definition| definition.command.subcommand())
+            .collect()
+    }
+
+    ///
+    /// Argument parser schema for the command. The global arguments, and the
+    /// legacy output argument, are accepted by every command
+    ///
+    pub fn subcommand(&self) -> Subcommand {
+        let definition = self.definition();
+
+        Subcommand::new(definition.name)
+            .with_description(definition.description)
+            .with_arguments(definition.arguments.iter().copied().map(ArgSpec::from))
+            .with_arguments(GLOBAL_ARGUMENTS.iter().copied().map(ArgSpec::from))
+            .with_argument(&ARG_OUTPUT_TYPE)
+    }
+
+    pub fn definition(&self) -> &'static CommandDefinition {
+        COMMANDS.iter()
+            .find(|definition| definition.command == *self)
+            .expect("Every command has a definition")
+    }
+
+    ///
+    /// Run the command with the given arguments
+    ///
+    pub fn run(&self, arguments: &ArgValues) -> Result<(), CommandError> {
+        match self {
+            Command::View => view::run(arguments),
+            Command::Convert => convert::run(arguments),
+            Command::Hex => hex::run(arguments),
+            Command::Info => info::run(arguments),
+            Command::Play => play::run(arguments),
+            Command::Slideshow => slideshow::run(arguments),
+            Command::Montage => montage::run(arguments),
+            Command::Histogram => histogram::run(arguments),
+            Command::Diff => diff::run(arguments),
+            Command::Formats => formats::run(arguments),
+            Command::Generate => generate::run(arguments),
+            Command::Pick => pick::run(arguments),
+            Command::Strip => strip::run(arguments),
+            Command::Validate => validate::run(arguments),
+            Command::Compare => compare::run(arguments),
+            Command::Help => {
+                print!("{}", crate::help::help_text(&program_name()));
+                Ok(())
+            }
+        }
+    }
+}
+
+///
+/// Settings the console tool parses arguments with
+///
+pub fn parse_settings() -> ParseArgsSettings {
+    let mut settings = ParseArgsSettings::init(
+        String::from(args::ARGUMENT_PREFIX),
+        String::from(args::ARGUMENT_DELIMITER)
+    );
+
+    settings.with_help_key(String::from(args::keys::HELP));
+    settings
+}
+
+///
+/// Read the config file given by the /config argument, or the
+/// RS_IMAGE_CONFIG environment variable, if either is set
+///
+pub fn load_config(settings: &ParseArgsSettings) -> Result<Option<Vec<Arg>>, CommandError> {
+    let path = argparser::find_value(std::env::args(), settings, args::keys::CONFIG)
+        .or_else(|| std::env::var(env::keys::CONFIG).ok().filter(|path| !path.is_empty()));
+
+    let Some(path) = path else {
+        return Ok(None);
+    };
+
+    let text = std::fs::read_to_string(&path)
+        .map_err(|err| CommandError::failure(format!("Failed to read config file '{path}': {err}")))?;
+
+    let config = argparser::parse_config(&text)
+        .map_err(|err| CommandError::usage(format!("Failed to parse config file '{path}': {}", err.join(", "))))?;
+
+    //The config file is found before it is read, so can't name another
+    Ok(Some(config.into_iter().filter(|arg| arg.key() != args::keys::CONFIG).collect()))
+}
+
+///
+/// Name the program was invoked as
+///
+pub fn program_name() -> String {
+    std::env::args().next().unwrap_or_else(|| String::from("console"))
+}
+
+///
+/// Whether the flag with the given key was set. Flags may also
+/// be given an explicit value, i.e. /log:false
+///
+pub fn flag_set(arguments: &ArgValues, key: &str) -> Result<bool, CommandError> {
+    arguments.get_bool(key).map_err(CommandError::usage)
+}
+
+///
+/// The least important level of message to log, as raised by /verbose or
+/// lowered by /quiet, which can't be used together. None if neither is set.
+///
+pub fn log_level(arguments: &ArgValues) -> Result<Option<rs_image::log::Level>, CommandError> {
+    match (flag_set(arguments, args::keys::VERBOSE)?, flag_set(arguments, args::keys::QUIET)?) {
+        (true, true) => Err(CommandError::usage(format!(
+            "Arguments '{0}{1}' and '{0}{2}' cannot be used together.",
+            args::ARGUMENT_PREFIX, args::keys::VERBOSE, args::keys::QUIET
+        ))),
+        (true, false) => Ok(Some(rs_image::log::Level::Debug)),
+        (false, true) => Ok(Some(rs_image::log::Level::Error)),
+        (false, false) => Ok(None)
+    }
+}
+
+///
+/// Get a count, which must be greater than zero, from the arguments
+///
+pub fn get_count(arguments: &ArgValues, key: &str) -> Result<Option<usize>, CommandError> {
+    match arguments.get_usize(key).map_err(CommandError::usage)? {
+        Some(0) => Err(CommandError::usage(format!("Argument '{key}' must be greater than 0."))),
+        count => Ok(count)
+    }
+}
+
+///
+/// Check that an image of the size given by the argument isn't too large to make
+///
+pub fn check_size(key: &str, width: usize, height: usize) -> Result<(), CommandError> {
+    match width.checked_mul(height) {
+        Some(pixels) if pixels <= rs_image::constants::image::MAX_PIXELS => Ok(()),
+        _ => Err(CommandError::usage(format!("Argument '{key}' of {width}x{height} is larger than the {} pixels allowed.", rs_image::constants::image::MAX_PIXELS)))
+    }
+}
+
+///
+/// Whether output should be colored, from the /color argument, NO_COLOR
+/// and whether stdout is a terminal
+///
+pub fn color_enabled(arguments: &ArgValues) -> Result<bool, CommandError> {
+    let when = arguments.get_enum::<ColorWhen>(args::keys::COLOR)
+        .map_err(CommandError::usage)?
+        .unwrap_or(ColorWhen::Auto);
+
+    match when {
+        ColorWhen::Always => Ok(true),
+        ColorWhen::Never => Ok(false),
+        ColorWhen::Auto => {
+            let no_color = std::env::var(env::keys::NO_COLOR).is_ok_and(|v| !v.is_empty());
+            Ok(!no_color && io::stdout().is_terminal())
+        }
+    }
+}
+
+///
+/// Get the format to print results in from the arguments
+///
+pub fn report_format(arguments: &ArgValues) -> Result<ReportFormat, CommandError> {
+    Ok(arguments.get_enum::<ReportFormat>(args::keys::FORMAT)
+        .map_err(CommandError::usage)?
+        .unwrap_or_default())
+}
+
+///
+/// Print the value as pretty JSON
+///
+pub fn print_json<T: serde::Serialize>(value: &T) -> Result<(), CommandError> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|err| CommandError::failure(format!("Failed to serialize results: {err}")))?;
+
+    println!("{json}");
+
+    Ok(())
+}
+
+///
+/// Read the bytes of the file at the path given in the arguments
+///
+pub fn load_bytes(arguments: &ArgValues) -> Result<Vec<u8>, CommandError> {
+    //Get image file path from args
+    let file_path = arguments.value(args::keys::FILE_PATH)
+        .ok_or_else(|| CommandError::usage(format!("Missing required argument '{}{}'.", args::ARGUMENT_PREFIX, args::keys::FILE_PATH)))?;
+
+    if file_path == args::values::STD_STREAM {
+        let mut bytes = Vec::new();
+
+        io::stdin().lock().read_to_end(&mut bytes)
+            .map_err(|err| CommandError::failure(format!("Failed to read stdin: {err}")))?;
+
+        return Ok(bytes);
+    }
+
+    load_bytes_at(file_path)
+}
+
+///
+/// Read the bytes of the file at the given path
+///
+pub fn load_bytes_at(path: &str) -> Result<Vec<u8>, CommandError> {
+    rs_image::utility::file::get_file_bytes(path)
+        .map_err(|err| CommandError::failure(format!("Failed to read '{path}': {err}")))
+}
+
+///
+/// Write bytes to the given path, or to stdout if the path is -
+///
+pub fn save_bytes(out_path: &str, bytes: &[u8]) -> Result<(), CommandError> {
+    if out_path == args::values::STD_STREAM {
+        let mut stdout = io::stdout().lock();
+
+        return stdout.write_all(bytes)
+            .and_then(|_| stdout.flush())
+            .map_err(|err| CommandError::failure(format!("Failed to write stdout: {err}")));
+    }
+
+    rs_image::utility::file::write_file_bytes(out_path, bytes)
+        .map_err(|err| CommandError::failure(format!("Failed to write '{out_path}': {err}")))?;
+
+    rs_image::log::info(format_args!("Wrote file {out_path}"));
+
+    Ok(())
+}
+
+///
+/// Read and parse the bitmap at the path given in the arguments
+///
+pub fn load_bitmap(arguments: &ArgValues) -> Result<Bitmap, CommandError> {
+    let bytes = load_bytes(arguments)?;
+
+    let _timer = rs_image::log::time("Decode");
+
+    //Parse bytes to bitmap
+    Ok(Bitmap::try_from(bytes)?)
+}
+
+///
+/// Read the image at the given path, in any format, detected from its bytes
+///
+pub fn load_image_at(path: &str) -> Result<Image, CommandError> {
+    let bytes = load_bytes_at(path)?;
+
+    let _timer = rs_image::log::time("Decode");
+
+    codec::load(&bytes)
+        .map_err(|err| CommandError::failure(format!("Failed to decode '{path}': {err}")))
+}
+
+///
+/// Read the image at the path given in the arguments, in any format, detected from its bytes.
+/// Its colors are converted from any color profile to sRGB, as they are drawn and measured in.
+///
+pub fn load_image(arguments: &ArgValues) -> Result<Image, CommandError> {
+    let options = LoadOptions {
+        apply_color_profile: true,
+        ..load_options(arguments)?
+    };
+    let bytes = load_bytes(arguments)?;
+
+    let _timer = rs_image::log::time("Decode");
+
+    Ok(codec::load_with_options(&bytes, options)?)
+}
+
+///
+/// Get how to treat decoded images from the arguments. Color profiles
+/// are kept, to be embedded again when re-encoding.
+///
+pub fn load_options(arguments: &ArgValues) -> Result<LoadOptions, CommandError> {
+    Ok(LoadOptions {
+        apply_orientation: !flag_set(arguments, args::keys::RAW_ORIENTATION)?,
+        ..LoadOptions::default()
+    })
+}
+
+///
+/// Whether the file at the path given in the arguments is in a format other than bmp,
+/// judging by its first bytes. Stdin can't be looked at before it is read, so is taken as bmp.
+///
+pub fn is_other_format(arguments: &ArgValues) -> bool {
+    let Some(path) = arguments.value(args::keys::FILE_PATH).filter(|path| *path != args::values::STD_STREAM) else {
+        return false;
+    };
+
+    let mut signature = Vec::new();
+
+    File::open(path)
+        .and_then(|file| file.take(16).read_to_end(&mut signature))
+        .is_ok_and(|_| format::detect(&signature).is_some_and(|format| format != ImageFormat::Bitmap))
+}
+
+///
+/// Read the image at the given path into the image, in any format. Bitmaps
+/// are decoded straight into it, reusing its pixel buffer.
+///
+pub fn load_image_into(path: &str, image: &mut Image) -> Result<(), CommandError> {
+    let bytes = load_bytes_at(path)?;
+
+    let _timer = rs_image::log::time("Decode");
+
+    let decoded = match format::detect(&bytes) {
+        Some(ImageFormat::Bitmap) => Bitmap::decode_into(&bytes, image, DecodeOptions::default()),
+        _ => codec::load(&bytes).map(|decoded| *image = decoded)
+    };
+
+    decoded.map_err(|err| CommandError::failure(format!("Failed to decode '{path}': {err}")))
+}
+
+///
+/// Read the bitmap given in the arguments, decoding only the given region (X, Y, W, H) of it
+///
+pub fn load_bitmap_region(arguments: &ArgValues, region: (usize, usize, usize, usize)) -> Result<Image, CommandError> {
+    let (x, y, width, height) = region;
+    let bytes = load_bytes(arguments)?;
+
+    let _timer = rs_image::log::time("Decode");
+
+    Ok(Bitmap::decode_region(&bytes, x, y, width, height, DecodeOptions::default())?)
+}
+
+///
+/// Get the paths of the images given in the arguments, either
+/// listed in them or contained in the directories they name
+///
+pub fn image_paths(arguments: &ArgValues) -> Result<Vec<PathBuf>, CommandError> {
+    let values = arguments.all_values(args::keys::FILE_PATH);
+
+    if values.is_empty() {
+        return Err(CommandError::usage(format!("Missing required argument '{}{}'.", args::ARGUMENT_PREFIX, args::keys::FILE_PATH)));
+    }
+
+    let mut paths = Vec::new();
+
+    //Each value may be a directory, or comma separated bitmaps
+    for value in values {
+        if Path::new(value).is_dir() {
+            paths.extend(list_directory(Path::new(value))?);
+        }
+        else {
+            paths.extend(value.split(args::values::PATH_SEPARATOR)
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from));
+        }
+    }
+
+    Ok(paths)
+}
+
+///
+/// List the files in a directory, in file name order
+///
+pub fn list_directory(directory: &Path) -> Result<Vec<PathBuf>, CommandError> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(directory)
+        .map_err(|err| CommandError::failure(format!("Failed to read '{}': {err}", directory.display())))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect();
+
+    paths.sort();
+
+    Ok(paths)
+}
\ No newline at end of file
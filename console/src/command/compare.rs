@@ -0,0 +1,149 @@
+use parse_args::{argparser::ArgValues, typed::{ArgEnum, TypedArgs}};
+use rs_image::image::Image;
+
+use crate::constants::{self, args};
+use super::{CommandError, diff};
+
+///
+/// What to measure when comparing two images, and which way the threshold goes
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+enum Mode {
+    ///
+    /// The images must be identical, within the tolerance
+    ///
+    #[default]
+    Exact,
+    ///
+    /// At most threshold pixels may differ
+    ///
+    Count,
+    ///
+    /// The peak signal-to-noise ratio must be at least threshold dB
+    ///
+    Psnr,
+    ///
+    /// The structural similarity must be at least threshold
+    ///
+    Ssim,
+    ///
+    /// The mean CIE76 color difference must be at most threshold
+    ///
+    DeltaE
+}
+
+impl ArgEnum for Mode {
+    fn variants() -> &'static [(&'static str, Self)] {
+        &[
+            (args::values::compare_mode::EXACT, Mode::Exact),
+            (args::values::compare_mode::COUNT, Mode::Count),
+            (args::values::compare_mode::PSNR, Mode::Psnr),
+            (args::values::compare_mode::SSIM, Mode::Ssim),
+            (args::values::compare_mode::DELTA_E, Mode::DeltaE)
+        ]
+    }
+}
+
+impl Mode {
+    fn name(&self) -> &'static str {
+        Self::variants().iter()
+            .find(|(_, mode)| mode == self)
+            .map_or("", |(name, _)| name)
+    }
+
+    fn default_threshold(&self) -> f64 {
+        match self {
+            Mode::Exact | Mode::Count => 0_f64,
+            Mode::Psnr => constants::compare::DEFAULT_PSNR,
+            Mode::Ssim => constants::compare::DEFAULT_SSIM,
+            Mode::DeltaE => constants::compare::DEFAULT_DELTA_E
+        }
+    }
+}
+
+///
+/// Compare two images by one measure, printing a single line of the form
+/// mode=psnr value=41.20 threshold=40 result=pass, and failing if the
+/// measure is past the threshold
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let mode = arguments.get_enum::<Mode>(args::keys::MODE)
+        .map_err(CommandError::usage)?
+        .unwrap_or_default();
+
+    //Exact comparisons have no threshold to give
+    let threshold = match arguments.get_f64(args::keys::THRESHOLD).map_err(CommandError::usage)? {
+        Some(threshold) if !threshold.is_finite() || threshold < 0_f64 => return Err(CommandError::usage(format!(
+            "Argument '{}' must be a number of at least 0, but was '{threshold}'.", args::keys::THRESHOLD
+        ))),
+        _ if mode == Mode::Exact => 0_f64,
+        threshold => threshold.unwrap_or(mode.default_threshold())
+    };
+
+    let tolerance = arguments.get_u8(args::keys::TOLERANCE)
+        .map_err(CommandError::usage)?
+        .unwrap_or(0);
+
+    let (img, other) = diff::load_pair(arguments)?;
+
+    //Images of different sizes fail every measure, but still get a summary to parse
+    let value = match mode {
+        Mode::DeltaE => delta_e(&img, &other),
+        _ => compare(&img, &other, mode, tolerance)
+    };
+
+    let passed = value.is_some_and(|value| match mode {
+        Mode::Exact | Mode::Count | Mode::DeltaE => value <= threshold,
+        Mode::Psnr | Mode::Ssim => value >= threshold
+    });
+
+    let value_text = match (mode, value) {
+        (_, None) => String::from("none"),
+        (Mode::Exact | Mode::Count, Some(value)) => format!("{value}"),
+        (_, Some(value)) if value.is_infinite() => String::from("inf"),
+        (_, Some(value)) => format!("{value:.4}")
+    };
+
+    println!("mode={} value={value_text} threshold={threshold} result={}", mode.name(), if passed { "pass" } else { "fail" });
+
+    if !passed {
+        return Err(CommandError::failure(format!("Images failed the {} comparison.", mode.name())));
+    }
+
+    Ok(())
+}
+
+fn compare(img: &Image, other: &Image, mode: Mode, tolerance: u8) -> Option<f64> {
+    match img.compare(other, tolerance) {
+        Ok(comparison) => Some(match mode {
+            Mode::Exact | Mode::Count => comparison.differing_pixels as f64,
+            Mode::Psnr => comparison.psnr,
+            _ => comparison.ssim
+        }),
+        Err(err) => {
+            rs_image::log::warn(format_args!("{err}"));
+            None
+        }
+    }
+}
+
+///
+/// The mean color difference of the other image from this one, logging
+/// the region where they differ most
+///
+fn delta_e(img: &Image, other: &Image) -> Option<f64> {
+    match img.quantization_report(other, constants::compare::DELTA_E_REGION_SIZE) {
+        Ok(report) => {
+            if let Some(worst) = report.worst_regions(1).first().filter(|worst| worst.mean_delta_e > 0_f32) {
+                rs_image::log::info(format_args!("Worst region is {}x{} at {},{}, with mean ΔE {:.4} and max ΔE {:.4}.",
+                    worst.width, worst.height, worst.x, worst.y, worst.mean_delta_e, worst.max_delta_e));
+            }
+
+            Some(report.mean_delta_e as f64)
+        },
+        Err(err) => {
+            rs_image::log::warn(format_args!("{err}"));
+            None
+        }
+    }
+}
\ No newline at end of file
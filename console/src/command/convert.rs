@@ -0,0 +1,328 @@
+#[cfg(test)]
+mod tests;
+
+use std::{panic, path::{Path, PathBuf}, thread, time::SystemTime};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use parse_args::{argparser::ArgValues, typed::TypedArgs};
+use rs_image::{image, convert::ConvertableFrom, constants::bitmap::compression};
+use image::format::{self, ImageFormat, codec::{self, ImageCodec, LoadOptions}, netpbm::{Netpbm, NetpbmConvertData, NetpbmKind}, bitmap::{Bitmap, BitDepth, BitmapConvertData, ImageConvertData}};
+
+use crate::constants::{self, args};
+use super::{CommandError, get_count, image_paths, load_bytes, load_bytes_at, load_options, save_bytes, transform, view};
+
+///
+/// How to re-encode each image
+///
+struct ConvertOptions {
+    format: ImageFormat,
+    netpbm: NetpbmKind,
+    bit_depth: Option<u16>,
+    compress: Option<String>
+}
+
+impl ConvertOptions {
+    ///
+    /// File extension for images written without an output path
+    ///
+    fn extension(&self) -> &'static str {
+        match self.format {
+            ImageFormat::Netpbm => self.netpbm.extension(),
+            format => format.extension()
+        }
+    }
+}
+
+///
+/// Re-encode the image and write it to a file, optionally
+/// changing its format, bit depth and compression
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    //Get file save path from args, or use default if not present
+    let out_path = arguments.value(args::keys::OUTPUT_PATH).map(String::from);
+
+    let options = options(arguments, out_path.as_deref())?;
+
+    if let Some(paths) = batch_paths(arguments)? {
+        if out_path.is_some() {
+            return Err(CommandError::usage(format!("Argument '{}' names a single file; use '{}' when converting several.",
+                args::keys::OUTPUT_PATH, args::keys::OUTPUT_DIRECTORY)));
+        }
+
+        return run_batch(arguments, &paths, &options);
+    }
+
+    let (img, source) = decode(load_bytes(arguments)?, load_options(arguments)?)?;
+    let bytes = encode(arguments, img, source, &options)?;
+
+    let out_path = out_path.unwrap_or_else(|| {
+        let time = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()
+            .unwrap_or_default()
+            .as_millis();
+
+        format!("{}/img{time}.{}", output_directory(arguments, options.format), options.extension())
+    });
+
+    save_bytes(&out_path, &bytes)
+}
+
+///
+/// Convert each of the files into the output directory, several at once. A file
+/// failing doesn't stop the others; every failure is reported once all are done.
+///
+fn run_batch(arguments: &ArgValues, paths: &[PathBuf], options: &ConvertOptions) -> Result<(), CommandError> {
+    let jobs = get_count(arguments, args::keys::JOBS)?
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+        .min(paths.len());
+
+    let outputs = batch_outputs(paths, &output_directory(arguments, options.format), options)?;
+    let next = AtomicUsize::new(0);
+
+    //Each worker takes the next file until none are left
+    let mut failures: Vec<(usize, CommandError)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| scope.spawn(|| {
+                let mut failures = Vec::new();
+
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+
+                    if index >= paths.len() {
+                        break;
+                    }
+
+                    if let Err(err) = convert_file(arguments, &paths[index], &outputs[index], options) {
+                        failures.push((index, err));
+                    }
+                }
+
+                failures
+            }))
+            .collect();
+
+        workers.into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|panic| panic::resume_unwind(panic)))
+            .collect()
+    });
+
+    if failures.is_empty() {
+        rs_image::log::info(format_args!("Converted {} files.", paths.len()));
+        return Ok(());
+    }
+
+    failures.sort_by_key(|(index, _)| *index);
+
+    for (index, err) in &failures {
+        rs_image::log::error(format_args!("{}: {}", paths[*index].display(), err.message));
+    }
+
+    Err(CommandError::failure(format!("Failed to convert {} of {} files.", failures.len(), paths.len())))
+}
+
+///
+/// The path each file of a batch is written to, named after the file. Fails if two
+/// files would be written to the same path, as one would silently replace the other,
+/// or if one is standard input, which can't be told apart from the rest.
+///
+fn batch_outputs(paths: &[PathBuf], directory: &str, options: &ConvertOptions) -> Result<Vec<String>, CommandError> {
+    let mut sources: HashMap<String, &Path> = HashMap::new();
+
+    paths.iter()
+        .map(|path| {
+            if path.as_os_str() == args::values::STD_STREAM {
+                return Err(CommandError::usage(format!("Standard input ('{}') can't be converted along with other files.", args::values::STD_STREAM)));
+            }
+
+            let name = path.file_stem()
+                .map(|stem| stem.to_string_lossy())
+                .unwrap_or_default();
+
+            let output = format!("{directory}/{name}.{}", options.extension());
+
+            if let Some(other) = sources.insert(output.clone(), path) {
+                return Err(CommandError::usage(format!("'{}' and '{}' would both be written to '{output}'.", other.display(), path.display())));
+            }
+
+            Ok(output)
+        })
+        .collect()
+}
+
+///
+/// Convert one file of a batch, writing it to the output path
+///
+fn convert_file(arguments: &ArgValues, path: &Path, out_path: &str, options: &ConvertOptions) -> Result<(), CommandError> {
+    let path_name = path.to_string_lossy();
+
+    let (img, source) = decode(load_bytes_at(&path_name)?, load_options(arguments)?)
+        .map_err(|err| CommandError::failure(format!("Failed to decode '{path_name}': {}", err.message)))?;
+
+    let bytes = encode(arguments, img, source, options)?;
+
+    save_bytes(out_path, &bytes)
+}
+
+///
+/// Get the files to convert, if more than one file may be given; that is,
+/// the paths are comma separated or name a directory
+///
+fn batch_paths(arguments: &ArgValues) -> Result<Option<Vec<PathBuf>>, CommandError> {
+    let values = arguments.all_values(args::keys::FILE_PATH);
+
+    let single = match values.as_slice() {
+        [value] => !value.contains(args::values::PATH_SEPARATOR) && !Path::new(value).is_dir(),
+        _ => false
+    };
+
+    if single {
+        return Ok(None);
+    }
+
+    image_paths(arguments).map(Some)
+}
+
+///
+/// Get how to re-encode images from the arguments
+///
+fn options(arguments: &ArgValues, out_path: Option<&str>) -> Result<ConvertOptions, CommandError> {
+    let to_stdout = out_path == Some(args::values::STD_STREAM);
+
+    //Get the target format from args, falling back to the extension of the output path, then bmp
+    let format = match arguments.value(args::keys::FORMAT) {
+        Some(format) => ImageFormat::from_extension(format)
+            .ok_or_else(|| CommandError::usage(format!("Unsupported output format '{format}'.")))?,
+        None => out_path
+            .filter(|_| !to_stdout)
+            .and_then(|path| Path::new(path).extension())
+            .and_then(|extension| ImageFormat::from_extension(&extension.to_string_lossy()))
+            .unwrap_or(ImageFormat::Bitmap)
+    };
+
+    if !format.can_encode() {
+        return Err(CommandError::usage(format!("Writing {} images is not supported.", format.name())));
+    }
+
+    //Netpbm covers pbm, pgm, ppm and pam, which are told apart by extension
+    let netpbm = arguments.value(args::keys::FORMAT)
+        .or_else(|| out_path
+            .filter(|_| !to_stdout)
+            .and_then(|path| Path::new(path).extension())
+            .and_then(|extension| extension.to_str()))
+        .and_then(NetpbmKind::from_extension)
+        .unwrap_or_default();
+
+    let bit_depth = match arguments.get_u16(args::keys::BIT_DEPTH).map_err(CommandError::usage)? {
+        Some(depth) if !args::values::bit_depth::ALL.contains(&depth) => return Err(CommandError::usage(format!("Unsupported bit depth '{depth}'."))),
+        depth => depth
+    };
+
+    let compress = arguments.value(args::keys::COMPRESSION)
+        .map(|c| c.to_ascii_lowercase());
+
+    Ok(ConvertOptions {
+        format,
+        netpbm,
+        bit_depth,
+        compress
+    })
+}
+
+///
+/// The directory to write to when no output path is given
+///
+fn output_directory(arguments: &ArgValues, format: ImageFormat) -> String {
+    arguments.value(args::keys::OUTPUT_DIRECTORY)
+        .map_or_else(|| format!("output/{}", format.extension()), String::from)
+}
+
+///
+/// Decode the image, in any format. For bitmaps, the headers of the source
+/// are kept too, to re-encode it like it was stored.
+///
+fn decode(bytes: Vec<u8>, load_options: LoadOptions) -> Result<(image::Image, BitmapConvertData), CommandError> {
+    let _timer = rs_image::log::time("Decode");
+
+    if format::detect(&bytes) != Some(ImageFormat::Bitmap) {
+        return Ok((codec::load_with_options(&bytes, load_options)?, BitmapConvertData::default()));
+    }
+
+    let bitmap = Bitmap::try_from(bytes)?;
+    let source = BitmapConvertData::from(&bitmap);
+
+    Ok((image::Image::try_convert_from(bitmap, ImageConvertData::default())?, source))
+}
+
+///
+/// Apply the transforms from the arguments to the image, and re-encode it
+///
+fn encode(arguments: &ArgValues, img: image::Image, mut encode_options: BitmapConvertData, options: &ConvertOptions) -> Result<Vec<u8>, CommandError> {
+    //Transforms make new images, but their colors are still in the color profile of the source
+    let metadata = img.metadata().clone();
+    let img = transform::apply(arguments, img)?;
+
+    //Formats or bit depths without alpha would otherwise just drop it
+    let img = view::background(arguments)?.flatten(img, constants::convert::CHECKER_CELL_SIZE).with_metadata(metadata);
+
+    let bytes = match options.format {
+        ImageFormat::Bitmap => {
+            let bit_depth = options.bit_depth
+                .or(encode_options.bit_depth.bits())
+                .unwrap_or_else(|| encode_options.bit_depth.resolve(&img, compression::BI_RGB));
+            encode_options.bit_depth = BitDepth::Bits(bit_depth);
+
+            encode_options.compression = match (options.compress.as_deref(), bit_depth) {
+                //Keep the compression of the source, unless it can't hold the new bit depth
+                (None, 8) if encode_options.compression == compression::BI_RLE8 => compression::BI_RLE8,
+                (None, 4) if encode_options.compression == compression::BI_RLE4 => compression::BI_RLE4,
+                (None, _) => compression::BI_RGB,
+                (Some(args::values::compression::NONE), _) => compression::BI_RGB,
+                (Some(args::values::compression::RLE), 8) => compression::BI_RLE8,
+                (Some(args::values::compression::RLE), 4) => compression::BI_RLE4,
+                (Some(args::values::compression::RLE), bit_depth) => {
+                    return Err(CommandError::usage(format!("RLE compression needs a bit depth of 4 or 8, not {bit_depth}.")));
+                },
+                (Some(other), _) => {
+                    return Err(CommandError::usage(format!("Unsupported compression '{other}'.")));
+                }
+            };
+
+            //Keep the orientation and mask of the source, though compressed bitmaps can only be stored bottom-up, without a mask
+            encode_options.top_down &= encode_options.compression == compression::BI_RGB;
+            encode_options.and_mask &= encode_options.compression == compression::BI_RGB;
+
+            let _timer = rs_image::log::time("Encode");
+
+            let bmp = Bitmap::try_convert_from(img, encode_options)?;
+
+            Vec::try_from(bmp)?
+        },
+        ImageFormat::Gif => {
+            let _timer = rs_image::log::time("Encode");
+            codec::GifCodec.encode(&img)?
+        },
+        ImageFormat::Netpbm => {
+            let _timer = rs_image::log::time("Encode");
+
+            Vec::try_from(Netpbm::try_convert_from(img, NetpbmConvertData {
+                kind: options.netpbm,
+                ..Default::default()
+            })?)?
+        },
+        ImageFormat::Png | ImageFormat::Dds | ImageFormat::Ilbm => return Err(CommandError::failure(format!("Writing {} images is not supported.", options.format.name())))
+    };
+
+    Ok(bytes)
+}
+
+///
+/// Check that a format is one images can be converted to
+///
+pub fn validate_format(value: &str) -> Result<(), String> {
+    match ImageFormat::from_extension(value) {
+        Some(format) if format.can_encode() => Ok(()),
+        Some(format) => Err(format!("Writing {} images is not supported.", format.name())),
+        None => Err(format!("Unsupported output format '{value}'."))
+    }
+}
\ No newline at end of file
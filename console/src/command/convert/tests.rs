@@ -0,0 +1,41 @@
+use super::*;
+
+fn bitmap_options() -> ConvertOptions {
+    ConvertOptions {
+        format: ImageFormat::Bitmap,
+        netpbm: NetpbmKind::default(),
+        bit_depth: None,
+        compress: None
+    }
+}
+
+#[test]
+fn batch_outputs_named_after_files() -> Result<(), String> {
+    let paths = [PathBuf::from("a/x.png"), PathBuf::from("b/y.bmp")];
+    let outputs = batch_outputs(&paths, "out", &bitmap_options()).map_err(|err| err.message)?;
+
+    if outputs != ["out/x.bmp", "out/y.bmp"] {
+        return Err(format!("Unexpected output paths {outputs:?}."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn batch_outputs_rejects_clashes() -> Result<(), String> {
+    let cases = [
+        [PathBuf::from("a/x.bmp"), PathBuf::from("b/x.bmp")],
+        [PathBuf::from("x.png"), PathBuf::from("x.gif")],
+        [PathBuf::from(args::values::STD_STREAM), PathBuf::from("b/x.bmp")]
+    ];
+
+    for paths in cases {
+        match batch_outputs(&paths, "out", &bitmap_options()) {
+            Err(err) if err.exit_code == constants::exit_codes::USAGE => (),
+            Err(err) => return Err(format!("Expected {paths:?} to be a usage error, got '{}'.", err.message)),
+            Ok(outputs) => return Err(format!("Expected {paths:?} to be rejected, got {outputs:?}."))
+        }
+    }
+
+    Ok(())
+}
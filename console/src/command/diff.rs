@@ -0,0 +1,134 @@
+use serde::Serialize;
+use parse_args::{argparser::ArgValues, typed::TypedArgs};
+use rs_image::{color, image, convert::ConvertableFrom};
+use image::format::bitmap::{Bitmap, BitDepth, BitmapConvertData};
+use image::resolution::Resolution;
+
+use crate::console;
+use crate::constants::{self, args};
+use super::{CommandError, ReportFormat, flag_set, load_image, load_image_at, print_json, report_format, save_bytes, view};
+
+///
+/// Results of comparing two images
+///
+#[derive(Serialize)]
+struct DiffReport {
+    differing_pixels: usize,
+    total_pixels: usize,
+    differing_percent: f64,
+    max_difference: u8,
+    mse: f64,
+    ///
+    /// None if the images are identical, since JSON can't represent infinity
+    ///
+    psnr: Option<f64>,
+    ssim: f64,
+    threshold_percent: f64,
+    passed: bool
+}
+
+///
+/// Compare two images, printing how much they differ and optionally
+/// drawing or saving the differences
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let report_format = report_format(arguments)?;
+
+    let threshold = match arguments.get_f64(args::keys::THRESHOLD).map_err(CommandError::usage)? {
+        Some(threshold) if !(0_f64..=100_f64).contains(&threshold) => return Err(CommandError::usage(format!(
+            "Argument '{}' must be a percentage, but was '{threshold}'.", args::keys::THRESHOLD
+        ))),
+        threshold => threshold.unwrap_or(0_f64)
+    };
+
+    let tolerance = arguments.get_u8(args::keys::TOLERANCE)
+        .map_err(CommandError::usage)?
+        .unwrap_or(0);
+
+    let (img, other) = load_pair(arguments)?;
+
+    let comparison = img.compare(&other, tolerance)?;
+
+    let differing_percent = comparison.differing_fraction() * 100_f64;
+
+    let report = DiffReport {
+        differing_pixels: comparison.differing_pixels,
+        total_pixels: comparison.total_pixels,
+        differing_percent,
+        max_difference: comparison.max_difference,
+        mse: comparison.mse,
+        psnr: Some(comparison.psnr).filter(|psnr| psnr.is_finite()),
+        ssim: comparison.ssim,
+        threshold_percent: threshold,
+        passed: differing_percent <= threshold
+    };
+
+    match report_format {
+        ReportFormat::Json => print_json(&report)?,
+        ReportFormat::Text => {
+            let label_width = 20;
+            println!("{:<label_width$}{} of {} ({:.3}%)", "Differing pixels:", report.differing_pixels, report.total_pixels, report.differing_percent);
+            println!("{:<label_width$}{}", "Max difference:", report.max_difference);
+            println!("{:<label_width$}{:.3}", "MSE:", report.mse);
+
+            match report.psnr {
+                Some(psnr) => println!("{:<label_width$}{psnr:.2} dB", "PSNR:"),
+                None => println!("{:<label_width$}inf", "PSNR:")
+            }
+
+            println!("{:<label_width$}{:.4}", "SSIM:", report.ssim);
+        }
+    }
+
+    let show = flag_set(arguments, args::keys::SHOW)?;
+    let out_path = arguments.value(args::keys::OUTPUT_PATH);
+
+    if show || out_path.is_some() {
+        let highlighted = img.highlight_differences(&other, tolerance, color::ARGB::from_u32(constants::diff::HIGHLIGHT, false))?;
+
+        if let Some(out_path) = out_path {
+            let bmp = Bitmap::try_convert_from(highlighted.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Bits(24),
+                compression: rs_image::constants::bitmap::compression::BI_RGB,
+                resolution: Resolution::default(),
+                ..Default::default()
+            })?;
+
+            save_bytes(out_path, &Vec::try_from(bmp)?)?;
+        }
+
+        if show {
+            console::write_image_to_console(highlighted, &view::settings(arguments)?);
+            println!();
+        }
+    }
+
+    if !report.passed {
+        return Err(CommandError::different(format!(
+            "Images differ: {differing_percent:.3}% of pixels differ, more than the allowed {threshold}%."
+        )));
+    }
+
+    Ok(())
+}
+
+///
+/// Read the two images to compare. The second may be given by repeating /path instead of with /other.
+///
+pub fn load_pair(arguments: &ArgValues) -> Result<(image::Image, image::Image), CommandError> {
+    let paths = arguments.all_values(args::keys::FILE_PATH);
+    let other_paths = arguments.all_values(args::keys::OTHER_PATH);
+
+    let other_path = match (&paths[..], &other_paths[..]) {
+        ([_], [other]) | ([_, other], []) => *other,
+        _ => return Err(CommandError::usage(format!(
+            "Give exactly two bitmaps to compare, with '{0}{1}' twice or with '{0}{1}' and '{0}{2}'.",
+            args::ARGUMENT_PREFIX, args::keys::FILE_PATH, args::keys::OTHER_PATH
+        )))
+    };
+
+    let img = load_image(arguments)?;
+    let other = load_image_at(other_path)?;
+
+    Ok((img, other))
+}
\ No newline at end of file
@@ -0,0 +1,43 @@
+use parse_args::argparser::ArgValues;
+use serde::Serialize;
+use rs_image::image::format::codec;
+
+use super::{CommandError, ReportFormat, print_json, report_format};
+
+///
+/// A format images can be read from
+///
+#[derive(Serialize)]
+struct FormatReport {
+    name: String,
+    extensions: Vec<String>,
+    encode: bool
+}
+
+///
+/// List the formats images can be read from, including those added by plugins
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let report_format = report_format(arguments)?;
+
+    let formats: Vec<FormatReport> = codec::formats().iter()
+        .map(|codec| FormatReport {
+            name: codec.name().to_string(),
+            extensions: codec.extensions().iter().map(|extension| extension.to_string()).collect(),
+            encode: codec.can_encode()
+        })
+        .collect();
+
+    if report_format == ReportFormat::Json {
+        return print_json(&formats);
+    }
+
+    let width = formats.iter().map(|format| format.name.len()).max().unwrap_or_default();
+
+    for format in formats {
+        let access = if format.encode { "read, write" } else { "read" };
+        println!("{: <width$}  {: <12}  .{}", format.name, access, format.extensions.join(", ."));
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,159 @@
+use parse_args::{argparser::ArgValues, typed::{ArgEnum, TypedArgs}};
+use rs_image::{color, image, convert::ConvertableFrom, error::ImageError};
+use image::{Image, dither::DitherMode, generate::TestPattern, format::bitmap::{Bitmap, BitDepth, BitmapConvertData}};
+
+use crate::constants::{self, args};
+use super::{CommandError, check_size, flag_set, save_bytes, transform};
+
+///
+/// Which test pattern to generate
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Pattern {
+    Solid,
+    Gradient,
+    Bars,
+    Checkerboard,
+    Noise
+}
+
+impl ArgEnum for Pattern {
+    fn variants() -> &'static [(&'static str, Self)] {
+        &[
+            (args::values::pattern::SOLID, Pattern::Solid),
+            (args::values::pattern::GRADIENT, Pattern::Gradient),
+            (args::values::pattern::BARS, Pattern::Bars),
+            (args::values::pattern::CHECKERBOARD, Pattern::Checkerboard),
+            (args::values::pattern::NOISE, Pattern::Noise)
+        ]
+    }
+}
+
+///
+/// Write a standard test image, i.e. as a fixture for a codec
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let pattern = arguments.get_enum::<Pattern>(args::keys::PATTERN)
+        .map_err(CommandError::usage)?
+        .ok_or_else(|| CommandError::usage(format!("Missing required argument '{}{}'.", args::ARGUMENT_PREFIX, args::keys::PATTERN)))?;
+
+    let size = arguments.value(args::keys::SIZE).unwrap_or(constants::generate::DEFAULT_SIZE);
+
+    let (width, height) = transform::parse_size(size)
+        .ok_or_else(|| CommandError::usage(format!("Argument '{}' must be in the form WxH, but was '{size}'.", args::keys::SIZE)))?;
+
+    check_size(args::keys::SIZE, width, height)?;
+
+    let bit_depth = match arguments.get_u16(args::keys::BIT_DEPTH).map_err(CommandError::usage)? {
+        Some(depth) if !args::values::bit_depth::ALL.contains(&depth) => return Err(CommandError::usage(format!("Unsupported bit depth '{depth}'."))),
+        depth => depth.unwrap_or(constants::generate::DEFAULT_BIT_DEPTH)
+    };
+
+    let out_path = arguments.value(args::keys::OUTPUT_PATH)
+        .ok_or_else(|| CommandError::usage(format!("Missing required argument '{}{}'.", args::ARGUMENT_PREFIX, args::keys::OUTPUT_PATH)))?;
+
+    let image = test_pattern(arguments, pattern)?.generate(width, height);
+
+    let bitmap = Bitmap::try_convert_from(reduce_colors(&image, bit_depth), BitmapConvertData {
+        bit_depth: BitDepth::Bits(bit_depth),
+        ..Default::default()
+    })?;
+
+    save_bytes(out_path, &Vec::try_from(bitmap)?)
+}
+
+///
+/// Build the pattern, with its colors and other parameters from the arguments
+///
+fn test_pattern(arguments: &ArgValues, pattern: Pattern) -> Result<TestPattern, CommandError> {
+    let colors = match arguments.value(args::keys::COLORS) {
+        None => Vec::new(),
+        Some(colors) => parse_colors(colors).map_err(|err| CommandError::usage(err.to_string()))?
+    };
+
+    //Black to white, unless colors are given
+    let first = colors.first().copied().unwrap_or(color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0x00 });
+    let second = colors.get(1).copied().unwrap_or(color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF });
+
+    let cell = arguments.get_usize(args::keys::CELL)
+        .map_err(CommandError::usage)?
+        .unwrap_or(constants::generate::DEFAULT_CELL);
+
+    let seed = arguments.get_u64(args::keys::SEED)
+        .map_err(CommandError::usage)?
+        .unwrap_or_default();
+
+    Ok(match pattern {
+        Pattern::Solid => TestPattern::Solid(first),
+        Pattern::Gradient => TestPattern::Gradient {
+            from: first,
+            to: second,
+            horizontal: !flag_set(arguments, args::keys::VERTICAL)?
+        },
+        Pattern::Bars => TestPattern::Bars,
+        Pattern::Checkerboard => TestPattern::Checkerboard {
+            cell,
+            colors: (first, second)
+        },
+        Pattern::Noise => TestPattern::Noise { seed }
+    })
+}
+
+///
+/// Check that colors are a comma separated list of hex colors
+///
+pub fn validate_colors(value: &str) -> Result<(), String> {
+    parse_colors(value).map(|_| ()).map_err(String::from)
+}
+
+fn parse_colors(value: &str) -> Result<Vec<color::ARGB>, ImageError> {
+    value.split(args::values::PATH_SEPARATOR)
+        .map(|hex| color::ARGB::from_hex(hex.trim()))
+        .collect()
+}
+
+///
+/// Map each pixel to the nearest color of a standard palette which fits in
+/// the color table of an indexed bit depth. Other bit depths are left alone.
+///
+fn reduce_colors(image: &Image, bit_depth: u16) -> Image {
+    let rgb = |red: u8, green: u8, blue: u8| color::ARGB { alpha: 0xFF, red, green, blue };
+
+    let palette: Vec<color::ARGB> = match bit_depth {
+        1 => vec![rgb(0x00, 0x00, 0x00), rgb(0xFF, 0xFF, 0xFF)],
+        //The 16 colors of CGA: each of red, green and blue off or on, at half or full intensity
+        4 => (0..16_u8)
+            .map(|n| {
+                let level = |on: bool| match (on, n & 8 != 0) {
+                    (false, _) => 0x00,
+                    (true, false) => 0x80,
+                    (true, true) => 0xFF
+                };
+
+                match n {
+                    7 => rgb(0xC0, 0xC0, 0xC0),
+                    8 => rgb(0x80, 0x80, 0x80),
+                    n => rgb(level(n & 4 != 0), level(n & 2 != 0), level(n & 1 != 0))
+                }
+            })
+            .collect(),
+        //3 bits of red and green, and 2 of blue
+        8 => (0..=255_u32)
+            .map(|n| {
+                let scale = |value: u32, max: u32| (value * 0xFF / max) as u8;
+                rgb(scale(n >> 5, 7), scale((n >> 2) & 7, 7), scale(n & 3, 3))
+            })
+            .collect(),
+        _ => return image.clone()
+    };
+
+    image.dither(DitherMode::None, |pixel| {
+        palette.iter()
+            .copied()
+            .min_by_key(|entry| {
+                let distance = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+                distance(entry.red, pixel.red) + distance(entry.green, pixel.green) + distance(entry.blue, pixel.blue)
+            })
+            .unwrap_or(pixel)
+    })
+}
\ No newline at end of file
@@ -0,0 +1,91 @@
+use std::io::{self, Write};
+
+use parse_args::{argparser::ArgValues, typed::TypedArgs};
+use rs_image::image::format::bitmap::{Bitmap, BitmapSection, BitstringOptions};
+
+use crate::constants::{self, args};
+use super::{CommandError, flag_set, get_count, load_bytes};
+
+///
+/// Print the bytes of the bitmap, either annotated or as a plain hexdump
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let bytes_per_line = get_count(arguments, args::keys::BYTES_PER_LINE)?
+        .map(|bytes_per_line| bytes_per_line.min(constants::hex::MAX_BYTES_PER_LINE));
+
+    let sections = arguments.value(args::keys::SECTION)
+        .map(parse_sections)
+        .transpose()?;
+
+    let bytes = load_bytes(arguments)?;
+
+    if flag_set(arguments, args::keys::RAW)? {
+        let bytes_per_line = bytes_per_line.unwrap_or(constants::hex::DEFAULT_RAW_BYTES_PER_LINE);
+
+        match sections {
+            //Without sections, the file doesn't need to be parsed, so even broken files can be dumped
+            None => println!("{}", rs_image::utility::hexdump(&bytes, bytes_per_line, 0)),
+            Some(sections) => {
+                let bitmap = Bitmap::try_from(&bytes[..])?;
+
+                let dumps: Vec<String> = sections.iter()
+                    .map(|section| {
+                        let (start, end) = bitmap.section_range(*section);
+                        let (start, end) = (start.min(bytes.len()), end.min(bytes.len()));
+                        rs_image::utility::hexdump(&bytes[start..end], bytes_per_line, start)
+                    })
+                    .collect();
+
+                println!("{}", dumps.join("\n\n"));
+            }
+        }
+    }
+    else {
+        let bitmap = Bitmap::try_from(bytes)?;
+
+        let mut options = BitstringOptions {
+            offsets: flag_set(arguments, args::keys::OFFSETS)?,
+            bytes_per_line,
+            ..Default::default()
+        };
+
+        if let Some(sections) = sections {
+            options.sections = sections;
+        }
+
+        //Stream the annotated bytes, since they're many times the size of the file
+        let mut stdout = io::BufWriter::new(io::stdout().lock());
+
+        bitmap.write_bitstring(&mut stdout, &options)
+            .and_then(|_| writeln!(stdout))
+            .and_then(|_| stdout.flush())
+            .map_err(|err| CommandError::failure(format!("Failed to write stdout: {err}")))?;
+    }
+
+    Ok(())
+}
+
+///
+/// Check that a list of sections can be parsed
+///
+pub fn validate_sections(value: &str) -> Result<(), String> {
+    parse_sections(value).map(|_| ()).map_err(|err| err.message)
+}
+
+///
+/// Parse a list of sections in the form header,palette,pixels
+///
+fn parse_sections(value: &str) -> Result<Vec<BitmapSection>, CommandError> {
+    use args::values::section;
+
+    value.split(section::SEPARATOR)
+        .map(|s| s.trim().to_ascii_lowercase())
+        .filter(|s| !s.is_empty())
+        .map(|s| match s.as_str() {
+            section::HEADER => Ok(BitmapSection::Header),
+            section::PALETTE => Ok(BitmapSection::Palette),
+            section::PIXELS => Ok(BitmapSection::Pixels),
+            _ => Err(CommandError::usage(format!("Unrecognized section '{s}'.")))
+        })
+        .collect()
+}
\ No newline at end of file
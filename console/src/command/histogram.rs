@@ -0,0 +1,123 @@
+use parse_args::argparser::ArgValues;
+use serde::Serialize;
+use colored::{Color, Colorize};
+use rs_image::image;
+use image::histogram::Histogram;
+
+use crate::constants::{self, args};
+use super::{CommandError, ReportFormat, flag_set, get_count, load_image, print_json, report_format};
+
+///
+/// Pixel counts of each channel, grouped into bins of equal size
+///
+#[derive(Serialize)]
+struct HistogramReport {
+    bins: usize,
+    alpha: Vec<u32>,
+    red: Vec<u32>,
+    green: Vec<u32>,
+    blue: Vec<u32>,
+    luminance: Vec<u32>
+}
+
+///
+/// Draw a bar chart of each color channel, and of luminance
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let report_format = report_format(arguments)?;
+
+    let log_scale = flag_set(arguments, args::keys::LOG_SCALE)?;
+
+    //Default to as many bars as fit in the terminal, or every value when not drawing
+    let bins = match (get_count(arguments, args::keys::BINS)?, report_format) {
+        (Some(bins), _) => bins.min(256),
+        (None, ReportFormat::Json) => 256,
+        (None, ReportFormat::Text) => termsize::get()
+            .map_or(constants::histogram::DEFAULT_BINS, |size| (size.cols as usize).saturating_sub(1).max(1))
+            .min(constants::histogram::DEFAULT_BINS)
+    };
+
+    let height = get_count(arguments, args::keys::HEIGHT)?
+        .unwrap_or(constants::histogram::DEFAULT_HEIGHT);
+
+    let img = load_image(arguments)?;
+
+    let histogram = img.histogram();
+
+    if report_format == ReportFormat::Json {
+        return print_json(&HistogramReport {
+            bins,
+            alpha: Histogram::bin(&histogram.alpha, bins),
+            red: Histogram::bin(&histogram.red, bins),
+            green: Histogram::bin(&histogram.green, bins),
+            blue: Histogram::bin(&histogram.blue, bins),
+            luminance: Histogram::bin(&histogram.luminance, bins)
+        });
+    }
+
+    let charts = [
+        ("Red", &histogram.red, Color::Red),
+        ("Green", &histogram.green, Color::Green),
+        ("Blue", &histogram.blue, Color::Blue),
+        ("Luminance", &histogram.luminance, Color::White)
+    ];
+
+    for (name, channel, color) in charts {
+        println!("{name}");
+
+        for line in chart(&Histogram::bin(channel, bins), height, log_scale) {
+            println!("{}", line.color(color));
+        }
+
+        println!("{}", axis(bins));
+    }
+
+    Ok(())
+}
+
+///
+/// Draw the counts as vertical bars, returning the lines of the chart from top to bottom
+///
+fn chart(counts: &[u32], height: usize, log_scale: bool) -> Vec<String> {
+    let scale = |count: u32| if log_scale {
+        (count as f32).ln_1p()
+    }
+    else {
+        count as f32
+    };
+
+    let max = counts.iter()
+        .map(|count| scale(*count))
+        .fold(0_f32, f32::max);
+
+    let bar_chars = constants::histogram::BAR_CHARS;
+    let steps = bar_chars.len();
+
+    //Height of each bar, in fractions of a line
+    let bars: Vec<usize> = counts.iter()
+        .map(|count| if max == 0_f32 {
+            0
+        }
+        else {
+            (scale(*count) / max * (height * steps) as f32).round() as usize
+        })
+        .collect();
+
+    (0..height).rev()
+        .map(|line| bars.iter()
+            .map(|bar| match bar.saturating_sub(line * steps) {
+                0 => ' ',
+                filled => bar_chars[filled.min(steps) - 1]
+            })
+            .collect())
+        .collect()
+}
+
+///
+/// Label the lowest and highest channel values under a chart
+///
+fn axis(bins: usize) -> String {
+    let (low, high) = ("0", "255");
+    let gap = bins.saturating_sub(low.len() + high.len()).max(1);
+    format!("{low}{}{high}", " ".repeat(gap))
+}
\ No newline at end of file
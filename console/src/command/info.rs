@@ -0,0 +1,275 @@
+use parse_args::argparser::ArgValues;
+use serde::Serialize;
+use rs_image::{image, convert::ConvertableFrom};
+use image::format::{self, ImageFormat, codec, bitmap::{Bitmap, BitmapRef, DecodeOptions, ImageConvertData}};
+use image::stats::ChannelStats;
+
+use crate::constants::args;
+use super::{CommandError, ReportFormat, flag_set, load_bytes, print_json, report_format};
+
+///
+/// Summary of the values of one channel
+///
+#[derive(Serialize)]
+struct ChannelReport {
+    min: u8,
+    max: u8,
+    mean: f32,
+    stddev: f32,
+    median: u8
+}
+
+impl From<ChannelStats> for ChannelReport {
+    fn from(value: ChannelStats) -> Self {
+        Self {
+            min: value.min,
+            max: value.max,
+            mean: value.mean,
+            stddev: value.stddev,
+            median: value.median
+        }
+    }
+}
+
+///
+/// Summary of the values of each channel
+///
+#[derive(Serialize)]
+struct StatsReport {
+    alpha: ChannelReport,
+    red: ChannelReport,
+    green: ChannelReport,
+    blue: ChannelReport,
+    luminance: ChannelReport,
+    entropy: f32,
+    average_color: Option<String>,
+    mode_color: Option<String>
+}
+
+impl From<&image::Image> for StatsReport {
+    fn from(value: &image::Image) -> Self {
+        let stats = value.stats();
+
+        Self {
+            alpha: stats.alpha.into(),
+            red: stats.red.into(),
+            green: stats.green.into(),
+            blue: stats.blue.into(),
+            luminance: stats.luminance.into(),
+            entropy: stats.entropy,
+            average_color: value.average_color(true).map(|color| color.to_hex()),
+            mode_color: value.mode_color().map(|color| color.to_hex())
+        }
+    }
+}
+
+///
+/// Metadata of an image
+///
+#[derive(Serialize)]
+struct InfoReport {
+    format: &'static str,
+    width: u32,
+    height: u32,
+    top_down: bool,
+    mirrored: bool,
+    orientation: &'static str,
+    bit_depth: u16,
+    compression: &'static str,
+    compression_code: u32,
+    palette_size: usize,
+    colors_used: u32,
+    important_colors: u32,
+    x_pixels_per_meter: u32,
+    y_pixels_per_meter: u32,
+    x_dpi: f32,
+    y_dpi: f32,
+    print_size_mm: Option<(f32, f32)>,
+    info_header_size: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color_space: Option<&'static str>,
+    data_offset: u32,
+    image_size: u32,
+    declared_file_size: u32,
+    computed_file_size: usize,
+    actual_file_size: usize,
+    warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<StatsReport>
+}
+
+///
+/// Metadata of an image in a format other than bmp, which only has what
+/// any decoded image does
+///
+#[derive(Serialize)]
+struct ImageInfoReport {
+    format: String,
+    width: usize,
+    height: usize,
+    actual_file_size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<StatsReport>
+}
+
+///
+/// Print the metadata of an image
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let report_format = report_format(arguments)?;
+    let with_stats = flag_set(arguments, args::keys::STATS)?;
+
+    let bytes = load_bytes(arguments)?;
+    let actual_size = bytes.len();
+
+    let detected = format::detect(&bytes)
+        .ok_or_else(|| CommandError::failure(String::from("Unrecognized image format.")))?;
+
+    if detected != ImageFormat::Bitmap {
+        return run_other(&bytes, report_format, with_stats);
+    }
+
+    //Only the headers are needed, so leave the pixel data unconverted
+    let (bitmap, decode_report) = BitmapRef::decode_with_report(&bytes[..], DecodeOptions::default())?;
+    let header = &bitmap.header;
+    let info_header = &bitmap.info_header;
+
+    let resolution = info_header.resolution();
+    let (x_dpi, y_dpi) = resolution.dpi();
+    let width = info_header.width.unsigned_abs();
+    let height = info_header.height.unsigned_abs();
+
+    let report = InfoReport {
+        format: detected.name(),
+        width,
+        height,
+        top_down: info_header.orientation().is_top_down(),
+        mirrored: info_header.orientation().is_mirrored(),
+        orientation: info_header.orientation().name(),
+        bit_depth: info_header.bit_depth,
+        compression: bitmap.compression_name(),
+        compression_code: info_header.compression,
+        palette_size: bitmap.color_table.colors.len(),
+        colors_used: info_header.colors_used,
+        important_colors: info_header.important_colors,
+        x_pixels_per_meter: resolution.x_pixels_per_meter(),
+        y_pixels_per_meter: resolution.y_pixels_per_meter(),
+        x_dpi: x_dpi.round(),
+        y_dpi: y_dpi.round(),
+        print_size_mm: resolution.physical_size(width as usize, height as usize),
+        info_header_size: info_header.size,
+        color_space: info_header.color_space_name(),
+        data_offset: header.data_offset,
+        image_size: info_header.image_size,
+        declared_file_size: header.file_size,
+        computed_file_size: bitmap.computed_file_size(),
+        actual_file_size: actual_size,
+        warnings: decode_report.warnings,
+        stats: None
+    };
+
+    //The pixels are only converted when asked for, since they're much more work than the headers
+    let report = if with_stats {
+        let (bitmap, _) = Bitmap::decode_with_report(&bytes[..], DecodeOptions::default())?;
+        let img = image::Image::try_convert_from(bitmap, ImageConvertData::default())?;
+
+        InfoReport { stats: Some(StatsReport::from(&img)), ..report }
+    }
+    else {
+        report
+    };
+
+    if report_format == ReportFormat::Json {
+        return print_json(&report);
+    }
+
+    println!("Format:            {}", report.format);
+    println!("Dimensions:        {} x {}", report.width, report.height);
+    println!("Orientation:       {}", report.orientation);
+    println!("Bit depth:         {}", report.bit_depth);
+    println!("Compression:       {} ({})", report.compression, report.compression_code);
+    println!("Palette size:      {} (colors used = {}, important = {})",
+        report.palette_size, report.colors_used, report.important_colors);
+    println!("Resolution:        {} x {} pixels/meter ({} x {} DPI)",
+        report.x_pixels_per_meter, report.y_pixels_per_meter, report.x_dpi, report.y_dpi);
+
+    if let Some((width, height)) = report.print_size_mm {
+        println!("Print size:        {width:.1} x {height:.1} mm");
+    }
+
+    println!("Info header size:  {}", report.info_header_size);
+
+    if let Some(color_space) = report.color_space {
+        println!("Color space:       {color_space}");
+    }
+
+    println!("Data offset:       {}", report.data_offset);
+    println!("Image size:        {}", report.image_size);
+    println!("File size:         {} declared, {} computed, {} actual",
+        report.declared_file_size, report.computed_file_size, report.actual_file_size);
+
+    if let Some(stats) = &report.stats {
+        print_stats(stats);
+    }
+
+    if !report.warnings.is_empty() {
+        println!("Warnings:");
+
+        for warning in &report.warnings {
+            println!("  {warning}");
+        }
+    }
+
+    Ok(())
+}
+
+///
+/// Print the metadata of an image in a format other than bmp, decoding it
+/// through the codec registry
+///
+fn run_other(bytes: &[u8], report_format: ReportFormat, with_stats: bool) -> Result<(), CommandError> {
+    let codec = codec::detect(bytes)
+        .ok_or_else(|| CommandError::failure(String::from("Unrecognized image format.")))?;
+
+    let img = {
+        let _timer = rs_image::log::time("Decode");
+        codec::load(bytes)?
+    };
+
+    let report = ImageInfoReport {
+        format: String::from(codec.name()),
+        width: img.width(),
+        height: img.height(),
+        actual_file_size: bytes.len(),
+        stats: with_stats.then(|| StatsReport::from(&img))
+    };
+
+    if report_format == ReportFormat::Json {
+        return print_json(&report);
+    }
+
+    println!("Format:            {}", report.format);
+    println!("Dimensions:        {} x {}", report.width, report.height);
+    println!("File size:         {}", report.actual_file_size);
+
+    if let Some(stats) = &report.stats {
+        print_stats(stats);
+    }
+
+    Ok(())
+}
+
+///
+/// Print the summary of each channel
+///
+fn print_stats(stats: &StatsReport) {
+    println!("Statistics:        min  max     mean   stddev median");
+
+    for (name, channel) in [("Alpha", &stats.alpha), ("Red", &stats.red), ("Green", &stats.green), ("Blue", &stats.blue), ("Luminance", &stats.luminance)] {
+        println!("  {name: <15} {: >4} {: >4} {: >8.2} {: >8.2} {: >7}", channel.min, channel.max, channel.mean, channel.stddev, channel.median);
+    }
+
+    println!("  Entropy:        {:.3} bits", stats.entropy);
+    println!("  Average color:  {}", stats.average_color.as_deref().unwrap_or("none"));
+    println!("  Mode color:     {}", stats.mode_color.as_deref().unwrap_or("none"));
+}
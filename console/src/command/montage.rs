@@ -0,0 +1,133 @@
+use parse_args::{argparser::ArgValues, typed::TypedArgs};
+use rs_image::{color, image, convert::ConvertableFrom};
+use image::{Image, text, transform::ResizeFilter};
+use image::format::bitmap::{Bitmap, BitDepth, BitmapConvertData};
+use image::resolution::Resolution;
+
+use crate::console;
+use crate::constants::{self, args};
+use super::{CommandError, get_count, image_paths, load_image_into, save_bytes, view};
+
+///
+/// Lay out thumbnails of several images in a grid, each labeled with
+/// its file name, and write the result to a file or the console
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let paths = image_paths(arguments)?;
+
+    if paths.is_empty() {
+        return Err(CommandError::failure(String::from("No images to lay out.")));
+    }
+
+    let thumbnail_size = get_count(arguments, args::keys::THUMBNAIL_SIZE)?
+        .unwrap_or(constants::montage::DEFAULT_THUMBNAIL_SIZE);
+
+    if thumbnail_size > constants::montage::MAX_THUMBNAIL_SIZE {
+        return Err(CommandError::usage(format!("Argument '{}' must be at most {}, but was {thumbnail_size}.",
+            args::keys::THUMBNAIL_SIZE, constants::montage::MAX_THUMBNAIL_SIZE)));
+    }
+
+    //Default to a grid that's roughly square
+    let columns = get_count(arguments, args::keys::COLUMNS)?
+        .unwrap_or_else(|| (paths.len() as f32).sqrt().ceil() as usize)
+        .min(paths.len());
+
+    let rows = paths.len().div_ceil(columns);
+
+    let padding = constants::montage::PADDING;
+    let label_scale = constants::montage::LABEL_SCALE;
+    let label_height = text::GLYPH_HEIGHT * label_scale;
+
+    let cell_width = thumbnail_size + padding;
+    let cell_height = thumbnail_size + padding + label_height + padding;
+
+    //Many images make a sheet too large to hold, even with small thumbnails
+    let (sheet_width, sheet_height) = columns.checked_mul(cell_width).and_then(|width| width.checked_add(padding))
+        .zip(rows.checked_mul(cell_height).and_then(|height| height.checked_add(padding)))
+        .filter(|(width, height)| width.checked_mul(*height).is_some_and(|pixels| pixels <= rs_image::constants::image::MAX_PIXELS))
+        .ok_or_else(|| CommandError::usage(format!("A sheet of {} images at a thumbnail size of {thumbnail_size} is larger than the {} pixels allowed.",
+            paths.len(), rs_image::constants::image::MAX_PIXELS)))?;
+
+    let mut sheet = Image::solid(
+        sheet_width,
+        sheet_height,
+        color::ARGB::from_u32(constants::montage::BACKGROUND, false)
+    );
+
+    let label_color = color::ARGB::from_u32(constants::montage::LABEL_COLOR, false);
+
+    //Fit as many characters as possible under the thumbnail
+    let max_label_chars = (thumbnail_size + text::GLYPH_SPACING * label_scale)
+        / ((text::GLYPH_WIDTH + text::GLYPH_SPACING) * label_scale);
+
+    //Every file is decoded into the same buffer, since only its thumbnail is kept
+    let mut decoded = Image::default();
+
+    for (index, path) in paths.iter().enumerate() {
+        load_image_into(&path.to_string_lossy(), &mut decoded)?;
+        let thumbnail = thumbnail(&decoded, thumbnail_size);
+
+        let (cell_x, cell_y) = (padding + (index % columns) * cell_width, padding + (index / columns) * cell_height);
+
+        //Center the thumbnail in its square
+        sheet.draw_image(
+            &thumbnail,
+            cell_x + (thumbnail_size - thumbnail.width()) / 2,
+            cell_y + (thumbnail_size - thumbnail.height()) / 2
+        );
+
+        let label: String = path.file_name()
+            .map(|name| name.to_string_lossy().chars().take(max_label_chars).collect())
+            .unwrap_or_default();
+
+        let (label_width, _) = text::text_size(&label, label_scale);
+
+        sheet.draw_text(
+            &label,
+            cell_x + (thumbnail_size - label_width) / 2,
+            cell_y + thumbnail_size + padding,
+            label_color,
+            label_scale
+        );
+    }
+
+    match arguments.value(args::keys::OUTPUT_PATH) {
+        Some(out_path) => {
+            let bmp = Bitmap::try_convert_from(sheet, BitmapConvertData {
+                bit_depth: BitDepth::Bits(32),
+                compression: rs_image::constants::bitmap::compression::BI_RGB,
+                resolution: Resolution::default(),
+                ..Default::default()
+            })?;
+
+            save_bytes(out_path, &Vec::try_from(bmp)?)
+        },
+        None => {
+            console::write_image_to_console(sheet, &view::settings(arguments)?);
+            println!();
+            Ok(())
+        }
+    }
+}
+
+///
+/// Scale the image to fit within a square of the given size, keeping its proportions
+///
+fn thumbnail(image: &Image, size: usize) -> Image {
+    if image.length() == 0 {
+        return image.clone();
+    }
+
+    let scale = (size as f32 / image.width() as f32).min(size as f32 / image.height() as f32);
+    let width = ((image.width() as f32 * scale).round() as usize).clamp(1, size);
+    let height = ((image.height() as f32 * scale).round() as usize).clamp(1, size);
+
+    let filter = if scale < 1_f32 {
+        ResizeFilter::Box
+    }
+    else {
+        ResizeFilter::Bilinear
+    };
+
+    image.resize(width, height, filter)
+}
\ No newline at end of file
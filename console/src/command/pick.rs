@@ -0,0 +1,217 @@
+use std::io::{stdin, stdout, BufRead, Write};
+use parse_args::{argparser::ArgValues, typed::TypedArgs};
+use serde::Serialize;
+use rs_image::{color, image, convert::ConvertableFrom};
+use color::{AHSV, ALAB, conversion::LABSettings, names};
+use image::{Image, transform::ResizeFilter};
+
+use crate::console;
+use crate::palette::Palette256;
+use crate::constants::{self, args};
+use super::{CommandError, ReportFormat, load_image, print_json, report_format, view};
+
+///
+/// The values of a picked pixel in each color space
+///
+#[derive(Serialize)]
+struct PickReport {
+    x: usize,
+    y: usize,
+    alpha: u8,
+    red: u8,
+    green: u8,
+    blue: u8,
+    hex: String,
+    ///
+    /// Hue in degrees, with saturation and value as percentages
+    ///
+    hsv: [f32; 3],
+    ///
+    /// CIELAB under the D65 white point
+    ///
+    lab: [f32; 3],
+    name: &'static str,
+    name_hex: String,
+    ansi: u8,
+    ansi_hex: String
+}
+
+impl PickReport {
+    fn new(image: &Image, x: usize, y: usize, palette: &Palette256) -> Result<Self, CommandError> {
+        let pixel = image.get(x, y)
+            .ok_or_else(|| CommandError::usage(format!("Pixel {x},{y} is outside of the {}x{} image.", image.width(), image.height())))?;
+
+        let hsv = AHSV::try_convert_from(pixel, ())?;
+        let lab = ALAB::try_convert_from(pixel, LABSettings::D65)?;
+        let (name, named) = names::nearest_named(&pixel);
+        let ansi = palette.nearest(pixel);
+
+        Ok(Self {
+            x,
+            y,
+            alpha: pixel.alpha,
+            red: pixel.red,
+            green: pixel.green,
+            blue: pixel.blue,
+            hex: pixel.to_hex(),
+            hsv: [hsv.h * 360_f32, hsv.s * 100_f32, hsv.v * 100_f32],
+            lab: [lab.l, lab.a, lab.b],
+            name,
+            name_hex: named.to_hex(),
+            ansi,
+            ansi_hex: palette.color(ansi).to_hex()
+        })
+    }
+
+    fn print(&self) {
+        println!("Position   {}, {}", self.x, self.y);
+        println!("ARGB       {}, {}, {}, {}", self.alpha, self.red, self.green, self.blue);
+        println!("Hex        {}", self.hex);
+        println!("HSV        {:.1}°, {:.1}%, {:.1}%", self.hsv[0], self.hsv[1], self.hsv[2]);
+        println!("Lab        {:.2}, {:.2}, {:.2}", self.lab[0], self.lab[1], self.lab[2]);
+        println!("Name       {} ({})", self.name, self.name_hex);
+        println!("ANSI       {} ({})", self.ansi, self.ansi_hex);
+    }
+}
+
+///
+/// Print the values of one pixel of an image, given by /at, or of the pixel
+/// under a crosshair moved over the image drawn in the console
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let image = load_image(arguments)?;
+    let palette = Palette256::new();
+
+    let Some(at) = arguments.value(args::keys::AT) else {
+        return interactive(arguments, &image, &palette);
+    };
+
+    let (x, y) = parse_position(at)
+        .ok_or_else(|| CommandError::usage(format!("Argument '{}' must be in the form X,Y, but was '{at}'.", args::keys::AT)))?;
+
+    let report = PickReport::new(&image, x, y, &palette)?;
+
+    match report_format(arguments)? {
+        ReportFormat::Json => print_json(&report),
+        ReportFormat::Text => {
+            report.print();
+            Ok(())
+        }
+    }
+}
+
+///
+/// Draw the image with a crosshair over the picked pixel, and move it by the
+/// lines read from stdin. Without a terminal in raw mode, keys only arrive once
+/// Enter is pressed, so each line is one move.
+///
+fn interactive(arguments: &ArgValues, image: &Image, palette: &Palette256) -> Result<(), CommandError> {
+    use constants::pick::keys;
+
+    if arguments.value(args::keys::FILE_PATH) == Some(args::values::STD_STREAM) {
+        return Err(CommandError::usage(format!("Argument '{}' is required when the image is read from stdin.", args::keys::AT)));
+    }
+
+    if image.width() == 0 || image.height() == 0 {
+        return Err(CommandError::failure(String::from("The image has no pixels to pick.")));
+    }
+
+    let mut settings = view::settings(arguments)?;
+
+    //Scale once up front, so the crosshair can be drawn at the size the image is shown
+    let (width, height) = console::fit_size(image, &settings);
+    let (width, height) = (width.max(1), height.max(1));
+
+    let drawn = if (width, height) == (image.width(), image.height()) {
+        image.clone()
+    }
+    else {
+        image.resize(width, height, if width < image.width() { ResizeFilter::Box } else { ResizeFilter::Bilinear })
+    };
+
+    settings.width = Some(width);
+    settings.height = Some(height);
+
+    //Source pixels covered by each drawn pixel, which is how far one move goes
+    let step_x = image.width().div_ceil(width).max(1);
+    let step_y = image.height().div_ceil(height).max(1);
+
+    let (mut x, mut y) = (image.width() / 2, image.height() / 2);
+    let mut lines = stdin().lock().lines();
+
+    loop {
+        print!("{}", constants::slideshow::CLEAR_SCREEN);
+
+        console::write_image_to_console(crosshair(&drawn, x * width / image.width(), y * height / image.height()), &settings);
+
+        println!("{}", constants::play::RESTORE_CURSOR);
+
+        PickReport::new(image, x, y, palette)?.print();
+
+        print!("Move with {}/{}/{}/{}, type X,Y to jump, or {} to quit: ", keys::UP, keys::LEFT, keys::DOWN, keys::RIGHT, keys::QUIT);
+
+        let _ = stdout().flush();
+
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+
+        let line = line.trim().to_ascii_lowercase();
+
+        match line.as_str() {
+            keys::QUIT => break,
+            keys::UP => y = y.saturating_sub(step_y),
+            keys::DOWN => y = (y + step_y).min(image.height() - 1),
+            keys::LEFT => x = x.saturating_sub(step_x),
+            keys::RIGHT => x = (x + step_x).min(image.width() - 1),
+            line => if let Some((to_x, to_y)) = parse_position(line) {
+                x = to_x.min(image.width() - 1);
+                y = to_y.min(image.height() - 1);
+            }
+        }
+    }
+
+    println!();
+
+    Ok(())
+}
+
+///
+/// Draw a line through the row and column of the given pixel, in the inverse
+/// of the colors under it, leaving the pixel itself uncovered
+///
+fn crosshair(image: &Image, x: usize, y: usize) -> Image {
+    let mut drawn = image.clone();
+
+    let invert = |pixel: color::ARGB| color::ARGB {
+        alpha: 0xFF,
+        red: !pixel.red,
+        green: !pixel.green,
+        blue: !pixel.blue
+    };
+
+    let row = (0..image.width()).filter(|i| *i != x).map(|i| (i, y));
+    let column = (0..image.height()).filter(|j| *j != y).map(|j| (x, j));
+
+    for (i, j) in row.chain(column) {
+        if let Some(pixel) = image.get(i, j) {
+            let _ = drawn.set(invert(pixel), i, j);
+        }
+    }
+
+    drawn
+}
+
+///
+/// Check that a position is in the form X,Y
+///
+pub fn validate_position(value: &str) -> Result<(), String> {
+    parse_position(value)
+        .map(|_| ())
+        .ok_or_else(|| String::from("expected the form X,Y."))
+}
+
+fn parse_position(value: &str) -> Option<(usize, usize)> {
+    let (x, y) = value.split_once(args::values::REGION_SEPARATOR)?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
\ No newline at end of file
@@ -0,0 +1,137 @@
+use std::{io::{stdout, Write}, path::Path, thread, time::Duration};
+use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+use parse_args::{argparser::ArgValues, typed::TypedArgs};
+use rs_image::{image, convert::ConvertableFrom};
+use image::{animation::{AnimatedImage, Disposal, Frame}, format::{self, ImageFormat, codec, bitmap::ImageConvertData, gif::Gif}};
+
+use crate::console;
+use crate::constants::{self, args};
+use super::{CommandError, list_directory, load_bytes, load_image_into, transform, view};
+
+///
+/// Play the frames at the given path in the console, drawing each
+/// frame over the last
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let settings = view::settings(arguments)?;
+
+    let delay = arguments.get_u64(args::keys::DELAY)
+        .map_err(CommandError::usage)?
+        .unwrap_or(constants::play::DEFAULT_DELAY_MS);
+
+    let loops = arguments.get_usize(args::keys::LOOP)
+        .map_err(CommandError::usage)?;
+
+    let animation = load_frames(arguments, Duration::from_millis(delay))?;
+    let loops = loops.unwrap_or(animation.loop_count());
+
+    if animation.is_empty() {
+        return Err(CommandError::failure(String::from("No frames to play.")));
+    }
+
+    //Every loop shows the same canvases, so compose and transform them once up front
+    let frames = animation.compose()
+        .map(|(canvas, delay)| Ok((transform::apply(arguments, canvas)?, delay)))
+        .collect::<Result<Vec<_>, CommandError>>()?;
+
+    //Stop at the end of the current frame on Ctrl-C, so the terminal can be restored
+    let interrupted = Arc::new(AtomicBool::new(false));
+
+    {
+        let interrupted = Arc::clone(&interrupted);
+        ctrlc::set_handler(move || interrupted.store(true, Ordering::SeqCst))
+            .map_err(|err| CommandError::failure(format!("Failed to handle interrupts: {err}")))?;
+    }
+
+    print!("{}", constants::play::HIDE_CURSOR);
+
+    let mut lines_drawn = 0;
+    let mut iteration = 0;
+
+    'playback: while loops == 0 || iteration < loops {
+        for (image, delay) in &frames {
+            if interrupted.load(Ordering::SeqCst) {
+                break 'playback;
+            }
+
+            //Move back to where the previous frame started, and draw over it
+            if lines_drawn > 0 {
+                print!("\x1b[{lines_drawn}A\r");
+            }
+
+            lines_drawn = console::write_image_to_console(image.clone(), &settings);
+
+            let _ = stdout().flush();
+
+            sleep_unless_interrupted(*delay, &interrupted);
+        }
+
+        iteration += 1;
+    }
+
+    println!("{}", constants::play::RESTORE_CURSOR);
+
+    Ok(())
+}
+
+///
+/// Decode each frame of the animation. A gif keeps its own frames, delays and disposal,
+/// falling back to the given delay for frames without one. Otherwise, each frame is a
+/// whole still, so it is cleared once shown rather than drawn over by the next.
+///
+fn load_frames(arguments: &ArgValues, delay: Duration) -> Result<AnimatedImage, CommandError> {
+    let directory = arguments.value(args::keys::FILE_PATH)
+        .map(Path::new)
+        .filter(|path| path.is_dir());
+
+    //Frames are decoded straight into images, without an intermediate bitmap
+    let images = match directory {
+        Some(directory) => list_directory(directory)?
+            .iter()
+            .map(|path| {
+                let mut image = image::Image::default();
+                load_image_into(&path.to_string_lossy(), &mut image)?;
+                Ok(image)
+            })
+            .collect::<Result<Vec<_>, CommandError>>()?,
+        None => {
+            let bytes = load_bytes(arguments)?;
+
+            let _timer = rs_image::log::time("Decode");
+
+            if format::detect(&bytes) == Some(ImageFormat::Gif) {
+                let gif = AnimatedImage::try_convert_from(Gif::try_from(bytes)?, ImageConvertData::default())?;
+
+                let mut animation = AnimatedImage::new(gif.width(), gif.height())
+                    .with_loop_count(gif.loop_count());
+
+                for frame in gif.iter().cloned() {
+                    animation.push(if frame.delay.is_zero() { Frame { delay, ..frame } } else { frame });
+                }
+
+                return Ok(animation);
+            }
+
+            //A single still image plays as one frame
+            vec![codec::load(&bytes)?]
+        }
+    };
+
+    Ok(AnimatedImage::from_frames(images.into_iter()
+        .map(|image| Frame::new(image, delay).with_disposal(Disposal::Background))
+        .collect()))
+}
+
+///
+/// Sleep for the given duration, waking early if interrupted
+///
+fn sleep_unless_interrupted(duration: Duration, interrupted: &AtomicBool) {
+    let poll = Duration::from_millis(constants::play::INTERRUPT_POLL_MS);
+    let mut remaining = duration;
+
+    while !remaining.is_zero() && !interrupted.load(Ordering::SeqCst) {
+        let step = remaining.min(poll);
+        thread::sleep(step);
+        remaining -= step;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,134 @@
+use std::{io::{stdin, stdout, BufRead, Write}, path::Path, thread, time::Duration};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use parse_args::{argparser::ArgValues, typed::TypedArgs};
+use rs_image::{image, log};
+
+use crate::console;
+use crate::constants::{self, args};
+use super::{CommandError, list_directory, load_image_into, transform, view};
+
+///
+/// What to do after showing an image
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Next,
+    Previous,
+    Quit
+}
+
+///
+/// Show each image in a directory in turn, fit to the console, moving on
+/// after the interval or when asked to on stdin
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let settings = view::settings(arguments)?;
+
+    let interval = arguments.get_u64(args::keys::INTERVAL)
+        .map_err(CommandError::usage)?
+        .unwrap_or(constants::slideshow::DEFAULT_INTERVAL_SECONDS);
+
+    let loops = arguments.get_usize(args::keys::LOOP)
+        .map_err(CommandError::usage)?
+        .unwrap_or(0);
+
+    let directory = arguments.value(args::keys::FILE_PATH)
+        .map(Path::new)
+        .filter(|path| path.is_dir())
+        .ok_or_else(|| CommandError::usage(format!("Argument '{}' must be a directory.", args::keys::FILE_PATH)))?;
+
+    let paths = list_directory(directory)?;
+
+    if paths.is_empty() {
+        return Err(CommandError::failure(format!("No images in '{}'.", directory.display())));
+    }
+
+    let (sender, steps) = mpsc::channel();
+
+    {
+        //The handler keeps a sender for the life of the process, so the channel is never disconnected
+        let sender = sender.clone();
+        ctrlc::set_handler(move || { let _ = sender.send(Step::Quit); })
+            .map_err(|err| CommandError::failure(format!("Failed to handle interrupts: {err}")))?;
+    }
+
+    read_steps(sender);
+
+    print!("{}", constants::play::HIDE_CURSOR);
+
+    //Images are decoded as they are shown, so a large directory starts at once
+    let mut index = 0;
+    let mut iteration = 0;
+
+    loop {
+        print!("{}", constants::slideshow::CLEAR_SCREEN);
+
+        match load(arguments, &paths[index]) {
+            Ok(image) => {
+                console::write_image_to_console(image, &settings);
+            },
+            //A file which isn't an image is skipped, rather than ending the show
+            Err(err) => log::warn(format_args!("Skipping '{}': {}", paths[index].display(), err.message))
+        }
+
+        let _ = stdout().flush();
+
+        let step = match steps.recv_timeout(Duration::from_secs(interval)) {
+            Ok(step) => step,
+            Err(RecvTimeoutError::Timeout) => Step::Next,
+            Err(RecvTimeoutError::Disconnected) => Step::Quit
+        };
+
+        index = match step {
+            Step::Quit => break,
+            Step::Next => (index + 1) % paths.len(),
+            Step::Previous => (index + paths.len() - 1) % paths.len()
+        };
+
+        //Moving on from the last image completes a loop
+        if step == Step::Next && index == 0 {
+            iteration += 1;
+
+            if loops > 0 && iteration >= loops {
+                break;
+            }
+        }
+    }
+
+    println!("{}", constants::play::RESTORE_CURSOR);
+
+    Ok(())
+}
+
+fn load(arguments: &ArgValues, path: &Path) -> Result<image::Image, CommandError> {
+    let mut image = image::Image::default();
+    load_image_into(&path.to_string_lossy(), &mut image)?;
+    transform::apply(arguments, image)
+}
+
+///
+/// Read steps from stdin, one per line, on a background thread. Without a terminal
+/// in raw mode, keys only arrive once Enter is pressed, so an empty line also moves on.
+///
+fn read_steps(sender: Sender<Step>) {
+    thread::spawn(move || {
+        use constants::slideshow::keys;
+
+        for line in stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+
+            let step = match line.trim().to_ascii_lowercase().as_str() {
+                "" | keys::NEXT => Step::Next,
+                keys::PREVIOUS => Step::Previous,
+                keys::QUIT => Step::Quit,
+                _ => continue
+            };
+
+            if sender.send(step).is_err() {
+                break;
+            }
+        }
+    });
+}
\ No newline at end of file
@@ -0,0 +1,36 @@
+use parse_args::{argparser::ArgValues, typed::TypedArgs};
+use rs_image::{log, image::{ImageMetadata, format::{self, ImageFormat, codec, bitmap::Bitmap}}};
+
+use crate::constants::args;
+use super::{CommandError, load_bytes, save_bytes};
+
+///
+/// Rewrite an image with only what is needed to show its pixels. Bitmaps keep
+/// their headers; other formats are decoded and re-encoded without metadata.
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let out_path = arguments.value(args::keys::OUTPUT_PATH)
+        .ok_or_else(|| CommandError::usage(format!("Missing required argument '{}{}'.", args::ARGUMENT_PREFIX, args::keys::OUTPUT_PATH)))?;
+
+    let bytes = load_bytes(arguments)?;
+
+    let bytes = if format::detect(&bytes) == Some(ImageFormat::Bitmap) {
+        let bitmap = Bitmap::try_from(bytes)?;
+        Vec::try_from(bitmap.strip_metadata()?)?
+    }
+    else {
+        let codec = codec::detect(&bytes)
+            .ok_or_else(|| CommandError::failure(String::from("Unrecognized image format.")))?;
+
+        if !codec.can_encode() {
+            return Err(CommandError::failure(format!("Writing {} images is not supported.", codec.name())));
+        }
+
+        let image = codec.decode(&bytes)?.with_metadata(ImageMetadata::default());
+        codec.encode(&image)?
+    };
+
+    log::info(format_args!("Stripped image is {} bytes", bytes.len()));
+
+    save_bytes(out_path, &bytes)
+}
@@ -0,0 +1,26 @@
+use super::*;
+use rs_image::log::Level;
+
+fn flags(keys: &[&str]) -> ArgValues {
+    argparser::collect_values(&keys.iter().map(|key| Arg::Flag(String::from(*key))).collect::<Vec<_>>())
+}
+
+#[test]
+fn verbose_and_quiet_set_log_level() -> Result<(), String> {
+    for (keys, expected) in [
+        (&[][..], None),
+        (&[args::keys::VERBOSE][..], Some(Level::Debug)),
+        (&[args::keys::QUIET][..], Some(Level::Error))
+    ] {
+        let level = log_level(&flags(keys)).map_err(|err| err.message)?;
+
+        if level != expected {
+            return Err(format!("Expected {keys:?} to set the log level to {expected:?}, got {level:?}."));
+        }
+    }
+
+    match log_level(&flags(&[args::keys::VERBOSE, args::keys::QUIET])) {
+        Err(err) if err.exit_code == exit_codes::USAGE => Ok(()),
+        other => Err(format!("Expected verbose and quiet together to be a usage error, got {:?}.", other.map_err(|err| err.message)))
+    }
+}
@@ -0,0 +1,246 @@
+#[cfg(test)]
+mod tests;
+
+use std::sync::OnceLock;
+
+use parse_args::{argparser::ArgValues, typed::TypedArgs};
+use rs_image::{color::lut::CubeLut, image::{Image, filter::Filter, recipe::Recipe, transform::{Flip, ResizeFilter, Rotation}}};
+
+use crate::constants::args;
+use super::{CommandError, check_size};
+
+///
+/// Apply the geometric transforms given in the arguments to the image,
+/// in the order crop, resize, rotate, flip, followed by any filters, LUT and recipe
+///
+pub fn apply(arguments: &ArgValues, image: Image) -> Result<Image, CommandError> {
+    let mut image = image;
+
+    if let Some((x, y, width, height)) = crop_region(arguments)? {
+        if width == 0 || height == 0
+            || x.checked_add(width).is_none_or(|right| right > image.width())
+            || y.checked_add(height).is_none_or(|bottom| bottom > image.height()) {
+            return Err(CommandError::usage(format!("Crop region '{}' is not within the {}x{} image.",
+                arguments.value(args::keys::CROP).unwrap_or_default(), image.width(), image.height())));
+        }
+
+        image = image.crop(x, y, width, height);
+    }
+
+    apply_after_crop(arguments, image)
+}
+
+///
+/// Parse the crop region given in the arguments, if any
+///
+pub fn crop_region(arguments: &ArgValues) -> Result<Option<(usize, usize, usize, usize)>, CommandError> {
+    let Some(crop) = arguments.value(args::keys::CROP) else {
+        return Ok(None);
+    };
+
+    parse_region(crop)
+        .map(Some)
+        .ok_or_else(|| CommandError::usage(format!("Argument '{}' must be in the form X,Y,WxH, but was '{crop}'.", args::keys::CROP)))
+}
+
+///
+/// Apply every transform given in the arguments except crop, for an
+/// image which was already cropped (e.g. by decoding only the region)
+///
+pub fn apply_after_crop(arguments: &ArgValues, image: Image) -> Result<Image, CommandError> {
+    let _timer = rs_image::log::time("Transform");
+
+    let mut image = image;
+
+    if let Some(resize) = arguments.value(args::keys::RESIZE) {
+        let (width, height) = parse_size(resize)
+            .filter(|(width, height)| *width > 0 && *height > 0)
+            .ok_or_else(|| CommandError::usage(format!("Argument '{}' must be in the form WxH, but was '{resize}'.", args::keys::RESIZE)))?;
+
+        check_size(args::keys::RESIZE, width, height)?;
+
+        //Box averages away detail when shrinking, but blocks up when enlarging
+        let filter = if width * height < image.length() {
+            ResizeFilter::Box
+        }
+        else {
+            ResizeFilter::Bilinear
+        };
+
+        image = image.resize(width, height, filter);
+    }
+
+    if let Some(rotate) = arguments.value(args::keys::ROTATE) {
+        let rotation = match rotate {
+            args::values::rotate::DEGREES_90 => Rotation::Rotate90,
+            args::values::rotate::DEGREES_180 => Rotation::Rotate180,
+            args::values::rotate::DEGREES_270 => Rotation::Rotate270,
+            _ => return Err(CommandError::usage(format!("Unsupported rotation '{rotate}'.")))
+        };
+
+        image = image.rotate(rotation);
+    }
+
+    if let Some(flip) = arguments.value(args::keys::FLIP) {
+        let flip = match flip.to_ascii_lowercase().as_str() {
+            args::values::flip::HORIZONTAL => Flip::Horizontal,
+            args::values::flip::VERTICAL => Flip::Vertical,
+            _ => return Err(CommandError::usage(format!("Unsupported flip '{flip}'.")))
+        };
+
+        image = image.flip(flip);
+    }
+
+    if let Some(filters) = arguments.value(args::keys::FILTER) {
+        let filters = parse_filters(filters)?;
+        image = image.filter_all(&filters);
+    }
+
+    if let Some(path) = arguments.value(args::keys::LUT) {
+        image = load_lut(path)?.apply(&image);
+    }
+
+    if let Some(path) = arguments.value(args::keys::RECIPE) {
+        image = load_recipe(path)?.apply(image)?;
+    }
+
+    Ok(image)
+}
+
+///
+/// Read the LUT at the path. It is only read once, as the arguments
+/// are the same for every frame or file the transforms are applied to.
+///
+fn load_lut(path: &str) -> Result<&'static CubeLut, CommandError> {
+    static LUT: OnceLock<Result<CubeLut, String>> = OnceLock::new();
+
+    LUT.get_or_init(|| {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read LUT '{path}': {err}"))?;
+
+        CubeLut::try_from(text.as_str())
+            .map_err(|err| format!("Failed to parse LUT '{path}': {err}"))
+    })
+    .as_ref()
+    .map_err(|message| CommandError::failure(message.clone()))
+}
+
+///
+/// Read the recipe at the path, once, as with the LUT
+///
+fn load_recipe(path: &str) -> Result<&'static Recipe, CommandError> {
+    static RECIPE: OnceLock<Result<Recipe, String>> = OnceLock::new();
+
+    RECIPE.get_or_init(|| {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read recipe '{path}': {err}"))?;
+
+        serde_json::from_str(&text)
+            .map_err(|err| format!("Failed to parse recipe '{path}': {err}"))
+    })
+    .as_ref()
+    .map_err(|message| CommandError::failure(message.clone()))
+}
+
+///
+/// Check that a list of filters can be parsed
+///
+pub fn validate_filters(value: &str) -> Result<(), String> {
+    parse_filters(value).map(|_| ()).map_err(|err| err.message)
+}
+
+///
+/// Check that a region is in the form X,Y,WxH
+///
+pub fn validate_region(value: &str) -> Result<(), String> {
+    parse_region(value)
+        .map(|_| ())
+        .ok_or_else(|| String::from("expected the form X,Y,WxH."))
+}
+
+///
+/// Check that a size is in the form WxH, with neither dimension 0
+///
+pub fn validate_size(value: &str) -> Result<(), String> {
+    parse_size(value)
+        .filter(|(width, height)| *width > 0 && *height > 0)
+        .map(|_| ())
+        .ok_or_else(|| String::from("expected the form WxH."))
+}
+
+///
+/// Parse a list of filters in the form grayscale,blur=2,brightness=+20
+///
+fn parse_filters(value: &str) -> Result<Vec<Filter>, CommandError> {
+    use args::values::filter;
+
+    value.split(filter::SEPARATOR)
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .map(|f| {
+            let (name, parameter) = match f.split_once(filter::PARAMETER_SEPARATOR) {
+                Some((name, parameter)) => (name.trim().to_ascii_lowercase(), Some(parameter.trim())),
+                None => (f.to_ascii_lowercase(), None)
+            };
+
+            let invalid_parameter = |expected: &str| CommandError::usage(
+                format!("Filter '{name}' requires {expected}, but was given '{}'.", parameter.unwrap_or_default()));
+
+            match (name.as_str(), parameter) {
+                (filter::GRAYSCALE, None) => Ok(Filter::Grayscale),
+                (filter::INVERT, None) => Ok(Filter::Invert),
+                (filter::AUTO_CONTRAST, None) => Ok(Filter::AutoContrast),
+                (filter::AUTO_LEVELS, None) => Ok(Filter::AutoLevels),
+                (filter::STIPPLE, None) => Ok(Filter::Stipple),
+                (filter::BLUR, _) => parameter
+                    .and_then(|p| p.parse::<usize>().ok())
+                    .map(|radius| Filter::Blur { radius })
+                    .ok_or_else(|| invalid_parameter("a radius in pixels")),
+                (filter::BRIGHTNESS, _) => parameter
+                    .and_then(|p| p.parse::<f32>().ok())
+                    .filter(|amount| amount.is_finite())
+                    .map(|amount| Filter::Brightness { amount })
+                    .ok_or_else(|| invalid_parameter("an amount to add to each channel")),
+                (filter::KUWAHARA, _) => parameter
+                    .and_then(|p| p.parse::<usize>().ok())
+                    .map(|radius| Filter::Kuwahara { radius })
+                    .ok_or_else(|| invalid_parameter("a radius in pixels")),
+                (filter::ANISOTROPIC_KUWAHARA, _) => parameter
+                    .and_then(|p| p.parse::<usize>().ok())
+                    .map(|radius| Filter::AnisotropicKuwahara { radius })
+                    .ok_or_else(|| invalid_parameter("a radius in pixels")),
+                (filter::HATCH, _) => parameter
+                    .and_then(|p| p.parse::<usize>().ok())
+                    .filter(|spacing| *spacing >= 2)
+                    .map(|spacing| Filter::Hatch { spacing })
+                    .ok_or_else(|| invalid_parameter("a line spacing of at least 2 pixels")),
+                (filter::GRAYSCALE | filter::INVERT | filter::AUTO_CONTRAST | filter::AUTO_LEVELS | filter::STIPPLE, Some(_)) => Err(CommandError::usage(format!("Filter '{name}' does not take a parameter."))),
+                _ => Err(CommandError::usage(format!("Unrecognized filter '{name}'.")))
+            }
+        })
+        .collect()
+}
+
+///
+/// Parse a size in the form WxH
+///
+pub fn parse_size(value: &str) -> Option<(usize, usize)> {
+    let (width, height) = value.to_ascii_lowercase()
+        .split_once(args::values::SIZE_SEPARATOR)
+        .map(|(w, h)| (w.trim().parse::<usize>(), h.trim().parse::<usize>()))?;
+
+    Some((width.ok()?, height.ok()?))
+}
+
+///
+/// Parse a region in the form X,Y,WxH
+///
+fn parse_region(value: &str) -> Option<(usize, usize, usize, usize)> {
+    let mut parts = value.splitn(3, args::values::REGION_SEPARATOR);
+
+    let x = parts.next()?.trim().parse::<usize>().ok()?;
+    let y = parts.next()?.trim().parse::<usize>().ok()?;
+    let (width, height) = parse_size(parts.next()?)?;
+
+    Some((x, y, width, height))
+}
\ No newline at end of file
@@ -0,0 +1,90 @@
+use super::*;
+
+fn arguments(key: &str, value: &str) -> ArgValues {
+    ArgValues::from([(String::from(key), vec![String::from(value)])])
+}
+
+#[test]
+fn sizes_parse() -> Result<(), String> {
+    for (value, expected) in [
+        ("4x3", Some((4, 3))),
+        (" 4 X 3 ", Some((4, 3))),
+        ("0x0", Some((0, 0))),
+        ("4", None),
+        ("4x", None),
+        ("-1x3", None),
+        ("4x3x2", None),
+        ("18446744073709551616x1", None)
+    ] {
+        if parse_size(value) != expected {
+            return Err(format!("Expected '{value}' to parse as {expected:?}, got {:?}.", parse_size(value)));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn regions_parse() -> Result<(), String> {
+    for (value, expected) in [
+        ("1,2,3x4", Some((1, 2, 3, 4))),
+        ("18446744073709551615,0,1x1", Some((usize::MAX, 0, 1, 1))),
+        ("1,2", None),
+        ("1,2,3", None),
+        ("a,2,3x4", None)
+    ] {
+        if parse_region(value) != expected {
+            return Err(format!("Expected '{value}' to parse as {expected:?}, got {:?}.", parse_region(value)));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn filters_parse() -> Result<(), String> {
+    let filters = parse_filters("grayscale, blur=2,brightness=+20,,KUWAHARA=3").map_err(|err| err.message)?;
+
+    let expected = [
+        Filter::Grayscale,
+        Filter::Blur { radius: 2 },
+        Filter::Brightness { amount: 20_f32 },
+        Filter::Kuwahara { radius: 3 }
+    ];
+
+    if filters != expected {
+        return Err(format!("Expected {expected:?}, got {filters:?}."));
+    }
+
+    for value in ["blur", "blur=-1", "brightness=inf", "hatch=1", "invert=1", "sharpen"] {
+        if parse_filters(value).is_ok() {
+            return Err(format!("Expected filters '{value}' to be rejected."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn crop_outside_of_image() -> Result<(), String> {
+    for crop in ["18446744073709551615,0,1x1", "0,18446744073709551615,1x1", "3,0,2x1", "0,0,0x1"] {
+        match apply(&arguments(args::keys::CROP, crop), Image::new(4, 3)) {
+            Err(err) if err.exit_code == crate::constants::exit_codes::USAGE => (),
+            other => return Err(format!("Expected crop '{crop}' to be a usage error, got {:?}.", other.map(|image| (image.width(), image.height()))))
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn resize_too_large() -> Result<(), String> {
+    for resize in ["4294967296x4294967296", "100000x100000"] {
+        match apply(&arguments(args::keys::RESIZE, resize), Image::new(4, 3)) {
+            Err(err) if err.exit_code == crate::constants::exit_codes::USAGE => (),
+            other => return Err(format!("Expected resize '{resize}' to be a usage error, got {:?}.", other.map(|image| (image.width(), image.height()))))
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::{io::{self, Read}, path::Path};
+use parse_args::argparser::ArgValues;
+use serde::Serialize;
+use rs_image::image::format::bitmap::{Bitmap, ConformanceReport};
+
+use crate::constants::args;
+use super::{CommandError, ReportFormat, image_paths, print_json, report_format};
+
+///
+/// A finding, with its severity by name
+///
+#[derive(Serialize)]
+struct FindingReport {
+    severity: &'static str,
+    message: String
+}
+
+///
+/// Whether one file conforms, and everything found wrong with it
+///
+#[derive(Serialize)]
+struct FileReport {
+    path: String,
+    passed: bool,
+    findings: Vec<FindingReport>
+}
+
+impl FileReport {
+    fn new(path: &Path, conformance: ConformanceReport) -> Self {
+        Self {
+            path: path.display().to_string(),
+            passed: conformance.passed(),
+            findings: conformance.findings.into_iter()
+                .map(|finding| FindingReport { severity: finding.severity.name(), message: finding.message })
+                .collect()
+        }
+    }
+}
+
+///
+/// Check each bitmap against the format, failing if any breaks it
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let report_format = report_format(arguments)?;
+
+    let reports = image_paths(arguments)?
+        .iter()
+        .map(|path| check(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let failed = reports.iter().filter(|report| !report.passed).count();
+
+    if report_format == ReportFormat::Json {
+        print_json(&reports)?;
+    }
+    else {
+        for report in &reports {
+            println!("{} {}", if report.passed { "PASS" } else { "FAIL" }, report.path);
+
+            for finding in &report.findings {
+                println!("  {}: {}", finding.severity, finding.message);
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(CommandError::invalid(format!("{failed} of {} files failed validation.", reports.len())));
+    }
+
+    Ok(())
+}
+
+fn check(path: &Path) -> Result<FileReport, CommandError> {
+    let bytes = if path == Path::new(args::values::STD_STREAM) {
+        let mut bytes = Vec::new();
+
+        io::stdin().lock().read_to_end(&mut bytes)
+            .map_err(|err| CommandError::failure(format!("Failed to read stdin: {err}")))?;
+
+        bytes
+    }
+    else {
+        rs_image::utility::file::get_file_bytes(&path.to_string_lossy())
+            .map_err(|err| CommandError::failure(format!("Failed to read '{}': {err}", path.display())))?
+    };
+
+    Ok(FileReport::new(path, Bitmap::check_conformance(&bytes)))
+}
\ No newline at end of file
@@ -0,0 +1,256 @@
+use std::{fs::File, io::{self, IsTerminal, Read}, time::{Duration, Instant}};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+use parse_args::{argparser::ArgValues, typed::TypedArgs};
+use rs_image::{color, image, convert::ConvertableFrom};
+use image::{dither::DitherMode, format::bitmap::{Bitmap, DecodeOptions, ImageConvertData}};
+
+use crate::console::{self, WriteImageToConsoleSettings, Background};
+use crate::color_mode::ColorMode;
+use crate::constants::{self, args, env};
+use super::{CommandError, color_enabled, flag_set, is_other_format, load_bitmap, load_bitmap_region, load_image, transform};
+
+///
+/// Draw the bitmap in the console
+///
+pub fn run(arguments: &ArgValues) -> Result<(), CommandError> {
+    let mut settings = settings(arguments)?;
+
+    //When cropping, only the region of a bitmap needs to be decoded
+    let img = match transform::crop_region(arguments)? {
+        _ if is_other_format(arguments) => transform::apply(arguments, load_image(arguments)?)?,
+        Some(region) => transform::apply_after_crop(arguments, load_bitmap_region(arguments, region)?)?,
+        None => {
+            //Only someone at a terminal can see the rows drawn as they arrive
+            let bitmap = if io::stdout().is_terminal() {
+                load_bitmap_with_preview(arguments, &settings)?
+            }
+            else {
+                load_bitmap(arguments)?
+            };
+
+            transform::apply(arguments, image::Image::try_convert_from(bitmap, ImageConvertData::default())?)?
+        }
+    };
+
+    settings.page_rows = page_rows(arguments)?;
+
+    console::write_image_to_console(img, &settings);
+
+    println!();
+
+    Ok(())
+}
+
+///
+/// Read and decode the bitmap as it arrives, redrawing the rows decoded so far
+/// whenever reading has taken longer than the preview interval since the last
+/// drawing. The preview is untransformed, and cleared once the bitmap is read.
+///
+fn load_bitmap_with_preview(arguments: &ArgValues, settings: &WriteImageToConsoleSettings) -> Result<Bitmap, CommandError> {
+    let path = arguments.value(args::keys::FILE_PATH)
+        .ok_or_else(|| CommandError::usage(format!("Missing required argument '{}{}'.", args::ARGUMENT_PREFIX, args::keys::FILE_PATH)))?;
+
+    let (reader, source): (Box<dyn Read>, String) = if path == args::values::STD_STREAM {
+        (Box::new(io::stdin().lock()), String::from("stdin"))
+    }
+    else {
+        let file = File::open(path).map_err(|err| CommandError::failure(format!("Failed to read '{path}': {err}")))?;
+        (Box::new(file), format!("'{path}'"))
+    };
+
+    let _timer = rs_image::log::time("Decode");
+
+    let interval = Duration::from_millis(constants::write_to_console::PREVIEW_INTERVAL_MS);
+    let mut last_drawn = Instant::now();
+    let mut previewed = false;
+
+    let bitmap = Bitmap::decode_progressive(reader, DecodeOptions::default(), |image, _| {
+        if last_drawn.elapsed() < interval {
+            return;
+        }
+
+        print!("{}", constants::slideshow::CLEAR_SCREEN);
+        console::write_image_to_console(image.clone(), settings);
+
+        previewed = true;
+        last_drawn = Instant::now();
+    })
+    .map_err(|err| CommandError::failure(format!("Failed to decode {source}: {err}")))?;
+
+    if previewed {
+        print!("{}", constants::slideshow::CLEAR_SCREEN);
+    }
+
+    Ok(bitmap)
+}
+
+///
+/// Get how many rows to draw before pausing, from /rows, or else the height of the
+/// terminal if there is someone at it to continue. 0 never pauses.
+///
+fn page_rows(arguments: &ArgValues) -> Result<Option<usize>, CommandError> {
+    if let Some(rows) = arguments.get_usize(args::keys::ROWS).map_err(CommandError::usage)? {
+        return Ok(Some(rows).filter(|rows| *rows > 0));
+    }
+
+    //Reading the image from stdin leaves nowhere to read the key to continue from
+    let interactive = io::stdin().is_terminal()
+        && io::stdout().is_terminal()
+        && arguments.value(args::keys::FILE_PATH) != Some(args::values::STD_STREAM);
+
+    if !interactive {
+        return Ok(None);
+    }
+
+    Ok(termsize::get()
+        .map(|size| (size.rows as usize).saturating_sub(constants::write_to_console::PAGE_MARGIN).max(1)))
+}
+
+///
+/// Build the console drawing settings from the environment and arguments
+///
+pub fn settings(arguments: &ArgValues) -> Result<WriteImageToConsoleSettings, CommandError> {
+    let pixels = parse_charset(arguments.value(args::keys::CHARSET)
+        .unwrap_or(constants::write_to_console::PIXEL_STRINGS))?;
+
+    let truecolor_env = std::env::var(env::keys::TRUECOLOR_ENABLED).unwrap_or_else(|_| String::from(""));
+
+    let truecolor_enabled = !flag_set(arguments, args::keys::FORCE_DISABLE_TRUECOLOR)?
+        && [env::values::TRUECOLOR_ENABLED_24BIT, 
+            env::values::TRUECOLOR_ENABLED_TRUECOLOR
+        ].contains(&truecolor_env.as_str());
+
+    //Terminals supporting the 256-color palette advertise it in their terminfo name, i.e. xterm-256color
+    let term_env = std::env::var(env::keys::TERM).unwrap_or_else(|_| String::from(""));
+
+    let ansi256_enabled = term_env.contains(env::values::TERM_256COLOR)
+        || truecolor_env.contains(env::values::TERM_256COLOR);
+
+    let color_mode = if !color_enabled(arguments)? {
+        ColorMode::Monochrome
+    }
+    else if truecolor_enabled {
+        ColorMode::TrueColor
+    }
+    else if ansi256_enabled {
+        ColorMode::Ansi256
+    }
+    else {
+        ColorMode::Ansi16
+    };
+
+    //Get dithering mode from args
+    let dither_arg = arguments.value(args::keys::DITHER)
+        .map_or(String::from(""), |v| v.to_ascii_lowercase());
+
+    let dither = if dither_arg == *args::values::dither::ORDERED {
+        DitherMode::Ordered {
+            spread: match color_mode {
+                ColorMode::Ansi256 => constants::write_to_console::ORDERED_DITHER_SPREAD_256,
+                //Distance between the brightness levels of the charset
+                ColorMode::Monochrome => 255_f32 / pixels.len().max(1) as f32,
+                _ => constants::write_to_console::ORDERED_DITHER_SPREAD_16
+            }
+        }
+    }
+    else if dither_arg == *args::values::dither::FLOYD_STEINBERG {
+        DitherMode::FloydSteinberg
+    }
+    else if dither_arg.is_empty() || dither_arg == *args::values::dither::NONE {
+        DitherMode::None
+    }
+    else {
+        return Err(CommandError::usage(format!("Unrecognized dithering mode '{dither_arg}'.")));
+    };
+
+    //Get explicit drawing size from args
+    let width = arguments.get_usize(args::keys::WIDTH).map_err(CommandError::usage)?;
+    let height = arguments.get_usize(args::keys::HEIGHT).map_err(CommandError::usage)?;
+
+    //Get background for transparent pixels from args
+    let background = background(arguments)?;
+
+    Ok(WriteImageToConsoleSettings {
+        color_mode,
+        pixels,
+        dither,
+        width,
+        height,
+        background,
+        page_rows: None
+    })
+}
+
+///
+/// Get what to draw behind transparent pixels from the arguments
+///
+pub fn background(arguments: &ArgValues) -> Result<Background, CommandError> {
+    Ok(match arguments.value(args::keys::BACKGROUND) {
+        None => Background::None,
+        Some(bg) if bg.eq_ignore_ascii_case(args::values::background::CHECKER) => Background::Checker,
+        Some(bg) => Background::Color(color::ARGB::from_hex(bg).map_err(|err| CommandError::usage(err.to_string()))?)
+    })
+}
+
+///
+/// Check that a charset is a preset or can be split into strings
+///
+pub fn validate_charset(value: &str) -> Result<(), String> {
+    parse_charset(value).map(|_| ()).map_err(|err| err.message)
+}
+
+///
+/// Check that a background is either a hex color or a checkerboard
+///
+pub fn validate_background(value: &str) -> Result<(), String> {
+    if value.eq_ignore_ascii_case(args::values::background::CHECKER) {
+        return Ok(());
+    }
+
+    color::ARGB::from_hex(value).map(|_| ()).map_err(String::from)
+}
+
+///
+/// Get the strings used to draw pixels, from most to least opaque, from a preset name,
+/// a delimited list of strings, or a single string with one character per level
+///
+fn parse_charset(value: &str) -> Result<Vec<String>, CommandError> {
+    use constants::write_to_console::{charsets, PIXEL_STRINGS_DELIMITER};
+
+    let charset = match value.to_ascii_lowercase().as_str() {
+        args::values::charset::BLOCKS => charsets::BLOCKS,
+        args::values::charset::SHADES => charsets::SHADES,
+        args::values::charset::ASCII => charsets::ASCII,
+        args::values::charset::DOTS => charsets::DOTS,
+        _ => value
+    };
+
+    let pixels: Vec<String> = if charset.contains(PIXEL_STRINGS_DELIMITER) {
+        charset.split(PIXEL_STRINGS_DELIMITER)
+            .map(String::from)
+            .collect()
+    }
+    else {
+        charset.graphemes(true)
+            .map(String::from)
+            .collect()
+    };
+
+    if pixels.iter().any(|pixel| pixel.is_empty()) {
+        return Err(CommandError::usage(format!("Charset '{value}' contains an empty string.")));
+    }
+
+    //Each pixel is laid out assuming every grapheme takes exactly one cell
+    if let Some(grapheme) = pixels.iter()
+        .flat_map(|pixel| pixel.graphemes(true))
+        .find(|grapheme| grapheme.width() != 1) {
+        return Err(CommandError::usage(format!("Charset '{value}' contains '{grapheme}', which is not one column wide.")));
+    }
+
+    if pixels.iter().any(|pixel| pixel.graphemes(true).count() != pixels[0].graphemes(true).count()) {
+        return Err(CommandError::usage(format!("Strings in charset '{value}' must all be the same length.")));
+    }
+
+    Ok(pixels)
+}
\ No newline at end of file
@@ -1,19 +1,90 @@
-use std::{collections::HashMap, io::{stdout, Write}};
+use std::{collections::HashMap, io::{stdin, stdout, BufRead, IsTerminal, Write}};
 use colored::Colorize;
 use unicode_segmentation::UnicodeSegmentation;
 use rs_image::{color, image};
+use image::dither::DitherMode;
+use image::transform::ResizeFilter;
+
+use crate::constants;
+
+use crate::color_mode::ColorMode;
+use crate::palette::Palette256;
 
 pub struct WriteImageToConsoleSettings {
     ///
-    /// Whether to use truecolor when drawing
+    /// The set of colors to use when drawing
     /// to console.
     ///
-    pub use_truecolor: bool,
+    pub color_mode: ColorMode,
     ///
     /// Strings used to represent different pixel opacities
     /// in the console
     ///
     pub pixels: Vec<String>,
+    ///
+    /// How to dither the image when the console
+    /// can only display a limited palette
+    ///
+    pub dither: DitherMode,
+    ///
+    /// Width, in pixels, to scale the image to. If not
+    /// given, it is derived from the height or the size
+    /// of the terminal.
+    ///
+    pub width: Option<usize>,
+    ///
+    /// Height, in pixels, to scale the image to. If not
+    /// given, it is derived from the width.
+    ///
+    pub height: Option<usize>,
+    ///
+    /// What to draw behind transparent pixels
+    ///
+    pub background: Background,
+    ///
+    /// Rows to draw before waiting for Enter, like a pager, so
+    /// the top of a tall image isn't scrolled away. If not
+    /// given, the whole image is drawn at once.
+    ///
+    pub page_rows: Option<usize>,
+}
+
+///
+/// What to draw behind transparent pixels
+///
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Background {
+    ///
+    /// Leave transparent pixels blank
+    ///
+    #[default]
+    None,
+    ///
+    /// Composite the image over a solid color
+    ///
+    Color(color::ARGB),
+    ///
+    /// Composite the image over a checkerboard
+    ///
+    Checker
+}
+
+impl Background {
+    ///
+    /// Composite the image over the background, drawing a checkerboard
+    /// with cells of the given size
+    ///
+    pub fn flatten(&self, img: image::Image, cell: usize) -> image::Image {
+        match self {
+            Self::None => img,
+            Self::Color(background) => img.flatten(*background),
+            Self::Checker => img.flatten_checkerboard(
+                cell,
+                color::ARGB::from_u32(constants::write_to_console::CHECKER_LIGHT, false),
+                color::ARGB::from_u32(constants::write_to_console::CHECKER_DARK, false)
+            )
+        }
+    }
 }
 
 impl WriteImageToConsoleSettings {
@@ -44,7 +115,100 @@ impl WriteImageToConsoleSettings {
     }
 }
 
-pub fn write_image_to_console(img: image::Image, settings: &WriteImageToConsoleSettings) {
+///
+/// The 16 basic console colors, and their approximate RGB values
+///
+const BASIC_COLORS: [(u32, colored::Color); 16] = [
+    (0x000000, colored::Color::Black), //Black
+    (0x000080, colored::Color::Blue), //Dark blue
+    (0x008000, colored::Color::Green), //Dark green
+    (0x008080, colored::Color::Cyan), //Cark cyan
+    (0x800000, colored::Color::Red), //Dark red
+    (0x800080, colored::Color::Magenta), //Dark magenta
+    (0x808000, colored::Color::Yellow), //Dark yellow
+    (0x808080, colored::Color::White), //Dark grey
+    (0x0000FF, colored::Color::BrightBlue), //Blue
+    (0x00FF00, colored::Color::BrightGreen), //Green
+    (0x00FFFF, colored::Color::BrightCyan), //Cyan
+    (0xFF0000, colored::Color::BrightRed), //Red
+    (0xFF00FF, colored::Color::BrightMagenta), //Magenta
+    (0xFFFF00, colored::Color::BrightYellow), //Yellow
+    (0xC0C0C0, colored::Color::BrightBlack), //Grey
+    (0xFFFFFF, colored::Color::BrightWhite) //White
+];
+
+///
+/// A color which can be applied to text in the console
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ConsoleColor {
+    ///
+    /// A basic or truecolor color, supported by colored
+    ///
+    Color(colored::Color),
+    ///
+    /// An index into the xterm 256-color palette
+    ///
+    Indexed(u8)
+}
+
+///
+/// Get the size, in pixels, the image should be drawn at, so that it fits within
+/// the given number of terminal columns and isn't stretched by the shape of the
+/// terminal's cells. Explicit sizes from the settings take precedence.
+///
+fn target_size(img: &image::Image, settings: &WriteImageToConsoleSettings, columns: Option<usize>) -> (usize, usize) {
+    let pixel_len = settings.pixel_width().max(1);
+
+    //How much taller than it is wide a pixel will be drawn, in screen space
+    let pixel_aspect = 1_f32 / (pixel_len as f32 * constants::write_to_console::CELL_ASPECT_RATIO);
+
+    //Rows needed for the given number of columns, keeping the image's proportions
+    let height_for = |width: usize| -> usize {
+        if img.width() == 0 {
+            0
+        }
+        else {
+            ((width as f32 * img.height() as f32) / (img.width() as f32 * pixel_aspect)).round().max(1_f32) as usize
+        }
+    };
+
+    //Columns needed for the given number of rows, keeping the image's proportions
+    let width_for = |height: usize| -> usize {
+        if img.height() == 0 {
+            0
+        }
+        else {
+            ((height as f32 * img.width() as f32 * pixel_aspect) / img.height() as f32).round().max(1_f32) as usize
+        }
+    };
+
+    match (settings.width, settings.height) {
+        (Some(width), Some(height)) => (width, height),
+        (Some(width), None) => (width, height_for(width)),
+        (None, Some(height)) => (width_for(height), height),
+        (None, None) => {
+            //Only ever shrink to fit; leave a column free so the cursor doesn't wrap
+            let max_width = columns.map_or(img.width(), |cols| cols.saturating_sub(1) / pixel_len);
+            let width = img.width().min(max_width);
+            (width, height_for(width))
+        }
+    }
+}
+
+///
+/// Get the size, in pixels, the image will be drawn at in the current terminal
+///
+pub fn fit_size(img: &image::Image, settings: &WriteImageToConsoleSettings) -> (usize, usize) {
+    target_size(img, settings, termsize::get().map(|tsize| tsize.cols as usize))
+}
+
+///
+/// Draw the image to the console, returning the number of lines written
+///
+pub fn write_image_to_console(img: image::Image, settings: &WriteImageToConsoleSettings) -> usize {
+    let _timer = rs_image::log::time("Render");
+
     let _ = colored::control::set_virtual_terminal(true);
 
     let terminal_size = termsize::get();
@@ -53,13 +217,65 @@ pub fn write_image_to_console(img: image::Image, settings: &WriteImageToConsoleS
 
     let mut stdoutlock = stdout().lock();
 
+    //Scale the image to fit the terminal
+    let (width, height) = target_size(&img, settings, terminal_size.as_ref().map(|tsize| tsize.cols as usize));
+
+    let img = if (width, height) == (img.width(), img.height()) {
+        img
+    }
+    else {
+        let filter = if width < img.width() {
+            ResizeFilter::Box
+        }
+        else {
+            ResizeFilter::Bilinear
+        };
+
+        img.resize(width, height, filter)
+    };
+
+    //Only build the 256-color palette if it's going to be used
+    let palette = if settings.color_mode == ColorMode::Ansi256 {
+        Some(Palette256::new())
+    }
+    else {
+        None
+    };
+
+    //Fill in transparent pixels
+    let img = settings.background.flatten(img, constants::write_to_console::CHECKER_CELL_SIZE);
+
+    //Spread quantization error across the character grid so gradients don't band
+    let img = match (settings.color_mode, settings.dither) {
+        (ColorMode::TrueColor, _) | (_, DitherMode::None) => img,
+        (_, dither) => img.dither(dither, |c| quantize(c, settings, palette.as_ref()))
+    };
+
+    //Nearest-color search is relatively expensive, so remember colors that have already been mapped
+    let mut coloring_cache: HashMap<color::ARGB, Option<ConsoleColor>> = HashMap::new();
+
     //Write some top padding
     writeln!(stdoutlock).unwrap();
 
+    let mut rows_drawn = 0_usize;
+
     //Outer loops is rows
-    for row in &img.iter() {
-        //Move to the next line
-        writeln!(stdoutlock).unwrap();
+    for (index, row) in img.iter().enumerate() {
+        let page_break = settings.page_rows.is_some_and(|page_rows| index > 0 && index % page_rows == 0);
+
+        if page_break {
+            match wait_for_next_page(&mut stdoutlock) {
+                //The prompt was cleared, leaving the cursor where the next row goes
+                PageStep::Continue => (),
+                PageStep::Quit => break
+            }
+        }
+        else {
+            //Move to the next line
+            writeln!(stdoutlock).unwrap();
+        }
+
+        rows_drawn += 1;
 
         for (column, color) in row.iter().enumerate() {
 
@@ -73,21 +289,67 @@ pub fn write_image_to_console(img: image::Image, settings: &WriteImageToConsoleS
             let pixel_string = get_pixel_string_from_opacity(*color, settings);
 
             //Get console color from given color
-            let coloring = get_coloring(*color, settings);
+            let coloring = *coloring_cache.entry(*color)
+                .or_insert_with(|| get_coloring(*color, settings, palette.as_ref()));
 
-            //Apply console color to pixel string
-            let colored_string = if let Some(console_color) = coloring {
-                colored::ColoredString::from(&pixel_string[..])
-                    .color(console_color)
+            //Apply console color to pixel string, and print it
+            match coloring {
+                Some(ConsoleColor::Color(console_color)) => {
+                    let colored_string = colored::ColoredString::from(&pixel_string[..])
+                        .color(console_color);
+                    write!(stdoutlock, "{colored_string}").unwrap();
+                },
+                Some(ConsoleColor::Indexed(index)) => {
+                    write!(stdoutlock, "\x1b[38;5;{index}m{pixel_string}\x1b[0m").unwrap();
+                },
+                None => {
+                    write!(stdoutlock, "{pixel_string}").unwrap();
+                }
             }
-            else {
-                colored::ColoredString::from(&pixel_string[..])
-            };
-
-            //Print colored string
-            write!(stdoutlock, "{colored_string}").unwrap();
         }
     }
+
+    //Top padding, plus one line per row
+    rows_drawn + 1
+}
+
+///
+/// What to do after a page of the image has been drawn
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PageStep {
+    Continue,
+    Quit
+}
+
+///
+/// Show the pager prompt below the rows drawn so far, and wait for a line on stdin.
+/// Without a terminal in raw mode, keys only arrive once Enter is pressed.
+///
+fn wait_for_next_page(out: &mut impl Write) -> PageStep {
+    use constants::write_to_console::{PAGE_PROMPT, PAGE_QUIT};
+
+    //Reset colors, so the prompt isn't drawn in the color of the last pixel
+    let _ = write!(out, "\x1b[0m\n{PAGE_PROMPT}");
+    let _ = out.flush();
+
+    let mut line = String::new();
+
+    //Without input to wait for, draw the rest at once
+    let step = match stdin().lock().read_line(&mut line) {
+        Ok(0) | Err(_) => PageStep::Continue,
+        Ok(_) if line.trim().eq_ignore_ascii_case(PAGE_QUIT) => PageStep::Quit,
+        Ok(_) => PageStep::Continue
+    };
+
+    //Enter typed at a terminal moved the cursor below the prompt, so go back up to clear it
+    if stdin().is_terminal() {
+        let _ = write!(out, "\x1b[1A");
+    }
+
+    let _ = write!(out, "\x1b[2K\r");
+
+    step
 }
 
 fn get_pixel_string_from_opacity(color: color::ARGB, settings: &WriteImageToConsoleSettings) -> String {
@@ -97,13 +359,20 @@ fn get_pixel_string_from_opacity(color: color::ARGB, settings: &WriteImageToCons
         String::from("")
     } 
     else {
-        let mut pixel_string_part = if color.alpha == 0 {
+        //Without color, brightness decides how dense the pixel is drawn
+        let alpha_ratio = if settings.color_mode == ColorMode::Monochrome {
+            (color.luminance() as f32 / 255_f32) * (color.alpha as f32 / 255_f32)
+        }
+        else {
+            (color.alpha as f32) / 255_f32
+        };
+
+        let mut pixel_string_part = if alpha_ratio <= 0_f32 {
             String::from(" ")
         } 
         else {
             let mut pixel_string_part = None;
 
-            let alpha_ratio = (color.alpha as f32) / 255_f32;
             let len = settings.pixels.len() as f32;
 
             for i in 1..=settings.pixels.len() {
@@ -128,47 +397,60 @@ fn get_pixel_string_from_opacity(color: color::ARGB, settings: &WriteImageToCons
     }
 }
 
-fn get_coloring(color: color::ARGB, settings: &WriteImageToConsoleSettings) -> Option<colored::Color> {
-    if color.alpha == 0 {
+fn get_coloring(color: color::ARGB, settings: &WriteImageToConsoleSettings, palette: Option<&Palette256>) -> Option<ConsoleColor> {
+    if color.alpha == 0 || settings.color_mode == ColorMode::Monochrome {
         None
     }
-    else if settings.use_truecolor {
-        Some(colored::Color::TrueColor { r: color.red, g: color.green, b: color.blue })
+    else if settings.color_mode == ColorMode::TrueColor {
+        Some(ConsoleColor::Color(colored::Color::TrueColor { r: color.red, g: color.green, b: color.blue }))
+    }
+    else if let (ColorMode::Ansi256, Some(palette)) = (settings.color_mode, palette) {
+        Some(ConsoleColor::Indexed(palette.nearest(color)))
     }
     else {
-        let default_color = 0x00000000;
-        let simple_colors: HashMap<u32, colored::Color> = HashMap::from([
-            (0x000000, colored::Color::Black), //Black
-            (0x000080, colored::Color::Blue), //Dark blue
-            (0x008000, colored::Color::Green), //Dark green
-            (0x008080, colored::Color::Cyan), //Cark cyan
-            (0x800000, colored::Color::Red), //Dark red
-            (0x800080, colored::Color::Magenta), //Dark magenta
-            (0x808000, colored::Color::Yellow), //Dark yellow
-            (0x808080, colored::Color::White), //Dark grey
-            (0x0000FF, colored::Color::BrightBlue), //Blue
-            (0x00FF00, colored::Color::BrightGreen), //Green
-            (0x00FFFF, colored::Color::BrightCyan), //Cyan
-            (0xFF0000, colored::Color::BrightRed), //Red
-            (0xFF00FF, colored::Color::BrightMagenta), //Magenta
-            (0xFFFF00, colored::Color::BrightYellow), //Yellow
-            (0xC0C0C0, colored::Color::BrightBlack), //Grey
-            (0xFFFFFF, colored::Color::BrightWhite) //White
-        ]);
-
-        let (hex, _) = simple_colors.keys()
-            .map(|k| (k, color::ARGB::from_u32(*k, false)
-                .with_alpha(color.alpha)
-                .distance_euclidean(&color)))
-            .reduce(|(hex_a, distance_a), (hex_b, distance_b)| {
-                if distance_a <= distance_b {
-                    (hex_a, distance_a)
-                }
-                else {
-                    (hex_b, distance_b)
-                }
-            }).unwrap_or((&default_color, 0_f32));
+        let (_, basic_color) = nearest_basic_color(color);
+        Some(ConsoleColor::Color(basic_color))
+    }
+}
 
-        simple_colors.get(hex).copied()
+///
+/// Get the basic console color closest to the given color, along
+/// with its (approximate) RGB value
+///
+fn nearest_basic_color(color: color::ARGB) -> (u32, colored::Color) {
+    BASIC_COLORS.iter()
+        .map(|(hex, basic_color)| ((*hex, *basic_color), color::ARGB::from_u32(*hex, false)
+            .with_alpha(color.alpha)
+            .distance_euclidean(&color)))
+        .reduce(|(basic_a, distance_a), (basic_b, distance_b)| {
+            if distance_a <= distance_b {
+                (basic_a, distance_a)
+            }
+            else {
+                (basic_b, distance_b)
+            }
+        })
+        .map_or((0x000000, colored::Color::Black), |(basic, _)| basic)
+}
+
+///
+/// Map a color to the color the console will actually display for it,
+/// so dithering can account for the difference
+///
+fn quantize(color: color::ARGB, settings: &WriteImageToConsoleSettings, palette: Option<&Palette256>) -> color::ARGB {
+    match (settings.color_mode, palette) {
+        (ColorMode::TrueColor, _) => color,
+        //Snap to the brightness levels the charset can show
+        (ColorMode::Monochrome, _) => {
+            let levels = settings.pixels.len().max(1) as f32;
+            let luma = ((color.luminance() as f32 / 255_f32 * levels).round() / levels * 255_f32).round() as u8;
+            color::ARGB { alpha: color.alpha, red: luma, green: luma, blue: luma }
+        },
+        (ColorMode::Ansi256, Some(palette)) => palette.color(palette.nearest(color))
+            .with_alpha(color.alpha),
+        _ => {
+            let (hex, _) = nearest_basic_color(color);
+            color::ARGB::from_u32(hex, false).with_alpha(color.alpha)
+        }
     }
 }
\ No newline at end of file
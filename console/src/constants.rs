@@ -20,8 +20,12 @@ pub mod args {
         ///
         /// Command line argument key to print help docs.
         /// 
-        //pub const HELP: &str = "help";
+        pub const HELP: &str = "help";
 
+        ///
+        /// Command line argument key for how to output the image.
+        /// Superseded by subcommands, but still accepted.
+        /// 
         pub const OUTPUT_TYPE: &str = "output";
 
         ///
@@ -40,21 +44,474 @@ pub mod args {
         /// Command line argument key for output file path
         /// 
         pub const OUTPUT_PATH: &str = "out_path";
+
+        ///
+        /// Command line argument key for the directory to write
+        /// files to when no output file path is given
+        /// 
+        pub const OUTPUT_DIRECTORY: &str = "out_dir";
+
+        ///
+        /// Command line argument key for how to dither the image
+        /// when the console only supports a limited palette
+        /// 
+        pub const DITHER: &str = "dither";
+
+        ///
+        /// Command line argument key for the width, in pixels,
+        /// to draw the image at
+        /// 
+        pub const WIDTH: &str = "width";
+
+        ///
+        /// Command line argument key for the height, in pixels,
+        /// to draw the image at
+        /// 
+        pub const HEIGHT: &str = "height";
+
+        ///
+        /// Command line argument key for the number of rows
+        /// to draw before pausing, like a pager
+        /// 
+        pub const ROWS: &str = "rows";
+
+        ///
+        /// Command line argument key for what to draw behind
+        /// transparent pixels; either a hex color or a checkerboard
+        /// 
+        pub const BACKGROUND: &str = "bg";
+
+        ///
+        /// Longer alias of the background argument key
+        /// 
+        pub const BACKGROUND_LONG: &str = "background";
+
+        ///
+        /// Command line argument key for the format to
+        /// convert an image to
+        /// 
+        pub const FORMAT: &str = "format";
+
+        ///
+        /// Command line argument key for the bit depth to
+        /// convert an image to
+        /// 
+        pub const BIT_DEPTH: &str = "depth";
+
+        ///
+        /// Command line argument key for the compression to
+        /// use when converting an image
+        /// 
+        pub const COMPRESSION: &str = "compress";
+
+        ///
+        /// Command line argument key for the size, as WxH,
+        /// to scale the image to before output
+        /// 
+        pub const RESIZE: &str = "resize";
+
+        ///
+        /// Command line argument key for the region, as X,Y,WxH,
+        /// to crop the image to before output
+        /// 
+        pub const CROP: &str = "crop";
+
+        ///
+        /// Command line argument key for the angle to rotate
+        /// the image clockwise by before output
+        /// 
+        pub const ROTATE: &str = "rotate";
+
+        ///
+        /// Command line argument key for the axis to mirror
+        /// the image across before output
+        /// 
+        pub const FLIP: &str = "flip";
+
+        ///
+        /// Command line argument key for a comma separated list of
+        /// filters to apply to the image before output
+        /// 
+        pub const FILTER: &str = "filter";
+
+        ///
+        /// Command line argument key for the path of a .cube
+        /// color lookup table to grade the image with before output
+        /// 
+        pub const LUT: &str = "lut";
+
+        ///
+        /// Command line argument key for the path of a JSON
+        /// recipe of edits to apply, after every other transform
+        /// 
+        pub const RECIPE: &str = "recipe";
+
+        ///
+        /// Command line argument key for the delay, in milliseconds,
+        /// between frames of an animation
+        /// 
+        pub const DELAY: &str = "delay";
+
+        ///
+        /// Command line argument key for how many times to play
+        /// an animation; 0 loops forever
+        /// 
+        pub const LOOP: &str = "loop";
+
+        ///
+        /// Command line argument key for how long, in seconds,
+        /// to show each image of a slideshow
+        /// 
+        pub const INTERVAL: &str = "interval";
+
+        ///
+        /// Command line argument key for the test pattern to generate
+        /// 
+        pub const PATTERN: &str = "pattern";
+
+        ///
+        /// Command line argument key for the size, as WxH,
+        /// of a generated image
+        /// 
+        pub const SIZE: &str = "size";
+
+        ///
+        /// Command line argument key for a comma separated list
+        /// of the hex colors of a generated image
+        /// 
+        pub const COLORS: &str = "colors";
+
+        ///
+        /// Command line argument key for the size, in pixels,
+        /// of each square of a generated checkerboard
+        /// 
+        pub const CELL: &str = "cell";
+
+        ///
+        /// Command line argument key for the seed of generated noise
+        /// 
+        pub const SEED: &str = "seed";
+
+        ///
+        /// Command line argument key for generating a gradient
+        /// from top to bottom instead of left to right
+        /// 
+        pub const VERTICAL: &str = "vertical";
+
+        ///
+        /// Command line argument key for the position, as X,Y,
+        /// of the pixel to pick
+        /// 
+        pub const AT: &str = "at";
+
+        ///
+        /// Command line argument key for the number of columns
+        /// in a montage
+        /// 
+        pub const COLUMNS: &str = "columns";
+
+        ///
+        /// Command line argument key for the size, in pixels,
+        /// of each thumbnail in a montage
+        /// 
+        pub const THUMBNAIL_SIZE: &str = "thumb";
+
+        ///
+        /// Command line argument key for how many files
+        /// to convert at once
+        /// 
+        pub const JOBS: &str = "jobs";
+
+        ///
+        /// Command line argument key for scaling histogram
+        /// bars logarithmically
+        /// 
+        pub const LOG_SCALE: &str = "log";
+
+        ///
+        /// Command line argument key for decoding the pixels
+        /// to report statistics of each channel
+        /// 
+        pub const STATS: &str = "stats";
+
+        ///
+        /// Command line argument key for the number of bars
+        /// in a histogram
+        /// 
+        pub const BINS: &str = "bins";
+
+        ///
+        /// Command line argument key for the path of the image
+        /// to compare against
+        /// 
+        pub const OTHER_PATH: &str = "other";
+
+        ///
+        /// Command line argument key for the percentage of pixels
+        /// allowed to differ before a comparison fails
+        /// 
+        pub const THRESHOLD: &str = "threshold";
+
+        ///
+        /// Command line argument key for how much a channel may
+        /// differ before a pixel counts as different
+        /// 
+        pub const TOLERANCE: &str = "tolerance";
+
+        ///
+        /// Command line argument key for what to measure
+        /// when comparing two images
+        /// 
+        pub const MODE: &str = "mode";
+
+        ///
+        /// Command line argument key for drawing the highlighted
+        /// differences in the console
+        /// 
+        pub const SHOW: &str = "show";
+
+        ///
+        /// Command line argument key for printing a plain hexdump
+        /// instead of annotated bytes
+        /// 
+        pub const RAW: &str = "raw";
+
+        ///
+        /// Command line argument key for showing byte offsets
+        /// next to annotated bytes
+        /// 
+        pub const OFFSETS: &str = "offsets";
+
+        ///
+        /// Command line argument key for the number of bytes
+        /// to print per line
+        /// 
+        pub const BYTES_PER_LINE: &str = "bytes_per_line";
+
+        ///
+        /// Command line argument key for a comma separated list
+        /// of the sections of a file to print
+        /// 
+        pub const SECTION: &str = "section";
+
+        ///
+        /// Command line argument key for logging details,
+        /// such as how long each stage took
+        /// 
+        pub const VERBOSE: &str = "verbose";
+
+        ///
+        /// Command line argument key for only logging errors
+        /// 
+        pub const QUIET: &str = "quiet";
+
+        ///
+        /// Command line argument key for the characters used
+        /// to draw pixels; either a preset or custom strings
+        /// 
+        pub const CHARSET: &str = "charset";
+
+        ///
+        /// Command line argument key for when to use color
+        /// 
+        pub const COLOR: &str = "color";
+
+        ///
+        /// Command line argument key for a config file to read
+        /// default arguments from
+        /// 
+        pub const CONFIG: &str = "config";
+
+        ///
+        /// Command line argument key indicating that an image should
+        /// be kept in the orientation it was stored in, rather than
+        /// turned upright by the orientation in its metadata
+        /// 
+        pub const RAW_ORIENTATION: &str = "raw_orientation";
     }
 
     ///
     /// Command line argument values
     /// 
     pub mod values {
+        ///
+        /// Path value meaning stdin when reading, or stdout when writing
+        /// 
+        pub const STD_STREAM: &str = "-";
+
+        ///
+        /// Separator between paths, where an argument accepts several
+        /// 
+        pub const PATH_SEPARATOR: char = ',';
+
         pub mod output_type {
             pub const FILE: &str = "file";
             pub const OUTPUT: &str = "console";
             pub const DRAW: &str = "draw";
             pub const HEX: &str = "hex";
+            pub const PLAY: &str = "play";
+            pub const SLIDESHOW: &str = "slideshow";
+        }
+
+        pub mod pattern {
+            pub const SOLID: &str = "solid";
+            pub const GRADIENT: &str = "gradient";
+            pub const BARS: &str = "bars";
+            pub const CHECKERBOARD: &str = "checkerboard";
+            pub const NOISE: &str = "noise";
+        }
+
+        pub mod compare_mode {
+            pub const EXACT: &str = "exact";
+            pub const COUNT: &str = "count";
+            pub const PSNR: &str = "psnr";
+            pub const SSIM: &str = "ssim";
+            pub const DELTA_E: &str = "delta-e";
+        }
+
+        pub mod dither {
+            pub const NONE: &str = "none";
+            pub const ORDERED: &str = "ordered";
+            pub const FLOYD_STEINBERG: &str = "floyd-steinberg";
+        }
+
+        pub mod background {
+            pub const CHECKER: &str = "checker";
+        }
+
+        pub mod report_format {
+            pub const TEXT: &str = "text";
+            pub const JSON: &str = "json";
+        }
+
+        pub mod bit_depth {
+            pub const ALL: [u16; 5] = [1, 4, 8, 24, 32];
+        }
+
+        pub mod compression {
+            pub const NONE: &str = "none";
+            pub const RLE: &str = "rle";
+        }
+
+        pub mod rotate {
+            pub const DEGREES_90: &str = "90";
+            pub const DEGREES_180: &str = "180";
+            pub const DEGREES_270: &str = "270";
+        }
+
+        pub mod flip {
+            pub const HORIZONTAL: &str = "h";
+            pub const VERTICAL: &str = "v";
+        }
+
+        pub mod color {
+            pub const AUTO: &str = "auto";
+            pub const ALWAYS: &str = "always";
+            pub const NEVER: &str = "never";
+        }
+
+        pub mod charset {
+            pub const BLOCKS: &str = "blocks";
+            pub const SHADES: &str = "shades";
+            pub const ASCII: &str = "ascii";
+            pub const DOTS: &str = "dots";
+        }
+
+        pub mod section {
+            pub const HEADER: &str = "header";
+            pub const PALETTE: &str = "palette";
+            pub const PIXELS: &str = "pixels";
+
+            ///
+            /// Separator between sections, i.e. header,palette
+            /// 
+            pub const SEPARATOR: char = ',';
+        }
+
+        pub mod filter {
+            pub const GRAYSCALE: &str = "grayscale";
+            pub const INVERT: &str = "invert";
+            pub const BLUR: &str = "blur";
+            pub const BRIGHTNESS: &str = "brightness";
+            pub const AUTO_CONTRAST: &str = "auto-contrast";
+            pub const AUTO_LEVELS: &str = "auto-levels";
+            pub const HATCH: &str = "hatch";
+            pub const STIPPLE: &str = "stipple";
+            pub const KUWAHARA: &str = "kuwahara";
+            pub const ANISOTROPIC_KUWAHARA: &str = "anisotropic-kuwahara";
+
+            ///
+            /// Separator between filters, i.e. grayscale,blur=2
+            /// 
+            pub const SEPARATOR: char = ',';
+
+            ///
+            /// Separator between a filter and its parameter, i.e. blur=2
+            /// 
+            pub const PARAMETER_SEPARATOR: char = '=';
         }
+
+        ///
+        /// Separator between width and height in a size, i.e. 640x480
+        /// 
+        pub const SIZE_SEPARATOR: char = 'x';
+
+        ///
+        /// Separator between the coordinates and size of a region, i.e. 10,20,640x480
+        /// 
+        pub const REGION_SEPARATOR: char = ',';
     }
 }
 
+///
+/// Subcommands
+/// 
+pub mod commands {
+    pub const VIEW: &str = "view";
+    pub const CONVERT: &str = "convert";
+    pub const HEX: &str = "hex";
+    pub const INFO: &str = "info";
+    pub const PLAY: &str = "play";
+    pub const SLIDESHOW: &str = "slideshow";
+    pub const MONTAGE: &str = "montage";
+    pub const HISTOGRAM: &str = "histogram";
+    pub const DIFF: &str = "diff";
+    pub const FORMATS: &str = "formats";
+    pub const GENERATE: &str = "generate";
+    pub const PICK: &str = "pick";
+    pub const STRIP: &str = "strip";
+    pub const VALIDATE: &str = "validate";
+    pub const COMPARE: &str = "compare";
+    pub const HELP: &str = "help";
+}
+
+///
+/// Process exit codes
+/// 
+pub mod exit_codes {
+    ///
+    /// The command failed
+    /// 
+    pub const FAILURE: u8 = 1;
+
+    ///
+    /// The command was invoked incorrectly
+    /// 
+    pub const USAGE: u8 = 2;
+
+    ///
+    /// The command succeeded, but the images compared
+    /// differed by more than the allowed threshold
+    /// 
+    pub const DIFFERENT: u8 = 3;
+
+    ///
+    /// The command succeeded, but at least one of the
+    /// images checked doesn't conform to its format
+    /// 
+    pub const INVALID: u8 = 4;
+}
+
 ///
 /// Environment variables
 /// 
@@ -68,6 +525,24 @@ pub mod env {
         /// truecolor output
         /// 
         pub const TRUECOLOR_ENABLED: &str = "COLORTERM";
+
+        ///
+        /// Environment variable for the terminfo name of the
+        /// console
+        /// 
+        pub const TERM: &str = "TERM";
+
+        ///
+        /// Environment variable that disables color when
+        /// set to anything but an empty string (https://no-color.org)
+        /// 
+        pub const NO_COLOR: &str = "NO_COLOR";
+
+        ///
+        /// Environment variable for a config file to read default
+        /// arguments from, when none is given on the command line
+        /// 
+        pub const CONFIG: &str = "RS_IMAGE_CONFIG";
     }
 
     ///
@@ -83,6 +558,12 @@ pub mod env {
         /// Value for COLORTERM env variable indicating truecolor is enabled
         /// 
         pub const TRUECOLOR_ENABLED_24BIT: &str = "24bit";
+
+        ///
+        /// Substring of the TERM env variable indicating the console
+        /// supports the xterm 256-color palette
+        /// 
+        pub const TERM_256COLOR: &str = "256color";
     }
 }
 
@@ -91,14 +572,265 @@ pub mod env {
 /// 
 pub mod write_to_console {
     ///
-    /// Strings to use to represent a pixel in the console
+    /// Strings to use to represent a pixel in the console,
+    /// from most to least opaque
+    /// 
+    pub const PIXEL_STRINGS: &str = charsets::BLOCKS;
+
+    ///
+    /// Preset strings to represent pixels, from most to least opaque
     /// 
-    pub const PIXEL_STRINGS: &str = "██,█▓,▓▓,▓▒,▒▒,▒░,░░,░ ";
+    pub mod charsets {
+        pub const BLOCKS: &str = "██,█▓,▓▓,▓▒,▒▒,▒░,░░,░ ";
+        pub const SHADES: &str = "██,▓▓,▒▒,░░";
+        pub const ASCII: &str = "@@,%%,##,**,++,==,--,::,..";
+        pub const DOTS: &str = "⣿⣿,⣾⣷,⣶⣶,⣴⣦,⣤⣤,⣠⣄,⣀⣀,⢀⡀";
+    }
 
     ///
     /// Delimiter between strings in PIXEL_STRINGS
     /// 
     pub const PIXEL_STRINGS_DELIMITER: &str = ",";
+
+    ///
+    /// Range of the ordered dithering threshold when drawing with the
+    /// 16 basic colors, roughly the distance between them
+    /// 
+    pub const ORDERED_DITHER_SPREAD_16: f32 = 128_f32;
+
+    ///
+    /// Range of the ordered dithering threshold when drawing with the
+    /// 256-color palette, roughly the distance between levels of its color cube
+    /// 
+    pub const ORDERED_DITHER_SPREAD_256: f32 = 40_f32;
+
+    ///
+    /// Approximate width of a terminal cell relative to its height
+    /// 
+    pub const CELL_ASPECT_RATIO: f32 = 0.5;
+
+    ///
+    /// Size, in pixels, of each cell of the checkerboard drawn
+    /// behind transparent pixels
+    /// 
+    pub const CHECKER_CELL_SIZE: usize = 2;
+
+    ///
+    /// Light color of the checkerboard drawn behind transparent pixels
+    /// 
+    pub const CHECKER_LIGHT: u32 = 0xFFCCCCCC;
+
+    ///
+    /// Dark color of the checkerboard drawn behind transparent pixels
+    /// 
+    pub const CHECKER_DARK: u32 = 0xFF999999;
+
+    ///
+    /// Shown below each page of a tall image, until Enter is pressed
+    /// 
+    pub const PAGE_PROMPT: &str = "-- More: press Enter to continue, or q then Enter to stop --";
+
+    ///
+    /// Line typed at the page prompt to stop drawing the image
+    /// 
+    pub const PAGE_QUIT: &str = "q";
+
+    ///
+    /// Lines of the terminal kept free of the image on each page,
+    /// for the top padding and the prompt
+    /// 
+    pub const PAGE_MARGIN: usize = 2;
+
+    ///
+    /// Milliseconds between redraws of the partly decoded image while
+    /// viewing, and before the first, so fast reads draw nothing early
+    /// 
+    pub const PREVIEW_INTERVAL_MS: u64 = 250;
+}
+
+pub mod convert {
+    ///
+    /// Size, in pixels, of each cell of the checkerboard
+    /// transparent pixels are flattened onto when converting
+    /// 
+    pub const CHECKER_CELL_SIZE: usize = 8;
+}
+
+pub mod montage {
+    ///
+    /// Size, in pixels, each image is shrunk to fit within
+    /// when not given
+    /// 
+    pub const DEFAULT_THUMBNAIL_SIZE: usize = 64;
+
+    ///
+    /// The largest size, in pixels, thumbnails may be given
+    /// 
+    pub const MAX_THUMBNAIL_SIZE: usize = 4096;
+
+    ///
+    /// Space, in pixels, around each thumbnail and label
+    /// 
+    pub const PADDING: usize = 4;
+
+    ///
+    /// Scale the built-in font is drawn at for labels
+    /// 
+    pub const LABEL_SCALE: usize = 1;
+
+    ///
+    /// Color behind the thumbnails
+    /// 
+    pub const BACKGROUND: u32 = 0xFF202020;
+
+    ///
+    /// Color of the labels under each thumbnail
+    /// 
+    pub const LABEL_COLOR: u32 = 0xFFFFFFFF;
+}
+
+pub mod histogram {
+    ///
+    /// Number of bars in each chart when not given
+    /// 
+    pub const DEFAULT_BINS: usize = 64;
+
+    ///
+    /// Number of lines in each chart when not given
+    /// 
+    pub const DEFAULT_HEIGHT: usize = 8;
+
+    ///
+    /// Characters used to draw the top of a bar, from 1/8 to 8/8 full
+    /// 
+    pub const BAR_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+}
+
+pub mod hex {
+    ///
+    /// Bytes per line of a plain hexdump, when not given
+    /// 
+    pub const DEFAULT_RAW_BYTES_PER_LINE: usize = 16;
+
+    ///
+    /// The most bytes per line; more are clamped to this, as each
+    /// line is padded out to the width of a full one
+    /// 
+    pub const MAX_BYTES_PER_LINE: usize = 1024;
+}
+
+pub mod diff {
+    ///
+    /// Color differing pixels are drawn in
+    /// 
+    pub const HIGHLIGHT: u32 = 0xFFFF0000;
+}
+
+pub mod compare {
+    ///
+    /// Lowest PSNR, in dB, at which images pass a
+    /// comparison, when no threshold is given
+    /// 
+    pub const DEFAULT_PSNR: f64 = 40_f64;
+
+    ///
+    /// Lowest SSIM at which images pass a comparison,
+    /// when no threshold is given
+    /// 
+    pub const DEFAULT_SSIM: f64 = 0.99;
+
+    ///
+    /// Highest mean ΔE at which images pass a comparison,
+    /// when no threshold is given. Around 2.3 is just noticeable.
+    /// 
+    pub const DEFAULT_DELTA_E: f64 = 2.3;
+
+    ///
+    /// Width and height of the regions searched for the
+    /// largest color difference
+    /// 
+    pub const DELTA_E_REGION_SIZE: usize = 16;
+}
+
+pub mod play {
+    ///
+    /// Delay, in milliseconds, between frames when
+    /// the source doesn't specify one
+    /// 
+    pub const DEFAULT_DELAY_MS: u64 = 100;
+
+    ///
+    /// Longest time, in milliseconds, to sleep before
+    /// checking whether playback was interrupted
+    /// 
+    pub const INTERRUPT_POLL_MS: u64 = 20;
+
+    ///
+    /// Escape sequence to hide the cursor while playing
+    /// 
+    pub const HIDE_CURSOR: &str = "\x1b[?25l";
+
+    ///
+    /// Escape sequence to show the cursor again, and
+    /// reset any colors left over from an interrupted frame
+    /// 
+    pub const RESTORE_CURSOR: &str = "\x1b[0m\x1b[?25h";
+}
+
+pub mod generate {
+    ///
+    /// Size of a generated image, when none is given
+    /// 
+    pub const DEFAULT_SIZE: &str = "256x256";
+
+    ///
+    /// Bit depth of a generated image, when none is given
+    /// 
+    pub const DEFAULT_BIT_DEPTH: u16 = 24;
+
+    ///
+    /// Size, in pixels, of each square of a generated
+    /// checkerboard, when none is given
+    /// 
+    pub const DEFAULT_CELL: usize = 16;
+}
+
+pub mod slideshow {
+    ///
+    /// Time, in seconds, to show each image for
+    /// before moving on to the next
+    /// 
+    pub const DEFAULT_INTERVAL_SECONDS: u64 = 5;
+
+    ///
+    /// Escape sequence to clear the console and move to its
+    /// top-left corner, as images may differ in size
+    /// 
+    pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+    ///
+    /// Lines typed while a slideshow is showing to move through it
+    /// 
+    pub mod keys {
+        pub const NEXT: &str = "n";
+        pub const PREVIOUS: &str = "p";
+        pub const QUIT: &str = "q";
+    }
+}
+
+pub mod pick {
+    ///
+    /// Lines typed while picking interactively to move the crosshair,
+    /// one drawn pixel at a time, or to stop. A line may also be a
+    /// position, as X,Y, to jump to.
+    /// 
+    pub mod keys {
+        pub const UP: &str = "w";
+        pub const LEFT: &str = "a";
+        pub const DOWN: &str = "s";
+        pub const RIGHT: &str = "d";
+        pub const QUIT: &str = "q";
+    }
 }
 
 // pub mod color {
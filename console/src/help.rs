@@ -0,0 +1,127 @@
+use parse_args::{argparser::ArgSpec, help};
+
+use crate::command::{COMMANDS, GLOBAL_ARGUMENTS, parse_settings};
+use crate::constants::{args, env};
+
+///
+/// Description of an environment variable the tool honors
+///
+pub struct EnvironmentDefinition {
+    pub key: &'static str,
+    pub description: &'static str
+}
+
+///
+/// All environment variables the tool honors
+///
+pub const ENVIRONMENT: &[EnvironmentDefinition] = &[
+    EnvironmentDefinition {
+        key: env::keys::TRUECOLOR_ENABLED,
+        description: "Set to truecolor or 24bit if the console supports 24-bit color."
+    },
+    EnvironmentDefinition {
+        key: env::keys::NO_COLOR,
+        description: "Set to anything but an empty string to disable color, unless /color:always is given."
+    },
+    EnvironmentDefinition {
+        key: env::keys::CONFIG,
+        description: "Config file to read default arguments from, unless /config is given."
+    },
+    EnvironmentDefinition {
+        key: env::keys::TERM,
+        description: "Terminal type; names containing 256color enable the 256-color palette."
+    }
+];
+
+///
+/// Example invocations, and what they do
+///
+pub const EXAMPLES: &[(&str, &str)] = &[
+    ("view /path:image.bmp", "Draw image.bmp in the console."),
+    ("view /path:image.bmp /dither:floyd-steinberg /bg:checker", "Draw with dithering, over a checkerboard."),
+    ("convert /path:image.bmp /out_path:copy.bmp", "Re-encode image.bmp to copy.bmp."),
+    ("convert /path:image.bmp /out_path:small.bmp /depth:8", "Re-encode image.bmp as an 8-bit bitmap."),
+    ("convert /path:image.bmp /out_path:packed.bmp /depth:8 /compress:rle", "Re-encode image.bmp as an RLE8 compressed bitmap."),
+    ("convert /path:image.bmp /out_path:thumb.bmp /crop:0,0,64x64 /resize:32x32 /rotate:90", "Crop, shrink and rotate image.bmp."),
+    ("convert /path:photo.bmp /out_path:photo.pgm", "Write a grayscale copy of photo.bmp as a binary pgm."),
+    ("convert /path:icon.bmp /out_path:icon.pam", "Write icon.bmp as a pam, keeping its transparency."),
+    ("view /path:boing.iff", "Draw an Amiga ilbm, including extra halfbrite and hold-and-modify images."),
+    ("view /path:albedo.dds", "Preview a texture, uncompressed or with BC1 or BC3 compression."),
+    ("view /path:banner.gif", "Draw the first frame of a gif, or an image in any other format that can be read."),
+    ("view /path:image.bmp /charset:ascii", "Draw image.bmp with plain ASCII characters."),
+    ("view /path:image.bmp /filter:grayscale,blur=2,brightness=+20", "Draw a blurred, brightened grayscale copy of image.bmp."),
+    ("view /path:image.bmp /charset:ascii /filter:hatch=4", "Draw image.bmp as a pen and ink hatching."),
+    ("convert /path:photo.bmp /out_path:painted.bmp /filter:anisotropic-kuwahara=6", "Smooth photo.bmp into painterly brush strokes."),
+    ("convert /path:image.bmp /out_path:graded.bmp /lut:film.cube", "Color grade image.bmp with the look in film.cube."),
+    ("convert /path:logo.bmp /out_path:flat.bmp /depth:24 /bg:FFFFFF", "Flatten the transparent parts of logo.bmp onto white."),
+    ("convert /path:photos /out_dir:graded /recipe:grade.json", "Apply the edits saved in grade.json to every image in photos."),
+    ("view /path:-", "Draw an image piped in through stdin."),
+    ("play /path:frames /delay:50", "Play the bitmaps in the frames directory as an animation."),
+    ("play /path:spinner.gif /loop:3", "Play an animated gif three times, with its own frame delays."),
+    ("slideshow /path:photos /interval:10", "Show each image in the photos directory for 10 seconds, in turn."),
+    ("montage /path:a.bmp,b.bmp,c.bmp /out_path:sheet.bmp", "Write a labeled contact sheet of three images to sheet.bmp."),
+    ("histogram /path:image.bmp /log", "Chart the channels of image.bmp on a log scale."),
+    ("diff /path:a.bmp /other:b.bmp /tolerance:2 /threshold:0.5", "Fail if more than 0.5% of pixels differ by more than 2."),
+    ("hex /path:image.bmp", "Print the annotated bytes of image.bmp."),
+    ("hex /path:image.bmp /raw /section:header", "Print a plain hexdump of the headers of image.bmp."),
+    ("info /path:image.bmp", "Print the dimensions, bit depth and other metadata of image.bmp."),
+    ("info /path:image.bmp /format:json", "Print the metadata of image.bmp as JSON."),
+    ("info /path:image.bmp /stats", "Print the metadata of image.bmp, and statistics of each channel."),
+    ("formats", "List the formats images can be read from and written to."),
+    ("generate /pattern:bars /size:640x480 /out_path:bars.bmp", "Write SMPTE-style color bars to bars.bmp."),
+    ("generate /pattern:gradient /colors:#FF0000,#0000FF /depth:8 /out_path:gradient.bmp", "Write a red to blue gradient as an 8-bit bitmap."),
+    ("pick /path:image.bmp /at:10,20", "Print the color of the pixel at 10,20, with the nearest named and ANSI colors."),
+    ("pick /path:image.bmp", "Move a crosshair over the image to pick a pixel."),
+    ("strip /path:image.bmp /out_path:clean.bmp", "Rewrite image.bmp without its metadata or trailing bytes."),
+    ("validate /path:assets /format:json", "Check every bitmap in assets against the format, for CI."),
+    ("view /path:tall.bmp /rows:40", "Draw a tall image 40 rows at a time, pressing Enter for each next page."),
+    ("compare /path:expected.bmp /other:actual.bmp /mode:psnr /threshold:35", "Exit with code 1 unless actual.bmp is within 35 dB PSNR of expected.bmp."),
+    ("compare /path:original.bmp /other:8bit.bmp /mode:delta-e", "Measure how far an 8-bit export strays from the original colors, in mean ΔE."),
+    ("view /path:image.bmp /config:prefs.cfg", "Draw image.bmp with the preferences in prefs.cfg, such as charset=ascii.")
+];
+
+///
+/// Build the overview help text, listing each command
+///
+pub fn help_text(program: &str) -> String {
+    let width = COMMANDS.iter()
+        .map(|definition| definition.name.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut text = format!("Usage: {program} <command> [arguments]\n\nCommands:\n");
+
+    for definition in COMMANDS {
+        text.push_str(&format!("  {: <width$}  {}\n", definition.name, definition.description));
+    }
+
+    text.push_str(&format!("\nRun '{program} <command> {}{}' for the arguments of a command.\n", args::ARGUMENT_PREFIX, args::keys::HELP));
+
+    text.push_str("\nGlobal arguments:\n");
+
+    let globals: Vec<ArgSpec> = GLOBAL_ARGUMENTS.iter()
+        .copied()
+        .map(ArgSpec::from)
+        .collect();
+
+    text.push_str(&help::help_arguments(&globals, &parse_settings()));
+
+    text.push_str("\nEnvironment:\n");
+
+    let env_width = ENVIRONMENT.iter()
+        .map(|definition| definition.key.len())
+        .max()
+        .unwrap_or(0);
+
+    for definition in ENVIRONMENT {
+        text.push_str(&format!("  {: <env_width$}  {}\n", definition.key, definition.description));
+    }
+
+    text.push_str("\nExamples:\n");
+
+    for (example, description) in EXAMPLES {
+        text.push_str(&format!("  {program} {example}\n      {description}\n"));
+    }
+
+    text
+}
\ No newline at end of file
@@ -0,0 +1,22 @@
+use std::fmt;
+use colored::Colorize;
+use rs_image::log::{Level, Logger};
+
+///
+/// Writes log messages to stderr, so they never mix
+/// with image or report output on stdout
+///
+pub struct StderrLogger;
+
+impl Logger for StderrLogger {
+    fn log(&self, level: Level, message: &fmt::Arguments) {
+        let prefix = match level {
+            Level::Error => level.name().red(),
+            Level::Warn => level.name().yellow(),
+            Level::Info => level.name().normal(),
+            Level::Debug => level.name().dimmed()
+        };
+
+        eprintln!("[{prefix}] {message}");
+    }
+}
\ No newline at end of file
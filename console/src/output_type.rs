@@ -1,8 +0,0 @@
-#[derive(Clone, PartialEq, Eq, Default, Debug)]
-pub enum OutputType {
-    #[default]
-    OutputToConsole,
-    DrawToConsole,
-    WriteToFile,
-    OutputHex
-}
\ No newline at end of file
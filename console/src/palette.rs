@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests;
+
+use rs_image::color::{self, AOKLAB};
+use rs_image::convert::ConvertableFrom;
+
+///
+/// The intensities used by each channel of the 6x6x6 color cube in
+/// the xterm 256-color palette
+///
+const CUBE_LEVELS: [u8; 6] = [0x00, 0x5F, 0x87, 0xAF, 0xD7, 0xFF];
+
+///
+/// The index of the first entry of the color cube. Entries before this
+/// are the 16 basic colors, which vary between terminal themes.
+///
+const CUBE_START: u8 = 16;
+
+///
+/// The index of the first entry of the grayscale ramp
+///
+const GRAYSCALE_START: u8 = 232;
+
+///
+/// The xterm 256-color palette, excluding the 16 themeable basic colors,
+/// with each entry precomputed in OKLab for nearest-color search
+///
+pub struct Palette256 {
+    entries: Vec<(u8, color::ARGB, AOKLAB)>
+}
+
+impl Palette256 {
+    pub fn new() -> Self {
+        let cube = (0..216_u8).map(|n| {
+            let color = color::ARGB {
+                alpha: 0xFF,
+                red: CUBE_LEVELS[(n / 36) as usize],
+                green: CUBE_LEVELS[((n / 6) % 6) as usize],
+                blue: CUBE_LEVELS[(n % 6) as usize]
+            };
+
+            (CUBE_START + n, color)
+        });
+
+        let grayscale = (0..24_u8).map(|n| {
+            let level = 8 + 10 * n;
+            (GRAYSCALE_START + n, color::ARGB { alpha: 0xFF, red: level, green: level, blue: level })
+        });
+
+        Self {
+            entries: cube.chain(grayscale)
+                .map(|(index, color)| (index, color, AOKLAB::convert_from(color, ())))
+                .collect()
+        }
+    }
+
+    ///
+    /// Get the palette index perceptually closest to the given color
+    ///
+    pub fn nearest(&self, color: color::ARGB) -> u8 {
+        let target = AOKLAB::convert_from(color, ());
+
+        self.entries.iter()
+            .map(|(index, _, entry)| (*index, entry.distance_euclidean(&target)))
+            .reduce(|(index_a, distance_a), (index_b, distance_b)| {
+                if distance_a <= distance_b {
+                    (index_a, distance_a)
+                }
+                else {
+                    (index_b, distance_b)
+                }
+            })
+            .map_or(CUBE_START, |(index, _)| index)
+    }
+
+    ///
+    /// Get the color of the given palette index. Indices of the 16 basic
+    /// colors aren't known, and produce black.
+    ///
+    pub fn color(&self, index: u8) -> color::ARGB {
+        index.checked_sub(CUBE_START)
+            .and_then(|offset| self.entries.get(offset as usize))
+            .map_or(color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0x00 }, |(_, color, _)| *color)
+    }
+}
+
+impl Default for Palette256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
\ No newline at end of file
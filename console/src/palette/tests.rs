@@ -0,0 +1,47 @@
+use super::*;
+
+#[test]
+fn palette_entries_are_nearest_to_themselves() -> Result<(), String> {
+    let palette = Palette256::new();
+
+    let cube = (0..216_u8).map(|n| (CUBE_START + n, color::ARGB {
+        alpha: 0xFF,
+        red: CUBE_LEVELS[(n / 36) as usize],
+        green: CUBE_LEVELS[((n / 6) % 6) as usize],
+        blue: CUBE_LEVELS[(n % 6) as usize]
+    }));
+
+    let grayscale = (0..24_u8).map(|n| (GRAYSCALE_START + n, color::ARGB { alpha: 0xFF, red: 8 + 10 * n, green: 8 + 10 * n, blue: 8 + 10 * n }));
+
+    for (index, color) in cube.chain(grayscale) {
+        let nearest = palette.nearest(color);
+
+        if nearest != index {
+            return Err(format!("Expected {color:?} to map back to index {index}, but got {nearest}."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn palette_nearest_color() -> Result<(), String> {
+    let palette = Palette256::new();
+
+    let cases = [
+        (color::ARGB { alpha: 0xFF, red: 0xFE, green: 0x02, blue: 0x01 }, 196),
+        (color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0x00 }, 16),
+        (color::ARGB { alpha: 0xFF, red: 0x80, green: 0x80, blue: 0x80 }, 244),
+        (color::ARGB { alpha: 0xFF, red: 0x5F, green: 0x87, blue: 0xAF }, 67)
+    ];
+
+    for (color, expected) in cases {
+        let nearest = palette.nearest(color);
+
+        if nearest != expected {
+            return Err(format!("Expected {color:?} to map to index {expected}, but got {nearest}."));
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
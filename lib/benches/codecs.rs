@@ -0,0 +1,84 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use rs_image::{convert::ConvertableFrom, image::Image};
+use rs_image::image::format::bitmap::{Bitmap, BitDepth, BitmapConvertData, DecodeOptions, ImageConvertData};
+
+mod common;
+
+///
+/// Each bit depth, with an image it could represent: palette
+/// images for 1, 4 and 8 bits, and a photo for 24 and 32 bits
+///
+fn fixtures() -> Vec<(u16, Image)> {
+    let (width, height) = common::SIZES[common::SIZES.len() - 1];
+
+    vec![
+        (1, common::indexed(width, height, 2)),
+        (4, common::indexed(width, height, 16)),
+        (8, common::indexed(width, height, 256)),
+        (24, common::photo(width, height)),
+        (32, common::photo(width, height))
+    ]
+}
+
+///
+/// Decode and encode large bitmaps at each bit depth
+///
+fn bitmaps(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bitmap");
+    group.sample_size(10);
+
+    for (bit_depth, image) in fixtures() {
+        group.throughput(Throughput::Elements(image.length() as u64));
+
+        let bitmap = Bitmap::convert_from(image, BitmapConvertData {
+            bit_depth: BitDepth::Bits(bit_depth),
+            ..Default::default()
+        });
+
+        let bytes = Vec::try_from(bitmap.clone()).expect("Failed to encode bitmap.");
+
+        group.bench_with_input(BenchmarkId::new("decode", bit_depth), &bytes, |b, bytes| {
+            b.iter(|| Bitmap::try_from(&bytes[..]))
+        });
+
+        group.bench_with_input(BenchmarkId::new("decode_into", bit_depth), &bytes, |b, bytes| {
+            let mut image = Image::default();
+            b.iter(|| Bitmap::decode_into(bytes, &mut image, DecodeOptions::default()))
+        });
+
+        group.bench_with_input(BenchmarkId::new("to_image", bit_depth), &bitmap, |b, bitmap| {
+            b.iter_batched(|| bitmap.clone(), |bitmap| Image::try_convert_from(bitmap, ImageConvertData::default()), BatchSize::LargeInput)
+        });
+
+        group.bench_with_input(BenchmarkId::new("encode", bit_depth), &bitmap, |b, bitmap| {
+            b.iter_batched(|| bitmap.clone(), Vec::<u8>::try_from, BatchSize::LargeInput)
+        });
+    }
+
+    group.finish();
+}
+
+///
+/// Decode a small region from the middle of large bitmaps
+///
+fn regions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("region");
+
+    for (bit_depth, image) in fixtures() {
+        let (x, y) = (image.width() / 2, image.height() / 2);
+
+        let bytes = Vec::try_from(Bitmap::convert_from(image, BitmapConvertData {
+            bit_depth: BitDepth::Bits(bit_depth),
+            ..Default::default()
+        })).expect("Failed to encode bitmap.");
+
+        group.bench_with_input(BenchmarkId::new("decode_region", bit_depth), &bytes, |b, bytes| {
+            b.iter(|| Bitmap::decode_region(bytes, x, y, 256, 256, DecodeOptions::default()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bitmaps, regions);
+criterion_main!(benches);
\ No newline at end of file
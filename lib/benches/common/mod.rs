@@ -0,0 +1,50 @@
+//Each bench uses only some of the fixtures
+#![allow(dead_code)]
+
+use rs_image::{color, image::Image};
+
+///
+/// Multi-megapixel sizes, so per-row work outweighs thread overhead
+///
+pub const SIZES: [(usize, usize); 2] = [(1024, 1024), (2048, 2048)];
+
+///
+/// Create an image with a gradient and some noise, like a photo, so
+/// no operation can take a shortcut on uniform input
+///
+pub fn photo(width: usize, height: usize) -> Image {
+    let pixels: Vec<color::ARGB> = (0..height)
+        .flat_map(|j| (0..width).map(move |i| color::ARGB {
+            red: (i * 255 / width) as u8,
+            green: (j * 255 / height) as u8,
+            blue: ((i * 31 + j * 17) % 256) as u8,
+            alpha: if (i ^ j) % 7 == 0 { 128 } else { 255 }
+        }))
+        .collect();
+
+    Image::new_pixels(width, height, pixels)
+}
+
+///
+/// Create an opaque image with at most the given number of colors, in
+/// bands like a chart or icon, so it can be stored with a palette
+///
+pub fn indexed(width: usize, height: usize, colors: usize) -> Image {
+    let palette: Vec<color::ARGB> = (0..colors)
+        .map(|n| color::ARGB {
+            red: (n * 97 % 256) as u8,
+            green: (n * 57 % 256) as u8,
+            blue: (n * 13 % 256) as u8,
+            alpha: 0xFF
+        })
+        .collect();
+
+    let pixels: Vec<color::ARGB> = (0..height)
+        .flat_map(|j| {
+            let palette = &palette;
+            (0..width).map(move |i| palette[((i / 16) + (j / 16) * 3) % palette.len()])
+        })
+        .collect();
+
+    Image::new_pixels(width, height, pixels)
+}
\ No newline at end of file
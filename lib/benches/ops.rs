@@ -0,0 +1,171 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rs_image::{color::{self, bulk}, convert::ConvertableFrom, image::{filter::Filter, pipeline::Pipeline, transform::{ResizeFilter, Rotation}}};
+
+mod common;
+
+fn ops(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ops");
+    group.sample_size(10);
+
+    for (width, height) in common::SIZES {
+        let image = common::photo(width, height);
+        let size = format!("{width}x{height}");
+
+        group.bench_with_input(BenchmarkId::new("grayscale", &size), &image, |b, image| {
+            b.iter(|| image.filter(Filter::Grayscale))
+        });
+
+        group.bench_with_input(BenchmarkId::new("blur", &size), &image, |b, image| {
+            b.iter(|| image.blur(4))
+        });
+
+        group.bench_with_input(BenchmarkId::new("rotate", &size), &image, |b, image| {
+            b.iter(|| image.rotate(Rotation::Rotate90))
+        });
+
+        group.bench_with_input(BenchmarkId::new("point/eager", &size), &image, |b, image| {
+            b.iter(|| image.brightness(12_f32).invert().grayscale().brightness(-4_f32))
+        });
+
+        group.bench_with_input(BenchmarkId::new("point/fused", &size), &image, |b, image| {
+            let pipeline = Pipeline::new().brightness(12_f32).invert().grayscale().brightness(-4_f32);
+            b.iter(|| pipeline.execute(image))
+        });
+    }
+
+    group.finish();
+}
+
+///
+/// Compare the bulk color conversions against converting one color at a time
+///
+fn conversions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("conversions");
+
+    let (width, height) = common::SIZES[0];
+    let pixels: Vec<color::ARGB> = common::photo(width, height).iter().flat_map(|row| row.iter().copied()).collect();
+
+    group.bench_function("ycbcr/scalar", |b| {
+        b.iter(|| pixels.iter().map(|pixel| color::AYCBCR::convert_from(*pixel, ())).collect::<Vec<_>>())
+    });
+
+    group.bench_function("ycbcr/bulk", |b| {
+        let mut converted = vec![color::AYCBCR::default(); pixels.len()];
+        b.iter(|| bulk::rgb_to_ycbcr_all(&pixels, &mut converted))
+    });
+
+    let ycbcr: Vec<color::AYCBCR> = pixels.iter().map(|pixel| color::AYCBCR::convert_from(*pixel, ())).collect();
+
+    group.bench_function("rgb/scalar", |b| {
+        b.iter(|| ycbcr.iter().map(|pixel| color::ARGB::convert_from(*pixel, ())).collect::<Vec<_>>())
+    });
+
+    group.bench_function("rgb/bulk", |b| {
+        let mut converted = vec![color::ARGB::default(); ycbcr.len()];
+        b.iter(|| bulk::ycbcr_to_rgb_all(&ycbcr, &mut converted))
+    });
+
+    group.bench_function("premultiply/scalar", |b| {
+        b.iter(|| pixels.iter().map(|pixel| pixel.premultiply()).collect::<Vec<_>>())
+    });
+
+    group.bench_function("premultiply/bulk", |b| {
+        let mut premultiplied = pixels.clone();
+        b.iter(|| bulk::premultiply_all(&mut premultiplied))
+    });
+
+    let premultiplied: Vec<color::ARGB> = pixels.iter().map(|pixel| pixel.premultiply()).collect();
+
+    group.bench_function("unpremultiply/scalar", |b| {
+        b.iter(|| premultiplied.iter().map(|pixel| pixel.unpremultiply()).collect::<Vec<_>>())
+    });
+
+    group.bench_function("unpremultiply/bulk", |b| {
+        let mut unpremultiplied = premultiplied.clone();
+        b.iter(|| bulk::unpremultiply_all(&mut unpremultiplied))
+    });
+
+    let channels: Vec<u8> = pixels.iter().flat_map(|pixel| [pixel.red, pixel.green, pixel.blue]).collect();
+
+    group.bench_function("srgb_to_linear/scalar", |b| {
+        b.iter(|| channels.iter().map(|channel| color::srgb_to_linear(*channel)).collect::<Vec<_>>())
+    });
+
+    group.bench_function("srgb_to_linear/bulk", |b| {
+        let mut linear = vec![0_f32; channels.len()];
+        b.iter(|| bulk::srgb_to_linear_all(&channels, &mut linear))
+    });
+
+    let mut linear = vec![0_f32; channels.len()];
+    bulk::srgb_to_linear_all(&channels, &mut linear);
+
+    group.bench_function("linear_to_srgb/scalar", |b| {
+        b.iter(|| linear.iter().map(|value| color::linear_to_srgb(*value)).collect::<Vec<_>>())
+    });
+
+    group.bench_function("linear_to_srgb/bulk", |b| {
+        let mut srgb = vec![0_u8; linear.len()];
+        b.iter(|| bulk::linear_to_srgb_all(&linear, &mut srgb))
+    });
+
+    group.finish();
+}
+
+///
+/// Shrink and enlarge with each resize filter
+///
+fn resize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resize");
+    group.sample_size(10);
+
+    let (width, height) = common::SIZES[0];
+    let large = common::photo(width, height);
+    let small = common::photo(width / 4, height / 4);
+
+    for filter in [ResizeFilter::Nearest, ResizeFilter::Bilinear, ResizeFilter::Box] {
+        let name = format!("{filter:?}").to_ascii_lowercase();
+
+        group.bench_with_input(BenchmarkId::new("shrink", &name), &large, |b, image| {
+            b.iter(|| image.resize(width / 3, height / 3, filter))
+        });
+
+        group.bench_with_input(BenchmarkId::new("enlarge", &name), &small, |b, image| {
+            b.iter(|| image.resize(width, height, filter))
+        });
+    }
+
+    group.finish();
+}
+
+///
+/// Run the heaviest operation on thread pools of increasing size,
+/// to show how it scales with the parallel feature
+///
+#[cfg(feature = "parallel")]
+fn scaling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scaling");
+    group.sample_size(10);
+
+    let (width, height) = common::SIZES[common::SIZES.len() - 1];
+    let image = common::photo(width, height);
+    let max = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+
+    for threads in (0..).map(|n| 1_usize << n).take_while(|threads| *threads <= max) {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("Failed to build thread pool.");
+
+        group.bench_with_input(BenchmarkId::new("blur", threads), &image, |b, image| {
+            b.iter(|| pool.install(|| image.blur(4)))
+        });
+    }
+
+    group.finish();
+}
+
+#[cfg(not(feature = "parallel"))]
+fn scaling(_: &mut Criterion) {}
+
+criterion_group!(benches, ops, resize, conversions, scaling);
+criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,29 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rs_image::{convert::ConvertableFrom, image::{Image, format}};
+use format::bitmap::{Bitmap, BitmapRef, DecodeOptions, ImageConvertData};
+
+//Everything a file piped into the console tool can reach: detection,
+//both decoders, conversion to an image, re-encoding and the hex dump
+fuzz_target!(|data: &[u8]| {
+    let _ = format::detect(data);
+
+    if let Ok(bitmap) = BitmapRef::try_from(data) {
+        for index in 0..bitmap.pixel_count().min(1 << 16) {
+            let _ = bitmap.get(index);
+        }
+    }
+
+    let _ = Bitmap::decode(data, DecodeOptions { strict: true, ..Default::default() });
+
+    if let Ok(bitmap) = Bitmap::try_from(data) {
+        let _ = bitmap.formatted_bitstring();
+
+        if let Ok(image) = Image::try_convert_from(bitmap.clone(), ImageConvertData::default()) {
+            let _ = image.length();
+        }
+
+        let _ = Vec::<u8>::try_from(bitmap);
+    }
+});
\ No newline at end of file
@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rs_image::image::{Image, format::bitmap::{Bitmap, DecodeOptions}};
+
+//The first 4 bytes choose the region, and the rest are the file
+fuzz_target!(|data: &[u8]| {
+    let Some((region, bytes)) = data.split_first_chunk::<4>() else {
+        return;
+    };
+
+    let [x, y, width, height] = region.map(usize::from);
+    let _ = Bitmap::decode_region(bytes, x, y, width, height, DecodeOptions::default());
+
+    let mut image = Image::default();
+    let _ = Bitmap::decode_into(bytes, &mut image, DecodeOptions::default());
+});
\ No newline at end of file
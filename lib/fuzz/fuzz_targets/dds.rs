@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rs_image::{convert::ConvertableFrom, image::{Image, format::dds::Dds}};
+
+//Decoding, and expanding the compressed blocks into pixels
+fuzz_target!(|data: &[u8]| {
+    if let Ok(dds) = Dds::try_from(data) {
+        if let Ok(image) = Image::try_convert_from(dds, ()) {
+            let _ = image.length();
+        }
+    }
+});
\ No newline at end of file
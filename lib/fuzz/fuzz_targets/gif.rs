@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rs_image::{convert::ConvertableFrom, image::{Image, animation::AnimatedImage, format}};
+use format::{bitmap::ImageConvertData, gif::Gif};
+
+//Decoding, drawing the first frame and the whole animation, and re-encoding
+fuzz_target!(|data: &[u8]| {
+    if let Ok(gif) = Gif::try_from(data) {
+        if let Ok(image) = Image::try_convert_from(gif.clone(), ImageConvertData::default()) {
+            let _ = image.length();
+        }
+
+        if let Ok(animation) = AnimatedImage::try_convert_from(gif.clone(), ImageConvertData::default()) {
+            let _ = animation.frames().len();
+        }
+
+        let _ = Vec::<u8>::try_from(gif);
+    }
+});
\ No newline at end of file
@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rs_image::{convert::ConvertableFrom, image::{Image, format}};
+use format::{bitmap::ImageConvertData, ilbm::Ilbm};
+
+//Decoding, unpacking the bitplanes, and drawing them through the palette
+fuzz_target!(|data: &[u8]| {
+    if let Ok(ilbm) = Ilbm::try_from(data) {
+        if let Ok(image) = Image::try_convert_from(ilbm, ImageConvertData::default()) {
+            let _ = image.length();
+        }
+    }
+});
\ No newline at end of file
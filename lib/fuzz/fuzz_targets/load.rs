@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rs_image::image::format::codec::{self, LoadOptions};
+
+//Detecting the format among all of them, decoding it, turning it upright,
+//and converting it from its color profile
+fuzz_target!(|data: &[u8]| {
+    let _ = codec::load(data);
+    let _ = codec::load_with_options(data, LoadOptions { apply_orientation: false, apply_color_profile: true });
+});
\ No newline at end of file
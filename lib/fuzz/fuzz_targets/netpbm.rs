@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rs_image::{convert::ConvertableFrom, image::{Image, format::netpbm::Netpbm}};
+
+//Decoding each kind, from plain pbm to pam, drawing it, and re-encoding
+fuzz_target!(|data: &[u8]| {
+    if let Ok(netpbm) = Netpbm::try_from(data) {
+        if let Ok(image) = Image::try_convert_from(netpbm.clone(), ()) {
+            let _ = image.length();
+        }
+
+        let _ = Vec::<u8>::try_from(netpbm);
+    }
+});
\ No newline at end of file
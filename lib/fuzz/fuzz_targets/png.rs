@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rs_image::{convert::ConvertableFrom, image::{Image, format}, utility::zlib};
+use format::{bitmap::ImageConvertData, png::Png};
+
+///
+/// Most a stream may inflate to, so a small input can't ask for a huge buffer
+///
+const INFLATE_LIMIT: usize = 1 << 24;
+
+//Inflating the data as both a zlib and a raw deflate stream, then decoding it
+//as a png, unfiltering its scanlines, and drawing it
+fuzz_target!(|data: &[u8]| {
+    let _ = zlib::decompress(data, INFLATE_LIMIT);
+    let _ = zlib::inflate(data, INFLATE_LIMIT);
+
+    if let Ok(png) = Png::try_from(data) {
+        if let Ok(image) = Image::try_convert_from(png, ImageConvertData::default()) {
+            let _ = image.length();
+        }
+    }
+});
\ No newline at end of file
@@ -1,6 +1,24 @@
+#[cfg(feature = "std")]
 pub mod conversion;
+#[cfg(feature = "std")]
+pub mod bulk;
+#[cfg(feature = "std")]
+pub mod icc;
+#[cfg(feature = "std")]
+pub mod lut;
+#[cfg(feature = "std")]
+pub mod names;
 
+use alloc::{format, string::String};
+
+use crate::error::ImageError;
+
+///
+/// An 8-bit sRGB color. Laid out as red, green, blue, alpha bytes,
+/// so slices of colors can be processed as bytes.
+///
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(C)]
 pub struct ARGB {
     pub red: u8,
     pub green: u8,
@@ -24,6 +42,31 @@ pub struct ALAB {
     pub alpha: u8
 }
 
+///
+/// A color in the OKLab perceptual color space.
+/// See: https://bottosson.github.io/posts/oklab/
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AOKLAB {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: u8
+}
+
+///
+/// A color as full range luma and chroma (BT.601, as used by JPEG).
+/// Laid out as y, cb, cr, alpha bytes.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(C)]
+pub struct AYCBCR {
+    pub y: u8,
+    pub cb: u8,
+    pub cr: u8,
+    pub alpha: u8
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct AHSV {
     pub h: f32,
@@ -32,6 +75,37 @@ pub struct AHSV {
     pub alpha: u8
 }
 
+///
+/// Convert an 8-bit sRGB channel to linear light, in [0, 1]
+///
+#[cfg(feature = "std")]
+pub fn srgb_to_linear(channel: u8) -> f32 {
+    let scaled = channel as f32 / 255_f32;
+
+    if scaled > 0.04045 {
+        ((scaled + 0.055) / 1.055).powf(2.4)
+    }
+    else {
+        scaled / 12.92
+    }
+}
+
+///
+/// Convert a linear light channel, in [0, 1], to 8-bit sRGB
+///
+#[cfg(feature = "std")]
+pub fn linear_to_srgb(channel: f32) -> u8 {
+    let scaled = if channel > 0.0031308 {
+        1.055 * channel.powf(1_f32 / 2.4) - 0.055
+    }
+    else {
+        12.92 * channel
+    };
+
+    (scaled * 255_f32).round().clamp(0_f32, 255_f32) as u8
+}
+
+#[cfg(feature = "std")]
 fn distance_euclidean(a: (f32, f32, f32), b: (f32, f32, f32)) -> f32 {
     f32::sqrt(
         (a.0 - b.0).powi(2)
@@ -81,6 +155,7 @@ fn from_u32(n: u32, little_endian: bool) -> (u8, u8, u8, u8) {
 }
 
 impl ARGB {
+    #[cfg(feature = "std")]
     pub fn distance_euclidean(&self, other: &Self) -> f32 {
         distance_euclidean(
             (self.red as f32, self.blue as f32, self.green as f32), 
@@ -144,6 +219,158 @@ impl ARGB {
             blue
         }
     }
+
+    ///
+    /// Parse a color from a hex string of the form RGB, RRGGBB or AARRGGBB,
+    /// optionally prefixed with # or 0x. Colors without alpha are opaque.
+    ///
+    pub fn from_hex(hex: &str) -> Result<Self, ImageError> {
+        let digits = hex.trim();
+        let digits = digits.strip_prefix('#')
+            .or_else(|| digits.strip_prefix("0x"))
+            .unwrap_or(digits);
+
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ImageError::InvalidArgument(format!("'{hex}' is not a valid hex color.")));
+        }
+
+        let n = u32::from_str_radix(digits, 16)
+            .map_err(|_| ImageError::InvalidArgument(format!("'{hex}' is not a valid hex color.")))?;
+
+        match digits.len() {
+            3 => {
+                //Each digit is doubled, i.e. F0A => FF00AA
+                let expand = |d: u32| ((d & 0xF) * 0x11) as u8;
+                Ok(Self { alpha: 0xFF, red: expand(n >> 8), green: expand(n >> 4), blue: expand(n) })
+            },
+            6 => Ok(Self::from_u32(n, false).with_alpha(0xFF)),
+            8 => Ok(Self::from_u32(n, false)),
+            _ => Err(ImageError::InvalidArgument(format!("'{hex}' is not a valid hex color; expected 3, 6 or 8 digits.")))
+        }
+    }
+
+    ///
+    /// Relative luminance of the color (Rec. 709), ignoring alpha
+    ///
+    #[cfg(feature = "std")]
+    pub fn luminance(&self) -> u8 {
+        (0.2126_f32 * self.red as f32
+            + 0.7152_f32 * self.green as f32
+            + 0.0722_f32 * self.blue as f32)
+            .round()
+            .clamp(0_f32, 255_f32) as u8
+    }
+
+    ///
+    /// Format the color as a hex string of the form #AARRGGBB
+    ///
+    pub fn to_hex(&self) -> String {
+        format!("#{:08X}", self.as_u32(false))
+    }
+
+    ///
+    /// Composite this color over the given background color
+    /// (Porter-Duff source-over)
+    ///
+    #[cfg(feature = "std")]
+    pub fn over(&self, background: &Self) -> Self {
+        let alpha_top = self.alpha as f32 / 255_f32;
+        let alpha_bottom = background.alpha as f32 / 255_f32;
+        let alpha = alpha_top + alpha_bottom * (1_f32 - alpha_top);
+
+        if alpha == 0_f32 {
+            return Self::default();
+        }
+
+        let blend = |top: u8, bottom: u8| -> u8 {
+            ((top as f32 * alpha_top + bottom as f32 * alpha_bottom * (1_f32 - alpha_top)) / alpha)
+                .round()
+                .clamp(0_f32, 255_f32) as u8
+        };
+
+        Self {
+            alpha: (alpha * 255_f32).round() as u8,
+            red: blend(self.red, background.red),
+            green: blend(self.green, background.green),
+            blue: blend(self.blue, background.blue)
+        }
+    }
+
+    ///
+    /// Add the given offsets to the red, green and blue channels,
+    /// clamping the results. Alpha is left untouched.
+    ///
+    #[cfg(feature = "std")]
+    pub fn offset(&self, red: f32, green: f32, blue: f32) -> Self {
+        fn offset_channel(channel: u8, offset: f32) -> u8 {
+            (channel as f32 + offset).round().clamp(0_f32, 255_f32) as u8
+        }
+
+        Self {
+            alpha: self.alpha,
+            red: offset_channel(self.red, red),
+            green: offset_channel(self.green, green),
+            blue: offset_channel(self.blue, blue)
+        }
+    }
+
+    ///
+    /// Scale each color channel by alpha
+    ///
+    pub fn premultiply(&self) -> Self {
+        //Exact round(channel * alpha / 255), without division
+        let scale = |channel: u8| -> u8 {
+            let t = channel as u16 * self.alpha as u16 + 128;
+            ((t + (t >> 8)) >> 8) as u8
+        };
+
+        Self {
+            alpha: self.alpha,
+            red: scale(self.red),
+            green: scale(self.green),
+            blue: scale(self.blue)
+        }
+    }
+
+    ///
+    /// Undo premultiplication, dividing each color channel by alpha.
+    /// Fully transparent colors become transparent black.
+    ///
+    pub fn unpremultiply(&self) -> Self {
+        if self.alpha == 0 {
+            return Self::default();
+        }
+
+        let alpha = self.alpha as u32;
+        let scale = |channel: u8| -> u8 {
+            ((channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8
+        };
+
+        Self {
+            alpha: self.alpha,
+            red: scale(self.red),
+            green: scale(self.green),
+            blue: scale(self.blue)
+        }
+    }
+
+    ///
+    /// Linearly interpolate each channel between this color (t = 0)
+    /// and other (t = 1)
+    ///
+    #[cfg(feature = "std")]
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        fn lerp_channel(a: u8, b: u8, t: f32) -> u8 {
+            ((a as f32) + ((b as f32) - (a as f32)) * t).round().clamp(0_f32, 255_f32) as u8
+        }
+
+        Self {
+            alpha: lerp_channel(self.alpha, other.alpha, t),
+            red: lerp_channel(self.red, other.red, t),
+            green: lerp_channel(self.green, other.green, t),
+            blue: lerp_channel(self.blue, other.blue, t)
+        }
+    }
 }
 
 impl From<u32> for ARGB {
@@ -159,6 +386,7 @@ impl From<ARGB> for u32 {
 }
 
 impl AXYZ {
+    #[cfg(feature = "std")]
     pub fn distance_euclidean(&self, other: &Self) -> f32 {
         distance_euclidean(
             (self.x, self.y, self.z), 
@@ -175,6 +403,24 @@ impl AXYZ {
 }
 
 impl ALAB {
+    #[cfg(feature = "std")]
+    pub fn distance_euclidean(&self, other: &Self) -> f32 {
+        distance_euclidean(
+            (self.l, self.a, self.b), 
+            (other.l, other.a, other.b)
+        )
+    }
+
+    pub fn distance_manhattan(&self, other: &Self) -> f32 {
+        distance_manhattan(
+            (self.l, self.a, self.b), 
+            (other.l, other.a, other.b)
+        )
+    }
+}
+
+impl AOKLAB {
+    #[cfg(feature = "std")]
     pub fn distance_euclidean(&self, other: &Self) -> f32 {
         distance_euclidean(
             (self.l, self.a, self.b), 
@@ -191,6 +437,7 @@ impl ALAB {
 }
 
 impl AHSV {
+    #[cfg(feature = "std")]
     pub fn distance_euclidean(&self, other: &Self) -> f32 {
         distance_euclidean(
             (self.h, self.s, self.v), 
@@ -0,0 +1,329 @@
+#[cfg(test)]
+mod tests;
+
+use std::sync::OnceLock;
+
+use crate::convert::ConvertableFrom;
+use super::{ARGB, AYCBCR, srgb_to_linear, linear_to_srgb};
+
+///
+/// Linear light value of each 8-bit sRGB channel
+///
+static LINEAR: OnceLock<[f32; 256]> = OnceLock::new();
+
+///
+/// Smallest linear light value which maps to each 8-bit sRGB channel from 1 to 255
+///
+static SRGB_THRESHOLDS: OnceLock<[f32; 255]> = OnceLock::new();
+
+fn linear_table() -> &'static [f32; 256] {
+    LINEAR.get_or_init(|| std::array::from_fn(|channel| srgb_to_linear(channel as u8)))
+}
+
+fn srgb_thresholds() -> &'static [f32; 255] {
+    SRGB_THRESHOLDS.get_or_init(|| std::array::from_fn(|index| {
+        let target = index as u8 + 1;
+
+        //Positive floats order the same as their bits, so search the bits for the first value reaching target
+        let (mut low, mut high) = (0_u32, 1_f32.to_bits());
+
+        while low < high {
+            let middle = low + (high - low) / 2;
+
+            if linear_to_srgb(f32::from_bits(middle)) >= target {
+                high = middle;
+            }
+            else {
+                low = middle + 1;
+            }
+        }
+
+        f32::from_bits(low)
+    }))
+}
+
+///
+/// Convert 8-bit sRGB channels to linear light, in [0, 1]. Converts as many
+/// channels as fit in both slices. Matches srgb_to_linear exactly, using a lookup table.
+///
+pub fn srgb_to_linear_all(channels: &[u8], linear: &mut [f32]) {
+    let table = linear_table();
+
+    for (channel, value) in channels.iter().zip(linear.iter_mut()) {
+        *value = table[*channel as usize];
+    }
+}
+
+///
+/// Convert linear light channels, in [0, 1], to 8-bit sRGB. Converts as many
+/// channels as fit in both slices. Matches linear_to_srgb exactly, searching a
+/// table of thresholds instead of raising each channel to a power.
+///
+pub fn linear_to_srgb_all(linear: &[f32], channels: &mut [u8]) {
+    let thresholds = srgb_thresholds();
+
+    for (value, channel) in linear.iter().zip(channels.iter_mut()) {
+        *channel = thresholds.partition_point(|threshold| threshold <= value) as u8;
+    }
+}
+
+///
+/// Convert colors to luma and chroma. Converts as many colors as fit in both slices.
+///
+pub fn rgb_to_ycbcr_all(pixels: &[ARGB], converted: &mut [AYCBCR]) {
+    //Safety: SSE2 is part of the x86_64 baseline, so it is always available
+    #[cfg(target_arch = "x86_64")]
+    let start = unsafe { sse2::rgb_to_ycbcr(pixels, converted) };
+
+    #[cfg(not(target_arch = "x86_64"))]
+    let start = 0;
+
+    for (pixel, value) in pixels[start..].iter().zip(converted[start..].iter_mut()) {
+        *value = AYCBCR::convert_from(*pixel, ());
+    }
+}
+
+///
+/// Convert luma and chroma back to colors. Converts as many colors as fit in both slices.
+///
+pub fn ycbcr_to_rgb_all(pixels: &[AYCBCR], converted: &mut [ARGB]) {
+    //Safety: SSE2 is part of the x86_64 baseline, so it is always available
+    #[cfg(target_arch = "x86_64")]
+    let start = unsafe { sse2::ycbcr_to_rgb(pixels, converted) };
+
+    #[cfg(not(target_arch = "x86_64"))]
+    let start = 0;
+
+    for (pixel, value) in pixels[start..].iter().zip(converted[start..].iter_mut()) {
+        *value = ARGB::convert_from(*pixel, ());
+    }
+}
+
+///
+/// Scale the color channels of each color by its alpha, in place
+///
+pub fn premultiply_all(pixels: &mut [ARGB]) {
+    //Safety: SSE2 is part of the x86_64 baseline, so it is always available
+    #[cfg(target_arch = "x86_64")]
+    let start = unsafe { sse2::premultiply(pixels) };
+
+    #[cfg(not(target_arch = "x86_64"))]
+    let start = 0;
+
+    for pixel in &mut pixels[start..] {
+        *pixel = pixel.premultiply();
+    }
+}
+
+///
+/// Divide the color channels of each color by its alpha, in place
+///
+pub fn unpremultiply_all(pixels: &mut [ARGB]) {
+    //Safety: SSE2 is part of the x86_64 baseline, so it is always available
+    #[cfg(target_arch = "x86_64")]
+    let start = unsafe { sse2::unpremultiply(pixels) };
+
+    #[cfg(not(target_arch = "x86_64"))]
+    let start = 0;
+
+    for pixel in &mut pixels[start..] {
+        *pixel = pixel.unpremultiply();
+    }
+}
+
+///
+/// SSE2 versions of the bulk conversions, which every x86_64 processor supports.
+/// Each handles colors in groups of 4 (16 bytes), returning how many it handled,
+/// and performs the same operations in the same order as the scalar version
+/// so results match exactly.
+///
+#[cfg(target_arch = "x86_64")]
+mod sse2 {
+    use std::arch::x86_64::*;
+
+    use crate::color::{ARGB, AYCBCR};
+
+    ///
+    /// Transpose 4 vectors of 4 lanes, turning 4 colors into 4 channels or back
+    ///
+    #[target_feature(enable = "sse2")]
+    fn transpose(a: __m128, b: __m128, c: __m128, d: __m128) -> [__m128; 4] {
+        let ab_low = _mm_unpacklo_ps(a, b);
+        let cd_low = _mm_unpacklo_ps(c, d);
+        let ab_high = _mm_unpackhi_ps(a, b);
+        let cd_high = _mm_unpackhi_ps(c, d);
+
+        [
+            _mm_movelh_ps(ab_low, cd_low),
+            _mm_movehl_ps(cd_low, ab_low),
+            _mm_movelh_ps(ab_high, cd_high),
+            _mm_movehl_ps(cd_high, ab_high)
+        ]
+    }
+
+    ///
+    /// Split 16 bytes into 4 vectors of 32-bit lanes, one per color
+    ///
+    #[target_feature(enable = "sse2")]
+    fn widen(bytes: __m128i) -> [__m128i; 4] {
+        let zero = _mm_setzero_si128();
+        let low = _mm_unpacklo_epi8(bytes, zero);
+        let high = _mm_unpackhi_epi8(bytes, zero);
+
+        [
+            _mm_unpacklo_epi16(low, zero),
+            _mm_unpackhi_epi16(low, zero),
+            _mm_unpacklo_epi16(high, zero),
+            _mm_unpackhi_epi16(high, zero)
+        ]
+    }
+
+    ///
+    /// Pack 4 vectors of 32-bit lanes back into 16 bytes, saturating each lane to [0, 255]
+    ///
+    #[target_feature(enable = "sse2")]
+    fn narrow(colors: [__m128i; 4]) -> __m128i {
+        _mm_packus_epi16(_mm_packs_epi32(colors[0], colors[1]), _mm_packs_epi32(colors[2], colors[3]))
+    }
+
+    ///
+    /// Split 16 bytes into 4 vectors of channels, as floats
+    ///
+    #[target_feature(enable = "sse2")]
+    fn channels(bytes: __m128i) -> [__m128; 4] {
+        let [a, b, c, d] = widen(bytes).map(|color| _mm_cvtepi32_ps(color));
+        transpose(a, b, c, d)
+    }
+
+    ///
+    /// Round 4 vectors of channels to the nearest 8-bit value, and pack them back into 16 bytes
+    ///
+    #[target_feature(enable = "sse2")]
+    fn pack_channels(channels: [__m128; 4]) -> __m128i {
+        let [a, b, c, d] = channels.map(|channel| _mm_add_ps(
+            _mm_min_ps(_mm_max_ps(channel, _mm_setzero_ps()), _mm_set1_ps(255_f32)),
+            _mm_set1_ps(0.5_f32)
+        ));
+
+        narrow(transpose(a, b, c, d).map(|color| _mm_cvttps_epi32(color)))
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub fn rgb_to_ycbcr(pixels: &[ARGB], converted: &mut [AYCBCR]) -> usize {
+        let mut count = 0;
+
+        for (chunk, target) in pixels.chunks_exact(4).zip(converted.chunks_exact_mut(4)) {
+            //Safety: both types are 4 bytes with no padding, so a chunk of 4 is 16 bytes
+            let bytes = unsafe { _mm_loadu_si128(chunk.as_ptr() as *const __m128i) };
+            let [r, g, b, alpha] = channels(bytes);
+
+            let term = |weight: f32, channel: __m128| _mm_mul_ps(_mm_set1_ps(weight), channel);
+            let offset = _mm_set1_ps(128_f32);
+
+            let y = _mm_add_ps(_mm_add_ps(term(0.299, r), term(0.587, g)), term(0.114, b));
+            let cb = _mm_add_ps(_mm_sub_ps(_mm_sub_ps(offset, term(0.168736, r)), term(0.331264, g)), term(0.5, b));
+            let cr = _mm_sub_ps(_mm_sub_ps(_mm_add_ps(offset, term(0.5, r)), term(0.418688, g)), term(0.081312, b));
+
+            let result = pack_channels([y, cb, cr, alpha]);
+
+            //Safety: as above
+            unsafe { _mm_storeu_si128(target.as_mut_ptr() as *mut __m128i, result) };
+            count += 4;
+        }
+
+        count
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub fn ycbcr_to_rgb(pixels: &[AYCBCR], converted: &mut [ARGB]) -> usize {
+        let mut count = 0;
+
+        for (chunk, target) in pixels.chunks_exact(4).zip(converted.chunks_exact_mut(4)) {
+            //Safety: both types are 4 bytes with no padding, so a chunk of 4 is 16 bytes
+            let bytes = unsafe { _mm_loadu_si128(chunk.as_ptr() as *const __m128i) };
+            let [y, cb, cr, alpha] = channels(bytes);
+
+            let term = |weight: f32, channel: __m128| _mm_mul_ps(_mm_set1_ps(weight), channel);
+            let offset = _mm_set1_ps(128_f32);
+            let (cb, cr) = (_mm_sub_ps(cb, offset), _mm_sub_ps(cr, offset));
+
+            let r = _mm_add_ps(y, term(1.402, cr));
+            let g = _mm_sub_ps(_mm_sub_ps(y, term(0.344136, cb)), term(0.714136, cr));
+            let b = _mm_add_ps(y, term(1.772, cb));
+
+            let result = pack_channels([r, g, b, alpha]);
+
+            //Safety: as above
+            unsafe { _mm_storeu_si128(target.as_mut_ptr() as *mut __m128i, result) };
+            count += 4;
+        }
+
+        count
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub fn premultiply(pixels: &mut [ARGB]) -> usize {
+        let mut count = 0;
+
+        //Alpha is the last of each color's 4 16-bit lanes
+        let alpha_mask = _mm_set_epi16(-1, 0, 0, 0, -1, 0, 0, 0);
+
+        let scale = |colors: __m128i| -> __m128i {
+            let alpha = _mm_shufflehi_epi16::<0xFF>(_mm_shufflelo_epi16::<0xFF>(colors));
+
+            //Exact round(channel * alpha / 255), without division
+            let t = _mm_add_epi16(_mm_mullo_epi16(colors, alpha), _mm_set1_epi16(128));
+            let scaled = _mm_srli_epi16::<8>(_mm_add_epi16(t, _mm_srli_epi16::<8>(t)));
+
+            _mm_or_si128(_mm_and_si128(alpha_mask, colors), _mm_andnot_si128(alpha_mask, scaled))
+        };
+
+        for chunk in pixels.chunks_exact_mut(4) {
+            //Safety: colors are 4 bytes with no padding, so a chunk of 4 is 16 bytes
+            let bytes = unsafe { _mm_loadu_si128(chunk.as_ptr() as *const __m128i) };
+
+            let zero = _mm_setzero_si128();
+            let low = scale(_mm_unpacklo_epi8(bytes, zero));
+            let high = scale(_mm_unpackhi_epi8(bytes, zero));
+
+            //Safety: as above
+            unsafe { _mm_storeu_si128(chunk.as_mut_ptr() as *mut __m128i, _mm_packus_epi16(low, high)) };
+            count += 4;
+        }
+
+        count
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub fn unpremultiply(pixels: &mut [ARGB]) -> usize {
+        let mut count = 0;
+
+        //Alpha is the last of each color's 4 32-bit lanes
+        let alpha_mask = _mm_set_epi32(-1, 0, 0, 0);
+
+        let scale = |color: __m128i| -> __m128i {
+            let alpha = _mm_shuffle_epi32::<0xFF>(color);
+
+            //(channel * 255 + alpha / 2) / alpha, which is exact in floating point for 8-bit values
+            let numerator = _mm_add_epi32(_mm_sub_epi32(_mm_slli_epi32::<8>(color), color), _mm_srli_epi32::<1>(alpha));
+            let quotient = _mm_cvttps_epi32(_mm_div_ps(_mm_cvtepi32_ps(numerator), _mm_cvtepi32_ps(alpha)));
+
+            let result = _mm_or_si128(_mm_and_si128(alpha_mask, color), _mm_andnot_si128(alpha_mask, quotient));
+
+            //Fully transparent colors become transparent black
+            _mm_andnot_si128(_mm_cmpeq_epi32(alpha, _mm_setzero_si128()), result)
+        };
+
+        for chunk in pixels.chunks_exact_mut(4) {
+            //Safety: colors are 4 bytes with no padding, so a chunk of 4 is 16 bytes
+            let bytes = unsafe { _mm_loadu_si128(chunk.as_ptr() as *const __m128i) };
+            let result = narrow(widen(bytes).map(scale));
+
+            //Safety: as above
+            unsafe { _mm_storeu_si128(chunk.as_mut_ptr() as *mut __m128i, result) };
+            count += 4;
+        }
+
+        count
+    }
+}
\ No newline at end of file
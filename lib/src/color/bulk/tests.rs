@@ -0,0 +1,109 @@
+use super::*;
+
+///
+/// Every combination of a channel value and alpha, with a length
+/// that isn't a multiple of 4 so the scalar remainder is covered too
+///
+fn all_pixels() -> Vec<ARGB> {
+    (0..=255_u8)
+        .flat_map(|alpha| (0..=255_u8).map(move |channel| ARGB {
+            red: channel,
+            green: 255 - channel,
+            blue: channel.wrapping_mul(7),
+            alpha
+        }))
+        .chain(std::iter::once(ARGB { red: 1, green: 2, blue: 3, alpha: 4 }))
+        .collect()
+}
+
+#[test]
+fn srgb_linear_match_scalar() -> Result<(), String> {
+    let channels: Vec<u8> = (0..=255_u8).collect();
+    let mut linear = vec![0_f32; channels.len()];
+    srgb_to_linear_all(&channels, &mut linear);
+
+    for (channel, value) in channels.iter().zip(&linear) {
+        if *value != srgb_to_linear(*channel) {
+            return Err(format!("Channel {channel} converted to {value}, expected {}.", srgb_to_linear(*channel)));
+        }
+    }
+
+    //Sweep past both ends of the range, finely enough to land near every threshold
+    let values: Vec<f32> = (-1000..=110000).map(|n| n as f32 / 100000_f32).chain([f32::NAN]).collect();
+    let mut srgb = vec![0_u8; values.len()];
+    linear_to_srgb_all(&values, &mut srgb);
+
+    for (value, channel) in values.iter().zip(&srgb) {
+        if *channel != linear_to_srgb(*value) {
+            return Err(format!("Value {value} converted to {channel}, expected {}.", linear_to_srgb(*value)));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn ycbcr_match_scalar() -> Result<(), String> {
+    let pixels = all_pixels();
+
+    let mut converted = vec![AYCBCR::default(); pixels.len()];
+    rgb_to_ycbcr_all(&pixels, &mut converted);
+
+    for (pixel, value) in pixels.iter().zip(&converted) {
+        if *value != AYCBCR::convert_from(*pixel, ()) {
+            return Err(format!("{pixel:?} converted to {value:?}, expected {:?}.", AYCBCR::convert_from(*pixel, ())));
+        }
+    }
+
+    let mut restored = vec![ARGB::default(); pixels.len()];
+    ycbcr_to_rgb_all(&converted, &mut restored);
+
+    for ((value, pixel), original) in converted.iter().zip(&restored).zip(&pixels) {
+        if *pixel != ARGB::convert_from(*value, ()) {
+            return Err(format!("{value:?} converted to {pixel:?}, expected {:?}.", ARGB::convert_from(*value, ())));
+        }
+
+        //Rounding to 8 bits twice loses at most a couple of steps per channel
+        let error = [(pixel.red, original.red), (pixel.green, original.green), (pixel.blue, original.blue)]
+            .iter()
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0);
+
+        if error > 2 {
+            return Err(format!("{original:?} round tripped to {pixel:?}."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn premultiply_match_scalar() -> Result<(), String> {
+    let pixels = all_pixels();
+
+    let mut premultiplied = pixels.clone();
+    premultiply_all(&mut premultiplied);
+
+    for (pixel, value) in pixels.iter().zip(&premultiplied) {
+        let expected = ARGB {
+            red: (pixel.red as f32 * pixel.alpha as f32 / 255_f32).round() as u8,
+            ..pixel.premultiply()
+        };
+
+        if *value != pixel.premultiply() || value.red != expected.red {
+            return Err(format!("{pixel:?} premultiplied to {value:?}, expected {expected:?}."));
+        }
+    }
+
+    let mut restored = premultiplied.clone();
+    unpremultiply_all(&mut restored);
+
+    for (value, pixel) in premultiplied.iter().zip(&restored) {
+        if *pixel != value.unpremultiply() {
+            return Err(format!("{value:?} unpremultiplied to {pixel:?}, expected {:?}.", value.unpremultiply()));
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
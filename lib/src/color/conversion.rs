@@ -1,12 +1,25 @@
-use crate::convert::ConvertableFrom;
+#[cfg(test)]
+mod tests;
+
+use crate::{convert::ConvertableFrom, error::ImageError};
 use super::*;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct LABSettings {
     pub refs: (f32, f32, f32)
 }
 
+impl LABSettings {
+    ///
+    /// The D65 white point, on the same scale as the XYZ colors converted from sRGB
+    ///
+    pub const D65: LABSettings = LABSettings {
+        refs: (0.95047, 1_f32, 1.08883)
+    };
+}
+
 impl ConvertableFrom<ARGB> for AXYZ {
-    type Error = ();
+    type Error = ImageError;
     type Options = ();
 
     fn try_convert_from(value: ARGB, _: Self::Options) -> Result<Self, Self::Error> {
@@ -34,7 +47,7 @@ impl ConvertableFrom<ARGB> for AXYZ {
 }
 
 impl ConvertableFrom<ARGB> for ALAB {
-    type Error = ();
+    type Error = ImageError;
     type Options = LABSettings;
 
     fn try_convert_from(value: ARGB, options: Self::Options) -> Result<Self, Self::Error> {
@@ -44,7 +57,7 @@ impl ConvertableFrom<ARGB> for ALAB {
 }
 
 impl ConvertableFrom<ARGB> for AHSV {
-    type Error = ();
+    type Error = ImageError;
     type Options = ();
 
     fn try_convert_from(value: ARGB, _: Self::Options) -> Result<Self, Self::Error> {
@@ -90,7 +103,7 @@ impl ConvertableFrom<ARGB> for AHSV {
             let h_adj = if h < 0_f32 {
                 h + 1_f32
             }
-            else if h > 0_f32 {
+            else if h > 1_f32 {
                 h - 1_f32
             }
             else {
@@ -107,17 +120,100 @@ impl ConvertableFrom<ARGB> for AHSV {
     }
 }
 
+impl ConvertableFrom<ARGB> for AOKLAB {
+    type Error = ImageError;
+    type Options = ();
+
+    fn try_convert_from(value: ARGB, _: Self::Options) -> Result<Self, Self::Error> {
+        let r = srgb_to_linear(value.red);
+        let g = srgb_to_linear(value.green);
+        let b = srgb_to_linear(value.blue);
+
+        //Linear sRGB to cone responses
+        let l = (0.41222146 * r + 0.53633255 * g + 0.051445995 * b).cbrt();
+        let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+        let s = (0.08830246 * r + 0.28171885 * g + 0.6299787 * b).cbrt();
+
+        Ok(Self {
+            l: 0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+            a: 1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+            b: 0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+            alpha: value.alpha
+        })
+    }
+}
+
+impl ConvertableFrom<ARGB> for AYCBCR {
+    type Error = ImageError;
+    type Options = ();
+
+    fn try_convert_from(value: ARGB, _: Self::Options) -> Result<Self, Self::Error> {
+        let (r, g, b) = (value.red as f32, value.green as f32, value.blue as f32);
+
+        //Operations are kept in the same order as the bulk conversions, so results match exactly
+        Ok(Self {
+            y: to_channel(0.299 * r + 0.587 * g + 0.114 * b),
+            cb: to_channel(128_f32 - 0.168736 * r - 0.331264 * g + 0.5 * b),
+            cr: to_channel(128_f32 + 0.5 * r - 0.418688 * g - 0.081312 * b),
+            alpha: value.alpha
+        })
+    }
+}
+
+impl ConvertableFrom<AYCBCR> for ARGB {
+    type Error = ImageError;
+    type Options = ();
+
+    fn try_convert_from(value: AYCBCR, _: Self::Options) -> Result<Self, Self::Error> {
+        let y = value.y as f32;
+        let cb = value.cb as f32 - 128_f32;
+        let cr = value.cr as f32 - 128_f32;
+
+        Ok(Self {
+            red: to_channel(y + 1.402 * cr),
+            green: to_channel(y - 0.344136 * cb - 0.714136 * cr),
+            blue: to_channel(y + 1.772 * cb),
+            alpha: value.alpha
+        })
+    }
+}
+
+///
+/// Round a value to the nearest 8-bit channel, clamping it to [0, 255]
+///
+pub(crate) fn to_channel(value: f32) -> u8 {
+    (value.clamp(0_f32, 255_f32) + 0.5_f32) as u8
+}
+
+impl ConvertableFrom<AOKLAB> for ARGB {
+    type Error = ImageError;
+    type Options = ();
+
+    fn try_convert_from(value: AOKLAB, _: Self::Options) -> Result<Self, Self::Error> {
+        let l = (value.l + 0.39633778 * value.a + 0.21580376 * value.b).powi(3);
+        let m = (value.l - 0.105561346 * value.a - 0.06385417 * value.b).powi(3);
+        let s = (value.l - 0.08948418 * value.a - 1.2914855 * value.b).powi(3);
+
+        Ok(Self {
+            red: linear_to_srgb(4.0767417 * l - 3.3077116 * m + 0.23096994 * s),
+            green: linear_to_srgb(-1.268438 * l + 2.6097574 * m - 0.34131938 * s),
+            blue: linear_to_srgb(-0.0041960863 * l - 0.7034186 * m + 1.7076147 * s),
+            alpha: value.alpha
+        })
+    }
+}
+
 impl ConvertableFrom<AXYZ> for ARGB {
-    type Error = ();
+    type Error = ImageError;
     type Options = ();
 
     fn try_convert_from(_value: AXYZ, _: Self::Options) -> Result<Self, Self::Error> {
-        todo!();
+        Err(ImageError::Unsupported(String::from("Converting XYZ to RGB is not supported yet.")))
     }
 }
 
 impl ConvertableFrom<AXYZ> for ALAB {
-    type Error = ();
+    type Error = ImageError;
     type Options = LABSettings;
 
     fn try_convert_from(value: AXYZ, options: Self::Options) -> Result<Self, Self::Error> {
@@ -144,64 +240,64 @@ impl ConvertableFrom<AXYZ> for ALAB {
 }
 
 impl ConvertableFrom<AXYZ> for AHSV {
-    type Error = ();
+    type Error = ImageError;
     type Options = ();
 
     fn try_convert_from(_value: AXYZ, _: Self::Options) -> Result<Self, Self::Error> {
-        todo!();
+        Err(ImageError::Unsupported(String::from("Converting XYZ to HSV is not supported yet.")))
     }
 }
 
 impl ConvertableFrom<ALAB> for ARGB {
-    type Error = ();
+    type Error = ImageError;
     type Options = LABSettings;
 
     fn try_convert_from(_value: ALAB, _options: Self::Options) -> Result<Self, Self::Error> {
-        todo!();
+        Err(ImageError::Unsupported(String::from("Converting LAB to RGB is not supported yet.")))
     }
 }
 
 impl ConvertableFrom<ALAB> for AXYZ {
-    type Error = ();
+    type Error = ImageError;
     type Options = LABSettings;
 
     fn try_convert_from(_value: ALAB, _options: Self::Options) -> Result<Self, Self::Error> {
-        todo!();
+        Err(ImageError::Unsupported(String::from("Converting LAB to XYZ is not supported yet.")))
     }
 }
 
 impl ConvertableFrom<ALAB> for AHSV {
-    type Error = ();
+    type Error = ImageError;
     type Options = LABSettings;
 
     fn try_convert_from(_value: ALAB, _options: Self::Options) -> Result<Self, Self::Error> {
-        todo!();
+        Err(ImageError::Unsupported(String::from("Converting LAB to HSV is not supported yet.")))
     }
 }
 
 impl ConvertableFrom<AHSV> for ARGB {
-    type Error = ();
+    type Error = ImageError;
     type Options = ();
 
     fn try_convert_from(_value: AHSV, _: Self::Options) -> Result<Self, Self::Error> {
-        todo!();
+        Err(ImageError::Unsupported(String::from("Converting HSV to RGB is not supported yet.")))
     }
 }
 
 impl ConvertableFrom<AHSV> for AXYZ {
-    type Error = ();
+    type Error = ImageError;
     type Options = ();
 
     fn try_convert_from(_value: AHSV, _: Self::Options) -> Result<Self, Self::Error> {
-        todo!();
+        Err(ImageError::Unsupported(String::from("Converting HSV to XYZ is not supported yet.")))
     }
 }
 
 impl ConvertableFrom<AHSV> for ALAB {
-    type Error = ();
+    type Error = ImageError;
     type Options = LABSettings;
 
     fn try_convert_from(_value: AHSV, _options: Self::Options) -> Result<Self, Self::Error> {
-        todo!();
+        Err(ImageError::Unsupported(String::from("Converting HSV to LAB is not supported yet.")))
     }
 }
\ No newline at end of file
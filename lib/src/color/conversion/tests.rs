@@ -0,0 +1,86 @@
+use super::*;
+
+#[test]
+fn hsv_hue_in_range() -> Result<(), String> {
+    //Hues of orange, chartreuse, azure and rose, one in each half of each sextant
+    let cases = [
+        (ARGB { alpha: 0xFF, red: 0xFF, green: 0x80, blue: 0x00 }, 30_f32),
+        (ARGB { alpha: 0xFF, red: 0x80, green: 0xFF, blue: 0x00 }, 90_f32),
+        (ARGB { alpha: 0xFF, red: 0x00, green: 0x80, blue: 0xFF }, 210_f32),
+        (ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x80 }, 330_f32)
+    ];
+
+    for (color, expected) in cases {
+        let hsv = AHSV::try_convert_from(color, ())?;
+
+        if !(0_f32..1_f32).contains(&hsv.h) || (hsv.h * 360_f32 - expected).abs() > 1_f32 {
+            return Err(format!("Expected a hue of {expected} for {}, but got {}.", color.to_hex(), hsv.h * 360_f32));
+        }
+
+        if hsv.s != 1_f32 || hsv.v != 1_f32 {
+            return Err(format!("Expected full saturation and value for {}, but got {hsv:?}.", color.to_hex()));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn lab_d65() -> Result<(), String> {
+    let white = ALAB::try_convert_from(ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF }, LABSettings::D65)?;
+    let red = ALAB::try_convert_from(ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x00 }, LABSettings::D65)?;
+
+    if (white.l - 100_f32).abs() > 0.1 || white.a.abs() > 0.1 || white.b.abs() > 0.1 {
+        return Err(format!("Expected white to be L 100, a 0, b 0, but got {white:?}."));
+    }
+
+    //Published value of sRGB red under D65
+    if (red.l - 53.24).abs() > 0.5 || (red.a - 80.09).abs() > 0.5 || (red.b - 67.20).abs() > 0.5 {
+        return Err(format!("Expected red to be L 53.24, a 80.09, b 67.20, but got {red:?}."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn oklab_reference() -> Result<(), String> {
+    //Published values for white and the sRGB primaries
+    let cases = [
+        (ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF }, (1_f32, 0_f32, 0_f32)),
+        (ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x00 }, (0.62796, 0.22486, 0.12585)),
+        (ARGB { alpha: 0xFF, red: 0x00, green: 0xFF, blue: 0x00 }, (0.86644, -0.23389, 0.17950)),
+        (ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0xFF }, (0.45201, -0.03246, -0.31153))
+    ];
+
+    for (color, (l, a, b)) in cases {
+        let lab = AOKLAB::try_convert_from(color, ())?;
+
+        if (lab.l - l).abs() > 0.001 || (lab.a - a).abs() > 0.001 || (lab.b - b).abs() > 0.001 {
+            return Err(format!("Expected {} to be L {l}, a {a}, b {b}, but got {lab:?}.", color.to_hex()));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn oklab_round_trip() -> Result<(), String> {
+    let grays = (0..=255_u8).map(|n| ARGB { alpha: 0xFF, red: n, green: n, blue: n });
+    let colors = (0..4096_u32).map(|n| ARGB {
+        alpha: (n * 7) as u8,
+        red: ((n >> 8) * 17) as u8,
+        green: (((n >> 4) & 0xF) * 17) as u8,
+        blue: ((n & 0xF) * 17) as u8
+    });
+
+    for color in grays.chain(colors) {
+        let lab = AOKLAB::try_convert_from(color, ())?;
+        let back = ARGB::try_convert_from(lab, ())?;
+
+        if back != color {
+            return Err(format!("Expected {} to survive OKLab, but got {} from {lab:?}.", color.to_hex(), back.to_hex()));
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,317 @@
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::error::ImageError;
+use crate::image::Image;
+use super::{ARGB, bulk};
+
+///
+/// Size, in bytes, of the header of an ICC profile, which is followed by the tag table
+///
+const HEADER_SIZE: usize = 128;
+
+///
+/// Size, in bytes, of each entry in the tag table
+///
+const TAG_ENTRY_SIZE: usize = 12;
+
+///
+/// Convert colors from the profile connection space (CIE XYZ, D50) to linear sRGB,
+/// with the Bradford adaptation from D50 to the D65 white point of sRGB
+///
+const PCS_TO_LINEAR_SRGB: [[f32; 3]; 3] = [
+    [3.133856, -1.616867, -0.4906146],
+    [-0.9787684, 1.9161415, 0.033454],
+    [0.0719453, -0.2289914, 1.4052427]
+];
+
+///
+/// The curve which maps a channel of a device color to linear light
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToneCurve {
+    ///
+    /// Raise the channel to the power
+    ///
+    Gamma(f32),
+    ///
+    /// Look the channel up in a table of evenly spaced samples, from 0 to 65535
+    ///
+    Table(Vec<u16>),
+    ///
+    /// One of the ICC parametric curve functions, from 0 to 4, with its parameters g, a, b, c, d, e and f
+    ///
+    Parametric(u16, [f32; 7])
+}
+
+impl ToneCurve {
+    ///
+    /// Map a channel, in [0, 1], to linear light
+    ///
+    pub fn linearize(&self, value: f32) -> f32 {
+        let value = value.clamp(0_f32, 1_f32);
+
+        match self {
+            Self::Gamma(gamma) => value.powf(*gamma),
+            Self::Table(samples) => match samples.as_slice() {
+                [] => value,
+                [only] => *only as f32 / 65535_f32,
+                samples => {
+                    let position = value * (samples.len() - 1) as f32;
+                    let below = (position.floor() as usize).min(samples.len() - 2);
+                    let t = position - below as f32;
+
+                    let sample = |index: usize| samples.get(index).copied().unwrap_or_default() as f32;
+                    (sample(below) + (sample(below + 1) - sample(below)) * t) / 65535_f32
+                }
+            },
+            Self::Parametric(function, [g, a, b, c, d, e, f]) => {
+                //Raising a negative base to a fractional power is undefined, so treat it as 0
+                let power = |x: f32| (a * x + b).max(0_f32).powf(*g);
+
+                match function {
+                    0 => value.powf(*g),
+                    1 => if value >= -b / a { power(value) } else { 0_f32 },
+                    2 => if value >= -b / a { power(value) + c } else { *c },
+                    3 => if value >= *d { power(value) } else { c * value },
+                    _ => if value >= *d { power(value) + e } else { c * value + f }
+                }
+            }
+        }
+    }
+}
+
+///
+/// An ICC profile describing an RGB color space with a matrix and a tone curve per channel,
+/// which is what most images embed. Profiles built from lookup tables are not supported.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct IccProfile {
+    raw: Vec<u8>,
+    curves: [ToneCurve; 3],
+    ///
+    /// Convert linear device colors straight to linear sRGB
+    ///
+    to_linear_srgb: [[f32; 3]; 3],
+    ///
+    /// Linear light value of each 8-bit value of each channel
+    ///
+    linear: [[f32; 256]; 3]
+}
+
+impl IccProfile {
+    ///
+    /// The bytes the profile was read from, to embed it when encoding
+    ///
+    pub fn bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    ///
+    /// The tone curves of the red, green and blue channels
+    ///
+    pub fn curves(&self) -> &[ToneCurve; 3] {
+        &self.curves
+    }
+
+    ///
+    /// Convert a color in the profile's color space to sRGB. Alpha is left untouched.
+    ///
+    pub fn to_srgb(&self, color: &ARGB) -> ARGB {
+        let mut channels = [0_u8; 3];
+        bulk::linear_to_srgb_all(&self.linear_srgb(color), &mut channels);
+
+        let [red, green, blue] = channels;
+        ARGB { red, green, blue, alpha: color.alpha }
+    }
+
+    ///
+    /// Convert an image whose colors are in the profile's color space to sRGB
+    ///
+    pub fn apply(&self, image: &Image) -> Image {
+        #[cfg(feature = "parallel")]
+        let rows: Vec<Vec<ARGB>> = (0..image.height()).into_par_iter().map(|j| self.row_to_srgb(image.row(j))).collect();
+
+        #[cfg(not(feature = "parallel"))]
+        let rows: Vec<Vec<ARGB>> = (0..image.height()).map(|j| self.row_to_srgb(image.row(j))).collect();
+
+        Image::new_pixels(image.width(), image.height(), rows.concat())
+    }
+
+    ///
+    /// The linear sRGB channels of a color in the profile's color space, clamped to [0, 1]
+    ///
+    fn linear_srgb(&self, color: &ARGB) -> [f32; 3] {
+        let [red, green, blue] = self.linear;
+        let device = [red[color.red as usize], green[color.green as usize], blue[color.blue as usize]];
+
+        self.to_linear_srgb.map(|row| {
+            (row[0] * device[0] + row[1] * device[1] + row[2] * device[2]).clamp(0_f32, 1_f32)
+        })
+    }
+
+    ///
+    /// Convert a row of colors to sRGB, encoding the channels of the whole row at once
+    ///
+    fn row_to_srgb(&self, row: &[ARGB]) -> Vec<ARGB> {
+        let linear: Vec<f32> = row.iter().flat_map(|color| self.linear_srgb(color)).collect();
+        let mut channels = vec![0_u8; linear.len()];
+        bulk::linear_to_srgb_all(&linear, &mut channels);
+
+        row.iter()
+            .zip(channels.chunks_exact(3))
+            .map(|(color, channels)| match *channels {
+                [red, green, blue] => ARGB { red, green, blue, alpha: color.alpha },
+                _ => *color
+            })
+            .collect()
+    }
+}
+
+///
+/// Read an ICC profile from its bytes
+///
+impl TryFrom<&[u8]> for IccProfile {
+    type Error = ImageError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() < HEADER_SIZE + 4 {
+            return Err(ImageError::Decode(format!("ICC profile is {} bytes, which is too short for its header.", value.len())));
+        }
+
+        if signature(value, 36)? != *b"acsp" {
+            return Err(ImageError::Decode(String::from("ICC profile signature must be 'acsp'.")));
+        }
+
+        let color_space = signature(value, 16)?;
+        let connection_space = signature(value, 20)?;
+
+        if color_space != *b"RGB " || connection_space != *b"XYZ " {
+            return Err(ImageError::Unsupported(format!(
+                "ICC profile converts {} to {}, but only RGB to XYZ profiles are supported.",
+                String::from_utf8_lossy(&color_space), String::from_utf8_lossy(&connection_space)
+            )));
+        }
+
+        //Find each tag which is needed in the tag table
+        let tag_count = read_u32(value, HEADER_SIZE)? as usize;
+
+        let tag = |name: &[u8; 4]| -> Result<&[u8], ImageError> {
+            (0..tag_count)
+                .map(|index| HEADER_SIZE + 4 + index * TAG_ENTRY_SIZE)
+                .find(|entry| signature(value, *entry).is_ok_and(|found| found == *name))
+                .ok_or_else(|| ImageError::Unsupported(format!(
+                    "ICC profile has no '{}' tag; only matrix and tone curve profiles are supported.", String::from_utf8_lossy(name)
+                )))
+                .and_then(|entry| {
+                    let offset = read_u32(value, entry + 4)? as usize;
+                    let size = read_u32(value, entry + 8)? as usize;
+
+                    offset.checked_add(size)
+                        .and_then(|end| value.get(offset..end))
+                        .ok_or_else(|| ImageError::Decode(format!("ICC profile '{}' tag is outside of the profile.", String::from_utf8_lossy(name))))
+                })
+        };
+
+        //Each colorant is a column of the matrix from linear device colors to the connection space
+        let colorants = [read_xyz(tag(b"rXYZ")?)?, read_xyz(tag(b"gXYZ")?)?, read_xyz(tag(b"bXYZ")?)?];
+        let curves = [read_curve(tag(b"rTRC")?)?, read_curve(tag(b"gTRC")?)?, read_curve(tag(b"bTRC")?)?];
+
+        let to_linear_srgb = PCS_TO_LINEAR_SRGB.map(|row| {
+            std::array::from_fn(|column| (0..3).map(|k| row[k] * colorants[column][k]).sum())
+        });
+
+        let linear = [0, 1, 2].map(|channel| {
+            std::array::from_fn(|value| curves[channel].linearize(value as f32 / 255_f32))
+        });
+
+        Ok(Self {
+            raw: value.to_vec(),
+            curves,
+            to_linear_srgb,
+            linear
+        })
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, ImageError> {
+    bytes.get(offset..offset + 4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u32::from_be_bytes)
+        .ok_or_else(|| ImageError::Decode(format!("ICC profile ends before offset {}.", offset + 4)))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, ImageError> {
+    bytes.get(offset..offset + 2)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u16::from_be_bytes)
+        .ok_or_else(|| ImageError::Decode(format!("ICC profile ends before offset {}.", offset + 2)))
+}
+
+///
+/// Read a signed fixed point number with 16 fractional bits
+///
+fn read_s15_fixed16(bytes: &[u8], offset: usize) -> Result<f32, ImageError> {
+    Ok(read_u32(bytes, offset)? as i32 as f32 / 65536_f32)
+}
+
+fn signature(bytes: &[u8], offset: usize) -> Result<[u8; 4], ImageError> {
+    Ok(read_u32(bytes, offset)?.to_be_bytes())
+}
+
+///
+/// Read a tag of type 'XYZ '
+///
+fn read_xyz(tag: &[u8]) -> Result<[f32; 3], ImageError> {
+    if signature(tag, 0)? != *b"XYZ " {
+        return Err(ImageError::Decode(String::from("ICC profile colorant is not an 'XYZ ' tag.")));
+    }
+
+    Ok([read_s15_fixed16(tag, 8)?, read_s15_fixed16(tag, 12)?, read_s15_fixed16(tag, 16)?])
+}
+
+///
+/// Read a tag of type 'curv' or 'para'
+///
+fn read_curve(tag: &[u8]) -> Result<ToneCurve, ImageError> {
+    match &signature(tag, 0)? {
+        b"curv" => {
+            let count = read_u32(tag, 8)? as usize;
+
+            match count {
+                0 => Ok(ToneCurve::Gamma(1_f32)),
+                //A single entry is a gamma, with 8 fractional bits
+                1 => Ok(ToneCurve::Gamma(read_u16(tag, 12)? as f32 / 256_f32)),
+                _ => (0..count)
+                    .map(|index| read_u16(tag, 12 + index * 2))
+                    .collect::<Result<Vec<u16>, ImageError>>()
+                    .map(ToneCurve::Table)
+            }
+        },
+        b"para" => {
+            let function = read_u16(tag, 8)?;
+
+            //The number of parameters each function has, which are g, a, b, c, d, e and f in order
+            let count = match function {
+                0 => 1,
+                1 => 3,
+                2 => 4,
+                3 => 5,
+                4 => 7,
+                other => return Err(ImageError::Unsupported(format!("ICC parametric curve function {other} is not supported.")))
+            };
+
+            let mut parameters = [0_f32; 7];
+
+            for (index, parameter) in parameters.iter_mut().enumerate().take(count) {
+                *parameter = read_s15_fixed16(tag, 12 + index * 4)?;
+            }
+
+            Ok(ToneCurve::Parametric(function, parameters))
+        },
+        other => Err(ImageError::Decode(format!("ICC profile tone curve has type '{}', but must be 'curv' or 'para'.", String::from_utf8_lossy(other))))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,160 @@
+use super::*;
+
+//Colorants of sRGB, adapted to D50
+const SRGB_COLORANTS: [[f32; 3]; 3] = [
+    [0.4361, 0.2225, 0.0139],
+    [0.3851, 0.7169, 0.0971],
+    [0.1431, 0.0606, 0.7141]
+];
+
+fn fixed(value: f32) -> [u8; 4] {
+    ((value * 65536_f32).round() as i32).to_be_bytes()
+}
+
+fn xyz_tag(xyz: [f32; 3]) -> Vec<u8> {
+    let mut tag = b"XYZ \0\0\0\0".to_vec();
+    xyz.iter().for_each(|value| tag.extend(fixed(*value)));
+    tag
+}
+
+fn srgb_curve() -> Vec<u8> {
+    let mut tag = b"para\0\0\0\0\0\x03\0\0".to_vec();
+    [2.4, 1_f32 / 1.055, 0.055 / 1.055, 1_f32 / 12.92, 0.04045].iter().for_each(|value| tag.extend(fixed(*value)));
+    tag
+}
+
+fn gamma_curve(gamma: f32) -> Vec<u8> {
+    let mut tag = b"curv\0\0\0\0\0\0\0\x01".to_vec();
+    tag.extend(((gamma * 256_f32) as u16).to_be_bytes());
+    tag
+}
+
+///
+/// Build an RGB matrix/TRC profile with the given tags
+///
+fn profile(tags: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let mut header = vec![0_u8; HEADER_SIZE];
+    header[16..20].copy_from_slice(b"RGB ");
+    header[20..24].copy_from_slice(b"XYZ ");
+    header[36..40].copy_from_slice(b"acsp");
+
+    let mut table = (tags.len() as u32).to_be_bytes().to_vec();
+    let mut data = Vec::new();
+    let data_start = HEADER_SIZE + 4 + tags.len() * TAG_ENTRY_SIZE;
+
+    for (name, tag) in tags {
+        table.extend(*name);
+        table.extend(((data_start + data.len()) as u32).to_be_bytes());
+        table.extend((tag.len() as u32).to_be_bytes());
+        data.extend(tag);
+    }
+
+    let mut profile = [header, table, data].concat();
+    let size = (profile.len() as u32).to_be_bytes();
+    profile[0..4].copy_from_slice(&size);
+    profile
+}
+
+fn matrix_profile(curve: Vec<u8>) -> Vec<u8> {
+    profile(&[
+        (b"rXYZ", xyz_tag(SRGB_COLORANTS[0])),
+        (b"gXYZ", xyz_tag(SRGB_COLORANTS[1])),
+        (b"bXYZ", xyz_tag(SRGB_COLORANTS[2])),
+        (b"rTRC", curve.clone()),
+        (b"gTRC", curve.clone()),
+        (b"bTRC", curve)
+    ])
+}
+
+#[test]
+fn srgb_profile_is_identity() -> Result<(), String> {
+    let bytes = matrix_profile(srgb_curve());
+    let profile = IccProfile::try_from(&bytes[..]).map_err(|e| e.to_string())?;
+
+    if profile.bytes() != &bytes[..] {
+        return Err(String::from("Expected the profile to keep its bytes."));
+    }
+
+    for value in (0..=255_u8).step_by(15) {
+        let color = ARGB { alpha: 0x80, red: value, green: 255 - value, blue: value / 2 };
+        let converted = profile.to_srgb(&color);
+
+        let close = |a: u8, b: u8| a.abs_diff(b) <= 1;
+
+        if converted.alpha != color.alpha || !close(converted.red, color.red)
+            || !close(converted.green, color.green) || !close(converted.blue, color.blue) {
+            return Err(format!("Expected {color:?} to be unchanged, got {converted:?}."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn linear_profile_is_encoded() -> Result<(), String> {
+    let bytes = matrix_profile(gamma_curve(1_f32));
+    let profile = IccProfile::try_from(&bytes[..]).map_err(|e| e.to_string())?;
+
+    let gray = profile.to_srgb(&ARGB { alpha: 0xFF, red: 128, green: 128, blue: 128 });
+    let expected = crate::color::linear_to_srgb(128_f32 / 255_f32);
+
+    if [gray.red, gray.green, gray.blue].iter().any(|channel| channel.abs_diff(expected) > 1) {
+        Err(format!("Expected linear gray 128 to become {expected}, got {gray:?}."))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn image_converts_as_each_color() -> Result<(), String> {
+    let bytes = matrix_profile(gamma_curve(1.8_f32));
+    let profile = IccProfile::try_from(&bytes[..]).map_err(|e| e.to_string())?;
+
+    let image = Image::from_fn(7, 5, |i, j| ARGB { alpha: (i * 40) as u8, red: (i * 36) as u8, green: (j * 60) as u8, blue: ((i + j) * 23) as u8 });
+    let converted = profile.apply(&image);
+
+    for (j, i) in (0..5).flat_map(|j| (0..7).map(move |i| (j, i))) {
+        let expected = profile.to_srgb(&image.row(j)[i]);
+
+        if converted.row(j)[i] != expected {
+            return Err(format!("Expected pixel ({i}, {j}) to become {expected:?}, got {:?}.", converted.row(j)[i]));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn malformed_profiles_are_rejected() -> Result<(), String> {
+    let valid = matrix_profile(srgb_curve());
+
+    let mut signature = valid.clone();
+    signature[36] = b'x';
+
+    let mut gray = valid.clone();
+    gray[16..20].copy_from_slice(b"GRAY");
+
+    let mut outside = valid.clone();
+    outside[HEADER_SIZE + 8..HEADER_SIZE + 12].copy_from_slice(&u32::MAX.to_be_bytes());
+
+    let cases: Vec<(&str, Vec<u8>, &str)> = vec![
+        ("short", valid[..64].to_vec(), "too short"),
+        ("signature", signature, "acsp"),
+        ("gray", gray, "only RGB"),
+        ("missing tag", profile(&[(b"rXYZ", xyz_tag(SRGB_COLORANTS[0]))]), "gXYZ"),
+        ("outside", outside, "outside"),
+        ("truncated", valid[..valid.len() - 4].to_vec(), "ICC profile")
+    ];
+
+    for (name, bytes, expected) in cases {
+        match IccProfile::try_from(&bytes[..]) {
+            Ok(_) => return Err(format!("Expected the {name} profile to be rejected.")),
+            Err(e) if !e.to_string().contains(expected) => {
+                return Err(format!("Expected the {name} profile error to mention '{expected}', got '{e}'."))
+            },
+            Err(_) => ()
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,231 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error::ImageError;
+use crate::image::Image;
+use super::ARGB;
+
+///
+/// Bounds on the number of samples along each axis of a .cube LUT, from the specification
+///
+const MIN_SIZE: usize = 2;
+const MAX_SIZE: usize = 256;
+
+///
+/// A 3D color lookup table, which maps each color to another by sampling a
+/// grid of output colors, as used to apply color-grading looks
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubeLut {
+    title: Option<String>,
+    size: usize,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    ///
+    /// Output color of each point of the grid, with red changing fastest, then green, then blue
+    ///
+    table: Vec<[f32; 3]>
+}
+
+impl CubeLut {
+    ///
+    /// Create a LUT with size samples along each axis over the domain [0, 1].
+    /// The table must have size^3 entries, with red changing fastest.
+    ///
+    pub fn new(size: usize, table: Vec<[f32; 3]>) -> Result<Self, ImageError> {
+        if !(MIN_SIZE..=MAX_SIZE).contains(&size) {
+            return Err(ImageError::InvalidArgument(format!("LUT size must be between {MIN_SIZE} and {MAX_SIZE}, but was {size}.")));
+        }
+
+        if table.len() != size * size * size {
+            return Err(ImageError::InvalidArgument(format!(
+                "LUT of size {size} must have {} entries, but has {}.", size * size * size, table.len()
+            )));
+        }
+
+        Ok(Self {
+            title: None,
+            size,
+            domain_min: [0_f32; 3],
+            domain_max: [1_f32; 3],
+            table
+        })
+    }
+
+    ///
+    /// A LUT which maps every color to itself
+    ///
+    pub fn identity(size: usize) -> Result<Self, ImageError> {
+        let step = |index: usize| index as f32 / (size.max(MIN_SIZE) - 1) as f32;
+
+        let table = (0..size * size * size)
+            .map(|index| [step(index % size), step((index / size) % size), step(index / (size * size))])
+            .collect();
+
+        Self::new(size, table)
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    ///
+    /// Number of samples along each axis
+    ///
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    ///
+    /// The lowest and highest input value of each channel which the table covers
+    ///
+    pub fn domain(&self) -> ([f32; 3], [f32; 3]) {
+        (self.domain_min, self.domain_max)
+    }
+
+    ///
+    /// Map a color, with each channel in the domain, by trilinear interpolation
+    /// between the 8 samples around it. Colors outside the domain are clamped to it.
+    ///
+    pub fn lookup(&self, color: [f32; 3]) -> [f32; 3] {
+        let last = (self.size - 1) as f32;
+
+        //Position of the color in the grid, split into the sample below and the fraction toward the next
+        let position: [(usize, f32); 3] = core::array::from_fn(|channel| {
+            let (min, max) = (self.domain_min[channel], self.domain_max[channel]);
+            let position = ((color[channel] - min) / (max - min)).clamp(0_f32, 1_f32) * last;
+            let below = (position as usize).min(self.size - 2);
+            (below, position - below as f32)
+        });
+
+        let sample = |red: usize, green: usize, blue: usize| {
+            self.table[red + (green + blue * self.size) * self.size]
+        };
+
+        let lerp = |a: [f32; 3], b: [f32; 3], t: f32| -> [f32; 3] {
+            core::array::from_fn(|channel| a[channel] + (b[channel] - a[channel]) * t)
+        };
+
+        let [(r, tr), (g, tg), (b, tb)] = position;
+
+        let near = lerp(
+            lerp(sample(r, g, b), sample(r + 1, g, b), tr),
+            lerp(sample(r, g + 1, b), sample(r + 1, g + 1, b), tr),
+            tg
+        );
+
+        let far = lerp(
+            lerp(sample(r, g, b + 1), sample(r + 1, g, b + 1), tr),
+            lerp(sample(r, g + 1, b + 1), sample(r + 1, g + 1, b + 1), tr),
+            tg
+        );
+
+        lerp(near, far, tb)
+    }
+
+    ///
+    /// Map a color, with each channel scaled from [0, 255] to [0, 1]. Alpha is left untouched.
+    ///
+    pub fn to_color(&self, color: &ARGB) -> ARGB {
+        let [red, green, blue] = self.lookup([color.red, color.green, color.blue].map(|channel| channel as f32 / 255_f32))
+            .map(|channel| (channel.clamp(0_f32, 1_f32) * 255_f32).round() as u8);
+
+        ARGB {
+            alpha: color.alpha,
+            red,
+            green,
+            blue
+        }
+    }
+
+    ///
+    /// Map every pixel of an image through the LUT
+    ///
+    pub fn apply(&self, image: &Image) -> Image {
+        image.map_pixels(|pixel| self.to_color(pixel))
+    }
+}
+
+///
+/// Read a LUT from the text of an Adobe .cube file. Only 3D LUTs are supported.
+///
+impl TryFrom<&str> for CubeLut {
+    type Error = ImageError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut title = None;
+        let mut size = None;
+        let mut domain_min = [0_f32; 3];
+        let mut domain_max = [1_f32; 3];
+        let mut table = Vec::new();
+
+        for (number, line) in value.lines().enumerate().map(|(index, line)| (index + 1, line.trim())) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let rest = rest.trim();
+
+            match keyword {
+                "TITLE" => title = Some(rest.trim_matches('"').to_string()),
+                "LUT_3D_SIZE" => size = Some(rest.parse::<usize>()
+                    .map_err(|_| ImageError::Decode(format!("LUT size '{rest}' on line {number} is not a number.")))?),
+                "LUT_1D_SIZE" => return Err(ImageError::Unsupported(String::from("1D LUTs are not supported."))),
+                "DOMAIN_MIN" => domain_min = read_triple(rest, number)?,
+                "DOMAIN_MAX" => domain_max = read_triple(rest, number)?,
+                //Written by some tools in place of DOMAIN_MIN and DOMAIN_MAX, with one range for every channel
+                "LUT_3D_INPUT_RANGE" => {
+                    let range = rest.split_whitespace()
+                        .map(|value| value.parse::<f32>())
+                        .collect::<Result<Vec<_>, _>>();
+
+                    match range.as_deref() {
+                        Ok([min, max]) => {
+                            domain_min = [*min; 3];
+                            domain_max = [*max; 3];
+                        },
+                        _ => return Err(ImageError::Decode(format!("Expected a minimum and maximum on line {number}.")))
+                    }
+                },
+                _ if keyword.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.') => {
+                    table.push(read_triple(line, number)?);
+                },
+                //Other keywords are extensions of other tools, which don't change the table
+                _ => ()
+            }
+        }
+
+        let size = size.ok_or_else(|| ImageError::Decode(String::from("LUT has no LUT_3D_SIZE.")))?;
+
+        if (0..3).any(|channel| domain_min[channel] >= domain_max[channel]) {
+            return Err(ImageError::Decode(format!("LUT domain minimum {domain_min:?} must be below its maximum {domain_max:?}.")));
+        }
+
+        let lut = Self::new(size, table).map_err(|e| match e {
+            ImageError::InvalidArgument(message) => ImageError::Decode(message),
+            e => e
+        })?;
+
+        Ok(Self {
+            title,
+            domain_min,
+            domain_max,
+            ..lut
+        })
+    }
+}
+
+///
+/// Read three whitespace separated numbers
+///
+fn read_triple(value: &str, line: usize) -> Result<[f32; 3], ImageError> {
+    let values = value.split_whitespace()
+        .map(|value| value.parse::<f32>().ok().filter(|value| value.is_finite()))
+        .collect::<Option<Vec<_>>>();
+
+    match values.as_deref() {
+        Some([red, green, blue]) => Ok([*red, *green, *blue]),
+        _ => Err(ImageError::Decode(format!("Expected three numbers on line {line}, but found '{value}'.")))
+    }
+}
\ No newline at end of file
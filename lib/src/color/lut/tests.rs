@@ -0,0 +1,86 @@
+use super::*;
+
+///
+/// A LUT of size 2 which inverts every channel
+///
+const INVERT: &str = "# Invert
+TITLE \"Invert\"
+LUT_3D_SIZE 2
+
+1.0 1.0 1.0
+0.0 1.0 1.0
+1.0 0.0 1.0
+0.0 0.0 1.0
+1.0 1.0 0.0
+0.0 1.0 0.0
+1.0 0.0 0.0
+0.0 0.0 0.0
+";
+
+#[test]
+fn parse_and_apply() -> Result<(), String> {
+    let lut = CubeLut::try_from(INVERT)?;
+
+    if lut.title() != Some("Invert") || lut.size() != 2 || lut.domain() != ([0_f32; 3], [1_f32; 3]) {
+        return Err(format!("Unexpected LUT header {lut:?}."));
+    }
+
+    let image = Image::new_pixels(2, 1, vec![
+        ARGB { alpha: 0x80, red: 0xFF, green: 0x40, blue: 0x00 },
+        ARGB { alpha: 0xFF, red: 0x10, green: 0x80, blue: 0xC0 }
+    ]);
+
+    let inverted = lut.apply(&image);
+    let expected = image.map_pixels(|pixel| ARGB { alpha: pixel.alpha, red: !pixel.red, green: !pixel.green, blue: !pixel.blue });
+
+    if inverted != expected {
+        return Err(format!("Expected trilinear interpolation to invert the image, got {inverted:?}."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn identity_and_domain() -> Result<(), String> {
+    let identity = CubeLut::identity(5)?;
+    let image = Image::new_pixels(3, 1, vec![
+        ARGB { alpha: 0xFF, red: 0x12, green: 0x34, blue: 0x56 },
+        ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x7F },
+        ARGB { alpha: 0x00, red: 0x01, green: 0xFE, blue: 0x80 }
+    ]);
+
+    if identity.apply(&image) != image {
+        return Err(String::from("Expected an identity LUT to leave the image unchanged."));
+    }
+
+    //Inputs outside of a narrower domain are clamped to its edges
+    let narrow = CubeLut::try_from(INVERT.replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 2\nDOMAIN_MIN 0 0 0\nDOMAIN_MAX 0.5 0.5 0.5").as_str())?;
+    let mapped = narrow.lookup([0.25, 0.75, 0_f32]);
+
+    if mapped.iter().zip([0.5, 0_f32, 1_f32]).any(|(actual, expected)| (actual - expected).abs() > 0.001) {
+        return Err(format!("Expected the domain to be respected, got {mapped:?}."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn invalid_luts() -> Result<(), String> {
+    let cases = [
+        (INVERT.replace("LUT_3D_SIZE 2", ""), "LUT_3D_SIZE"),
+        (INVERT.replace("0.0 0.0 0.0\n", ""), "8 entries"),
+        (INVERT.replace("0.0 0.0 0.0\n", "0.0 0.0\n"), "line 12"),
+        (INVERT.replace("LUT_3D_SIZE 2", "LUT_1D_SIZE 2"), "1D"),
+        (INVERT.replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 2\nDOMAIN_MIN 1 1 1"), "below")
+    ];
+
+    for (text, expected) in cases {
+        match CubeLut::try_from(text.as_str()) {
+            Ok(_) => return Err(format!("Expected a LUT to be rejected for '{expected}'.")),
+            Err(e) if !e.to_string().contains(expected) => return Err(format!("Expected the error to mention '{expected}', got '{e}'.")),
+            Err(_) => ()
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,42 @@
+use super::*;
+
+#[test]
+fn nearest_named_exact() -> Result<(), String> {
+    for (name, rgb) in NAMED_COLORS {
+        let color = ARGB::from_u32(*rgb, false).with_alpha(0x40);
+        let (nearest, value) = nearest_named(&color);
+
+        if nearest != *name || value != color.with_alpha(0xFF) {
+            return Err(format!("Expected {name} for {}, but got {nearest} ({}).", color.to_hex(), value.to_hex()));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn nearest_named_close() -> Result<(), String> {
+    let cases = [
+        (ARGB { alpha: 0xFF, red: 0xF8, green: 0x02, blue: 0x06 }, "red"),
+        (ARGB { alpha: 0xFF, red: 0x1A, green: 0x1B, blue: 0x72 }, "midnightblue"),
+        (ARGB { alpha: 0xFF, red: 0x7E, green: 0x81, blue: 0x80 }, "gray")
+    ];
+
+    for (color, expected) in cases {
+        let (name, _) = nearest_named(&color);
+
+        if name != expected {
+            return Err(format!("Expected {expected} for {}, but got {name}.", color.to_hex()));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn named_ignores_case() -> Result<(), String> {
+    match (named("RebeccaPurple"), named("notacolor")) {
+        (Some(color), None) if color == ARGB::from_u32(0xFF663399, false) => Ok(()),
+        other => Err(format!("Unexpected lookup {other:?}."))
+    }
+}
\ No newline at end of file
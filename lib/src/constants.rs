@@ -15,8 +15,337 @@ pub mod bitmap {
     /// 
     pub const INFO_HEADER_SIZE: u32 = 40;
 
+    ///
+    /// The sizes of each known version of the info header, in bytes:
+    /// BITMAPINFOHEADER, the Adobe V2 and V3 extensions, V4 and V5
+    /// 
+    pub const INFO_HEADER_SIZES: [u32; 5] = [40, 52, 56, 108, 124];
+
+    ///
+    /// The size of the Adobe V2 info header, which adds red, green and blue masks, in bytes
+    /// 
+    pub const V2_INFO_HEADER_SIZE: u32 = 52;
+
+    ///
+    /// The size of the Adobe V3 info header, which adds an alpha mask, in bytes
+    /// 
+    pub const V3_INFO_HEADER_SIZE: u32 = 56;
+
+    ///
+    /// The size of the BITMAPV4HEADER, in bytes
+    /// 
+    pub const V4_INFO_HEADER_SIZE: u32 = 108;
+
+    ///
+    /// The size of the BITMAPV5HEADER, in bytes
+    /// 
+    pub const V5_INFO_HEADER_SIZE: u32 = 124;
+
+    ///
+    /// The size of the red, green and blue masks which follow
+    /// a BITMAPINFOHEADER when compression is BI_BITFIELDS
+    /// 
+    pub const BITFIELDS_SIZE: usize = 12;
+
     ///
     /// The size of the color table is this times the number of records
     /// 
     pub const COLOR_TABLE_SIZE_FACTOR: u32 = 4;
+
+    ///
+    /// The most pixels a bitmap may have, i.e. 16384x16384. Guards against
+    /// malformed headers declaring sizes that would exhaust memory to decode.
+    /// 
+    pub const MAX_PIXELS: usize = 1 << 28;
+
+    ///
+    /// The most bytes of pixel data a bitmap may be missing, beyond as many as it
+    /// has, for the missing pixels to still be filled in when decoding leniently
+    /// 
+    pub const MAX_MISSING_PIXEL_DATA: usize = 1 << 16;
+
+    ///
+    /// How many bytes a progressive decode reads at a time, and so
+    /// roughly how often it reports the rows which have arrived
+    /// 
+    pub const PROGRESSIVE_CHUNK_SIZE: usize = 1 << 16;
+
+    ///
+    /// Roughly how many bytes of scanlines are encoded at a time
+    /// before being written out
+    /// 
+    pub const ENCODE_CHUNK_SIZE: usize = 1 << 16;
+
+    ///
+    /// Pixels less opaque than this are set in the AND mask when encoding one
+    /// 
+    pub const MASK_ALPHA_THRESHOLD: u8 = 0x80;
+
+    ///
+    /// Values of the compression field of the info header
+    /// 
+    pub mod compression {
+        pub const BI_RGB: u32 = 0;
+        pub const BI_RLE8: u32 = 1;
+        pub const BI_RLE4: u32 = 2;
+        pub const BI_BITFIELDS: u32 = 3;
+        pub const BI_JPEG: u32 = 4;
+        pub const BI_PNG: u32 = 5;
+    }
+
+    ///
+    /// Values of the color space type field of the V4 and V5 info headers,
+    /// most of which are their ASCII names read as a big endian integer
+    /// 
+    pub mod color_space {
+        pub const CALIBRATED_RGB: u32 = 0;
+        pub const SRGB: u32 = u32::from_be_bytes(*b"sRGB");
+        pub const WINDOWS_COLOR_SPACE: u32 = u32::from_be_bytes(*b"Win ");
+        pub const PROFILE_LINKED: u32 = u32::from_be_bytes(*b"LINK");
+        pub const PROFILE_EMBEDDED: u32 = u32::from_be_bytes(*b"MBED");
+    }
+
+    ///
+    /// Values of the rendering intent field of the V5 info header
+    /// 
+    pub mod intent {
+        pub const BUSINESS: u32 = 1;
+        pub const GRAPHICS: u32 = 2;
+        pub const IMAGES: u32 = 4;
+        pub const ABS_COLORIMETRIC: u32 = 8;
+    }
+}
+
+pub mod image {
+    ///
+    /// The most pixels an image made to a given size, as by a resize, generator or
+    /// recipe, may have. The sizes come from arguments, so are limited as for decoding.
+    /// 
+    pub const MAX_PIXELS: usize = super::bitmap::MAX_PIXELS;
+
+    ///
+    /// The largest radius a filter from a recipe may have. The windows of larger
+    /// radii would take far too long to go through for each pixel.
+    /// 
+    pub const MAX_FILTER_RADIUS: usize = 1 << 8;
+}
+
+pub mod png {
+    ///
+    /// Every png starts with these bytes
+    /// 
+    pub const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    ///
+    /// The most pixels a png may have, as for bitmaps
+    /// 
+    pub const MAX_PIXELS: usize = super::bitmap::MAX_PIXELS;
+
+    ///
+    /// The size of the IHDR chunk's data, in bytes
+    /// 
+    pub const HEADER_SIZE: usize = 13;
+
+    ///
+    /// The most bytes an embedded ICC profile may inflate to
+    /// 
+    pub const MAX_ICC_PROFILE_SIZE: usize = 1 << 24;
+
+    ///
+    /// Types of the chunks which are read. Other chunks are skipped,
+    /// unless the case of their first letter marks them as critical.
+    /// 
+    pub mod chunk {
+        pub const IHDR: [u8; 4] = *b"IHDR";
+        pub const PLTE: [u8; 4] = *b"PLTE";
+        pub const TRNS: [u8; 4] = *b"tRNS";
+        pub const IDAT: [u8; 4] = *b"IDAT";
+        pub const IEND: [u8; 4] = *b"IEND";
+        pub const EXIF: [u8; 4] = *b"eXIf";
+        pub const ICCP: [u8; 4] = *b"iCCP";
+    }
+
+    ///
+    /// Values of the color type field of the header
+    /// 
+    pub mod color_type {
+        pub const GRAYSCALE: u8 = 0;
+        pub const RGB: u8 = 2;
+        pub const INDEXED: u8 = 3;
+        pub const GRAYSCALE_ALPHA: u8 = 4;
+        pub const RGBA: u8 = 6;
+    }
+}
+
+pub mod gif {
+    ///
+    /// Every gif starts with one of these, for the two versions of the format
+    /// 
+    pub const SIGNATURE_87A: [u8; 6] = *b"GIF87a";
+    pub const SIGNATURE_89A: [u8; 6] = *b"GIF89a";
+
+    ///
+    /// The size of the logical screen descriptor following the signature, in bytes
+    /// 
+    pub const SCREEN_DESCRIPTOR_SIZE: usize = 7;
+
+    ///
+    /// The size of an image descriptor, after its separator, in bytes
+    /// 
+    pub const IMAGE_DESCRIPTOR_SIZE: usize = 9;
+
+    ///
+    /// The most bits an LZW code may have, and so the
+    /// most entries the code table may hold
+    /// 
+    pub const MAX_CODE_SIZE: u8 = 12;
+    pub const MAX_CODES: usize = 1 << MAX_CODE_SIZE;
+
+    ///
+    /// The most pixels a gif's logical screen or any of its frames may have, as for bitmaps
+    /// 
+    pub const MAX_PIXELS: usize = super::bitmap::MAX_PIXELS;
+
+    ///
+    /// Bytes introducing each kind of block
+    /// 
+    pub mod block {
+        pub const EXTENSION: u8 = 0x21;
+        pub const IMAGE: u8 = 0x2C;
+        pub const TRAILER: u8 = 0x3B;
+    }
+
+    ///
+    /// Labels following the introducer of an extension
+    /// 
+    pub mod extension {
+        pub const GRAPHIC_CONTROL: u8 = 0xF9;
+        pub const APPLICATION: u8 = 0xFF;
+    }
+
+    ///
+    /// Identifier of the application extension giving how many times to loop
+    /// 
+    pub const NETSCAPE: [u8; 11] = *b"NETSCAPE2.0";
+}
+
+pub mod netpbm {
+    ///
+    /// The most pixels a netpbm image may have, as for bitmaps
+    /// 
+    pub const MAX_PIXELS: usize = super::bitmap::MAX_PIXELS;
+
+    ///
+    /// The longest line of samples written to an ASCII netpbm image
+    /// 
+    pub const MAX_LINE_LENGTH: usize = 70;
+}
+
+pub mod dds {
+    ///
+    /// Every dds starts with these bytes
+    /// 
+    pub const SIGNATURE: [u8; 4] = *b"DDS ";
+
+    ///
+    /// The most pixels a dds may have, as for bitmaps
+    /// 
+    pub const MAX_PIXELS: usize = super::bitmap::MAX_PIXELS;
+
+    ///
+    /// The size of the header after the signature, which is also
+    /// the value of its size field
+    /// 
+    pub const HEADER_SIZE: usize = 124;
+
+    ///
+    /// The size of the pixel format within the header, and its offset
+    /// 
+    pub const PIXEL_FORMAT_SIZE: usize = 32;
+    pub const PIXEL_FORMAT_OFFSET: usize = 72;
+
+    ///
+    /// Pixels per side of a block of compressed texture
+    /// 
+    pub const BLOCK_SIZE: usize = 4;
+
+    ///
+    /// Flags of the header, marking which fields are set
+    /// 
+    pub mod flags {
+        pub const PITCH: u32 = 0x8;
+    }
+
+    ///
+    /// Flags of the pixel format
+    /// 
+    pub mod pixel_format {
+        pub const ALPHA_PIXELS: u32 = 0x1;
+        pub const ALPHA: u32 = 0x2;
+        pub const FOUR_CC: u32 = 0x4;
+        pub const RGB: u32 = 0x40;
+        pub const LUMINANCE: u32 = 0x2_0000;
+    }
+
+    ///
+    /// Codes naming the compression of the pixels
+    /// 
+    pub mod four_cc {
+        pub const DXT1: [u8; 4] = *b"DXT1";
+        pub const DXT5: [u8; 4] = *b"DXT5";
+    }
+}
+
+pub mod ilbm {
+    ///
+    /// Every ilbm is an IFF FORM of this type
+    /// 
+    pub const FORM: [u8; 4] = *b"FORM";
+    pub const FORM_TYPE: [u8; 4] = *b"ILBM";
+
+    ///
+    /// The most pixels an ilbm may have, as for bitmaps
+    /// 
+    pub const MAX_PIXELS: usize = super::bitmap::MAX_PIXELS;
+
+    ///
+    /// The size of the BMHD chunk's data, in bytes
+    /// 
+    pub const HEADER_SIZE: usize = 20;
+
+    ///
+    /// Types of the chunks which are read. Other chunks are skipped.
+    /// 
+    pub mod chunk {
+        pub const BMHD: [u8; 4] = *b"BMHD";
+        pub const CMAP: [u8; 4] = *b"CMAP";
+        pub const CAMG: [u8; 4] = *b"CAMG";
+        pub const BODY: [u8; 4] = *b"BODY";
+    }
+
+    ///
+    /// Values of the masking field of the header
+    /// 
+    pub mod masking {
+        pub const NONE: u8 = 0;
+        pub const HAS_MASK: u8 = 1;
+        pub const TRANSPARENT_COLOR: u8 = 2;
+        pub const LASSO: u8 = 3;
+    }
+
+    ///
+    /// Values of the compression field of the header
+    /// 
+    pub mod compression {
+        pub const NONE: u8 = 0;
+        pub const BYTE_RUN_1: u8 = 1;
+    }
+
+    ///
+    /// Flags of the Amiga display mode in the CAMG chunk
+    /// 
+    pub mod mode {
+        pub const EXTRA_HALFBRITE: u32 = 0x80;
+        pub const HOLD_AND_MODIFY: u32 = 0x800;
+    }
 }
\ No newline at end of file
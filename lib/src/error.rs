@@ -0,0 +1,75 @@
+use alloc::string::{String, ToString};
+use core::{error, fmt};
+
+#[cfg(feature = "std")]
+use std::io;
+
+///
+/// An error from reading, writing or operating on an image
+///
+#[derive(Debug)]
+pub enum ImageError {
+    ///
+    /// The bytes are not a valid image in the format being read
+    ///
+    Decode(String),
+    ///
+    /// The image can't be written with the options given
+    ///
+    Encode(String),
+    ///
+    /// An argument was out of range, i.e. a region outside of the image
+    ///
+    InvalidArgument(String),
+    ///
+    /// The image or operation is valid, but not supported yet,
+    /// i.e. a bit depth or color conversion
+    ///
+    Unsupported(String),
+    ///
+    /// Reading or writing a file failed
+    ///
+    #[cfg(feature = "std")]
+    Io(io::Error)
+}
+
+pub type Result<T> = core::result::Result<T, ImageError>;
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(message)
+            | Self::Encode(message)
+            | Self::InvalidArgument(message)
+            | Self::Unsupported(message) => f.write_str(message),
+            #[cfg(feature = "std")]
+            Self::Io(err) => err.fmt(f)
+        }
+    }
+}
+
+impl error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for ImageError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+///
+/// For callers which report errors as plain messages
+///
+impl From<ImageError> for String {
+    fn from(value: ImageError) -> Self {
+        value.to_string()
+    }
+}
\ No newline at end of file
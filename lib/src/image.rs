@@ -1,14 +1,73 @@
 pub mod format;
+pub mod transform;
+pub mod orientation;
+pub mod resolution;
+pub mod buffer;
 
-use std::cell::RefCell;
+#[cfg(feature = "std")]
+pub mod noise;
+#[cfg(feature = "std")]
+pub mod dither;
+#[cfg(feature = "std")]
+pub mod composite;
+#[cfg(feature = "std")]
+pub mod filter;
+#[cfg(feature = "std")]
+pub mod stylize;
+#[cfg(feature = "std")]
+pub mod text;
+#[cfg(feature = "std")]
+pub mod histogram;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod compare;
+#[cfg(feature = "std")]
+pub mod pool;
+#[cfg(feature = "std")]
+pub mod generate;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod pipeline;
+#[cfg(feature = "std")]
+pub mod recipe;
+#[cfg(feature = "std")]
+pub mod animation;
+#[cfg(feature = "std")]
+pub mod quantization;
+
+
+#[cfg(test)]
+mod tests;
+
+use alloc::{format, vec, vec::Vec};
+use core::cell::RefCell;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 use super::color;
+use crate::error::ImageError;
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Image {
     width: usize,
     height: usize,
-    pixels: Vec<color::ARGB>
+    pixels: Vec<color::ARGB>,
+    metadata: ImageMetadata
+}
+
+///
+/// Information about an image which isn't its pixels, carried from
+/// the file it was decoded from to the file it is encoded to
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ImageMetadata {
+    ///
+    /// The ICC color profile the colors of the pixels are in
+    ///
+    pub icc_profile: Option<Vec<u8>>
 }
 
 impl Image {
@@ -16,16 +75,37 @@ impl Image {
         Image {
             width,
             height,
-            pixels: vec![color::ARGB::default(); width * height]
+            pixels: vec![color::ARGB::default(); width * height],
+            metadata: ImageMetadata::default()
         }
     }
 
+    ///
+    /// Make an image of the given pixels, in rows from the top. Panics if
+    /// there isn't exactly one pixel for each of width x height.
+    ///
     pub fn new_pixels(width: usize, height: usize, pixels: Vec<color::ARGB>) -> Image {
-        Image {
+        match Self::try_new_pixels(width, height, pixels) {
+            Ok(image) => image,
+            Err(err) => panic!("{err}")
+        }
+    }
+
+    ///
+    /// Make an image of the given pixels, in rows from the top. Fails if
+    /// there isn't exactly one pixel for each of width x height.
+    ///
+    pub fn try_new_pixels(width: usize, height: usize, pixels: Vec<color::ARGB>) -> Result<Image, ImageError> {
+        if width.checked_mul(height) != Some(pixels.len()) {
+            return Err(ImageError::InvalidArgument(format!("Image {width}x{height} can't have {} pixels.", pixels.len())));
+        }
+
+        Ok(Image {
             width,
             height,
-            pixels
-        }
+            pixels,
+            metadata: ImageMetadata::default()
+        })
     }
 
     fn calculate_index(&self, i: usize, j: usize) -> usize {
@@ -33,19 +113,27 @@ impl Image {
     }
 
     pub fn get(&self, i: usize, j: usize) -> Option<color::ARGB> {
-        let index = self.calculate_index(i, j);
-
-        if index > self.pixels.capacity() {
+        if i >= self.width || j >= self.height {
             None
         }
         else {
-            Some(self.pixels[index])
+            self.pixels.get(self.calculate_index(i, j)).copied()
         }
     }
 
-    pub fn set(&mut self, value: color::ARGB, i: usize, j: usize) {
+    ///
+    /// Set the pixel at (i, j), which is an error if it is outside of the image
+    ///
+    pub fn set(&mut self, value: color::ARGB, i: usize, j: usize) -> Result<(), ImageError> {
         let index = self.calculate_index(i, j);
-        self.pixels[index] = value;
+
+        match self.pixels.get_mut(index) {
+            Some(pixel) if i < self.width && j < self.height => {
+                *pixel = value;
+                Ok(())
+            },
+            _ => Err(ImageError::InvalidArgument(format!("Pixel {i},{j} is outside of the {}x{} image.", self.width, self.height)))
+        }
     }
 
     pub fn width(&self) -> usize {
@@ -60,13 +148,103 @@ impl Image {
         self.width() * self.height()
     }
 
+    ///
+    /// The pixels of row j, or an empty row if j is outside of the image
+    ///
     pub fn row(&self, j: usize) -> &[color::ARGB] {
-        &self.pixels[(self.calculate_index(0, j))..(self.calculate_index(self.width, j))]
+        if j >= self.height {
+            return &[];
+        }
+
+        self.pixels.get(self.calculate_index(0, j)..self.calculate_index(self.width, j)).unwrap_or_default()
     }
 
-    pub fn iter(&self) -> ImageIterator {
+    pub fn iter(&self) -> ImageIterator<'_> {
         ImageIterator::new(self)
     }
+
+    pub fn metadata(&self) -> &ImageMetadata {
+        &self.metadata
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut ImageMetadata {
+        &mut self.metadata
+    }
+
+    ///
+    /// Attach the metadata to the image, replacing any it had
+    ///
+    pub fn with_metadata(self, metadata: ImageMetadata) -> Image {
+        Image { metadata, ..self }
+    }
+
+    ///
+    /// Take the pixel buffer out of the image
+    ///
+    pub fn into_pixels(self) -> Vec<color::ARGB> {
+        self.pixels
+    }
+
+    ///
+    /// Replace the contents of the image, reusing its pixel buffer. The buffer is
+    /// passed to fill, which returns the new dimensions; on error, the image is left empty.
+    ///
+    pub(crate) fn refill<F>(&mut self, fill: F) -> Result<(), ImageError> where F: FnOnce(&mut Vec<color::ARGB>) -> Result<(usize, usize), ImageError> {
+        self.metadata = ImageMetadata::default();
+
+        match fill(&mut self.pixels) {
+            Ok((width, height)) => {
+                self.width = width;
+                self.height = height;
+                Ok(())
+            },
+            Err(err) => {
+                self.pixels.clear();
+                self.width = 0;
+                self.height = 0;
+                Err(err)
+            }
+        }
+    }
+
+    ///
+    /// Create an image by computing each pixel from its position.
+    /// With the parallel feature, rows are computed in parallel.
+    ///
+    pub(crate) fn from_fn<F>(width: usize, height: usize, f: F) -> Image where F: Fn(usize, usize) -> color::ARGB + Send + Sync {
+        let mut pixels = vec![color::ARGB::default(); width * height];
+
+        if width > 0 {
+            #[cfg(feature = "parallel")]
+            let rows = pixels.par_chunks_mut(width);
+
+            #[cfg(not(feature = "parallel"))]
+            let rows = pixels.chunks_mut(width);
+
+            rows.enumerate().for_each(|(j, row)| {
+                for (i, pixel) in row.iter_mut().enumerate() {
+                    *pixel = f(i, j);
+                }
+            });
+        }
+
+        Image::new_pixels(width, height, pixels)
+    }
+
+    ///
+    /// Create an image of the same size by mapping each pixel.
+    /// With the parallel feature, pixels are mapped in parallel.
+    ///
+    #[cfg(feature = "std")]
+    pub(crate) fn map_pixels<F>(&self, f: F) -> Image where F: Fn(&color::ARGB) -> color::ARGB + Send + Sync {
+        #[cfg(feature = "parallel")]
+        let pixels = self.pixels.par_iter().map(f).collect();
+
+        #[cfg(not(feature = "parallel"))]
+        let pixels = self.pixels.iter().map(f).collect();
+
+        Image::new_pixels(self.width, self.height, pixels)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -0,0 +1,249 @@
+#[cfg(test)]
+mod tests;
+
+use core::time::Duration;
+
+use crate::color;
+use super::Image;
+
+///
+/// What happens to the area of a frame once it has been shown,
+/// before the next frame is drawn
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Disposal {
+    ///
+    /// Leave the frame in place, for the next frame to be drawn over
+    ///
+    #[default]
+    Keep,
+    ///
+    /// Clear the area of the frame to transparent
+    ///
+    Background,
+    ///
+    /// Restore the area of the frame to what it was before it was drawn
+    ///
+    Previous
+}
+
+///
+/// One image of an animation, drawn onto the canvas with its top-left
+/// corner at (left, top), and shown for the delay
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub image: Image,
+    pub delay: Duration,
+    pub disposal: Disposal,
+    pub left: usize,
+    pub top: usize
+}
+
+impl Frame {
+    ///
+    /// A frame covering the canvas from its top-left corner, which is kept
+    ///
+    pub fn new(image: Image, delay: Duration) -> Self {
+        Self {
+            image,
+            delay,
+            disposal: Disposal::default(),
+            left: 0,
+            top: 0
+        }
+    }
+
+    pub fn with_disposal(mut self, disposal: Disposal) -> Self {
+        self.disposal = disposal;
+        self
+    }
+
+    pub fn with_offset(mut self, left: usize, top: usize) -> Self {
+        self.left = left;
+        self.top = top;
+        self
+    }
+}
+
+///
+/// A sequence of frames drawn in turn onto a canvas of a fixed size.
+/// Whatever the source of the frames, such as an animated format or a
+/// directory of stills, an animation is played by composing its frames.
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AnimatedImage {
+    width: usize,
+    height: usize,
+    frames: Vec<Frame>,
+    ///
+    /// How many times to play the frames, or 0 to repeat forever
+    ///
+    loop_count: usize
+}
+
+impl AnimatedImage {
+    ///
+    /// An animation with no frames, on a canvas of the given size
+    ///
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            frames: Vec::new(),
+            loop_count: 0
+        }
+    }
+
+    ///
+    /// An animation of the given frames, on a canvas just large enough to hold them all
+    ///
+    pub fn from_frames(frames: Vec<Frame>) -> Self {
+        let width = frames.iter().map(|frame| frame.left + frame.image.width()).max().unwrap_or_default();
+        let height = frames.iter().map(|frame| frame.top + frame.image.height()).max().unwrap_or_default();
+
+        Self {
+            width,
+            height,
+            frames,
+            loop_count: 0
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn push(&mut self, frame: Frame) {
+        self.frames.push(frame);
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, Frame> {
+        self.frames.iter()
+    }
+
+    pub fn loop_count(&self) -> usize {
+        self.loop_count
+    }
+
+    pub fn with_loop_count(mut self, loop_count: usize) -> Self {
+        self.loop_count = loop_count;
+        self
+    }
+
+    ///
+    /// How long one play through every frame takes
+    ///
+    pub fn total_duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.delay).sum()
+    }
+
+    ///
+    /// The index of the frame shown at the given time into one play through
+    /// the frames, or None if the time is past the end
+    ///
+    pub fn frame_at(&self, time: Duration) -> Option<usize> {
+        let mut end = Duration::ZERO;
+
+        self.frames.iter().position(|frame| {
+            end += frame.delay;
+            time < end
+        })
+    }
+
+    ///
+    /// Draw each frame onto the canvas in turn, yielding the whole canvas as it
+    /// is shown for each frame, along with its delay. The canvas starts transparent.
+    ///
+    pub fn compose(&self) -> Compose<'_> {
+        Compose {
+            frames: self.frames.iter(),
+            canvas: Image::new(self.width, self.height),
+            dispose: None
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a AnimatedImage {
+    type Item = &'a Frame;
+    type IntoIter = core::slice::Iter<'a, Frame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+///
+/// The canvas of an animation as each frame is shown
+///
+pub struct Compose<'a> {
+    frames: core::slice::Iter<'a, Frame>,
+    canvas: Image,
+    ///
+    /// The area of the last frame, and what to restore it to, if anything;
+    /// None for the first frame, or if it is kept
+    ///
+    dispose: Option<(&'a Frame, Option<Image>)>
+}
+
+impl Iterator for Compose<'_> {
+    type Item = (Image, Duration);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = self.frames.next()?;
+
+        //Dispose of the last frame only once the next is due, so the final frame stays shown
+        if let Some((last, previous)) = self.dispose.take() {
+            match previous {
+                Some(previous) => self.canvas = previous,
+                None => self.canvas.fill_rect(last.left, last.top, last.image.width(), last.image.height(), color::ARGB::default())
+            }
+        }
+
+        self.dispose = match frame.disposal {
+            Disposal::Keep => None,
+            Disposal::Background => Some((frame, None)),
+            Disposal::Previous => Some((frame, Some(self.canvas.clone())))
+        };
+
+        self.canvas.draw_image(&frame.image, frame.left, frame.top);
+
+        Some((self.canvas.clone(), frame.delay))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.frames.size_hint()
+    }
+}
+
+impl Image {
+    ///
+    /// Set every pixel of the rectangle with top-left corner (x, y) to the
+    /// color. Anything falling outside of this image is clipped.
+    ///
+    fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: color::ARGB) {
+        let width = width.min(self.width.saturating_sub(x));
+        let height = height.min(self.height.saturating_sub(y));
+
+        for j in y..(y + height) {
+            let start = self.calculate_index(x, j);
+            self.pixels[start..(start + width)].fill(color);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,63 @@
+use super::*;
+
+const RED: color::ARGB = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0, blue: 0 };
+const BLUE: color::ARGB = color::ARGB { alpha: 0xFF, red: 0, green: 0, blue: 0xFF };
+const CLEAR: color::ARGB = color::ARGB { alpha: 0, red: 0, green: 0, blue: 0 };
+
+fn ms(milliseconds: u64) -> Duration {
+    Duration::from_millis(milliseconds)
+}
+
+#[test]
+fn animation_timing() -> Result<(), String> {
+    let animation = AnimatedImage::from_frames(vec![
+        Frame::new(Image::solid(4, 2, RED), ms(100)),
+        Frame::new(Image::solid(2, 2, BLUE), ms(50)).with_offset(3, 1)
+    ]);
+
+    if (animation.width(), animation.height()) != (5, 3) {
+        return Err(format!("Expected a 5x3 canvas, got {}x{}.", animation.width(), animation.height()));
+    }
+
+    if animation.total_duration() != ms(150) {
+        return Err(format!("Expected 150ms in total, got {:?}.", animation.total_duration()));
+    }
+
+    let shown: Vec<Option<usize>> = [0, 99, 100, 149, 150].into_iter().map(|t| animation.frame_at(ms(t))).collect();
+
+    if shown != [Some(0), Some(0), Some(1), Some(1), None] {
+        return Err(format!("Unexpected frames shown over time: {shown:?}."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn animation_compose_disposal() -> Result<(), String> {
+    let mut animation = AnimatedImage::new(3, 1);
+
+    animation.push(Frame::new(Image::solid(3, 1, RED), ms(10)));
+    animation.push(Frame::new(Image::solid(1, 1, BLUE), ms(10)).with_offset(1, 0).with_disposal(Disposal::Previous));
+    animation.push(Frame::new(Image::solid(1, 1, BLUE), ms(10)).with_offset(2, 0).with_disposal(Disposal::Background));
+    animation.push(Frame::new(Image::new(1, 1), ms(10)));
+
+    let canvases: Vec<Vec<color::ARGB>> = animation.compose()
+        .map(|(canvas, _)| canvas.into_pixels())
+        .collect();
+
+    let expected = [
+        vec![RED, RED, RED],
+        //Drawn over the kept first frame
+        vec![RED, BLUE, RED],
+        //The second frame is restored to what was under it
+        vec![RED, RED, BLUE],
+        //The third frame is cleared
+        vec![RED, RED, CLEAR]
+    ];
+
+    if canvases != expected {
+        return Err(format!("Unexpected canvases {canvases:?}."));
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,289 @@
+#[cfg(test)]
+mod tests;
+
+use alloc::{format, vec, vec::Vec};
+
+use crate::color;
+use crate::error::ImageError;
+use super::Image;
+
+///
+/// The layout of the pixels in a raw buffer. Each row of a buffer starts
+/// stride bytes after the last, so rows may be padded past their pixels.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PixelFormat {
+    ///
+    /// 3 bytes per pixel, red first
+    ///
+    Rgb8,
+    ///
+    /// 3 bytes per pixel, blue first
+    ///
+    Bgr8,
+    ///
+    /// 4 bytes per pixel, red first and alpha last
+    ///
+    Rgba8,
+    ///
+    /// 4 bytes per pixel, blue first and alpha last
+    ///
+    Bgra8,
+    ///
+    /// 4 bytes per pixel, alpha first and blue last
+    ///
+    Argb8,
+    ///
+    /// 1 byte of luminance (Rec. 709) per pixel
+    ///
+    Gray8,
+    ///
+    /// A plane of 1 byte of luma per pixel, then planes of blue and red
+    /// chroma with 1 byte per 2x2 pixels, in BT.601 limited range. Rows of
+    /// the luma plane are stride bytes apart, and rows of each chroma plane
+    /// half of that, rounded up.
+    ///
+    Yuv420p
+}
+
+impl PixelFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rgb8 => "RGB8",
+            Self::Bgr8 => "BGR8",
+            Self::Rgba8 => "RGBA8",
+            Self::Bgra8 => "BGRA8",
+            Self::Argb8 => "ARGB8",
+            Self::Gray8 => "Gray8",
+            Self::Yuv420p => "YUV420p"
+        }
+    }
+
+    ///
+    /// Bytes per pixel; for Yuv420p, those of the luma plane
+    ///
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            Self::Rgb8 | Self::Bgr8 => 3,
+            Self::Rgba8 | Self::Bgra8 | Self::Argb8 => 4,
+            Self::Gray8 | Self::Yuv420p => 1
+        }
+    }
+
+    ///
+    /// The stride of a row without padding. Fails if it doesn't fit in a usize.
+    ///
+    pub fn min_stride(&self, width: usize) -> Result<usize, ImageError> {
+        width.checked_mul(self.bytes_per_pixel())
+            .ok_or_else(|| ImageError::InvalidArgument(format!("A row of {width} {} pixels is too large.", self.name())))
+    }
+
+    ///
+    /// Number of bytes needed to hold an image of the given size. The
+    /// last row of a packed format doesn't need its padding. Fails if
+    /// it doesn't fit in a usize.
+    ///
+    pub fn buffer_size(&self, width: usize, height: usize, stride: usize) -> Result<usize, ImageError> {
+        let size = match self {
+            _ if height == 0 => Some(0),
+            Self::Yuv420p => stride.checked_mul(height)
+                .zip(stride.div_ceil(2).checked_mul(height.div_ceil(2)).and_then(|chroma| chroma.checked_mul(2)))
+                .and_then(|(luma, chroma)| luma.checked_add(chroma)),
+            _ => stride.checked_mul(height - 1)
+                .and_then(|rows| rows.checked_add(self.min_stride(width).ok()?))
+        };
+
+        size.ok_or_else(|| ImageError::InvalidArgument(format!("A {width}x{height} {} buffer with stride {stride} is too large.", self.name())))
+    }
+
+    fn validate(&self, width: usize, height: usize, stride: usize) -> Result<(), ImageError> {
+        if stride < self.min_stride(width)? {
+            return Err(ImageError::InvalidArgument(format!("Stride {stride} is too short for {width} {} pixels.", self.name())));
+        }
+
+        if width.checked_mul(height).is_none() {
+            return Err(ImageError::InvalidArgument(format!("Image {width}x{height} is too large.")));
+        }
+
+        //Whole rows are written, so the padding of the last one has to fit too
+        if stride.checked_mul(height).is_none() {
+            return Err(ImageError::InvalidArgument(format!("A {width}x{height} {} buffer with stride {stride} is too large.", self.name())));
+        }
+
+        self.buffer_size(width, height, stride).map(|_| ())
+    }
+
+    ///
+    /// The color of one pixel of a packed format
+    ///
+    fn read(&self, bytes: &[u8]) -> color::ARGB {
+        let [alpha, red, green, blue] = match self {
+            Self::Rgb8 => [0xFF, bytes[0], bytes[1], bytes[2]],
+            Self::Bgr8 => [0xFF, bytes[2], bytes[1], bytes[0]],
+            Self::Rgba8 => [bytes[3], bytes[0], bytes[1], bytes[2]],
+            Self::Bgra8 => [bytes[3], bytes[2], bytes[1], bytes[0]],
+            Self::Argb8 => [bytes[0], bytes[1], bytes[2], bytes[3]],
+            Self::Gray8 | Self::Yuv420p => [0xFF, bytes[0], bytes[0], bytes[0]]
+        };
+
+        color::ARGB { alpha, red, green, blue }
+    }
+
+    ///
+    /// Write one pixel of a packed format
+    ///
+    fn write(&self, pixel: color::ARGB, bytes: &mut [u8]) {
+        let color::ARGB { alpha, red, green, blue } = pixel;
+
+        match self {
+            Self::Rgb8 => bytes.copy_from_slice(&[red, green, blue]),
+            Self::Bgr8 => bytes.copy_from_slice(&[blue, green, red]),
+            Self::Rgba8 => bytes.copy_from_slice(&[red, green, blue, alpha]),
+            Self::Bgra8 => bytes.copy_from_slice(&[blue, green, red, alpha]),
+            Self::Argb8 => bytes.copy_from_slice(&[alpha, red, green, blue]),
+            Self::Gray8 | Self::Yuv420p => {
+                //Rec. 709 weights, scaled to sum to 256
+                bytes[0] = ((54 * red as u32 + 183 * green as u32 + 19 * blue as u32 + 128) >> 8) as u8;
+            }
+        }
+    }
+}
+
+impl Image {
+    ///
+    /// Read an image from a headerless buffer of pixels in the given format,
+    /// with rows stride bytes apart, from the top. Formats without alpha
+    /// are read as opaque.
+    ///
+    pub fn from_raw_buffer(bytes: &[u8], width: usize, height: usize, stride: usize, format: PixelFormat) -> Result<Image, ImageError> {
+        format.validate(width, height, stride)?;
+
+        let size = format.buffer_size(width, height, stride)?;
+
+        if bytes.len() < size {
+            return Err(ImageError::InvalidArgument(format!("A {width}x{height} {} buffer with stride {stride} needs {size} bytes, but has {}.",
+                format.name(), bytes.len())));
+        }
+
+        if format == PixelFormat::Yuv420p {
+            return Ok(from_yuv420p(bytes, width, height, stride));
+        }
+
+        let bytes_per_pixel = format.bytes_per_pixel();
+        let row_size = format.min_stride(width)?;
+
+        let pixels = (0..height)
+            .flat_map(|j| bytes[(j * stride)..(j * stride + row_size)].chunks_exact(bytes_per_pixel))
+            .map(|pixel| format.read(pixel))
+            .collect();
+
+        Image::try_new_pixels(width, height, pixels)
+    }
+
+    ///
+    /// Write the image to a headerless buffer of pixels in the given format,
+    /// with rows stride bytes apart, from the top. Padding is left as zeros,
+    /// and alpha is dropped by formats without it.
+    ///
+    pub fn to_raw_buffer(&self, stride: usize, format: PixelFormat) -> Result<Vec<u8>, ImageError> {
+        format.validate(self.width, self.height, stride)?;
+
+        if format == PixelFormat::Yuv420p {
+            return Ok(to_yuv420p(self, stride));
+        }
+
+        let bytes_per_pixel = format.bytes_per_pixel();
+        let mut bytes = vec![0_u8; stride * self.height];
+
+        if self.width == 0 {
+            return Ok(bytes);
+        }
+
+        for (row, pixels) in bytes.chunks_exact_mut(stride).zip(self.pixels.chunks_exact(self.width)) {
+            for (pixel, out) in pixels.iter().zip(row.chunks_exact_mut(bytes_per_pixel)) {
+                format.write(*pixel, out);
+            }
+        }
+
+        Ok(bytes)
+    }
+}
+
+fn from_yuv420p(bytes: &[u8], width: usize, height: usize, stride: usize) -> Image {
+    let chroma_stride = stride.div_ceil(2);
+    let (luma, chroma) = bytes.split_at(stride * height);
+    let (blue, red) = chroma.split_at(chroma_stride * height.div_ceil(2));
+
+    let pixels = (0..height)
+        .flat_map(|j| (0..width).map(move |i| (i, j)))
+        .map(|(i, j)| {
+            let chroma = (j / 2) * chroma_stride + i / 2;
+            yuv_to_argb(luma[j * stride + i], blue[chroma], red[chroma])
+        })
+        .collect();
+
+    Image::new_pixels(width, height, pixels)
+}
+
+fn to_yuv420p(image: &Image, stride: usize) -> Vec<u8> {
+    let (width, height) = (image.width, image.height);
+    let chroma_stride = stride.div_ceil(2);
+    let chroma_size = chroma_stride * height.div_ceil(2);
+
+    let mut bytes = vec![0_u8; stride * height + 2 * chroma_size];
+    let (luma, chroma) = bytes.split_at_mut(stride * height);
+    let (blue, red) = chroma.split_at_mut(chroma_size);
+
+    for j in 0..height {
+        for i in 0..width {
+            luma[j * stride + i] = argb_to_luma(image.pixels[image.calculate_index(i, j)]);
+        }
+    }
+
+    //Each chroma sample is the average over its block, which may be cut short at the edges
+    for j in 0..height.div_ceil(2) {
+        for i in 0..width.div_ceil(2) {
+            let block: Vec<color::ARGB> = ((2 * j)..(2 * j + 2).min(height))
+                .flat_map(|y| ((2 * i)..(2 * i + 2).min(width)).map(move |x| (x, y)))
+                .map(|(x, y)| image.pixels[image.calculate_index(x, y)])
+                .collect();
+
+            let (u, v) = argb_to_chroma(&block);
+            blue[j * chroma_stride + i] = u;
+            red[j * chroma_stride + i] = v;
+        }
+    }
+
+    bytes
+}
+
+//BT.601 limited range conversions, in 8-bit fixed point
+
+fn argb_to_luma(pixel: color::ARGB) -> u8 {
+    let (red, green, blue) = (pixel.red as i32, pixel.green as i32, pixel.blue as i32);
+    (((66 * red + 129 * green + 25 * blue + 128) >> 8) + 16) as u8
+}
+
+fn argb_to_chroma(block: &[color::ARGB]) -> (u8, u8) {
+    let count = block.len().max(1) as i32;
+    let average = |channel: fn(&color::ARGB) -> u8| (block.iter().map(|pixel| channel(pixel) as i32).sum::<i32>() + count / 2) / count;
+    let (red, green, blue) = (average(|pixel| pixel.red), average(|pixel| pixel.green), average(|pixel| pixel.blue));
+
+    let u = ((-38 * red - 74 * green + 112 * blue + 128) >> 8) + 128;
+    let v = ((112 * red - 94 * green - 18 * blue + 128) >> 8) + 128;
+
+    (u as u8, v as u8)
+}
+
+fn yuv_to_argb(y: u8, u: u8, v: u8) -> color::ARGB {
+    let c = 298 * (y as i32 - 16);
+    let (d, e) = (u as i32 - 128, v as i32 - 128);
+    let clamp = |value: i32| ((value + 128) >> 8).clamp(0, 255) as u8;
+
+    color::ARGB {
+        alpha: 0xFF,
+        red: clamp(c + 409 * e),
+        green: clamp(c - 100 * d - 208 * e),
+        blue: clamp(c + 516 * d)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,100 @@
+use super::*;
+use alloc::string::String;
+
+#[cfg(feature = "std")]
+fn sample() -> Image {
+    let pixels = (0..12_u8)
+        .map(|n| color::ARGB { alpha: 0x80 + n, red: n * 20, green: 255 - n * 20, blue: n * 7 })
+        .collect();
+
+    Image::new_pixels(4, 3, pixels)
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn raw_buffer_round_trip() -> Result<(), String> {
+    let image = sample();
+
+    for format in [PixelFormat::Rgba8, PixelFormat::Bgra8, PixelFormat::Argb8] {
+        //Pad each row by 3 bytes, and leave off the padding of the last
+        let stride = format.min_stride(image.width())? + 3;
+        let mut bytes = image.to_raw_buffer(stride, format)?;
+        bytes.truncate(format.buffer_size(image.width(), image.height(), stride)?);
+
+        let read = Image::from_raw_buffer(&bytes, image.width(), image.height(), stride, format)?;
+
+        if read != image {
+            return Err(format!("Reading back a {} buffer changed the image.", format.name()));
+        }
+    }
+
+    let bytes = image.to_raw_buffer(12, PixelFormat::Rgb8)?;
+
+    if bytes[..6] != [0, 255, 0, 20, 235, 7] {
+        return Err(format!("Unexpected RGB8 bytes {:?}.", &bytes[..6]));
+    }
+
+    if Image::from_raw_buffer(&bytes, 4, 3, 12, PixelFormat::Rgb8)? != image.map_pixels(|pixel| pixel.with_alpha(0xFF)) {
+        return Err(String::from("Reading back an RGB8 buffer should only drop alpha."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn raw_buffer_yuv420p() -> Result<(), String> {
+    //Odd sizes leave the last chroma samples covering partial blocks
+    let gray = (0..15_u8).map(|n| color::ARGB { alpha: 0xFF, red: n * 17, green: n * 17, blue: n * 17 }).collect();
+    let image = Image::new_pixels(5, 3, gray);
+
+    let bytes = image.to_raw_buffer(6, PixelFormat::Yuv420p)?;
+
+    if bytes.len() != PixelFormat::Yuv420p.buffer_size(5, 3, 6)? || bytes.len() != 6 * 3 + 2 * 3 * 2 {
+        return Err(format!("Unexpected YUV420p buffer size {}.", bytes.len()));
+    }
+
+    let read = Image::from_raw_buffer(&bytes, 5, 3, 6, PixelFormat::Yuv420p)?;
+
+    for (expected, actual) in image.pixels.iter().zip(read.pixels.iter()) {
+        if expected.distance_manhattan(actual) > 6_f32 {
+            return Err(format!("Expected about {expected:?}, got {actual:?}."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn raw_buffer_invalid() -> Result<(), String> {
+    if !matches!(Image::from_raw_buffer(&[0; 12], 2, 2, 5, PixelFormat::Rgb8), Err(ImageError::InvalidArgument(_))) {
+        return Err(String::from("A stride shorter than a row should be rejected."));
+    }
+
+    if !matches!(Image::from_raw_buffer(&[0; 11], 2, 2, 6, PixelFormat::Rgb8), Err(ImageError::InvalidArgument(_))) {
+        return Err(String::from("A buffer too short for the image should be rejected."));
+    }
+
+    //Sizes past usize::MAX must be rejected rather than wrap around to a small buffer
+    let overflows = [
+        Image::from_raw_buffer(&[0; 12], usize::MAX / 2, 2, usize::MAX, PixelFormat::Rgba8),
+        Image::from_raw_buffer(&[0; 12], 2, usize::MAX / 2, 8, PixelFormat::Rgba8),
+        Image::from_raw_buffer(&[0; 12], 2, 3, usize::MAX, PixelFormat::Yuv420p)
+    ];
+
+    if overflows.iter().any(|result| !matches!(result, Err(ImageError::InvalidArgument(_)))) {
+        return Err(String::from("A buffer too large to address should be rejected."));
+    }
+
+    if !matches!(Image::new(2, 2).to_raw_buffer(usize::MAX, PixelFormat::Rgb8), Err(ImageError::InvalidArgument(_)))
+        || !matches!(PixelFormat::Rgba8.min_stride(usize::MAX), Err(ImageError::InvalidArgument(_))) {
+        return Err(String::from("A stride too large to address should be rejected."));
+    }
+
+    let gray = Image::from_raw_buffer(&[0, 0x80, 9, 0xFF], 2, 1, 4, PixelFormat::Gray8)?;
+
+    if gray.get(1, 0) != Some(color::ARGB { alpha: 0xFF, red: 0x80, green: 0x80, blue: 0x80 }) {
+        return Err(format!("Unexpected gray pixel {:?}.", gray.get(1, 0)));
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,243 @@
+#[cfg(test)]
+mod tests;
+
+use std::path::Path;
+
+use crate::{color::{self, lut::CubeLut}, constants};
+use crate::error::ImageError;
+use crate::utility::file;
+use super::Image;
+use super::dither::DitherMode;
+use super::filter::Filter;
+use super::format::codec;
+use super::pipeline::Pipeline;
+use super::transform::{Flip, ResizeFilter, Rotation};
+
+impl Image {
+    ///
+    /// Read and decode the image at the given path, in any registered
+    /// format, detected from its bytes
+    ///
+    pub fn open(path: impl AsRef<Path>) -> Result<Image, ImageError> {
+        let bytes = file::get_file_bytes(&path.as_ref().to_string_lossy())?;
+        codec::load(&bytes)
+    }
+
+    ///
+    /// Encode the image in the format which uses the extension of the
+    /// given path, and write it there
+    ///
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ImageError> {
+        let path = path.as_ref();
+
+        let extension = path.extension()
+            .map(|extension| extension.to_string_lossy())
+            .ok_or_else(|| ImageError::InvalidArgument(format!("Path '{}' has no extension to choose a format by.", path.display())))?;
+
+        let codec = codec::find(&extension)
+            .ok_or_else(|| ImageError::Unsupported(format!("No format uses the extension '{extension}'.")))?;
+
+        if !codec.can_encode() {
+            return Err(ImageError::Unsupported(format!("Format {} cannot be encoded.", codec.name())));
+        }
+
+        let bytes = codec.encode(self)?;
+        file::write_file_bytes(&path.to_string_lossy(), &bytes)
+    }
+
+    ///
+    /// Start a chain of operations on the image
+    ///
+    pub fn edit(self) -> ImageBuilder {
+        ImageBuilder::new(self)
+    }
+}
+
+///
+/// A chain of operations on an image. Each operation applies immediately,
+/// and the first error skips every operation after it, to be returned
+/// by build or save.
+///
+#[derive(Debug)]
+pub struct ImageBuilder {
+    image: Result<Image, ImageError>,
+    resize_filter: ResizeFilter
+}
+
+impl ImageBuilder {
+    pub fn new(image: Image) -> Self {
+        Self {
+            image: Ok(image),
+            resize_filter: ResizeFilter::default()
+        }
+    }
+
+    ///
+    /// Start a chain of operations on the image at the given path. If it
+    /// cannot be read, the error is returned once the chain ends.
+    ///
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        Self {
+            image: Image::open(path),
+            resize_filter: ResizeFilter::default()
+        }
+    }
+
+    ///
+    /// The filter used by every following resize
+    ///
+    pub fn resize_filter(mut self, filter: ResizeFilter) -> Self {
+        self.resize_filter = filter;
+        self
+    }
+
+    ///
+    /// Resize to exactly the given width and height
+    ///
+    pub fn resize(self, width: usize, height: usize) -> Self {
+        let filter = self.resize_filter;
+        self.resize_with(width, height, filter)
+    }
+
+    ///
+    /// Resize to exactly the given width and height, with the given filter
+    /// rather than the one set for the chain. The size may have at most
+    /// constants::image::MAX_PIXELS pixels.
+    ///
+    pub fn resize_with(self, width: usize, height: usize, filter: ResizeFilter) -> Self {
+        self.try_map(|image| {
+            if width == 0 || height == 0 {
+                return Err(ImageError::InvalidArgument(format!("Cannot resize to {width}x{height}.")));
+            }
+
+            if width.checked_mul(height).is_none_or(|pixels| pixels > constants::image::MAX_PIXELS) {
+                return Err(ImageError::InvalidArgument(format!("Cannot resize to {width}x{height}, more than the {} pixels allowed.", constants::image::MAX_PIXELS)));
+            }
+
+            Ok(image.resize(width, height, filter))
+        })
+    }
+
+    ///
+    /// Keep only the given rectangle, which must lie within the image
+    ///
+    pub fn crop(self, x: usize, y: usize, width: usize, height: usize) -> Self {
+        self.try_map(|image| {
+            if x.checked_add(width).is_none_or(|right| right > image.width())
+                || y.checked_add(height).is_none_or(|bottom| bottom > image.height()) {
+                return Err(ImageError::InvalidArgument(format!(
+                    "Region {width}x{height} at {x},{y} is outside of the {}x{} image.", image.width(), image.height()
+                )));
+            }
+
+            Ok(image.crop(x, y, width, height))
+        })
+    }
+
+    pub fn rotate(self, rotation: Rotation) -> Self {
+        self.map(|image| image.rotate(rotation))
+    }
+
+    pub fn flip(self, flip: Flip) -> Self {
+        self.map(|image| image.flip(flip))
+    }
+
+    pub fn filter(self, filter: Filter) -> Self {
+        self.map(|image| image.filter(filter))
+    }
+
+    pub fn grayscale(self) -> Self {
+        self.map(|image| image.grayscale())
+    }
+
+    pub fn invert(self) -> Self {
+        self.map(|image| image.invert())
+    }
+
+    pub fn brightness(self, amount: f32) -> Self {
+        self.map(|image| image.brightness(amount))
+    }
+
+    pub fn blur(self, radius: usize) -> Self {
+        self.map(|image| image.blur(radius))
+    }
+
+    ///
+    /// Composite the image over a solid background
+    ///
+    pub fn flatten(self, background: color::ARGB) -> Self {
+        self.map(|image| image.flatten(background))
+    }
+
+    pub fn lut(self, lut: &CubeLut) -> Self {
+        self.map(|image| lut.apply(&image))
+    }
+
+    ///
+    /// Apply a pipeline of point ops, in a single pass
+    ///
+    pub fn pipeline(self, pipeline: &Pipeline) -> Self {
+        self.try_map(|image| pipeline.execute(&image))
+    }
+
+    ///
+    /// Reduce the image to the given palette, mapping each color to the
+    /// palette color at the least euclidean distance
+    ///
+    pub fn dither(self, mode: DitherMode, palette: &[color::ARGB]) -> Self {
+        self.try_map(|image| {
+            if palette.is_empty() {
+                return Err(ImageError::InvalidArgument(String::from("Cannot dither to an empty palette.")));
+            }
+
+            Ok(image.dither(mode, |pixel| nearest(palette, pixel)))
+        })
+    }
+
+    ///
+    /// Apply any operation on the image
+    ///
+    pub fn map(self, operation: impl FnOnce(Image) -> Image) -> Self {
+        self.try_map(|image| Ok(operation(image)))
+    }
+
+    ///
+    /// Apply any operation on the image which may fail
+    ///
+    pub fn try_map(self, operation: impl FnOnce(Image) -> Result<Image, ImageError>) -> Self {
+        Self {
+            image: self.image.and_then(operation),
+            resize_filter: self.resize_filter
+        }
+    }
+
+    ///
+    /// End the chain, returning the image or the first error
+    ///
+    pub fn build(self) -> Result<Image, ImageError> {
+        self.image
+    }
+
+    ///
+    /// End the chain by saving the image to the given path, in the format
+    /// of its extension, returning the saved image
+    ///
+    pub fn save(self, path: impl AsRef<Path>) -> Result<Image, ImageError> {
+        let image = self.image?;
+        image.save(path)?;
+        Ok(image)
+    }
+}
+
+impl From<Image> for ImageBuilder {
+    fn from(image: Image) -> Self {
+        Self::new(image)
+    }
+}
+
+fn nearest(palette: &[color::ARGB], pixel: color::ARGB) -> color::ARGB {
+    palette.iter()
+        .copied()
+        .min_by(|a, b| pixel.distance_euclidean(a).total_cmp(&pixel.distance_euclidean(b)))
+        .unwrap_or(pixel)
+}
\ No newline at end of file
@@ -0,0 +1,107 @@
+use super::*;
+
+fn gradient() -> Image {
+    let pixels = (0..16_u8)
+        .map(|n| color::ARGB { alpha: 0xFF, red: n * 16, green: n * 8, blue: 255 - n * 16 })
+        .collect();
+
+    Image::new_pixels(4, 4, pixels)
+}
+
+#[test]
+fn builder_matches_individual_ops() -> Result<(), String> {
+    let built = gradient().edit()
+        .resize_filter(ResizeFilter::Nearest)
+        .resize(8, 8)
+        .rotate(Rotation::Rotate90)
+        .grayscale()
+        .build()?;
+
+    let expected = gradient()
+        .resize(8, 8, ResizeFilter::Nearest)
+        .rotate(Rotation::Rotate90)
+        .grayscale();
+
+    if built != expected {
+        return Err(String::from("Builder result differs from applying each op in turn."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn builder_error_short_circuits() -> Result<(), String> {
+    let mut applied = false;
+
+    let result = gradient().edit()
+        .crop(2, 2, 4, 4)
+        .map(|image| {
+            applied = true;
+            image
+        })
+        .build();
+
+    if !matches!(result, Err(ImageError::InvalidArgument(_))) {
+        return Err(format!("Expected an invalid argument error, got {result:?}."));
+    }
+
+    if applied {
+        return Err(String::from("An op after the error was still applied."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn builder_crop_overflow() -> Result<(), String> {
+    for (x, y) in [(usize::MAX, 0), (0, usize::MAX)] {
+        let result = gradient().edit().crop(x, y, 1, 1).build();
+
+        if !matches!(result, Err(ImageError::InvalidArgument(_))) {
+            return Err(format!("Expected cropping at {x},{y} to be an invalid argument, got {result:?}."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn builder_dither_to_palette() -> Result<(), String> {
+    let palette = [
+        color::ARGB { alpha: 0xFF, red: 0, green: 0, blue: 0 },
+        color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF }
+    ];
+
+    let image = gradient().edit()
+        .dither(DitherMode::FloydSteinberg, &palette)
+        .build()?;
+
+    if let Some(pixel) = image.pixels.iter().find(|pixel| !palette.contains(pixel)) {
+        return Err(format!("Pixel {} is not in the palette.", pixel.to_hex()));
+    }
+
+    if !matches!(gradient().edit().dither(DitherMode::None, &[]).build(), Err(ImageError::InvalidArgument(_))) {
+        return Err(String::from("Dithering to an empty palette should fail."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn builder_save_and_open() -> Result<(), String> {
+    let path = std::env::temp_dir().join(format!("rs_image_builder_{}.bmp", std::process::id()));
+
+    let saved = ImageBuilder::new(gradient()).invert().save(&path)?;
+    let opened = Image::open(&path);
+    let _ = std::fs::remove_file(&path);
+
+    if opened? != saved {
+        return Err(String::from("The opened image differs from the saved one."));
+    }
+
+    if !matches!(saved.save(path.with_extension("unknown")), Err(ImageError::Unsupported(_))) {
+        return Err(String::from("Saving with an unknown extension should fail."));
+    }
+
+    Ok(())
+}
\ No newline at end of file
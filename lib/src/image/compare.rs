@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{color, error::ImageError};
+use super::Image;
+
+///
+/// Side length, in pixels, of the windows SSIM is measured over
+///
+const SSIM_WINDOW: usize = 8;
+
+///
+/// Measures of how much two images of the same size differ
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    ///
+    /// Number of pixels with any channel differing by more than the tolerance
+    ///
+    pub differing_pixels: usize,
+    ///
+    /// Number of pixels compared
+    ///
+    pub total_pixels: usize,
+    ///
+    /// Largest difference in any channel of any pixel
+    ///
+    pub max_difference: u8,
+    ///
+    /// Mean squared error across all channels, including alpha
+    ///
+    pub mse: f64,
+    ///
+    /// Peak signal-to-noise ratio, in decibels. Infinite if the images are identical.
+    ///
+    pub psnr: f64,
+    ///
+    /// Mean structural similarity of luminance, from -1 to 1, where 1 is identical
+    ///
+    pub ssim: f64
+}
+
+impl Comparison {
+    ///
+    /// Fraction of pixels that differ, from 0 to 1
+    ///
+    pub fn differing_fraction(&self) -> f64 {
+        if self.total_pixels == 0 {
+            0_f64
+        }
+        else {
+            self.differing_pixels as f64 / self.total_pixels as f64
+        }
+    }
+}
+
+fn channels(pixel: &color::ARGB) -> [u8; 4] {
+    [pixel.alpha, pixel.red, pixel.green, pixel.blue]
+}
+
+fn pixel_difference(a: &color::ARGB, b: &color::ARGB) -> u8 {
+    channels(a).iter()
+        .zip(channels(b).iter())
+        .map(|(a, b)| a.abs_diff(*b))
+        .max()
+        .unwrap_or(0)
+}
+
+impl Image {
+    ///
+    /// Measure how much this image differs from another of the same size. Pixels
+    /// only count as differing if a channel differs by more than the tolerance.
+    ///
+    pub fn compare(&self, other: &Image, tolerance: u8) -> Result<Comparison, ImageError> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(ImageError::InvalidArgument(format!("Cannot compare a {}x{} image to a {}x{} image.", self.width, self.height, other.width, other.height)));
+        }
+
+        let mut differing_pixels = 0_usize;
+        let mut max_difference = 0_u8;
+        let mut squared_error = 0_f64;
+
+        for (a, b) in self.pixels.iter().zip(other.pixels.iter()) {
+            let difference = pixel_difference(a, b);
+
+            if difference > tolerance {
+                differing_pixels += 1;
+            }
+
+            max_difference = max_difference.max(difference);
+
+            squared_error += channels(a).iter()
+                .zip(channels(b).iter())
+                .map(|(a, b)| (*a as f64 - *b as f64).powi(2))
+                .sum::<f64>();
+        }
+
+        let mse = if self.pixels.is_empty() {
+            0_f64
+        }
+        else {
+            squared_error / (self.pixels.len() * 4) as f64
+        };
+
+        let psnr = if mse == 0_f64 {
+            f64::INFINITY
+        }
+        else {
+            10_f64 * (255_f64 * 255_f64 / mse).log10()
+        };
+
+        Ok(Comparison {
+            differing_pixels,
+            total_pixels: self.pixels.len(),
+            max_difference,
+            mse,
+            psnr,
+            ssim: self.ssim(other)
+        })
+    }
+
+    ///
+    /// Draw the differences between this image and another of the same size. Matching
+    /// pixels are drawn as faded grayscale, and differing pixels in the highlight color.
+    ///
+    pub fn highlight_differences(&self, other: &Image, tolerance: u8, highlight: color::ARGB) -> Result<Image, ImageError> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(ImageError::InvalidArgument(format!("Cannot compare a {}x{} image to a {}x{} image.", self.width, self.height, other.width, other.height)));
+        }
+
+        let white = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF };
+
+        let pixels: Vec<color::ARGB> = self.pixels.iter()
+            .zip(other.pixels.iter())
+            .map(|(a, b)| if pixel_difference(a, b) > tolerance {
+                highlight
+            }
+            else {
+                let luma = a.luminance();
+                let gray = color::ARGB { alpha: 0xFF, red: luma, green: luma, blue: luma };
+                gray.with_alpha(a.alpha).over(&white).lerp(&white, 0.5_f32)
+            })
+            .collect();
+
+        Ok(Image::new_pixels(self.width, self.height, pixels))
+    }
+
+    ///
+    /// Mean structural similarity of the luminance of two images of the same
+    /// size, measured over non-overlapping windows
+    ///
+    fn ssim(&self, other: &Image) -> f64 {
+        const C1: f64 = (0.01_f64 * 255_f64) * (0.01_f64 * 255_f64);
+        const C2: f64 = (0.03_f64 * 255_f64) * (0.03_f64 * 255_f64);
+
+        if self.pixels.is_empty() {
+            return 1_f64;
+        }
+
+        let mut total = 0_f64;
+        let mut windows = 0_usize;
+
+        for y in (0..self.height).step_by(SSIM_WINDOW) {
+            for x in (0..self.width).step_by(SSIM_WINDOW) {
+                let window: Vec<(f64, f64)> = (y..(y + SSIM_WINDOW).min(self.height))
+                    .flat_map(|j| (x..(x + SSIM_WINDOW).min(self.width)).map(move |i| (i, j)))
+                    .map(|(i, j)| (self.row(j)[i].luminance() as f64, other.row(j)[i].luminance() as f64))
+                    .collect();
+
+                let n = window.len() as f64;
+                let mean_a = window.iter().map(|(a, _)| a).sum::<f64>() / n;
+                let mean_b = window.iter().map(|(_, b)| b).sum::<f64>() / n;
+
+                let (mut variance_a, mut variance_b, mut covariance) = (0_f64, 0_f64, 0_f64);
+
+                for (a, b) in window.iter() {
+                    variance_a += (a - mean_a).powi(2);
+                    variance_b += (b - mean_b).powi(2);
+                    covariance += (a - mean_a) * (b - mean_b);
+                }
+
+                variance_a /= n;
+                variance_b /= n;
+                covariance /= n;
+
+                total += ((2_f64 * mean_a * mean_b + C1) * (2_f64 * covariance + C2))
+                    / ((mean_a * mean_a + mean_b * mean_b + C1) * (variance_a + variance_b + C2));
+
+                windows += 1;
+            }
+        }
+
+        total / windows as f64
+    }
+}
\ No newline at end of file
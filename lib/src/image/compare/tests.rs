@@ -0,0 +1,55 @@
+use super::*;
+
+fn gradient() -> Image {
+    let pixels = (0..16_u8)
+        .map(|n| color::ARGB { alpha: 0xFF, red: n * 16, green: n * 8, blue: 255 - n * 16 })
+        .collect();
+
+    Image::new_pixels(4, 4, pixels)
+}
+
+#[test]
+fn compare_identical() -> Result<(), String> {
+    let comparison = gradient().compare(&gradient(), 0)?;
+
+    if comparison.differing_pixels != 0 || comparison.mse != 0_f64 {
+        return Err(format!("Identical images should not differ, but {} pixels did.", comparison.differing_pixels));
+    }
+
+    if !comparison.psnr.is_infinite() || (comparison.ssim - 1_f64).abs() > 1e-9 {
+        return Err(format!("Expected infinite PSNR and SSIM of 1, got {} and {}.", comparison.psnr, comparison.ssim));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn compare_tolerance() -> Result<(), String> {
+    let image = gradient();
+    let mut other = gradient();
+    other.set(image.row(0)[0].offset(3_f32, 0_f32, 0_f32), 0, 0)?;
+    other.set(image.row(1)[1].offset(0_f32, 20_f32, 0_f32), 1, 1)?;
+
+    let comparison = image.compare(&other, 5)?;
+
+    if comparison.differing_pixels != 1 || comparison.max_difference != 20 {
+        return Err(format!(
+            "Expected 1 differing pixel and a max difference of 20, got {} and {}.",
+            comparison.differing_pixels, comparison.max_difference
+        ));
+    }
+
+    if comparison.ssim >= 1_f64 {
+        return Err(String::from("Differing images should have an SSIM below 1."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn compare_size_mismatch() -> Result<(), String> {
+    match gradient().compare(&Image::new(2, 2), 0) {
+        Ok(_) => Err(String::from("Comparing images of different sizes should fail.")),
+        Err(_) => Ok(())
+    }
+}
\ No newline at end of file
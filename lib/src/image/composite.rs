@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests;
+
+use crate::color;
+use super::Image;
+
+impl Image {
+    ///
+    /// Create an image filled with a single color
+    ///
+    pub fn solid(width: usize, height: usize, color: color::ARGB) -> Image {
+        Image::new_pixels(width, height, vec![color; width * height])
+    }
+
+    ///
+    /// Create a checkerboard of square cells of the given size, alternating
+    /// between the two colors and starting with the first in the top-left
+    ///
+    pub fn checkerboard(width: usize, height: usize, cell: usize, first: color::ARGB, second: color::ARGB) -> Image {
+        let cell = cell.max(1);
+
+        let pixels: Vec<color::ARGB> = (0..height)
+            .flat_map(|j| (0..width).map(move |i| checker(i, j, cell, first, second)))
+            .collect();
+
+        Image::new_pixels(width, height, pixels)
+    }
+
+    ///
+    /// Composite this image over the given background image, which
+    /// is tiled if it is smaller than this image
+    ///
+    pub fn composite_over(&self, background: &Image) -> Image {
+        if background.length() == 0 {
+            return self.clone();
+        }
+
+        Image::from_fn(self.width, self.height, |i, j| {
+            let underneath = background.row(j % background.height)[i % background.width];
+            self.row(j)[i].over(&underneath)
+        })
+    }
+
+    ///
+    /// Composite this image over a solid background color. If the
+    /// background is opaque, so is the result.
+    ///
+    pub fn flatten(&self, background: color::ARGB) -> Image {
+        self.map_pixels(|pixel| pixel.over(&background))
+    }
+
+    ///
+    /// Composite this image over a checkerboard of square cells of the given size,
+    /// alternating between the two colors and starting with the first in the
+    /// top-left. If both colors are opaque, so is the result.
+    ///
+    pub fn flatten_checkerboard(&self, cell: usize, first: color::ARGB, second: color::ARGB) -> Image {
+        let cell = cell.max(1);
+
+        Image::from_fn(self.width, self.height, |i, j| {
+            self.row(j)[i].over(&checker(i, j, cell, first, second))
+        })
+    }
+
+    ///
+    /// Composite the given image over this one, with its top-left corner
+    /// at (x, y). Anything falling outside of this image is clipped.
+    ///
+    pub fn draw_image(&mut self, image: &Image, x: usize, y: usize) {
+        let width = image.width.min(self.width.saturating_sub(x));
+        let height = image.height.min(self.height.saturating_sub(y));
+
+        for j in 0..height {
+            for i in 0..width {
+                let index = self.calculate_index(x + i, y + j);
+                self.pixels[index] = image.row(j)[i].over(&self.pixels[index]);
+            }
+        }
+    }
+}
+
+///
+/// The color of the checkerboard cell containing the pixel (i, j)
+///
+fn checker(i: usize, j: usize, cell: usize, first: color::ARGB, second: color::ARGB) -> color::ARGB {
+    if ((i / cell) + (j / cell)) & 1 == 0 {
+        first
+    }
+    else {
+        second
+    }
+}
\ No newline at end of file
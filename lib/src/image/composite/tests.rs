@@ -0,0 +1,45 @@
+use super::*;
+
+const WHITE: color::ARGB = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF };
+const GRAY: color::ARGB = color::ARGB { alpha: 0xFF, red: 0x80, green: 0x80, blue: 0x80 };
+
+fn translucent() -> Image {
+    let pixels = (0..24_u8)
+        .map(|n| color::ARGB { alpha: n * 11, red: n * 10, green: 255 - n * 10, blue: n * 5 })
+        .collect();
+
+    Image::new_pixels(6, 4, pixels)
+}
+
+#[test]
+fn flatten_is_opaque() -> Result<(), String> {
+    let flattened = translucent().flatten(GRAY);
+
+    if let Some(pixel) = flattened.pixels.iter().find(|pixel| pixel.alpha != 0xFF) {
+        return Err(format!("Flattened pixel {} is not opaque.", pixel.to_hex()));
+    }
+
+    //Fully transparent pixels become the background
+    if flattened.row(0)[0] != GRAY {
+        return Err(format!("Expected the background under a transparent pixel, got {}.", flattened.row(0)[0].to_hex()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn flatten_checkerboard_matches_composite() -> Result<(), String> {
+    let image = translucent();
+    let flattened = image.flatten_checkerboard(2, WHITE, GRAY);
+    let expected = image.composite_over(&Image::checkerboard(6, 4, 2, WHITE, GRAY));
+
+    if flattened != expected {
+        return Err(String::from("Flattening onto a checkerboard differs from compositing over one."));
+    }
+
+    if let Some(pixel) = flattened.pixels.iter().find(|pixel| pixel.alpha != 0xFF) {
+        return Err(format!("Flattened pixel {} is not opaque.", pixel.to_hex()));
+    }
+
+    Ok(())
+}
\ No newline at end of file
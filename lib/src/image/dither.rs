@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests;
+
+use crate::color;
+use super::Image;
+
+///
+/// 4x4 Bayer threshold matrix, used for ordered dithering
+///
+const BAYER_4X4: [[u8; 4]; 4] = [
+    [0, 8, 2, 10],
+    [12, 4, 14, 6],
+    [3, 11, 1, 9],
+    [15, 7, 13, 5]
+];
+
+///
+/// How to spread quantization error when reducing an image
+/// to a limited palette
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DitherMode {
+    ///
+    /// Map each pixel directly to its nearest palette color
+    ///
+    #[default]
+    None,
+    ///
+    /// Offset each pixel by a threshold from a repeating Bayer matrix
+    /// before mapping it. Spread is the range of the offset in each channel,
+    /// and should be roughly the distance between neighboring palette colors.
+    ///
+    Ordered {
+        spread: f32
+    },
+    ///
+    /// Diffuse each pixel's quantization error onto its unvisited
+    /// neighbors (Floyd-Steinberg)
+    ///
+    FloydSteinberg
+}
+
+impl Image {
+    ///
+    /// Reduce the image to a limited palette, using the given dithering mode.
+    /// The nearest function maps any color to the closest color in the palette.
+    /// Fully transparent pixels are left untouched, and do not receive error.
+    ///
+    pub fn dither<F>(&self, mode: DitherMode, nearest: F) -> Image where F: Fn(color::ARGB) -> color::ARGB {
+        let pixels: Vec<color::ARGB> = match mode {
+            DitherMode::None => {
+                self.pixels.iter()
+                    .map(|pixel| if pixel.alpha == 0 { *pixel } else { nearest(*pixel) })
+                    .collect()
+            },
+            DitherMode::Ordered { spread } => {
+                self.pixels.iter()
+                    .enumerate()
+                    .map(|(index, pixel)| {
+                        if pixel.alpha == 0 {
+                            return *pixel;
+                        }
+
+                        let (i, j) = (index % self.width, index / self.width);
+
+                        //Threshold in (-0.5, 0.5)
+                        let threshold = (BAYER_4X4[j % 4][i % 4] as f32 + 0.5_f32) / 16_f32 - 0.5_f32;
+                        let offset = threshold * spread;
+
+                        nearest(pixel.offset(offset, offset, offset))
+                    })
+                    .collect()
+            },
+            DitherMode::FloydSteinberg => {
+                //Accumulated error for each channel of each pixel
+                let mut error: Vec<(f32, f32, f32)> = vec![(0_f32, 0_f32, 0_f32); self.length()];
+                let mut pixels: Vec<color::ARGB> = Vec::with_capacity(self.length());
+
+                for (index, pixel) in self.pixels.iter().enumerate() {
+                    if pixel.alpha == 0 {
+                        pixels.push(*pixel);
+                        continue;
+                    }
+
+                    let (i, j) = (index % self.width, index / self.width);
+
+                    let (red, green, blue) = error[index];
+                    let adjusted = pixel.offset(red, green, blue);
+                    let mapped = nearest(adjusted);
+                    pixels.push(mapped);
+
+                    let residual = (
+                        adjusted.red as f32 - mapped.red as f32,
+                        adjusted.green as f32 - mapped.green as f32,
+                        adjusted.blue as f32 - mapped.blue as f32
+                    );
+
+                    //Distribute error to the right, and to the three pixels below
+                    let neighbors = [
+                        (i + 1 < self.width, index + 1, 7_f32 / 16_f32),
+                        (i > 0 && j + 1 < self.height, index + self.width - 1, 3_f32 / 16_f32),
+                        (j + 1 < self.height, index + self.width, 5_f32 / 16_f32),
+                        (i + 1 < self.width && j + 1 < self.height, index + self.width + 1, 1_f32 / 16_f32)
+                    ];
+
+                    for (in_bounds, neighbor, weight) in neighbors {
+                        if in_bounds {
+                            let e = &mut error[neighbor];
+                            e.0 += residual.0 * weight;
+                            e.1 += residual.1 * weight;
+                            e.2 += residual.2 * weight;
+                        }
+                    }
+                }
+
+                pixels
+            }
+        };
+
+        Image::new_pixels(self.width, self.height, pixels)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,147 @@
+use super::*;
+use core::cell::Cell;
+
+const BLACK: color::ARGB = color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0x00 };
+const WHITE: color::ARGB = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF };
+const CLEAR: color::ARGB = color::ARGB { alpha: 0x00, red: 0x12, green: 0x34, blue: 0x56 };
+
+///
+/// The closest of the given colors by euclidean distance, keeping the pixel's alpha
+///
+fn nearest_of(palette: &[color::ARGB], pixel: color::ARGB) -> color::ARGB {
+    let closest = palette.iter()
+        .copied()
+        .min_by(|a, b| a.distance_euclidean(&pixel).total_cmp(&b.distance_euclidean(&pixel)))
+        .unwrap_or(pixel);
+
+    color::ARGB { alpha: pixel.alpha, ..closest }
+}
+
+fn gray(width: usize, height: usize, level: u8) -> Image {
+    Image::new_pixels(width, height, vec![color::ARGB { alpha: 0xFF, red: level, green: level, blue: level }; width * height])
+}
+
+#[test]
+fn dither_none_is_nearest() -> Result<(), String> {
+    let palette = [BLACK, WHITE, color::ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x00 }];
+
+    let pixels: Vec<color::ARGB> = (0..64_u8)
+        .map(|n| color::ARGB { alpha: 0xFF, red: n * 4, green: n * 2, blue: 255 - n * 4 })
+        .collect();
+    let image = Image::new_pixels(8, 8, pixels);
+
+    let dithered = image.dither(DitherMode::None, |pixel| nearest_of(&palette, pixel));
+
+    for (index, (before, after)) in image.pixels.iter().zip(dithered.pixels.iter()).enumerate() {
+        if *after != nearest_of(&palette, *before) {
+            return Err(format!("Expected pixel {index} to map from {} to its nearest color, but got {}.", before.to_hex(), after.to_hex()));
+        }
+    }
+
+    if DitherMode::default() != DitherMode::None {
+        return Err(String::from("Expected no dithering by default."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn dither_ordered_follows_bayer() -> Result<(), String> {
+    let nearest = |pixel| nearest_of(&[BLACK, WHITE], pixel);
+
+    //With a full spread, a flat gray is white wherever the threshold of its cell is high enough
+    for (level, first_white) in [(0x80, 8), (0x40, 12)] {
+        let image = gray(12, 8, level);
+        let dithered = image.dither(DitherMode::Ordered { spread: 255_f32 }, nearest);
+
+        if dithered != image.dither(DitherMode::Ordered { spread: 255_f32 }, nearest) {
+            return Err(format!("Ordered dithering of gray {level} differs between runs."));
+        }
+
+        for j in 0..dithered.height() {
+            for (i, pixel) in dithered.row(j).iter().enumerate() {
+                let expected = if BAYER_4X4[j % 4][i % 4] >= first_white { WHITE } else { BLACK };
+
+                if *pixel != expected {
+                    return Err(format!("Expected {} at ({i}, {j}) of gray {level}, but got {}.", expected.to_hex(), pixel.to_hex()));
+                }
+            }
+        }
+    }
+
+    //Without spread, it's the same as no dithering
+    let image = gray(4, 4, 0x70);
+
+    if image.dither(DitherMode::Ordered { spread: 0_f32 }, nearest) != image.dither(DitherMode::None, nearest) {
+        return Err(String::from("Expected ordered dithering without spread to map directly."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn dither_floyd_steinberg_mixes() -> Result<(), String> {
+    let image = gray(32, 32, 0x80);
+    let dithered = image.dither(DitherMode::FloydSteinberg, |pixel| nearest_of(&[BLACK, WHITE], pixel));
+
+    if let Some(pixel) = dithered.pixels.iter().find(|pixel| **pixel != BLACK && **pixel != WHITE) {
+        return Err(format!("Expected only black and white, but got {}.", pixel.to_hex()));
+    }
+
+    //Mid-gray is about half of the way from black to white
+    let white = dithered.pixels.iter().filter(|pixel| **pixel == WHITE).count();
+
+    if !(460..=564).contains(&white) {
+        return Err(format!("Expected about half of the {} pixels to be white, but got {white}.", dithered.length()));
+    }
+
+    //Neither color should clump into a solid row
+    for j in 0..dithered.height() {
+        if dithered.row(j).iter().all(|pixel| *pixel == dithered.row(j)[0]) {
+            return Err(format!("Row {j} is a single color."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn dither_skips_transparent() -> Result<(), String> {
+    //A transparent column down the middle of a gray field
+    let mut image = gray(8, 8, 0x80);
+
+    for j in 0..8 {
+        image.pixels[j * 8 + 3] = CLEAR;
+    }
+
+    let modes = [DitherMode::None, DitherMode::Ordered { spread: 255_f32 }, DitherMode::FloydSteinberg];
+
+    for mode in modes {
+        let calls = Cell::new(0_usize);
+
+        let dithered = image.dither(mode, |pixel| {
+            calls.set(calls.get() + 1);
+
+            if pixel.alpha == 0 {
+                //Returning a visible color here would show up below
+                WHITE
+            }
+            else {
+                nearest_of(&[BLACK, WHITE], pixel)
+            }
+        });
+
+        for j in 0..8 {
+            if dithered.row(j)[3] != CLEAR {
+                return Err(format!("Expected {mode:?} to leave the transparent pixel on row {j} alone, but got {}.", dithered.row(j)[3].to_hex()));
+            }
+        }
+
+        //Only the opaque pixels are mapped, so none of the error reaches the transparent ones
+        if calls.get() != 8 * 7 {
+            return Err(format!("Expected {mode:?} to map the 56 opaque pixels, but it mapped {}.", calls.get()));
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,511 @@
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{color, constants};
+use crate::error::ImageError;
+use super::Image;
+use super::stats::{ChannelStats, RegionStats};
+
+///
+/// Number of sectors the anisotropic Kuwahara filter divides each neighborhood into
+///
+const KUWAHARA_SECTORS: usize = 8;
+
+///
+/// How sharply the anisotropic Kuwahara filter favors the sectors with the least
+/// variance; higher values keep edges crisper, lower values blend more smoothly
+///
+const KUWAHARA_SHARPNESS: i32 = 8;
+
+///
+/// A per-image adjustment, applied as one step of a filter pipeline
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Filter {
+    ///
+    /// Replace each color with its luminance
+    ///
+    Grayscale,
+    ///
+    /// Invert each color channel, leaving alpha untouched
+    ///
+    Invert,
+    ///
+    /// Average each pixel with its neighbors within the given radius
+    ///
+    Blur {
+        radius: usize
+    },
+    ///
+    /// Add the given amount to each color channel
+    ///
+    Brightness {
+        amount: f32
+    },
+    ///
+    /// Stretch the luminance to the full range, scaling every
+    /// color channel alike so that hues are kept
+    ///
+    AutoContrast,
+    ///
+    /// Stretch each color channel to the full range separately,
+    /// which also removes color casts
+    ///
+    AutoLevels,
+    ///
+    /// Redraw as layers of black lines, the given number of pixels apart, on white
+    ///
+    Hatch {
+        spacing: usize
+    },
+    ///
+    /// Redraw as blue noise black dots on white
+    ///
+    Stipple,
+    ///
+    /// Smooth away detail while keeping edges, for a painted look, by replacing each
+    /// pixel with the mean of the most uniform corner of the window around it
+    ///
+    Kuwahara {
+        radius: usize
+    },
+    ///
+    /// Kuwahara smoothing which follows the direction of the edges, giving
+    /// brush strokes along them rather than square blocks
+    ///
+    AnisotropicKuwahara {
+        radius: usize
+    }
+}
+
+impl Filter {
+    ///
+    /// Check the parameters of a filter given from outside, such as in a recipe
+    ///
+    pub(crate) fn validate(&self) -> Result<(), ImageError> {
+        match *self {
+            Self::Blur { radius } | Self::Kuwahara { radius } | Self::AnisotropicKuwahara { radius } if radius > constants::image::MAX_FILTER_RADIUS => {
+                Err(ImageError::InvalidArgument(format!("Filter radius {radius} is larger than the {} allowed.", constants::image::MAX_FILTER_RADIUS)))
+            },
+            Self::Brightness { amount } if !amount.is_finite() => Err(ImageError::InvalidArgument(format!("Brightness {amount} must be finite."))),
+            _ => Ok(())
+        }
+    }
+}
+
+impl Image {
+    ///
+    /// Apply a single filter to the image
+    ///
+    pub fn filter(&self, filter: Filter) -> Image {
+        match filter {
+            Filter::Grayscale => self.grayscale(),
+            Filter::Invert => self.invert(),
+            Filter::Blur { radius } => self.blur(radius),
+            Filter::Brightness { amount } => self.brightness(amount),
+            Filter::AutoContrast => self.auto_contrast(),
+            Filter::AutoLevels => self.auto_levels(),
+            Filter::Hatch { spacing } => self.hatch(spacing),
+            Filter::Stipple => self.stipple(),
+            Filter::Kuwahara { radius } => self.kuwahara(radius),
+            Filter::AnisotropicKuwahara { radius } => self.anisotropic_kuwahara(radius)
+        }
+    }
+
+    ///
+    /// Apply each filter to the image, in order
+    ///
+    pub fn filter_all(&self, filters: &[Filter]) -> Image {
+        filters.iter()
+            .fold(self.clone(), |image, filter| image.filter(*filter))
+    }
+
+    ///
+    /// Replace each color with its luminance (Rec. 709)
+    ///
+    pub fn grayscale(&self) -> Image {
+        self.map_pixels(|pixel| {
+            let luma = pixel.luminance();
+
+            color::ARGB {
+                alpha: pixel.alpha,
+                red: luma,
+                green: luma,
+                blue: luma
+            }
+        })
+    }
+
+    ///
+    /// Invert each color channel, leaving alpha untouched
+    ///
+    pub fn invert(&self) -> Image {
+        self.map_pixels(|pixel| color::ARGB {
+            alpha: pixel.alpha,
+            red: 255 - pixel.red,
+            green: 255 - pixel.green,
+            blue: 255 - pixel.blue
+        })
+    }
+
+    ///
+    /// Add the given amount to each color channel
+    ///
+    pub fn brightness(&self, amount: f32) -> Image {
+        self.map_pixels(|pixel| pixel.offset(amount, amount, amount))
+    }
+
+    ///
+    /// Stretch the luminance to the full range, scaling every
+    /// color channel alike so that hues are kept
+    ///
+    pub fn auto_contrast(&self) -> Image {
+        let luminance = self.stats().luminance;
+        let (low, high) = (luminance.min, luminance.max);
+
+        if low >= high {
+            return self.clone();
+        }
+
+        self.map_pixels(|pixel| color::ARGB {
+            alpha: pixel.alpha,
+            red: stretch(pixel.red, low, high),
+            green: stretch(pixel.green, low, high),
+            blue: stretch(pixel.blue, low, high)
+        })
+    }
+
+    ///
+    /// Stretch each color channel to the full range separately,
+    /// which also removes color casts
+    ///
+    pub fn auto_levels(&self) -> Image {
+        let stats = self.stats();
+
+        //A channel with a single value is left alone, rather than stretched to nothing
+        let level = |value: u8, channel: &ChannelStats| if channel.min < channel.max {
+            stretch(value, channel.min, channel.max)
+        }
+        else {
+            value
+        };
+
+        self.map_pixels(|pixel| color::ARGB {
+            alpha: pixel.alpha,
+            red: level(pixel.red, &stats.red),
+            green: level(pixel.green, &stats.green),
+            blue: level(pixel.blue, &stats.blue)
+        })
+    }
+
+    ///
+    /// Box blur the image with the given radius, horizontally then vertically.
+    /// Edges are extended, and color channels are weighted by alpha so transparent
+    /// pixels don't bleed their color into their neighbors. A radius past the size
+    /// of the image is taken as the size, as the window already covers all of it.
+    ///
+    pub fn blur(&self, radius: usize) -> Image {
+        if radius == 0 || self.length() == 0 {
+            return self.clone();
+        }
+
+        let radius = radius.min(self.width.max(self.height));
+
+        let horizontal = self.blur_pass(radius, true);
+        horizontal.blur_pass(radius, false)
+    }
+
+    ///
+    /// Kuwahara filter: split the (2 * radius + 1) square window around each pixel into
+    /// the four overlapping squares with the pixel at a corner, and replace the pixel with
+    /// the mean color of the square whose colors vary least. Areas are smoothed, but no
+    /// square straddles an edge unless every one does, so edges stay sharp. Alpha is kept.
+    /// A radius past the size of the image is taken as the size, as for blur.
+    ///
+    pub fn kuwahara(&self, radius: usize) -> Image {
+        if radius == 0 || self.length() == 0 {
+            return self.clone();
+        }
+
+        let radius = radius.min(self.width.max(self.height));
+        let table = self.summed_area_table();
+        let size = radius + 1;
+
+        Image::from_fn(self.width, self.height, |x, y| {
+            let pixel = self.row(y)[x];
+            let (left, top) = (x.saturating_sub(radius), y.saturating_sub(radius));
+
+            //Squares are clipped to the image, so the near ones are smaller at the edges
+            let squares = [
+                table.region(left, top, x - left + 1, y - top + 1),
+                table.region(x, top, size, y - top + 1),
+                table.region(left, y, x - left + 1, size),
+                table.region(x, y, size, size)
+            ];
+
+            squares.into_iter()
+                .filter(|square| square.weight > 0_f32)
+                .min_by(|a, b| a.variance.total_cmp(&b.variance))
+                .map_or(pixel, |square| with_mean(pixel, &square))
+        })
+    }
+
+    ///
+    /// Anisotropic Kuwahara filter (Kyprianidis et al.): the window around each pixel is an
+    /// ellipse stretched along the local edge direction, found from the structure tensor,
+    /// and split into eight sectors. Rather than picking one, the sector means are blended,
+    /// weighted toward those with the least variance, which avoids the blocky artifacts of
+    /// the square windows. Alpha is kept. A radius past the size of the image is taken
+    /// as the size, as for blur.
+    ///
+    pub fn anisotropic_kuwahara(&self, radius: usize) -> Image {
+        if radius == 0 || self.length() == 0 {
+            return self.clone();
+        }
+
+        let radius = radius.min(self.width.max(self.height));
+
+        let (width, height) = (self.width, self.height);
+        let orientation = self.local_orientation();
+
+        Image::from_fn(width, height, |x, y| {
+            let pixel = self.row(y)[x];
+            let (tangent, anisotropy) = orientation[y * width + x];
+
+            //Longer along the edge, and narrower across it, the more strongly oriented it is
+            let major = radius as f32 * (1_f32 + anisotropy);
+            let minor = radius as f32 / (1_f32 + anisotropy);
+            let reach = major.ceil() as isize;
+
+            //Weight, alpha-weighted color and sum of squared color of each sector
+            let mut sectors = [[0_f64; 5]; KUWAHARA_SECTORS];
+
+            //Whether each sector has anything but the center, without which it would seem perfectly uniform
+            let mut neighbors = [false; KUWAHARA_SECTORS];
+
+            //Only the part of the window within the image
+            let (x, y) = (x as isize, y as isize);
+
+            for dy in (-reach).max(-y)..=reach.min(height as isize - 1 - y) {
+                for dx in (-reach).max(-x)..=reach.min(width as isize - 1 - x) {
+                    let (u, v) = (x + dx, y + dy);
+
+                    //Offset in the frame of the ellipse, where it is the unit circle
+                    let (dx, dy) = (dx as f32, dy as f32);
+                    let along = (dx * tangent.0 + dy * tangent.1) / major;
+                    let across = (dy * tangent.0 - dx * tangent.1) / minor;
+                    let distance = along * along + across * across;
+
+                    if distance > 1_f32 {
+                        continue;
+                    }
+
+                    let neighbor = self.row(v as usize)[u as usize];
+                    let weight = (-2_f32 * distance).exp() as f64 * neighbor.alpha as f64 / 255_f64;
+                    let [red, green, blue] = [neighbor.red, neighbor.green, neighbor.blue].map(|channel| channel as f64);
+                    let values = [weight, red * weight, green * weight, blue * weight, (red * red + green * green + blue * blue) * weight];
+
+                    //The center belongs to every sector, so that one reaching across an edge isn't uniform
+                    let members = if distance == 0_f32 { 0..KUWAHARA_SECTORS } else {
+                        let sector = (across.atan2(along) / std::f32::consts::TAU * KUWAHARA_SECTORS as f32).floor() as isize;
+                        let sector = sector.rem_euclid(KUWAHARA_SECTORS as isize) as usize;
+                        neighbors[sector] = true;
+                        sector..sector + 1
+                    };
+
+                    for sector in members {
+                        sectors[sector].iter_mut().zip(values).for_each(|(sum, value)| *sum += value);
+                    }
+                }
+            }
+
+            let (mut total, mut blended) = (0_f64, [0_f64; 3]);
+
+            let sectors = sectors.into_iter()
+                .zip(neighbors)
+                .filter(|(sector, neighbors)| *neighbors && sector[0] > 0_f64)
+                .map(|(sector, _)| sector);
+
+            for [weight, red, green, blue, squares] in sectors {
+                let mean = [red / weight, green / weight, blue / weight];
+                let variance = (squares / weight - mean.iter().map(|mean| mean * mean).sum::<f64>()).max(0_f64);
+
+                let weight = 1_f64 / (1_f64 + variance.sqrt().powi(KUWAHARA_SHARPNESS));
+                total += weight;
+                blended.iter_mut().zip(mean).for_each(|(blended, mean)| *blended += mean * weight);
+            }
+
+            if total == 0_f64 {
+                return pixel;
+            }
+
+            with_mean(pixel, &RegionStats {
+                weight: 1_f32,
+                mean: blended.map(|channel| (channel / total) as f32),
+                variance: 0_f32
+            })
+        })
+    }
+
+    ///
+    /// Direction along the edges at each pixel, as a unit vector, and how strongly oriented
+    /// the area is, from 0 where there is no one direction up to 1 along a straight edge.
+    /// Found from the eigenvectors of the smoothed structure tensor of the luminance.
+    ///
+    fn local_orientation(&self) -> Vec<((f32, f32), f32)> {
+        let (width, height) = (self.width, self.height);
+
+        let luminance = |x: isize, y: isize| {
+            let (x, y) = (x.clamp(0, width as isize - 1) as usize, y.clamp(0, height as isize - 1) as usize);
+            self.row(y)[x].luminance() as f32
+        };
+
+        //Products of the Sobel gradients, stored as a color so that they can be smoothed like one
+        let tensor: Vec<[f32; 3]> = (0..width * height)
+            .map(|index| {
+                let (x, y) = ((index % width) as isize, (index / width) as isize);
+
+                let gx = luminance(x + 1, y - 1) + 2_f32 * luminance(x + 1, y) + luminance(x + 1, y + 1)
+                    - luminance(x - 1, y - 1) - 2_f32 * luminance(x - 1, y) - luminance(x - 1, y + 1);
+                let gy = luminance(x - 1, y + 1) + 2_f32 * luminance(x, y + 1) + luminance(x + 1, y + 1)
+                    - luminance(x - 1, y - 1) - 2_f32 * luminance(x, y - 1) - luminance(x + 1, y - 1);
+
+                [gx * gx, gx * gy, gy * gy]
+            })
+            .collect();
+
+        //Average the tensor over a small neighborhood, so that noise doesn't swing the direction
+        const SMOOTHING: isize = 2;
+
+        (0..width * height)
+            .map(|index| {
+                let (x, y) = ((index % width) as isize, (index / width) as isize);
+                let mut sum = [0_f32; 3];
+
+                for v in (y - SMOOTHING).max(0)..=(y + SMOOTHING).min(height as isize - 1) {
+                    for u in (x - SMOOTHING).max(0)..=(x + SMOOTHING).min(width as isize - 1) {
+                        let value = tensor[v as usize * width + u as usize];
+                        sum.iter_mut().zip(value).for_each(|(sum, value)| *sum += value);
+                    }
+                }
+
+                let [e, f, g] = sum;
+
+                if e + g == 0_f32 {
+                    return ((1_f32, 0_f32), 0_f32);
+                }
+
+                //The gradient is along the major eigenvector, and the edge across it
+                let gradient = 0.5_f32 * (2_f32 * f).atan2(e - g);
+                let difference = ((e - g) * (e - g) + 4_f32 * f * f).sqrt();
+
+                ((-gradient.sin(), gradient.cos()), difference / (e + g))
+            })
+            .collect()
+    }
+
+    ///
+    /// Box blur each row, or each column, sliding the window along it so that each
+    /// pixel only adds the one entering the window and removes the one leaving
+    ///
+    fn blur_pass(&self, radius: usize, horizontal: bool) -> Image {
+        let (width, height) = (self.width, self.height);
+        let (lines, length) = if horizontal { (height, width) } else { (width, height) };
+        let last = length - 1;
+
+        let at = |line: usize, position: usize| if horizontal { self.row(line)[position] } else { self.row(position)[line] };
+
+        //Color weighted by alpha, and alpha, of each pixel, summed across the window
+        let weigh = |pixel: color::ARGB, count: usize| {
+            let weight = pixel.alpha as u64 * count as u64;
+            [pixel.red as u64 * weight, pixel.green as u64 * weight, pixel.blue as u64 * weight, weight]
+        };
+
+        let window = (2 * radius + 1) as f64;
+
+        let blur_line = |line: usize| {
+            //The window around the first pixel, with the edges extended
+            let mut sum = weigh(at(line, 0), radius + 1);
+
+            for position in 1..=radius.min(last) {
+                add_weights(&mut sum, weigh(at(line, position), 1), true);
+            }
+
+            add_weights(&mut sum, weigh(at(line, last), radius.saturating_sub(last)), true);
+
+            (0..length)
+                .map(|position| {
+                    let [red, green, blue, alpha] = sum;
+
+                    add_weights(&mut sum, weigh(at(line, (position + radius + 1).min(last)), 1), true);
+                    add_weights(&mut sum, weigh(at(line, position.saturating_sub(radius)), 1), false);
+
+                    if alpha == 0 {
+                        return color::ARGB::default();
+                    }
+
+                    color::ARGB {
+                        alpha: (alpha as f64 / window).round() as u8,
+                        red: (red as f64 / alpha as f64).round() as u8,
+                        green: (green as f64 / alpha as f64).round() as u8,
+                        blue: (blue as f64 / alpha as f64).round() as u8
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+
+        #[cfg(feature = "parallel")]
+        let blurred: Vec<Vec<color::ARGB>> = (0..lines).into_par_iter().map(blur_line).collect();
+
+        #[cfg(not(feature = "parallel"))]
+        let blurred: Vec<Vec<color::ARGB>> = (0..lines).map(blur_line).collect();
+
+        if horizontal {
+            Image::new_pixels(width, height, blurred.concat())
+        }
+        else {
+            Image::from_fn(width, height, |i, j| blurred[i][j])
+        }
+    }
+}
+
+///
+/// Add the weights of a pixel to the sums of a window, or remove them
+///
+fn add_weights(sum: &mut [u64; 4], weights: [u64; 4], add: bool) {
+    for (total, weight) in sum.iter_mut().zip(weights) {
+        if add {
+            *total += weight;
+        }
+        else {
+            *total -= weight;
+        }
+    }
+}
+
+///
+/// The pixel, with its color replaced by the mean of the region
+///
+fn with_mean(pixel: color::ARGB, region: &RegionStats) -> color::ARGB {
+    let [red, green, blue] = region.mean.map(|channel| channel.round().clamp(0_f32, 255_f32) as u8);
+
+    color::ARGB {
+        alpha: pixel.alpha,
+        red,
+        green,
+        blue
+    }
+}
+
+///
+/// Map [low, high] onto [0, 255], clamping values outside of it
+///
+fn stretch(value: u8, low: u8, high: u8) -> u8 {
+    ((value as f32 - low as f32) * 255_f32 / (high - low) as f32)
+        .round()
+        .clamp(0_f32, 255_f32) as u8
+}
\ No newline at end of file
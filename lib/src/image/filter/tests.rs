@@ -0,0 +1,161 @@
+use super::*;
+
+#[test]
+fn grayscale_equal_channels() -> Result<(), String> {
+    let image = Image::new_pixels(2, 1, vec![
+        color::ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x00 },
+        color::ARGB { alpha: 0x80, red: 0x10, green: 0x80, blue: 0xF0 }
+    ]);
+
+    let gray = image.grayscale();
+
+    for (pixel, original) in gray.row(0).iter().zip(image.row(0)) {
+        if pixel.red != pixel.green || pixel.green != pixel.blue {
+            return Err(format!("Expected a gray pixel, got {}.", pixel.to_hex()));
+        }
+
+        if pixel.alpha != original.alpha {
+            return Err(String::from("Grayscale should not change alpha."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn invert_twice() -> Result<(), String> {
+    let image = Image::new_pixels(1, 1, vec![color::ARGB { alpha: 0x40, red: 0x12, green: 0x34, blue: 0x56 }]);
+
+    let inverted = image.invert();
+
+    if inverted.row(0)[0] != (color::ARGB { alpha: 0x40, red: 0xED, green: 0xCB, blue: 0xA9 }) {
+        return Err(format!("Unexpected inverted color {}.", inverted.row(0)[0].to_hex()));
+    }
+
+    if inverted.invert() != image {
+        return Err(String::from("Inverting twice did not restore the original image."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blur_uniform() -> Result<(), String> {
+    let gray = color::ARGB { alpha: 0xFF, red: 0x80, green: 0x80, blue: 0x80 };
+    let image = Image::new_pixels(3, 3, vec![gray; 9]);
+
+    if image.blur(2) != image {
+        return Err(String::from("Blurring a uniform image should not change it."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blur_large_radius() -> Result<(), String> {
+    let image = Image::new_pixels(3, 2, vec![
+        color::ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x00 },
+        color::ARGB { alpha: 0xFF, red: 0x00, green: 0xFF, blue: 0x00 },
+        color::ARGB { alpha: 0x80, red: 0x00, green: 0x00, blue: 0xFF },
+        color::ARGB { alpha: 0x00, red: 0xFF, green: 0xFF, blue: 0xFF },
+        color::ARGB { alpha: 0xFF, red: 0x40, green: 0x40, blue: 0x40 },
+        color::ARGB { alpha: 0xFF, red: 0xC0, green: 0xC0, blue: 0xC0 }
+    ]);
+
+    //The window covers the whole image long before the radius overflows
+    if image.blur(usize::MAX) != image.blur(3) {
+        return Err(String::from("Expected a radius past the size of the image to blur as the size."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn auto_adjustments_stretch_range() -> Result<(), String> {
+    let image = Image::new_pixels(2, 1, vec![
+        color::ARGB { alpha: 0xFF, red: 0x40, green: 0x50, blue: 0x40 },
+        color::ARGB { alpha: 0x80, red: 0xC0, green: 0x60, blue: 0x40 }
+    ]);
+
+    let levels = image.auto_levels();
+
+    let expected = [
+        color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0x40 },
+        color::ARGB { alpha: 0x80, red: 0xFF, green: 0xFF, blue: 0x40 }
+    ];
+
+    if levels.row(0) != expected {
+        return Err(format!("Expected each channel to be stretched separately, got {:?}.", levels.row(0)));
+    }
+
+    let gray = |value: u8| color::ARGB { alpha: 0xFF, red: value, green: value, blue: value };
+    let contrast = Image::new_pixels(3, 1, vec![gray(0x40), gray(0x80), gray(0xC0)]).auto_contrast();
+
+    if contrast.row(0) != [gray(0x00), gray(0x80), gray(0xFF)] {
+        return Err(format!("Expected luminance to span the full range, got {:?}.", contrast.row(0)));
+    }
+
+    let flat = Image::new_pixels(2, 1, vec![image.row(0)[0]; 2]);
+
+    if flat.auto_contrast() != flat || flat.auto_levels() != flat {
+        return Err(String::from("Expected a single color to be left alone."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn kuwahara_keeps_edges() -> Result<(), String> {
+    let (dark, light) = (color::ARGB { alpha: 0xFF, red: 0x20, green: 0x20, blue: 0x20 }, color::ARGB { alpha: 0xFF, red: 0xE0, green: 0xE0, blue: 0xE0 });
+
+    //A hard vertical edge
+    let expected = Image::from_fn(8, 8, |x, _| if x < 4 { dark } else { light });
+
+    if expected.kuwahara(2) != expected {
+        return Err(String::from("Expected a clean edge to be kept exactly."));
+    }
+
+    //The same edge, with a speck of noise on each side
+    let mut pixels: Vec<color::ARGB> = expected.iter().flatten().copied().collect();
+    pixels[2 * 8 + 1] = light;
+    pixels[5 * 8 + 6] = dark;
+
+    let image = Image::new_pixels(8, 8, pixels);
+    let smoothed = image.kuwahara(2);
+
+    //Every window includes the speck itself, so it is faded toward its surroundings rather than removed
+    let faded = smoothed.iter().flatten().zip(expected.iter().flatten())
+        .all(|(actual, expected)| actual.red.abs_diff(expected.red) < (light.red - dark.red) / 4);
+
+    if !faded {
+        return Err(format!("Expected the specks to be smoothed and the edge kept, got {:?}.", smoothed.iter().flatten().map(|pixel| pixel.red).collect::<Vec<_>>()));
+    }
+
+    let anisotropic = expected.anisotropic_kuwahara(2);
+
+    //Blending sectors may soften values slightly, but the edge must not blur across
+    let blurred = anisotropic.iter().flatten().zip(expected.iter().flatten())
+        .any(|(actual, expected)| actual.red.abs_diff(expected.red) > 1);
+
+    if blurred {
+        return Err(format!("Expected the anisotropic filter to keep the edge, got {:?}.", anisotropic.iter().flatten().map(|pixel| pixel.red).collect::<Vec<_>>()));
+    }
+
+    if image.kuwahara(0) != image || Image::new(0, 0).anisotropic_kuwahara(3) != Image::new(0, 0) {
+        return Err(String::from("Expected a radius of 0 or an empty image to be left alone."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn kuwahara_large_radius() -> Result<(), String> {
+    let image = Image::from_fn(4, 3, |x, y| color::ARGB { alpha: 0xFF, red: (x * 60) as u8, green: (y * 100) as u8, blue: ((x + y) * 30) as u8 });
+
+    //The window covers the whole image long before the radius overflows
+    if image.kuwahara(usize::MAX) != image.kuwahara(4) || image.anisotropic_kuwahara(usize::MAX) != image.anisotropic_kuwahara(4) {
+        return Err(String::from("Expected a radius past the size of the image to filter as the size."));
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -1 +1,145 @@
-pub mod bitmap;
\ No newline at end of file
+pub mod bitmap;
+pub mod png;
+pub mod gif;
+pub mod netpbm;
+pub mod dds;
+pub mod raw;
+pub mod ilbm;
+#[cfg(feature = "std")]
+pub mod codec;
+
+use crate::constants;
+
+///
+/// An image file format supported by this crate
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Bitmap,
+    Png,
+    Gif,
+    Netpbm,
+    Dds,
+    Ilbm
+}
+
+impl ImageFormat {
+    ///
+    /// File extensions of the formats images can be written in. Netpbm is
+    /// listed by each of its kinds, since they're told apart by extension.
+    ///
+    pub const WRITABLE_EXTENSIONS: &'static [&'static str] = &["bmp", "gif", "pbm", "pgm", "ppm", "pam"];
+
+    ///
+    /// Short, human-readable name of the format
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bitmap => "BMP",
+            Self::Png => "PNG",
+            Self::Gif => "GIF",
+            Self::Netpbm => "Netpbm",
+            Self::Dds => "DDS",
+            Self::Ilbm => "ILBM"
+        }
+    }
+
+    ///
+    /// File extension usually used for the format
+    ///
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Bitmap => "bmp",
+            Self::Png => "png",
+            Self::Gif => "gif",
+            Self::Netpbm => "ppm",
+            Self::Dds => "dds",
+            Self::Ilbm => "iff"
+        }
+    }
+
+    ///
+    /// The media type of the format, as given in a data URI or HTTP header
+    ///
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Bitmap => "image/bmp",
+            Self::Png => "image/png",
+            Self::Gif => "image/gif",
+            Self::Netpbm => "image/x-portable-anymap",
+            Self::Dds => "image/vnd-ms.dds",
+            Self::Ilbm => "image/x-ilbm"
+        }
+    }
+
+    ///
+    /// Find the format with the given media type, ignoring any parameters
+    ///
+    pub fn from_mime_type(mime_type: &str) -> Option<ImageFormat> {
+        let mime_type = mime_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+
+        match mime_type.as_str() {
+            "image/bmp" | "image/x-bmp" | "image/x-ms-bmp" => Some(Self::Bitmap),
+            "image/png" => Some(Self::Png),
+            "image/gif" => Some(Self::Gif),
+            "image/x-portable-anymap" | "image/x-portable-bitmap" | "image/x-portable-graymap"
+                | "image/x-portable-pixmap" | "image/x-portable-arbitrarymap" => Some(Self::Netpbm),
+            "image/vnd-ms.dds" | "image/vnd.ms-dds" => Some(Self::Dds),
+            "image/x-ilbm" | "image/x-iff" => Some(Self::Ilbm),
+            _ => None
+        }
+    }
+
+    ///
+    /// Whether images can be written in the format
+    ///
+    pub fn can_encode(&self) -> bool {
+        matches!(self, Self::Bitmap | Self::Gif | Self::Netpbm)
+    }
+
+    ///
+    /// Find the format with the given name or file extension
+    ///
+    pub fn from_extension(extension: &str) -> Option<ImageFormat> {
+        let extension = extension.trim_start_matches('.').to_ascii_lowercase();
+
+        match extension.as_str() {
+            "bmp" | "dib" => Some(Self::Bitmap),
+            "png" => Some(Self::Png),
+            "gif" => Some(Self::Gif),
+            "pbm" | "pgm" | "ppm" | "pnm" | "pam" => Some(Self::Netpbm),
+            "dds" => Some(Self::Dds),
+            "iff" | "ilbm" | "lbm" => Some(Self::Ilbm),
+            _ => None
+        }
+    }
+}
+
+///
+/// Detect the format of an image from the signature at the
+/// start of its bytes
+///
+pub fn detect(bytes: &[u8]) -> Option<ImageFormat> {
+    if bytes.len() >= 2 && u16::from_le_bytes([bytes[0], bytes[1]]) == constants::bitmap::SIGNATURE {
+        Some(ImageFormat::Bitmap)
+    }
+    else if bytes.starts_with(&constants::png::SIGNATURE) {
+        Some(ImageFormat::Png)
+    }
+    else if bytes.starts_with(&constants::gif::SIGNATURE_89A) || bytes.starts_with(&constants::gif::SIGNATURE_87A) {
+        Some(ImageFormat::Gif)
+    }
+    else if bytes.starts_with(&constants::dds::SIGNATURE) {
+        Some(ImageFormat::Dds)
+    }
+    else if bytes.starts_with(&constants::ilbm::FORM) && bytes.get(8..12) == Some(&constants::ilbm::FORM_TYPE) {
+        Some(ImageFormat::Ilbm)
+    }
+    //Netpbm has only a two byte magic number, so also needs the whitespace after it
+    else if matches!(bytes, [b'P', b'1'..=b'7', space, ..] if space.is_ascii_whitespace()) {
+        Some(ImageFormat::Netpbm)
+    }
+    else {
+        None
+    }
+}
\ No newline at end of file
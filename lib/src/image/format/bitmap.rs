@@ -1,14 +1,29 @@
-#[cfg(test)]
-mod tests;
+//Decoding must not panic on any input, so only tests may index or unwrap
+#![deny(clippy::indexing_slicing, clippy::unwrap_used, clippy::expect_used)]
 
-use std::collections::HashMap;
-use std::collections::hash_map::Entry;
+#[cfg(all(test, feature = "std"))]
+#[allow(clippy::indexing_slicing, clippy::unwrap_used)]
+mod tests;
 
-use crate::{color, image, utility, convert::ConvertableFrom};
-use image::Image;
+use alloc::{format, string::String, vec, vec::Vec};
+use alloc::collections::{BTreeMap, BTreeSet, btree_map::Entry};
+#[cfg(feature = "std")]
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use core::fmt::{self, Write as _};
+#[cfg(feature = "std")]
+use std::io::{self, Read as _};
+
+use crate::{color, image, log, utility, convert::ConvertableFrom, error::ImageError};
+use image::{Image, ImageMetadata};
+use image::orientation::Orientation;
+use image::resolution::Resolution;
 use utility::FromBitSlice;
 use crate::constants::bitmap;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 ///
 /// A image in bmp format.
 /// Bitmap format:
@@ -20,6 +35,18 @@ pub struct Bitmap {
     pub info_header: BitmapInfoHeader,
     pub color_table: BitmapColorTable,
     pub pixels: BitmapPixels,
+    ///
+    /// The ICC color profile embedded in the file, where the V5 info header says it is
+    ///
+    pub icc_profile: Option<Vec<u8>>,
+    ///
+    /// The AND mask following the pixel data, as icons store transparency, with a bit
+    /// for each pixel in the order they are stored, set where it is transparent. The
+    /// info header in the file declares twice the height, for the pixels and then the
+    /// mask, though the height here is that of the image. It is only read when asked
+    /// for with DecodeOptions::and_mask.
+    ///
+    pub mask: Option<Vec<bool>>,
 }
 
 ///
@@ -61,14 +88,14 @@ pub struct BitmapInfoHeader {
     pub size: u32,
     ///
     /// Horizontal width of bitmap, in pixels.
-    /// If negative, indicates the image is mirrored
-    /// vertically.
+    /// If negative, columns are stored right to left;
+    /// see BitmapInfoHeader::orientation.
     ///
     pub width: i32,
     ///
     /// Vertical height of bitmap, in pixels.
-    /// If negative, indicates the image is mirrored
-    /// horizontally.
+    /// If negative, rows are stored top to bottom;
+    /// see BitmapInfoHeader::orientation.
     ///
     pub height: i32,
     ///
@@ -97,14 +124,10 @@ pub struct BitmapInfoHeader {
     pub image_size: u32,
     ///
     /// Horizontal resolution in pixels per meter
-    /// If negative, indicates the image is mirrored
-    /// vertically.
     ///
     pub x_pixels_per_meter: i32,
     ///
     /// Vertical resolution in pixels per meter
-    /// If negative, indicates the image is mirrored
-    /// horizontally.
     ///
     pub y_pixels_per_meter: i32,
     ///
@@ -116,6 +139,145 @@ pub struct BitmapInfoHeader {
     /// 0 = all
     ///
     pub important_colors: u32,
+    ///
+    /// Channel masks which follow a BITMAPINFOHEADER when compression
+    /// is BI_BITFIELDS, or end an Adobe V2 or V3 info header.
+    /// Later versions of the header hold them in the V4 fields.
+    ///
+    pub bitfields: Option<BitmapMasks>,
+    ///
+    /// Fields added by the BITMAPV4HEADER, present
+    /// if the info header is at least 108 bytes
+    ///
+    pub v4: Option<BitmapV4Fields>,
+    ///
+    /// Fields added by the BITMAPV5HEADER, present
+    /// if the info header is at least 124 bytes
+    ///
+    pub v5: Option<BitmapV5Fields>,
+}
+
+///
+/// Bits of a 16 or 32-bit pixel which hold each channel
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BitmapMasks {
+    pub red: u32,
+    pub green: u32,
+    pub blue: u32,
+    ///
+    /// If 0, pixels are opaque
+    ///
+    pub alpha: u32,
+}
+
+impl BitmapMasks {
+    ///
+    /// The layout of uncompressed 16-bit pixels, with 5 bits for each color and the top bit unused
+    ///
+    pub const RGB_555: Self = Self { red: 0x7C00, green: 0x03E0, blue: 0x001F, alpha: 0 };
+
+    ///
+    /// The usual layout of 16-bit pixels with bitfields, with 6 bits for green
+    ///
+    pub const RGB_565: Self = Self { red: 0xF800, green: 0x07E0, blue: 0x001F, alpha: 0 };
+
+    ///
+    /// The layout of uncompressed 32-bit pixels, with a byte for each channel
+    ///
+    pub const ARGB_8888: Self = Self { red: 0x00FF_0000, green: 0x0000_FF00, blue: 0x0000_00FF, alpha: 0xFF00_0000 };
+
+    ///
+    /// Get the color of the pixel, scaling each channel from the bits of its mask up to 8 bits
+    ///
+    pub fn extract(&self, pixel: u32) -> color::ARGB {
+        let channel = |mask: u32| {
+            let max = mask.checked_shr(mask.trailing_zeros()).unwrap_or_default();
+
+            match max {
+                0 => 0,
+                max => (u64::from((pixel & mask) >> mask.trailing_zeros()) * 255 / u64::from(max)) as u8
+            }
+        };
+
+        color::ARGB {
+            alpha: if self.alpha == 0 { 0xFF } else { channel(self.alpha) },
+            red: channel(self.red),
+            green: channel(self.green),
+            blue: channel(self.blue)
+        }
+    }
+
+    ///
+    /// Get the pixel holding the color, scaling each channel from 8 bits down to the bits of its mask
+    ///
+    pub fn pack(&self, color: color::ARGB) -> u32 {
+        let channel = |mask: u32, value: u8| {
+            let max = mask.checked_shr(mask.trailing_zeros()).unwrap_or_default();
+            let scaled = (u64::from(value) * u64::from(max) + 127) / 255;
+
+            (scaled as u32).checked_shl(mask.trailing_zeros()).unwrap_or_default() & mask
+        };
+
+        channel(self.alpha, color.alpha) | channel(self.red, color.red) | channel(self.green, color.green) | channel(self.blue, color.blue)
+    }
+}
+
+///
+/// Fields which the BITMAPV4HEADER adds
+/// after those of the BITMAPINFOHEADER
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BitmapV4Fields {
+    ///
+    /// Bits of each pixel holding each channel,
+    /// if compression is BI_BITFIELDS
+    ///
+    pub red_mask: u32,
+    pub green_mask: u32,
+    pub blue_mask: u32,
+    pub alpha_mask: u32,
+    ///
+    /// The color space of the pixels; see constants::bitmap::color_space
+    ///
+    pub color_space_type: u32,
+    ///
+    /// CIE XYZ coordinates of the red, green and blue endpoints, as
+    /// 2.30 fixed point numbers, if the color space is calibrated
+    ///
+    pub endpoints: [i32; 9],
+    ///
+    /// Tone response curves of red, green and blue, as 16.16 fixed
+    /// point numbers, if the color space is calibrated
+    ///
+    pub gamma_red: u32,
+    pub gamma_green: u32,
+    pub gamma_blue: u32,
+}
+
+///
+/// Fields which the BITMAPV5HEADER adds
+/// after those of the BITMAPV4HEADER
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BitmapV5Fields {
+    ///
+    /// Rendering intent of the color profile
+    ///
+    pub intent: u32,
+    ///
+    /// Offset of the color profile from the start of the info header,
+    /// if the color space is a linked or embedded profile
+    ///
+    pub profile_data: u32,
+    ///
+    /// Size of the color profile, in bytes
+    ///
+    pub profile_size: u32,
+    ///
+    /// Should always be 0
+    ///
+    pub reserved: u32,
 }
 
 ///
@@ -149,9 +311,136 @@ impl Default for BitmapPixelData {
 ///
 /// The actual image data in the bitmap.
 ///
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Eq, Default)]
 pub struct BitmapPixels {
     pub pixels: BitmapPixelData,
+    ///
+    /// The run-length encoded pixel data of a bitmap converted from an image
+    /// with RLE compression, so it isn't encoded again to be written
+    ///
+    encoded: Option<Vec<u8>>
+}
+
+impl PartialEq for BitmapPixels {
+    fn eq(&self, other: &Self) -> bool {
+        self.pixels == other.pixels
+    }
+}
+
+///
+/// Options for reading a bmp from bytes
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DecodeOptions {
+    ///
+    /// Reject any violation of the format, such as a wrong file size or short scanlines.
+    /// Otherwise, the decoder repairs what it can and reports what it repaired.
+    ///
+    pub strict: bool,
+    ///
+    /// Read the bmp as an icon's image, whose info header declares twice the height
+    /// of the image, for the pixel data and then the AND mask which follows it
+    ///
+    pub and_mask: bool,
+}
+
+///
+/// Violations of the format found while reading a bmp, which
+/// were repaired or ignored rather than stopping the decode
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DecodeReport {
+    pub warnings: Vec<String>,
+    ///
+    /// Those of the warnings which break the format, rather than being unusual
+    /// but allowed. Strict decoding fails on the first of these.
+    ///
+    pub violations: Vec<String>,
+}
+
+impl DecodeReport {
+    ///
+    /// Whether nothing was wrong with the bmp
+    ///
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    ///
+    /// Log each warning
+    ///
+    pub fn log(&self) {
+        for warning in &self.warnings {
+            log::warn(format_args!("{warning}"));
+        }
+    }
+}
+
+///
+/// How serious a conformance finding is
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    ///
+    /// The bmp breaks the format, and a strict reader may reject it
+    ///
+    Error,
+    ///
+    /// The bmp is allowed by the format, but is unusual or wasteful
+    ///
+    Warning
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning"
+        }
+    }
+}
+
+///
+/// Something found wrong with a bmp while checking its conformance
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+///
+/// Everything found wrong with a bmp, by strict decoding and checks
+/// of the structure which the decoder doesn't need to make
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConformanceReport {
+    pub findings: Vec<Finding>,
+}
+
+impl ConformanceReport {
+    ///
+    /// Whether the bmp conforms to the format, though it may have warnings
+    ///
+    pub fn passed(&self) -> bool {
+        !self.findings.iter().any(|finding| finding.severity == Severity::Error)
+    }
+
+    fn push(&mut self, severity: Severity, message: String) {
+        self.findings.push(Finding { severity, message });
+    }
+}
+
+///
+/// Options for creating a grid of pixels from a bmp or png image
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ImageConvertData {
+    ///
+    /// The color to use for pixels whose palette index is outside
+    /// of the color table. If not given, such pixels are an error.
+    ///
+    pub palette_fallback: Option<color::ARGB>,
 }
 
 ///
@@ -162,12 +451,9 @@ pub struct BitmapPixels {
 pub struct BitmapConvertData {
     ///
     /// Pixel bit depth, i.e. the number of
-    /// bits required to represent a color.
-    ///
-    /// 1, 4, 8: Bits contain index to a color in the color table.
-    /// 16, 24, 32: Bits contain color data.
+    /// bits required to represent a color
     ///
-    pub bit_depth: u16,
+    pub bit_depth: BitDepth,
     ///
     /// The type of compression used.
     ///     0 = BI_RGB   no compression
@@ -176,434 +462,2244 @@ pub struct BitmapConvertData {
     ///
     pub compression: u32,
     ///
-    /// Horizontal resolution in pixels per meter
-    /// If negative, indicates the image is mirrored
-    /// vertically.
+    /// Physical density of the pixels
     ///
-    pub x_pixels_per_meter: i32,
+    pub resolution: Resolution,
     ///
-    /// Vertical resolution in pixels per meter
-    /// If negative, indicates the image is mirrored
-    /// horizontally.
+    /// Store rows top to bottom, with a negative height, rather than bottom to top.
+    /// Compressed bitmaps can't be top-down.
     ///
-    pub y_pixels_per_meter: i32,
+    pub top_down: bool,
+    ///
+    /// The order of the colors of the color table, for indexed bit depths
+    ///
+    pub palette_order: PaletteOrder,
+    ///
+    /// Store an AND mask after the pixel data, as icons do, set for pixels less opaque than
+    /// constants::bitmap::MASK_ALPHA_THRESHOLD, and double the height in the info header
+    /// to hold it. Compressed bitmaps can't have a mask.
+    ///
+    pub and_mask: bool,
 }
 
-impl Bitmap {
+///
+/// The order to put the colors of a bmp's color table in
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaletteOrder {
     ///
-    /// Get the color at index i of the color table, if it exists
-    /// 
-    pub fn color_table_color(&self, i: usize) -> Option<color::ARGB> {
-        if i >= self.color_table.colors.len() {
-            None
-        }
-        else {
-            Some(self.color_table.colors[i])
-        }
-    }
-
-    pub fn formatted_bitstring(&self) -> String {
-
-        fn u16_bits(n: u16) -> [u8; 2] {
-            n.to_le_bytes()
-        }
+    /// The order in which the colors are first found,
+    /// in the order the pixels are stored
+    ///
+    #[default]
+    FirstSeen,
+    ///
+    /// The colors used by the most pixels first, which the format
+    /// calls the order of importance. Runs of the same index tend
+    /// to be longer, so this can also compress better.
+    ///
+    Frequency
+}
 
-        fn u32_bits(n: u32) -> [u8; 4] {
-            n.to_le_bytes()
-        }
+///
+/// The bit depth to encode a bmp with
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitDepth {
+    ///
+    /// The fewest bits which hold the image as it is: 32 if any pixel isn't opaque, otherwise
+    /// 1, 4 or 8 if it has few enough colors to index, or else 24. Run-length encoding
+    /// always uses the bit depth of its compression.
+    ///
+    #[default]
+    Auto,
+    ///
+    /// 1, 4, 8: Bits contain index to a color in the color table.
+    /// 16, 24, 32: Bits contain color data.
+    ///
+    Bits(u16)
+}
 
-        fn i32_bits(n: i32) -> [u8; 4] {
-            n.to_le_bytes()
+impl BitDepth {
+    ///
+    /// Get the number of bits, unless they are chosen from the image
+    ///
+    pub fn bits(&self) -> Option<u16> {
+        match self {
+            Self::Auto => None,
+            Self::Bits(bits) => Some(*bits)
         }
+    }
 
-        fn bitstring(bits: &[u8], skip_last: usize) -> String {
-            bits.iter()
-                .take(4 - skip_last)
-                .map(|n| format!("{:#04X?},", n))
-                .reduce(|a, b| format!("{a} {b}"))
-                .unwrap()
-        }
+    ///
+    /// Get the number of bits to encode the image with the given compression in
+    ///
+    pub fn resolve(&self, image: &Image, compression: u32) -> u16 {
+        match (self, compression) {
+            (Self::Bits(bits), _) => *bits,
+            (Self::Auto, bitmap::compression::BI_RLE8) => 8,
+            (Self::Auto, bitmap::compression::BI_RLE4) => 4,
+            (Self::Auto, bitmap::compression::BI_BITFIELDS) => 32,
+            (Self::Auto, _) => {
+                let pixels = || image.pixels.iter();
+
+                if pixels().any(|pixel| pixel.alpha != 0xFF) {
+                    return 32;
+                }
 
-        let color_table = if self.color_table.colors.is_empty() {
-            String::from("//Empty")
-        }
-        else {
-            let mut row = -1;
-            self.color_table.colors.iter()
-                .map(|color| {
-                    row += 1;
-                    let color_u32 = color.as_u32(false);
-                    format!("{: <22} //Color table entry {row}", bitstring(&u32_bits(color_u32), 0))
-                })
-                .reduce(|a, b| format!("{a}\n{b}"))
-                .unwrap()
-        };
+                //Stop counting once there are too many colors to index
+                let mut colors: BTreeSet<u32> = BTreeSet::new();
 
-        let pixel_data = match &self.pixels.pixels {
-            BitmapPixelData::Colors(pixels) => {
-                if pixels.is_empty() {
-                    String::from("//Empty")
-                }
-                else {
-                    let columns = self.info_header.width.unsigned_abs() as usize;
-                    let bytes_per_pixel = (self.info_header.bit_depth as f32 / 8_f32).ceil() as usize;
-                    let mut row = -1;
-                    pixels.chunks_exact(columns)
-                    .map(|scanline| {
-                        row += 1;
-                        let mut col = -1;
-
-                        let columns = scanline.iter()
-                            .map(|pixel| {
-                                col += 1;
-                                let color_u32 = if self.info_header.bit_depth < 32_u16 {
-                                    pixel.with_alpha(0_u8).as_u32(false)
-                                }
-                                else {
-                                    pixel.as_u32(false)
-                                };
-                                
-                                format!("{: <22} //Column {col}", bitstring(&u32_bits(color_u32), 4 - bytes_per_pixel))
-                            })
-                            .reduce(|a, b| format!("{a}\n{b}"))
-                            .unwrap();
-
-                        format!("//Row {row}\n{columns}")
-                    })
-                    .reduce(|a, b| format!("{a}\n\n{b}"))
-                    .unwrap()
-                }
-            },
-            BitmapPixelData::Indices(indices) => {
-                if indices.is_empty() {
-                    String::from("//Empty")
+                for pixel in pixels() {
+                    if colors.insert(pixel.as_u32(true)) && colors.len() > 256 {
+                        return 24;
+                    }
                 }
-                else {
-                    let columns = self.info_header.width.unsigned_abs() as usize;
-                    let mut row = -1;
-                    indices.chunks_exact(columns)
-                    .map(|scanline| {
-                        row += 1;
-                        let mut column = -1;
-
-                        let columns = scanline.chunks(4)
-                            .map(|index_chunk| {
-                                
-                                let column_start = column;
-
-                                let s = index_chunk.iter()
-                                    .map(|index| {
-                                        let s = bitstring(&[*index; 1], 0_usize);
-
-                                        column += 1;
-                                        s
-                                    })
-                                    .reduce(|a, b| format!("{a} {b}"))
-                                    .unwrap();
-
-                                format!("{: <22} //Columns {} - {column}", s, column_start + 1)
-                            })
-                            .reduce(|a, b| format!("{a}\n{b}"))
-                            .unwrap();
-
-                        format!("//Row {row}\n{columns}")
-                    })
-                    .reduce(|a, b| format!("{a}\n\n{b}"))
-                    .unwrap()
+
+                match colors.len() {
+                    0..=2 => 1,
+                    3..=16 => 4,
+                    _ => 8
                 }
             }
-        };
-
-        format!("
-//Header
-{: <22} //Signature = {}
-{: <22} //File Size = {}
-{: <22} //Reserved = {}
-{: <22} //Data Offset = {}
-
-//Info Header
-{: <22} //Info Header Size = {}
-{: <22} //Width = {}
-{: <22} //Height = {}
-{: <22} //Planes = {}
-{: <22} //Bit Depth = {}
-{: <22} //Compression = {}
-{: <22} //Image Size = {}
-{: <22} //X Resolution = {}
-{: <22} //Y Resolution = {}
-{: <22} //Colors Used = {}
-{: <22} //Important Colors = {}
-
-//Color Table
-{}
-
-//Pixel Data
-{}",
-            bitstring(&u16_bits(self.header.signature), 0), self.header.signature,
-            bitstring(&u32_bits(self.header.file_size), 0), self.header.file_size,
-            bitstring(&u32_bits(self.header.reserved), 0), self.header.reserved,
-            bitstring(&u32_bits(self.header.data_offset), 0), self.header.data_offset,
-
-            bitstring(&u32_bits(self.info_header.size), 0), self.info_header.size,
-            bitstring(&i32_bits(self.info_header.width), 0), self.info_header.width,
-            bitstring(&i32_bits(self.info_header.height), 0), self.info_header.height,
-            bitstring(&u16_bits(self.info_header.planes), 0), self.info_header.planes,
-            bitstring(&u16_bits(self.info_header.bit_depth), 0), self.info_header.bit_depth,
-            bitstring(&u32_bits(self.info_header.compression), 0), self.info_header.compression,
-            bitstring(&u32_bits(self.info_header.image_size), 0), self.info_header.image_size,
-            bitstring(&i32_bits(self.info_header.x_pixels_per_meter), 0), self.info_header.x_pixels_per_meter,
-            bitstring(&i32_bits(self.info_header.y_pixels_per_meter), 0), self.info_header.y_pixels_per_meter,
-            bitstring(&u32_bits(self.info_header.colors_used), 0), self.info_header.colors_used,
-            bitstring(&u32_bits(self.info_header.important_colors), 0), self.info_header.important_colors,
-            color_table,
-            pixel_data
-        )
-    }
-}
-
-impl From<Bitmap> for BitmapConvertData {
-    fn from(value: Bitmap) -> Self {
-        Self 
-        { 
-            bit_depth: value.info_header.bit_depth, 
-            compression: value.info_header.compression,
-            x_pixels_per_meter: value.info_header.x_pixels_per_meter,
-            y_pixels_per_meter: value.info_header.y_pixels_per_meter
         }
     }
 }
 
-impl From<&Bitmap> for BitmapConvertData {
-    fn from(value: &Bitmap) -> Self {
-        Self 
-        { 
-            bit_depth: value.info_header.bit_depth, 
-            compression: value.info_header.compression,
-            x_pixels_per_meter: value.info_header.x_pixels_per_meter,
-            y_pixels_per_meter: value.info_header.y_pixels_per_meter
-        }
+impl From<u16> for BitDepth {
+    fn from(value: u16) -> Self {
+        Self::Bits(value)
     }
 }
 
 ///
-/// Read a bmp from an array of bytes
+/// A part of an encoded bitmap
 ///
-impl TryFrom<&[u8]> for Bitmap {
-    type Error = String;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitmapSection {
+    ///
+    /// The file header and info header
+    ///
+    Header,
+    ///
+    /// The color table
+    ///
+    Palette,
+    ///
+    /// The pixel data
+    ///
+    Pixels
+}
 
-    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        let mut offset: usize = 0;
+///
+/// Settings for annotating the bytes of a bitmap
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitstringOptions {
+    ///
+    /// Show the offset of each line's first byte in the file
+    ///
+    pub offsets: bool,
+    ///
+    /// Number of bytes of pixel data to show per line. By default,
+    /// each pixel gets its own line, or 4 indices share a line.
+    ///
+    pub bytes_per_line: Option<usize>,
+    ///
+    /// The sections to show
+    ///
+    pub sections: Vec<BitmapSection>
+}
 
-        fn get_next_bytes<'a, 'b>(buffer: &'a [u8], offset: &'b mut usize, n: usize) -> &'a [u8] {
-            let start = *offset;
-            *offset += n;
-            &buffer[start..*offset]
+impl Default for BitstringOptions {
+    fn default() -> Self {
+        Self {
+            offsets: false,
+            bytes_per_line: None,
+            sections: vec![BitmapSection::Header, BitmapSection::Palette, BitmapSection::Pixels]
         }
+    }
+}
 
-        let next_u16 =
-            |offset: &mut usize| u16::reduce_bit_slice(get_next_bytes(value, offset, 2));
-
-        let next_u32 =
-            |offset: &mut usize| u32::reduce_bit_slice(get_next_bytes(value, offset, 4));
+impl BitmapInfoHeader {
+    ///
+    /// Get the order in which the pixels are stored, from the signs of the dimensions
+    ///
+    pub fn orientation(&self) -> Orientation {
+        Orientation::from_signs(self.width, self.height)
+    }
 
-        let next_i32 =
-            |offset: &mut usize| i32::reduce_bit_slice(get_next_bytes(value, offset, 4));
+    ///
+    /// Get the physical density of the pixels
+    ///
+    pub fn resolution(&self) -> Resolution {
+        Resolution::from_signed(self.x_pixels_per_meter, self.y_pixels_per_meter)
+    }
 
-        //File header
-        let header = BitmapHeader {
-            signature: next_u16(&mut offset),
-            file_size: next_u32(&mut offset),
-            reserved: next_u32(&mut offset),
-            data_offset: next_u32(&mut offset),
-        };
+    ///
+    /// Get the name of the compression method
+    /// 
+    pub fn compression_name(&self) -> &'static str {
+        compression_name(self.compression)
+    }
 
-        //Image header
-        let info_header = BitmapInfoHeader {
-            size: next_u32(&mut offset),
-            width: next_i32(&mut offset),
-            height: next_i32(&mut offset),
-            planes: next_u16(&mut offset),
-            bit_depth: next_u16(&mut offset),
-            compression: next_u32(&mut offset),
-            image_size: next_u32(&mut offset),
-            x_pixels_per_meter: next_i32(&mut offset),
-            y_pixels_per_meter: next_i32(&mut offset),
-            colors_used: next_u32(&mut offset),
-            important_colors: next_u32(&mut offset),
-        };
-        //Color table
-
-        //All data between the current offset and the data offset from the header goes in the color table
-        let color_table_length = match (header.data_offset as usize).checked_sub(offset) {
-            Some(len) => Ok(len),
-            None => Err(String::from(
-                "Bitmap data is malformed; data offset points to the info header.",
-            )),
-        }?;
-
-        let palette: Option<Vec<color::ARGB>> = if color_table_length > 0 {
-            let color_table_raw = get_next_bytes(value, &mut offset, color_table_length);
-
-            //Each color in the pallette is 4 bytes, the first 3 representing the Blue, Green and Red intensities respectively, with the last unused or alpha
-            Some(
-                color_table_raw
-                    .chunks(4)
-                    .map(|chunk| color::ARGB {
-                        blue: *chunk.first().unwrap_or(&0),
-                        green: *chunk.get(1).unwrap_or(&0),
-                        red: *chunk.get(2).unwrap_or(&0),
-                        alpha: *chunk.get(3).unwrap_or(&0),
-                    })
-                    .collect(),
-            )
-        } else {
-            None
-        };
+    ///
+    /// Get the masks of the channels of each pixel if compression is BI_BITFIELDS,
+    /// from the V4 fields if the info header has them, or else the bitfields
+    /// 
+    pub fn channel_masks(&self) -> Option<BitmapMasks> {
+        if self.compression != bitmap::compression::BI_BITFIELDS {
+            return None;
+        }
 
-        let color_table = BitmapColorTable {
-            colors: palette.unwrap_or_default(),
+        match (self.v4, self.bitfields) {
+            (Some(v4), _) => Some(BitmapMasks { red: v4.red_mask, green: v4.green_mask, blue: v4.blue_mask, alpha: v4.alpha_mask }),
+            (None, bitfields) => bitfields
+        }
+    }
+
+    ///
+    /// Whether the bitfields follow the info header, rather than being part of it
+    /// 
+    fn bitfields_follow_header(&self) -> bool {
+        self.size < bitmap::V2_INFO_HEADER_SIZE && self.compression == bitmap::compression::BI_BITFIELDS && self.bitfields.is_some()
+    }
+
+    ///
+    /// Get the offset in the file at which the color table starts, after the info
+    /// header and the bitfields which may follow it
+    /// 
+    pub fn color_table_offset(&self) -> usize {
+        let bitfields_size = if self.bitfields_follow_header() { bitmap::BITFIELDS_SIZE } else { 0 };
+        (bitmap::HEADER_SIZE + self.size) as usize + bitfields_size
+    }
+
+    ///
+    /// Get the name of the color space, if the info header has one
+    /// 
+    pub fn color_space_name(&self) -> Option<&'static str> {
+        self.v4.map(|v4| match v4.color_space_type {
+            bitmap::color_space::CALIBRATED_RGB => "Calibrated RGB",
+            bitmap::color_space::SRGB => "sRGB",
+            bitmap::color_space::WINDOWS_COLOR_SPACE => "Windows",
+            bitmap::color_space::PROFILE_LINKED => "Linked profile",
+            bitmap::color_space::PROFILE_EMBEDDED => "Embedded profile",
+            _ => "Unknown"
+        })
+    }
+
+    ///
+    /// Get the range of bytes in the file, start inclusive and end exclusive,
+    /// of the embedded color profile, if the info header declares one
+    /// 
+    pub fn embedded_profile_range(&self) -> Option<(usize, usize)> {
+        match (self.v4, self.v5) {
+            (Some(v4), Some(v5)) if v4.color_space_type == bitmap::color_space::PROFILE_EMBEDDED && v5.profile_size > 0 => {
+                let start = bitmap::HEADER_SIZE as usize + v5.profile_data as usize;
+                Some((start, start + v5.profile_size as usize))
+            },
+            _ => None
+        }
+    }
+
+    ///
+    /// The fields of the info header past those of the BITMAPINFOHEADER which are present
+    /// and fit in its size, and the bitfields following it if there are any, with their
+    /// names and values, in the order they are stored
+    /// 
+    fn extended_fields(&self) -> Vec<(&'static str, u32)> {
+        let mut fields = Vec::new();
+
+        if let Some(masks) = self.bitfields.filter(|_| self.size >= bitmap::V2_INFO_HEADER_SIZE || self.bitfields_follow_header()) {
+            if self.size < bitmap::V4_INFO_HEADER_SIZE {
+                fields.extend([("Red Mask", masks.red), ("Green Mask", masks.green), ("Blue Mask", masks.blue)]);
+            }
+
+            if (bitmap::V3_INFO_HEADER_SIZE..bitmap::V4_INFO_HEADER_SIZE).contains(&self.size) {
+                fields.push(("Alpha Mask", masks.alpha));
+            }
+        }
+
+        if let Some(v4) = self.v4.filter(|_| self.size >= bitmap::V4_INFO_HEADER_SIZE) {
+            fields.extend([("Red Mask", v4.red_mask), ("Green Mask", v4.green_mask), ("Blue Mask", v4.blue_mask),
+                ("Alpha Mask", v4.alpha_mask), ("Color Space Type", v4.color_space_type)]);
+            fields.extend(["Red X", "Red Y", "Red Z", "Green X", "Green Y", "Green Z", "Blue X", "Blue Y", "Blue Z"]
+                .into_iter()
+                .zip(v4.endpoints.map(|endpoint| endpoint as u32)));
+            fields.extend([("Gamma Red", v4.gamma_red), ("Gamma Green", v4.gamma_green), ("Gamma Blue", v4.gamma_blue)]);
+
+            if let Some(v5) = self.v5.filter(|_| self.size >= bitmap::V5_INFO_HEADER_SIZE) {
+                fields.extend([("Intent", v5.intent), ("Profile Data", v5.profile_data), ("Profile Size", v5.profile_size), ("Reserved", v5.reserved)]);
+            }
+        }
+
+        fields
+    }
+
+    ///
+    /// Size of the file as computed from the headers, a color table
+    /// of the given length, and pixel data, which when run-length
+    /// encoded is only as large as the image size says
+    /// 
+    fn computed_file_size(&self, palette_size: usize) -> usize {
+        let pixel_bytes = match self.compression {
+            bitmap::compression::BI_RLE8 | bitmap::compression::BI_RLE4 => self.image_size as usize,
+            _ => self.row_size() * self.height.unsigned_abs() as usize
+        };
+
+        self.color_table_offset()
+            + (bitmap::COLOR_TABLE_SIZE_FACTOR as usize * palette_size)
+            + pixel_bytes
+    }
+
+    ///
+    /// Size, in bytes, of each row of pixel data, including padding
+    /// 
+    fn row_size(&self) -> usize {
+        let bits_per_row = self.width.unsigned_abs() as usize * self.bit_depth as usize;
+        utility::round_to_next_multiple_of_4(bits_per_row.div_ceil(8) as i32)
+    }
+
+    ///
+    /// Size, in bytes, of each row of an AND mask, which has a bit per pixel
+    /// 
+    fn mask_row_size(&self) -> usize {
+        utility::round_to_next_multiple_of_4((self.width.unsigned_abs() as usize).div_ceil(8) as i32)
+    }
+
+    ///
+    /// Size, in bytes, of an AND mask following the pixel data, or 0 if
+    /// the pixel data is compressed, so can't be followed by one
+    /// 
+    fn mask_size(&self) -> usize {
+        if ![bitmap::compression::BI_RGB, bitmap::compression::BI_BITFIELDS].contains(&self.compression) {
+            return 0;
+        }
+
+        self.mask_row_size() * self.height.unsigned_abs() as usize
+    }
+}
+
+impl Bitmap {
+    ///
+    /// Get the color at index i of the color table, if it exists
+    /// 
+    pub fn color_table_color(&self, i: usize) -> Option<color::ARGB> {
+        self.color_table.colors.get(i).copied()
+    }
+
+    ///
+    /// Get the name of the compression method used by the bitmap
+    /// 
+    pub fn compression_name(&self) -> &'static str {
+        self.info_header.compression_name()
+    }
+
+    ///
+    /// Get the size of the file as computed from the headers, color table,
+    /// pixel data and mask, as opposed to the size declared in the header
+    /// 
+    pub fn computed_file_size(&self) -> usize {
+        self.info_header.computed_file_size(self.color_table.colors.len())
+            + if self.has_mask() { self.info_header.mask_size() } else { 0 }
+    }
+
+    ///
+    /// Whether an AND mask follows the pixel data, which only uncompressed pixel data can have
+    /// 
+    fn has_mask(&self) -> bool {
+        self.mask.is_some() && self.info_header.mask_size() > 0
+    }
+
+    ///
+    /// The height as stored in the info header, which is doubled to hold an AND mask
+    /// 
+    fn stored_height(&self) -> i32 {
+        if self.has_mask() { self.info_header.height * 2 } else { self.info_header.height }
+    }
+
+    ///
+    /// Get a copy of the bitmap with only what is needed to show its pixels, for
+    /// privacy or to normalize files byte-for-byte. Reserved fields, resolution,
+    /// important colors, and any fields past the basic info header (such as a
+    /// color profile) are cleared, a color table is dropped unless the pixels
+    /// index it, and missing pixels are filled in. Bytes between the color table
+    /// and the pixels, or after the pixels, aren't kept by the decoder, so
+    /// aren't written either, though an AND mask is kept.
+    ///
+    pub fn strip_metadata(&self) -> Result<Bitmap, ImageError> {
+        if self.info_header.compression != bitmap::compression::BI_RGB {
+            return Err(ImageError::Unsupported(format!("Stripping {} bitmaps is not supported.", self.compression_name())));
+        }
+
+        let pixel_count = self.info_header.width.unsigned_abs() as usize * self.info_header.height.unsigned_abs() as usize;
+
+        let (color_table, pixels) = match &self.pixels.pixels {
+            BitmapPixelData::Indices(indices) => {
+                let mut indices = indices.clone();
+                indices.resize(pixel_count, 0);
+                (self.color_table.clone(), BitmapPixelData::Indices(indices))
+            },
+            BitmapPixelData::Colors(colors) => {
+                let mut colors = colors.clone();
+                colors.resize(pixel_count, color::ARGB::default());
+                (BitmapColorTable::default(), BitmapPixelData::Colors(colors))
+            }
+        };
+
+        let info_header = BitmapInfoHeader {
+            size: bitmap::INFO_HEADER_SIZE,
+            image_size: (self.row_size() * self.info_header.height.unsigned_abs() as usize) as u32,
+            x_pixels_per_meter: 0_i32,
+            y_pixels_per_meter: 0_i32,
+            colors_used: color_table.colors.len() as u32,
+            important_colors: 0_u32,
+            bitfields: None,
+            v4: None,
+            v5: None,
+            ..self.info_header.clone()
+        };
+
+        let data_offset = bitmap::HEADER_SIZE + bitmap::INFO_HEADER_SIZE + bitmap::COLOR_TABLE_SIZE_FACTOR * color_table.colors.len() as u32;
+
+        Ok(Bitmap {
+            header: BitmapHeader {
+                signature: bitmap::SIGNATURE,
+                file_size: (info_header.computed_file_size(color_table.colors.len())
+                    + self.mask.as_ref().map_or(0, |_| info_header.mask_size())) as u32,
+                reserved: 0_u32,
+                data_offset
+            },
+            info_header,
+            color_table,
+            pixels: BitmapPixels {
+                pixels,
+                encoded: None
+            },
+            icc_profile: None,
+            mask: self.mask.clone()
+        })
+    }
+
+    ///
+    /// Get the range of bytes, start inclusive and end exclusive, that
+    /// the given section occupies in the encoded bitmap
+    /// 
+    pub fn section_range(&self, section: BitmapSection) -> (usize, usize) {
+        let info_end = self.info_header.color_table_offset();
+
+        match section {
+            BitmapSection::Header => (0, info_end),
+            BitmapSection::Palette => (info_end, info_end + bitmap::COLOR_TABLE_SIZE_FACTOR as usize * self.color_table.colors.len()),
+            BitmapSection::Pixels => {
+                let start = self.header.data_offset as usize;
+                (start, start + self.row_size() * self.info_header.height.unsigned_abs() as usize)
+            }
+        }
+    }
+
+    ///
+    /// Size, in bytes, of each row of pixel data, including padding
+    /// 
+    fn row_size(&self) -> usize {
+        self.info_header.row_size()
+    }
+
+    #[cfg(feature = "std")]
+    pub fn formatted_bitstring(&self) -> String {
+        self.formatted_bitstring_with(&BitstringOptions::default())
+    }
+
+    ///
+    /// Get the bytes of the bitmap, annotated with the field or pixel each represents
+    /// 
+    #[cfg(feature = "std")]
+    pub fn formatted_bitstring_with(&self, options: &BitstringOptions) -> String {
+        let mut buffer: Vec<u8> = Vec::new();
+
+        //Writing to memory can't fail, and the output is ASCII
+        let _ = self.write_bitstring(&mut buffer, options);
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+
+    ///
+    /// Write the bytes of the bitmap, annotated with the field or pixel each represents,
+    /// line by line, so large bitmaps can be streamed without building the whole text
+    /// 
+    #[cfg(feature = "std")]
+    pub fn write_bitstring<W: io::Write>(&self, out: &mut W, options: &BitstringOptions) -> io::Result<()> {
+        ///
+        /// Append bytes to the line, formatted as hex literals
+        ///
+        fn push_bytes(line: &mut String, bits: &[u8]) {
+            for n in bits {
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+
+                let _ = write!(line, "{:#04X?},", n);
+            }
+        }
+
+        ///
+        /// Write a line of bytes, with the given comment, on a new line
+        ///
+        fn write_line<W: io::Write>(out: &mut W, options: &BitstringOptions, offset: usize, bytes: &str, comment: fmt::Arguments) -> io::Result<()> {
+            //Pad the bytes so the comments line up, with room for at least 4 bytes
+            let pad = options.bytes_per_line.map_or(22, |bytes| bytes.saturating_mul(6).max(22));
+
+            if options.offsets {
+                write!(out, "\n{offset:08X}: {bytes: <pad$} //{comment}")
+            }
+            else {
+                write!(out, "\n{bytes: <pad$} //{comment}")
+            }
+        }
+
+        //Bytes of the current line, reused between lines
+        let mut line = String::new();
+
+        let sections = [BitmapSection::Header, BitmapSection::Palette, BitmapSection::Pixels]
+            .into_iter()
+            .filter(|section| options.sections.contains(section));
+
+        for (index, section) in sections.enumerate() {
+            //Sections are separated by a blank line, and the first is preceded by one
+            out.write_all(if index == 0 { b"\n" } else { b"\n\n" })?;
+
+            match section {
+                BitmapSection::Header => {
+                    let fields: [(&[u8], &str, i64); 15] = [
+                        (&self.header.signature.to_le_bytes(), "Signature", self.header.signature as i64),
+                        (&self.header.file_size.to_le_bytes(), "File Size", self.header.file_size as i64),
+                        (&self.header.reserved.to_le_bytes(), "Reserved", self.header.reserved as i64),
+                        (&self.header.data_offset.to_le_bytes(), "Data Offset", self.header.data_offset as i64),
+                        (&self.info_header.size.to_le_bytes(), "Info Header Size", self.info_header.size as i64),
+                        (&self.info_header.width.to_le_bytes(), "Width", self.info_header.width as i64),
+                        (&self.stored_height().to_le_bytes(), "Height", self.stored_height() as i64),
+                        (&self.info_header.planes.to_le_bytes(), "Planes", self.info_header.planes as i64),
+                        (&self.info_header.bit_depth.to_le_bytes(), "Bit Depth", self.info_header.bit_depth as i64),
+                        (&self.info_header.compression.to_le_bytes(), "Compression", self.info_header.compression as i64),
+                        (&self.info_header.image_size.to_le_bytes(), "Image Size", self.info_header.image_size as i64),
+                        (&self.info_header.x_pixels_per_meter.to_le_bytes(), "X Resolution", self.info_header.x_pixels_per_meter as i64),
+                        (&self.info_header.y_pixels_per_meter.to_le_bytes(), "Y Resolution", self.info_header.y_pixels_per_meter as i64),
+                        (&self.info_header.colors_used.to_le_bytes(), "Colors Used", self.info_header.colors_used as i64),
+                        (&self.info_header.important_colors.to_le_bytes(), "Important Colors", self.info_header.important_colors as i64)
+                    ];
+
+                    let mut offset = 0;
+
+                    //The first 4 fields are the file header, the rest the info header
+                    out.write_all(b"//Header")?;
+
+                    for (field, (bytes, name, value)) in fields.iter().enumerate() {
+                        if field == 4 {
+                            out.write_all(b"\n\n//Info Header")?;
+                        }
+
+                        line.clear();
+                        push_bytes(&mut line, bytes);
+                        write_line(out, options, offset, &line, format_args!("{name} = {value}"))?;
+                        offset += bytes.len();
+                    }
+
+                    for (name, value) in self.info_header.extended_fields() {
+                        line.clear();
+                        push_bytes(&mut line, &value.to_le_bytes());
+                        write_line(out, options, offset, &line, format_args!("{name} = 0x{value:08X}"))?;
+                        offset += 4;
+                    }
+                },
+                BitmapSection::Palette => {
+                    out.write_all(b"//Color Table")?;
+
+                    if self.color_table.colors.is_empty() {
+                        out.write_all(b"\n//Empty")?;
+                    }
+
+                    let (start, _) = self.section_range(BitmapSection::Palette);
+
+                    for (row, color) in self.color_table.colors.iter().enumerate() {
+                        line.clear();
+                        push_bytes(&mut line, &color.as_u32(false).to_le_bytes());
+                        write_line(out, options, start + row * bitmap::COLOR_TABLE_SIZE_FACTOR as usize, &line,
+                            format_args!("Color table entry {row}"))?;
+                    }
+                },
+                BitmapSection::Pixels => {
+                    out.write_all(b"//Pixel Data")?;
+
+                    let columns = self.info_header.width.unsigned_abs() as usize;
+                    let (data_start, _) = self.section_range(BitmapSection::Pixels);
+                    let row_size = self.row_size();
+
+                    match &self.pixels.pixels {
+                        BitmapPixelData::Colors(pixels) if !pixels.is_empty() && columns > 0 => {
+                            let bytes_per_pixel = (self.info_header.bit_depth as usize).div_ceil(8);
+                            let per_line = options.bytes_per_line.map_or(1, |bytes| (bytes / bytes_per_pixel).max(1));
+
+                            for (row, scanline) in pixels.chunks_exact(columns).enumerate() {
+                                write!(out, "{}//Row {row}", if row == 0 { "\n" } else { "\n\n" })?;
+
+                                for (chunk, pixel_chunk) in scanline.chunks(per_line).enumerate() {
+                                    let first = chunk * per_line;
+                                    let last = first + pixel_chunk.len() - 1;
+
+                                    line.clear();
+
+                                    for pixel in pixel_chunk {
+                                        let color_u32 = if self.info_header.bit_depth < 32_u16 {
+                                            pixel.with_alpha(0_u8).as_u32(false)
+                                        }
+                                        else {
+                                            pixel.as_u32(false)
+                                        };
+
+                                        push_bytes(&mut line, color_u32.to_le_bytes().get(..bytes_per_pixel.min(4)).unwrap_or_default());
+                                    }
+
+                                    let offset = data_start + row * row_size + first * bytes_per_pixel;
+
+                                    if first == last {
+                                        write_line(out, options, offset, &line, format_args!("Column {first}"))?;
+                                    }
+                                    else {
+                                        write_line(out, options, offset, &line, format_args!("Columns {first} - {last}"))?;
+                                    }
+                                }
+                            }
+                        },
+                        BitmapPixelData::Indices(indices) if !indices.is_empty() && columns > 0 => {
+                            let per_line = options.bytes_per_line.unwrap_or(4).max(1);
+                            let bit_depth = self.info_header.bit_depth as usize;
+
+                            for (row, scanline) in indices.chunks_exact(columns).enumerate() {
+                                write!(out, "{}//Row {row}", if row == 0 { "\n" } else { "\n\n" })?;
+
+                                for (chunk, index_chunk) in scanline.chunks(per_line).enumerate() {
+                                    let first = chunk * per_line;
+
+                                    line.clear();
+                                    push_bytes(&mut line, index_chunk);
+
+                                    //Indices smaller than a byte share bytes, so this is the byte holding the first index
+                                    write_line(out, options, data_start + row * row_size + first * bit_depth / 8, &line,
+                                        format_args!("Columns {first} - {}", first + index_chunk.len() - 1))?;
+                                }
+                            }
+                        },
+                        _ => out.write_all(b"\n//Empty")?
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl From<Bitmap> for BitmapConvertData {
+    fn from(value: Bitmap) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&Bitmap> for BitmapConvertData {
+    fn from(value: &Bitmap) -> Self {
+        Self 
+        { 
+            bit_depth: BitDepth::Bits(value.info_header.bit_depth), 
+            compression: value.info_header.compression,
+            resolution: value.info_header.resolution(),
+            top_down: value.info_header.orientation().is_top_down(),
+            palette_order: PaletteOrder::default(),
+            and_mask: value.mask.is_some()
+        }
+    }
+}
+
+///
+/// Get the next n bytes of the buffer, advancing the offset past them
+///
+fn get_next_bytes<'a>(buffer: &'a [u8], offset: &mut usize, n: usize) -> Result<&'a [u8], ImageError> {
+    let start = *offset;
+
+    let bytes = start.checked_add(n)
+        .and_then(|end| buffer.get(start..end))
+        .ok_or_else(|| ImageError::Decode(format!("Bitmap is {} bytes, which is too short for {n} bytes at offset {start}.", buffer.len())))?;
+
+    *offset += n;
+    Ok(bytes)
+}
+
+///
+/// Read the next little-endian value of n bytes from the buffer
+///
+fn get_next_value<T: FromBitSlice>(buffer: &[u8], offset: &mut usize, n: usize) -> Result<T, ImageError> {
+    let start = *offset;
+
+    T::reduce_bit_slice(get_next_bytes(buffer, offset, n)?)
+        .ok_or_else(|| ImageError::Decode(format!("Bitmap has no value of {n} bytes at offset {start}.")))
+}
+
+///
+/// The options of a decode, and what it has found so far
+///
+struct DecodeContext {
+    options: DecodeOptions,
+    report: DecodeReport
+}
+
+impl DecodeContext {
+    fn new(options: DecodeOptions) -> Self {
+        Self {
+            options,
+            report: DecodeReport::default()
+        }
+    }
+
+    ///
+    /// Handle a violation of the format which the decoder can repair or ignore;
+    /// an error in strict mode, otherwise a warning in the report
+    ///
+    fn violation(&mut self, message: String) -> Result<(), ImageError> {
+        if self.options.strict {
+            Err(ImageError::Decode(message))
+        }
+        else {
+            self.report.violations.push(message.clone());
+            self.warning(message);
+            Ok(())
+        }
+    }
+
+    ///
+    /// Report something unusual about the bmp which is allowed by the format
+    ///
+    fn warning(&mut self, message: String) {
+        self.report.warnings.push(message);
+    }
+}
+
+///
+/// Read the headers and color table of a bmp, returning them along
+/// with the offset at which the pixel data begins. The size of the whole
+/// file is checked against the header if it is known.
+///
+fn read_headers(value: &[u8], file_size: Option<usize>, context: &mut DecodeContext) -> Result<(BitmapHeader, BitmapInfoHeader, BitmapColorTable, usize), ImageError> {
+    let mut offset: usize = 0;
+
+    let headers_size = (bitmap::HEADER_SIZE + bitmap::INFO_HEADER_SIZE) as usize;
+
+    if value.len() < headers_size {
+        return Err(ImageError::Decode(format!("Bitmap is {} bytes, which is too short for its {headers_size} bytes of headers.", value.len())));
+    }
+
+    let next_u16 =
+        |offset: &mut usize| get_next_value::<u16>(value, offset, 2);
+
+    let next_u32 =
+        |offset: &mut usize| get_next_value::<u32>(value, offset, 4);
+
+    let next_i32 =
+        |offset: &mut usize| get_next_value::<i32>(value, offset, 4);
+
+    //File header
+    let header = BitmapHeader {
+        signature: next_u16(&mut offset)?,
+        file_size: next_u32(&mut offset)?,
+        reserved: next_u32(&mut offset)?,
+        data_offset: next_u32(&mut offset)?,
+    };
+
+    if let Some(file_size) = file_size {
+        check_file_size(&header, file_size, context)?;
+    }
+
+    //Image header
+    let info_header = BitmapInfoHeader {
+        size: next_u32(&mut offset)?,
+        width: next_i32(&mut offset)?,
+        height: next_i32(&mut offset)?,
+        planes: next_u16(&mut offset)?,
+        bit_depth: next_u16(&mut offset)?,
+        compression: next_u32(&mut offset)?,
+        image_size: next_u32(&mut offset)?,
+        x_pixels_per_meter: next_i32(&mut offset)?,
+        y_pixels_per_meter: next_i32(&mut offset)?,
+        colors_used: next_u32(&mut offset)?,
+        important_colors: next_u32(&mut offset)?,
+        bitfields: None,
+        v4: None,
+        v5: None,
+    };
+
+    //Later versions of the info header add fields after the first 40 bytes
+    let info_header = read_extended_headers(value, offset, info_header)?;
+
+    //Icons declare twice the height of the image, for the rows of pixels and then the rows of the mask
+    let info_header = if context.options.and_mask {
+        if info_header.height % 2 != 0 || info_header.mask_size() == 0 {
+            return Err(ImageError::Decode(format!("Bitmap height is {} with {} compression, which can't hold its pixels and an AND mask.",
+                info_header.height, info_header.compression_name())));
+        }
+
+        BitmapInfoHeader { height: info_header.height / 2, ..info_header }
+    }
+    else {
+        info_header
+    };
+
+    validate_headers(&header, &info_header, value.len())?;
+
+    //Color table
+
+    //The color table follows the info header, which may be longer than the fields read here, and any bitfields
+    let table_start = info_header.color_table_offset();
+    let data_offset = header.data_offset as usize;
+
+    let available = data_offset.checked_sub(table_start)
+        .ok_or_else(|| ImageError::Decode(format!("Bitmap data offset is {data_offset}, which is inside the {table_start} bytes of headers.")))?;
+
+    //Indexed pixels are meaningless without colors to index, though an empty image has nothing to index
+    let has_pixels = info_header.width != 0 && info_header.height != 0;
+
+    //A colors used of 0 means as many colors as the bit depth allows; images with more
+    //bits per pixel don't need a color table, but may have one of colors used colors
+    let declared = match info_header.colors_used {
+        0 if [1, 4, 8].contains(&info_header.bit_depth) => 1_usize << info_header.bit_depth,
+        colors_used => colors_used as usize
+    };
+
+    let room = available / bitmap::COLOR_TABLE_SIZE_FACTOR as usize;
+    let colors = declared.min(room);
+
+    if colors < declared && has_pixels {
+        context.violation(format!("Bitmap color table should have {declared} colors, but there is only room for {room} before the pixel data."))?;
+    }
+
+    offset = table_start;
+    let color_table_raw = get_next_bytes(value, &mut offset, colors * bitmap::COLOR_TABLE_SIZE_FACTOR as usize)?;
+
+    //Each color in the pallette is 4 bytes, the first 3 representing the Blue, Green and Red intensities respectively, with the last unused or alpha
+    let color_table = BitmapColorTable {
+        colors: color_table_raw
+            .chunks_exact(4)
+            .filter_map(|chunk| match *chunk {
+                [blue, green, red, alpha] => Some(color::ARGB { alpha, red, green, blue }),
+                _ => None
+            })
+            .collect()
+    };
+
+    let table_end = table_start + colors * bitmap::COLOR_TABLE_SIZE_FACTOR as usize;
+
+    //The color profile may be stored between the color table and the pixel data, which isn't unused
+    let profile_bytes = info_header.embedded_profile_range()
+        .map_or(0, |(start, end)| end.min(data_offset).saturating_sub(start.max(table_end)));
+    let gap = data_offset - table_end - profile_bytes;
+
+    if gap > 0 {
+        context.warning(format!("Bitmap has {gap} unused bytes between the color table and the pixel data, which are skipped."));
+    }
+
+    if [1, 4, 8].contains(&info_header.bit_depth) && color_table.colors.is_empty() && has_pixels {
+        return Err(ImageError::Decode(format!("Bitmap has a bit depth of {}, but no color table.", info_header.bit_depth)));
+    }
+
+    offset = data_offset;
+
+    if let Some(file_size) = file_size {
+        check_pixel_data_size(&info_header, file_size.saturating_sub(offset))?;
+    }
+
+    Ok((header, info_header, color_table, offset))
+}
+
+///
+/// Check that the file is as large as its header declares
+///
+fn check_file_size(header: &BitmapHeader, file_size: usize, context: &mut DecodeContext) -> Result<(), ImageError> {
+    if header.file_size as usize != file_size {
+        context.violation(format!("Bitmap header declares a file size of {} bytes, but the file is {file_size} bytes.", header.file_size))?;
+    }
+
+    Ok(())
+}
+
+///
+/// Check that the pixel data could hold most of the pixels the dimensions call for. Pixels missing
+/// from a truncated bmp are filled in when decoding leniently, but only so many, so that a few bytes
+/// declaring large dimensions can't make the decoder fill in a huge image from nothing. Compressed
+/// pixel data needs at least 2 bytes for each run, which is at most 255 pixels.
+///
+fn check_pixel_data_size(info_header: &BitmapInfoHeader, available: usize) -> Result<(), ImageError> {
+    let rows = info_header.height.unsigned_abs() as usize;
+
+    let least = match info_header.compression {
+        bitmap::compression::BI_RLE8 | bitmap::compression::BI_RLE4 =>
+            (info_header.width.unsigned_abs() as usize * rows).div_ceil(u8::MAX as usize) * 2,
+        _ => info_header.row_size() * rows
+    };
+
+    if least.saturating_sub(available) > available.max(bitmap::MAX_MISSING_PIXEL_DATA) {
+        return Err(ImageError::Decode(format!("Bitmap dimensions are {}x{}, which need at least {least} bytes of pixel data, but there are only {available}.",
+            info_header.width, info_header.height)));
+    }
+
+    Ok(())
+}
+
+///
+/// Find the color profile embedded in the bmp, if its info header declares one,
+/// which is a violation if it isn't within the file
+///
+fn find_icc_profile<'a>(value: &'a [u8], info_header: &BitmapInfoHeader, context: &mut DecodeContext) -> Result<Option<&'a [u8]>, ImageError> {
+    let Some((start, end)) = info_header.embedded_profile_range() else {
+        return Ok(None);
+    };
+
+    match value.get(start..end) {
+        Some(profile) => Ok(Some(profile)),
+        None => {
+            missing_section("color profile", start, end, value.len(), context)?;
+            Ok(None)
+        }
+    }
+}
+
+///
+/// Handle a part of the bmp, such as the color profile, at the range of bytes, which runs past the end of the file
+///
+fn missing_section(name: &str, start: usize, end: usize, file_size: usize, context: &mut DecodeContext) -> Result<(), ImageError> {
+    context.violation(format!("Bitmap {name} at bytes {start} to {end} is past the end of the {file_size} byte file, and is ignored."))
+}
+
+///
+/// Find the AND mask following the pixel data which starts at the offset, if the
+/// bmp is read as an icon's image, which is a violation if it isn't within the file
+///
+fn find_mask<'a>(value: &'a [u8], offset: usize, info_header: &BitmapInfoHeader, context: &mut DecodeContext) -> Result<Option<&'a [u8]>, ImageError> {
+    if !context.options.and_mask {
+        return Ok(None);
+    }
+
+    let start = offset + info_header.row_size() * info_header.height.unsigned_abs() as usize;
+    let end = start + info_header.mask_size();
+
+    match value.get(start..end) {
+        Some(mask) => Ok(Some(mask)),
+        None => {
+            missing_section("AND mask", start, end, value.len(), context)?;
+            Ok(None)
+        }
+    }
+}
+
+///
+/// Whether the pixel at the given row and column of an AND mask, in the
+/// order rows are stored, is set, making the pixel transparent
+///
+fn mask_bit(mask: &[u8], info_header: &BitmapInfoHeader, row: usize, column: usize) -> bool {
+    mask.get(row * info_header.mask_row_size() + column / 8)
+        .is_some_and(|byte| (byte >> (7 - column % 8)) & 1 == 1)
+}
+
+///
+/// Unpack an AND mask into a bit for each pixel, in the order they are stored
+///
+fn unpack_mask(mask: &[u8], info_header: &BitmapInfoHeader) -> Vec<bool> {
+    let width = info_header.width.unsigned_abs() as usize;
+
+    (0..info_header.height.unsigned_abs() as usize)
+        .flat_map(|row| (0..width).map(move |column| mask_bit(mask, info_header, row, column)))
+        .collect()
+}
+
+///
+/// Read the fields which later versions of the info header add, and the bitfields which may
+/// follow it, from the offset at the end of the BITMAPINFOHEADER, if they are there
+///
+fn read_extended_headers(value: &[u8], mut offset: usize, info_header: BitmapInfoHeader) -> Result<BitmapInfoHeader, ImageError> {
+    let mut next_u32 = || get_next_value::<u32>(value, &mut offset, 4);
+
+    //Adobe V2 and V3 headers end with the masks, and a BITMAPINFOHEADER is followed by them if they're used
+    let bitfields = match info_header.size {
+        size if (bitmap::V2_INFO_HEADER_SIZE..bitmap::V4_INFO_HEADER_SIZE).contains(&size) => Some(BitmapMasks {
+            red: next_u32()?,
+            green: next_u32()?,
+            blue: next_u32()?,
+            alpha: if size >= bitmap::V3_INFO_HEADER_SIZE { next_u32()? } else { 0 }
+        }),
+        size if size < bitmap::V2_INFO_HEADER_SIZE && info_header.compression == bitmap::compression::BI_BITFIELDS => Some(BitmapMasks {
+            red: next_u32()?,
+            green: next_u32()?,
+            blue: next_u32()?,
+            alpha: 0
+        }),
+        _ => None
+    };
+
+    let v4 = match info_header.size {
+        size if size >= bitmap::V4_INFO_HEADER_SIZE => Some(BitmapV4Fields {
+            red_mask: next_u32()?,
+            green_mask: next_u32()?,
+            blue_mask: next_u32()?,
+            alpha_mask: next_u32()?,
+            color_space_type: next_u32()?,
+            endpoints: {
+                let mut endpoints = [0_i32; 9];
+
+                for endpoint in &mut endpoints {
+                    *endpoint = next_u32()? as i32;
+                }
+
+                endpoints
+            },
+            gamma_red: next_u32()?,
+            gamma_green: next_u32()?,
+            gamma_blue: next_u32()?,
+        }),
+        _ => None
+    };
+
+    let v5 = match info_header.size {
+        size if size >= bitmap::V5_INFO_HEADER_SIZE => Some(BitmapV5Fields {
+            intent: next_u32()?,
+            profile_data: next_u32()?,
+            profile_size: next_u32()?,
+            reserved: next_u32()?,
+        }),
+        _ => None
+    };
+
+    Ok(BitmapInfoHeader { bitfields, v4, v5, ..info_header })
+}
+
+///
+/// Check that the fields of the headers are consistent with each other
+/// and with the file, so that nothing is decoded from garbage
+///
+fn validate_headers(header: &BitmapHeader, info_header: &BitmapInfoHeader, file_size: usize) -> Result<(), ImageError> {
+    if header.signature != bitmap::SIGNATURE {
+        return Err(ImageError::Decode(format!("Bitmap signature is 0x{:04X}, but must be 0x{:04X} ('BM').", header.signature, bitmap::SIGNATURE)));
+    }
+
+    if header.data_offset as usize > file_size {
+        return Err(ImageError::Decode(format!("Bitmap data offset is {}, which is past the end of the {file_size} byte file.", header.data_offset)));
+    }
+
+    if info_header.size < bitmap::INFO_HEADER_SIZE {
+        return Err(ImageError::Decode(format!("Bitmap info header size is {}, but must be at least {}.", info_header.size, bitmap::INFO_HEADER_SIZE)));
+    }
+
+    let pixels = info_header.width.unsigned_abs() as usize * info_header.height.unsigned_abs() as usize;
+
+    if pixels > bitmap::MAX_PIXELS {
+        return Err(ImageError::Decode(format!("Bitmap dimensions are {}x{}, which is more than the limit of {} pixels.",
+            info_header.width, info_header.height, bitmap::MAX_PIXELS)));
+    }
+
+    if info_header.planes != 1 {
+        return Err(ImageError::Decode(format!("Bitmap planes is {}, but must be 1.", info_header.planes)));
+    }
+
+    let bit_depths = valid_bit_depths(info_header.compression)
+        .ok_or_else(|| ImageError::Decode(format!("Bitmap compression is {}, which is not a known compression.", info_header.compression)))?;
+
+    if !bit_depths.contains(&info_header.bit_depth) {
+        return Err(ImageError::Decode(format!("Bitmap bit depth is {}, which is not valid with {} compression.", info_header.bit_depth, info_header.compression_name())));
+    }
+
+    if [1, 4, 8].contains(&info_header.bit_depth) && info_header.colors_used > 1 << info_header.bit_depth {
+        return Err(ImageError::Decode(format!("Bitmap colors used is {}, but a {}-bit bitmap can have at most {}.",
+            info_header.colors_used, info_header.bit_depth, 1 << info_header.bit_depth)));
+    }
+
+    Ok(())
+}
+
+///
+/// Get the bit depths pixels can have with the compression, or None if it isn't a known one
+///
+fn valid_bit_depths(compression: u32) -> Option<&'static [u16]> {
+    use bitmap::compression::*;
+
+    match compression {
+        BI_RGB => Some(&[1, 4, 8, 16, 24, 32]),
+        BI_RLE8 => Some(&[8]),
+        BI_RLE4 => Some(&[4]),
+        BI_BITFIELDS => Some(&[16, 32]),
+        BI_JPEG | BI_PNG => Some(&[0]),
+        _ => None
+    }
+}
+
+///
+/// Get the name of the compression method
+///
+fn compression_name(compression: u32) -> &'static str {
+    match compression {
+        bitmap::compression::BI_RGB => "BI_RGB",
+        bitmap::compression::BI_RLE8 => "BI_RLE8",
+        bitmap::compression::BI_RLE4 => "BI_RLE4",
+        bitmap::compression::BI_BITFIELDS => "BI_BITFIELDS",
+        bitmap::compression::BI_JPEG => "BI_JPEG",
+        bitmap::compression::BI_PNG => "BI_PNG",
+        _ => "Unknown"
+    }
+}
+
+///
+/// Read the pixel data of a bmp, starting at the given offset
+///
+fn read_pixels(value: &[u8], mut offset: usize, info_header: &BitmapInfoHeader, context: &mut DecodeContext) -> Result<BitmapPixelData, ImageError> {
+    let rows = info_header.height.unsigned_abs() as usize;
+
+    //RLE8 and RLE4 compress indices of the color table into runs, which are expanded row by row
+    if [bitmap::compression::BI_RLE8, bitmap::compression::BI_RLE4].contains(&info_header.compression) {
+        return Ok(BitmapPixelData::Indices(read_rle_indices(value, offset, info_header, context)?));
+    }
+
+    let scanline_width = info_header.row_size();
+    let mut decoder = ScanlineDecoder::new(info_header, value.len().saturating_sub(offset))?;
+
+    //Read in each scanline, until the data runs out
+    for _ in 0..rows {
+        let count = read_scanline_count(value, offset, scanline_width, context)?;
+
+        //Get the scanline data
+        let scanline = get_next_bytes(value, &mut offset, count)?;
+        decoder.decode(scanline);
+
+        if count == 0 || count < scanline_width {
+            break;
+        }
+    }
+
+    Ok(decoder.pixels)
+}
+
+///
+/// Decodes the uncompressed scanlines of a bmp one at a time, appending their pixels
+///
+struct ScanlineDecoder {
+    width: usize,
+    bit_depth: usize,
+    ///
+    /// Which bits of each pixel hold each channel, for 16-bit pixels and any with bitfields
+    ///
+    masks: Option<BitmapMasks>,
+    pixels: BitmapPixelData
+}
+
+impl ScanlineDecoder {
+    ///
+    /// A decoder for the pixels of the info header, with room for as many of them
+    /// as the given number of bytes of pixel data could hold, in case the dimensions
+    /// are wrong. Only bit depths of 1, 4, 8, 16, 24 and 32 are supported.
+    ///
+    fn new(info_header: &BitmapInfoHeader, available: usize) -> Result<Self, ImageError> {
+        let width = info_header.width.unsigned_abs() as usize;
+        let bit_depth = info_header.bit_depth as usize;
+        let expected_pixels = width * info_header.height.unsigned_abs() as usize;
+
+        let pixels = match bit_depth {
+            //bpp = 1, 4 or 8: value of each pixel has a size <= 1 byte, and is an index of the color table
+            1 | 4 | 8 => BitmapPixelData::Indices(Vec::with_capacity(expected_pixels.min(available * (8 / bit_depth)))),
+            16 | 24 | 32 => BitmapPixelData::Colors(Vec::with_capacity(expected_pixels.min(available / (bit_depth / 8)))),
+            _ => return Err(ImageError::Unsupported(format!("Not implemented for {bit_depth}-bit images!")))
+        };
+
+        //bpp = 16: value of each pixel is 2 bytes, with each 5 bits representing Blue, Green and Red intensities respectively, and the last bit being unused.
+        //With BI_BITFIELDS, the masks say which bits of each 16 or 32-bit pixel hold which channel instead.
+        let masks = match info_header.channel_masks() {
+            None if bit_depth == 16 => Some(BitmapMasks::RGB_555),
+            None if info_header.compression == bitmap::compression::BI_BITFIELDS => Some(BitmapMasks::ARGB_8888),
+            masks => masks
+        };
+
+        Ok(Self {
+            width,
+            bit_depth,
+            masks,
+            pixels
+        })
+    }
+
+    ///
+    /// Append the pixels of a scanline, ignoring the 0-padding at its end. A scanline
+    /// cut short only has the pixels which are wholly in it.
+    ///
+    fn decode(&mut self, scanline: &[u8]) {
+        match &mut self.pixels {
+            BitmapPixelData::Indices(indices) => {
+                let bit_depth = self.bit_depth;
+                let pixels_per_byte = 8 / bit_depth;
+                let mask = ((1_u16 << bit_depth) - 1) as u8;
+
+                // Loop over each bit in the scanline, ignoring 0-padding at the end of the scanline.
+                for (ndx, chunk) in scanline.iter().take(self.width.div_ceil(pixels_per_byte)).enumerate() {
+                    //For each pixel in the byte, stopping at the end of the line
+                    for i in 1..=pixels_per_byte.min(self.width - pixels_per_byte * ndx) {
+                        //Extract the palette index of the (i - 1)th pixel from the byte
+                        indices.push((*chunk >> (8 - bit_depth * i)) & mask);
+                    }
+                }
+            },
+            BitmapPixelData::Colors(colors) => {
+                let chunks = scanline.chunks_exact(self.bit_depth / 8).take(self.width);
+
+                match self.masks {
+                    //Loop over each little endian pixel in the scanline
+                    Some(masks) => colors.extend(chunks.map(|chunk| masks.extract(chunk.iter().rev().fold(0_u32, |pixel, byte| pixel << 8 | *byte as u32)))),
+                    //bpp = 24: value of each pixel is 3 bytes, representing Blue, Green and Red intensities respectively
+                    //bpp = 32: value of each pixel is 4 bytes, representing Alpha, Blue, Green and Red intensities respectively
+                    None => colors.extend(chunks.filter_map(stored_color))
+                }
+            }
+        }
+    }
+}
+
+///
+/// Expand the RLE8 or RLE4 pixel data of a bmp, starting at the given offset, into indices
+/// of the color table in the order rows are stored. Each pair of bytes is either a run of
+/// a count of pixels with the given index (or alternating pair of indices for RLE4), or an
+/// escape of 0 followed by 0 for end of line, 1 for end of bitmap, 2 for a delta of the
+/// next 2 bytes right and down, or else the count of indices stored absolutely after it,
+/// padded to a multiple of 2 bytes. Pixels which are skipped are left as index 0.
+///
+fn read_rle_indices(value: &[u8], mut offset: usize, info_header: &BitmapInfoHeader, context: &mut DecodeContext) -> Result<Vec<u8>, ImageError> {
+    let width = info_header.width.unsigned_abs() as usize;
+    let rows = info_header.height.unsigned_abs() as usize;
+    let nibbles = info_header.compression == bitmap::compression::BI_RLE4;
+
+    let mut indices = vec![0_u8; width * rows];
+    let (mut x, mut y) = (0_usize, 0_usize);
+    let mut outside = 0_usize;
+
+    //Set the index of the next pixel in the row, if it is within the image
+    let mut put = |x: &mut usize, y: usize, index: u8| {
+        match indices.get_mut(y * width + *x) {
+            Some(pixel) if *x < width => *pixel = index,
+            _ => outside += 1
+        }
+
+        *x += 1;
+    };
+
+    loop {
+        let Some(&[count, second]) = value.get(offset..offset + 2) else {
+            context.violation(format!("Bitmap {} data ends at offset {offset} without an end of bitmap.", info_header.compression_name()))?;
+            break;
+        };
+
+        offset += 2;
+
+        match (count, second) {
+            //End of line
+            (0, 0) => {
+                x = 0;
+                y += 1;
+            },
+            //End of bitmap
+            (0, 1) => break,
+            //Delta
+            (0, 2) => {
+                let Some(&[dx, dy]) = value.get(offset..offset + 2) else {
+                    context.violation(format!("Bitmap {} delta at offset {} is truncated.", info_header.compression_name(), offset - 2))?;
+                    break;
+                };
+
+                offset += 2;
+                x += dx as usize;
+                y += dy as usize;
+            },
+            //Absolute run of indices, padded to a multiple of 2 bytes
+            (0, count) => {
+                let count = count as usize;
+                let size = if nibbles { count.div_ceil(2) } else { count };
+
+                let Some(run) = value.get(offset..offset + size) else {
+                    context.violation(format!("Bitmap {} absolute run at offset {} is truncated.", info_header.compression_name(), offset - 2))?;
+                    break;
+                };
+
+                offset += size.next_multiple_of(2);
+
+                for i in 0..count {
+                    let byte = run.get(if nibbles { i / 2 } else { i }).copied().unwrap_or_default();
+                    put(&mut x, y, rle_index(byte, i, nibbles));
+                }
+            },
+            //Encoded run of the same index, or alternating indices for RLE4
+            (count, byte) => {
+                for i in 0..count as usize {
+                    put(&mut x, y, rle_index(byte, i, nibbles));
+                }
+            }
+        }
+    }
+
+    if outside > 0 {
+        context.violation(format!("Bitmap {} data has {outside} pixels outside of the image, which are ignored.", info_header.compression_name()))?;
+    }
+
+    Ok(indices)
+}
+
+///
+/// Get the index of the ith pixel of an RLE run from its byte, which for
+/// RLE4 holds 2 indices, starting from the most significant bits
+///
+fn rle_index(byte: u8, i: usize, nibbles: bool) -> u8 {
+    match (nibbles, i % 2) {
+        (false, _) => byte,
+        (true, 0) => byte >> 4,
+        (true, _) => byte & 0x0F
+    }
+}
+
+///
+/// Get how many bytes of the scanline at the offset are in the data,
+/// which is a violation if the file ends partway through it
+///
+fn read_scanline_count(value: &[u8], offset: usize, scanline_width: usize, context: &mut DecodeContext) -> Result<usize, ImageError> {
+    let count = scanline_width.min(value.len().saturating_sub(offset));
+    check_scanline_count(offset, count, scanline_width, context)?;
+
+    Ok(count)
+}
+
+///
+/// Check that all of the scanline at the offset is there, unless none of it is
+///
+fn check_scanline_count(offset: usize, count: usize, scanline_width: usize, context: &mut DecodeContext) -> Result<(), ImageError> {
+    if count > 0 && count < scanline_width {
+        context.violation(format!("Bitmap scanline at offset {offset} is truncated to {count} of {scanline_width} bytes."))?;
+    }
+
+    Ok(())
+}
+
+///
+/// The color stored in 3 or 4 bytes of pixel data, which are
+/// blue, green, red, and alpha if present
+///
+fn stored_color(chunk: &[u8]) -> Option<color::ARGB> {
+    match *chunk {
+        [blue, green, red] => Some(color::ARGB { alpha: 0xFF, red, green, blue }),
+        [blue, green, red, alpha] => Some(color::ARGB { alpha, red, green, blue }),
+        _ => None
+    }
+}
+
+///
+/// Read the pixel at the given row and column of uncompressed pixel data, in the order
+/// rows are stored, if it exists. Only bit depths of 1, 4, 8, 24 and 32 are supported.
+///
+fn read_stored_pixel(data: &[u8], info_header: &BitmapInfoHeader, color_table: &BitmapColorTable, row: usize, column: usize) -> Option<color::ARGB> {
+    let bit_depth = info_header.bit_depth as usize;
+    let row_start = row * info_header.row_size();
+
+    match bit_depth {
+        1 | 4 | 8 => {
+            //Indices smaller than a byte share bytes, starting from the most significant bits
+            let byte = *data.get(row_start + column * bit_depth / 8)?;
+            let shift = 8 - bit_depth * (column % (8 / bit_depth) + 1);
+            let index = (byte >> shift) & ((1_u16 << bit_depth) - 1) as u8;
+
+            color_table.colors.get(index as usize).copied()
+        },
+        24 | 32 => {
+            let start = row_start + column * bit_depth / 8;
+            stored_color(data.get(start..start + bit_depth / 8)?)
+        },
+        _ => None
+    }
+}
+
+///
+/// Check that the number of pixels read matches the dimensions of the bmp
+///
+fn check_pixel_count(info_header: &BitmapInfoHeader, pixel_count: usize, context: &mut DecodeContext) -> Result<(), ImageError> {
+    let expected_pixels = info_header.width.unsigned_abs() as usize * info_header.height.unsigned_abs() as usize;
+
+    if pixel_count != expected_pixels {
+        context.violation(format!("Bitmap has {pixel_count} pixels, but its dimensions call for {expected_pixels}."))?;
+    }
+
+    Ok(())
+}
+
+///
+/// Decode the given region (X, Y, W, H) of the bmp in the bytes into the buffer, or the whole
+/// image if no region is given. Returns the width and height of the decoded pixels.
+///
+fn decode_pixels_into(value: &[u8], region: Option<(usize, usize, usize, usize)>, pixels: &mut Vec<color::ARGB>, context: &mut DecodeContext) -> Result<(usize, usize), ImageError> {
+    let (header, info_header, color_table, offset) = read_headers(value, Some(value.len()), context)?;
+
+    let image_width = info_header.width.unsigned_abs() as usize;
+    let image_height = info_header.height.unsigned_abs() as usize;
+
+    let (x, y, width, height) = region.unwrap_or((0, 0, image_width, image_height));
+
+    if region.is_some() && (width == 0 || height == 0
+        || x.checked_add(width).is_none_or(|right| right > image_width)
+        || y.checked_add(height).is_none_or(|bottom| bottom > image_height)) {
+        return Err(ImageError::InvalidArgument(format!("Region {x},{y},{width}x{height} is not within the {image_width}x{image_height} image.")));
+    }
+
+    pixels.clear();
+
+    if info_header.compression != bitmap::compression::BI_RGB || ![1, 4, 8, 24, 32].contains(&info_header.bit_depth) {
+        let decoded = BitmapPixels { pixels: read_pixels(value, offset, &info_header, context)?, encoded: None };
+        let mask = find_mask(value, offset, &info_header, context)?.map(|mask| unpack_mask(mask, &info_header));
+        let image = Image::try_convert_from(Bitmap { header, info_header, color_table, pixels: decoded, icc_profile: None, mask }, ImageConvertData::default())?;
+        pixels.extend(image.crop(x, y, width, height).into_pixels());
+        return Ok((width, height));
+    }
+
+    let data = value.get(offset..).unwrap_or_default();
+    let mask = find_mask(value, offset, &info_header, context)?;
+    let orientation = info_header.orientation();
+    let mut missing = 0_usize;
+
+    pixels.reserve(width * height);
+    pixels.extend((y..(y + height))
+        .flat_map(|j| (x..(x + width)).map(move |i| (i, j)))
+        .map(|(i, j)| {
+            let (column, row) = orientation.stored_position(i, j, image_width, image_height);
+
+            let color = read_stored_pixel(data, &info_header, &color_table, row, column).unwrap_or_else(|| {
+                missing += 1;
+                color::ARGB::default()
+            });
+
+            match mask {
+                Some(mask) if mask_bit(mask, &info_header, row, column) => color::ARGB { alpha: 0, ..color },
+                _ => color
+            }
+        }));
+
+    if missing > 0 {
+        context.violation(format!("Bitmap is missing {missing} pixels, which are left transparent."))?;
+    }
+
+    Ok((width, height))
+}
+
+impl Bitmap {
+    ///
+    /// Read a bmp from an array of bytes with the given options
+    ///
+    pub fn decode(value: &[u8], options: DecodeOptions) -> Result<Self, ImageError> {
+        let (bitmap, report) = Self::decode_with_report(value, options)?;
+        report.log();
+
+        Ok(bitmap)
+    }
+
+    ///
+    /// Read a bmp from an array of bytes with the given options, along
+    /// with a report of what was wrong with it that wasn't an error
+    ///
+    pub fn decode_with_report(value: &[u8], options: DecodeOptions) -> Result<(Self, DecodeReport), ImageError> {
+        let mut context = DecodeContext::new(options);
+        let context = &mut context;
+
+        let (header, info_header, color_table, offset) = read_headers(value, Some(value.len()), context)?;
+        let pixel_vec = read_pixels(value, offset, &info_header, context)?;
+
+        check_pixel_count(&info_header, match &pixel_vec {
+            BitmapPixelData::Indices(indices) => indices.len(),
+            BitmapPixelData::Colors(colors) => colors.len()
+        }, context)?;
+
+        let pixels = BitmapPixels { pixels: pixel_vec, encoded: None };
+        let icc_profile = find_icc_profile(value, &info_header, context)?.map(<[u8]>::to_vec);
+        let mask = find_mask(value, offset, &info_header, context)?.map(|mask| unpack_mask(mask, &info_header));
+
+        let bitmap = Self {
+            header,
+            info_header,
+            color_table,
+            pixels,
+            icc_profile,
+            mask
+        };
+
+        Ok((bitmap, core::mem::take(&mut context.report)))
+    }
+
+    ///
+    /// Check the bmp in the bytes against the format. Everything strict decoding
+    /// rejects is an error, along with inconsistent sizes and palette indices
+    /// past the color table. Unusual but allowed structure, such as set reserved
+    /// fields, unset padding or trailing bytes, is a warning.
+    ///
+    pub fn check_conformance(value: &[u8]) -> ConformanceReport {
+        let mut conformance = ConformanceReport::default();
+
+        let (bitmap, report) = match Self::decode_with_report(value, DecodeOptions::default()) {
+            Ok(decoded) => decoded,
+            Err(err) => {
+                conformance.push(Severity::Error, format!("{err}"));
+                return conformance;
+            }
         };
 
-        //Get pixels in the bitmap
-        //bpp = 1, 4 or 8: value of each pixel has a size <= 1 byte, and is an index of the color table
-        let pixel_vec: BitmapPixelData = if [1, 4, 8].contains(&info_header.bit_depth) {
-            let mut pixel_indices: Vec<u8> = Vec::new();
-
-            //Get the width of the scanline based on bit depth and line width
-            let pixels_per_bit = f32::ceil(8_f32 / (info_header.bit_depth as f32)) as usize;
-            let scaline_width_temp =
-                f32::ceil(f32::abs(info_header.width as f32) / (pixels_per_bit as f32)) as i32;
-            let scanline_width = utility::round_to_next_multiple_of_4(scaline_width_temp);
-
-            //Read in each scanline
-            loop {
-                let mut done = false;
-                let mut count = scanline_width;
-
-                // I don't think this should ever happen for a properly-formatted
-                // bitmap, but if the scanline goes past the end of the file,
-                // truncate it
-                if value.len() < offset + scanline_width {
-                    count = ((value.len() as i32) - (offset as i32)) as usize;
-                    done = true;
+        for warning in &report.warnings {
+            let severity = if report.violations.contains(warning) { Severity::Error } else { Severity::Warning };
+            conformance.push(severity, warning.clone());
+        }
+
+        //Violations found leniently should be all that strict decoding rejects, but make sure
+        if let Err(err) = Self::decode_with_report(value, DecodeOptions { strict: true, ..Default::default() }) {
+            let message = format!("{err}");
+
+            if !report.violations.iter().any(|violation| message.contains(violation.as_str())) {
+                conformance.push(Severity::Error, message);
+            }
+        }
+
+        bitmap.check_structure(value, &mut conformance);
+
+        conformance
+    }
+
+    ///
+    /// Check the parts of the encoded bmp which the decoder skips over or doesn't need
+    ///
+    fn check_structure(&self, value: &[u8], conformance: &mut ConformanceReport) {
+        let header = &self.header;
+        let info_header = &self.info_header;
+
+        if header.reserved != 0 {
+            conformance.push(Severity::Warning, format!("Bitmap reserved field is 0x{:08X}, but should be 0.", header.reserved));
+        }
+
+        if !bitmap::INFO_HEADER_SIZES.contains(&info_header.size) {
+            conformance.push(Severity::Warning, format!("Bitmap info header size is {}, which is not the size of any known version.", info_header.size));
+        }
+
+        let rows = info_header.height.unsigned_abs() as usize;
+        let row_size = self.row_size();
+        let pixel_bytes = row_size * rows;
+
+        if info_header.compression == bitmap::compression::BI_RGB && info_header.image_size != 0 && info_header.image_size as usize != pixel_bytes {
+            conformance.push(Severity::Error, format!("Bitmap declares an image size of {} bytes, but its pixel data is {pixel_bytes} bytes.", info_header.image_size));
+        }
+
+        let table = &self.color_table.colors;
+
+        if info_header.important_colors as usize > table.len() && !table.is_empty() {
+            conformance.push(Severity::Warning, format!("Bitmap has {} important colors, but only {} colors.", info_header.important_colors, table.len()));
+        }
+
+        if let BitmapPixelData::Indices(indices) = &self.pixels.pixels {
+            let out_of_range = indices.iter().filter(|index| **index as usize >= table.len()).count();
+
+            if out_of_range > 0 {
+                conformance.push(Severity::Error, format!("Bitmap has {out_of_range} pixels which index past the {} colors of the color table.", table.len()));
+            }
+        }
+
+        if info_header.compression != bitmap::compression::BI_RGB {
+            return;
+        }
+
+        let data_offset = header.data_offset as usize;
+        let row_data = (info_header.width.unsigned_abs() as usize * info_header.bit_depth as usize).div_ceil(8);
+
+        let padded_rows = (0..rows)
+            .filter_map(|row| value.get(data_offset + row * row_size + row_data..data_offset + (row + 1) * row_size))
+            .filter(|padding| padding.iter().any(|byte| *byte != 0))
+            .count();
+
+        if padded_rows > 0 {
+            conformance.push(Severity::Warning, format!("Bitmap has {padded_rows} rows with nonzero padding bytes."));
+        }
+
+        //The color profile may follow the pixel data, and isn't trailing bytes
+        let data_end = info_header.embedded_profile_range()
+            .filter(|_| self.icc_profile.is_some())
+            .map_or(0, |(_, end)| end)
+            .max(data_offset + pixel_bytes);
+
+        let trailing = value.len().saturating_sub(data_end);
+
+        if trailing > 0 {
+            conformance.push(Severity::Warning, format!("Bitmap has {trailing} bytes after the pixel data."));
+        }
+    }
+
+    ///
+    /// Decode only the given region of the bmp in the bytes, with (x, y) the top-left corner of
+    /// the region in the image. Uncompressed pixel data is read only for the rows and columns in
+    /// the region; anything else is decoded in full and cropped.
+    ///
+    pub fn decode_region(value: &[u8], x: usize, y: usize, width: usize, height: usize, options: DecodeOptions) -> Result<Image, ImageError> {
+        let mut pixels = Vec::new();
+        let mut context = DecodeContext::new(options);
+        let (width, height) = decode_pixels_into(value, Some((x, y, width, height)), &mut pixels, &mut context)?;
+        context.report.log();
+
+        Image::try_new_pixels(width, height, pixels)
+    }
+
+    ///
+    /// Decode the bmp in the bytes straight into the image, reusing its pixel buffer
+    /// rather than allocating a new one. On error, the image is left empty.
+    ///
+    pub fn decode_into(value: &[u8], image: &mut Image, options: DecodeOptions) -> Result<(), ImageError> {
+        let mut context = DecodeContext::new(options);
+        image.refill(|pixels| decode_pixels_into(value, None, pixels, &mut context))?;
+        context.report.log();
+
+        Ok(())
+    }
+}
+
+///
+/// Read a bmp from an array of bytes, repairing what violations of the format it can
+///
+impl TryFrom<&[u8]> for Bitmap {
+    type Error = ImageError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::decode(value, DecodeOptions::default())
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl Bitmap {
+    ///
+    /// Read the bmp at the given path, decoding directly from a memory
+    /// mapping of the file rather than reading it into memory first.
+    /// Anything other than a regular file, such as a pipe, is read instead.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be truncated or modified until this returns; see
+    /// utility::file::map_file_bytes.
+    ///
+    pub unsafe fn from_path_mmap(path: &str) -> Result<Self, ImageError> {
+        if !std::fs::metadata(path)?.is_file() {
+            return Self::try_from(utility::file::get_file_bytes(path)?);
+        }
+
+        //Safety: upheld by the caller
+        let bytes = unsafe { utility::file::map_file_bytes(path) }?;
+
+        Self::try_from(&bytes[..])
+    }
+}
+
+#[cfg(feature = "std")]
+impl Bitmap {
+    ///
+    /// Read a bmp from a reader with the given options, without reading the whole file into memory
+    /// first. The headers and color table are read, then the pixel data one scanline at a time into
+    /// the bitmap, so a large file takes little more memory than its pixels. Run-length encoded bitmaps
+    /// are read whole, then expanded. The bitmap is the same as decoding all of the bytes at once.
+    ///
+    pub fn from_reader<R: io::Read>(reader: R, options: DecodeOptions) -> Result<Self, ImageError> {
+        let (bitmap, report) = Self::from_reader_with_report(reader, options)?;
+        report.log();
+
+        Ok(bitmap)
+    }
+
+    ///
+    /// Read a bmp from a reader with the given options, along with
+    /// a report of what was wrong with it that wasn't an error
+    ///
+    pub fn from_reader_with_report<R: io::Read>(mut reader: R, options: DecodeOptions) -> Result<(Self, DecodeReport), ImageError> {
+        let mut context = DecodeContext::new(options);
+        let context = &mut context;
+
+        //The file header says where the headers and color table end, though the headers are read in full even if it is wrong
+        let mut headers: Vec<u8> = Vec::new();
+        reader.by_ref().take(u64::from(bitmap::HEADER_SIZE)).read_to_end(&mut headers)?;
+
+        let data_offset = headers.get(10..14).and_then(u32::reduce_bit_slice).unwrap_or_default();
+        let headers_size = data_offset.max(bitmap::HEADER_SIZE + bitmap::INFO_HEADER_SIZE) as usize;
+        reader.by_ref().take(headers_size.saturating_sub(headers.len()) as u64).read_to_end(&mut headers)?;
+
+        //The length of the file isn't known until it has all been read
+        let (header, info_header, color_table, offset) = read_headers(&headers, None, context)?;
+
+        //Any bytes read past the data offset along with the headers are the start of the pixel data
+        let mut data = headers.get(offset..).unwrap_or_default().chain(reader);
+        let mut file_size = offset;
+        let mut icc_profile: Option<Vec<u8>> = None;
+        let mut mask: Option<Vec<bool>> = None;
+
+        let pixels = if [bitmap::compression::BI_RLE8, bitmap::compression::BI_RLE4].contains(&info_header.compression) {
+            //Runs can't be expanded without knowing where they end, so the rest of the file is read with the headers
+            let mut file = headers.get(..offset).unwrap_or_default().to_vec();
+            data.read_to_end(&mut file)?;
+            file_size = file.len();
+
+            check_pixel_data_size(&info_header, file_size.saturating_sub(offset))?;
+            let pixels = read_pixels(&file, offset, &info_header, context)?;
+            icc_profile = find_icc_profile(&file, &info_header, context)?.map(<[u8]>::to_vec);
+
+            pixels
+        }
+        else {
+            let scanline_width = info_header.row_size();
+
+            //The length of the data isn't known either, so the pixels grow as scanlines arrive
+            let mut decoder = ScanlineDecoder::new(&info_header, 0)?;
+            let mut scanline: Vec<u8> = Vec::with_capacity(scanline_width);
+
+            for _ in 0..info_header.height.unsigned_abs() {
+                scanline.clear();
+                let count = data.by_ref().take(scanline_width as u64).read_to_end(&mut scanline)?;
+
+                check_scanline_count(file_size, count, scanline_width, context)?;
+                decoder.decode(&scanline);
+                file_size += count;
+
+                if count == 0 || count < scanline_width {
+                    break;
+                }
+            }
+
+            check_pixel_data_size(&info_header, file_size - offset)?;
+
+            //Icons follow the pixel data with the AND mask
+            if context.options.and_mask {
+                let start = offset + scanline_width * info_header.height.unsigned_abs() as usize;
+                let end = start + info_header.mask_size();
+                let mut trailing: Vec<u8> = Vec::with_capacity(end - start);
+
+                if file_size == start {
+                    file_size += data.by_ref().take((end - start) as u64).read_to_end(&mut trailing)?;
+                }
+
+                if trailing.len() == end - start {
+                    mask = Some(unpack_mask(&trailing, &info_header));
+                }
+                else {
+                    //Find the size of the file for the message
+                    file_size += io::copy(&mut data, &mut io::sink())? as usize;
+                    missing_section("AND mask", start, end, file_size, context)?;
+                }
+            }
+
+            //The color profile usually follows the pixel data, but may be between it and the color table
+            if let Some((start, end)) = info_header.embedded_profile_range() {
+                icc_profile = headers.get(start..end).map(<[u8]>::to_vec);
+
+                if icc_profile.is_none() && start >= file_size {
+                    file_size += io::copy(&mut data.by_ref().take((start - file_size) as u64), &mut io::sink())? as usize;
+
+                    let mut profile: Vec<u8> = Vec::new();
+                    file_size += data.by_ref().take((end - start) as u64).read_to_end(&mut profile)?;
+
+                    icc_profile = Some(profile).filter(|profile| profile.len() == end - start);
+                }
+
+                if icc_profile.is_none() {
+                    //Find the size of the file for the message
+                    file_size += io::copy(&mut data, &mut io::sink())? as usize;
+                    missing_section("color profile", start, end, file_size, context)?;
                 }
+            }
+
+            decoder.pixels
+        };
+
+        //Skip any bytes after the pixel data, to find the size of the file
+        file_size += io::copy(&mut data, &mut io::sink())? as usize;
+        check_file_size(&header, file_size, context)?;
+
+        check_pixel_count(&info_header, match &pixels {
+            BitmapPixelData::Indices(indices) => indices.len(),
+            BitmapPixelData::Colors(colors) => colors.len()
+        }, context)?;
+
+        let bitmap = Self {
+            header,
+            info_header,
+            color_table,
+            pixels: BitmapPixels { pixels, encoded: None },
+            icc_profile,
+            mask
+        };
+
+        Ok((bitmap, core::mem::take(&mut context.report)))
+    }
+
+    ///
+    /// Read a bmp from a reader, such as a slow file or stdin, calling on_rows_decoded
+    /// with the image so far and the range of its rows which just arrived, each time
+    /// more rows have been read. Rows which haven't arrived are transparent. Only
+    /// uncompressed bitmaps of 1, 4, 8, 24 or 32 bits are decoded as they arrive.
+    /// The bitmap returned is the same as decoding all of the bytes at once.
+    ///
+    pub fn decode_progressive<R, F>(mut reader: R, options: DecodeOptions, mut on_rows_decoded: F) -> Result<Self, ImageError>
+    where R: io::Read, F: FnMut(&Image, core::ops::Range<usize>) {
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut chunk = vec![0_u8; bitmap::PROGRESSIVE_CHUNK_SIZE];
+        let mut progress = Progress::Waiting;
+
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into())
+            };
+
+            bytes.extend_from_slice(chunk.get(..read).unwrap_or_default());
+
+            if matches!(progress, Progress::Waiting) {
+                progress = Progress::start(&bytes, options);
+            }
+
+            if let Progress::Decoding(rows) = &mut progress {
+                rows.update(&bytes, &mut on_rows_decoded);
+            }
+        }
+
+        Self::decode(&bytes, options)
+    }
+}
+
+///
+/// How far a progressive decode has got
+///
+#[cfg(feature = "std")]
+enum Progress {
+    ///
+    /// The headers and color table haven't all arrived
+    ///
+    Waiting,
+    Decoding(Box<ProgressiveRows>),
+    ///
+    /// The bitmap can't be decoded as it arrives, and is only decoded once it all has
+    ///
+    Off
+}
+
+#[cfg(feature = "std")]
+impl Progress {
+    fn start(bytes: &[u8], options: DecodeOptions) -> Self {
+        let Some(data_offset) = bytes.get(10..14).and_then(u32::reduce_bit_slice) else {
+            return Self::Waiting;
+        };
+
+        let Some(headers) = bytes.get(..data_offset as usize) else {
+            return Self::Waiting;
+        };
+
+        //Anything wrong with the headers is reported by the full decode at the end
+        let mut context = DecodeContext::new(DecodeOptions { and_mask: options.and_mask, ..Default::default() });
+
+        let Ok((_, info_header, color_table, _)) = read_headers(headers, None, &mut context) else {
+            return Self::Off;
+        };
+
+        let (width, height) = (info_header.width.unsigned_abs() as usize, info_header.height.unsigned_abs() as usize);
+
+        if info_header.compression != bitmap::compression::BI_RGB || ![1, 4, 8, 24, 32].contains(&info_header.bit_depth) || width == 0 || height == 0 {
+            return Self::Off;
+        }
+
+        Self::Decoding(Box::new(ProgressiveRows {
+            image: Image::new(width, height),
+            data_offset: data_offset as usize,
+            info_header,
+            color_table,
+            rows_decoded: 0
+        }))
+    }
+}
+
+///
+/// The rows of a progressive decode which have arrived so far
+///
+#[cfg(feature = "std")]
+struct ProgressiveRows {
+    image: Image,
+    data_offset: usize,
+    info_header: BitmapInfoHeader,
+    color_table: BitmapColorTable,
+    ///
+    /// How many rows, in the order they are stored, have been decoded
+    ///
+    rows_decoded: usize
+}
 
-                //Get the scanline data
-                let scanline = get_next_bytes(value, &mut offset, count);
+#[cfg(feature = "std")]
+impl ProgressiveRows {
+    ///
+    /// Decode every row which has arrived since the last update, and report them
+    ///
+    fn update<F: FnMut(&Image, core::ops::Range<usize>)>(&mut self, bytes: &[u8], on_rows_decoded: &mut F) {
+        let (width, height) = (self.image.width(), self.image.height());
+        let data = bytes.get(self.data_offset..).unwrap_or_default();
+        let arrived = (data.len() / self.info_header.row_size()).min(height);
 
-                // Loop over each bit in the scanline, ignoring 0-padding at the end of the scanline.
-                scanline.iter().enumerate().for_each(|(ndx, chunk)| {
-                    if ndx < (scaline_width_temp as usize) {
-                        //For each pixel in the bit
-                        for i in 1..=pixels_per_bit {
-                            //If past the width of the line, the rest of the bits are padding
-                            if (pixels_per_bit * ndx) + i > (info_header.width as usize) {
-                                break;
-                            }
+        if arrived <= self.rows_decoded {
+            return;
+        }
 
-                            //Extract the palette index of the (i - 1)th pixel from the byte
-                            let index = (*chunk
-                                >> (8 - ((info_header.bit_depth as i32) * (i as i32))))
-                                & ((2_u16.pow(info_header.bit_depth as u32) - 1) as u8);
+        let orientation = self.info_header.orientation();
 
-                            pixel_indices.push(index);
-                        }
-                    }
-                });
+        for row in self.rows_decoded..arrived {
+            for column in 0..width {
+                //Storing is its own inverse, so the stored position of a stored position is the pixel
+                let (i, j) = orientation.stored_position(column, row, width, height);
 
-                if done {
-                    break;
+                if let Some(pixel) = read_stored_pixel(data, &self.info_header, &self.color_table, row, column) {
+                    let _ = self.image.set(pixel, i, j);
                 }
             }
+        }
 
-            BitmapPixelData::Indices(pixel_indices)
+        let rows = if orientation.is_top_down() {
+            self.rows_decoded..arrived
         }
-        //bpp = 16: value of each pixel is 2 bytes, with each 5 bits representing Blue, Green and Red intensities respectively, and the last bit being unused.
-        else if info_header.bit_depth == 16 {
-            return Err(String::from("Not implemented for 16-bit images!"));
-        }
-        //bpp = 24: value of each pixel is 3 bytes, representing Blue, Green and Red intensities respectively
-        //bpp = 32: value of each pixel is 4 bytes, representing Alpha, Blue, Green and Red intensities respectively
-        else if [24, 32].contains(&info_header.bit_depth) {
-            let mut pixel_values: Vec<color::ARGB> = Vec::new();
-
-            //Get scanline width based on line width
-            let bytesperpixel = f32::ceil((info_header.bit_depth as f32) / 8_f32) as usize;
-            let scaline_width_temp = i32::abs(info_header.width * (bytesperpixel as i32));
-            let scanline_width = utility::round_to_next_multiple_of_4(scaline_width_temp);
-
-            //Read in each scanline
-            loop {
-                let mut done = false;
-                let mut count = scanline_width;
-
-                // I don't think this should ever happen for a properly-formatted
-                // bitmap, but if the scanline goes past the end of the file,
-                // truncate it
-                if value.len() < offset + scanline_width {
-                    count = ((value.len() as i32) - (offset as i32)) as usize;
-                    done = true;
-                }
+        else {
+            (height - arrived)..(height - self.rows_decoded)
+        };
 
-                //Get the scanline data
-                let scanline = get_next_bytes(value, &mut offset, count);
-                let mut line: Vec<color::ARGB> = Vec::new();
-
-                // Loop over each chunk of 3/4 bytes in the scanline, ignoring 0-padding at the end of the scanline.
-                scanline.chunks(bytesperpixel).for_each(|chunk| {
-                    //Ignore 0-padding
-                    if chunk.len() == bytesperpixel && (line.len() as u32) < info_header.width.unsigned_abs() {
-                        //Extract alpha, blue, green, and red from their respective bytes
-                        let color = color::ARGB {
-                            blue: *chunk.first().unwrap_or(&0),
-                            green: *chunk.get(1).unwrap_or(&0),
-                            red: *chunk.get(2).unwrap_or(&0),
-                            alpha: match bytesperpixel {
-                                4 => *chunk.get(3).unwrap_or(&0),
-                                _ => 0xFF,
-                            },
-                        };
+        self.rows_decoded = arrived;
 
-                        line.push(color);
-                    }
-                });
+        on_rows_decoded(&self.image, rows);
+    }
+}
 
-                //Append the scanline
-                pixel_values.append(&mut line);
+///
+/// Read a bmp from an array of bytes
+///
+impl TryFrom<Vec<u8>> for Bitmap {
+    type Error = ImageError;
 
-                if done {
-                    break;
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        let as_slice = &value[..];
+        Self::try_from(as_slice)
+    }
+}
+
+///
+/// A bmp read from an array of bytes, which borrows its pixel data from the
+/// bytes rather than copying it where it can. Colors are converted as they are
+/// accessed, so reading just the headers of a large image is cheap.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitmapRef<'a> {
+    pub header: BitmapHeader,
+    pub info_header: BitmapInfoHeader,
+    pub color_table: BitmapColorTable,
+    pub pixels: BitmapPixelsRef<'a>,
+    pub icc_profile: Option<&'a [u8]>,
+    ///
+    /// The AND mask following the pixel data, packed a bit per pixel in rows padded to 4 bytes
+    ///
+    pub mask: Option<&'a [u8]>,
+}
+
+///
+/// Pixel data of a borrowed bmp. Uncompressed 24 and 32-bit pixel
+/// data is borrowed as is, and anything else is decoded.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BitmapPixelsRef<'a> {
+    Borrowed(&'a [u8]),
+    Decoded(BitmapPixelData)
+}
+
+impl BitmapRef<'_> {
+    ///
+    /// Get the name of the compression method used by the bitmap
+    /// 
+    pub fn compression_name(&self) -> &'static str {
+        self.info_header.compression_name()
+    }
+
+    ///
+    /// Get the size of the file as computed from the headers, color table
+    /// and pixel data, as opposed to the size declared in the header
+    /// 
+    pub fn computed_file_size(&self) -> usize {
+        self.info_header.computed_file_size(self.color_table.colors.len())
+    }
+
+    ///
+    /// Whether the pixel data is borrowed, rather than decoded
+    /// 
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.pixels, BitmapPixelsRef::Borrowed(_))
+    }
+
+    ///
+    /// Number of pixels in the pixel data. This is less than the
+    /// dimensions call for if the data is truncated.
+    /// 
+    pub fn pixel_count(&self) -> usize {
+        match &self.pixels {
+            BitmapPixelsRef::Borrowed(data) => {
+                let width = self.info_header.width.unsigned_abs() as usize;
+                let height = self.info_header.height.unsigned_abs() as usize;
+                let row_size = self.info_header.row_size();
+
+                if width == 0 || row_size == 0 {
+                    return 0;
                 }
-            }
 
-            BitmapPixelData::Colors(pixel_values)
-        } else {
-            return Err(format!(
-                "Not implemented for {}-bit images!",
-                info_header.bit_depth
-            ));
+                //Whole rows, then whatever whole pixels remain of a truncated row
+                let bytes_per_pixel = self.info_header.bit_depth as usize / 8;
+                let partial = ((data.len() % row_size) / bytes_per_pixel).min(width);
+
+                ((data.len() / row_size) * width + partial).min(width * height)
+            },
+            BitmapPixelsRef::Decoded(BitmapPixelData::Colors(colors)) => colors.len(),
+            BitmapPixelsRef::Decoded(BitmapPixelData::Indices(indices)) => indices.len()
+        }
+    }
+
+    ///
+    /// Get the color at the given index of the pixel data, in the
+    /// order rows are stored, if it exists
+    /// 
+    pub fn get(&self, index: usize) -> Option<color::ARGB> {
+        match &self.pixels {
+            BitmapPixelsRef::Borrowed(data) => {
+                if index >= self.pixel_count() {
+                    return None;
+                }
+
+                let width = self.info_header.width.unsigned_abs() as usize;
+                read_stored_pixel(data, &self.info_header, &self.color_table, index / width, index % width)
+            },
+            BitmapPixelsRef::Decoded(BitmapPixelData::Colors(colors)) => colors.get(index).copied(),
+            BitmapPixelsRef::Decoded(BitmapPixelData::Indices(indices)) => indices.get(index)
+                .and_then(|index| self.color_table.colors.get(*index as usize))
+                .copied()
+        }
+    }
+}
+
+impl<'a> BitmapRef<'a> {
+    ///
+    /// Read a bmp from an array of bytes with the given options, borrowing its pixel data if possible,
+    /// along with a report of what was wrong with it that wasn't an error
+    ///
+    pub fn decode_with_report(value: &'a [u8], options: DecodeOptions) -> Result<(Self, DecodeReport), ImageError> {
+        let mut context = DecodeContext::new(options);
+        let context = &mut context;
+
+        let (header, info_header, color_table, offset) = read_headers(value, Some(value.len()), context)?;
+
+        let pixels = if info_header.compression == bitmap::compression::BI_RGB && [24, 32].contains(&info_header.bit_depth) {
+            BitmapPixelsRef::Borrowed(value.get(offset..).unwrap_or_default())
+        }
+        else {
+            BitmapPixelsRef::Decoded(read_pixels(value, offset, &info_header, context)?)
         };
 
-        let pixels = BitmapPixels { pixels: pixel_vec };
+        let icc_profile = find_icc_profile(value, &info_header, context)?;
+        let mask = find_mask(value, offset, &info_header, context)?;
 
-        Ok(Self {
+        let bitmap = Self {
             header,
             info_header,
             color_table,
             pixels,
-        })
+            icc_profile,
+            mask
+        };
+
+        check_pixel_count(&bitmap.info_header, bitmap.pixel_count(), context)?;
+
+        Ok((bitmap, core::mem::take(&mut context.report)))
     }
 }
 
 ///
-/// Read a bmp from an array of bytes
+/// Read a bmp from an array of bytes, borrowing its pixel data if possible
 ///
-impl TryFrom<Vec<u8>> for Bitmap {
-    type Error = String;
+impl<'a> TryFrom<&'a [u8]> for BitmapRef<'a> {
+    type Error = ImageError;
 
-    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
-        let as_slice = &value[..];
-        Self::try_from(as_slice)
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        let (bitmap, report) = Self::decode_with_report(value, DecodeOptions::default())?;
+        report.log();
+
+        Ok(bitmap)
+    }
+}
+
+///
+/// Copy the pixel data of a borrowed bmp, converting it to colors
+///
+impl From<BitmapRef<'_>> for Bitmap {
+    fn from(value: BitmapRef<'_>) -> Self {
+        let pixels = match value.pixels {
+            BitmapPixelsRef::Borrowed(_) => BitmapPixelData::Colors((0..value.pixel_count())
+                .filter_map(|index| value.get(index))
+                .collect()),
+            BitmapPixelsRef::Decoded(ref pixels) => pixels.clone()
+        };
+
+        let mask = value.mask.map(|mask| unpack_mask(mask, &value.info_header));
+
+        Self {
+            header: value.header,
+            info_header: value.info_header,
+            color_table: value.color_table,
+            pixels: BitmapPixels { pixels, encoded: None },
+            icc_profile: value.icc_profile.map(<[u8]>::to_vec),
+            mask
+        }
+    }
+}
+
+impl Bitmap {
+    ///
+    /// Encode the bmp, handing each run of bytes to emit in the order they are stored:
+    /// the headers and color table, then the pixel data a chunk of scanlines at a time,
+    /// then the mask and color profile if there are any
+    ///
+    fn encode<E, F: FnMut(&[u8]) -> Result<(), E>>(&self, mut emit: F) -> Result<(), E> {
+        let mut buffer: Vec<u8> = Vec::with_capacity(self.info_header.color_table_offset()
+            + bitmap::COLOR_TABLE_SIZE_FACTOR as usize * self.color_table.colors.len());
+
+        for field in [self.header.signature.to_le_bytes().as_slice(), 
+            self.header.file_size.to_le_bytes().as_slice(),
+            self.header.reserved.to_le_bytes().as_slice(),
+            self.header.data_offset.to_le_bytes().as_slice(),
+            self.info_header.size.to_le_bytes().as_slice(),
+            self.info_header.width.to_le_bytes().as_slice(),
+            self.stored_height().to_le_bytes().as_slice(),
+            self.info_header.planes.to_le_bytes().as_slice(),
+            self.info_header.bit_depth.to_le_bytes().as_slice(),
+            self.info_header.compression.to_le_bytes().as_slice(),
+            self.info_header.image_size.to_le_bytes().as_slice(),
+            self.info_header.x_pixels_per_meter.to_le_bytes().as_slice(),
+            self.info_header.y_pixels_per_meter.to_le_bytes().as_slice(),
+            self.info_header.colors_used.to_le_bytes().as_slice(),
+            self.info_header.important_colors.to_le_bytes().as_slice()] {
+            buffer.extend_from_slice(field);
+        }
+
+        //Write the fields of later versions of the info header, and pad out any
+        //which aren't known, so the color table starts where the size says
+        let info_end = (bitmap::HEADER_SIZE + self.info_header.size) as usize;
+
+        buffer.extend(self.info_header.extended_fields()
+            .into_iter()
+            .flat_map(|(_, field)| field.to_le_bytes()));
+        buffer.resize(buffer.len().max(info_end), 0_u8);
+
+        buffer.extend(self.color_table.colors.iter()
+            .flat_map(|color| (color.as_u32(false)).to_le_bytes()));
+
+        //A color profile may be stored between the color table and the pixel data
+        let profile = self.icc_profile.as_deref()
+            .zip(self.info_header.embedded_profile_range())
+            .filter(|(profile, (start, end))| profile.len() == end - start);
+
+        if let Some((profile, (start, _))) = profile.filter(|(_, (start, end))| *start >= buffer.len() && *end <= self.header.data_offset as usize) {
+            buffer.resize(start, 0_u8);
+            buffer.extend_from_slice(profile);
+            buffer.resize(self.header.data_offset as usize, 0_u8);
+        }
+
+        emit(&buffer)?;
+        let mut written = buffer.len();
+
+        //An empty row still needs a nonzero chunk size to split the pixels into rows
+        let width = (self.info_header.width.unsigned_abs() as usize).max(1);
+
+        //Encode enough rows at a time to be worth splitting across workers, reusing the buffer for each chunk
+        let rows_per_chunk = (bitmap::ENCODE_CHUNK_SIZE / self.row_size().max(1)).max(1);
+
+        //Convert the bitmap pixels to bytes
+        match &self.pixels.pixels {
+            BitmapPixelData::Indices(indices) => {
+                let bit_depth = self.info_header.bit_depth;
+
+                //Compressed pixel data is small enough to encode at once
+                if [bitmap::compression::BI_RLE8, bitmap::compression::BI_RLE4].contains(&self.info_header.compression) {
+                    let encoded = self.pixels.encoded.is_none().then(|| rle_encode(indices, width, bit_depth));
+                    let data = self.pixels.encoded.as_deref().or(encoded.as_deref()).unwrap_or_default();
+                    emit(data)?;
+                    written += data.len();
+                }
+                else {
+                    for chunk in indices.chunks(width * rows_per_chunk) {
+                        buffer.clear();
+                        encode_rows(&mut buffer, chunk, width, packed_row_bytes(width, bit_depth), |scanline, row| pack_indices(scanline, row, bit_depth));
+                        emit(&buffer)?;
+                        written += buffer.len();
+                    }
+                }
+            },
+            BitmapPixelData::Colors(colors) => {
+                let bytes_per_pixel = (self.info_header.bit_depth as usize).div_ceil(8).min(4);
+
+                //16-bit pixels, and any with bitfields, are packed into the bits of their masks
+                let masks = match self.info_header.channel_masks() {
+                    None if self.info_header.bit_depth == 16 => Some(BitmapMasks::RGB_555),
+                    masks => masks
+                };
+
+                for chunk in colors.chunks(width * rows_per_chunk) {
+                    buffer.clear();
+
+                    encode_rows(&mut buffer, chunk, width, width * bytes_per_pixel, |scanline, row| {
+                        for (color, out) in scanline.iter().zip(row.chunks_exact_mut(bytes_per_pixel)) {
+                            let pixel = masks.map_or_else(|| color.as_u32(false), |masks| masks.pack(*color));
+                            out.copy_from_slice(pixel.to_le_bytes().get(..bytes_per_pixel).unwrap_or_default());
+                        }
+                    });
+
+                    emit(&buffer)?;
+                    written += buffer.len();
+                }
+            }
+        }
+
+        //The mask follows the pixel data, a bit per pixel
+        if let Some(mask) = self.mask.as_ref().filter(|_| self.has_mask()) {
+            let mask: Vec<u8> = mask.iter().map(|bit| u8::from(*bit)).collect();
+
+            for chunk in mask.chunks(width * rows_per_chunk) {
+                buffer.clear();
+                encode_rows(&mut buffer, chunk, width, packed_row_bytes(width, 1), |scanline, row| pack_indices(scanline, row, 1));
+                emit(&buffer)?;
+                written += buffer.len();
+            }
+        }
+
+        //Otherwise, the color profile follows the pixel data
+        if let Some((profile, (start, _))) = profile.filter(|(_, (start, _))| *start >= written) {
+            buffer.clear();
+            buffer.resize(start - written, 0_u8);
+            buffer.extend_from_slice(profile);
+            emit(&buffer)?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Write the bmp into the writer, the headers and color table and then the pixel
+    /// data a chunk of scanlines at a time, without encoding the whole file in memory
+    ///
+    #[cfg(feature = "std")]
+    pub fn write_to<W: io::Write>(&self, out: &mut W) -> Result<(), ImageError> {
+        self.encode(|bytes| out.write_all(bytes))?;
+
+        Ok(())
     }
 }
 
@@ -611,100 +2707,156 @@ impl TryFrom<Vec<u8>> for Bitmap {
 /// Convert a bmp to an array of bytes
 ///
 impl TryFrom<Bitmap> for Vec<u8> {
-    type Error = String;
+    type Error = ImageError;
 
     fn try_from(value: Bitmap) -> Result<Self, Self::Error> {
-        //Convert the bitmap pixels to bytes
-        let width = value.info_header.width.unsigned_abs() as usize;
-        let mut pixel_bytes: Vec<u8> = Vec::new();
-
-        match value.pixels.pixels {
-            BitmapPixelData::Indices(ref indices) => {
-                for scanline in indices.chunks_exact(width) {
-                    let mut bytes: Vec<u8> = Vec::new();
-
-                    if [1, 4, 8].contains(&value.info_header.bit_depth) {
-                        let bit_depth_u8 = value.info_header.bit_depth as u8;
-                        let pixels_per_bit = f32::ceil(8_f32 / (value.info_header.bit_depth as f32)) as usize;
-
-                        let mut first: bool = true;
-                        let mut current: u8 = 0;
-
-                        for (index, color_index) in scanline.iter().enumerate() {
-                            //Only take the relevant bits from the color_index
-                            let normalized_index = color_index & ((2 << (value.info_header.bit_depth + 1)) - 1) as u8;
-                            let index_mod = (index % pixels_per_bit) as u8;
-
-                            //If first index in byte, push current to vector
-                            if index_mod == 0 {
-                                if first {
-                                    first = false;
-                                }
-                                else {
-                                    bytes.push(current.to_be());
-                                }
+        //Allocate the whole file up front, and append each part as it is encoded
+        let mut bytes: Vec<u8> = Vec::with_capacity(value.info_header.color_table_offset()
+            + bitmap::COLOR_TABLE_SIZE_FACTOR as usize * value.color_table.colors.len()
+            + value.row_size() * value.info_header.height.unsigned_abs() as usize);
 
-                                current = 0;
-                            }
+        value.encode(|part| {
+            bytes.extend_from_slice(part);
+            Ok::<(), core::convert::Infallible>(())
+        }).unwrap_or_else(|never| match never {});
 
-                            //Add to the current byte
-                            let shifted_index = normalized_index << (8 - bit_depth_u8 - (index_mod * bit_depth_u8));
+        Ok(bytes)
+    }
+}
 
-                            current += shifted_index;
+///
+/// Append each complete scanline of the given width, encoded into row_bytes
+/// bytes and padded to a multiple of 4. Each row is written into its own slice
+/// of the output, so with the parallel feature rows are encoded in parallel,
+/// and still come out in order.
+///
+fn encode_rows<T, F>(bytes: &mut Vec<u8>, pixels: &[T], width: usize, row_bytes: usize, encode: F)
+where T: Sync, F: Fn(&[T], &mut [u8]) + Sync {
+    let row_size = utility::round_to_next_multiple_of_4(row_bytes as i32);
 
-                            //If last element, push current byte
-                            if index == scanline.len() - 1 {
-                                bytes.push(current.to_be());
-                            }
-                        }
-                    }
+    if row_size == 0 {
+        return;
+    }
 
-                    //Pad row to a multiple of 4 bytes
-                    bytes.resize(utility::round_to_next_multiple_of_4(bytes.len() as i32), 0_u8);
-                    pixel_bytes.append(&mut bytes);
-                }
-            },
-            BitmapPixelData::Colors(ref colors) => {
-                let bytes_per_pixel = f32::ceil((value.info_header.bit_depth as f32) / 8_f32) as usize;
+    let start = bytes.len();
+    bytes.resize(start + row_size * (pixels.len() / width), 0_u8);
+
+    let rows = bytes.get_mut(start..).unwrap_or_default();
+
+    #[cfg(feature = "parallel")]
+    rows.par_chunks_exact_mut(row_size)
+        .zip(pixels.par_chunks_exact(width))
+        .for_each(|(row, scanline)| encode(scanline, row));
+
+    #[cfg(not(feature = "parallel"))]
+    rows.chunks_exact_mut(row_size)
+        .zip(pixels.chunks_exact(width))
+        .for_each(|(row, scanline)| encode(scanline, row));
+}
+
+///
+/// Number of bytes a scanline of the given width of color table indices packs into,
+/// before padding, or 0 if the bit depth doesn't hold indices
+///
+fn packed_row_bytes(width: usize, bit_depth: u16) -> usize {
+    match bit_depth {
+        1 | 4 | 8 => (width * bit_depth as usize).div_ceil(8),
+        _ => 0
+    }
+}
+
+///
+/// Pack a scanline of color table indices into bytes, leftmost pixel in the high bits.
+/// Only the low bits of each index which fit the bit depth are kept, and the unused
+/// low bits of a partial final byte, and any bytes past the scanline, are left 0.
+///
+fn pack_indices(scanline: &[u8], row: &mut [u8], bit_depth: u16) {
+    if ![1, 4, 8].contains(&bit_depth) {
+        return;
+    }
+
+    let bit_depth = bit_depth as usize;
+    let pixels_per_byte = 8 / bit_depth;
+    let mask = ((1_u16 << bit_depth) - 1) as u8;
+
+    row.fill(0);
+
+    for (byte, indices) in row.iter_mut().zip(scanline.chunks(pixels_per_byte)) {
+        *byte = indices.iter()
+            .enumerate()
+            .fold(0_u8, |byte, (i, index)| byte | (index & mask) << (8 - bit_depth * (i + 1)));
+    }
+}
+
+///
+/// Run-length encode each complete scanline of the given width of color table indices as RLE8,
+/// or RLE4 if the bit depth is 4. Repeated indices are encoded as runs and anything else as
+/// absolute runs, with 1 or 2 pixels between repeats encoded as short runs, since absolute runs
+/// must be at least 3 pixels. Each row ends with an end of line, and the last with an end of bitmap.
+///
+fn rle_encode(indices: &[u8], width: usize, bit_depth: u16) -> Vec<u8> {
+    let nibbles = bit_depth == 4;
+
+    //The byte of a run which alternates the 2 indices, which for RLE8 is just the first
+    let run_byte = |first: u8, second: u8| if nibbles { (first & 0x0F) << 4 | (second & 0x0F) } else { first };
+
+    //The length of the run of the same index starting at the given column, up to the most one run can hold
+    let run_length = |scanline: &[u8], start: usize| {
+        let first = scanline.get(start);
+        scanline.iter().skip(start).take(u8::MAX as usize).take_while(|index| Some(*index) == first).count()
+    };
+
+    let mut bytes = Vec::new();
+
+    for scanline in indices.chunks_exact(width.max(1)) {
+        let mut column = 0;
+
+        while let Some(&index) = scanline.get(column) {
+            let run = run_length(scanline, column);
+
+            if run >= 3 {
+                bytes.extend_from_slice(&[run as u8, run_byte(index, index)]);
+                column += run;
+                continue;
+            }
 
-                for scanline in colors.chunks_exact(width) {
-                    let mut bytes: Vec<u8> = Vec::new();
+            //Pixels up to the next run of at least 3, which is cheaper encoded as a run
+            let literal = (column..scanline.len())
+                .take(u8::MAX as usize)
+                .take_while(|start| run_length(scanline, *start) < 3)
+                .count();
 
-                    for color in scanline {
-                        let color_u32 = color.as_u32(false);
-                        let mut color_bytes = Vec::from(color_u32.to_le_bytes());
-                        color_bytes.truncate(bytes_per_pixel);
-                        bytes.append(&mut color_bytes);
+            match *scanline.get(column..column + literal).unwrap_or_default() {
+                [only] => bytes.extend_from_slice(&[1, run_byte(only, 0)]),
+                [first, second] if nibbles || first == second => bytes.extend_from_slice(&[2, run_byte(first, second)]),
+                [first, second] => bytes.extend_from_slice(&[1, first, 1, second]),
+                ref pixels => {
+                    bytes.extend_from_slice(&[0, literal as u8]);
+
+                    let start = bytes.len();
+
+                    if nibbles {
+                        bytes.extend(pixels.chunks(2).map(|pair| run_byte(pair.first().copied().unwrap_or_default(), pair.get(1).copied().unwrap_or_default())));
+                    }
+                    else {
+                        bytes.extend_from_slice(pixels);
                     }
 
-                    //Pad row to a multiple of 4 bytes
-                    bytes.resize(utility::round_to_next_multiple_of_4(bytes.len() as i32), 0_u8);
-                    pixel_bytes.append(&mut bytes);
+                    //Absolute runs are padded to a multiple of 2 bytes
+                    if (bytes.len() - start) % 2 == 1 {
+                        bytes.push(0);
+                    }
                 }
             }
+
+            column += literal;
         }
 
-        //Concatenate all of the bitmap's bytes together
-        Ok([value.header.signature.to_le_bytes().as_slice(), 
-            value.header.file_size.to_le_bytes().as_slice(),
-            value.header.reserved.to_le_bytes().as_slice(),
-            value.header.data_offset.to_le_bytes().as_slice(),
-            value.info_header.size.to_le_bytes().as_slice(),
-            value.info_header.width.to_le_bytes().as_slice(),
-            value.info_header.height.to_le_bytes().as_slice(),
-            value.info_header.planes.to_le_bytes().as_slice(),
-            value.info_header.bit_depth.to_le_bytes().as_slice(),
-            value.info_header.compression.to_le_bytes().as_slice(),
-            value.info_header.image_size.to_le_bytes().as_slice(),
-            value.info_header.x_pixels_per_meter.to_le_bytes().as_slice(),
-            value.info_header.y_pixels_per_meter.to_le_bytes().as_slice(),
-            value.info_header.colors_used.to_le_bytes().as_slice(),
-            value.info_header.important_colors.to_le_bytes().as_slice(),
-            &value.color_table.colors.iter()
-                .flat_map(|color| (color.as_u32(false)).to_le_bytes())
-                .collect::<Vec<u8>>(),
-            &pixel_bytes].concat())
+        bytes.extend_from_slice(&[0, 0]);
     }
+
+    bytes.extend_from_slice(&[0, 1]);
+    bytes
 }
 
 ///
@@ -713,38 +2865,93 @@ impl TryFrom<Bitmap> for Vec<u8> {
 ///
 impl ConvertableFrom<Image> for Bitmap {
     type Options = BitmapConvertData;
-    type Error = String;
+    type Error = ImageError;
 
     #[allow(unused_variables)]
     fn try_convert_from(value: Image, options: Self::Options) -> Result<Self, Self::Error> {
-        
-        let mut color_table: HashMap<u32, u8> = HashMap::new();
+        let bit_depth = options.bit_depth.resolve(&value, options.compression);
+
+        //Run-length encoding only holds indices of a single bit depth
+        let rle = match options.compression {
+            bitmap::compression::BI_RLE8 => Some((8, "BI_RLE8")),
+            bitmap::compression::BI_RLE4 => Some((4, "BI_RLE4")),
+            _ => None
+        };
+
+        if let Some((rle_bit_depth, name)) = rle.filter(|(rle_bit_depth, _)| *rle_bit_depth != bit_depth) {
+            return Err(ImageError::InvalidArgument(format!("Bitmap bit depth is {bit_depth}, but {name} compression needs a bit depth of {rle_bit_depth}.")));
+        }
+
+        if let Some((_, name)) = rle.filter(|_| options.top_down) {
+            return Err(ImageError::InvalidArgument(format!("Top-down bitmaps can't have {name} compression.")));
+        }
+
+        if let Some((_, name)) = rle.filter(|_| options.and_mask) {
+            return Err(ImageError::InvalidArgument(format!("Bitmaps with an AND mask can't have {name} compression.")));
+        }
+
+        let bit_depths = valid_bit_depths(options.compression)
+            .ok_or_else(|| ImageError::InvalidArgument(format!("Bitmap compression is {}, which is not a known compression.", options.compression)))?;
+
+        if !bit_depths.contains(&bit_depth) {
+            return Err(ImageError::InvalidArgument(format!("Bitmap bit depth is {bit_depth}, which is not valid with {} compression.", compression_name(options.compression))));
+        }
+
+        //Pixels with bitfields are packed into the usual masks for their bit depth. Only the V5 info
+        //header, used for a color profile, has room for an alpha mask; the masks following a
+        //BITMAPINFOHEADER are only those of the colors.
+        let masks = (options.compression == bitmap::compression::BI_BITFIELDS).then_some(match bit_depth {
+            16 => BitmapMasks::RGB_565,
+            _ if value.metadata().icc_profile.is_some() => BitmapMasks::ARGB_8888,
+            _ => BitmapMasks { alpha: 0, ..BitmapMasks::ARGB_8888 }
+        });
+
+        //Rows are stored bottom-up unless asked otherwise, and storing is its own inverse
+        let orientation = Orientation::new(options.top_down, false);
+        let stored_rows = (0..value.height).map(|row| value.row(orientation.stored_position(0, row, value.width, value.height).1));
+
+        let mask: Option<Vec<bool>> = options.and_mask.then(|| stored_rows.clone()
+            .flat_map(|row| row.iter().map(|pixel| pixel.alpha < bitmap::MASK_ALPHA_THRESHOLD))
+            .collect());
+
+        let mut color_table: BTreeMap<u32, u8> = BTreeMap::new();
         let mut color_table_colors: Vec<color::ARGB> = Vec::new();
 
-        let pixels: BitmapPixelData = if [1, 4, 8].contains(&options.bit_depth) {
+        let pixels: BitmapPixelData = if [1, 4, 8].contains(&bit_depth) {
             //For bit depth of 1, 4, or 8, construct the color table and set pixels to be indices into the color table
             let mut color_table_indices: Vec<u8> = Vec::new();
+            let capacity = 1_usize << bit_depth;
 
-            for (rownum, row) in value.iter().enumerate() {
+            for row in stored_rows {
                 for pixel in row {
-                    let pixel_u32 = pixel.as_u32(true);
-                    let color_table_len = color_table.len() as u8;
+                    let color_table_len = color_table.len();
+
+                    let index = match color_table.entry(pixel.as_u32(true)) {
+                        Entry::Occupied(entry) => *entry.get(),
+                        //An index past the end of the color table would wrap around to another color
+                        Entry::Vacant(_) if color_table_len >= capacity => {
+                            let colors = value.iter().flat_map(|row| row.iter().map(|pixel| pixel.as_u32(true))).collect::<BTreeSet<_>>().len();
+                            return Err(ImageError::Encode(format!("Image has {colors} colors, but a {bit_depth}-bit bitmap can only hold {capacity}.")));
+                        },
+                        Entry::Vacant(entry) => {
+                            color_table_colors.push(*pixel);
+                            *entry.insert(color_table_len as u8)
+                        }
+                    };
 
-                    if let Entry::Vacant(e) = color_table.entry(pixel_u32) {
-                        e.insert(color_table_len);
-                        color_table_colors.push(*pixel);
-                    }
-    
-                    color_table_indices.push(*color_table.get(&pixel_u32).unwrap());
+                    color_table_indices.push(index);
                 }
             }
 
+            if options.palette_order == PaletteOrder::Frequency {
+                order_by_frequency(&mut color_table_colors, &mut color_table_indices);
+            }
+
             BitmapPixelData::Indices(color_table_indices)
         }
         else {
             //For any other bit depth, the color table isn't necessary, and the pixel data will be the literal (A)RGB values
-            let img_pixels: Vec<color::ARGB> = value.iter()
-                .rev()
+            let img_pixels: Vec<color::ARGB> = stored_rows
                 .flat_map(|r| r.iter().copied())
                 .collect();
 
@@ -766,83 +2973,179 @@ impl ConvertableFrom<Image> for Bitmap {
             BitmapPixelData::Colors(img_pixels)
         };
 
-        let data_offset: u32 = bitmap::HEADER_SIZE + bitmap::INFO_HEADER_SIZE + (bitmap::COLOR_TABLE_SIZE_FACTOR * color_table.len() as u32);
-        
-        //The size of the actual pixel data is the number of bytes per pixel times the number of pixels in a row (rounded to a multiple of 4 for padding),
-        //times the number of rows
-        let bytes_per_pixel = f32::ceil((options.bit_depth as f32) / 8_f32) as usize;
-        let image_size = (utility::round_to_next_multiple_of_4((value.width * bytes_per_pixel) as i32) * value.height) as u32;
+        //Only the V5 info header can point to a color profile
+        let icc_profile = value.metadata().icc_profile.clone();
+        let info_header_size = if icc_profile.is_some() { bitmap::V5_INFO_HEADER_SIZE } else { bitmap::INFO_HEADER_SIZE };
+
+        let info_header = BitmapInfoHeader { 
+            size: info_header_size, 
+            width: value.width as i32, 
+            height: if options.top_down { -(value.height as i32) } else { value.height as i32 }, 
+            planes: 1, 
+            bit_depth, 
+            compression: options.compression, 
+            image_size: 0_u32, 
+            x_pixels_per_meter: i32::try_from(options.resolution.x_pixels_per_meter()).unwrap_or(i32::MAX),
+            y_pixels_per_meter: i32::try_from(options.resolution.y_pixels_per_meter()).unwrap_or(i32::MAX), 
+            colors_used: color_table.len() as u32, 
+            important_colors: 0_u32,
+            //The V5 info header holds the masks in its V4 fields instead
+            bitfields: masks.filter(|_| icc_profile.is_none()),
+            v4: None,
+            v5: None
+        };
+
+        //The bitfields, if any, come between the info header and the color table
+        let data_offset = (info_header.color_table_offset() + bitmap::COLOR_TABLE_SIZE_FACTOR as usize * color_table.len()) as u32;
+
+        //The size of uncompressed pixel data is the size of each row of packed pixels, padded to a
+        //multiple of 4, times the number of rows. Compressed pixel data is as large as it encodes to.
+        let encoded = match &pixels {
+            BitmapPixelData::Indices(indices) if rle.is_some() => Some(rle_encode(indices, value.width, bit_depth)),
+            _ => None
+        };
+
+        let image_size = encoded.as_ref().map_or_else(|| info_header.row_size() * value.height, Vec::len) as u32;
+
+        let info_header = BitmapInfoHeader { image_size, ..info_header };
+
+        let mask_size = mask.as_ref().map_or(0, |_| info_header.mask_size());
+
+        //The color profile is stored after the pixel data and any mask, at an offset from the start of the info header
+        let info_header = match &icc_profile {
+            Some(profile) => BitmapInfoHeader {
+                v4: Some(BitmapV4Fields {
+                    red_mask: masks.map_or(0, |masks| masks.red),
+                    green_mask: masks.map_or(0, |masks| masks.green),
+                    blue_mask: masks.map_or(0, |masks| masks.blue),
+                    alpha_mask: masks.map_or(0, |masks| masks.alpha),
+                    color_space_type: bitmap::color_space::PROFILE_EMBEDDED,
+                    ..Default::default()
+                }),
+                v5: Some(BitmapV5Fields {
+                    intent: bitmap::intent::IMAGES,
+                    profile_data: (info_header.computed_file_size(color_table_colors.len()) + mask_size - bitmap::HEADER_SIZE as usize) as u32,
+                    profile_size: profile.len() as u32,
+                    reserved: 0_u32
+                }),
+                ..info_header
+            },
+            None => info_header
+        };
+
+        let profile_size = icc_profile.as_ref().map_or(0, Vec::len);
 
         Ok(Bitmap { 
             header: BitmapHeader { 
                 signature: bitmap::SIGNATURE, 
-                file_size: data_offset + image_size,
+                file_size: (info_header.computed_file_size(color_table_colors.len()) + mask_size + profile_size) as u32,
                 reserved: 0_u32,
                 data_offset
             }, 
-            info_header: BitmapInfoHeader { 
-                size: bitmap::INFO_HEADER_SIZE, 
-                width: value.width as i32, 
-                height: value.height as i32, 
-                planes: 1, 
-                bit_depth: options.bit_depth, 
-                compression: options.compression, 
-                image_size: 0_u32, 
-                x_pixels_per_meter: options.x_pixels_per_meter,
-                y_pixels_per_meter: options.y_pixels_per_meter, 
-                colors_used: color_table.len() as u32, 
-                important_colors: 0_u32
-            }, 
+            info_header, 
             color_table: BitmapColorTable { 
                 colors: color_table_colors
             }, 
             pixels: BitmapPixels { 
-                pixels
-            }
+                pixels,
+                encoded
+            },
+            icc_profile,
+            mask
         })
     }
 }
 
+///
+/// Reorder the colors of a color table so those indexed by the most pixels come first,
+/// keeping the order of colors used equally often, and update the indices to match
+///
+fn order_by_frequency(colors: &mut Vec<color::ARGB>, indices: &mut [u8]) {
+    let mut counts = vec![0_usize; colors.len()];
+
+    for index in indices.iter() {
+        if let Some(count) = counts.get_mut(*index as usize) {
+            *count += 1;
+        }
+    }
+
+    //The old index of each color, in the new order
+    let mut order: Vec<usize> = (0..colors.len()).collect();
+    order.sort_by_key(|old| core::cmp::Reverse(counts.get(*old).copied().unwrap_or_default()));
+
+    //The new index of each color, by its old index
+    let mut remap = vec![0_u8; colors.len()];
+
+    for (new, old) in order.iter().enumerate() {
+        if let Some(index) = remap.get_mut(*old) {
+            *index = new as u8;
+        }
+    }
+
+    *colors = order.iter()
+        .filter_map(|old| colors.get(*old).copied())
+        .collect();
+
+    for index in indices.iter_mut() {
+        *index = remap.get(*index as usize).copied().unwrap_or(*index);
+    }
+}
+
 ///
 /// Build a grid of pixels from an image in bmp format
 ///
 impl ConvertableFrom<Bitmap> for Image {
-    type Options = ();
-    type Error = String;
+    type Options = ImageConvertData;
+    type Error = ImageError;
 
-    fn try_convert_from(value: Bitmap, _: Self::Options) -> Result<Self, Self::Error> {
-        let width = value.info_header.width;
-        let height = value.info_header.height;
+    fn try_convert_from(value: Bitmap, options: Self::Options) -> Result<Self, Self::Error> {
+        let orientation = value.info_header.orientation();
 
-        let abs_width = width.unsigned_abs();
-        let abs_height = height.unsigned_abs();
+        let abs_width = value.info_header.width.unsigned_abs() as usize;
+        let abs_height = value.info_header.height.unsigned_abs() as usize;
 
         let mut pixels: Vec<color::ARGB> = Vec::new();
+        let mut out_of_range = 0_usize;
+        let mut missing = 0_usize;
 
         //For each row
-        for r in 0..abs_height {
-            //If height is negative, the image is mirrored horizontally
-            let j = match height {
-                h if h < 0 => (abs_height - 1) - r,
-                _ => r
-            };
-
+        for j in 0..abs_height {
             //For each column
-            for c in 0..abs_width {
-                //If width is negative, the image is mirrored vertically
-                let i = match width {
-                    w if w < 0 => (abs_width - 1) - c,
-                    _ => c
-                };
-
-                //The bitmap pixels are a flat array; calculate index based off of row and column
-                let index = (abs_width * (abs_height - j - 1) + i) as usize;
+            for i in 0..abs_width {
+                //The bitmap pixels are a flat array in the order they are stored; calculate index based off of row and column
+                let (column, row) = orientation.stored_position(i, j, abs_width, abs_height);
+                let index = abs_width * row + column;
 
                 //bpp = 1, 4 or 8: each value is an index in the color table
                 //otherwise, it is the color values of the pixel    
                 let color = match value.pixels.pixels {
-                    BitmapPixelData::Indices(ref indices) => value.color_table.colors[indices[index] as usize],
-                    BitmapPixelData::Colors(ref colors) => colors[index]
+                    BitmapPixelData::Indices(ref indices) => match indices.get(index) {
+                        Some(palette_index) => match (value.color_table_color(*palette_index as usize), options.palette_fallback) {
+                            (Some(color), _) => Some(color),
+                            (None, Some(fallback)) => {
+                                out_of_range += 1;
+                                Some(fallback)
+                            },
+                            (None, None) => return Err(ImageError::Decode(format!(
+                                "Pixel {i},{j} has palette index {palette_index}, but the color table only has {} colors.",
+                                value.color_table.colors.len()
+                            )))
+                        },
+                        None => None
+                    },
+                    BitmapPixelData::Colors(ref colors) => colors.get(index).copied()
+                };
+
+                //Pixel data shorter than the dimensions call for was already warned about when decoding
+                let color = color.unwrap_or_else(|| {
+                    missing += 1;
+                    color::ARGB::default()
+                });
+
+                //Pixels set in the mask are transparent, whatever their color
+                let color = match value.mask.as_ref().and_then(|mask| mask.get(index).copied()) {
+                    Some(true) => color::ARGB { alpha: 0, ..color },
+                    _ => color
                 };
 
                 //Add the pixel to the grid
@@ -850,6 +3153,14 @@ impl ConvertableFrom<Bitmap> for Image {
             }
         }
 
-        Ok(Image::new_pixels(abs_width as usize, abs_height as usize, pixels))
+        if missing > 0 {
+            log::warn(format_args!("Bitmap is missing {missing} pixels, which are left transparent."));
+        }
+
+        if out_of_range > 0 {
+            log::warn(format_args!("Bitmap has {out_of_range} pixels with palette indices outside of the color table, which were replaced with the fallback color."));
+        }
+
+        Ok(Image::try_new_pixels(abs_width, abs_height, pixels)?.with_metadata(ImageMetadata { icc_profile: value.icc_profile }))
     }
 }
@@ -2,6 +2,11 @@ use std::collections::HashSet;
 
 use super::*;
 
+///
+/// Seed of the generated test images, fixed so that any failure can be reproduced
+///
+const SEED: u64 = 0x5EED;
+
 ///
 /// Test whether 2 bitmaps are equivalent, even if the underlying data isn't exactly
 /// equal
@@ -47,8 +52,8 @@ fn test_equivalence(a: &Bitmap, b: &Bitmap) -> Result<(), String> {
     }
 
     if a.info_header.width != b.info_header.width {
-        if a.info_header.width == b.info_header.width.abs() {
-            //Reversed width indicates the columns are mirrored, so they might still be equivalent
+        if a.info_header.width.unsigned_abs() == b.info_header.width.unsigned_abs() {
+            //The sign of the width is part of the orientation, so they might still be equivalent
         } else {
             diffs.push(format!(
                 "Info Header: width mismatch ({}/{})!",
@@ -58,8 +63,8 @@ fn test_equivalence(a: &Bitmap, b: &Bitmap) -> Result<(), String> {
     }
 
     if a.info_header.height != b.info_header.height {
-        if a.info_header.height == b.info_header.height.abs() {
-            //Reversed height indicates the rows are mirrored, so they might still be equivalent
+        if a.info_header.height.unsigned_abs() == b.info_header.height.unsigned_abs() {
+            //The sign of the height is part of the orientation, so they might still be equivalent
         } else {
             diffs.push(format!(
                 "Info Header: height mismatch ({}/{})!",
@@ -90,32 +95,28 @@ fn test_equivalence(a: &Bitmap, b: &Bitmap) -> Result<(), String> {
     }
 
     if a.info_header.image_size != b.info_header.image_size {
-        diffs.push(format!(
-            "Info Header: image size mismatch ({}/{})!",
-            a.info_header.image_size, b.info_header.image_size
-        ));
-    }
-
-    if a.info_header.y_pixels_per_meter != b.info_header.y_pixels_per_meter {
-        if a.info_header.y_pixels_per_meter == b.info_header.y_pixels_per_meter.abs() {
-            //Reversed y-resolution indicates the columns are mirrored, so they might still be equivalent
+        if a.info_header.compression == 0 && (a.info_header.image_size == 0 || b.info_header.image_size == 0) {
+            //The image size of an uncompressed bitmap may be left 0, so they might still be equivalent
         } else {
             diffs.push(format!(
-                "Info Header: vertical resolution mismatch ({}/{})!",
-                a.info_header.y_pixels_per_meter, b.info_header.y_pixels_per_meter
+                "Info Header: image size mismatch ({}/{})!",
+                a.info_header.image_size, b.info_header.image_size
             ));
         }
     }
 
+    if a.info_header.y_pixels_per_meter != b.info_header.y_pixels_per_meter {
+        diffs.push(format!(
+            "Info Header: vertical resolution mismatch ({}/{})!",
+            a.info_header.y_pixels_per_meter, b.info_header.y_pixels_per_meter
+        ));
+    }
+
     if a.info_header.x_pixels_per_meter != b.info_header.x_pixels_per_meter {
-        if a.info_header.x_pixels_per_meter == b.info_header.x_pixels_per_meter.abs() {
-            //Reversed x-resolution indicates the columns are mirrored, so they might still be equivalent
-        } else {
-            diffs.push(format!(
-                "Info Header: horizontal resolution mismatch ({}/{})!",
-                a.info_header.x_pixels_per_meter, b.info_header.x_pixels_per_meter
-            ));
-        }
+        diffs.push(format!(
+            "Info Header: horizontal resolution mismatch ({}/{})!",
+            a.info_header.x_pixels_per_meter, b.info_header.x_pixels_per_meter
+        ));
     }
 
     if a.info_header.colors_used != b.info_header.colors_used {
@@ -172,9 +173,9 @@ fn test_equivalence(a: &Bitmap, b: &Bitmap) -> Result<(), String> {
         BitmapPixelData::Colors(a_pixels) => {
             match &b.pixels.pixels {
                 BitmapPixelData::Colors(b_pixels) => {
-                    //Get scanlines from a, reversing them if height xor vertical resolution is negative
+                    //Get scanlines from a, reversing them if they are stored top-down
                     let scanlines_a: Vec<&[color::ARGB]> =
-                        if (a.info_header.height < 0) ^ (a.info_header.y_pixels_per_meter < 0) {
+                        if a.info_header.orientation().is_top_down() {
                             a_pixels
                                 .chunks_exact(a.info_header.width.unsigned_abs() as usize)
                                 .rev()
@@ -185,9 +186,9 @@ fn test_equivalence(a: &Bitmap, b: &Bitmap) -> Result<(), String> {
                                 .collect()
                         };
 
-                    //Get scanlines from b, reversing them if height xor vertical resolution is negative
+                    //Get scanlines from b, reversing them if they are stored top-down
                     let scanlines_b: Vec<&[color::ARGB]> =
-                        if (b.info_header.height < 0) ^ (b.info_header.y_pixels_per_meter < 0) {
+                        if b.info_header.orientation().is_top_down() {
                             b_pixels
                                 .chunks_exact(b.info_header.width.unsigned_abs() as usize)
                                 .rev()
@@ -224,17 +225,17 @@ fn test_equivalence(a: &Bitmap, b: &Bitmap) -> Result<(), String> {
                         let scanline_a = scanline_a.unwrap();
                         let scanline_b = scanline_b.unwrap();
 
-                        //Get pixels from scanline_a, reversing them if width xor horizontal resolution is negative
+                        //Get pixels from scanline_a, reversing them if they are mirrored
                         let cols_a: Vec<color::ARGB> =
-                            if (a.info_header.width < 0) ^ (a.info_header.x_pixels_per_meter < 0) {
+                            if a.info_header.orientation().is_mirrored() {
                                 scanline_a.iter().rev().copied().collect()
                             } else {
                                 Vec::from(scanline_a)
                             };
 
-                        //Get pixels from scanline_b, reversing them if width xor horizontal resolution is negative
+                        //Get pixels from scanline_b, reversing them if they are mirrored
                         let cols_b: Vec<color::ARGB> =
-                            if (b.info_header.width < 0) ^ (b.info_header.x_pixels_per_meter < 0) {
+                            if b.info_header.orientation().is_mirrored() {
                                 scanline_b.iter().rev().copied().collect()
                             } else {
                                 Vec::from(scanline_b)
@@ -291,9 +292,9 @@ fn test_equivalence(a: &Bitmap, b: &Bitmap) -> Result<(), String> {
                     ));
                 }
                 BitmapPixelData::Indices(b_indices) => {
-                    //Get scanlines from a, reversing them if height xor vertical resolution is negative
+                    //Get scanlines from a, reversing them if they are stored top-down
                     let scanlines_a: Vec<&[u8]> =
-                        if (a.info_header.height < 0) ^ (a.info_header.y_pixels_per_meter < 0) {
+                        if a.info_header.orientation().is_top_down() {
                             a_indices
                                 .chunks_exact(a.info_header.width.unsigned_abs() as usize)
                                 .rev()
@@ -304,9 +305,9 @@ fn test_equivalence(a: &Bitmap, b: &Bitmap) -> Result<(), String> {
                                 .collect()
                         };
 
-                    //Get scanlines from b, reversing them if height xor vertical resolution is negative
+                    //Get scanlines from b, reversing them if they are stored top-down
                     let scanlines_b: Vec<&[u8]> =
-                        if (b.info_header.height < 0) ^ (b.info_header.y_pixels_per_meter < 0) {
+                        if b.info_header.orientation().is_top_down() {
                             b_indices
                                 .chunks_exact(b.info_header.width.unsigned_abs() as usize)
                                 .rev()
@@ -343,17 +344,17 @@ fn test_equivalence(a: &Bitmap, b: &Bitmap) -> Result<(), String> {
                         let scanline_a = scanline_a.unwrap();
                         let scanline_b = scanline_b.unwrap();
 
-                        //Get pixels from scanline_a, reversing them if width xor horizontal resolution is negative
+                        //Get pixels from scanline_a, reversing them if they are mirrored
                         let cols_a: Vec<u8> =
-                            if (a.info_header.width < 0) ^ (a.info_header.x_pixels_per_meter < 0) {
+                            if a.info_header.orientation().is_mirrored() {
                                 scanline_a.iter().rev().copied().collect()
                             } else {
                                 Vec::from(scanline_a)
                             };
 
-                        //Get pixels from scanline_b, reversing them if width xor horizontal resolution is negative
+                        //Get pixels from scanline_b, reversing them if they are mirrored
                         let cols_b: Vec<u8> =
-                            if (b.info_header.width < 0) ^ (b.info_header.x_pixels_per_meter < 0) {
+                            if b.info_header.orientation().is_mirrored() {
                                 scanline_b.iter().rev().copied().collect()
                             } else {
                                 Vec::from(scanline_b)
@@ -478,6 +479,9 @@ mod tests_24_1 {
                 y_pixels_per_meter: 0x0E_C4_i32,
                 colors_used: 0x00_u32,
                 important_colors: 0x00_u32,
+                bitfields: None,
+                v4: None,
+                v5: None,
             },
             color_table: BitmapColorTable { colors: Vec::new() },
             pixels: BitmapPixels {
@@ -579,7 +583,10 @@ mod tests_24_1 {
                         blue: 0xCC,
                     },
                 ]),
+                encoded: None,
             },
+            icc_profile: None,
+            mask: None,
         };
 
         let image = Image {
@@ -683,6 +690,7 @@ mod tests_24_1 {
                     blue: 0x00,
                 },
             ],
+            metadata: ImageMetadata::default(),
         };
 
         (input_bytes, bitmap, image)
@@ -699,6 +707,43 @@ mod tests_24_1 {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn bitmap_from_mapped_file() -> Result<(), String> {
+        let (input_bytes, expected, _) = input();
+
+        let path = std::env::temp_dir().join(format!("rs_image_mmap_{}.bmp", std::process::id()));
+        let path = path.to_string_lossy();
+
+        utility::file::write_file_bytes(&path, input_bytes).map_err(|err| err.to_string())?;
+        //Safety: the file is only removed once the bitmap is decoded
+        let bitmap = unsafe { Bitmap::from_path_mmap(&path) };
+        let _ = std::fs::remove_file(&*path);
+
+        super::test_equivalence(&bitmap?, &expected)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn bitmap_ref_from_bytes() -> Result<(), String> {
+        let (input_bytes, expected, _) = input();
+
+        let bitmap_ref = BitmapRef::try_from(input_bytes)?;
+
+        if !bitmap_ref.is_borrowed() {
+            return Err(String::from("Expected 24-bit pixel data to be borrowed."));
+        }
+
+        if bitmap_ref.get(bitmap_ref.pixel_count()).is_some() {
+            return Err(String::from("Expected no pixel past the end of the data."));
+        }
+
+        super::test_equivalence(&Bitmap::from(bitmap_ref), &expected)?;
+
+        Ok(())
+    }
+
     #[test]
     fn bytes_from_bitmap() -> Result<(), String> {
         let (expected, bitmap, _) = input();
@@ -718,7 +763,7 @@ mod tests_24_1 {
     fn image_from_bitmap() -> Result<(), String> {
         let (_, bitmap, expected) = input();
 
-        let bitmap_image = Image::try_convert_from(bitmap, ())?;
+        let bitmap_image = Image::try_convert_from(bitmap, ImageConvertData::default())?;
 
         if bitmap_image != expected {
             Err(String::from(
@@ -736,10 +781,10 @@ mod tests_24_1 {
         let image_bitmap = Bitmap::try_convert_from(
             image,
             BitmapConvertData {
-                bit_depth: 24,
+                bit_depth: BitDepth::Bits(24),
                 compression: 0,
-                x_pixels_per_meter: 3780,
-                y_pixels_per_meter: 3780,
+                resolution: Resolution::new(3780, 3780),
+                ..Default::default()
             },
         )?;
 
@@ -747,4 +792,1948 @@ mod tests_24_1 {
 
         Ok(())
     }
+
+    #[test]
+    fn bitstring_sections() -> Result<(), String> {
+        let (_, bitmap, _) = input();
+
+        let options = BitstringOptions {
+            offsets: true,
+            bytes_per_line: None,
+            sections: vec![BitmapSection::Pixels, BitmapSection::Header]
+        };
+
+        let text = bitmap.formatted_bitstring_with(&options);
+
+        let mut streamed: Vec<u8> = Vec::new();
+        bitmap.write_bitstring(&mut streamed, &options).map_err(|err| err.to_string())?;
+
+        if text.as_bytes() != streamed {
+            Err(String::from("Streamed bitstring does not match the formatted bitstring."))
+        } else if !text.starts_with("\n//Header\n00000000: 0x42, 0x4D,") {
+            Err(format!("Expected the header first, got {}.", text.lines().nth(1).unwrap_or_default()))
+        } else if !text.contains("\n\n//Pixel Data\n//Row 0\n00000036: ") || text.contains("//Color Table") {
+            Err(String::from("Expected only the header and pixel data sections."))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn section_ranges() -> Result<(), String> {
+        let (input_bytes, bitmap, _) = input();
+
+        let (header_start, header_end) = bitmap.section_range(BitmapSection::Header);
+        let (_, pixels_end) = bitmap.section_range(BitmapSection::Pixels);
+
+        if (header_start, header_end) != (0, 54) {
+            Err(format!("Expected the headers to span bytes 0 - 54, got {header_start} - {header_end}."))
+        } else if pixels_end != input_bytes.len() {
+            Err(format!("Expected the pixel data to end at byte {}, got {pixels_end}.", input_bytes.len()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+mod tests_region {
+    use super::super::*;
+    use crate::image::*;
+
+    ///
+    /// An image with few enough colors to be stored with any bit depth,
+    /// and with an odd width so rows end partway through a byte
+    ///
+    fn input() -> Image {
+        let colors = [
+            color::ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x00 },
+            color::ARGB { alpha: 0xFF, red: 0x00, green: 0x80, blue: 0xFF }
+        ];
+
+        Image::new_pixels(7, 5, (0..35).map(|n| colors[(n * n / 3) % 2]).collect())
+    }
+
+    #[test]
+    fn region_matches_crop() -> Result<(), String> {
+        let image = input();
+
+        //Compare against the full decode rather than the input, so only the region logic is under test
+
+        for bit_depth in [1, 4, 8, 24, 32] {
+            let bitmap = Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Bits(bit_depth),
+                ..Default::default()
+            })?;
+
+            let bytes = Vec::try_from(bitmap)?;
+            let decoded = Image::try_convert_from(Bitmap::try_from(&bytes[..])?, ImageConvertData::default())?;
+
+            for (x, y, width, height) in [(0, 0, 7, 5), (1, 2, 3, 2), (6, 4, 1, 1), (2, 0, 5, 5)] {
+                let region = Bitmap::decode_region(&bytes, x, y, width, height, DecodeOptions::default())?;
+
+                if region != decoded.crop(x, y, width, height) {
+                    return Err(format!("Region {x},{y},{width}x{height} of the {bit_depth}-bit bitmap does not match the cropped full decode."));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn region_out_of_bounds() -> Result<(), String> {
+        let bytes = Vec::try_from(Bitmap::try_convert_from(input(), BitmapConvertData {
+            bit_depth: BitDepth::Bits(24),
+            ..Default::default()
+        })?)?;
+
+        //Past the right edge, and with coordinates so large the edges overflow
+        for (x, y) in [(5, 0), (usize::MAX, 0), (0, usize::MAX)] {
+            match Bitmap::decode_region(&bytes, x, y, 3, 1, DecodeOptions::default()) {
+                Err(crate::error::ImageError::InvalidArgument(_)) => (),
+                Err(err) => return Err(format!("Expected an invalid argument error, but got {err:?}.")),
+                Ok(_) => return Err(format!("Expected the region at {x},{y} to be rejected."))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+mod tests_validation {
+    use super::super::*;
+    use crate::error::ImageError;
+    use crate::image::*;
+
+    fn valid(bit_depth: u16) -> Result<Vec<u8>, String> {
+        let image = Image::new_pixels(2, 2, vec![color::ARGB { alpha: 0xFF, red: 0x12, green: 0x34, blue: 0x56 }; 4]);
+
+        Ok(Vec::try_from(Bitmap::try_convert_from(image, BitmapConvertData {
+            bit_depth: BitDepth::Bits(bit_depth),
+            ..Default::default()
+        })?)?)
+    }
+
+    ///
+    /// Insert bytes at the offset of a bmp, moving the pixel data and growing the file to match
+    ///
+    fn insert_bytes(bytes: &[u8], offset: usize, inserted: &[u8]) -> Vec<u8> {
+        let mut grown = bytes.to_vec();
+        grown.splice(offset..offset, inserted.iter().copied());
+
+        let file_size = grown.len() as u32;
+        let data_offset = u32::from_le_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]) + inserted.len() as u32;
+
+        grown[2..6].copy_from_slice(&file_size.to_le_bytes());
+        grown[10..14].copy_from_slice(&data_offset.to_le_bytes());
+        grown
+    }
+
+    #[test]
+    fn palette_limits() -> Result<(), String> {
+        let bytes = valid(8)?;
+        let expected = Image::try_convert_from(Bitmap::try_from(&bytes[..])?, ImageConvertData::default())?;
+
+        //Gap bytes between the color table and the pixel data are skipped, which the format allows
+        let gap = insert_bytes(&bytes, 58, &[0xAB; 6]);
+        let (bitmap, report) = Bitmap::decode_with_report(&gap, DecodeOptions { strict: true, ..Default::default() })?;
+
+        if bitmap.color_table.colors.len() != 1 || !report.warnings.iter().any(|warning| warning.contains("6 unused bytes")) {
+            return Err(format!("Expected 1 color and 6 unused bytes, but got {} colors and {:?}.", bitmap.color_table.colors.len(), report.warnings));
+        }
+
+        //A colors used of 0 calls for a whole 8-bit palette, which doesn't fit
+        let mut short = bytes.clone();
+        short[46..50].copy_from_slice(&0_u32.to_le_bytes());
+
+        let (bitmap, report) = Bitmap::decode_with_report(&short, DecodeOptions::default())?;
+
+        if bitmap.color_table.colors.len() != 1 || !report.warnings.iter().any(|warning| warning.contains("256 colors")) {
+            return Err(format!("Expected the palette to be cut to 1 color, but got {} colors and {:?}.", bitmap.color_table.colors.len(), report.warnings));
+        }
+
+        if Bitmap::decode(&short, DecodeOptions { strict: true, ..Default::default() }).is_ok() {
+            return Err(String::from("Expected a palette which doesn't fit to be an error in strict mode."));
+        }
+
+        //The color table follows an info header longer than the fields which are read
+        let mut long_header = insert_bytes(&bytes, 54, &[0; 16]);
+        long_header[14..18].copy_from_slice(&56_u32.to_le_bytes());
+
+        let decoded = Image::try_convert_from(Bitmap::decode(&long_header, DecodeOptions { strict: true, ..Default::default() })?, ImageConvertData::default())?;
+
+        if decoded != expected {
+            Err(String::from("Expected the palette after a long info header to be read."))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn invalid_headers() -> Result<(), String> {
+        //Byte offset of the field, its new little endian value, and what the error should name
+        let cases: [(usize, &[u8], &str); 7] = [
+            (0, b"MB", "signature"),
+            (10, &[0xFF, 0xFF, 0, 0], "data offset"),
+            (14, &[12, 0, 0, 0], "info header size"),
+            (26, &[2, 0], "planes"),
+            (28, &[7, 0], "bit depth"),
+            (30, &[9, 0, 0, 0], "compression"),
+            (30, &[1, 0, 0, 0], "bit depth")
+        ];
+
+        let bytes = valid(24)?;
+
+        for (offset, value, field) in cases {
+            let mut malformed = bytes.clone();
+            malformed[offset..offset + value.len()].copy_from_slice(value);
+
+            match Bitmap::try_from(&malformed[..]) {
+                Err(ImageError::Decode(message)) if message.contains(field) => (),
+                other => return Err(format!("Expected an error naming the {field}, but got {:?}.", other.map(|_| ())))
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_palette() -> Result<(), String> {
+        let mut bytes = valid(8)?;
+
+        //Colors used
+        bytes[46..50].copy_from_slice(&[0, 1, 1, 0]);
+
+        match Bitmap::try_from(&bytes[..]) {
+            Err(ImageError::Decode(message)) if message.contains("colors used") => Ok(()),
+            other => Err(format!("Expected an error naming the colors used, but got {:?}.", other.map(|_| ())))
+        }
+    }
+
+    #[test]
+    fn palette_index_out_of_range() -> Result<(), String> {
+        let mut bytes = valid(8)?;
+
+        //The first pixel indexes past the single color in the table
+        let data_offset = u32::from_le_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]) as usize;
+        bytes[data_offset] = 5;
+
+        let bitmap = Bitmap::try_from(&bytes[..])?;
+
+        match Image::try_convert_from(bitmap.clone(), ImageConvertData::default()) {
+            Err(ImageError::Decode(message)) if message.contains("palette index 5") => (),
+            other => return Err(format!("Expected an error naming the palette index, but got {:?}.", other.map(|_| ())))
+        }
+
+        let fallback = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0xFF };
+
+        let image = Image::try_convert_from(bitmap, ImageConvertData {
+            palette_fallback: Some(fallback)
+        })?;
+
+        //Rows are stored bottom-up, so the first pixel stored is the bottom left
+        if image.get(0, 1) != Some(fallback) || image.get(1, 1) == Some(fallback) {
+            return Err(String::from("Expected only the out of range pixel to use the fallback color."));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn degenerate_dimensions() -> Result<(), String> {
+        let bytes = valid(32)?;
+
+        //Zero width, which used to read empty scanlines forever
+        let mut empty = bytes.clone();
+        empty[18..22].copy_from_slice(&[0; 4]);
+
+        let image = Image::try_convert_from(Bitmap::try_from(&empty[..])?, ImageConvertData::default())?;
+        let _ = Vec::try_from(Bitmap::try_from(&empty[..])?)?;
+
+        if image.length() != 0 {
+            return Err(format!("Expected an empty image, but it had {} pixels.", image.length()));
+        }
+
+        //Far more pixels than could be decoded
+        let mut huge = bytes.clone();
+        huge[18..26].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0x00, 0xFF, 0xFF, 0xFF, 0x00]);
+
+        match Bitmap::try_from(&huge[..]) {
+            Err(ImageError::Decode(message)) if message.contains("dimensions") => Ok(()),
+            other => Err(format!("Expected an error naming the dimensions, but got {:?}.", other.map(|_| ())))
+        }
+    }
+
+    #[test]
+    fn missing_pixels() -> Result<(), String> {
+        let bytes = valid(32)?;
+
+        //Only the first stored row, which is the bottom row of the image
+        let truncated = &bytes[..bytes.len() - 8];
+        let image = Image::try_convert_from(Bitmap::try_from(truncated)?, ImageConvertData::default())?;
+
+        if image.get(0, 0) != Some(color::ARGB::default()) || image.get(0, 1) == Some(color::ARGB::default()) {
+            return Err(String::from("Expected only the missing row to be left transparent."));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn dimensions_beyond_pixel_data() -> Result<(), String> {
+        //7x16777221 pixels, within the most allowed, but with only the pixel data for 2x2
+        let mut bytes = valid(32)?;
+        bytes[18..26].copy_from_slice(&[7, 0, 0, 0, 5, 0, 0, 1]);
+
+        match Bitmap::try_from(&bytes[..]) {
+            Err(ImageError::Decode(message)) if message.contains("pixel data") => (),
+            other => return Err(format!("Expected an error naming the pixel data, but got {:?}.", other.map(|_| ())))
+        }
+
+        match Bitmap::from_reader(&bytes[..], DecodeOptions::default()) {
+            Err(ImageError::Decode(message)) if message.contains("pixel data") => Ok(()),
+            other => Err(format!("Expected reading to fail naming the pixel data, but got {:?}.", other.map(|_| ())))
+        }
+    }
+
+    #[test]
+    fn truncated_headers() -> Result<(), String> {
+        let bytes = valid(24)?;
+
+        match Bitmap::try_from(&bytes[..20]) {
+            Err(ImageError::Decode(_)) => Ok(()),
+            other => Err(format!("Expected truncated headers to be rejected, but got {:?}.", other.map(|_| ())))
+        }
+    }
+}
+
+mod tests_generated {
+    use super::super::*;
+    use crate::image::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    ///
+    /// Encode the image with the given bit depth, and check that decoding it gives back the same image
+    ///
+    fn round_trip(image: &Image, bit_depth: u16) -> Result<(), String> {
+        let bitmap = Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+            bit_depth: BitDepth::Bits(bit_depth),
+            ..Default::default()
+        })?;
+
+        let bytes = Vec::try_from(bitmap)?;
+        let decoded = Image::try_convert_from(Bitmap::try_from(bytes.as_slice())?, ImageConvertData::default())?;
+
+        if decoded != *image {
+            Err(format!("{}-bit round trip of a {}x{} image does not match.", bit_depth, image.width(), image.height()))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn generated_round_trip_32() -> Result<(), String> {
+        ImageGenerator::new(32).images(40, 256).iter()
+            .try_for_each(|image| round_trip(image, 32))
+    }
+
+    #[test]
+    fn generated_round_trip_24() -> Result<(), String> {
+        ImageGenerator::new(24).opaque(true).images(40, 256).iter()
+            .try_for_each(|image| round_trip(image, 24))
+    }
+
+    #[test]
+    fn generated_round_trip_8() -> Result<(), String> {
+        let mut generator = ImageGenerator::new(8);
+        let mut images = generator.images(0, 0);
+
+        for colors in [1, 2, 16, 255, 256] {
+            let (width, height) = generator.size();
+            images.push(generator.image_with(width, height, Fill::Palette(colors)));
+        }
+
+        images.iter().try_for_each(|image| round_trip(image, 8))
+    }
+
+    #[test]
+    fn encoded_sizes_match() -> Result<(), String> {
+        let mut generator = ImageGenerator::new(super::SEED).opaque(true);
+
+        for (bit_depth, colors) in [(1, 2), (4, 16), (8, 256), (24, 256), (32, 256)] {
+            for width in [1, 3, 6, 13] {
+                let image = generator.image_with(width, 3, Fill::Palette(colors));
+
+                let bitmap = Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                    bit_depth: BitDepth::Bits(bit_depth),
+                    ..Default::default()
+                })?;
+
+                let (file_size, image_size, data_offset) = (bitmap.header.file_size as usize, bitmap.info_header.image_size as usize, bitmap.header.data_offset as usize);
+                let bytes = Vec::try_from(bitmap)?;
+
+                if file_size != bytes.len() || image_size != bytes.len() - data_offset {
+                    return Err(format!("{bit_depth}-bit bitmap {width} pixels wide is {} bytes with {} of pixels, but declares a file size of {file_size} and an image size of {image_size}.",
+                        bytes.len(), bytes.len() - data_offset));
+                }
+
+                //Strict decoding checks the sizes too
+                let decoded = Image::try_convert_from(Bitmap::decode(&bytes, DecodeOptions { strict: true, ..Default::default() })?, ImageConvertData::default())?;
+
+                if decoded != image {
+                    return Err(format!("{bit_depth}-bit round trip of a {width}x3 image does not match."));
+                }
+
+                if !Bitmap::check_conformance(&bytes).passed() {
+                    return Err(format!("{bit_depth}-bit bitmap {width} pixels wide does not conform."));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn encoded_rows_in_order() -> Result<(), String> {
+        //Odd width, so every row is padded, and enough rows to be split across workers
+        let (width, height) = (5, 300);
+
+        let pixels = (0..width * height)
+            .map(|i| color::ARGB { alpha: 0xFF, red: (i / width) as u8, green: ((i / width) >> 8) as u8, blue: (i % width) as u8 })
+            .collect();
+
+        let image = Image::new_pixels(width, height, pixels);
+
+        let bitmap = Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+            bit_depth: BitDepth::Bits(24),
+            ..Default::default()
+        })?;
+
+        let data_offset = bitmap.header.data_offset as usize;
+        let bytes = Vec::try_from(bitmap)?;
+        let row_size = 16;
+
+        if bytes.len() != data_offset + row_size * height {
+            return Err(format!("Expected {} bytes, got {}.", data_offset + row_size * height, bytes.len()));
+        }
+
+        //Rows are stored bottom-up
+        for (j, row) in bytes[data_offset..].chunks_exact(row_size).enumerate() {
+            let expected: Vec<u8> = image.row(height - 1 - j).iter()
+                .flat_map(|pixel| [pixel.blue, pixel.green, pixel.red])
+                .chain([0_u8])
+                .collect();
+
+            if row != expected.as_slice() {
+                return Err(format!("Stored row {j} is {row:?}, expected {expected:?}."));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn indexed_rows_stored_bottom_up() -> Result<(), String> {
+        let (top, bottom) = (color::ARGB { alpha: 0xFF, red: 0xFF, green: 0, blue: 0 }, color::ARGB { alpha: 0xFF, red: 0, green: 0, blue: 0xFF });
+        let image = Image::new_pixels(1, 2, vec![top, bottom]);
+
+        for bit_depth in [1, 4, 8] {
+            let bitmap = Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Bits(bit_depth),
+                ..Default::default()
+            })?;
+
+            //The first row stored is the bottom row, so indexes the bottom color
+            let stored = match &bitmap.pixels.pixels {
+                BitmapPixelData::Indices(indices) => indices.first().and_then(|index| bitmap.color_table.colors.get(*index as usize)).copied(),
+                BitmapPixelData::Colors(_) => None
+            };
+
+            if stored != Some(bottom) {
+                return Err(format!("{bit_depth}-bit bitmap stores {stored:?} first, expected the bottom row's {bottom:?}."));
+            }
+
+            round_trip(&image, bit_depth)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty_indexed_round_trip() -> Result<(), String> {
+        //No pixels means no colors for the color table, which is fine when there is nothing to index
+        for (width, height) in [(0, 0), (3, 0), (0, 3)] {
+            round_trip(&Image::new(width, height), 8)?;
+        }
+
+        Ok(())
+    }
 }
+
+mod tests_strict {
+    use super::super::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    const STRICT: DecodeOptions = DecodeOptions { strict: true, and_mask: false };
+
+    ///
+    /// A 24-bit bitmap of a random opaque 5x3 image
+    ///
+    fn valid() -> Result<Vec<u8>, String> {
+        let image = ImageGenerator::new(super::SEED).opaque(true).image_with(5, 3, Fill::Noise);
+
+        Ok(Vec::try_from(Bitmap::try_convert_from(image, BitmapConvertData {
+            bit_depth: BitDepth::Bits(24),
+            ..Default::default()
+        })?)?)
+    }
+
+    ///
+    /// Check that the lenient decoder accepts the bytes and reports a warning containing the expected
+    /// text, and that the strict decoder rejects them with an error containing it
+    ///
+    fn rejected_only_when_strict(bytes: &[u8], expected: &str) -> Result<(), String> {
+        let (_, report) = Bitmap::decode_with_report(bytes, DecodeOptions::default())?;
+
+        if !report.warnings.iter().any(|warning| warning.contains(expected)) {
+            return Err(format!("Expected a warning containing '{expected}', but got {:?}.", report.warnings));
+        }
+
+        match Bitmap::decode(bytes, STRICT) {
+            Err(ImageError::Decode(message)) if message.contains(expected) => Ok(()),
+            other => Err(format!("Expected a strict error containing '{expected}', but got {:?}.", other.map(|_| ())))
+        }
+    }
+
+    #[test]
+    fn strict_accepts_valid() -> Result<(), String> {
+        for (bit_depth, opaque) in [(8, false), (24, true), (32, false)] {
+            for image in ImageGenerator::new(bit_depth as u64).opaque(opaque).images(10, 256) {
+                //Only images with few enough colors can be indexed
+                let colors: std::collections::HashSet<color::ARGB> = image.iter().flatten().copied().collect();
+
+                if bit_depth == 8 && colors.len() > 256 {
+                    continue;
+                }
+
+                let bytes = Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                    bit_depth: BitDepth::Bits(bit_depth),
+                    ..Default::default()
+                })?)?;
+
+                let decoded = Image::try_convert_from(Bitmap::decode(&bytes, STRICT)?, ImageConvertData::default())?;
+
+                if decoded != image {
+                    return Err(format!("Strict {bit_depth}-bit decode of a {}x{} image does not match.", image.width(), image.height()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn valid_report_is_clean() -> Result<(), String> {
+        let bytes = valid()?;
+        let (_, report) = Bitmap::decode_with_report(&bytes, DecodeOptions::default())?;
+        let (_, borrowed_report) = BitmapRef::decode_with_report(&bytes, DecodeOptions::default())?;
+
+        if !report.is_clean() || !borrowed_report.is_clean() {
+            Err(format!("Expected no warnings, but got {:?} and {:?}.", report.warnings, borrowed_report.warnings))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn wrong_file_size() -> Result<(), String> {
+        let mut bytes = valid()?;
+        bytes[2..6].copy_from_slice(&1000_u32.to_le_bytes());
+
+        rejected_only_when_strict(&bytes, "file size")
+    }
+
+    #[test]
+    fn wrong_colors_used() -> Result<(), String> {
+        let mut bytes = valid()?;
+        bytes[46..50].copy_from_slice(&2_u32.to_le_bytes());
+
+        rejected_only_when_strict(&bytes, "room for")
+    }
+
+    #[test]
+    fn short_scanlines() -> Result<(), String> {
+        let mut bytes = valid()?;
+        bytes.truncate(bytes.len() - 6);
+
+        //The declared file size is fixed, so that only the scanline is wrong
+        let file_size = bytes.len() as u32;
+        bytes[2..6].copy_from_slice(&file_size.to_le_bytes());
+
+        rejected_only_when_strict(&bytes, "truncated")?;
+
+        match Bitmap::decode_region(&bytes, 0, 0, 5, 1, STRICT) {
+            Err(ImageError::Decode(message)) if message.contains("missing") => Ok(()),
+            other => Err(format!("Expected a strict region decode to report missing pixels, but got {:?}.", other.map(|_| ())))
+        }
+    }
+
+    #[test]
+    fn prefixes_are_errors_or_images() -> Result<(), String> {
+        let mut generator = ImageGenerator::new(super::SEED).max_size(6);
+
+        for bit_depth in [8, 24, 32] {
+            let image = generator.image_with(5, 3, Fill::Palette(4));
+
+            let bytes = Vec::try_from(Bitmap::try_convert_from(image, BitmapConvertData {
+                bit_depth: BitDepth::Bits(bit_depth),
+                ..Default::default()
+            })?)?;
+
+            //Every decoder must return, rather than panic, however much of the file is missing
+            for length in 0..bytes.len() {
+                let prefix = &bytes[..length];
+
+                if let Ok(bitmap) = Bitmap::decode(prefix, DecodeOptions::default()) {
+                    Image::try_convert_from(bitmap, ImageConvertData::default())?;
+                }
+
+                let _ = Bitmap::decode(prefix, DecodeOptions { strict: true, ..Default::default() });
+                let _ = BitmapRef::try_from(prefix);
+                let _ = Bitmap::decode_region(prefix, 1, 1, 3, 2, DecodeOptions::default());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+mod tests_orientation {
+    use super::super::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    #[test]
+    fn stored_orientations_decode_the_same() -> Result<(), String> {
+        let image = ImageGenerator::new(super::SEED).image_with(5, 3, Fill::Noise);
+
+        let bottom_up = Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+            bit_depth: BitDepth::Bits(32),
+            ..Default::default()
+        })?)?;
+
+        let (headers, _) = bottom_up.split_at(54);
+
+        for orientation in [Orientation::TopDown, Orientation::BottomUp, Orientation::TopDownMirrored, Orientation::BottomUpMirrored] {
+            //Store the pixels in the orientation, and sign the dimensions to match
+            let mut bytes = headers.to_vec();
+            let width = if orientation.is_mirrored() { -5_i32 } else { 5 };
+            let height = if orientation.is_top_down() { -3_i32 } else { 3 };
+
+            bytes[18..22].copy_from_slice(&width.to_le_bytes());
+            bytes[22..26].copy_from_slice(&height.to_le_bytes());
+            bytes.extend(image.reoriented(Orientation::TopDown, orientation).iter().flatten().flat_map(|pixel| pixel.as_u32(false).to_le_bytes()));
+
+            let decoded = Image::try_convert_from(Bitmap::try_from(bytes.as_slice())?, ImageConvertData::default())?;
+            let region = Bitmap::decode_region(&bytes, 1, 1, 3, 2, DecodeOptions::default())?;
+
+            if decoded != image {
+                return Err(format!("{} bitmap does not decode to the image.", orientation.name()));
+            }
+
+            if region != image.crop(1, 1, 3, 2) {
+                return Err(format!("Region of {} bitmap does not match the image.", orientation.name()));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn top_down_write() -> Result<(), String> {
+        let image = ImageGenerator::new(super::SEED).opaque(true).image_with(5, 3, Fill::Palette(4));
+
+        for bit_depth in [4, 8, 24, 32] {
+            let encode = |top_down| Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Bits(bit_depth),
+                top_down,
+                ..Default::default()
+            });
+
+            let (top_down, bottom_up) = (encode(true)?, encode(false)?);
+
+            if top_down.info_header.height != -3 || !top_down.info_header.orientation().is_top_down() {
+                return Err(format!("Top-down {bit_depth}-bit bitmap has a height of {}.", top_down.info_header.height));
+            }
+
+            //The same rows, in the opposite order. The color table is built in the order rows are
+            //stored, so indexed rows are compared by the colors their indices stand for.
+            let stored_colors = |bitmap: &Bitmap| -> Vec<color::ARGB> {
+                match &bitmap.pixels.pixels {
+                    BitmapPixelData::Indices(indices) => indices.iter()
+                        .map(|index| bitmap.color_table_color(*index as usize).unwrap_or_default())
+                        .collect(),
+                    BitmapPixelData::Colors(colors) => colors.clone()
+                }
+            };
+
+            let reversed: Vec<color::ARGB> = stored_colors(&bottom_up)
+                .chunks_exact(5)
+                .rev()
+                .flatten()
+                .copied()
+                .collect();
+
+            if stored_colors(&top_down) != reversed {
+                return Err(format!("Top-down {bit_depth}-bit rows are not the bottom-up rows reversed."));
+            }
+
+            let bytes = Vec::try_from(top_down.clone())?;
+
+            if !BitmapConvertData::from(&top_down).top_down {
+                return Err(format!("Top-down {bit_depth}-bit bitmap does not convert back to top-down options."));
+            }
+
+            let decoded = Image::try_convert_from(Bitmap::try_from(bytes.as_slice())?, ImageConvertData::default())?;
+
+            if decoded != image {
+                return Err(format!("Top-down {bit_depth}-bit bitmap does not decode to the image."));
+            }
+        }
+
+        match Bitmap::try_convert_from(image, BitmapConvertData { bit_depth: BitDepth::Bits(8), compression: bitmap::compression::BI_RLE8, top_down: true, ..Default::default() }) {
+            Err(ImageError::InvalidArgument(_)) => Ok(()),
+            other => Err(format!("Expected a top-down RLE8 bitmap to be rejected, got {other:?}."))
+        }
+    }
+}
+
+mod tests_progressive {
+    use super::super::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    ///
+    /// A reader which hands out at most a few bytes at a time, like a slow stream
+    ///
+    struct Trickle<'a> {
+        bytes: &'a [u8],
+        step: usize
+    }
+
+    impl io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let count = self.step.min(buf.len()).min(self.bytes.len());
+            let (read, rest) = self.bytes.split_at(count);
+
+            buf[..count].copy_from_slice(read);
+            self.bytes = rest;
+
+            Ok(count)
+        }
+    }
+
+    ///
+    /// The result of a progressive decode, the rows reported by each
+    /// call, and the image as of the last call
+    ///
+    struct Progressive {
+        result: Result<Bitmap, ImageError>,
+        calls: Vec<core::ops::Range<usize>>,
+        last: Option<Image>
+    }
+
+    fn decode(bytes: &[u8], step: usize) -> Progressive {
+        let mut calls = Vec::new();
+        let mut last = None;
+
+        let result = Bitmap::decode_progressive(Trickle { bytes, step }, DecodeOptions::default(), |image, rows| {
+            calls.push(rows);
+            last = Some(image.clone());
+        });
+
+        Progressive { result, calls, last }
+    }
+
+    #[test]
+    fn progressive_reports_every_row() -> Result<(), String> {
+        let image = ImageGenerator::new(super::SEED).image_with(7, 9, Fill::Noise);
+
+        for bit_depth in [8, 24, 32] {
+            let bytes = Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Bits(bit_depth),
+                ..Default::default()
+            })?)?;
+
+            let Progressive { result, calls, last } = decode(&bytes, 5);
+            let bitmap = result?;
+
+            if bitmap != Bitmap::try_from(bytes.as_slice())? {
+                return Err(format!("{bit_depth}-bit progressive decode differs from decoding all at once."));
+            }
+
+            //Bottom-up, so each call reports the rows just above the last
+            let mut next = image.height();
+
+            for rows in &calls {
+                if rows.end != next || rows.is_empty() {
+                    return Err(format!("{bit_depth}-bit rows reported out of order: {calls:?}."));
+                }
+
+                next = rows.start;
+            }
+
+            if next != 0 || calls.len() < 2 {
+                return Err(format!("{bit_depth}-bit rows were not all reported as they arrived: {calls:?}."));
+            }
+
+            if last.as_ref() != Some(&Image::try_convert_from(bitmap, ImageConvertData::default())?) {
+                return Err(format!("{bit_depth}-bit preview does not match the decoded image."));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn progressive_unsupported_decodes_at_end() -> Result<(), String> {
+        let mut bytes = Vec::try_from(Bitmap::try_convert_from(ImageGenerator::new(super::SEED).image_with(4, 4, Fill::Palette(4)), BitmapConvertData {
+            bit_depth: BitDepth::Bits(8),
+            ..Default::default()
+        })?)?;
+
+        //Claim RLE8, which isn't decoded as it arrives
+        bytes[30..34].copy_from_slice(&bitmap::compression::BI_RLE8.to_le_bytes());
+
+        let progressive = decode(&bytes, 3);
+
+        if !progressive.calls.is_empty() {
+            return Err(format!("Expected no rows before the end, got {:?}.", progressive.calls));
+        }
+
+        if progressive.result.ok() != Bitmap::try_from(bytes.as_slice()).ok() {
+            return Err(String::from("Progressive decode differs from decoding all at once."));
+        }
+
+        //Garbage fails just as it would all at once
+        if decode(b"not a bitmap at all", 4).result.is_ok() {
+            return Err(String::from("Expected garbage to fail to decode."));
+        }
+
+        Ok(())
+    }
+}
+
+mod tests_strip {
+    use super::super::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    #[test]
+    fn strip_matches_clean_encoding() -> Result<(), String> {
+        let image = ImageGenerator::new(super::SEED).image_with(5, 3, Fill::Noise);
+
+        for bit_depth in [8, 24, 32] {
+            let clean = Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Bits(bit_depth),
+                ..Default::default()
+            })?)?;
+
+            //Fill the reserved field and resolution, lengthen the info header, and add trailing bytes
+            let mut dirty = clean.clone();
+            dirty[6..10].copy_from_slice(&0xDEADBEEF_u32.to_le_bytes());
+            dirty[38..46].copy_from_slice(&[0x13, 0x0B, 0, 0, 0x13, 0x0B, 0, 0]);
+            dirty[50..54].copy_from_slice(&1_u32.to_le_bytes());
+            dirty.splice(54..54, [0xAB_u8; 16]);
+            dirty[14..18].copy_from_slice(&56_u32.to_le_bytes());
+
+            let data_offset = u32::from_le_bytes([dirty[10], dirty[11], dirty[12], dirty[13]]) + 16;
+            dirty[10..14].copy_from_slice(&data_offset.to_le_bytes());
+            dirty.extend_from_slice(b"trailing");
+
+            let stripped = Bitmap::try_from(dirty.as_slice())?.strip_metadata()?;
+
+            if Vec::try_from(stripped.clone())? != clean {
+                return Err(format!("Stripped {bit_depth}-bit bitmap does not match the clean encoding."));
+            }
+
+            let expected = Image::try_convert_from(Bitmap::try_from(clean.as_slice())?, ImageConvertData::default())?;
+
+            if Image::try_convert_from(stripped, ImageConvertData::default())? != expected {
+                return Err(format!("Stripped {bit_depth}-bit bitmap does not decode to the clean image."));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+mod tests_conformance {
+    use super::super::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    fn encode(bit_depth: u16) -> Result<Vec<u8>, String> {
+        let image = ImageGenerator::new(super::SEED).image_with(5, 3, Fill::Noise);
+
+        Ok(Vec::try_from(Bitmap::try_convert_from(image, BitmapConvertData {
+            bit_depth: BitDepth::Bits(bit_depth),
+            ..Default::default()
+        })?)?)
+    }
+
+    fn expect(report: &ConformanceReport, severity: Severity, expected: &str) -> Result<(), String> {
+        if report.findings.iter().any(|finding| finding.severity == severity && finding.message.contains(expected)) {
+            Ok(())
+        }
+        else {
+            Err(format!("Expected {} containing '{expected}', but got {:?}.", severity.name(), report.findings))
+        }
+    }
+
+    #[test]
+    fn clean_bitmap_passes() -> Result<(), String> {
+        for bit_depth in [8, 24, 32] {
+            let report = Bitmap::check_conformance(&encode(bit_depth)?);
+
+            if !report.passed() || !report.findings.is_empty() {
+                return Err(format!("Expected a clean {bit_depth}-bit bitmap to pass, but got {:?}.", report.findings));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn structural_warnings() -> Result<(), String> {
+        let mut bytes = encode(24)?;
+
+        //Set the reserved field and the padding of the first row, and add trailing bytes counted in the file size
+        bytes[6..10].copy_from_slice(&1_u32.to_le_bytes());
+        bytes[54 + 15] = 0xFF;
+        bytes.extend_from_slice(&[0_u8; 5]);
+
+        let file_size = bytes.len() as u32;
+        bytes[2..6].copy_from_slice(&file_size.to_le_bytes());
+
+        let report = Bitmap::check_conformance(&bytes);
+
+        if !report.passed() {
+            return Err(format!("Expected only warnings, but got {:?}.", report.findings));
+        }
+
+        expect(&report, Severity::Warning, "reserved field")?;
+        expect(&report, Severity::Warning, "1 rows with nonzero padding")?;
+        expect(&report, Severity::Warning, "5 bytes after the pixel data")
+    }
+
+    #[test]
+    fn violations_fail() -> Result<(), String> {
+        //Wrong file size and image size
+        let mut bytes = encode(24)?;
+        bytes[2..6].copy_from_slice(&1_u32.to_le_bytes());
+        bytes[34..38].copy_from_slice(&7_u32.to_le_bytes());
+
+        let report = Bitmap::check_conformance(&bytes);
+
+        if report.passed() {
+            return Err(String::from("Expected a bitmap with wrong sizes to fail."));
+        }
+
+        expect(&report, Severity::Error, "file size")?;
+        expect(&report, Severity::Error, "image size of 7 bytes")?;
+
+        //A pixel indexing past the color table
+        let mut bytes = encode(8)?;
+        let data_offset = u32::from_le_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]) as usize;
+        bytes[data_offset] = 0xFF;
+
+        expect(&Bitmap::check_conformance(&bytes), Severity::Error, "1 pixels which index past")?;
+
+        //Not a bitmap at all
+        expect(&Bitmap::check_conformance(b"not a bitmap"), Severity::Error, "too short")
+    }
+}
+
+mod tests_rle {
+    use super::super::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    //The headers and color table of a bmp with the given dimensions and compression, followed by the data
+    fn compressed(width: i32, height: i32, compression: u32, data: &[u8]) -> Result<Vec<u8>, String> {
+        let bytes = Vec::try_from(Bitmap::try_convert_from(ImageGenerator::new(super::SEED).image_with(4, 4, Fill::Palette(4)), BitmapConvertData {
+            bit_depth: BitDepth::Bits(8),
+            ..Default::default()
+        })?)?;
+
+        let data_offset = u32::from_le_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]) as usize;
+        let bit_depth: u16 = if compression == bitmap::compression::BI_RLE4 { 4 } else { 8 };
+
+        let mut bytes = bytes[..data_offset].to_vec();
+        bytes.extend_from_slice(data);
+        let file_size = bytes.len() as u32;
+
+        bytes[2..6].copy_from_slice(&file_size.to_le_bytes());
+        bytes[18..22].copy_from_slice(&width.to_le_bytes());
+        bytes[22..26].copy_from_slice(&height.to_le_bytes());
+        bytes[28..30].copy_from_slice(&bit_depth.to_le_bytes());
+        bytes[30..34].copy_from_slice(&compression.to_le_bytes());
+        bytes[34..38].copy_from_slice(&(data.len() as u32).to_le_bytes());
+
+        Ok(bytes)
+    }
+
+    fn indices(bytes: &[u8]) -> Result<Vec<u8>, String> {
+        match Bitmap::decode(bytes, DecodeOptions { strict: true, ..Default::default() })?.pixels.pixels {
+            BitmapPixelData::Indices(indices) => Ok(indices),
+            BitmapPixelData::Colors(_) => Err(String::from("Expected an RLE bitmap to decode to indices."))
+        }
+    }
+
+    #[test]
+    fn rle8_runs_and_escapes() -> Result<(), String> {
+        let bytes = compressed(4, 3, bitmap::compression::BI_RLE8, &[
+            //A run of 4, then end of line
+            4, 1, 0, 0,
+            //3 absolute indices padded to 4 bytes, then a delta of 0 right and 1 down
+            0, 3, 2, 3, 1, 0, 0, 2, 0, 1,
+            //A run of 1, then end of bitmap
+            1, 2, 0, 1
+        ])?;
+
+        let expected = vec![1, 1, 1, 1, 2, 3, 1, 0, 0, 0, 0, 2];
+        let actual = indices(&bytes)?;
+
+        if actual != expected {
+            return Err(format!("Expected indices {expected:?}, got {actual:?}."));
+        }
+
+        //Decoding a region agrees with decoding the whole image
+        let image = Image::try_convert_from(Bitmap::try_from(bytes.as_slice())?, ImageConvertData::default())?;
+
+        if Bitmap::decode_region(&bytes, 1, 1, 3, 2, DecodeOptions::default())? != image.crop(1, 1, 3, 2) {
+            return Err(String::from("Region of RLE8 bitmap does not match the image."));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn rle4_runs_and_escapes() -> Result<(), String> {
+        let bytes = compressed(4, 2, bitmap::compression::BI_RLE4, &[
+            //A run of 3 alternating 1 and 2, then end of line
+            3, 0x12, 0, 0,
+            //4 absolute indices in 2 bytes, then end of bitmap
+            0, 4, 0x32, 0x10, 0, 1
+        ])?;
+
+        let expected = vec![1, 2, 1, 0, 3, 2, 1, 0];
+        let actual = indices(&bytes)?;
+
+        if actual != expected {
+            return Err(format!("Expected indices {expected:?}, got {actual:?}."));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn rle_violations() -> Result<(), String> {
+        //No end of bitmap, and a run past the end of the row
+        for (data, expected) in [(&[4_u8, 1][..], "without an end of bitmap"), (&[6, 1, 0, 1][..], "2 pixels outside of the image")] {
+            let bytes = compressed(4, 1, bitmap::compression::BI_RLE8, data)?;
+
+            match Bitmap::decode(&bytes, DecodeOptions { strict: true, ..Default::default() }) {
+                Err(ImageError::Decode(message)) if message.contains(expected) => (),
+                other => return Err(format!("Expected strict decoding to fail with '{expected}', got {other:?}."))
+            }
+
+            let (bitmap, report) = Bitmap::decode_with_report(&bytes, DecodeOptions::default())?;
+
+            if bitmap.pixels.pixels != BitmapPixelData::Indices(vec![1; 4]) || report.is_clean() {
+                return Err(format!("Expected '{expected}' to be repaired and reported, got {report:?}."));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn rle_dimensions_beyond_data() -> Result<(), String> {
+        //An end of bitmap alone leaves the image blank, which is fine while the image is small
+        let bytes = compressed(64, 64, bitmap::compression::BI_RLE8, &[0, 1])?;
+
+        if indices(&bytes)? != vec![0; 64 * 64] {
+            return Err(String::from("Expected an end of bitmap to leave a small image blank."));
+        }
+
+        //But runs of at most 255 pixels can't fill 50331655x5 pixels from a few bytes
+        let bytes = compressed(50_331_655, 5, bitmap::compression::BI_RLE8, &[0xFF, 1, 0xFF, 1, 0, 1])?;
+
+        for result in [Bitmap::decode(&bytes, DecodeOptions::default()), Bitmap::from_reader(&bytes[..], DecodeOptions::default())] {
+            match result {
+                Err(ImageError::Decode(message)) if message.contains("pixel data") => (),
+                other => return Err(format!("Expected an error naming the pixel data, but got {:?}.", other.map(|_| ())))
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn rle_round_trip() -> Result<(), String> {
+        let mut generator = ImageGenerator::new(super::SEED);
+
+        for (bit_depth, compression) in [(8, bitmap::compression::BI_RLE8), (4, bitmap::compression::BI_RLE4)] {
+            //Flat areas become runs and noise becomes absolute runs
+            for image in [generator.image_with(13, 5, Fill::Palette(6)), generator.image_with(300, 2, Fill::Palette(2)), Image::new(7, 3)] {
+                let bytes = Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                    bit_depth: BitDepth::Bits(bit_depth),
+                    compression,
+                    ..Default::default()
+                })?)?;
+
+                let data_offset = u32::from_le_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]) as usize;
+                let image_size = u32::from_le_bytes([bytes[34], bytes[35], bytes[36], bytes[37]]) as usize;
+                let file_size = u32::from_le_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]) as usize;
+
+                if image_size != bytes.len() - data_offset || file_size != bytes.len() {
+                    return Err(format!("{bit_depth}-bit RLE bitmap of {} bytes declares an image size of {image_size} and a file size of {file_size}.", bytes.len()));
+                }
+
+                let decoded = Image::try_convert_from(Bitmap::decode(&bytes, DecodeOptions { strict: true, ..Default::default() })?, ImageConvertData::default())?;
+
+                if decoded != image {
+                    return Err(format!("{bit_depth}-bit RLE bitmap of a {}x{} image does not decode to it.", image.width, image.height));
+                }
+            }
+        }
+
+        //RLE8 needs 8 bit indices
+        match Bitmap::try_convert_from(Image::new(2, 2), BitmapConvertData { bit_depth: BitDepth::Bits(24), compression: bitmap::compression::BI_RLE8, ..Default::default() }) {
+            Err(ImageError::InvalidArgument(_)) => Ok(()),
+            other => Err(format!("Expected RLE8 with a bit depth of 24 to be rejected, got {other:?}."))
+        }
+    }
+}
+
+mod tests_header_versions {
+    use super::super::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    //A bmp of the image with the info header extended to the given size by the fields
+    fn extended(image: &Image, size: u32, fields: &[u32]) -> Result<Vec<u8>, String> {
+        let bytes = Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+            bit_depth: BitDepth::Bits(32),
+            ..Default::default()
+        })?)?;
+
+        let added = size - bitmap::INFO_HEADER_SIZE;
+        let mut extended = bytes[..54].to_vec();
+        extended.extend(fields.iter().flat_map(|field| field.to_le_bytes()));
+        extended.extend_from_slice(&bytes[54..]);
+
+        extended[2..6].copy_from_slice(&(bytes.len() as u32 + added).to_le_bytes());
+        extended[10..14].copy_from_slice(&(54 + added).to_le_bytes());
+        extended[14..18].copy_from_slice(&size.to_le_bytes());
+
+        Ok(extended)
+    }
+
+    #[test]
+    fn v4_and_v5_round_trip() -> Result<(), String> {
+        let image = ImageGenerator::new(super::SEED).image_with(3, 2, Fill::Noise);
+
+        let mut v4 = vec![0x00FF0000, 0x0000FF00, 0x000000FF, 0xFF000000, bitmap::color_space::SRGB];
+        v4.extend([1, 2, 3, 4, 5, 6, 7, 8, 9, 0x10000, 0x20000, 0x30000]);
+
+        let mut v5 = v4.clone();
+        v5.extend([4, 124, 0, 0]);
+
+        for (size, fields) in [(bitmap::V4_INFO_HEADER_SIZE, v4), (bitmap::V5_INFO_HEADER_SIZE, v5)] {
+            let bytes = extended(&image, size, &fields)?;
+            let bitmap = Bitmap::decode(&bytes, DecodeOptions { strict: true, ..Default::default() })?;
+
+            let expected_v4 = BitmapV4Fields {
+                red_mask: 0x00FF0000,
+                green_mask: 0x0000FF00,
+                blue_mask: 0x000000FF,
+                alpha_mask: 0xFF000000,
+                color_space_type: bitmap::color_space::SRGB,
+                endpoints: [1, 2, 3, 4, 5, 6, 7, 8, 9],
+                gamma_red: 0x10000,
+                gamma_green: 0x20000,
+                gamma_blue: 0x30000
+            };
+
+            if bitmap.info_header.v4 != Some(expected_v4) || bitmap.info_header.color_space_name() != Some("sRGB") {
+                return Err(format!("Expected the V4 fields of a {size} byte info header to be {expected_v4:?}, got {:?}.", bitmap.info_header.v4));
+            }
+
+            let expected_v5 = (size == bitmap::V5_INFO_HEADER_SIZE).then_some(BitmapV5Fields { intent: 4, profile_data: 124, profile_size: 0, reserved: 0 });
+
+            if bitmap.info_header.v5 != expected_v5 {
+                return Err(format!("Expected the V5 fields of a {size} byte info header to be {expected_v5:?}, got {:?}.", bitmap.info_header.v5));
+            }
+
+            if Image::try_convert_from(bitmap.clone(), ImageConvertData::default())? != image {
+                return Err(format!("Bitmap with a {size} byte info header does not decode to the image."));
+            }
+
+            if Vec::try_from(bitmap)? != bytes {
+                return Err(format!("Bitmap with a {size} byte info header does not re-encode to the same bytes."));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_extended_header() -> Result<(), String> {
+        let image = ImageGenerator::new(super::SEED).image_with(1, 1, Fill::Noise);
+        let bytes = extended(&image, bitmap::V5_INFO_HEADER_SIZE, &[0; 21])?;
+
+        //The info header claims to run past the end of the file
+        match Bitmap::try_from(&bytes[..100]) {
+            Err(ImageError::Decode(_)) => Ok(()),
+            other => Err(format!("Expected a truncated V5 header to fail to decode, got {other:?}."))
+        }
+    }
+}
+
+mod tests_bitfields {
+    use super::super::*;
+
+    //A 2x2 bmp with the given info header size, bit depth and compression, followed by the fields, masks and pixels
+    fn file(size: u32, bit_depth: u16, compression: u32, after_info: &[u32], pixels: &[u8]) -> Vec<u8> {
+        let data_offset = 54 + after_info.len() as u32 * 4;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&bitmap::SIGNATURE.to_le_bytes());
+        bytes.extend_from_slice(&(data_offset + pixels.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&0_u32.to_le_bytes());
+        bytes.extend_from_slice(&data_offset.to_le_bytes());
+
+        for field in [size, 2, 2] {
+            bytes.extend_from_slice(&field.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&1_u16.to_le_bytes());
+        bytes.extend_from_slice(&bit_depth.to_le_bytes());
+
+        for field in [compression, pixels.len() as u32, 0, 0, 0, 0].iter().chain(after_info) {
+            bytes.extend_from_slice(&field.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(pixels);
+        bytes
+    }
+
+    fn check(bytes: &[u8], expected: &Image) -> Result<(), String> {
+        let bitmap = Bitmap::decode(bytes, DecodeOptions { strict: true, ..Default::default() })?;
+        let decoded = Image::try_convert_from(bitmap.clone(), ImageConvertData::default())?;
+
+        if &decoded != expected {
+            return Err(format!("Expected {expected:?}, got {decoded:?}."));
+        }
+
+        if Vec::try_from(bitmap)? != bytes {
+            return Err(String::from("Bitmap does not re-encode to the same bytes."));
+        }
+
+        Ok(())
+    }
+
+    fn image(pixels: [color::ARGB; 4]) -> Image {
+        //Rows are stored bottom-up
+        Image::new_pixels(2, 2, vec![pixels[2], pixels[3], pixels[0], pixels[1]])
+    }
+
+    fn rgb(red: u8, green: u8, blue: u8) -> color::ARGB {
+        color::ARGB { alpha: 0xFF, red, green, blue }
+    }
+
+    #[test]
+    fn masks_after_info_header() -> Result<(), String> {
+        //5-6-5 masks following a BITMAPINFOHEADER
+        let pixels: Vec<u8> = [0xF800_u16, 0x07E0, 0x001F, 0xFFFF].iter().flat_map(|pixel| pixel.to_le_bytes()).collect();
+        let bytes = file(40, 16, bitmap::compression::BI_BITFIELDS, &[0xF800, 0x07E0, 0x001F], &pixels);
+
+        check(&bytes, &image([rgb(0xFF, 0, 0), rgb(0, 0xFF, 0), rgb(0, 0, 0xFF), rgb(0xFF, 0xFF, 0xFF)]))
+    }
+
+    #[test]
+    fn masks_in_v4_header() -> Result<(), String> {
+        //RGBA byte order, rather than BGRA, with partial alpha
+        let pixels: Vec<u8> = [0xFF0000FF_u32, 0x00FF0080, 0x0000FF00, 0x12345678].iter().flat_map(|pixel| pixel.to_le_bytes()).collect();
+
+        let mut v4 = vec![0xFF000000, 0x00FF0000, 0x0000FF00, 0x000000FF, bitmap::color_space::SRGB];
+        v4.extend([0; 12]);
+
+        let bytes = file(bitmap::V4_INFO_HEADER_SIZE, 32, bitmap::compression::BI_BITFIELDS, &v4, &pixels);
+
+        check(&bytes, &image([
+            rgb(0xFF, 0, 0),
+            color::ARGB { alpha: 0x80, red: 0, green: 0xFF, blue: 0 },
+            color::ARGB { alpha: 0, red: 0, green: 0, blue: 0xFF },
+            color::ARGB { alpha: 0x78, red: 0x12, green: 0x34, blue: 0x56 }
+        ]))
+    }
+
+    #[test]
+    fn uncompressed_16_bit() -> Result<(), String> {
+        //5-5-5, with the top bit unused
+        let pixels: Vec<u8> = [0x7C00_u16, 0x03E0, 0x001F, 0x0000].iter().flat_map(|pixel| pixel.to_le_bytes()).collect();
+        let bytes = file(40, 16, bitmap::compression::BI_RGB, &[], &pixels);
+
+        let expected = image([rgb(0xFF, 0, 0), rgb(0, 0xFF, 0), rgb(0, 0, 0xFF), rgb(0, 0, 0)]);
+        check(&bytes, &expected)?;
+
+        //Encoding a 16-bit bitmap packs the pixels the same way
+        let encoded = Vec::try_from(Bitmap::try_convert_from(expected.clone(), BitmapConvertData {
+            bit_depth: BitDepth::Bits(16),
+            ..Default::default()
+        })?)?;
+
+        if Image::try_convert_from(Bitmap::try_from(encoded.as_slice())?, ImageConvertData::default())? != expected {
+            return Err(String::from("Encoded 16-bit bitmap does not decode to the image."));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn bitfields_round_trip() -> Result<(), String> {
+        let cases = [
+            (16, image([rgb(0xFF, 0, 0), rgb(0, 0xFF, 0), rgb(0, 0, 0xFF), rgb(0xFF, 0xFF, 0xFF)])),
+            (32, image([rgb(0xFF, 0, 0), rgb(0, 0xFF, 0x80), rgb(0, 0, 0), rgb(0x12, 0x34, 0x56)]))
+        ];
+
+        for (bit_depth, expected) in cases {
+            let bitmap = Bitmap::try_convert_from(expected.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Bits(bit_depth),
+                compression: bitmap::compression::BI_BITFIELDS,
+                ..Default::default()
+            })?;
+
+            //The masks follow the info header
+            if bitmap.header.data_offset != 54 + bitmap::BITFIELDS_SIZE as u32 {
+                return Err(format!("Expected the {bit_depth}-bit pixel data to start after the masks, but it starts at {}.", bitmap.header.data_offset));
+            }
+
+            let file_size = bitmap.header.file_size as usize;
+            let bytes = Vec::try_from(bitmap)?;
+
+            if bytes.len() != file_size || file_size != 54 + bitmap::BITFIELDS_SIZE + bit_depth as usize / 2 {
+                return Err(format!("Expected the {bit_depth}-bit file size of {file_size} to count the masks, and the {} bytes written.", bytes.len()));
+            }
+
+            check(&bytes, &expected)?;
+        }
+
+        //Bitfields only hold 16 or 32-bit pixels
+        match Bitmap::try_convert_from(image([rgb(0, 0, 0); 4]), BitmapConvertData {
+            bit_depth: BitDepth::Bits(24),
+            compression: bitmap::compression::BI_BITFIELDS,
+            ..Default::default()
+        }) {
+            Err(err) if err.to_string().contains("BI_BITFIELDS") => Ok(()),
+            other => Err(format!("Expected an error about BI_BITFIELDS, got {other:?}."))
+        }
+    }
+}
+
+mod tests_packing {
+    use super::super::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    fn packed(scanline: &[u8], bit_depth: u16) -> Vec<u8> {
+        let mut row = vec![0xAA_u8; utility::round_to_next_multiple_of_4(packed_row_bytes(scanline.len(), bit_depth) as i32)];
+        pack_indices(scanline, &mut row, bit_depth);
+        row
+    }
+
+    #[test]
+    fn packs_odd_widths() -> Result<(), String> {
+        let cases: [(&[u8], u16, &[u8]); 6] = [
+            //A partial final byte keeps its unused low bits 0, and padding is cleared
+            (&[1, 0, 1], 1, &[0b1010_0000, 0, 0, 0]),
+            (&[1, 1, 1, 1, 1, 1, 1, 1, 1], 1, &[0xFF, 0b1000_0000, 0, 0]),
+            (&[0x0A, 0x05, 0x0F], 4, &[0xA5, 0xF0, 0, 0]),
+            (&[1, 2, 3, 4, 5, 6, 7, 8, 9], 4, &[0x12, 0x34, 0x56, 0x78, 0x90, 0, 0, 0]),
+            (&[0xFE, 0x01, 0x80], 8, &[0xFE, 0x01, 0x80, 0]),
+            //Only the bits which fit the bit depth are kept
+            (&[0xFF, 0x12], 4, &[0xF2, 0, 0, 0])
+        ];
+
+        for (scanline, bit_depth, expected) in cases {
+            let row = packed(scanline, bit_depth);
+
+            if row != expected {
+                return Err(format!("{bit_depth}-bit scanline {scanline:?} packed to {row:?}, expected {expected:?}."));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn round_trips_1_and_4_bit() -> Result<(), String> {
+        let mut generator = ImageGenerator::new(super::SEED);
+
+        for (bit_depth, colors) in [(1, 2), (4, 16)] {
+            for width in [1, 3, 7, 9, 15, 33] {
+                let image = generator.image_with(width, 3, Fill::Palette(colors));
+
+                let bytes = Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                    bit_depth: BitDepth::Bits(bit_depth),
+                    ..Default::default()
+                })?)?;
+
+                let decoded = Image::try_convert_from(Bitmap::try_from(bytes.as_slice())?, ImageConvertData::default())?;
+
+                if decoded != image {
+                    return Err(format!("{bit_depth}-bit round trip of a {width}x3 image does not match."));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+mod tests_reader {
+    use super::super::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    ///
+    /// Check that reading the bytes as a stream gives the same bitmap and warnings as decoding them at once
+    ///
+    fn same_as_decode(bytes: &[u8]) -> Result<(), String> {
+        let (expected, expected_report) = Bitmap::decode_with_report(bytes, DecodeOptions::default())?;
+        let (bitmap, report) = Bitmap::from_reader_with_report(bytes, DecodeOptions::default())?;
+
+        if bitmap != expected {
+            return Err(format!("Bitmap read from a {}-byte stream does not match the decoded bitmap.", bytes.len()));
+        }
+
+        //Streaming finds the size of the file last, so reports it in a different order
+        let (mut warnings, mut expected_warnings) = (report.warnings, expected_report.warnings);
+        warnings.sort();
+        expected_warnings.sort();
+
+        if warnings != expected_warnings {
+            return Err(format!("Expected warnings {expected_warnings:?}, got {warnings:?}."));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn reader_matches_decode() -> Result<(), String> {
+        let image = ImageGenerator::new(super::SEED).image_with(7, 5, Fill::Palette(2));
+
+        for (bit_depth, compression) in [(1, 0), (4, 0), (8, 0), (16, 0), (24, 0), (32, 0), (8, bitmap::compression::BI_RLE8), (4, bitmap::compression::BI_RLE4)] {
+            let mut bytes = Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Bits(bit_depth),
+                compression,
+                ..Default::default()
+            })?)?;
+
+            same_as_decode(&bytes)?;
+
+            //Cut off partway through the pixels, and with bytes after them
+            same_as_decode(&bytes[..bytes.len() - 7])?;
+
+            bytes.extend_from_slice(b"trailing");
+            same_as_decode(&bytes)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn reader_errors() -> Result<(), String> {
+        let bytes = Vec::try_from(Bitmap::try_convert_from(ImageGenerator::new(super::SEED).image_with(3, 3, Fill::Noise), BitmapConvertData {
+            bit_depth: BitDepth::Bits(24),
+            ..Default::default()
+        })?)?;
+
+        //Too short for the headers
+        if Bitmap::from_reader(&bytes[..20], DecodeOptions::default()).is_ok() {
+            return Err(String::from("Expected a stream too short for the headers to fail."));
+        }
+
+        match Bitmap::from_reader(&bytes[..bytes.len() - 1], DecodeOptions { strict: true, ..Default::default() }) {
+            Err(ImageError::Decode(_)) => Ok(()),
+            other => Err(format!("Expected a truncated stream to fail strict decoding, got {other:?}."))
+        }
+    }
+}
+
+mod tests_writer {
+    use super::super::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    #[test]
+    fn writer_matches_bytes() -> Result<(), String> {
+        let image = ImageGenerator::new(super::SEED).image_with(7, 5, Fill::Palette(2));
+
+        for (bit_depth, compression) in [(1, 0), (4, 0), (8, 0), (16, 0), (24, 0), (32, 0), (8, bitmap::compression::BI_RLE8)] {
+            let bitmap = Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Bits(bit_depth),
+                compression,
+                ..Default::default()
+            })?;
+
+            let mut written = Vec::new();
+            bitmap.write_to(&mut written)?;
+
+            if written != Vec::try_from(bitmap)? {
+                return Err(format!("{bit_depth}-bit bitmap written to a writer does not match its bytes."));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn writer_round_trips_many_chunks() -> Result<(), String> {
+        //Enough rows to be encoded in several chunks
+        let image = ImageGenerator::new(super::SEED).image_with(300, 120, Fill::Noise);
+        let bitmap = Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+            bit_depth: BitDepth::Bits(32),
+            ..Default::default()
+        })?;
+
+        let mut written = Vec::new();
+        bitmap.write_to(&mut written)?;
+
+        let decoded = Image::try_convert_from(Bitmap::from_reader(written.as_slice(), DecodeOptions { strict: true, ..Default::default() })?, ImageConvertData::default())?;
+
+        if decoded != image {
+            return Err(String::from("Bitmap written in chunks does not decode to the image."));
+        }
+
+        Ok(())
+    }
+}
+
+mod tests_bit_depth {
+    use super::super::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    #[test]
+    fn auto_bit_depth() -> Result<(), String> {
+        let mut generator = ImageGenerator::new(super::SEED).opaque(true);
+
+        let transparent = Image::new_pixels(2, 1, vec![color::ARGB { alpha: 0x80, red: 1, green: 2, blue: 3 }; 2]);
+
+        let cases = [
+            (generator.image_with(9, 9, Fill::Palette(2)), 0, 1),
+            (generator.image_with(9, 9, Fill::Palette(16)), 0, 4),
+            (generator.image_with(20, 20, Fill::Palette(256)), 0, 8),
+            (generator.image_with(20, 20, Fill::Noise), 0, 24),
+            (transparent, 0, 32),
+            (generator.image_with(9, 9, Fill::Palette(2)), bitmap::compression::BI_RLE8, 8)
+        ];
+
+        for (image, compression, expected) in cases {
+            let bitmap = Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Auto,
+                compression,
+                ..Default::default()
+            })?;
+
+            if bitmap.info_header.bit_depth != expected {
+                return Err(format!("Expected a bit depth of {expected}, got {}.", bitmap.info_header.bit_depth));
+            }
+
+            let decoded = Image::try_convert_from(Bitmap::try_from(Vec::try_from(bitmap)?)?, ImageConvertData::default())?;
+
+            if decoded != image {
+                return Err(format!("{expected}-bit bitmap chosen automatically does not decode to the image."));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn too_many_colors() -> Result<(), String> {
+        let image = ImageGenerator::new(super::SEED).image_with(20, 20, Fill::Palette(17));
+
+        match Bitmap::try_convert_from(image, BitmapConvertData { bit_depth: BitDepth::Bits(4), ..Default::default() }) {
+            Err(ImageError::Encode(message)) if message.contains("17 colors") => Ok(()),
+            other => Err(format!("Expected 17 colors to be too many for a 4-bit bitmap, got {other:?}."))
+        }
+    }
+}
+
+mod tests_palette_order {
+    use super::super::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    #[test]
+    fn palette_by_frequency() -> Result<(), String> {
+        let (red, green, blue) = (
+            color::ARGB { alpha: 0xFF, red: 0xFF, green: 0, blue: 0 },
+            color::ARGB { alpha: 0xFF, red: 0, green: 0xFF, blue: 0 },
+            color::ARGB { alpha: 0xFF, red: 0, green: 0, blue: 0xFF }
+        );
+
+        //Red is seen first, but blue is the most common and green the next
+        let image = Image::new_pixels(3, 2, vec![blue, green, blue, red, green, blue]);
+
+        let bitmap = Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+            bit_depth: BitDepth::Bits(4),
+            palette_order: PaletteOrder::Frequency,
+            ..Default::default()
+        })?;
+
+        if bitmap.color_table.colors != [blue, green, red] {
+            return Err(format!("Expected the colors ordered by frequency, got {:?}.", bitmap.color_table.colors));
+        }
+
+        if Image::try_convert_from(bitmap, ImageConvertData::default())? != image {
+            return Err(String::from("Bitmap with its palette ordered by frequency does not match the image."));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn frequency_round_trip() -> Result<(), String> {
+        let mut generator = ImageGenerator::new(super::SEED);
+
+        for (bit_depth, colors, compression) in [(1, 2, 0), (4, 16, bitmap::compression::BI_RLE4), (8, 200, bitmap::compression::BI_RLE8)] {
+            let image = generator.image_with(17, 9, Fill::Palette(colors));
+
+            let bytes = Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Bits(bit_depth),
+                compression,
+                palette_order: PaletteOrder::Frequency,
+                ..Default::default()
+            })?)?;
+
+            let (bitmap, report) = Bitmap::decode_with_report(&bytes, DecodeOptions { strict: true, ..Default::default() })?;
+
+            let BitmapPixelData::Indices(indices) = &bitmap.pixels.pixels else {
+                return Err(format!("{bit_depth}-bit bitmap has no indices."));
+            };
+
+            let counts: Vec<usize> = (0..bitmap.color_table.colors.len())
+                .map(|color| indices.iter().filter(|index| **index as usize == color).count())
+                .collect();
+
+            if counts.windows(2).any(|pair| pair[0] < pair[1]) {
+                return Err(format!("{bit_depth}-bit color table is not ordered by frequency: {counts:?}."));
+            }
+
+            if !report.is_clean() || Image::try_convert_from(bitmap, ImageConvertData::default())? != image {
+                return Err(format!("{bit_depth}-bit bitmap with its palette ordered by frequency does not round trip."));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+mod tests_icc {
+    use super::super::*;
+    use crate::image::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    ///
+    /// Bytes standing in for an ICC profile, which the bitmap doesn't parse
+    ///
+    fn profile() -> Vec<u8> {
+        (0..=u8::MAX).cycle().take(301).collect()
+    }
+
+    fn with_profile(image: Image) -> Image {
+        image.with_metadata(ImageMetadata { icc_profile: Some(profile()) })
+    }
+
+    #[test]
+    fn profile_round_trip() -> Result<(), String> {
+        let mut generator = ImageGenerator::new(super::SEED).opaque(true);
+
+        for (bit_depth, compression) in [(1, 0), (4, 0), (8, bitmap::compression::BI_RLE8), (24, 0), (32, 0)] {
+            let image = with_profile(generator.image_with(9, 5, Fill::Palette(2)));
+
+            let bytes = Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Bits(bit_depth),
+                compression,
+                ..Default::default()
+            })?)?;
+
+            let (bitmap, report) = Bitmap::decode_with_report(&bytes, DecodeOptions { strict: true, ..Default::default() })?;
+
+            if bitmap.info_header.size != bitmap::V5_INFO_HEADER_SIZE || bitmap.icc_profile != Some(profile()) {
+                return Err(format!("{bit_depth}-bit bitmap does not have a V5 info header with the color profile."));
+            }
+
+            if !report.is_clean() || Image::try_convert_from(bitmap, ImageConvertData::default())? != image {
+                return Err(format!("{bit_depth}-bit bitmap with a color profile does not round trip."));
+            }
+
+            let (borrowed, _) = BitmapRef::decode_with_report(&bytes, DecodeOptions { strict: true, ..Default::default() })?;
+
+            if borrowed.icc_profile != Some(profile().as_slice()) {
+                return Err(format!("Borrowed {bit_depth}-bit bitmap does not have the color profile."));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn no_profile() -> Result<(), String> {
+        let image = ImageGenerator::new(super::SEED).opaque(true).image_with(4, 4, Fill::Noise);
+        let bitmap = Bitmap::try_convert_from(image, BitmapConvertData { bit_depth: BitDepth::Bits(24), ..Default::default() })?;
+
+        if bitmap.info_header.size != bitmap::INFO_HEADER_SIZE || bitmap.icc_profile.is_some() {
+            return Err(String::from("Bitmap without a color profile should have the basic info header."));
+        }
+
+        let decoded = Image::try_convert_from(Bitmap::decode(&Vec::try_from(bitmap)?, DecodeOptions::default())?, ImageConvertData::default())?;
+
+        if decoded.metadata().icc_profile.is_some() {
+            return Err(String::from("Image from a bitmap without a color profile has one."));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn profile_before_pixels() -> Result<(), String> {
+        let image = with_profile(ImageGenerator::new(super::SEED).opaque(true).image_with(6, 3, Fill::Palette(16)));
+        let mut bitmap = Bitmap::try_convert_from(image.clone(), BitmapConvertData { bit_depth: BitDepth::Bits(4), ..Default::default() })?;
+
+        //Move the profile between the color table and the pixel data
+        let profile_size = profile().len() as u32;
+        let table_end = bitmap.header.data_offset;
+        bitmap.header.data_offset += profile_size;
+        bitmap.info_header.v5 = bitmap.info_header.v5.map(|v5| BitmapV5Fields { profile_data: table_end - bitmap::HEADER_SIZE, ..v5 });
+
+        let file_size = bitmap.header.file_size as usize;
+        let bytes = Vec::try_from(bitmap)?;
+
+        if bytes.len() != file_size {
+            return Err(format!("Expected the color profile to be written once, but the file is {} bytes.", bytes.len()));
+        }
+
+        let (bitmap, report) = Bitmap::decode_with_report(&bytes, DecodeOptions { strict: true, ..Default::default() })?;
+
+        if bitmap.icc_profile != Some(profile()) || !report.is_clean() || Image::try_convert_from(bitmap, ImageConvertData::default())? != image {
+            return Err(String::from("Bitmap with a color profile before its pixel data does not round trip."));
+        }
+
+        let bitmap = Bitmap::from_reader(bytes.as_slice(), DecodeOptions { strict: true, ..Default::default() })?;
+
+        if bitmap.icc_profile != Some(profile()) {
+            return Err(String::from("Bitmap read from a stream with a color profile before its pixel data lost the profile."));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn profile_from_reader() -> Result<(), String> {
+        let image = with_profile(ImageGenerator::new(super::SEED).opaque(true).image_with(5, 5, Fill::Palette(2)));
+
+        for compression in [0, bitmap::compression::BI_RLE8] {
+            let bytes = Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+                bit_depth: BitDepth::Bits(8),
+                compression,
+                ..Default::default()
+            })?)?;
+
+            let (bitmap, report) = Bitmap::from_reader_with_report(bytes.as_slice(), DecodeOptions { strict: true, ..Default::default() })?;
+
+            if bitmap.icc_profile != Some(profile()) || !report.is_clean() {
+                return Err(format!("Bitmap read from a stream with compression {compression} lost the color profile."));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_profile() -> Result<(), String> {
+        let image = with_profile(ImageGenerator::new(super::SEED).opaque(true).image_with(3, 3, Fill::Noise));
+        let mut bytes = Vec::try_from(Bitmap::try_convert_from(image, BitmapConvertData { bit_depth: BitDepth::Bits(24), ..Default::default() })?)?;
+        bytes.truncate(bytes.len() - 10);
+
+        if Bitmap::decode_with_report(&bytes, DecodeOptions { strict: true, ..Default::default() }).is_ok() {
+            return Err(String::from("Strict decoding should fail on a truncated color profile."));
+        }
+
+        let (bitmap, report) = Bitmap::decode_with_report(&bytes, DecodeOptions::default())?;
+        let (streamed, streamed_report) = Bitmap::from_reader_with_report(bytes.as_slice(), DecodeOptions::default())?;
+
+        for (bitmap, report) in [(bitmap, report), (streamed, streamed_report)] {
+            if bitmap.icc_profile.is_some() || !report.violations.iter().any(|violation| violation.contains("color profile")) {
+                return Err(format!("Expected a violation for the truncated color profile, got {:?}.", report.violations));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+mod tests_mask {
+    use super::super::*;
+    use crate::image::*;
+    use crate::image::generate::{Fill, ImageGenerator};
+
+    const ICON: DecodeOptions = DecodeOptions { strict: true, and_mask: true };
+
+    ///
+    /// An image of 2 colors with a transparent diagonal, and a width
+    /// which doesn't fill the last byte of each row of the mask
+    ///
+    fn input() -> Result<Image, String> {
+        let mut image = ImageGenerator::new(super::SEED).opaque(true).image_with(11, 6, Fill::Palette(2));
+
+        for j in 0..image.height() {
+            let pixel = image.get(j, j).unwrap_or_default();
+            image.set(color::ARGB { alpha: 0, ..pixel }, j, j)?;
+        }
+
+        Ok(image)
+    }
+
+    fn encode(image: &Image, bit_depth: u16, top_down: bool) -> Result<Vec<u8>, String> {
+        Ok(Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+            bit_depth: BitDepth::Bits(bit_depth),
+            top_down,
+            and_mask: true,
+            ..Default::default()
+        })?)?)
+    }
+
+    #[test]
+    fn mask_round_trip() -> Result<(), String> {
+        let image = input()?;
+
+        for (bit_depth, top_down) in [(4, false), (8, true), (24, false), (24, true), (32, false)] {
+            let bytes = encode(&image, bit_depth, top_down)?;
+            let (bitmap, report) = Bitmap::decode_with_report(&bytes, ICON)?;
+
+            if bitmap.info_header.height.unsigned_abs() as usize != image.height() || bitmap.mask.as_ref().map(|mask| mask.iter().filter(|bit| **bit).count()) != Some(6) {
+                return Err(format!("{bit_depth}-bit bitmap should have a mask of the 6 transparent pixels after the pixel data."));
+            }
+
+            if !report.is_clean() || Image::try_convert_from(bitmap, ImageConvertData::default())? != image {
+                return Err(format!("{bit_depth}-bit bitmap with a mask does not round trip."));
+            }
+
+            if Bitmap::decode_region(&bytes, 0, 0, image.width(), image.height(), ICON)? != image {
+                return Err(format!("{bit_depth}-bit bitmap with a mask decoded as a region does not match the image."));
+            }
+
+            let streamed = Bitmap::from_reader(bytes.as_slice(), ICON)?;
+
+            if Image::try_convert_from(streamed, ImageConvertData::default())? != image {
+                return Err(format!("{bit_depth}-bit bitmap with a mask read from a stream does not match the image."));
+            }
+
+            let (borrowed, _) = BitmapRef::decode_with_report(&bytes, ICON)?;
+
+            if Image::try_convert_from(Bitmap::from(borrowed), ImageConvertData::default())? != image {
+                return Err(format!("Borrowed {bit_depth}-bit bitmap with a mask does not match the image."));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn doubled_height() -> Result<(), String> {
+        let bytes = encode(&input()?, 24, false)?;
+
+        //The info header counts the rows of the mask along with those of the pixels, as icons do
+        if bytes.get(22..26) != Some(12_i32.to_le_bytes().as_slice()) {
+            return Err(format!("Expected the stored height to be doubled, got {:?}.", bytes.get(22..26)));
+        }
+
+        let bitmap = Bitmap::decode(&bytes, ICON)?;
+
+        if bitmap.computed_file_size() != bytes.len() || Vec::try_from(bitmap.strip_metadata()?)? != bytes {
+            return Err(String::from("Bitmap with a mask should count and keep it."));
+        }
+
+        if Bitmap::decode(&bytes[..bytes.len() - 1], ICON).is_ok() {
+            return Err(String::from("Strict decoding should fail on a truncated mask."));
+        }
+
+        let (streamed, report) = Bitmap::from_reader_with_report(&bytes[..bytes.len() - 1], DecodeOptions { and_mask: true, ..Default::default() })?;
+
+        if streamed.mask.is_some() || !report.violations.iter().any(|violation| violation.contains("AND mask")) {
+            return Err(format!("Expected a violation for the truncated mask, got {:?}.", report.violations));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn odd_height_fails() -> Result<(), String> {
+        let bytes = Vec::try_from(Bitmap::try_convert_from(input()?.crop(0, 0, 11, 5), BitmapConvertData {
+            bit_depth: BitDepth::Bits(24),
+            ..Default::default()
+        })?)?;
+
+        match Bitmap::decode(&bytes, ICON) {
+            Err(ImageError::Decode(_)) => Ok(()),
+            other => Err(format!("Expected an error for an odd height read with a mask, got {other:?}."))
+        }
+    }
+
+    #[test]
+    fn mask_with_profile() -> Result<(), String> {
+        let profile: Vec<u8> = (0..64).collect();
+        let image = input()?.with_metadata(ImageMetadata { icc_profile: Some(profile.clone()) });
+        let bytes = encode(&image, 8, false)?;
+
+        let (bitmap, report) = Bitmap::decode_with_report(&bytes, ICON)?;
+
+        if bitmap.mask.is_none() || bitmap.icc_profile.as_ref() != Some(&profile) || !report.is_clean() {
+            return Err(String::from("Bitmap should have both its mask and the color profile after it."));
+        }
+
+        let streamed = Bitmap::from_reader(bytes.as_slice(), ICON)?;
+
+        if streamed != bitmap {
+            return Err(String::from("Bitmap with a mask and color profile read from a stream does not match the decoded bitmap."));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn trailing_bytes_are_not_a_mask() -> Result<(), String> {
+        let image = input()?;
+
+        let mut bytes = Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+            bit_depth: BitDepth::Bits(24),
+            ..Default::default()
+        })?)?;
+
+        //Exactly the size of a mask of every pixel, which is only trailing data unless asked for
+        bytes.extend_from_slice(&[0xFF; 4 * 6]);
+
+        let bitmap = Bitmap::decode(&bytes, DecodeOptions::default())?;
+
+        if bitmap.mask.is_some() || Image::try_convert_from(bitmap, ImageConvertData::default())?.get(1, 2).map(|pixel| pixel.alpha) != Some(0xFF) {
+            return Err(String::from("Bitmap with trailing bytes should not have a mask."));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn compressed_mask_fails() -> Result<(), String> {
+        match Bitmap::try_convert_from(input()?, BitmapConvertData { bit_depth: BitDepth::Bits(8), compression: bitmap::compression::BI_RLE8, and_mask: true, ..Default::default() }) {
+            Err(ImageError::InvalidArgument(_)) => Ok(()),
+            other => Err(format!("Expected an error for an RLE bitmap with a mask, got {other:?}."))
+        }
+    }
+}
\ No newline at end of file
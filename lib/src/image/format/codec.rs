@@ -0,0 +1,374 @@
+#[cfg(test)]
+mod tests;
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::{convert::ConvertableFrom, error::ImageError};
+use crate::{color::icc::IccProfile, log};
+use crate::image::{Image, ImageMetadata};
+use crate::image::animation::{AnimatedImage, Frame};
+use crate::image::orientation::ExifOrientation;
+use super::bitmap::{Bitmap, BitDepth, BitmapConvertData, ImageConvertData};
+use super::gif::Gif;
+use super::dds::Dds;
+use super::ilbm::Ilbm;
+use super::netpbm::{Netpbm, NetpbmConvertData};
+use super::png::Png;
+
+///
+/// A format images can be read from, and optionally written to. Implement this
+/// in another crate and pass it to register_format to add a format without
+/// changing this one; it is then detected and loaded like the built-in formats.
+///
+pub trait ImageCodec: Send + Sync {
+    ///
+    /// Short, human-readable name of the format
+    ///
+    fn name(&self) -> &str;
+
+    ///
+    /// File extensions usually used for the format, the preferred one first
+    ///
+    fn extensions(&self) -> &[&str];
+
+    ///
+    /// Whether the bytes start with the format's signature
+    ///
+    fn detect(&self, bytes: &[u8]) -> bool;
+
+    fn decode(&self, bytes: &[u8]) -> Result<Image, ImageError>;
+
+    ///
+    /// The orientation given by the image's metadata, if it has any. Decode
+    /// returns the pixels as stored; load turns them upright by this.
+    ///
+    fn orientation(&self, _bytes: &[u8]) -> Option<ExifOrientation> {
+        None
+    }
+
+    ///
+    /// Whether images can be written in the format
+    ///
+    fn can_encode(&self) -> bool {
+        false
+    }
+
+    fn encode(&self, _image: &Image) -> Result<Vec<u8>, ImageError> {
+        Err(ImageError::Unsupported(format!("Writing {} images is not supported.", self.name())))
+    }
+}
+
+///
+/// The bmp format, written at 32 bits per pixel
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BitmapCodec;
+
+impl ImageCodec for BitmapCodec {
+    fn name(&self) -> &str {
+        super::ImageFormat::Bitmap.name()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bmp", "dib"]
+    }
+
+    fn detect(&self, bytes: &[u8]) -> bool {
+        super::detect(bytes) == Some(super::ImageFormat::Bitmap)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Image, ImageError> {
+        Image::try_convert_from(Bitmap::try_from(bytes)?, ImageConvertData::default())
+    }
+
+    fn can_encode(&self) -> bool {
+        true
+    }
+
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, ImageError> {
+        Vec::try_from(Bitmap::try_convert_from(image.clone(), BitmapConvertData {
+            bit_depth: BitDepth::Bits(32),
+            ..Default::default()
+        })?)
+    }
+}
+
+///
+/// The png format, which can only be read
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PngCodec;
+
+impl ImageCodec for PngCodec {
+    fn name(&self) -> &str {
+        super::ImageFormat::Png.name()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["png"]
+    }
+
+    fn detect(&self, bytes: &[u8]) -> bool {
+        super::detect(bytes) == Some(super::ImageFormat::Png)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Image, ImageError> {
+        Image::try_convert_from(Png::try_from(bytes)?, ImageConvertData::default())
+    }
+
+    fn orientation(&self, bytes: &[u8]) -> Option<ExifOrientation> {
+        super::png::orientation(bytes)
+    }
+}
+
+///
+/// The gif format. Only the first frame is loaded, and images are written as a
+/// single frame; convert between Gif and AnimatedImage to keep every frame.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GifCodec;
+
+impl ImageCodec for GifCodec {
+    fn name(&self) -> &str {
+        super::ImageFormat::Gif.name()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gif"]
+    }
+
+    fn detect(&self, bytes: &[u8]) -> bool {
+        super::detect(bytes) == Some(super::ImageFormat::Gif)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Image, ImageError> {
+        Image::try_convert_from(Gif::try_from(bytes)?, ImageConvertData::default())
+    }
+
+    fn can_encode(&self) -> bool {
+        true
+    }
+
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, ImageError> {
+        let mut animation = AnimatedImage::new(image.width(), image.height());
+        animation.push(Frame::new(image.clone(), Default::default()));
+
+        Vec::try_from(Gif::try_convert_from(animation, ())?)
+    }
+}
+
+///
+/// The netpbm formats: pbm, pgm and ppm, in ASCII or binary, and pam. Images are written as binary ppm.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NetpbmCodec;
+
+impl ImageCodec for NetpbmCodec {
+    fn name(&self) -> &str {
+        super::ImageFormat::Netpbm.name()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ppm", "pgm", "pbm", "pnm", "pam"]
+    }
+
+    fn detect(&self, bytes: &[u8]) -> bool {
+        super::detect(bytes) == Some(super::ImageFormat::Netpbm)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Image, ImageError> {
+        Image::try_convert_from(Netpbm::try_from(bytes)?, ())
+    }
+
+    fn can_encode(&self) -> bool {
+        true
+    }
+
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, ImageError> {
+        Vec::try_from(Netpbm::try_convert_from(image.clone(), NetpbmConvertData::default())?)
+    }
+}
+
+///
+/// The dds texture format, which can only be read. Only the largest mipmap is loaded.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DdsCodec;
+
+impl ImageCodec for DdsCodec {
+    fn name(&self) -> &str {
+        super::ImageFormat::Dds.name()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dds"]
+    }
+
+    fn detect(&self, bytes: &[u8]) -> bool {
+        super::detect(bytes) == Some(super::ImageFormat::Dds)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Image, ImageError> {
+        Image::try_convert_from(Dds::try_from(bytes)?, ())
+    }
+}
+
+///
+/// The Amiga ilbm format, which can only be read
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IlbmCodec;
+
+impl ImageCodec for IlbmCodec {
+    fn name(&self) -> &str {
+        super::ImageFormat::Ilbm.name()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["iff", "ilbm", "lbm"]
+    }
+
+    fn detect(&self, bytes: &[u8]) -> bool {
+        super::detect(bytes) == Some(super::ImageFormat::Ilbm)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Image, ImageError> {
+        Image::try_convert_from(Ilbm::try_from(bytes)?, ImageConvertData::default())
+    }
+}
+
+///
+/// Formats added by register_format, in the order they were added
+///
+static REGISTERED: RwLock<Vec<Arc<dyn ImageCodec>>> = RwLock::new(Vec::new());
+
+///
+/// Add a format, which is then detected, loaded and listed along with the built-in
+/// ones. Fails if it has no extensions, or one is already claimed by another format.
+///
+pub fn register_format(codec: Box<dyn ImageCodec>) -> Result<(), ImageError> {
+    if codec.extensions().is_empty() {
+        return Err(ImageError::InvalidArgument(format!("Format {} must have at least one extension.", codec.name())));
+    }
+
+    let mut registered = REGISTERED.write().unwrap_or_else(PoisonError::into_inner);
+
+    //Checked while holding the lock, so two formats can't claim an extension at once
+    let claimed = built_in().into_iter()
+        .chain(registered.iter().cloned())
+        .find_map(|existing| codec.extensions().iter()
+            .find(|extension| claims(existing.as_ref(), extension))
+            .map(|extension| (existing, *extension)));
+
+    if let Some((existing, extension)) = claimed {
+        return Err(ImageError::InvalidArgument(format!(
+            "Extension '{extension}' of format {} is already used by format {}.", codec.name(), existing.name()
+        )));
+    }
+
+    registered.push(Arc::from(codec));
+    Ok(())
+}
+
+///
+/// Every format, built-in ones first
+///
+pub fn formats() -> Vec<Arc<dyn ImageCodec>> {
+    let registered = REGISTERED.read().unwrap_or_else(PoisonError::into_inner);
+    built_in().into_iter().chain(registered.iter().cloned()).collect()
+}
+
+///
+/// Find the format which uses the given file extension
+///
+pub fn find(extension: &str) -> Option<Arc<dyn ImageCodec>> {
+    let extension = extension.trim_start_matches('.');
+    formats().into_iter().find(|codec| claims(codec.as_ref(), extension))
+}
+
+///
+/// Detect the format of an image from the signature at the start of its bytes
+///
+pub fn detect(bytes: &[u8]) -> Option<Arc<dyn ImageCodec>> {
+    formats().into_iter().find(|codec| codec.detect(bytes))
+}
+
+///
+/// How load treats a decoded image
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadOptions {
+    ///
+    /// Turn the image upright by the orientation in its metadata. Otherwise,
+    /// the pixels are kept in the orientation they were stored in.
+    ///
+    pub apply_orientation: bool,
+    ///
+    /// Convert the colors from the ICC profile in the image's metadata to sRGB,
+    /// and drop the profile. Otherwise, the colors are kept as stored, with
+    /// the profile, so it can be embedded again when encoding.
+    ///
+    pub apply_color_profile: bool
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            apply_orientation: true,
+            apply_color_profile: false
+        }
+    }
+}
+
+///
+/// Decode an image in any format, detected from its bytes, and turn it
+/// upright by the orientation in its metadata
+///
+pub fn load(bytes: &[u8]) -> Result<Image, ImageError> {
+    load_with_options(bytes, LoadOptions::default())
+}
+
+///
+/// Decode an image in any format, detected from its bytes
+///
+pub fn load_with_options(bytes: &[u8], options: LoadOptions) -> Result<Image, ImageError> {
+    let codec = detect(bytes)
+        .ok_or_else(|| ImageError::Unsupported(String::from("Unrecognized image format.")))?;
+
+    let mut image = codec.decode(bytes)?;
+
+    if options.apply_color_profile {
+        image = to_srgb(image);
+    }
+
+    match codec.orientation(bytes) {
+        Some(orientation) if options.apply_orientation => Ok(orientation.apply(&image)),
+        _ => Ok(image)
+    }
+}
+
+///
+/// Convert the colors of the image from its ICC profile to sRGB. A profile
+/// which can't be read is dropped, leaving the colors as they were.
+///
+fn to_srgb(image: Image) -> Image {
+    let Some(bytes) = image.metadata().icc_profile.as_deref() else {
+        return image;
+    };
+
+    match IccProfile::try_from(bytes) {
+        Ok(profile) => profile.apply(&image),
+        Err(err) => {
+            log::warn(format_args!("Ignoring the color profile of the image: {err}"));
+            image.with_metadata(ImageMetadata::default())
+        }
+    }
+}
+
+fn built_in() -> Vec<Arc<dyn ImageCodec>> {
+    vec![Arc::new(BitmapCodec), Arc::new(PngCodec), Arc::new(GifCodec), Arc::new(NetpbmCodec), Arc::new(DdsCodec), Arc::new(IlbmCodec)]
+}
+
+fn claims(codec: &dyn ImageCodec, extension: &str) -> bool {
+    codec.extensions().iter().any(|claimed| claimed.eq_ignore_ascii_case(extension))
+}
\ No newline at end of file
@@ -0,0 +1,243 @@
+use super::*;
+use crate::image::format::ImageFormat;
+use crate::color;
+use crate::image::transform::Rotation;
+
+///
+/// A minimal format, as a downstream crate would add: a signature, the width
+/// and height as little-endian u32s, then each pixel as an ARGB u32
+///
+struct TestCodec {
+    signature: &'static [u8; 4],
+    extensions: &'static [&'static str],
+    orientation: Option<ExifOrientation>
+}
+
+impl ImageCodec for TestCodec {
+    fn name(&self) -> &str {
+        "Test"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+
+    fn detect(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(self.signature)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Image, ImageError> {
+        let words: Vec<u32> = bytes.get(4..).unwrap_or_default()
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+
+        match words.as_slice() {
+            [width, height, pixels @ ..] if pixels.len() == (*width * *height) as usize => {
+                Ok(Image::new_pixels(*width as usize, *height as usize, pixels.iter().map(|pixel| color::ARGB::from(*pixel)).collect()))
+            },
+            _ => Err(ImageError::Decode(String::from("Test image is truncated.")))
+        }
+    }
+
+    fn orientation(&self, _bytes: &[u8]) -> Option<ExifOrientation> {
+        self.orientation
+    }
+
+    fn can_encode(&self) -> bool {
+        true
+    }
+
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, ImageError> {
+        let mut bytes = self.signature.to_vec();
+        bytes.extend((image.width() as u32).to_le_bytes());
+        bytes.extend((image.height() as u32).to_le_bytes());
+        bytes.extend(image.iter().flatten().flat_map(|pixel| u32::from(*pixel).to_le_bytes()));
+        Ok(bytes)
+    }
+}
+
+#[test]
+fn registered_formats_are_detected_and_loaded() -> Result<(), String> {
+    let codec = TestCodec { signature: b"TST1", extensions: &["tst1"], orientation: None };
+
+    let image = Image::new_pixels(2, 1, vec![
+        color::ARGB { alpha: 0xFF, red: 1, green: 2, blue: 3 },
+        color::ARGB { alpha: 0x80, red: 4, green: 5, blue: 6 }
+    ]);
+
+    let bytes = codec.encode(&image)?;
+
+    if load(&bytes).is_ok() {
+        return Err(String::from("Expected an unregistered format to be unrecognized."));
+    }
+
+    register_format(Box::new(codec))?;
+
+    if find(".TST1").map(|codec| codec.name().to_string()) != Some(String::from("Test")) {
+        return Err(String::from("Expected the registered format to be found by its extension."));
+    }
+
+    if !formats().iter().any(|codec| codec.extensions() == ["tst1"]) {
+        return Err(String::from("Expected the registered format to be listed."));
+    }
+
+    if load(&bytes)? != image {
+        return Err(String::from("Expected the registered format to load the image."));
+    }
+
+    //Built-in formats are still detected first
+    let bitmap = find("bmp").ok_or("Expected bmp to be built in.")?.encode(&image)?;
+
+    if load(&bitmap)? != image {
+        Err(String::from("Expected a bitmap to load through the built-in format."))
+    }
+    else {
+        Ok(())
+    }
+}
+
+#[test]
+fn claimed_extensions_are_rejected() -> Result<(), String> {
+    let cases: [(&'static [&'static str], &str); 3] = [
+        (&[], "at least one extension"),
+        (&["tst2", "BMP"], "'BMP'"),
+        (&["tst2"], "'tst2'")
+    ];
+
+    register_format(Box::new(TestCodec { signature: b"TST2", extensions: &["tst2"], orientation: None }))?;
+
+    for (extensions, expected) in cases {
+        match register_format(Box::new(TestCodec { signature: b"TST3", extensions, orientation: None })) {
+            Ok(()) => return Err(format!("Expected extensions {extensions:?} to be rejected.")),
+            Err(e) if !e.to_string().contains(expected) => {
+                return Err(format!("Expected the error for {extensions:?} to mention {expected}, got '{e}'."))
+            },
+            Err(_) => ()
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn load_applies_orientation() -> Result<(), String> {
+    let codec = TestCodec { signature: b"TST4", extensions: &["tst4"], orientation: Some(ExifOrientation::Rotate90) };
+
+    let image = Image::new_pixels(3, 1, vec![
+        color::ARGB { alpha: 0xFF, red: 1, green: 0, blue: 0 },
+        color::ARGB { alpha: 0xFF, red: 2, green: 0, blue: 0 },
+        color::ARGB { alpha: 0xFF, red: 3, green: 0, blue: 0 }
+    ]);
+
+    let bytes = codec.encode(&image)?;
+    register_format(Box::new(codec))?;
+
+    if load(&bytes)? != image.rotate(Rotation::Rotate90) {
+        return Err(String::from("Expected load to turn the image upright."));
+    }
+
+    if load_with_options(&bytes, LoadOptions { apply_orientation: false, ..Default::default() })? != image {
+        Err(String::from("Expected the stored orientation to be kept when asked."))
+    }
+    else {
+        Ok(())
+    }
+}
+#[test]
+fn writable_extensions_match_encoders() -> Result<(), String> {
+    for extension in ImageFormat::WRITABLE_EXTENSIONS {
+        match find(extension) {
+            Some(codec) if codec.can_encode() => (),
+            _ => return Err(format!("Expected a format which can encode images to use extension '{extension}'."))
+        }
+    }
+
+    for codec in built_in().iter().filter(|codec| codec.can_encode()) {
+        if !codec.extensions().iter().any(|extension| ImageFormat::WRITABLE_EXTENSIONS.contains(extension)) {
+            return Err(format!("Expected an extension of {} to be listed as writable.", codec.name()));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn load_applies_png_exif_orientation() -> Result<(), String> {
+    //A 3x2 png whose eXIf chunk says to turn it 90 degrees clockwise
+    let bytes = include_bytes!("../png/exif_rotate90.png");
+    let stored = PngCodec.decode(bytes)?;
+
+    if (stored.width(), stored.height()) != (3, 2) || PngCodec.orientation(bytes) != Some(ExifOrientation::Rotate90) {
+        return Err(format!("Expected a 3x2 png turned 90 degrees, got {}x{} and {:?}.",
+            stored.width(), stored.height(), PngCodec.orientation(bytes)));
+    }
+
+    if load(bytes)? != stored.rotate(Rotation::Rotate90) {
+        return Err(String::from("Expected load to turn the png upright."));
+    }
+
+    if load_with_options(bytes, LoadOptions { apply_orientation: false, ..Default::default() })? != stored {
+        Err(String::from("Expected the stored orientation of the png to be kept when asked."))
+    }
+    else {
+        Ok(())
+    }
+}
+
+///
+/// An ICC profile with the colorants of sRGB, adapted to D50, and linear tone curves
+///
+fn linear_profile() -> Vec<u8> {
+    let colorants = [[0.4361_f32, 0.2225, 0.0139], [0.3851, 0.7169, 0.0971], [0.1431, 0.0606, 0.7141]];
+
+    let mut tags: Vec<([u8; 4], Vec<u8>)> = [*b"rXYZ", *b"gXYZ", *b"bXYZ"].into_iter()
+        .zip(colorants)
+        .map(|(name, xyz)| (name, [b"XYZ \0\0\0\0".to_vec(), xyz.iter().flat_map(|value| ((value * 65536_f32).round() as i32).to_be_bytes()).collect()].concat()))
+        .collect();
+
+    //A curve without entries is the identity
+    tags.extend([*b"rTRC", *b"gTRC", *b"bTRC"].map(|name| (name, b"curv\0\0\0\0\0\0\0\0".to_vec())));
+
+    let mut header = vec![0_u8; 128];
+    header[16..20].copy_from_slice(b"RGB ");
+    header[20..24].copy_from_slice(b"XYZ ");
+    header[36..40].copy_from_slice(b"acsp");
+
+    let mut table = (tags.len() as u32).to_be_bytes().to_vec();
+    let mut data = Vec::new();
+
+    for (name, tag) in &tags {
+        table.extend(name);
+        table.extend(((128 + 4 + 12 * tags.len() + data.len()) as u32).to_be_bytes());
+        table.extend((tag.len() as u32).to_be_bytes());
+        data.extend(tag);
+    }
+
+    let mut profile = [header, table, data].concat();
+    let size = (profile.len() as u32).to_be_bytes();
+    profile[..4].copy_from_slice(&size);
+    profile
+}
+
+#[test]
+fn load_applies_color_profile_when_asked() -> Result<(), String> {
+    let gray = color::ARGB { alpha: 0xFF, red: 128, green: 128, blue: 128 };
+    let metadata = ImageMetadata { icc_profile: Some(linear_profile()) };
+    let image = Image::new_pixels(2, 1, vec![gray; 2]).with_metadata(metadata.clone());
+
+    let bytes = BitmapCodec.encode(&image)?;
+
+    if load(&bytes)? != image {
+        return Err(String::from("Expected the colors and profile to be kept by default."));
+    }
+
+    let converted = load_with_options(&bytes, LoadOptions { apply_color_profile: true, ..Default::default() })?;
+    let expected = color::linear_to_srgb(128_f32 / 255_f32);
+
+    match converted.get(1, 0) {
+        _ if converted.metadata().icc_profile.is_some() => Err(String::from("Expected the profile to be dropped once applied.")),
+        Some(pixel) if pixel.red.abs_diff(expected) <= 1 && pixel.alpha == 0xFF => Ok(()),
+        pixel => Err(format!("Expected linear gray 128 to become {expected}, got {pixel:?}."))
+    }
+}
@@ -0,0 +1,366 @@
+//Decoding must not panic on any input, so only tests may index or unwrap
+#![deny(clippy::indexing_slicing, clippy::unwrap_used, clippy::expect_used)]
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing, clippy::unwrap_used)]
+mod tests;
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+use crate::{color, image, convert::ConvertableFrom, error::ImageError};
+use image::Image;
+use crate::constants::dds;
+
+///
+/// A texture in dds format, with the pixels of its largest mipmap decoded.
+/// Smaller mipmaps, cube map faces and volume slices are skipped.
+/// Dds format:
+/// https://learn.microsoft.com/en-us/windows/win32/direct3ddds/dx-graphics-dds-pguide
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dds {
+    pub header: DdsHeader,
+    ///
+    /// The color of each pixel, in rows from the top
+    ///
+    pub pixels: Vec<color::ARGB>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DdsHeader {
+    pub width: usize,
+    pub height: usize,
+    ///
+    /// Number of mipmaps stored, including the full size image
+    ///
+    pub mipmap_count: u32,
+    pub pixel_format: DdsPixelFormat
+}
+
+///
+/// How the pixels are stored
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DdsPixelFormat {
+    ///
+    /// Each pixel is a little-endian value, with the channels picked out by masks
+    ///
+    Uncompressed(DdsMasks),
+    ///
+    /// DXT1 compression, of 4x4 blocks in 8 bytes, with 1-bit alpha
+    ///
+    Bc1,
+    ///
+    /// DXT5 compression, of 4x4 blocks in 16 bytes, with interpolated alpha
+    ///
+    Bc3
+}
+
+///
+/// The layout of an uncompressed pixel
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DdsMasks {
+    pub bit_count: u32,
+    pub red: u32,
+    pub green: u32,
+    pub blue: u32,
+    ///
+    /// Zero if the pixels have no alpha
+    ///
+    pub alpha: u32,
+    ///
+    /// Whether the red mask holds the gray level of every channel
+    ///
+    pub luminance: bool
+}
+
+impl DdsPixelFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Uncompressed(_) => "uncompressed",
+            Self::Bc1 => "BC1",
+            Self::Bc3 => "BC3"
+        }
+    }
+}
+
+impl DdsMasks {
+    ///
+    /// The color of a pixel, given its value
+    ///
+    fn color(&self, value: u32) -> color::ARGB {
+        let red = channel(value, self.red).unwrap_or_default();
+
+        let (green, blue) = if self.luminance {
+            (red, red)
+        }
+        else {
+            (channel(value, self.green).unwrap_or_default(), channel(value, self.blue).unwrap_or_default())
+        };
+
+        color::ARGB {
+            alpha: channel(value, self.alpha).unwrap_or(0xFF),
+            red,
+            green,
+            blue
+        }
+    }
+}
+
+///
+/// The bits of the value under the mask, scaled to 8 bits
+///
+fn channel(value: u32, mask: u32) -> Option<u8> {
+    if mask == 0 {
+        return None;
+    }
+
+    let max = (mask >> mask.trailing_zeros()) as u64;
+    let sample = ((value & mask) >> mask.trailing_zeros()) as u64;
+
+    Some(((sample * 255 + max / 2) / max) as u8)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    bytes.get(offset..(offset + 4))
+        .map(|field| field.iter().rev().fold(0, |value, byte| (value << 8) | *byte as u32))
+        .unwrap_or_default()
+}
+
+impl TryFrom<&[u8]> for Dds {
+    type Error = ImageError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if !value.starts_with(&dds::SIGNATURE) {
+            return Err(ImageError::Decode(String::from("Dds signature is missing.")));
+        }
+
+        let start = dds::SIGNATURE.len();
+
+        let header = value.get(start..(start + dds::HEADER_SIZE))
+            .ok_or_else(|| ImageError::Decode(String::from("Dds header is truncated.")))?;
+
+        let size = read_u32(header, 0);
+
+        if size as usize != dds::HEADER_SIZE {
+            return Err(ImageError::Decode(format!("Dds header size is {size}, but must be {}.", dds::HEADER_SIZE)));
+        }
+
+        let flags = read_u32(header, 4);
+        let height = read_u32(header, 8) as usize;
+        let width = read_u32(header, 12) as usize;
+        let pitch = read_u32(header, 16) as usize;
+        let mipmap_count = read_u32(header, 24).max(1);
+
+        let format = header.get(dds::PIXEL_FORMAT_OFFSET..(dds::PIXEL_FORMAT_OFFSET + dds::PIXEL_FORMAT_SIZE)).unwrap_or_default();
+        let format_flags = read_u32(format, 4);
+
+        let pixel_format = if format_flags & dds::pixel_format::FOUR_CC != 0 {
+            match format.get(8..12).unwrap_or_default() {
+                code if code == dds::four_cc::DXT1 => DdsPixelFormat::Bc1,
+                code if code == dds::four_cc::DXT5 => DdsPixelFormat::Bc3,
+                code => return Err(ImageError::Unsupported(format!("Dds compression '{}' is not supported.", String::from_utf8_lossy(code))))
+            }
+        }
+        else if format_flags & (dds::pixel_format::RGB | dds::pixel_format::LUMINANCE | dds::pixel_format::ALPHA) != 0 {
+            let has_alpha = format_flags & (dds::pixel_format::ALPHA_PIXELS | dds::pixel_format::ALPHA) != 0;
+
+            let masks = DdsMasks {
+                bit_count: read_u32(format, 12),
+                red: read_u32(format, 16),
+                green: read_u32(format, 20),
+                blue: read_u32(format, 24),
+                alpha: if has_alpha { read_u32(format, 28) } else { 0 },
+                luminance: format_flags & dds::pixel_format::LUMINANCE != 0
+            };
+
+            if !matches!(masks.bit_count, 8 | 16 | 24 | 32) {
+                return Err(ImageError::Unsupported(format!("Dds bit count {} is not supported.", masks.bit_count)));
+            }
+
+            DdsPixelFormat::Uncompressed(masks)
+        }
+        else {
+            return Err(ImageError::Unsupported(format!("Dds pixel format flags {format_flags:#x} are not supported.")));
+        };
+
+        match width.checked_mul(height) {
+            Some(pixels) if pixels <= dds::MAX_PIXELS => (),
+            _ => return Err(ImageError::Decode(format!("Dds image {width}x{height} has more than the {} pixels allowed.", dds::MAX_PIXELS)))
+        }
+
+        let data = value.get((start + dds::HEADER_SIZE)..).unwrap_or_default();
+
+        let pixels = match pixel_format {
+            DdsPixelFormat::Uncompressed(masks) => {
+                let min_pitch = (width * masks.bit_count as usize).div_ceil(8);
+
+                //Writers often leave the pitch unset, so only trust it if it can hold a row
+                let pitch = if flags & dds::flags::PITCH != 0 && pitch >= min_pitch { pitch } else { min_pitch };
+
+                read_uncompressed(data, width, height, pitch, &masks)?
+            },
+            DdsPixelFormat::Bc1 => read_blocks::<8>(data, width, height, |block| decode_bc1(block, false))?,
+            DdsPixelFormat::Bc3 => read_blocks::<16>(data, width, height, decode_bc3)?
+        };
+
+        Ok(Self {
+            header: DdsHeader {
+                width,
+                height,
+                mipmap_count,
+                pixel_format
+            },
+            pixels
+        })
+    }
+}
+
+impl TryFrom<Vec<u8>> for Dds {
+    type Error = ImageError;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_slice())
+    }
+}
+
+fn read_uncompressed(data: &[u8], width: usize, height: usize, pitch: usize, masks: &DdsMasks) -> Result<Vec<color::ARGB>, ImageError> {
+    let bytes_per_pixel = masks.bit_count as usize / 8;
+    let size = if height == 0 { 0 } else { pitch * (height - 1) + width * bytes_per_pixel };
+
+    if data.len() < size {
+        return Err(ImageError::Decode(format!("Dds pixels need {size} bytes, but have {}.", data.len())));
+    }
+
+    Ok((0..height)
+        .flat_map(|j| data.get((j * pitch)..(j * pitch + width * bytes_per_pixel)).unwrap_or_default().chunks_exact(bytes_per_pixel))
+        .map(|pixel| masks.color(pixel.iter().rev().fold(0, |value, byte| (value << 8) | *byte as u32)))
+        .collect())
+}
+
+///
+/// Decode every block of a compressed texture, in rows from the top. Blocks
+/// at the right and bottom edges may hang past the image, and are cut short.
+///
+fn read_blocks<const N: usize>(data: &[u8], width: usize, height: usize, decode: impl Fn(&[u8; N]) -> [color::ARGB; 16]) -> Result<Vec<color::ARGB>, ImageError> {
+    let columns = width.div_ceil(dds::BLOCK_SIZE);
+    let rows = height.div_ceil(dds::BLOCK_SIZE);
+
+    if data.len() < columns * rows * N {
+        return Err(ImageError::Decode(format!("Dds blocks need {} bytes, but have {}.", columns * rows * N, data.len())));
+    }
+
+    let mut pixels = vec![color::ARGB::default(); width * height];
+
+    for (n, block) in data.chunks_exact(N).take(columns * rows).enumerate() {
+        let Ok(block) = <&[u8; N]>::try_from(block) else {
+            continue;
+        };
+
+        let (x, y) = ((n % columns) * dds::BLOCK_SIZE, (n / columns) * dds::BLOCK_SIZE);
+
+        for (i, color) in decode(block).into_iter().enumerate() {
+            let (column, row) = (x + i % dds::BLOCK_SIZE, y + i / dds::BLOCK_SIZE);
+
+            if column < width {
+                if let Some(pixel) = pixels.get_mut(row * width + column) {
+                    *pixel = color;
+                }
+            }
+        }
+    }
+
+    Ok(pixels)
+}
+
+///
+/// Expand a 5:6:5 color to 8 bits a channel
+///
+fn rgb565(value: u16) -> color::ARGB {
+    let (red, green, blue) = ((value >> 11) as u8, ((value >> 5) & 0x3F) as u8, (value & 0x1F) as u8);
+
+    color::ARGB {
+        alpha: 0xFF,
+        red: (red << 3) | (red >> 2),
+        green: (green << 2) | (green >> 4),
+        blue: (blue << 3) | (blue >> 2)
+    }
+}
+
+///
+/// A weighted average of two colors
+///
+fn mix(a: color::ARGB, a_weight: u16, b: color::ARGB, b_weight: u16) -> color::ARGB {
+    let total = a_weight + b_weight;
+    let blend = |a: u8, b: u8| ((a as u16 * a_weight + b as u16 * b_weight) / total) as u8;
+
+    color::ARGB {
+        alpha: 0xFF,
+        red: blend(a.red, b.red),
+        green: blend(a.green, b.green),
+        blue: blend(a.blue, b.blue)
+    }
+}
+
+///
+/// Decode a block of two 5:6:5 colors and a 2-bit index per pixel. In a BC1 texture, a
+/// first color no greater than the second marks a block with a transparent fourth color.
+///
+fn decode_bc1(block: &[u8; 8], always_opaque: bool) -> [color::ARGB; 16] {
+    let [c0_low, c0_high, c1_low, c1_high, i0, i1, i2, i3] = *block;
+    let (c0, c1) = (u16::from_le_bytes([c0_low, c0_high]), u16::from_le_bytes([c1_low, c1_high]));
+    let indices = u32::from_le_bytes([i0, i1, i2, i3]);
+    let (first, second) = (rgb565(c0), rgb565(c1));
+
+    let palette = if c0 > c1 || always_opaque {
+        [first, second, mix(first, 2, second, 1), mix(first, 1, second, 2)]
+    }
+    else {
+        [first, second, mix(first, 1, second, 1), color::ARGB::default()]
+    };
+
+    core::array::from_fn(|i| palette.get(((indices >> (2 * i)) & 0x3) as usize).copied().unwrap_or_default())
+}
+
+///
+/// Decode a block of interpolated alpha with a 3-bit index per pixel, then a BC1 color block
+///
+fn decode_bc3(block: &[u8; 16]) -> [color::ARGB; 16] {
+    let [a0, a1, b0, b1, b2, b3, b4, b5, colors @ ..] = *block;
+    let (a0, a1) = (a0 as u16, a1 as u16);
+
+    //Six steps between the two values, or four and then fully transparent and opaque
+    let alphas: [u8; 8] = core::array::from_fn(|i| match i as u16 {
+        0 => a0 as u8,
+        1 => a1 as u8,
+        i if a0 > a1 => (((8 - i) * a0 + (i - 1) * a1) / 7) as u8,
+        6 => 0,
+        7 => 0xFF,
+        i => (((6 - i) * a0 + (i - 1) * a1) / 5) as u8
+    });
+
+    let indices = u64::from_le_bytes([b0, b1, b2, b3, b4, b5, 0, 0]);
+
+    let mut pixels = decode_bc1(&colors, true);
+
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        pixel.alpha = alphas.get(((indices >> (3 * i)) & 0x7) as usize).copied().unwrap_or_default();
+    }
+
+    pixels
+}
+
+impl ConvertableFrom<Dds> for Image {
+    type Options = ();
+    type Error = ImageError;
+
+    fn try_convert_from(value: Dds, _options: Self::Options) -> Result<Self, Self::Error> {
+        if value.pixels.len() != value.header.width * value.header.height {
+            return Err(ImageError::Decode(format!("A {}x{} dds needs {} pixels, but has {}.",
+                value.header.width, value.header.height, value.header.width * value.header.height, value.pixels.len())));
+        }
+
+        Image::try_new_pixels(value.header.width, value.header.height, value.pixels)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,135 @@
+use super::*;
+use alloc::{string::ToString, vec};
+
+///
+/// A dds header, with the pixel format given as its flags, four-cc, bit count and masks
+///
+fn header(width: u32, height: u32, pitch: Option<u32>, format: [u32; 7]) -> Vec<u8> {
+    let mut fields = [0_u32; dds::HEADER_SIZE / 4];
+    fields[0] = dds::HEADER_SIZE as u32;
+    fields[1] = 0x1007 | if pitch.is_some() { dds::flags::PITCH } else { 0 };
+    fields[2] = height;
+    fields[3] = width;
+    fields[4] = pitch.unwrap_or_default();
+    fields[18] = dds::PIXEL_FORMAT_SIZE as u32;
+    fields[19..26].copy_from_slice(&format);
+
+    dds::SIGNATURE.iter().copied()
+        .chain(fields.iter().flat_map(|field| field.to_le_bytes()))
+        .collect()
+}
+
+fn four_cc(code: [u8; 4]) -> [u32; 7] {
+    [dds::pixel_format::FOUR_CC, u32::from_le_bytes(code), 0, 0, 0, 0, 0]
+}
+
+fn argb(alpha: u8, red: u8, green: u8, blue: u8) -> color::ARGB {
+    color::ARGB { alpha, red, green, blue }
+}
+
+#[test]
+fn dds_reads_uncompressed() -> Result<(), String> {
+    //A8R8G8B8, with each row padded to 12 bytes
+    let mut bytes = header(2, 2, Some(12), [dds::pixel_format::RGB | dds::pixel_format::ALPHA_PIXELS, 0, 32, 0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000]);
+    bytes.extend([0x30, 0x20, 0x10, 0xFF, 0, 0, 0xFF, 0x80, 0, 0, 0, 0]);
+    bytes.extend([0, 0xFF, 0, 0x40, 0xFF, 0xFF, 0xFF, 0]);
+
+    let image = Image::try_convert_from(Dds::try_from(bytes)?, ())?;
+    let expected = [argb(0xFF, 0x10, 0x20, 0x30), argb(0x80, 0xFF, 0, 0), argb(0x40, 0, 0xFF, 0), argb(0, 0xFF, 0xFF, 0xFF)];
+
+    if image.iter().flatten().copied().collect::<Vec<_>>() != expected {
+        return Err(format!("Unexpected A8R8G8B8 pixels {image:?}."));
+    }
+
+    //R5G6B5, without alpha
+    let mut bytes = header(2, 1, None, [dds::pixel_format::RGB, 0, 16, 0xF800, 0x07E0, 0x001F, 0]);
+    bytes.extend(0xF800_u16.to_le_bytes());
+    bytes.extend(0x07FF_u16.to_le_bytes());
+
+    let image = Image::try_convert_from(Dds::try_from(bytes)?, ())?;
+
+    if image.get(0, 0) != Some(argb(0xFF, 0xFF, 0, 0)) || image.get(1, 0) != Some(argb(0xFF, 0, 0xFF, 0xFF)) {
+        return Err(format!("Unexpected R5G6B5 pixels {image:?}."));
+    }
+
+    //L8
+    let mut bytes = header(1, 1, None, [dds::pixel_format::LUMINANCE, 0, 8, 0xFF, 0, 0, 0]);
+    bytes.push(0x7F);
+
+    match Image::try_convert_from(Dds::try_from(bytes)?, ())?.get(0, 0) {
+        Some(pixel) if pixel == argb(0xFF, 0x7F, 0x7F, 0x7F) => Ok(()),
+        other => Err(format!("Unexpected L8 pixel {other:?}."))
+    }
+}
+
+#[test]
+fn dds_decodes_blocks() -> Result<(), String> {
+    let (red, blue) = (0xF800_u16, 0x001F_u16);
+
+    //A 5x3 image takes two blocks, cut short at the edges. The first block has four
+    //colors, and the second three and transparency, each index repeating across rows.
+    let mut bytes = header(5, 3, None, four_cc(dds::four_cc::DXT1));
+    bytes.extend(red.to_le_bytes().into_iter().chain(blue.to_le_bytes()).chain([0xE4; 4]));
+    bytes.extend(blue.to_le_bytes().into_iter().chain(red.to_le_bytes()).chain([0xE4; 4]));
+
+    let dds = Dds::try_from(bytes)?;
+
+    if dds.header.pixel_format != DdsPixelFormat::Bc1 {
+        return Err(format!("Expected BC1, got {:?}.", dds.header.pixel_format));
+    }
+
+    let image = Image::try_convert_from(dds, ())?;
+    let row = [argb(0xFF, 0xFF, 0, 0), argb(0xFF, 0, 0, 0xFF), argb(0xFF, 170, 0, 85), argb(0xFF, 85, 0, 170), argb(0xFF, 0, 0, 0xFF)];
+
+    for j in 0..3 {
+        if image.row(j) != row {
+            return Err(format!("Row {j} should be {row:?}, got {:?}.", image.row(j)));
+        }
+    }
+
+    let second = Image::try_convert_from(Dds::try_from([header(4, 1, None, four_cc(dds::four_cc::DXT1)), blue.to_le_bytes().into_iter().chain(red.to_le_bytes()).chain([0xE4; 4]).collect()].concat())?, ())?;
+
+    if second.row(0) != [argb(0xFF, 0, 0, 0xFF), argb(0xFF, 0xFF, 0, 0), argb(0xFF, 127, 0, 127), color::ARGB::default()] {
+        return Err(format!("Unexpected three color block {:?}.", second.row(0)));
+    }
+
+    //BC3 alpha of the third of eight steps, over a color block which is always opaque
+    let alpha_indices = (0..16).fold(0_u64, |indices, i| indices | (2 << (3 * i)));
+
+    let mut bytes = header(4, 4, None, four_cc(dds::four_cc::DXT5));
+    bytes.extend([0xFF, 0]);
+    bytes.extend(&alpha_indices.to_le_bytes()[..6]);
+    bytes.extend(blue.to_le_bytes().into_iter().chain(red.to_le_bytes()).chain([0xFF; 4]));
+
+    let image = Image::try_convert_from(Dds::try_from(bytes)?, ())?;
+
+    if image.iter().flatten().any(|pixel| *pixel != argb(218, 170, 0, 85)) {
+        return Err(format!("Unexpected BC3 pixels {:?}.", image.row(0)));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn dds_rejects_malformed() -> Result<(), String> {
+    let mut bad_size = header(1, 1, None, four_cc(dds::four_cc::DXT1));
+    bad_size[4] = 100;
+
+    let cases: [(Vec<u8>, &str); 5] = [
+        (b"DDX ".to_vec(), "signature"),
+        (header(1, 1, None, four_cc(dds::four_cc::DXT1))[..64].to_vec(), "truncated"),
+        (bad_size, "header size"),
+        (header(1, 1, None, four_cc(*b"DX10")), "DX10"),
+        ([header(8, 4, None, four_cc(dds::four_cc::DXT5)), vec![0; 16]].concat(), "need 32 bytes")
+    ];
+
+    for (bytes, expected) in cases {
+        match Dds::try_from(bytes.as_slice()) {
+            Ok(_) => return Err(format!("Expected an error mentioning {expected}.")),
+            Err(err) if !err.to_string().contains(expected) => return Err(format!("Expected an error mentioning {expected}, got '{err}'.")),
+            Err(_) => ()
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
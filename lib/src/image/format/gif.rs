@@ -0,0 +1,876 @@
+//Decoding must not panic on any input, so only tests may index or unwrap
+#![deny(clippy::indexing_slicing, clippy::unwrap_used, clippy::expect_used)]
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing, clippy::unwrap_used)]
+mod tests;
+
+use alloc::{format, string::String, vec, vec::Vec};
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+use crate::{color, image, log, convert::ConvertableFrom, error::ImageError};
+use image::Image;
+use crate::constants::gif;
+use super::bitmap::ImageConvertData;
+#[cfg(feature = "std")]
+use image::animation::{AnimatedImage, Disposal, Frame};
+
+///
+/// A image in gif format, with the pixel data of each of its frames
+/// decompressed and deinterlaced. Comments and extensions of other
+/// applications are skipped.
+/// Gif format:
+/// https://www.w3.org/Graphics/GIF/spec-gif89a.txt
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Gif {
+    pub header: GifHeader,
+    pub global_color_table: Option<Vec<color::ARGB>>,
+    pub frames: Vec<GifFrame>,
+    ///
+    /// How many times to repeat the frames after the first time, or 0 to repeat
+    /// forever, from the NETSCAPE2.0 extension. Without one, they are shown once.
+    ///
+    pub loop_count: Option<u16>
+}
+
+///
+/// The signature and logical screen descriptor, which describe
+/// the canvas every frame is drawn on
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GifHeader {
+    ///
+    /// Whether the signature is GIF89a rather than GIF87a
+    ///
+    pub is_89a: bool,
+    pub width: u16,
+    pub height: u16,
+    ///
+    /// Index in the global color table of the color behind the frames
+    ///
+    pub background_index: u8,
+    pub pixel_aspect_ratio: u8
+}
+
+///
+/// One image of a gif, placed on the canvas at (left, top)
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GifFrame {
+    pub left: u16,
+    pub top: u16,
+    pub width: u16,
+    pub height: u16,
+    ///
+    /// Whether the rows were stored interlaced. They are always kept top to bottom.
+    ///
+    pub interlaced: bool,
+    ///
+    /// Colors used by this frame instead of the global color table
+    ///
+    pub local_color_table: Option<Vec<color::ARGB>>,
+    ///
+    /// The graphic control extension before the frame, if there was one
+    ///
+    pub control: Option<GraphicControl>,
+    ///
+    /// Index in the color table of each pixel, in rows from the top
+    ///
+    pub indices: Vec<u8>
+}
+
+///
+/// How a frame is shown, from the graphic control extension before it
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GraphicControl {
+    ///
+    /// What happens to the frame once it has been shown: 0 or 1 to leave it,
+    /// 2 to clear it to the background, and 3 to restore what was behind it
+    ///
+    pub disposal: u8,
+    ///
+    /// How long to show the frame, in hundredths of a second
+    ///
+    pub delay: u16,
+    ///
+    /// Index of the color which is left transparent
+    ///
+    pub transparent_index: Option<u8>,
+    pub user_input: bool
+}
+
+impl GifFrame {
+    ///
+    /// The colors this frame's indices refer to
+    ///
+    pub fn color_table<'a>(&'a self, gif: &'a Gif) -> &'a [color::ARGB] {
+        self.local_color_table.as_deref()
+            .or(gif.global_color_table.as_deref())
+            .unwrap_or_default()
+    }
+
+    pub fn transparent_index(&self) -> Option<u8> {
+        self.control.and_then(|control| control.transparent_index)
+    }
+}
+
+impl TryFrom<&[u8]> for Gif {
+    type Error = ImageError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let mut reader = Reader { bytes: value };
+
+        let signature = reader.take(6)?;
+
+        let is_89a = if signature == gif::SIGNATURE_89A {
+            true
+        }
+        else if signature == gif::SIGNATURE_87A {
+            false
+        }
+        else {
+            return Err(ImageError::Decode(String::from("Gif signature is missing.")));
+        };
+
+        let [w0, w1, h0, h1, packed, background_index, pixel_aspect_ratio] = *reader.take(gif::SCREEN_DESCRIPTOR_SIZE)? else {
+            return Err(ImageError::Decode(String::from("Gif screen descriptor is truncated.")));
+        };
+
+        let header = GifHeader {
+            is_89a,
+            width: u16::from_le_bytes([w0, w1]),
+            height: u16::from_le_bytes([h0, h1]),
+            background_index,
+            pixel_aspect_ratio
+        };
+
+        if header.width as usize * header.height as usize > gif::MAX_PIXELS {
+            return Err(ImageError::Decode(format!("Gif screen {}x{} has more than the {} pixels allowed.", header.width, header.height, gif::MAX_PIXELS)));
+        }
+
+        let global_color_table = read_color_table(&mut reader, packed)?;
+
+        let mut frames = Vec::new();
+        let mut control = None;
+        let mut loop_count = None;
+
+        loop {
+            //Plenty of gifs end without their trailer; once there is a frame to show, accept them
+            let Ok(block) = reader.byte() else {
+                if frames.is_empty() {
+                    return Err(ImageError::Decode(String::from("Gif ends before its first frame.")));
+                }
+
+                log::warn(format_args!("Gif is missing its trailer."));
+                break;
+            };
+
+            match block {
+                gif::block::EXTENSION => {
+                    let label = reader.byte()?;
+                    let data = reader.sub_blocks()?;
+
+                    match (label, data.as_slice()) {
+                        (gif::extension::GRAPHIC_CONTROL, data) => control = Some(parse_control(data)?),
+                        (gif::extension::APPLICATION, [application @ .., 1, l0, l1]) if *application == gif::NETSCAPE => {
+                            loop_count = Some(u16::from_le_bytes([*l0, *l1]));
+                        },
+                        _ => ()
+                    }
+                },
+                gif::block::IMAGE => {
+                    let frame = read_frame(&mut reader, control.take())?;
+
+                    //Frames hanging off of the canvas are clipped when drawn, so the gif is still usable
+                    if frame.left as usize + frame.width as usize > header.width as usize
+                        || frame.top as usize + frame.height as usize > header.height as usize {
+                        log::warn(format_args!("Frame {} extends past the {}x{} canvas.", frames.len(), header.width, header.height));
+                    }
+
+                    frames.push(frame);
+                },
+                gif::block::TRAILER => break,
+                other => return Err(ImageError::Decode(format!("Unknown gif block {other:#04x}.")))
+            }
+        }
+
+        Ok(Self {
+            header,
+            global_color_table,
+            frames,
+            loop_count
+        })
+    }
+}
+
+impl TryFrom<Vec<u8>> for Gif {
+    type Error = ImageError;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from(&value[..])
+    }
+}
+
+///
+/// Read the color table flagged in a packed field, if there is one
+///
+fn read_color_table(reader: &mut Reader, packed: u8) -> Result<Option<Vec<color::ARGB>>, ImageError> {
+    if packed & 0x80 == 0 {
+        return Ok(None);
+    }
+
+    let size = 2_usize << (packed & 0x07);
+
+    let colors = reader.take(3 * size)?
+        .chunks_exact(3)
+        .map(|rgb| match *rgb {
+            [red, green, blue] => color::ARGB { alpha: 0xFF, red, green, blue },
+            _ => color::ARGB::default()
+        })
+        .collect();
+
+    Ok(Some(colors))
+}
+
+fn parse_control(data: &[u8]) -> Result<GraphicControl, ImageError> {
+    let [packed, d0, d1, transparent_index, ..] = *data else {
+        return Err(ImageError::Decode(format!("Graphic control extension is {} bytes, but should be 4.", data.len())));
+    };
+
+    Ok(GraphicControl {
+        disposal: (packed >> 2) & 0x07,
+        delay: u16::from_le_bytes([d0, d1]),
+        transparent_index: (packed & 0x01 != 0).then_some(transparent_index),
+        user_input: packed & 0x02 != 0
+    })
+}
+
+fn read_frame(reader: &mut Reader, control: Option<GraphicControl>) -> Result<GifFrame, ImageError> {
+    let [l0, l1, t0, t1, w0, w1, h0, h1, packed] = *reader.take(gif::IMAGE_DESCRIPTOR_SIZE)? else {
+        return Err(ImageError::Decode(String::from("Gif image descriptor is truncated.")));
+    };
+
+    let width = u16::from_le_bytes([w0, w1]);
+    let height = u16::from_le_bytes([h0, h1]);
+    let interlaced = packed & 0x40 != 0;
+
+    if width as usize * height as usize > gif::MAX_PIXELS {
+        return Err(ImageError::Decode(format!("Gif frame {width}x{height} has more than the {} pixels allowed.", gif::MAX_PIXELS)));
+    }
+
+    let local_color_table = read_color_table(reader, packed)?;
+
+    let min_code_size = reader.byte()?;
+    let data = reader.sub_blocks()?;
+
+    let count = width as usize * height as usize;
+
+    //Each code adds at most a string as long as the code table holds, so the data can only
+    //make so many pixels; rather than pad out the rest, a frame larger than that is rejected
+    let most_pixels = data.len() * 8 / (min_code_size as usize + 1) * gif::MAX_CODES;
+
+    if count > most_pixels {
+        return Err(ImageError::Decode(format!("Gif frame {width}x{height} has only {} bytes of data, too few for its {count} pixels.", data.len())));
+    }
+
+    let mut indices = lzw_decode(min_code_size, &data, count)?;
+
+    if indices.len() < count {
+        log::warn(format_args!("Gif frame is missing {} pixels, which are left as index 0.", count - indices.len()));
+        indices.resize(count, 0);
+    }
+
+    if interlaced {
+        indices = deinterlace(&indices, width as usize, height as usize);
+    }
+
+    Ok(GifFrame {
+        left: u16::from_le_bytes([l0, l1]),
+        top: u16::from_le_bytes([t0, t1]),
+        width,
+        height,
+        interlaced,
+        local_color_table,
+        control,
+        indices
+    })
+}
+
+///
+/// Reorder rows stored in the four passes of an interlaced gif to run top to bottom
+///
+fn deinterlace(indices: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let order = [(0, 8), (4, 8), (2, 4), (1, 2)].into_iter()
+        .flat_map(|(start, step)| (start..height).step_by(step));
+
+    let mut rows = vec![0_u8; indices.len()];
+
+    for (stored, row) in order.enumerate() {
+        let source = indices.get((stored * width)..((stored + 1) * width));
+        let target = rows.get_mut((row * width)..((row + 1) * width));
+
+        if let (Some(source), Some(target)) = (source, target) {
+            target.copy_from_slice(source);
+        }
+    }
+
+    rows
+}
+
+///
+/// Reorder rows running top to bottom into the four passes of an interlaced gif
+///
+fn interlace(indices: &[u8], width: usize, height: usize) -> Vec<u8> {
+    [(0, 8), (4, 8), (2, 4), (1, 2)].into_iter()
+        .flat_map(|(start, step)| (start..height).step_by(step))
+        .flat_map(|row| indices.get((row * width)..((row + 1) * width)).unwrap_or_default().iter().copied())
+        .collect()
+}
+
+///
+/// Decompress variable-length LZW codes, as packed by gif, into at most limit indices
+///
+fn lzw_decode(min_code_size: u8, data: &[u8], limit: usize) -> Result<Vec<u8>, ImageError> {
+    if !(2..gif::MAX_CODE_SIZE).contains(&min_code_size) {
+        return Err(ImageError::Decode(format!("LZW minimum code size {min_code_size} is not valid.")));
+    }
+
+    let clear = 1_u16 << min_code_size;
+    let end = clear + 1;
+
+    //Each code is the code before it, with one more index on the end
+    let mut prefixes = vec![0_u16; gif::MAX_CODES];
+    let mut suffixes: Vec<u8> = (0..gif::MAX_CODES).map(|code| code as u8).collect();
+    let mut firsts = suffixes.clone();
+
+    let mut next = clear + 2;
+    let mut code_size = min_code_size + 1;
+    let mut previous: Option<u16> = None;
+
+    //Don't trust the frame size alone for the reservation; the output still grows past it if the codes do
+    let mut out: Vec<u8> = Vec::with_capacity(limit.min(data.len() * 8));
+    let mut string: Vec<u8> = Vec::new();
+    let mut bits = BitReader { bytes: data, position: 0 };
+
+    while out.len() < limit {
+        //Some encoders leave off the end code
+        let Some(code) = bits.read(code_size) else {
+            break;
+        };
+
+        if code == clear {
+            next = clear + 2;
+            code_size = min_code_size + 1;
+            previous = None;
+            continue;
+        }
+
+        if code == end {
+            break;
+        }
+
+        let Some(last) = previous else {
+            if code >= clear {
+                return Err(ImageError::Decode(format!("LZW code {code} comes before any table entry.")));
+            }
+
+            out.push(code as u8);
+            previous = Some(code);
+            continue;
+        };
+
+        let last_first = firsts.get(last as usize).copied().unwrap_or_default();
+
+        //A code one past the table is the last string followed by its own first index
+        let first = match code {
+            code if code < next => firsts.get(code as usize).copied().unwrap_or_default(),
+            code if code == next => last_first,
+            code => return Err(ImageError::Decode(format!("LZW code {code} is past the end of the table.")))
+        };
+
+        if (next as usize) < gif::MAX_CODES {
+            if let (Some(prefix), Some(suffix), Some(first_of)) = (prefixes.get_mut(next as usize), suffixes.get_mut(next as usize), firsts.get_mut(next as usize)) {
+                *prefix = last;
+                *suffix = first;
+                *first_of = last_first;
+            }
+
+            next += 1;
+
+            if next as usize == 1 << code_size && code_size < gif::MAX_CODE_SIZE {
+                code_size += 1;
+            }
+        }
+
+        //Walk back through the prefixes, collecting the string backwards
+        string.clear();
+        let mut current = code;
+
+        while current >= clear {
+            string.push(suffixes.get(current as usize).copied().unwrap_or_default());
+            current = prefixes.get(current as usize).copied().unwrap_or_default();
+
+            if string.len() > gif::MAX_CODES {
+                return Err(ImageError::Decode(String::from("LZW code table has a cycle.")));
+            }
+        }
+
+        string.push(current as u8);
+        out.extend(string.iter().rev());
+        previous = Some(code);
+    }
+
+    out.truncate(limit);
+    Ok(out)
+}
+
+///
+/// Compress indices into variable-length LZW codes, packed as gif
+/// expects, clearing the code table whenever it fills
+///
+fn lzw_encode(min_code_size: u8, indices: &[u8]) -> Vec<u8> {
+    let clear = 1_u16 << min_code_size;
+    let mut bits = BitWriter::default();
+
+    //Each string in the table, as the code of its prefix and its last index
+    let mut table: BTreeMap<(u16, u8), u16> = BTreeMap::new();
+    let mut next = clear + 2;
+    let mut code_size = min_code_size + 1;
+
+    bits.write(clear, code_size);
+
+    let mut iter = indices.iter();
+
+    let Some(first) = iter.next() else {
+        bits.write(clear + 1, code_size);
+        return bits.bytes;
+    };
+
+    let mut current = *first as u16;
+
+    for index in iter {
+        if let Some(code) = table.get(&(current, *index)) {
+            current = *code;
+            continue;
+        }
+
+        bits.write(current, code_size);
+
+        if next as usize == gif::MAX_CODES {
+            bits.write(clear, code_size);
+            table.clear();
+            next = clear + 2;
+            code_size = min_code_size + 1;
+        }
+        else {
+            table.insert((current, *index), next);
+            next += 1;
+
+            //The decoder adds each entry a code later, so only needs the larger size once past it
+            if next as usize > 1 << code_size && code_size < gif::MAX_CODE_SIZE {
+                code_size += 1;
+            }
+        }
+
+        current = *index as u16;
+    }
+
+    bits.write(current, code_size);
+    bits.write(clear + 1, code_size);
+
+    bits.bytes
+}
+
+///
+/// Packs codes into bytes, least significant bit first
+///
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit: usize
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, count: u8) {
+        for shift in 0..count {
+            if self.bit.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+
+            if let Some(byte) = self.bytes.last_mut() {
+                *byte |= (((code >> shift) & 1) as u8) << (self.bit % 8);
+            }
+
+            self.bit += 1;
+        }
+    }
+}
+
+///
+/// Reads codes from the bytes, least significant bit first
+///
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, count: u8) -> Option<u16> {
+        let mut value = 0_u16;
+
+        for shift in 0..count {
+            let byte = self.bytes.get(self.position / 8)?;
+            value |= (((byte >> (self.position % 8)) & 1) as u16) << shift;
+            self.position += 1;
+        }
+
+        Some(value)
+    }
+}
+
+///
+/// Reads the blocks of a gif in order
+///
+struct Reader<'a> {
+    bytes: &'a [u8]
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ImageError> {
+        let taken = self.bytes.get(..count)
+            .ok_or_else(|| ImageError::Decode(String::from("Gif is truncated.")))?;
+
+        self.bytes = self.bytes.get(count..).unwrap_or_default();
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, ImageError> {
+        self.take(1).map(|bytes| bytes.first().copied().unwrap_or_default())
+    }
+
+    ///
+    /// Read a run of sub-blocks, each a byte of length then that many bytes,
+    /// up to an empty one, as one run of data
+    ///
+    fn sub_blocks(&mut self) -> Result<Vec<u8>, ImageError> {
+        let mut data = Vec::new();
+
+        loop {
+            let length = self.byte()? as usize;
+
+            if length == 0 {
+                return Ok(data);
+            }
+
+            data.extend_from_slice(self.take(length)?);
+        }
+    }
+}
+
+impl ConvertableFrom<Gif> for Image {
+    type Options = ImageConvertData;
+    type Error = ImageError;
+
+    ///
+    /// Draw the first frame onto a transparent canvas
+    ///
+    fn try_convert_from(value: Gif, options: Self::Options) -> Result<Self, Self::Error> {
+        let width = value.header.width as usize;
+        let height = value.header.height as usize;
+        let mut pixels = vec![color::ARGB::default(); width * height];
+
+        if let Some(frame) = value.frames.first() {
+            let colors = frame_colors(&value, frame, &options)?;
+
+            for (j, row) in colors.chunks_exact(frame.width.max(1) as usize).enumerate() {
+                let y = frame.top as usize + j;
+
+                for (i, color) in row.iter().enumerate() {
+                    let x = frame.left as usize + i;
+
+                    if x >= width || y >= height {
+                        continue;
+                    }
+
+                    if let (Some(color), Some(pixel)) = (color, pixels.get_mut(y * width + x)) {
+                        *pixel = *color;
+                    }
+                }
+            }
+        }
+
+        Image::try_new_pixels(width, height, pixels)
+    }
+}
+
+///
+/// The color of each pixel of a frame, or None where it is transparent
+///
+fn frame_colors(gif: &Gif, frame: &GifFrame, options: &ImageConvertData) -> Result<Vec<Option<color::ARGB>>, ImageError> {
+    let table = frame.color_table(gif);
+    let transparent = frame.transparent_index();
+    let mut out_of_range = 0_usize;
+
+    let colors = frame.indices.iter()
+        .enumerate()
+        .map(|(index, palette_index)| match (table.get(*palette_index as usize), options.palette_fallback) {
+            _ if Some(*palette_index) == transparent => Ok(None),
+            (Some(color), _) => Ok(Some(*color)),
+            (None, Some(fallback)) => {
+                out_of_range += 1;
+                Ok(Some(fallback))
+            },
+            (None, None) => Err(ImageError::Decode(format!(
+                "Pixel {},{} has palette index {palette_index}, but the color table only has {} colors.",
+                index % frame.width.max(1) as usize, index / frame.width.max(1) as usize, table.len()
+            )))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if out_of_range > 0 {
+        log::warn(format_args!("Gif has {out_of_range} pixels with palette indices outside of the color table, which were replaced with the fallback color."));
+    }
+
+    Ok(colors)
+}
+
+///
+/// Write the gif. Color tables are padded to a power of two, and frames
+/// marked as interlaced are stored interlaced.
+///
+impl TryFrom<Gif> for Vec<u8> {
+    type Error = ImageError;
+
+    fn try_from(value: Gif) -> Result<Self, Self::Error> {
+        let mut bytes = gif::SIGNATURE_89A.to_vec();
+
+        bytes.extend(value.header.width.to_le_bytes());
+        bytes.extend(value.header.height.to_le_bytes());
+        bytes.push(color_table_flags(value.global_color_table.as_deref())?);
+        bytes.extend([value.header.background_index, value.header.pixel_aspect_ratio]);
+        write_color_table(&mut bytes, value.global_color_table.as_deref());
+
+        if let Some(loop_count) = value.loop_count {
+            bytes.extend([gif::block::EXTENSION, gif::extension::APPLICATION, gif::NETSCAPE.len() as u8]);
+            bytes.extend(gif::NETSCAPE);
+            bytes.extend([3, 1]);
+            bytes.extend(loop_count.to_le_bytes());
+            bytes.push(0);
+        }
+
+        for frame in &value.frames {
+            if let Some(control) = frame.control {
+                let packed = ((control.disposal & 0x07) << 2)
+                    | ((control.user_input as u8) << 1)
+                    | control.transparent_index.is_some() as u8;
+
+                bytes.extend([gif::block::EXTENSION, gif::extension::GRAPHIC_CONTROL, 4, packed]);
+                bytes.extend(control.delay.to_le_bytes());
+                bytes.extend([control.transparent_index.unwrap_or_default(), 0]);
+            }
+
+            if frame.indices.len() != frame.width as usize * frame.height as usize {
+                return Err(ImageError::Encode(format!("Frame has {} indices, but is {}x{}.", frame.indices.len(), frame.width, frame.height)));
+            }
+
+            bytes.push(gif::block::IMAGE);
+
+            for field in [frame.left, frame.top, frame.width, frame.height] {
+                bytes.extend(field.to_le_bytes());
+            }
+
+            bytes.push(color_table_flags(frame.local_color_table.as_deref())? | ((frame.interlaced as u8) << 6));
+            write_color_table(&mut bytes, frame.local_color_table.as_deref());
+
+            //Codes need at least one bit more than the largest index, and never fewer than 3
+            let largest = frame.indices.iter().copied().max().unwrap_or_default();
+            let min_code_size = (u8::BITS - largest.leading_zeros()).max(2) as u8;
+
+            let data = if frame.interlaced {
+                lzw_encode(min_code_size, &interlace(&frame.indices, frame.width as usize, frame.height as usize))
+            }
+            else {
+                lzw_encode(min_code_size, &frame.indices)
+            };
+
+            bytes.push(min_code_size);
+
+            for block in data.chunks(u8::MAX as usize) {
+                bytes.push(block.len() as u8);
+                bytes.extend_from_slice(block);
+            }
+
+            bytes.push(0);
+        }
+
+        bytes.push(gif::block::TRAILER);
+
+        Ok(bytes)
+    }
+}
+
+///
+/// The packed flags for a color table: whether there is one,
+/// and its size, as the power of two it is padded to
+///
+fn color_table_flags(table: Option<&[color::ARGB]>) -> Result<u8, ImageError> {
+    match table {
+        None => Ok(0),
+        Some(colors) if colors.len() > 256 => Err(ImageError::Encode(format!("Color table has {} colors, but can only hold 256.", colors.len()))),
+        Some(colors) => Ok(0x80 | (colors.len().max(2).next_power_of_two().trailing_zeros() - 1) as u8)
+    }
+}
+
+fn write_color_table(bytes: &mut Vec<u8>, table: Option<&[color::ARGB]>) {
+    let Some(colors) = table else {
+        return;
+    };
+
+    let size = colors.len().max(2).next_power_of_two();
+
+    bytes.extend(colors.iter().flat_map(|color| [color.red, color.green, color.blue]));
+    bytes.extend(core::iter::repeat_n(0, 3 * (size - colors.len())));
+}
+
+///
+/// Each frame of the gif, with its own size, offset, delay and disposal. Frames
+/// with no delay keep it, though viewers tend to show them for 100ms or so.
+///
+#[cfg(feature = "std")]
+impl ConvertableFrom<Gif> for AnimatedImage {
+    type Options = ImageConvertData;
+    type Error = ImageError;
+
+    fn try_convert_from(value: Gif, options: Self::Options) -> Result<Self, Self::Error> {
+        let mut animation = AnimatedImage::new(value.header.width as usize, value.header.height as usize)
+            .with_loop_count(match value.loop_count {
+                None => 1,
+                Some(0) => 0,
+                Some(repeats) => repeats as usize + 1
+            });
+
+        for frame in &value.frames {
+            let pixels = frame_colors(&value, frame, &options)?
+                .into_iter()
+                .map(Option::unwrap_or_default)
+                .collect();
+
+            let control = frame.control.unwrap_or_default();
+
+            let disposal = match control.disposal {
+                2 => Disposal::Background,
+                3 => Disposal::Previous,
+                _ => Disposal::Keep
+            };
+
+            animation.push(Frame::new(Image::try_new_pixels(frame.width as usize, frame.height as usize, pixels)?, Duration::from_millis(control.delay as u64 * 10))
+                .with_disposal(disposal)
+                .with_offset(frame.left as usize, frame.top as usize));
+        }
+
+        Ok(animation)
+    }
+}
+
+///
+/// A gif of the animation, with its own color table for each frame. Pixels less
+/// than half opaque are transparent, and the rest are opaque. Fails if a frame
+/// has more than 256 colors, counting transparency as one, as gif can't hold them.
+///
+#[cfg(feature = "std")]
+impl ConvertableFrom<AnimatedImage> for Gif {
+    type Options = ();
+    type Error = ImageError;
+
+    fn try_convert_from(value: AnimatedImage, _options: Self::Options) -> Result<Self, Self::Error> {
+        let dimension = |size: usize, name: &str| u16::try_from(size)
+            .map_err(|_| ImageError::Encode(format!("Gif {name} {size} is larger than the {} allowed.", u16::MAX)));
+
+        let header = GifHeader {
+            is_89a: true,
+            width: dimension(value.width(), "width")?,
+            height: dimension(value.height(), "height")?,
+            background_index: 0,
+            pixel_aspect_ratio: 0
+        };
+
+        let frames = value.iter()
+            .enumerate()
+            .map(|(number, frame)| {
+                let mut table: Vec<color::ARGB> = Vec::new();
+                let mut lookup: BTreeMap<u32, u8> = BTreeMap::new();
+                let transparent = frame.image.iter().flatten().any(|pixel| pixel.alpha < 0x80);
+
+                //Transparency takes the first entry, so the rest keep the order they are first seen in
+                if transparent {
+                    table.push(color::ARGB::default());
+                }
+
+                let mut indices = Vec::with_capacity(frame.image.width() * frame.image.height());
+
+                for pixel in frame.image.iter().flatten() {
+                    if pixel.alpha < 0x80 {
+                        indices.push(0);
+                        continue;
+                    }
+
+                    let opaque = pixel.with_alpha(0xFF);
+
+                    let index = match lookup.get(&opaque.as_u32(true)) {
+                        Some(index) => *index,
+                        None if table.len() < 256 => {
+                            let index = table.len() as u8;
+                            lookup.insert(opaque.as_u32(true), index);
+                            table.push(opaque);
+                            index
+                        },
+                        None => return Err(ImageError::Encode(format!(
+                            "Frame {number} has more than {} colors, but a gif frame can only hold 256.", 256 - transparent as usize
+                        )))
+                    };
+
+                    indices.push(index);
+                }
+
+                let disposal = match frame.disposal {
+                    Disposal::Keep => 1,
+                    Disposal::Background => 2,
+                    Disposal::Previous => 3
+                };
+
+                Ok(GifFrame {
+                    left: dimension(frame.left, "frame offset")?,
+                    top: dimension(frame.top, "frame offset")?,
+                    width: dimension(frame.image.width(), "frame width")?,
+                    height: dimension(frame.image.height(), "frame height")?,
+                    interlaced: false,
+                    local_color_table: Some(table),
+                    control: Some(GraphicControl {
+                        disposal,
+                        delay: u16::try_from(frame.delay.as_millis().div_ceil(10)).unwrap_or(u16::MAX),
+                        transparent_index: transparent.then_some(0),
+                        user_input: false
+                    }),
+                    indices
+                })
+            })
+            .collect::<Result<Vec<_>, ImageError>>()?;
+
+        let loop_count = match value.loop_count() {
+            0 => Some(0),
+            1 => None,
+            loops => Some(u16::try_from(loops - 1).unwrap_or(u16::MAX))
+        };
+
+        Ok(Self {
+            header,
+            global_color_table: None,
+            frames,
+            loop_count
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,309 @@
+use super::*;
+use alloc::{collections::BTreeMap, string::ToString};
+
+///
+/// Compress indices as gif does, growing the code size as the table fills and
+/// clearing it once full, so that every path of the decoder is taken
+///
+fn lzw_encode(min_code_size: u8, indices: &[u8]) -> Vec<u8> {
+    let clear = 1_u16 << min_code_size;
+    let mut codes: Vec<(u16, u8)> = vec![(clear, min_code_size + 1)];
+
+    let mut table: BTreeMap<Vec<u8>, u16> = BTreeMap::new();
+    let mut next = clear + 2;
+    let mut code_size = min_code_size + 1;
+    let mut current: Vec<u8> = Vec::new();
+
+    for index in indices {
+        let mut extended = current.clone();
+        extended.push(*index);
+
+        if extended.len() == 1 || table.contains_key(&extended) {
+            current = extended;
+            continue;
+        }
+
+        let code = if current.len() == 1 { current[0] as u16 } else { table[&current] };
+        codes.push((code, code_size));
+
+        if next as usize == gif::MAX_CODES {
+            codes.push((clear, code_size));
+            table.clear();
+            next = clear + 2;
+            code_size = min_code_size + 1;
+        }
+        else {
+            table.insert(extended, next);
+            next += 1;
+
+            if next as usize == (1 << code_size) + 1 && code_size < gif::MAX_CODE_SIZE {
+                code_size += 1;
+            }
+        }
+
+        current = vec![*index];
+    }
+
+    if !current.is_empty() {
+        let code = if current.len() == 1 { current[0] as u16 } else { table[&current] };
+        codes.push((code, code_size));
+    }
+
+    codes.push((clear + 1, code_size));
+
+    let mut bytes = Vec::new();
+    let mut bit = 0;
+
+    for (code, size) in codes {
+        for shift in 0..size {
+            if bit % 8 == 0 {
+                bytes.push(0);
+            }
+
+            *bytes.last_mut().unwrap() |= (((code >> shift) & 1) as u8) << (bit % 8);
+            bit += 1;
+        }
+    }
+
+    bytes
+}
+
+fn sub_blocks(data: &[u8]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = data.chunks(255).flat_map(|chunk| core::iter::once(chunk.len() as u8).chain(chunk.iter().copied())).collect();
+    bytes.push(0);
+    bytes
+}
+
+fn build(width: u16, height: u16, palette: &[[u8; 3]], frame: &[u8], transparent: Option<u8>, interlaced: bool) -> Vec<u8> {
+    let size_bits = (palette.len().max(2).next_power_of_two().trailing_zeros() - 1) as u8;
+
+    let mut bytes = gif::SIGNATURE_89A.to_vec();
+    bytes.extend(width.to_le_bytes());
+    bytes.extend(height.to_le_bytes());
+    bytes.extend([0x80 | size_bits, 0, 0]);
+    bytes.extend(palette.iter().flatten());
+    bytes.extend(core::iter::repeat_n(0, 3 * ((2 << size_bits) - palette.len())));
+
+    if let Some(index) = transparent {
+        bytes.extend([gif::block::EXTENSION, gif::extension::GRAPHIC_CONTROL, 4, 0x09, 10, 0, index, 0]);
+    }
+
+    //A comment, which is skipped
+    bytes.extend([gif::block::EXTENSION, 0xFE]);
+    bytes.extend(sub_blocks(b"made by hand"));
+
+    bytes.push(gif::block::IMAGE);
+    bytes.extend([0, 0, 0, 0]);
+    bytes.extend(width.to_le_bytes());
+    bytes.extend(height.to_le_bytes());
+    bytes.push(if interlaced { 0x40 } else { 0 });
+
+    let min_code_size = (size_bits + 1).max(2);
+    bytes.push(min_code_size);
+    bytes.extend(sub_blocks(&lzw_encode(min_code_size, frame)));
+    bytes.push(gif::block::TRAILER);
+
+    bytes
+}
+
+#[test]
+fn gif_decodes_first_frame() -> Result<(), String> {
+    let palette = [[0, 0, 0], [0xFF, 0, 0], [0, 0xFF, 0], [0, 0, 0xFF]];
+
+    //Enough repetition and variety to fill the code table and clear it
+    let indices: Vec<u8> = (0..120_usize * 90).map(|n| ((n * n / 7 + n / 13) % 4) as u8).collect();
+    let bytes = build(120, 90, &palette, &indices, Some(3), false);
+
+    let gif = Gif::try_from(bytes.as_slice())?;
+
+    if gif.frames.len() != 1 || gif.frames[0].indices != indices {
+        return Err(String::from("Decoded indices do not match those encoded."));
+    }
+
+    if gif.frames[0].control.map(|control| (control.delay, control.disposal)) != Some((10, 2)) {
+        return Err(format!("Unexpected graphic control {:?}.", gif.frames[0].control));
+    }
+
+    let image = Image::try_convert_from(gif, ImageConvertData::default())?;
+
+    for (n, index) in indices.iter().enumerate() {
+        let [red, green, blue] = palette[*index as usize];
+        let alpha = if *index == 3 { 0 } else { 0xFF };
+        let expected = if alpha == 0 { color::ARGB::default() } else { color::ARGB { alpha, red, green, blue } };
+
+        if image.get(n % 120, n / 120) != Some(expected) {
+            return Err(format!("Pixel {},{} should be {expected:?}, got {:?}.", n % 120, n / 120, image.get(n % 120, n / 120)));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn gif_deinterlaces_rows() -> Result<(), String> {
+    let palette: Vec<[u8; 3]> = (0..16).map(|n| [n * 16, 0, 0]).collect();
+
+    //Each row is filled with its own index, stored in interlaced order
+    let stored: Vec<u8> = [(0, 8), (4, 8), (2, 4), (1, 2)].into_iter()
+        .flat_map(|(start, step)| (start..11).step_by(step))
+        .flat_map(|row| [row as u8; 3])
+        .collect();
+
+    let gif = Gif::try_from(build(3, 11, &palette, &stored, None, true))?;
+    let expected: Vec<u8> = (0..11).flat_map(|row| [row as u8; 3]).collect();
+
+    if gif.frames[0].indices != expected {
+        return Err(format!("Expected rows in order, got {:?}.", gif.frames[0].indices));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn gif_rejects_malformed() -> Result<(), String> {
+    let valid = build(2, 2, &[[0, 0, 0], [0xFF, 0xFF, 0xFF]], &[0, 1, 1, 0], None, false);
+
+    if crate::image::format::detect(&valid) != Some(crate::image::format::ImageFormat::Gif) {
+        return Err(String::from("Expected the gif to be detected."));
+    }
+
+    //Cut off before the image
+    let header_only = valid[..(6 + gif::SCREEN_DESCRIPTOR_SIZE + 6)].to_vec();
+
+    //A clear code, then code 7 before the table has anything past the clear and end codes
+    let mut bad_code = valid.clone();
+    let lzw = bad_code.len() - 5;
+    bad_code[lzw] = 0b0011_1100;
+
+    let cases: [(Vec<u8>, &str); 3] = [
+        (b"GIF88a".to_vec(), "signature"),
+        (header_only, "first frame"),
+        (bad_code, "LZW code")
+    ];
+
+    for (bytes, expected) in cases {
+        match Gif::try_from(bytes.as_slice()) {
+            Ok(_) => return Err(format!("Expected an error mentioning {expected}.")),
+            Err(err) if !err.to_string().contains(expected) => return Err(format!("Expected an error mentioning {expected}, got '{err}'.")),
+            Err(_) => ()
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn gif_rejects_oversized() -> Result<(), String> {
+    let valid = build(2, 2, &[[0, 0, 0], [0xFF, 0xFF, 0xFF]], &[0, 1, 1, 0], None, false);
+
+    //Both sizes at 65535x65535, far past the limit, with only a handful of bytes behind them
+    let mut screen = valid.clone();
+    screen[6..10].copy_from_slice(&[0xFF; 4]);
+
+    let image = valid.iter().position(|&byte| byte == gif::block::IMAGE).ok_or("Expected an image block.")?;
+    let mut frame = valid.clone();
+    frame[(image + 5)..(image + 9)].copy_from_slice(&[0xFF; 4]);
+
+    for (bytes, expected) in [(screen, "Gif screen"), (frame, "Gif frame")] {
+        match Gif::try_from(bytes.as_slice()) {
+            Ok(_) => return Err(format!("Expected {expected} to be too large.")),
+            Err(err) if !err.to_string().contains(expected) || !err.to_string().contains("pixels allowed") => return Err(format!("Expected {expected} to be too large, got '{err}'.")),
+            Err(_) => ()
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn gif_rejects_frames_without_data() -> Result<(), String> {
+    let mut bytes = build(2, 2, &[[0, 0, 0], [0xFF, 0xFF, 0xFF]], &[0, 1, 1, 0], None, false);
+    bytes.pop();
+
+    //Frames of 65535x4096 pixels, each with an empty sub-block for its data
+    for _ in 0..40 {
+        bytes.push(gif::block::IMAGE);
+        bytes.extend([0, 0, 0, 0, 0xFF, 0xFF, 0x00, 0x10, 0]);
+        bytes.extend([2, 0]);
+    }
+
+    bytes.push(gif::block::TRAILER);
+
+    match Gif::try_from(bytes.as_slice()) {
+        Ok(_) => Err(String::from("Expected frames without data to be rejected.")),
+        Err(err) if !err.to_string().contains("bytes of data") => Err(format!("Expected frames without data to be rejected, got '{err}'.")),
+        Err(_) => Ok(())
+    }
+}
+
+#[test]
+fn gif_round_trips_frames() -> Result<(), String> {
+    let palette: Vec<color::ARGB> = (0..16).map(|n| color::ARGB { alpha: 0xFF, red: n * 16, green: 0xFF - n * 16, blue: n }).collect();
+
+    let frame = |left: u16, width: u16, height: u16, interlaced: bool, seed: usize| GifFrame {
+        left,
+        top: 1,
+        width,
+        height,
+        interlaced,
+        local_color_table: (seed % 2 == 1).then(|| palette[..8].to_vec()),
+        control: Some(GraphicControl { disposal: seed as u8 % 4, delay: 7 * seed as u16, transparent_index: Some(1), user_input: false }),
+        indices: (0..(width as usize * height as usize)).map(|n| ((n * n / 11 + n / 3 + seed) % 5) as u8).collect()
+    };
+
+    //Large enough to fill the code table, and a frame of a single pixel
+    let gif = Gif {
+        header: GifHeader { is_89a: true, width: 130, height: 100, background_index: 2, pixel_aspect_ratio: 0 },
+        global_color_table: Some(palette.clone()),
+        frames: vec![frame(0, 130, 99, false, 0), frame(5, 17, 23, true, 1), frame(3, 1, 1, false, 2)],
+        loop_count: Some(0)
+    };
+
+    let bytes: Vec<u8> = gif.clone().try_into()?;
+    let decoded = Gif::try_from(bytes.as_slice())?;
+
+    if decoded != gif {
+        return Err(String::from("Decoded gif does not match the one written."));
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn gif_round_trips_animation() -> Result<(), String> {
+    use core::time::Duration;
+    use crate::image::animation::{AnimatedImage, Disposal, Frame};
+
+    let red = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0, blue: 0 };
+    let blue = color::ARGB { alpha: 0xFF, red: 0, green: 0, blue: 0xFF };
+
+    let mut animation = AnimatedImage::new(4, 3).with_loop_count(3);
+    animation.push(Frame::new(Image::new_pixels(4, 3, vec![red; 12]), Duration::from_millis(100)));
+    animation.push(Frame::new(Image::new_pixels(2, 1, vec![blue, color::ARGB::default()]), Duration::from_millis(250))
+        .with_disposal(Disposal::Previous)
+        .with_offset(1, 2));
+
+    let gif = Gif::try_convert_from(animation.clone(), ())?;
+
+    if gif.loop_count != Some(2) || gif.frames[1].control.map(|control| (control.delay, control.disposal)) != Some((25, 3)) {
+        return Err(format!("Unexpected loop count {:?} or graphic control {:?}.", gif.loop_count, gif.frames[1].control));
+    }
+
+    let bytes: Vec<u8> = gif.try_into()?;
+    let decoded = AnimatedImage::try_convert_from(Gif::try_from(bytes)?, ImageConvertData::default())?;
+
+    if decoded != animation {
+        return Err(format!("Expected {animation:?}, got {decoded:?}."));
+    }
+
+    //Too many colors for one frame
+    let pixels = (0..=256).map(|n| color::ARGB { alpha: 0xFF, red: (n / 256) as u8, green: n as u8, blue: 0 }).collect();
+    let mut crowded = AnimatedImage::new(257, 1);
+    crowded.push(Frame::new(Image::new_pixels(257, 1, pixels), Duration::ZERO));
+
+    match Gif::try_convert_from(crowded, ()) {
+        Err(err) if err.to_string().contains("256") => Ok(()),
+        other => Err(format!("Expected an error about 256 colors, got {other:?}."))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,419 @@
+//Decoding must not panic on any input, so only tests may index or unwrap
+#![deny(clippy::indexing_slicing, clippy::unwrap_used, clippy::expect_used)]
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing, clippy::unwrap_used)]
+mod tests;
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+use crate::{color, image, log, convert::ConvertableFrom, error::ImageError};
+use image::Image;
+use crate::constants::ilbm;
+use super::bitmap::ImageConvertData;
+
+///
+/// An Amiga interleaved bitmap, with its bitplanes decompressed and combined
+/// into a value per pixel. Chunks other than BMHD, CMAP, CAMG and BODY are skipped.
+/// Ilbm format:
+/// https://wiki.amigaos.net/wiki/ILBM_IFF_Interleaved_Bitmap
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Ilbm {
+    pub header: IlbmHeader,
+    ///
+    /// Colors of the CMAP chunk, or shades of gray if there was none. For extra
+    /// halfbrite, followed by those colors at half brightness. With a transparent
+    /// color, that color has no alpha.
+    ///
+    pub palette: Vec<color::ARGB>,
+    ///
+    /// The Amiga display mode, from the CAMG chunk
+    ///
+    pub mode: u32,
+    pub pixels: IlbmPixels,
+    ///
+    /// For each pixel, whether the mask plane marks it as opaque
+    ///
+    pub mask: Option<Vec<bool>>
+}
+
+///
+/// The BMHD chunk, which describes the size and layout of the image
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IlbmHeader {
+    pub width: u16,
+    pub height: u16,
+    pub x: i16,
+    pub y: i16,
+    ///
+    /// Number of bitplanes, and so bits per pixel
+    ///
+    pub planes: u8,
+    pub masking: u8,
+    pub compression: u8,
+    ///
+    /// Palette index of the transparent color, if masking says there is one
+    ///
+    pub transparent_color: u16,
+    pub x_aspect: u8,
+    pub y_aspect: u8,
+    pub page_width: i16,
+    pub page_height: i16
+}
+
+///
+/// Pixel data of an ilbm, in rows from the top
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IlbmPixels {
+    ///
+    /// Indices into the palette, of an ilbm with up to 8 planes
+    ///
+    Indices(Vec<u8>),
+    ///
+    /// Colors of a hold-and-modify or 24-bit ilbm
+    ///
+    Colors(Vec<color::ARGB>)
+}
+
+impl Default for IlbmPixels {
+    fn default() -> Self {
+        Self::Indices(Vec::new())
+    }
+}
+
+impl IlbmHeader {
+    ///
+    /// Bytes in each row of one plane, which is padded to a whole word
+    ///
+    pub fn row_bytes(&self) -> usize {
+        (self.width as usize).div_ceil(16) * 2
+    }
+
+    fn has_mask(&self) -> bool {
+        self.masking == ilbm::masking::HAS_MASK
+    }
+}
+
+impl TryFrom<&[u8]> for Ilbm {
+    type Error = ImageError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let form_size = match value.get(..12) {
+            Some([f0, f1, f2, f3, s0, s1, s2, s3, t0, t1, t2, t3]) if [*f0, *f1, *f2, *f3] == ilbm::FORM && [*t0, *t1, *t2, *t3] == ilbm::FORM_TYPE => {
+                u32::from_be_bytes([*s0, *s1, *s2, *s3]) as usize
+            },
+            _ => return Err(ImageError::Decode(String::from("Ilbm must start with an IFF FORM of type ILBM.")))
+        };
+
+        //The size counts the form type, but not the FORM and size before it
+        let mut chunks = value.get(12..(8 + form_size).min(value.len())).unwrap_or_default();
+
+        let mut header = None;
+        let mut colors = None;
+        let mut mode = 0;
+        let mut body = None;
+
+        while !chunks.is_empty() {
+            let (kind, data) = next_chunk(&mut chunks)?;
+
+            match kind {
+                ilbm::chunk::BMHD => header = Some(read_header(data)?),
+                ilbm::chunk::CMAP => colors = Some(data.chunks_exact(3).map(|rgb| match rgb {
+                    [red, green, blue] => color::ARGB { alpha: 0xFF, red: *red, green: *green, blue: *blue },
+                    _ => color::ARGB::default()
+                }).collect::<Vec<_>>()),
+                ilbm::chunk::CAMG => mode = data.get(..4).map_or(0, |mode| mode.iter().fold(0, |mode, byte| (mode << 8) | *byte as u32)),
+                ilbm::chunk::BODY => body = Some(data),
+                _ => ()
+            }
+        }
+
+        let header = header.ok_or_else(|| ImageError::Decode(String::from("Ilbm has no BMHD chunk.")))?;
+        let body = body.ok_or_else(|| ImageError::Decode(String::from("Ilbm has no BODY chunk.")))?;
+
+        let (width, height) = (header.width as usize, header.height as usize);
+
+        if width * height > ilbm::MAX_PIXELS {
+            return Err(ImageError::Decode(format!("Ilbm image {width}x{height} has more than the {} pixels allowed.", ilbm::MAX_PIXELS)));
+        }
+
+        let hold_and_modify = mode & ilbm::mode::HOLD_AND_MODIFY != 0;
+
+        match header.planes {
+            1..=8 => (),
+            24 | 32 if !hold_and_modify => (),
+            planes => return Err(ImageError::Unsupported(format!("Ilbm with {planes} planes is not supported.")))
+        }
+
+        if hold_and_modify && !matches!(header.planes, 6 | 8) {
+            return Err(ImageError::Unsupported(format!("Hold-and-modify ilbm with {} planes is not supported.", header.planes)));
+        }
+
+        let planes = header.planes as usize + header.has_mask() as usize;
+        let size = header.row_bytes() * planes * height;
+
+        let data = match header.compression {
+            ilbm::compression::NONE => body.get(..size)
+                .map(Vec::from)
+                .ok_or_else(|| ImageError::Decode(format!("Ilbm body needs {size} bytes, but has {}.", body.len())))?,
+            ilbm::compression::BYTE_RUN_1 => unpack_byte_run_1(body, size)?,
+            other => return Err(ImageError::Unsupported(format!("Ilbm compression {other} is not supported.")))
+        };
+
+        let values = read_planes(&data, &header);
+
+        let mut palette = colors.unwrap_or_else(|| gray_palette(header.planes));
+
+        if mode & ilbm::mode::EXTRA_HALFBRITE != 0 {
+            palette.resize(32, color::ARGB { alpha: 0xFF, ..Default::default() });
+
+            let half: Vec<color::ARGB> = palette.iter()
+                .map(|color| color::ARGB { alpha: 0xFF, red: color.red >> 1, green: color.green >> 1, blue: color.blue >> 1 })
+                .collect();
+
+            palette.extend(half);
+        }
+
+        if header.masking == ilbm::masking::TRANSPARENT_COLOR {
+            if let Some(color) = palette.get_mut(header.transparent_color as usize) {
+                color.alpha = 0;
+            }
+        }
+
+        let pixels = if hold_and_modify {
+            IlbmPixels::Colors(hold_and_modify_colors(&values, width, header.planes, &palette))
+        }
+        else if header.planes > 8 {
+            //Deep ilbms hold red, then green, then blue in 8 planes each, and maybe alpha
+            IlbmPixels::Colors(values.iter()
+                .map(|(value, _)| {
+                    let [red, green, blue, alpha] = value.to_le_bytes();
+                    color::ARGB { alpha: if header.planes == 32 { alpha } else { 0xFF }, red, green, blue }
+                })
+                .collect())
+        }
+        else {
+            IlbmPixels::Indices(values.iter().map(|(value, _)| *value as u8).collect())
+        };
+
+        Ok(Self {
+            header,
+            palette,
+            mode,
+            pixels,
+            mask: header.has_mask().then(|| values.iter().map(|(_, opaque)| *opaque).collect())
+        })
+    }
+}
+
+impl TryFrom<Vec<u8>> for Ilbm {
+    type Error = ImageError;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_slice())
+    }
+}
+
+///
+/// Read the next chunk, as its type and data. Chunks of an odd
+/// size are followed by a byte of padding.
+///
+fn next_chunk<'a>(bytes: &mut &'a [u8]) -> Result<([u8; 4], &'a [u8]), ImageError> {
+    let (kind, length) = match bytes.get(..8) {
+        Some([k0, k1, k2, k3, l0, l1, l2, l3]) => ([*k0, *k1, *k2, *k3], u32::from_be_bytes([*l0, *l1, *l2, *l3]) as usize),
+        _ => return Err(ImageError::Decode(String::from("Ilbm chunk header is truncated.")))
+    };
+
+    let data = bytes.get(8..(8 + length))
+        .ok_or_else(|| ImageError::Decode(format!("Ilbm chunk {} is truncated.", String::from_utf8_lossy(&kind))))?;
+
+    *bytes = bytes.get((8 + length + length % 2)..).unwrap_or_default();
+
+    Ok((kind, data))
+}
+
+fn read_header(data: &[u8]) -> Result<IlbmHeader, ImageError> {
+    let Some([w0, w1, h0, h1, x0, x1, y0, y1, planes, masking, compression, _, t0, t1, x_aspect, y_aspect, pw0, pw1, ph0, ph1]) = data.get(..ilbm::HEADER_SIZE) else {
+        return Err(ImageError::Decode(format!("Ilbm BMHD chunk has {} bytes, but needs {}.", data.len(), ilbm::HEADER_SIZE)));
+    };
+
+    let (width, height) = (u16::from_be_bytes([*w0, *w1]), u16::from_be_bytes([*h0, *h1]));
+
+    if width == 0 || height == 0 {
+        return Err(ImageError::Decode(format!("Ilbm image size {width}x{height} is empty.")));
+    }
+
+    Ok(IlbmHeader {
+        width,
+        height,
+        x: i16::from_be_bytes([*x0, *x1]),
+        y: i16::from_be_bytes([*y0, *y1]),
+        planes: *planes,
+        masking: *masking,
+        compression: *compression,
+        transparent_color: u16::from_be_bytes([*t0, *t1]),
+        x_aspect: *x_aspect,
+        y_aspect: *y_aspect,
+        page_width: i16::from_be_bytes([*pw0, *pw1]),
+        page_height: i16::from_be_bytes([*ph0, *ph1])
+    })
+}
+
+///
+/// Unpack ByteRun1 (PackBits) runs until there are the given number of bytes. Each run starts
+/// with a byte n: up to 127 copies the next n + 1 bytes, and from -1 to -127 repeats the next
+/// byte 1 - n times. -128 does nothing.
+///
+fn unpack_byte_run_1(packed: &[u8], size: usize) -> Result<Vec<u8>, ImageError> {
+    let mut bytes = Vec::with_capacity(size);
+    let mut iter = packed.iter();
+    let truncated = || ImageError::Decode(format!("Ilbm body unpacks to fewer than the {size} bytes needed."));
+
+    while bytes.len() < size {
+        let control = *iter.next().ok_or_else(truncated)? as i8;
+
+        match control {
+            0..=i8::MAX => {
+                for _ in 0..=control {
+                    bytes.push(*iter.next().ok_or_else(truncated)?);
+                }
+            },
+            i8::MIN => (),
+            _ => {
+                let byte = *iter.next().ok_or_else(truncated)?;
+                bytes.extend(core::iter::repeat_n(byte, 1 + control.unsigned_abs() as usize));
+            }
+        }
+    }
+
+    //The last run may overshoot a row's end in files from some writers
+    bytes.truncate(size);
+
+    Ok(bytes)
+}
+
+///
+/// Combine the planes of each row into a value per pixel, the first plane
+/// holding the lowest bit, with whether any mask plane marks it opaque
+///
+fn read_planes(data: &[u8], header: &IlbmHeader) -> Vec<(u32, bool)> {
+    let (width, row_bytes) = (header.width as usize, header.row_bytes());
+    let planes = header.planes as usize;
+    let mut values = vec![(0_u32, true); width * header.height as usize];
+
+    let rows = data.chunks(row_bytes * (planes + header.has_mask() as usize)).take(header.height as usize);
+
+    for (row, interleaved) in values.chunks_mut(width.max(1)).zip(rows) {
+        for (plane, bits) in interleaved.chunks(row_bytes).enumerate() {
+            for (x, (value, opaque)) in row.iter_mut().enumerate() {
+                let bit = bits.get(x / 8).is_some_and(|byte| (byte >> (7 - x % 8)) & 1 != 0);
+
+                if plane < planes {
+                    *value |= (bit as u32) << plane;
+                }
+                else {
+                    *opaque = bit;
+                }
+            }
+        }
+    }
+
+    values
+}
+
+///
+/// Colors of a hold-and-modify image. The top two bits of each value pick either a
+/// palette color, or to keep the last pixel's color but change its blue, red or
+/// green to the rest of the value. Each row starts from the first palette color.
+///
+fn hold_and_modify_colors(values: &[(u32, bool)], width: usize, planes: u8, palette: &[color::ARGB]) -> Vec<color::ARGB> {
+    let bits = planes.saturating_sub(2) as u32;
+    let low_mask = (1_u32 << bits) - 1;
+
+    //Scale the 4 or 6 bits to 8 by repeating them
+    let scale = |value: u32| ((value << (8 - bits)) | (value >> (2 * bits - 8))) as u8;
+
+    values.chunks(width.max(1))
+        .flat_map(|row| {
+            let mut last = palette.first().copied().unwrap_or(color::ARGB { alpha: 0xFF, ..Default::default() });
+
+            row.iter().map(move |(value, _)| {
+                let low = value & low_mask;
+
+                last = match value >> bits {
+                    0 => palette.get(low as usize).copied().unwrap_or(last),
+                    1 => color::ARGB { blue: scale(low), ..last },
+                    2 => color::ARGB { red: scale(low), ..last },
+                    _ => color::ARGB { green: scale(low), ..last }
+                };
+
+                last
+            })
+        })
+        .collect()
+}
+
+///
+/// Evenly spaced shades of gray, for an ilbm without a CMAP chunk
+///
+fn gray_palette(planes: u8) -> Vec<color::ARGB> {
+    let count = 1_u32 << planes.min(8);
+
+    (0..count)
+        .map(|n| {
+            let level = (n * 255 / (count - 1).max(1)) as u8;
+            color::ARGB { alpha: 0xFF, red: level, green: level, blue: level }
+        })
+        .collect()
+}
+
+impl ConvertableFrom<Ilbm> for Image {
+    type Options = ImageConvertData;
+    type Error = ImageError;
+
+    fn try_convert_from(value: Ilbm, options: Self::Options) -> Result<Self, Self::Error> {
+        let width = value.header.width as usize;
+        let height = value.header.height as usize;
+
+        let mut pixels = match value.pixels {
+            IlbmPixels::Colors(colors) => colors,
+            IlbmPixels::Indices(indices) => {
+                let mut out_of_range = 0_usize;
+
+                let colors = indices.iter()
+                    .enumerate()
+                    .map(|(index, palette_index)| match (value.palette.get(*palette_index as usize), options.palette_fallback) {
+                        (Some(color), _) => Ok(*color),
+                        (None, Some(fallback)) => {
+                            out_of_range += 1;
+                            Ok(fallback)
+                        },
+                        (None, None) => Err(ImageError::Decode(format!(
+                            "Pixel {},{} has palette index {palette_index}, but the palette only has {} colors.",
+                            index % width.max(1), index / width.max(1), value.palette.len()
+                        )))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if out_of_range > 0 {
+                    log::warn(format_args!("Ilbm has {out_of_range} pixels with palette indices outside of the palette, which were replaced with the fallback color."));
+                }
+
+                colors
+            }
+        };
+
+        if let Some(mask) = value.mask {
+            for (pixel, opaque) in pixels.iter_mut().zip(mask) {
+                if !opaque {
+                    *pixel = color::ARGB::default();
+                }
+            }
+        }
+
+        Image::try_new_pixels(width, height, pixels)
+    }
+}
\ No newline at end of file
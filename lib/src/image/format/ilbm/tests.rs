@@ -0,0 +1,230 @@
+use super::*;
+use alloc::string::ToString;
+
+///
+/// Pack bytes into ByteRun1 runs, repeating runs of 3 or more of a byte
+///
+fn pack_byte_run_1(bytes: &[u8]) -> Vec<u8> {
+    let mut packed = Vec::new();
+    let mut literal: Vec<u8> = Vec::new();
+    let mut rest = bytes;
+
+    let flush = |packed: &mut Vec<u8>, literal: &mut Vec<u8>| {
+        for run in literal.chunks(128) {
+            packed.push((run.len() - 1) as u8);
+            packed.extend_from_slice(run);
+        }
+
+        literal.clear();
+    };
+
+    while let Some(first) = rest.first() {
+        let repeats = rest.iter().take(128).take_while(|byte| *byte == first).count();
+
+        if repeats >= 3 {
+            flush(&mut packed, &mut literal);
+            packed.extend([(1 - repeats as i16) as u8, *first]);
+        }
+        else {
+            literal.extend_from_slice(&rest[..repeats]);
+        }
+
+        rest = &rest[repeats..];
+    }
+
+    flush(&mut packed, &mut literal);
+
+    //A no-op run, which is skipped
+    packed.push(0x80);
+    packed
+}
+
+///
+/// Split a value per pixel into interleaved planes, with an optional mask plane after each row
+///
+fn to_planes(values: &[u32], width: usize, planes: usize, mask: Option<&[bool]>) -> Vec<u8> {
+    let row_bytes = width.div_ceil(16) * 2;
+    let mut bytes = Vec::new();
+
+    for (j, row) in values.chunks(width).enumerate() {
+        for plane in 0..(planes + mask.is_some() as usize) {
+            let mut bits = vec![0_u8; row_bytes];
+
+            for (x, value) in row.iter().enumerate() {
+                let bit = match mask {
+                    Some(mask) if plane == planes => mask[j * width + x],
+                    _ => (value >> plane) & 1 == 1
+                };
+
+                bits[x / 8] |= (bit as u8) << (7 - x % 8);
+            }
+
+            bytes.extend(bits);
+        }
+    }
+
+    bytes
+}
+
+fn chunk(kind: [u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut bytes = kind.to_vec();
+    bytes.extend((data.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(data);
+
+    if data.len() % 2 == 1 {
+        bytes.push(0);
+    }
+
+    bytes
+}
+
+fn build((width, height): (u16, u16), planes: u8, masking: u8, compressed: bool, palette: &[[u8; 3]], mode: u32, body: &[u8]) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    //Color 3 is transparent, if masking says so
+    header.extend([0, 0, 0, 0, planes, masking, compressed as u8, 0, 0, 3, 10, 11]);
+    header.extend(320_i16.to_be_bytes());
+    header.extend(200_i16.to_be_bytes());
+
+    let mut chunks = ilbm::FORM_TYPE.to_vec();
+    chunks.extend(chunk(ilbm::chunk::BMHD, &header));
+
+    //An annotation, which is skipped, of odd length to need padding
+    chunks.extend(chunk(*b"ANNO", b"odd"));
+
+    if !palette.is_empty() {
+        chunks.extend(chunk(ilbm::chunk::CMAP, &palette.concat()));
+    }
+
+    if mode != 0 {
+        chunks.extend(chunk(ilbm::chunk::CAMG, &mode.to_be_bytes()));
+    }
+
+    chunks.extend(chunk(ilbm::chunk::BODY, &if compressed { pack_byte_run_1(body) } else { body.to_vec() }));
+
+    let mut bytes = ilbm::FORM.to_vec();
+    bytes.extend((chunks.len() as u32).to_be_bytes());
+    bytes.extend(chunks);
+    bytes
+}
+
+fn rgb(red: u8, green: u8, blue: u8) -> color::ARGB {
+    color::ARGB { alpha: 0xFF, red, green, blue }
+}
+
+#[test]
+fn ilbm_decodes_planes() -> Result<(), String> {
+    let palette: Vec<[u8; 3]> = (0..16).map(|n| [n * 17, 0xFF - n * 17, n]).collect();
+
+    //Width not a multiple of 16, so each plane's rows are padded
+    let (width, height) = (21, 5);
+    let values: Vec<u32> = (0..(width * height) as u32).map(|n| if n % 9 < 4 { 3 } else { (n * n / 5) % 16 }).collect();
+    let body = to_planes(&values, width, 4, None);
+
+    let plain = Ilbm::try_from(build((width as u16, height as u16), 4, ilbm::masking::NONE, false, &palette, 0, &body))?;
+    let packed = Ilbm::try_from(build((width as u16, height as u16), 4, ilbm::masking::NONE, true, &palette, 0, &body))?;
+
+    let expected = IlbmPixels::Indices(values.iter().map(|value| *value as u8).collect());
+
+    if plain.pixels != expected || packed.pixels != expected {
+        return Err(String::from("Decoded indices do not match those encoded."));
+    }
+
+    if plain.header.page_width != 320 || plain.header.x_aspect != 10 {
+        return Err(format!("Unexpected header {:?}.", plain.header));
+    }
+
+    //With color 3 transparent
+    let bytes = build((width as u16, height as u16), 4, ilbm::masking::TRANSPARENT_COLOR, true, &palette, 0, &body);
+    let image = Image::try_convert_from(Ilbm::try_from(bytes)?, ImageConvertData::default())?;
+
+    for (n, value) in values.iter().enumerate() {
+        let [red, green, blue] = palette[*value as usize];
+        let expected = if *value == 3 { rgb(red, green, blue).with_alpha(0) } else { rgb(red, green, blue) };
+
+        if image.get(n % width, n / width) != Some(expected) {
+            return Err(format!("Pixel {n} should be {expected:?}, got {:?}.", image.get(n % width, n / width)));
+        }
+    }
+
+    //A mask plane, which clears every other pixel
+    let mask: Vec<bool> = (0..(width * height)).map(|n| n % 2 == 0).collect();
+    let bytes = build((width as u16, height as u16), 4, ilbm::masking::HAS_MASK, true, &palette, 0, &to_planes(&values, width, 4, Some(&mask)));
+    let image = Image::try_convert_from(Ilbm::try_from(bytes)?, ImageConvertData::default())?;
+
+    if image.iter().flatten().enumerate().any(|(n, pixel)| (pixel.alpha == 0xFF) != mask[n]) {
+        return Err(String::from("Masked pixels should be transparent, and the rest opaque."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn ilbm_decodes_display_modes() -> Result<(), String> {
+    let palette: Vec<[u8; 3]> = (0..32).map(|n| [n * 8, 0x80, 0xFE - n * 8]).collect();
+
+    //Extra halfbrite: indices past 32 are the first 32 colors at half brightness
+    let values = [0, 31, 32, 63];
+    let bytes = build((4, 1), 6, ilbm::masking::NONE, false, &palette, ilbm::mode::EXTRA_HALFBRITE, &to_planes(&values, 4, 6, None));
+    let image = Image::try_convert_from(Ilbm::try_from(bytes)?, ImageConvertData::default())?;
+
+    if image.row(0) != [rgb(0, 0x80, 0xFE), rgb(248, 0x80, 6), rgb(0, 0x40, 0x7F), rgb(124, 0x40, 3)] {
+        return Err(format!("Unexpected extra halfbrite colors {:?}.", image.row(0)));
+    }
+
+    //Hold and modify: a palette color, then change blue, red and green in turn
+    let values = [2, (1 << 4) | 0xF, (2 << 4) | 0x1, (3 << 4) | 0xA, 5];
+    let bytes = build((5, 1), 6, ilbm::masking::NONE, true, &palette, ilbm::mode::HOLD_AND_MODIFY, &to_planes(&values, 5, 6, None));
+    let image = Image::try_convert_from(Ilbm::try_from(bytes)?, ImageConvertData::default())?;
+
+    if image.row(0) != [rgb(16, 0x80, 0xEE), rgb(16, 0x80, 0xFF), rgb(0x11, 0x80, 0xFF), rgb(0x11, 0xAA, 0xFF), rgb(40, 0x80, 0xD6)] {
+        return Err(format!("Unexpected hold-and-modify colors {:?}.", image.row(0)));
+    }
+
+    //24 planes of red, green then blue, without a palette
+    let values = [0x00_33_22_11, 0x00_FF_80_01];
+    let bytes = build((2, 1), 24, ilbm::masking::NONE, true, &[], 0, &to_planes(&values, 2, 24, None));
+    let image = Image::try_convert_from(Ilbm::try_from(bytes)?, ImageConvertData::default())?;
+
+    if image.row(0) != [rgb(0x11, 0x22, 0x33), rgb(0x01, 0x80, 0xFF)] {
+        return Err(format!("Unexpected 24-bit colors {:?}.", image.row(0)));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn ilbm_rejects_malformed() -> Result<(), String> {
+    let valid = build((16, 2), 1, ilbm::masking::NONE, true, &[[0, 0, 0], [0xFF, 0xFF, 0xFF]], 0, &[0xFF, 0, 0x0F, 0xF0]);
+
+    if crate::image::format::detect(&valid) != Some(crate::image::format::ImageFormat::Ilbm) {
+        return Err(String::from("Expected the ilbm to be detected."));
+    }
+
+    //Cut the body short, leaving the form size to claim the rest
+    let mut truncated = valid.clone();
+    truncated.truncate(valid.len() - 3);
+
+    let mut wrong_type = valid.clone();
+    wrong_type[8..12].copy_from_slice(b"8SVX");
+
+    let cases: [(Vec<u8>, &str); 6] = [
+        (wrong_type, "FORM of type ILBM"),
+        (truncated, "BODY"),
+        (build((0, 2), 1, ilbm::masking::NONE, true, &[], 0, &[]), "0x2 is empty"),
+        (build((16, 0), 1, ilbm::masking::HAS_MASK, true, &[], 0, &[]), "16x0 is empty"),
+        (build((16, 2), 12, ilbm::masking::NONE, false, &[], 0, &[0; 48]), "12 planes"),
+        (build((16, 2), 1, ilbm::masking::NONE, true, &[], 0, &[0x05, 1]), "fewer than the 4 bytes")
+    ];
+
+    for (bytes, expected) in cases {
+        match Ilbm::try_from(bytes.as_slice()) {
+            Ok(_) => return Err(format!("Expected an error mentioning {expected}.")),
+            Err(err) if !err.to_string().contains(expected) => return Err(format!("Expected an error mentioning {expected}, got '{err}'.")),
+            Err(_) => ()
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
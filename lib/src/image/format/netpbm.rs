@@ -0,0 +1,651 @@
+//Decoding must not panic on any input, so only tests may index or unwrap
+#![deny(clippy::indexing_slicing, clippy::unwrap_used, clippy::expect_used)]
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing, clippy::unwrap_used)]
+mod tests;
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+use crate::{color, image, convert::ConvertableFrom, error::ImageError};
+use image::Image;
+use crate::constants::netpbm;
+
+///
+/// A image in one of the netpbm formats: a bitmap (pbm), graymap (pgm) or
+/// pixmap (ppm), each stored as either ASCII text or binary, or a pam, which
+/// is always binary and may have alpha. Only the first image is read from a
+/// file holding several.
+/// Netpbm formats:
+/// https://netpbm.sourceforge.net/doc/pbm.html
+/// https://netpbm.sourceforge.net/doc/pam.html
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Netpbm {
+    pub header: NetpbmHeader,
+    ///
+    /// The samples of each pixel, row by row from the top. Bitmaps and graymaps
+    /// have one per pixel, pixmaps red, green then blue, and pams those of their
+    /// tuple type. Bitmap samples are 1 for black and 0 for white, but those of
+    /// black and white pams are 0 for black and 1 for white.
+    ///
+    pub samples: Vec<u16>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NetpbmHeader {
+    pub kind: NetpbmKind,
+    ///
+    /// Whether samples are stored as bytes rather than ASCII decimal. Pams are always binary.
+    ///
+    pub binary: bool,
+    pub width: usize,
+    pub height: usize,
+    ///
+    /// The value of a sample at full intensity; always 1 for bitmaps and black and white pams
+    ///
+    pub max_value: u16
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NetpbmKind {
+    Bitmap,
+    Graymap,
+    #[default]
+    Pixmap,
+    Pam(TupleType)
+}
+
+///
+/// What the samples of each pixel of a pam are
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TupleType {
+    BlackAndWhite,
+    Grayscale,
+    Rgb,
+    BlackAndWhiteAlpha,
+    GrayscaleAlpha,
+    RgbAlpha
+}
+
+impl TupleType {
+    ///
+    /// The name given in the TUPLTYPE line of the header
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BlackAndWhite => "BLACKANDWHITE",
+            Self::Grayscale => "GRAYSCALE",
+            Self::Rgb => "RGB",
+            Self::BlackAndWhiteAlpha => "BLACKANDWHITE_ALPHA",
+            Self::GrayscaleAlpha => "GRAYSCALE_ALPHA",
+            Self::RgbAlpha => "RGB_ALPHA"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TupleType> {
+        [Self::BlackAndWhite, Self::Grayscale, Self::Rgb, Self::BlackAndWhiteAlpha, Self::GrayscaleAlpha, Self::RgbAlpha]
+            .into_iter()
+            .find(|tuple_type| tuple_type.name() == name)
+    }
+
+    ///
+    /// The tuple type usually meant by a pam of the given depth without one
+    ///
+    pub fn from_depth(depth: usize) -> Option<TupleType> {
+        match depth {
+            1 => Some(Self::Grayscale),
+            2 => Some(Self::GrayscaleAlpha),
+            3 => Some(Self::Rgb),
+            4 => Some(Self::RgbAlpha),
+            _ => None
+        }
+    }
+
+    ///
+    /// Number of samples per pixel
+    ///
+    pub fn depth(&self) -> usize {
+        match self {
+            Self::BlackAndWhite | Self::Grayscale => 1,
+            Self::BlackAndWhiteAlpha | Self::GrayscaleAlpha => 2,
+            Self::Rgb => 3,
+            Self::RgbAlpha => 4
+        }
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        matches!(self, Self::BlackAndWhiteAlpha | Self::GrayscaleAlpha | Self::RgbAlpha)
+    }
+
+    fn is_black_and_white(&self) -> bool {
+        matches!(self, Self::BlackAndWhite | Self::BlackAndWhiteAlpha)
+    }
+}
+
+impl NetpbmKind {
+    ///
+    /// Number of samples per pixel
+    ///
+    pub fn channels(&self) -> usize {
+        match self {
+            Self::Bitmap | Self::Graymap => 1,
+            Self::Pixmap => 3,
+            Self::Pam(tuple_type) => tuple_type.depth()
+        }
+    }
+
+    ///
+    /// File extension usually used for the kind
+    ///
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Bitmap => "pbm",
+            Self::Graymap => "pgm",
+            Self::Pixmap => "ppm",
+            Self::Pam(_) => "pam"
+        }
+    }
+
+    ///
+    /// The only maximum value samples of the kind may have, if it is fixed
+    ///
+    fn fixed_max_value(&self) -> Option<u16> {
+        match self {
+            Self::Bitmap => Some(1),
+            Self::Pam(tuple_type) if tuple_type.is_black_and_white() => Some(1),
+            _ => None
+        }
+    }
+
+    ///
+    /// Find the kind with the given file extension. The generic pnm is taken
+    /// as a pixmap, and a pam as holding RGB with alpha.
+    ///
+    pub fn from_extension(extension: &str) -> Option<NetpbmKind> {
+        match extension.trim_start_matches('.').to_ascii_lowercase().as_str() {
+            "pbm" => Some(Self::Bitmap),
+            "pgm" => Some(Self::Graymap),
+            "ppm" | "pnm" => Some(Self::Pixmap),
+            "pam" => Some(Self::Pam(TupleType::RgbAlpha)),
+            _ => None
+        }
+    }
+}
+
+impl NetpbmHeader {
+    ///
+    /// The magic number the image starts with, from P1 to P7
+    ///
+    pub fn magic(&self) -> [u8; 2] {
+        let number = match self.kind {
+            NetpbmKind::Bitmap => 1,
+            NetpbmKind::Graymap => 2,
+            NetpbmKind::Pixmap => 3,
+            NetpbmKind::Pam(_) => return [b'P', b'7']
+        };
+
+        [b'P', b'0' + number + if self.binary { 3 } else { 0 }]
+    }
+
+    ///
+    /// Bytes per sample of a binary graymap or pixmap
+    ///
+    fn sample_size(&self) -> usize {
+        if self.max_value > u8::MAX as u16 { 2 } else { 1 }
+    }
+}
+
+///
+/// Reads the whitespace-separated fields of the header and ASCII samples,
+/// skipping comments
+///
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize
+}
+
+impl Reader<'_> {
+    fn skip_space(&mut self) {
+        let mut comment = false;
+
+        while let Some(byte) = self.bytes.get(self.position) {
+            match byte {
+                b'\n' | b'\r' => comment = false,
+                b'#' => comment = true,
+                _ if comment || byte.is_ascii_whitespace() => (),
+                _ => break
+            }
+
+            self.position += 1;
+        }
+    }
+
+    ///
+    /// Read a decimal number, naming it in any error
+    ///
+    fn number(&mut self, name: &str) -> Result<u32, ImageError> {
+        self.skip_space();
+
+        let start = self.position;
+        let mut value: u32 = 0;
+
+        while let Some(digit) = self.bytes.get(self.position).filter(|byte| byte.is_ascii_digit()) {
+            value = value.checked_mul(10)
+                .and_then(|value| value.checked_add((digit - b'0') as u32))
+                .ok_or_else(|| ImageError::Decode(format!("Netpbm {name} at byte {start} is too large.")))?;
+
+            self.position += 1;
+        }
+
+        if self.position == start {
+            return Err(ImageError::Decode(format!("Expected netpbm {name} at byte {start}.")));
+        }
+
+        Ok(value)
+    }
+
+    ///
+    /// Read a run of bytes up to the next whitespace
+    ///
+    fn word(&mut self) -> &[u8] {
+        self.skip_space();
+
+        let start = self.position;
+
+        while self.bytes.get(self.position).is_some_and(|byte| !byte.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+
+        self.bytes.get(start..self.position).unwrap_or_default()
+    }
+
+    ///
+    /// Read a sample of an ASCII bitmap, which needs no whitespace between samples
+    ///
+    fn bit(&mut self) -> Result<u16, ImageError> {
+        self.skip_space();
+
+        let value = match self.bytes.get(self.position) {
+            Some(b'0') => 0,
+            Some(b'1') => 1,
+            Some(other) => return Err(ImageError::Decode(format!("Expected a bitmap sample of 0 or 1 at byte {}, got '{}'.", self.position, *other as char))),
+            None => return Err(ImageError::Decode(String::from("Netpbm image ends before its last sample.")))
+        };
+
+        self.position += 1;
+
+        Ok(value)
+    }
+}
+
+impl TryFrom<&[u8]> for Netpbm {
+    type Error = ImageError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.get(..2) == Some(b"P7") {
+            let mut reader = Reader { bytes: value, position: 2 };
+            let (kind, width, height, max_value) = read_pam_header(&mut reader)?;
+            return read_body(value, reader, NetpbmHeader { kind, binary: true, width, height, max_value });
+        }
+
+        let (kind, binary) = match value.get(..2) {
+            Some(b"P1") => (NetpbmKind::Bitmap, false),
+            Some(b"P2") => (NetpbmKind::Graymap, false),
+            Some(b"P3") => (NetpbmKind::Pixmap, false),
+            Some(b"P4") => (NetpbmKind::Bitmap, true),
+            Some(b"P5") => (NetpbmKind::Graymap, true),
+            Some(b"P6") => (NetpbmKind::Pixmap, true),
+            _ => return Err(ImageError::Decode(String::from("Netpbm magic number must be one of P1 to P7.")))
+        };
+
+        let mut reader = Reader { bytes: value, position: 2 };
+
+        let width = reader.number("width")? as usize;
+        let height = reader.number("height")? as usize;
+
+        let max_value = match kind {
+            NetpbmKind::Bitmap => 1,
+            _ => match reader.number("maximum value")? {
+                0 => return Err(ImageError::Decode(String::from("Netpbm maximum value must be at least 1."))),
+                max => u16::try_from(max)
+                    .map_err(|_| ImageError::Decode(format!("Netpbm maximum value {max} is larger than the {} allowed.", u16::MAX)))?
+            }
+        };
+
+        read_body(value, reader, NetpbmHeader { kind, binary, width, height, max_value })
+    }
+}
+
+impl TryFrom<Vec<u8>> for Netpbm {
+    type Error = ImageError;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_slice())
+    }
+}
+
+///
+/// Read the header of a pam, made of lines of a keyword and its value, up to ENDHDR.
+/// A pam without a tuple type is taken to be grayscale or RGB, by its depth.
+///
+fn read_pam_header(reader: &mut Reader) -> Result<(NetpbmKind, usize, usize, u16), ImageError> {
+    let (mut width, mut height, mut depth, mut max_value, mut tuple_type) = (None, None, None, None, None);
+
+    loop {
+        match reader.word() {
+            b"ENDHDR" => break,
+            b"WIDTH" => width = Some(reader.number("width")? as usize),
+            b"HEIGHT" => height = Some(reader.number("height")? as usize),
+            b"DEPTH" => depth = Some(reader.number("depth")? as usize),
+            b"MAXVAL" => max_value = Some(reader.number("maximum value")?),
+            b"TUPLTYPE" => {
+                let name = String::from_utf8_lossy(reader.word()).into_owned();
+
+                tuple_type = Some(TupleType::from_name(&name)
+                    .ok_or_else(|| ImageError::Unsupported(format!("Pam tuple type '{name}' is not supported.")))?);
+            },
+            [] => return Err(ImageError::Decode(String::from("Pam header ends before ENDHDR."))),
+            other => return Err(ImageError::Decode(format!("Unknown pam header field '{}'.", String::from_utf8_lossy(other))))
+        }
+    }
+
+    let missing = |name: &str| ImageError::Decode(format!("Pam header has no {name}."));
+
+    let (width, height, depth) = (width.ok_or_else(|| missing("WIDTH"))?, height.ok_or_else(|| missing("HEIGHT"))?, depth.ok_or_else(|| missing("DEPTH"))?);
+
+    let tuple_type = match tuple_type.or_else(|| TupleType::from_depth(depth)) {
+        Some(tuple_type) if tuple_type.depth() == depth => tuple_type,
+        Some(tuple_type) => return Err(ImageError::Decode(format!("Pam tuple type {} has depth {}, but the header gives {depth}.", tuple_type.name(), tuple_type.depth()))),
+        None => return Err(ImageError::Unsupported(format!("Pam depth {depth} is not supported without a tuple type.")))
+    };
+
+    let max_value = match max_value.ok_or_else(|| missing("MAXVAL"))? {
+        0 => return Err(ImageError::Decode(String::from("Netpbm maximum value must be at least 1."))),
+        max => u16::try_from(max)
+            .map_err(|_| ImageError::Decode(format!("Netpbm maximum value {max} is larger than the {} allowed.", u16::MAX)))?
+    };
+
+    Ok((NetpbmKind::Pam(tuple_type), width, height, max_value))
+}
+
+///
+/// Read the samples following the header
+///
+fn read_body(value: &[u8], mut reader: Reader, header: NetpbmHeader) -> Result<Netpbm, ImageError> {
+    let NetpbmHeader { kind, binary, width, height, max_value } = header;
+
+    match width.checked_mul(height) {
+        Some(pixels) if pixels <= netpbm::MAX_PIXELS => (),
+        _ => return Err(ImageError::Decode(format!("Netpbm image {width}x{height} has more than the {} pixels allowed.", netpbm::MAX_PIXELS)))
+    }
+
+    let count = width * height * kind.channels();
+
+    let samples = if !binary {
+        (0..count)
+            .map(|_| match kind {
+                NetpbmKind::Bitmap => reader.bit(),
+                _ => reader.number("sample").map(|sample| sample.min(u16::MAX as u32) as u16)
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    }
+    else {
+        //Exactly one whitespace byte separates the header from the samples
+        match value.get(reader.position) {
+            Some(byte) if byte.is_ascii_whitespace() => (),
+            _ => return Err(ImageError::Decode(format!("Expected whitespace before the netpbm samples at byte {}.", reader.position)))
+        }
+
+        let raster = value.get((reader.position + 1)..).unwrap_or_default();
+
+        match kind {
+            NetpbmKind::Bitmap => read_bits(raster, width, height)?,
+            _ => read_samples(raster, count, header.sample_size())?
+        }
+    };
+
+    if let Some(position) = samples.iter().position(|sample| *sample > max_value) {
+        return Err(ImageError::Decode(format!("Netpbm sample {position} is {}, above the maximum value {max_value}.",
+            samples.get(position).copied().unwrap_or_default())));
+    }
+
+    Ok(Netpbm {
+        header,
+        samples
+    })
+}
+
+///
+/// Unpack the rows of a binary bitmap, each padded to a whole byte, most significant bit first
+///
+fn read_bits(raster: &[u8], width: usize, height: usize) -> Result<Vec<u16>, ImageError> {
+    let row_bytes = width.div_ceil(8);
+
+    if raster.len() < row_bytes * height {
+        return Err(ImageError::Decode(format!("Netpbm bitmap needs {} bytes of samples, but has {}.", row_bytes * height, raster.len())));
+    }
+
+    Ok(raster.chunks(row_bytes.max(1))
+        .take(height)
+        .flat_map(|row| (0..width).map(move |i| ((row.get(i / 8).copied().unwrap_or_default() >> (7 - i % 8)) & 1) as u16))
+        .collect())
+}
+
+///
+/// Read the samples of a binary graymap or pixmap, which are big-endian if they take two bytes
+///
+fn read_samples(raster: &[u8], count: usize, sample_size: usize) -> Result<Vec<u16>, ImageError> {
+    let data = raster.get(..(count * sample_size))
+        .ok_or_else(|| ImageError::Decode(format!("Netpbm image needs {} bytes of samples, but has {}.", count * sample_size, raster.len())))?;
+
+    Ok(match sample_size {
+        1 => data.iter().map(|sample| *sample as u16).collect(),
+        _ => data.chunks_exact(2).map(|pair| u16::from_be_bytes([pair.first().copied().unwrap_or_default(), pair.last().copied().unwrap_or_default()])).collect()
+    })
+}
+
+///
+/// Write the image, breaking the lines of ASCII samples to keep them short
+///
+impl TryFrom<Netpbm> for Vec<u8> {
+    type Error = ImageError;
+
+    fn try_from(value: Netpbm) -> Result<Self, Self::Error> {
+        let header = value.header;
+        let count = header.width * header.height * header.kind.channels();
+
+        if value.samples.len() != count {
+            return Err(ImageError::Encode(format!("A {}x{} {} needs {count} samples, but has {}.",
+                header.width, header.height, header.kind.extension(), value.samples.len())));
+        }
+
+        let max_value = header.kind.fixed_max_value().unwrap_or(header.max_value);
+
+        if max_value == 0 {
+            return Err(ImageError::Encode(String::from("Netpbm maximum value must be at least 1.")));
+        }
+
+        if let Some(sample) = value.samples.iter().find(|sample| **sample > max_value) {
+            return Err(ImageError::Encode(format!("Netpbm sample {sample} is above the maximum value {max_value}.")));
+        }
+
+        let mut bytes = header.magic().to_vec();
+
+        match header.kind {
+            NetpbmKind::Pam(tuple_type) => bytes.extend(format!("\nWIDTH {}\nHEIGHT {}\nDEPTH {}\nMAXVAL {max_value}\nTUPLTYPE {}\nENDHDR\n",
+                header.width, header.height, tuple_type.depth(), tuple_type.name()).bytes()),
+            NetpbmKind::Bitmap => bytes.extend(format!("\n{} {}\n", header.width, header.height).bytes()),
+            _ => bytes.extend(format!("\n{} {}\n{max_value}\n", header.width, header.height).bytes())
+        }
+
+        let binary = header.binary || matches!(header.kind, NetpbmKind::Pam(_));
+
+        if !binary {
+            let mut line = 0;
+
+            for sample in &value.samples {
+                let text = format!("{sample}");
+
+                if line > 0 && line + 1 + text.len() > netpbm::MAX_LINE_LENGTH {
+                    bytes.push(b'\n');
+                    line = 0;
+                }
+                else if line > 0 {
+                    bytes.push(b' ');
+                    line += 1;
+                }
+
+                bytes.extend(text.bytes());
+                line += text.len();
+            }
+
+            bytes.push(b'\n');
+        }
+        else if header.kind == NetpbmKind::Bitmap {
+            for row in value.samples.chunks(header.width.max(1)) {
+                for byte in row.chunks(8) {
+                    bytes.push(byte.iter().enumerate().fold(0, |packed, (i, bit)| packed | ((*bit as u8 & 1) << (7 - i))));
+                }
+            }
+        }
+        else if header.sample_size() == 2 {
+            bytes.extend(value.samples.iter().flat_map(|sample| sample.to_be_bytes()));
+        }
+        else {
+            bytes.extend(value.samples.iter().map(|sample| *sample as u8));
+        }
+
+        Ok(bytes)
+    }
+}
+
+///
+/// Additional data required to create a netpbm image from
+/// a grid of pixels
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetpbmConvertData {
+    pub kind: NetpbmKind,
+    pub binary: bool,
+    ///
+    /// The value of a sample at full intensity. Ignored for bitmaps.
+    ///
+    pub max_value: u16
+}
+
+impl Default for NetpbmConvertData {
+    fn default() -> Self {
+        Self {
+            kind: NetpbmKind::Pixmap,
+            binary: true,
+            max_value: u8::MAX as u16
+        }
+    }
+}
+
+impl From<&Netpbm> for NetpbmConvertData {
+    fn from(value: &Netpbm) -> Self {
+        Self {
+            kind: value.header.kind,
+            binary: value.header.binary,
+            max_value: value.header.max_value
+        }
+    }
+}
+
+///
+/// Only pams with alpha keep it, and other kinds drop it. Graymaps keep the
+/// luminance (Rec. 709) of each pixel, and bitmaps make pixels darker than half black.
+///
+impl ConvertableFrom<Image> for Netpbm {
+    type Options = NetpbmConvertData;
+    type Error = ImageError;
+
+    fn try_convert_from(value: Image, options: Self::Options) -> Result<Self, Self::Error> {
+        let max_value = match options.kind.fixed_max_value() {
+            Some(max_value) => max_value,
+            None if options.max_value == 0 => return Err(ImageError::InvalidArgument(String::from("Netpbm maximum value must be at least 1."))),
+            None => options.max_value
+        };
+
+        let scale = |channel: u8| ((channel as u32 * max_value as u32 + 127) / 255) as u16;
+
+        let rows = value.iter();
+        let pixels = rows.flatten();
+
+        let samples = match options.kind {
+            NetpbmKind::Bitmap => pixels.map(|pixel| (luminance(pixel) < 0x80) as u16).collect(),
+            NetpbmKind::Graymap => pixels.map(|pixel| scale(luminance(pixel))).collect(),
+            NetpbmKind::Pixmap => pixels.flat_map(|pixel| [scale(pixel.red), scale(pixel.green), scale(pixel.blue)]).collect(),
+            NetpbmKind::Pam(tuple_type) => pixels.flat_map(|pixel| {
+                let colors = match tuple_type {
+                    TupleType::BlackAndWhite | TupleType::BlackAndWhiteAlpha => vec![(luminance(pixel) >= 0x80) as u16],
+                    TupleType::Grayscale | TupleType::GrayscaleAlpha => vec![scale(luminance(pixel))],
+                    TupleType::Rgb | TupleType::RgbAlpha => vec![scale(pixel.red), scale(pixel.green), scale(pixel.blue)]
+                };
+
+                colors.into_iter().chain(tuple_type.has_alpha().then(|| scale(pixel.alpha)))
+            }).collect()
+        };
+
+        Ok(Self {
+            header: NetpbmHeader {
+                kind: options.kind,
+                binary: options.binary || matches!(options.kind, NetpbmKind::Pam(_)),
+                width: value.width(),
+                height: value.height(),
+                max_value
+            },
+            samples
+        })
+    }
+}
+
+impl ConvertableFrom<Netpbm> for Image {
+    type Options = ();
+    type Error = ImageError;
+
+    fn try_convert_from(value: Netpbm, _options: Self::Options) -> Result<Self, Self::Error> {
+        let header = value.header;
+        let channels = header.kind.channels();
+
+        if value.samples.len() < header.width * header.height * channels {
+            return Err(ImageError::Decode(format!("A {}x{} {} needs {} samples, but has {}.",
+                header.width, header.height, header.kind.extension(), header.width * header.height * channels, value.samples.len())));
+        }
+
+        let max_value = header.max_value.max(1) as u32;
+        let scale = |sample: u16| ((sample.min(header.max_value) as u32 * 255 + max_value / 2) / max_value) as u8;
+
+        let pixels = value.samples.chunks_exact(channels)
+            .take(header.width * header.height)
+            .map(|pixel| match (header.kind, pixel) {
+                (NetpbmKind::Bitmap, [bit, ..]) => gray(if *bit == 0 { 0xFF } else { 0 }),
+                (NetpbmKind::Pixmap | NetpbmKind::Pam(TupleType::Rgb), [red, green, blue, ..]) => {
+                    color::ARGB { alpha: 0xFF, red: scale(*red), green: scale(*green), blue: scale(*blue) }
+                },
+                (NetpbmKind::Pam(TupleType::RgbAlpha), [red, green, blue, alpha, ..]) => {
+                    color::ARGB { alpha: scale(*alpha), red: scale(*red), green: scale(*green), blue: scale(*blue) }
+                },
+                (NetpbmKind::Pam(TupleType::BlackAndWhiteAlpha | TupleType::GrayscaleAlpha), [sample, alpha, ..]) => gray(scale(*sample)).with_alpha(scale(*alpha)),
+                (_, [sample, ..]) => gray(scale(*sample)),
+                (_, []) => color::ARGB::default()
+            })
+            .collect();
+
+        Image::try_new_pixels(header.width, header.height, pixels)
+    }
+}
+
+fn gray(value: u8) -> color::ARGB {
+    color::ARGB { alpha: 0xFF, red: value, green: value, blue: value }
+}
+
+///
+/// Rec. 709 luminance, with the weights scaled to sum to 256
+///
+fn luminance(pixel: &color::ARGB) -> u8 {
+    ((54 * pixel.red as u32 + 183 * pixel.green as u32 + 19 * pixel.blue as u32 + 128) >> 8) as u8
+}
\ No newline at end of file
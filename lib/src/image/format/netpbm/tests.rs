@@ -0,0 +1,172 @@
+use super::*;
+use alloc::{string::ToString, vec};
+
+#[test]
+fn netpbm_reads_ascii_and_binary() -> Result<(), String> {
+    //A 3x2 pixmap, with comments in the header and between samples
+    let ascii = b"P3\n# made by hand\n3 2 # size\n15\n15 0 0  0 15 0  0 0 15\n# second row\n0 0 0 15 15 15 5 10 15\n";
+    let ppm = Netpbm::try_from(ascii.as_slice())?;
+    let image = Image::try_convert_from(ppm.clone(), ())?;
+
+    let expected = [(0xFF, 0, 0), (0, 0xFF, 0), (0, 0, 0xFF), (0, 0, 0), (0xFF, 0xFF, 0xFF), (0x55, 0xAA, 0xFF)];
+
+    for (n, (red, green, blue)) in expected.into_iter().enumerate() {
+        if image.get(n % 3, n / 3) != Some(color::ARGB { alpha: 0xFF, red, green, blue }) {
+            return Err(format!("Pixel {n} should be ({red}, {green}, {blue}), got {:?}.", image.get(n % 3, n / 3)));
+        }
+    }
+
+    //The same samples in binary
+    let mut binary = b"P6 3 2 15\n".to_vec();
+    binary.extend(ppm.samples.iter().map(|sample| *sample as u8));
+
+    if Netpbm::try_from(binary)?.samples != ppm.samples {
+        return Err(String::from("Binary samples do not match the ASCII ones."));
+    }
+
+    //Plain bitmaps need no space between samples, and binary ones pad each row to a byte
+    let plain = Netpbm::try_from(b"P1 10 2 0101010101\n1111100000".as_slice())?;
+    let packed = Netpbm::try_from([b"P4\n10 2\n".as_slice(), &[0b0101_0101, 0b0100_0000, 0b1111_1000, 0]].concat())?;
+
+    if plain.samples != packed.samples || plain.samples[..4] != [0, 1, 0, 1] {
+        return Err(format!("Bitmap samples differ: {:?} and {:?}.", plain.samples, packed.samples));
+    }
+
+    let bitmap = Image::try_convert_from(plain, ())?;
+
+    if bitmap.get(1, 0) != Some(gray(0)) || bitmap.get(0, 0) != Some(gray(0xFF)) {
+        return Err(String::from("Bitmap samples of 1 should be black, and 0 white."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn netpbm_round_trips() -> Result<(), String> {
+    let pixels = (0..35_u32).map(|n| color::ARGB { alpha: 0xFF, red: (n * 7) as u8, green: (n * 13) as u8, blue: (255 - n * 5) as u8 }).collect();
+    let image = Image::new_pixels(7, 5, pixels);
+
+    for kind in [NetpbmKind::Bitmap, NetpbmKind::Graymap, NetpbmKind::Pixmap] {
+        for binary in [false, true] {
+            for max_value in [255, 1000, u16::MAX] {
+                let options = NetpbmConvertData { kind, binary, max_value };
+                let netpbm = Netpbm::try_convert_from(image.clone(), options)?;
+                let bytes: Vec<u8> = netpbm.clone().try_into()?;
+
+                if crate::image::format::detect(&bytes) != Some(crate::image::format::ImageFormat::Netpbm) {
+                    return Err(format!("Expected {options:?} to be detected."));
+                }
+
+                let decoded = Netpbm::try_from(bytes.as_slice())?;
+
+                if decoded != netpbm {
+                    return Err(format!("Decoded {options:?} does not match the one written."));
+                }
+
+                if !binary && bytes.split(|byte| *byte == b'\n').any(|line| line.len() > netpbm::MAX_LINE_LENGTH) {
+                    return Err(format!("ASCII {options:?} has a line longer than {}.", netpbm::MAX_LINE_LENGTH));
+                }
+
+                //Only a pixmap keeps the colors, which survive 8 bits or more exactly
+                if kind == NetpbmKind::Pixmap && Image::try_convert_from(decoded, ())? != image {
+                    return Err(format!("Pixels of {options:?} do not match the original."));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn netpbm_rejects_malformed() -> Result<(), String> {
+    let cases: [(&[u8], &str); 6] = [
+        (b"P8 1 1 255\n", "magic number"),
+        (b"P2 2 x", "height"),
+        (b"P2 2 2 0\n", "maximum value"),
+        (b"P2 2 1 7\n1 8\n", "above the maximum"),
+        (b"P5 2 2 255\n\x01\x02\x03", "needs 4 bytes"),
+        (b"P1 2 1 0 2", "0 or 1")
+    ];
+
+    for (bytes, expected) in cases {
+        match Netpbm::try_from(bytes) {
+            Ok(_) => return Err(format!("Expected an error mentioning {expected}.")),
+            Err(err) if !err.to_string().contains(expected) => return Err(format!("Expected an error mentioning {expected}, got '{err}'.")),
+            Err(_) => ()
+        }
+    }
+
+    let short = Netpbm { header: NetpbmHeader { kind: NetpbmKind::Graymap, binary: true, width: 2, height: 2, max_value: 255 }, samples: vec![0; 3] };
+
+    match Vec::try_from(short) {
+        Ok(_) => Err(String::from("Expected too few samples to fail to encode.")),
+        Err(_) => Ok(())
+    }
+}
+
+#[test]
+fn pam_keeps_alpha() -> Result<(), String> {
+    let pixels = (0..12_u32).map(|n| color::ARGB { alpha: (n * 23) as u8, red: (n * 20) as u8, green: 0xFF - n as u8, blue: (n * 3) as u8 }).collect();
+    let image = Image::new_pixels(4, 3, pixels);
+
+    let tuple_types = [TupleType::BlackAndWhite, TupleType::Grayscale, TupleType::Rgb, TupleType::BlackAndWhiteAlpha, TupleType::GrayscaleAlpha, TupleType::RgbAlpha];
+
+    for tuple_type in tuple_types {
+        for max_value in [255, u16::MAX] {
+            let options = NetpbmConvertData { kind: NetpbmKind::Pam(tuple_type), binary: false, max_value };
+            let pam = Netpbm::try_convert_from(image.clone(), options)?;
+            let bytes: Vec<u8> = pam.clone().try_into()?;
+
+            if !bytes.starts_with(format!("P7\nWIDTH 4\nHEIGHT 3\nDEPTH {}\n", tuple_type.depth()).as_bytes()) {
+                return Err(format!("Unexpected header for {options:?}."));
+            }
+
+            let decoded = Netpbm::try_from(bytes)?;
+
+            if decoded != pam {
+                return Err(format!("Decoded {options:?} does not match the one written."));
+            }
+
+            let read = Image::try_convert_from(decoded, ())?;
+
+            if tuple_type == TupleType::RgbAlpha && read != image {
+                return Err(format!("Pixels of {options:?} do not match the original."));
+            }
+
+            if tuple_type.has_alpha() != read.iter().flatten().any(|pixel| pixel.alpha != 0xFF) {
+                return Err(format!("Alpha of {options:?} should be kept only if it has alpha."));
+            }
+        }
+    }
+
+    //Black and white pams have 1 for white, the opposite of bitmaps
+    let white = Netpbm::try_from(b"P7\n# a comment\nWIDTH 1\nHEIGHT 1\nDEPTH 1\nMAXVAL 1\nTUPLTYPE BLACKANDWHITE\nENDHDR\n\x01".as_slice())?;
+
+    if Image::try_convert_from(white, ())?.get(0, 0) != Some(gray(0xFF)) {
+        return Err(String::from("Black and white pam sample of 1 should be white."));
+    }
+
+    //Without a tuple type, a depth of 4 is taken as RGB with alpha
+    let untyped = Netpbm::try_from(b"P7 WIDTH 1 HEIGHT 1 DEPTH 4 MAXVAL 255 ENDHDR\n\x01\x02\x03\x04".as_slice())?;
+
+    if untyped.header.kind != NetpbmKind::Pam(TupleType::RgbAlpha) {
+        return Err(format!("Expected RGB_ALPHA, got {:?}.", untyped.header.kind));
+    }
+
+    let cases: [(&[u8], &str); 3] = [
+        (b"P7\nWIDTH 1\nHEIGHT 1\nDEPTH 3\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n", "depth 4"),
+        (b"P7\nWIDTH 1\nDEPTH 1\nMAXVAL 255\nENDHDR\n", "HEIGHT"),
+        (b"P7\nWIDTH 1\nHEIGHT 1\nDEPTH 1\nMAXVAL 255\nTUPLTYPE CMYK\nENDHDR\n", "CMYK")
+    ];
+
+    for (bytes, expected) in cases {
+        match Netpbm::try_from(bytes) {
+            Ok(_) => return Err(format!("Expected an error mentioning {expected}.")),
+            Err(err) if !err.to_string().contains(expected) => return Err(format!("Expected an error mentioning {expected}, got '{err}'.")),
+            Err(_) => ()
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,636 @@
+//Decoding must not panic on any input, so only tests may index or unwrap
+#![deny(clippy::indexing_slicing, clippy::unwrap_used, clippy::expect_used)]
+
+#[cfg(all(test, feature = "std"))]
+#[allow(clippy::indexing_slicing, clippy::unwrap_used)]
+mod tests;
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+use crate::{color, image, log, utility, convert::ConvertableFrom, error::ImageError};
+use image::{Image, ImageMetadata, orientation::ExifOrientation};
+use crate::constants::png;
+use super::bitmap::ImageConvertData;
+
+///
+/// The offset and spacing, in columns then rows, of the pixels
+/// in each of the seven passes of an Adam7 interlaced png
+///
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2)
+];
+
+///
+/// A image in png format, with its pixel data inflated, unfiltered and
+/// deinterlaced. Only the chunks needed to show the image, and its color
+/// profile, are kept.
+/// Png format:
+/// https://www.w3.org/TR/png/
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Png {
+    pub header: PngHeader,
+    ///
+    /// Colors of the palette, with the alpha given by any tRNS chunk.
+    /// Only indexed pngs are required to have one.
+    ///
+    pub palette: Vec<color::ARGB>,
+    pub pixels: PngPixels,
+    ///
+    /// The ICC profile of the iCCP chunk, inflated, which the
+    /// colors of the pixels are in
+    ///
+    pub icc_profile: Option<Vec<u8>>
+}
+
+///
+/// The IHDR chunk, which describes the size and layout of the image
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PngHeader {
+    pub width: u32,
+    pub height: u32,
+    ///
+    /// Bits per sample, or per palette index
+    ///
+    pub bit_depth: u8,
+    pub color_type: PngColorType,
+    ///
+    /// Whether the pixels are stored in the seven passes of Adam7
+    ///
+    pub interlaced: bool
+}
+
+///
+/// The channels stored for each pixel of a png
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PngColorType {
+    #[default]
+    Grayscale,
+    Rgb,
+    ///
+    /// Each pixel is an index into the palette
+    ///
+    Indexed,
+    GrayscaleAlpha,
+    Rgba
+}
+
+impl PngColorType {
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            png::color_type::GRAYSCALE => Some(Self::Grayscale),
+            png::color_type::RGB => Some(Self::Rgb),
+            png::color_type::INDEXED => Some(Self::Indexed),
+            png::color_type::GRAYSCALE_ALPHA => Some(Self::GrayscaleAlpha),
+            png::color_type::RGBA => Some(Self::Rgba),
+            _ => None
+        }
+    }
+
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::Grayscale => png::color_type::GRAYSCALE,
+            Self::Rgb => png::color_type::RGB,
+            Self::Indexed => png::color_type::INDEXED,
+            Self::GrayscaleAlpha => png::color_type::GRAYSCALE_ALPHA,
+            Self::Rgba => png::color_type::RGBA
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Grayscale => "Grayscale",
+            Self::Rgb => "RGB",
+            Self::Indexed => "Indexed",
+            Self::GrayscaleAlpha => "Grayscale with alpha",
+            Self::Rgba => "RGBA"
+        }
+    }
+
+    ///
+    /// Number of samples stored for each pixel
+    ///
+    pub fn channels(&self) -> usize {
+        match self {
+            Self::Grayscale | Self::Indexed => 1,
+            Self::GrayscaleAlpha => 2,
+            Self::Rgb => 3,
+            Self::Rgba => 4
+        }
+    }
+
+    ///
+    /// Whether a png of this color type may have the given bit depth
+    ///
+    pub fn allows_bit_depth(&self, bit_depth: u8) -> bool {
+        match self {
+            Self::Grayscale => matches!(bit_depth, 1 | 2 | 4 | 8 | 16),
+            Self::Indexed => matches!(bit_depth, 1 | 2 | 4 | 8),
+            Self::Rgb | Self::GrayscaleAlpha | Self::Rgba => matches!(bit_depth, 8 | 16)
+        }
+    }
+}
+
+impl PngHeader {
+    pub fn bits_per_pixel(&self) -> usize {
+        self.bit_depth as usize * self.color_type.channels()
+    }
+
+    ///
+    /// Number of bytes in a row of the given number of pixels, without its filter type
+    ///
+    fn row_size(&self, width: usize) -> usize {
+        (width * self.bits_per_pixel()).div_ceil(8)
+    }
+
+    ///
+    /// Where the pixels of each pass start and how far apart they are, and how many of them there are
+    ///
+    fn passes(&self) -> Vec<(usize, usize, usize, usize, usize, usize)> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let passes: &[(usize, usize, usize, usize)] = if self.interlaced { &ADAM7 } else { &[(0, 0, 1, 1)] };
+
+        passes.iter()
+            .map(|(x, y, dx, dy)| (*x, *y, *dx, *dy, width.saturating_sub(*x).div_ceil(*dx), height.saturating_sub(*y).div_ceil(*dy)))
+            .filter(|pass| pass.4 > 0 && pass.5 > 0)
+            .collect()
+    }
+
+    fn parse(data: &[u8]) -> Result<Self, ImageError> {
+        let [w0, w1, w2, w3, h0, h1, h2, h3, bit_depth, color_type, compression, filter, interlace] = *data else {
+            return Err(ImageError::Decode(format!("IHDR chunk is {} bytes, but should be {}.", data.len(), png::HEADER_SIZE)));
+        };
+
+        let width = u32::from_be_bytes([w0, w1, w2, w3]);
+        let height = u32::from_be_bytes([h0, h1, h2, h3]);
+
+        let color_type = PngColorType::from_code(color_type)
+            .ok_or_else(|| ImageError::Decode(format!("Color type {color_type} is not valid.")))?;
+
+        if width == 0 || height == 0 {
+            return Err(ImageError::Decode(format!("Image size {width}x{height} is empty.")));
+        }
+
+        if (width as usize).saturating_mul(height as usize) > png::MAX_PIXELS {
+            return Err(ImageError::Unsupported(format!("Image size {width}x{height} is larger than the {} pixels supported.", png::MAX_PIXELS)));
+        }
+
+        if !color_type.allows_bit_depth(bit_depth) {
+            return Err(ImageError::Decode(format!("Bit depth {bit_depth} is not valid for color type {}.", color_type.name())));
+        }
+
+        if compression != 0 || filter != 0 || interlace > 1 {
+            return Err(ImageError::Decode(format!("Compression method {compression}, filter method {filter} or interlace method {interlace} is not valid.")));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            bit_depth,
+            color_type,
+            interlaced: interlace == 1
+        })
+    }
+}
+
+///
+/// Pixel data of a png, in rows from the top
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PngPixels {
+    ///
+    /// Indices into the palette, of an indexed png
+    ///
+    Indices(Vec<u8>),
+    ///
+    /// Colors of any other png. Samples of 16 bits are cut to 8.
+    ///
+    Colors(Vec<color::ARGB>)
+}
+
+impl Default for PngPixels {
+    fn default() -> Self {
+        Self::Colors(Vec::new())
+    }
+}
+
+///
+/// The single color or gray given by a tRNS chunk, which is transparent
+/// wherever it appears, compared at the full bit depth
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransparentKey {
+    Gray(u16),
+    Rgb(u16, u16, u16)
+}
+
+impl TryFrom<&[u8]> for Png {
+    type Error = ImageError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let mut bytes = value.strip_prefix(png::SIGNATURE.as_slice())
+            .ok_or_else(|| ImageError::Decode(String::from("Png signature is missing.")))?;
+
+        let mut header: Option<PngHeader> = None;
+        let mut palette: Vec<color::ARGB> = Vec::new();
+        let mut key: Option<TransparentKey> = None;
+        let mut compressed: Vec<u8> = Vec::new();
+        let mut icc_profile: Option<Vec<u8>> = None;
+
+        loop {
+            let (kind, data) = next_chunk(&mut bytes)?;
+
+            //Every chunk but IHDR needs the header to make sense of it
+            let current = match (kind, header) {
+                (png::chunk::IHDR, None) => {
+                    header = Some(PngHeader::parse(data)?);
+                    continue;
+                },
+                (png::chunk::IHDR, Some(_)) => return Err(ImageError::Decode(String::from("Png has more than one IHDR chunk."))),
+                (_, None) => return Err(ImageError::Decode(String::from("Png does not start with an IHDR chunk."))),
+                (_, Some(header)) => header
+            };
+
+            match kind {
+                png::chunk::PLTE => {
+                    if data.len() % 3 != 0 || data.len() / 3 > 1 << current.bit_depth.min(8) || data.is_empty() {
+                        return Err(ImageError::Decode(format!("PLTE chunk of {} bytes is not a valid palette.", data.len())));
+                    }
+
+                    palette = data.chunks_exact(3)
+                        .map(|rgb| match *rgb {
+                            [red, green, blue] => color::ARGB { alpha: 0xFF, red, green, blue },
+                            _ => color::ARGB::default()
+                        })
+                        .collect();
+                },
+                png::chunk::TRNS => match current.color_type {
+                    PngColorType::Indexed => {
+                        if data.len() > palette.len() {
+                            return Err(ImageError::Decode(format!("tRNS chunk has {} alphas, but the palette only has {} colors.", data.len(), palette.len())));
+                        }
+
+                        for (color, alpha) in palette.iter_mut().zip(data) {
+                            color.alpha = *alpha;
+                        }
+                    },
+                    PngColorType::Grayscale => match *data {
+                        [g0, g1] => key = Some(TransparentKey::Gray(u16::from_be_bytes([g0, g1]))),
+                        _ => return Err(ImageError::Decode(String::from("tRNS chunk of a grayscale png should be 2 bytes.")))
+                    },
+                    PngColorType::Rgb => match *data {
+                        [r0, r1, g0, g1, b0, b1] => key = Some(TransparentKey::Rgb(
+                            u16::from_be_bytes([r0, r1]), u16::from_be_bytes([g0, g1]), u16::from_be_bytes([b0, b1])
+                        )),
+                        _ => return Err(ImageError::Decode(String::from("tRNS chunk of an RGB png should be 6 bytes.")))
+                    },
+                    _ => log::warn(format_args!("Ignoring tRNS chunk of a png which already has alpha."))
+                },
+                png::chunk::IDAT => compressed.extend_from_slice(data),
+                //Without its profile, the image can still be shown, if with slightly off colors
+                png::chunk::ICCP => match read_icc_profile(data) {
+                    Ok(profile) => icc_profile = Some(profile),
+                    Err(err) => log::warn(format_args!("Ignoring iCCP chunk of a png: {err}"))
+                },
+                png::chunk::IEND => break,
+                //Bit 5 of the first letter is clear for chunks which can't be skipped
+                [first, ..] if first & 0x20 == 0 => {
+                    return Err(ImageError::Unsupported(format!("Critical chunk {} is not supported.", String::from_utf8_lossy(&kind))));
+                },
+                _ => ()
+            }
+        }
+
+        let header = header.ok_or_else(|| ImageError::Decode(String::from("Png has no IHDR chunk.")))?;
+
+        if header.color_type == PngColorType::Indexed && palette.is_empty() {
+            return Err(ImageError::Decode(String::from("Indexed png has no PLTE chunk.")));
+        }
+
+        if compressed.is_empty() {
+            return Err(ImageError::Decode(String::from("Png has no IDAT chunks.")));
+        }
+
+        //Each row of each pass is stored with a byte for its filter type
+        let expected = header.passes().iter()
+            .map(|pass| pass.5 * (1 + header.row_size(pass.4)))
+            .sum();
+
+        let data = utility::zlib::decompress(&compressed, expected)?;
+
+        if data.len() < expected {
+            return Err(ImageError::Decode(format!("Image data is {} bytes, but should be {expected}.", data.len())));
+        }
+
+        Ok(Self {
+            header,
+            palette,
+            pixels: decode_pixels(&header, &data, key)?,
+            icc_profile
+        })
+    }
+}
+
+impl TryFrom<Vec<u8>> for Png {
+    type Error = ImageError;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from(&value[..])
+    }
+}
+
+///
+/// The orientation given by the EXIF metadata in a png's eXIf chunk, if it has one.
+/// Only the chunks are read, without inflating the pixels.
+///
+pub fn orientation(bytes: &[u8]) -> Option<ExifOrientation> {
+    let mut bytes = bytes.strip_prefix(png::SIGNATURE.as_slice())?;
+
+    loop {
+        match next_chunk(&mut bytes).ok()? {
+            (png::chunk::EXIF, data) => return ExifOrientation::from_exif(data),
+            (png::chunk::IEND, _) => return None,
+            _ => ()
+        }
+    }
+}
+
+///
+/// Read the ICC profile of an iCCP chunk: a name of 1 to 79 bytes ending at
+/// the first null, the compression method, then the zlib compressed profile
+///
+fn read_icc_profile(data: &[u8]) -> Result<Vec<u8>, ImageError> {
+    let name_end = data.iter()
+        .position(|byte| *byte == 0)
+        .filter(|end| (1..=79).contains(end))
+        .ok_or_else(|| ImageError::Decode(String::from("iCCP chunk has no valid profile name.")))?;
+
+    match data.get((name_end + 1)..) {
+        Some([0, compressed @ ..]) => utility::zlib::decompress(compressed, png::MAX_ICC_PROFILE_SIZE),
+        Some([method, ..]) => Err(ImageError::Unsupported(format!("iCCP compression method {method} is not supported."))),
+        _ => Err(ImageError::Decode(String::from("iCCP chunk is truncated.")))
+    }
+}
+
+///
+/// Read the next chunk, as its type and data, checking its CRC
+///
+fn next_chunk<'a>(bytes: &mut &'a [u8]) -> Result<([u8; 4], &'a [u8]), ImageError> {
+    let truncated = || ImageError::Decode(String::from("Png is truncated before its IEND chunk."));
+
+    let (length, kind) = match bytes.get(..8) {
+        Some([l0, l1, l2, l3, k0, k1, k2, k3]) => (u32::from_be_bytes([*l0, *l1, *l2, *l3]) as usize, [*k0, *k1, *k2, *k3]),
+        _ => return Err(truncated())
+    };
+
+    let data = bytes.get(8..(8 + length)).ok_or_else(truncated)?;
+
+    let crc = match bytes.get((8 + length)..(12 + length)) {
+        Some([c0, c1, c2, c3]) => u32::from_be_bytes([*c0, *c1, *c2, *c3]),
+        _ => return Err(truncated())
+    };
+
+    if crc != crc32(bytes.get(4..(8 + length)).unwrap_or_default()) {
+        return Err(ImageError::Decode(format!("CRC of chunk {} does not match its data.", String::from_utf8_lossy(&kind))));
+    }
+
+    *bytes = bytes.get((12 + length)..).unwrap_or_default();
+
+    Ok((kind, data))
+}
+
+///
+/// Unfilter each row of each pass, and place its pixels in the image
+///
+fn decode_pixels(header: &PngHeader, data: &[u8], key: Option<TransparentKey>) -> Result<PngPixels, ImageError> {
+    let width = header.width as usize;
+    let count = width * header.height as usize;
+
+    let mut pixels = match header.color_type {
+        PngColorType::Indexed => PngPixels::Indices(vec![0; count]),
+        _ => PngPixels::Colors(vec![color::ARGB::default(); count])
+    };
+
+    //Filters work on whole bytes, looking back at least one
+    let filter_step = (header.bits_per_pixel() / 8).max(1);
+    let channels = header.color_type.channels();
+    let mut offset = 0;
+
+    for (x, y, dx, dy, pass_width, pass_height) in header.passes() {
+        let row_size = header.row_size(pass_width);
+        let mut previous = vec![0_u8; row_size];
+
+        for j in 0..pass_height {
+            let (filter, row) = match data.get(offset..(offset + 1 + row_size)) {
+                Some([filter, row @ ..]) => (*filter, row),
+                _ => return Err(ImageError::Decode(String::from("Image data is truncated.")))
+            };
+
+            offset += 1 + row_size;
+
+            let mut current = row.to_vec();
+            unfilter(filter, &mut current, &previous, filter_step)
+                .map_err(|_| ImageError::Decode(format!("Row {} has unknown filter type {filter}.", y + j * dy)))?;
+
+            for i in 0..pass_width {
+                let index = (y + j * dy) * width + x + i * dx;
+                let mut samples = [0_u16; 4];
+
+                for (channel, sample) in samples.iter_mut().enumerate().take(channels) {
+                    *sample = read_sample(&current, i * channels + channel, header.bit_depth);
+                }
+
+                match pixels {
+                    PngPixels::Indices(ref mut indices) => if let Some(pixel) = indices.get_mut(index) {
+                        *pixel = samples[0] as u8;
+                    },
+                    PngPixels::Colors(ref mut colors) => if let Some(pixel) = colors.get_mut(index) {
+                        *pixel = sample_color(header, samples, key);
+                    }
+                }
+            }
+
+            previous = current;
+        }
+    }
+
+    Ok(pixels)
+}
+
+///
+/// Undo the filter of a row in place, given the unfiltered row above it
+///
+fn unfilter(filter: u8, row: &mut [u8], previous: &[u8], step: usize) -> Result<(), ()> {
+    if filter > 4 {
+        return Err(());
+    }
+
+    if filter == 0 {
+        return Ok(());
+    }
+
+    for index in 0..row.len() {
+        let left = index.checked_sub(step).and_then(|left| row.get(left)).copied().unwrap_or_default();
+        let up = previous.get(index).copied().unwrap_or_default();
+        let up_left = index.checked_sub(step).and_then(|left| previous.get(left)).copied().unwrap_or_default();
+
+        let prediction = match filter {
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            _ => paeth(left, up, up_left)
+        };
+
+        if let Some(value) = row.get_mut(index) {
+            *value = value.wrapping_add(prediction);
+        }
+    }
+
+    Ok(())
+}
+
+///
+/// Whichever of the neighbours is closest to left + up - up_left
+///
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let (to_left, to_up, to_up_left) = ((estimate - left as i16).abs(), (estimate - up as i16).abs(), (estimate - up_left as i16).abs());
+
+    if to_left <= to_up && to_left <= to_up_left {
+        left
+    }
+    else if to_up <= to_up_left {
+        up
+    }
+    else {
+        up_left
+    }
+}
+
+///
+/// The sample at the given position in an unfiltered row, counting
+/// samples rather than bytes, as they may be smaller than a byte
+///
+fn read_sample(row: &[u8], position: usize, bit_depth: u8) -> u16 {
+    let byte = |index: usize| row.get(index).copied().unwrap_or_default();
+
+    match bit_depth {
+        16 => u16::from_be_bytes([byte(2 * position), byte(2 * position + 1)]),
+        8 => byte(position) as u16,
+        _ => {
+            let bit = position * bit_depth as usize;
+            let shift = 8 - bit_depth as usize - bit % 8;
+            ((byte(bit / 8) >> shift) & ((1 << bit_depth) - 1)) as u16
+        }
+    }
+}
+
+fn sample_color(header: &PngHeader, samples: [u16; 4], key: Option<TransparentKey>) -> color::ARGB {
+    //Spread samples of fewer than 8 bits over the whole range, and cut those of 16 bits to their high byte
+    let scale = |sample: u16| match header.bit_depth {
+        16 => (sample >> 8) as u8,
+        8 => sample as u8,
+        bit_depth => (sample * (255 / ((1 << bit_depth) - 1))) as u8
+    };
+
+    let [first, second, third, fourth] = samples;
+
+    match header.color_type {
+        PngColorType::Grayscale => {
+            let gray = scale(first);
+            let alpha = if key == Some(TransparentKey::Gray(first)) { 0 } else { 0xFF };
+            color::ARGB { alpha, red: gray, green: gray, blue: gray }
+        },
+        PngColorType::Rgb => {
+            let alpha = if key == Some(TransparentKey::Rgb(first, second, third)) { 0 } else { 0xFF };
+            color::ARGB { alpha, red: scale(first), green: scale(second), blue: scale(third) }
+        },
+        PngColorType::GrayscaleAlpha => {
+            let gray = scale(first);
+            color::ARGB { alpha: scale(second), red: gray, green: gray, blue: gray }
+        },
+        PngColorType::Rgba => color::ARGB { alpha: scale(fourth), red: scale(first), green: scale(second), blue: scale(third) },
+        PngColorType::Indexed => color::ARGB::default()
+    }
+}
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+//Only ever evaluated at compile time, where an index out of bounds fails the build
+#[allow(clippy::indexing_slicing)]
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0_u32; 256];
+    let mut n = 0;
+
+    while n < 256 {
+        let mut value = n as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            value = if value & 1 == 1 { 0xEDB8_8320 ^ (value >> 1) } else { value >> 1 };
+            bit += 1;
+        }
+
+        table[n] = value;
+        n += 1;
+    }
+
+    table
+}
+
+///
+/// The CRC-32 of the bytes, as used by png chunks
+///
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(u32::MAX, |crc, byte| {
+        CRC_TABLE.get(((crc ^ *byte as u32) & 0xFF) as usize).copied().unwrap_or_default() ^ (crc >> 8)
+    })
+}
+
+impl ConvertableFrom<Png> for Image {
+    type Options = ImageConvertData;
+    type Error = ImageError;
+
+    fn try_convert_from(value: Png, options: Self::Options) -> Result<Self, Self::Error> {
+        let width = value.header.width as usize;
+        let height = value.header.height as usize;
+
+        let pixels = match value.pixels {
+            PngPixels::Colors(colors) => colors,
+            PngPixels::Indices(indices) => {
+                let mut out_of_range = 0_usize;
+
+                let colors = indices.iter()
+                    .enumerate()
+                    .map(|(index, palette_index)| match (value.palette.get(*palette_index as usize), options.palette_fallback) {
+                        (Some(color), _) => Ok(*color),
+                        (None, Some(fallback)) => {
+                            out_of_range += 1;
+                            Ok(fallback)
+                        },
+                        (None, None) => Err(ImageError::Decode(format!(
+                            "Pixel {},{} has palette index {palette_index}, but the palette only has {} colors.",
+                            index % width.max(1), index / width.max(1), value.palette.len()
+                        )))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if out_of_range > 0 {
+                    log::warn(format_args!("Png has {out_of_range} pixels with palette indices outside of the palette, which were replaced with the fallback color."));
+                }
+
+                colors
+            }
+        };
+
+        Ok(Image::try_new_pixels(width, height, pixels)?.with_metadata(ImageMetadata { icc_profile: value.icc_profile }))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,245 @@
+use super::*;
+use crate::image::generate::{Fill, ImageGenerator};
+
+///
+/// Seed of the generated test images, fixed so that any failure can be reproduced
+///
+const SEED: u64 = 0x5EED;
+
+///
+/// Wrap the data in a zlib stream of stored blocks
+///
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = if data.is_empty() { vec![&[]] } else { data.chunks(0xFFFF).collect() };
+
+    for (index, block) in blocks.iter().enumerate() {
+        bytes.push((index + 1 == blocks.len()) as u8);
+        bytes.extend((block.len() as u16).to_le_bytes());
+        bytes.extend((!(block.len() as u16)).to_le_bytes());
+        bytes.extend_from_slice(block);
+    }
+
+    bytes.extend(utility::zlib::adler32(data).to_be_bytes());
+    bytes
+}
+
+fn chunk(kind: [u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut bytes = (data.len() as u32).to_be_bytes().to_vec();
+    bytes.extend(kind);
+    bytes.extend_from_slice(data);
+    bytes.extend(crc32(&bytes[4..]).to_be_bytes());
+    bytes
+}
+
+///
+/// A png of the given rows, each already starting with its filter type
+///
+fn build(header: PngHeader, chunks: &[([u8; 4], Vec<u8>)], rows: &[u8]) -> Vec<u8> {
+    let mut ihdr = header.width.to_be_bytes().to_vec();
+    ihdr.extend(header.height.to_be_bytes());
+    ihdr.extend([header.bit_depth, header.color_type.code(), 0, 0, header.interlaced as u8]);
+
+    let mut bytes = png::SIGNATURE.to_vec();
+    bytes.extend(chunk(png::chunk::IHDR, &ihdr));
+
+    for (kind, data) in chunks {
+        bytes.extend(chunk(*kind, data));
+    }
+
+    bytes.extend(chunk(png::chunk::IDAT, &zlib_stored(rows)));
+    bytes.extend(chunk(png::chunk::IEND, &[]));
+    bytes
+}
+
+///
+/// Filter a row with the given filter type, as an encoder would
+///
+fn filter(kind: u8, row: &[u8], previous: &[u8], step: usize) -> Vec<u8> {
+    let mut filtered = vec![kind];
+
+    for index in 0..row.len() {
+        let left = if index >= step { row[index - step] } else { 0 };
+        let up_left = if index >= step { previous[index - step] } else { 0 };
+        let up = previous[index];
+
+        let prediction = match kind {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            _ => paeth(left, up, up_left)
+        };
+
+        filtered.push(row[index].wrapping_sub(prediction));
+    }
+
+    filtered
+}
+
+fn rgba_rows(image: &Image, columns: impl Iterator<Item = usize> + Clone, rows: impl Iterator<Item = usize>) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut previous: Option<Vec<u8>> = None;
+
+    for (index, j) in rows.enumerate() {
+        let row: Vec<u8> = columns.clone()
+            .map(|i| image.get(i, j).unwrap())
+            .flat_map(|pixel| [pixel.red, pixel.green, pixel.blue, pixel.alpha])
+            .collect();
+
+        let previous_row = previous.take().unwrap_or_else(|| vec![0; row.len()]);
+        data.extend(filter((index % 5) as u8, &row, &previous_row, 4));
+        previous = Some(row);
+    }
+
+    data
+}
+
+fn header(width: u32, height: u32, bit_depth: u8, color_type: PngColorType) -> PngHeader {
+    PngHeader {
+        width,
+        height,
+        bit_depth,
+        color_type,
+        interlaced: false
+    }
+}
+
+#[test]
+fn png_rgba_with_every_filter() -> Result<(), String> {
+    let image = ImageGenerator::new(SEED).image_with(7, 5, Fill::Noise);
+    let bytes = build(header(7, 5, 8, PngColorType::Rgba), &[], &rgba_rows(&image, 0..7, 0..5));
+
+    let decoded = Image::try_convert_from(Png::try_from(bytes.as_slice())?, ImageConvertData::default())?;
+
+    if decoded != image {
+        return Err(String::from("Decoded png does not match the image it was built from."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn png_adam7_matches_progressive() -> Result<(), String> {
+    let image = ImageGenerator::new(SEED).image_with(11, 9, Fill::Noise);
+
+    let rows: Vec<u8> = ADAM7.iter()
+        .filter(|(x, y, _, _)| *x < 11 && *y < 9)
+        .flat_map(|(x, y, dx, dy)| rgba_rows(&image, (*x..11).step_by(*dx), (*y..9).step_by(*dy)))
+        .collect();
+
+    let mut interlaced = header(11, 9, 8, PngColorType::Rgba);
+    interlaced.interlaced = true;
+
+    let decoded = Image::try_convert_from(Png::try_from(build(interlaced, &[], &rows))?, ImageConvertData::default())?;
+
+    if decoded != image {
+        return Err(String::from("Decoded interlaced png does not match the image it was built from."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn png_indexed_and_gray() -> Result<(), String> {
+    let palette = vec![0xFF, 0, 0, 0, 0xFF, 0, 0, 0, 0xFF];
+
+    //3 pixels of 2 bits per row, padded to a byte: 0 1 2, then 2 1 0
+    let rows = [0, 0b0001_1000, 0, 0b1001_0000];
+
+    let bytes = build(header(3, 2, 2, PngColorType::Indexed), &[(png::chunk::PLTE, palette.clone()), (png::chunk::TRNS, vec![0x80])], &rows);
+    let decoded = Png::try_from(bytes.as_slice())?;
+
+    if decoded.pixels != PngPixels::Indices(vec![0, 1, 2, 2, 1, 0]) {
+        return Err(format!("Unexpected indices {:?}.", decoded.pixels));
+    }
+
+    let image = Image::try_convert_from(decoded, ImageConvertData::default())?;
+
+    if image.get(0, 0) != Some(color::ARGB { alpha: 0x80, red: 0xFF, green: 0, blue: 0 })
+        || image.get(1, 1) != Some(color::ARGB { alpha: 0xFF, red: 0, green: 0xFF, blue: 0 }) {
+        return Err(format!("Unexpected colors {:?} and {:?}.", image.get(0, 0), image.get(1, 1)));
+    }
+
+    //Index 3 is past the end of the palette
+    let bytes = build(header(1, 1, 2, PngColorType::Indexed), &[(png::chunk::PLTE, palette)], &[0, 0b1100_0000]);
+
+    if Image::try_convert_from(Png::try_from(bytes.as_slice())?, ImageConvertData::default()).is_ok() {
+        return Err(String::from("Expected an index outside of the palette to fail without a fallback."));
+    }
+
+    //16-bit gray, where 0x1234 is transparent
+    let rows = [0, 0x12, 0x34, 0xAB, 0xCD];
+    let bytes = build(header(2, 1, 16, PngColorType::Grayscale), &[(png::chunk::TRNS, vec![0x12, 0x34])], &rows);
+    let image = Image::try_convert_from(Png::try_from(bytes.as_slice())?, ImageConvertData::default())?;
+
+    if image.get(0, 0).map(|pixel| pixel.alpha) != Some(0) || image.get(1, 0) != Some(color::ARGB { alpha: 0xFF, red: 0xAB, green: 0xAB, blue: 0xAB }) {
+        return Err(format!("Unexpected gray pixels {:?} and {:?}.", image.get(0, 0), image.get(1, 0)));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn png_rejects_malformed() -> Result<(), String> {
+    let valid = build(header(1, 1, 8, PngColorType::Rgb), &[], &[0, 1, 2, 3]);
+
+    if crate::image::format::detect(&valid) != Some(crate::image::format::ImageFormat::Png) {
+        return Err(String::from("Expected the png to be detected."));
+    }
+
+    let mut corrupt = valid.clone();
+    corrupt[20] ^= 1;
+
+    let cases: [(Vec<u8>, &str); 4] = [
+        (corrupt, "CRC"),
+        (valid[..valid.len() - 6].to_vec(), "truncated"),
+        (build(header(1, 1, 8, PngColorType::Indexed), &[], &[0, 0]), "PLTE"),
+        (build(header(1, 1, 4, PngColorType::Rgb), &[], &[0, 0]), "Bit depth")
+    ];
+
+    for (bytes, expected) in cases {
+        match Png::try_from(bytes.as_slice()) {
+            Ok(_) => return Err(format!("Expected an error mentioning {expected}.")),
+            Err(err) if !err.to_string().contains(expected) => return Err(format!("Expected an error mentioning {expected}, got '{err}'.")),
+            Err(_) => ()
+        }
+    }
+
+    Ok(())
+}
+#[test]
+fn png_icc_profile() -> Result<(), String> {
+    let profile = b"not a real profile, but kept as it is".to_vec();
+
+    let iccp = |name: &[u8], method: u8| {
+        let mut data = name.to_vec();
+        data.extend([0, method]);
+        data.extend(zlib_stored(&profile));
+        (png::chunk::ICCP, data)
+    };
+
+    let bytes = build(header(1, 1, 8, PngColorType::Rgb), &[iccp(b"Display", 0)], &[0, 1, 2, 3]);
+    let png = Png::try_from(bytes.as_slice())?;
+
+    if png.icc_profile.as_ref() != Some(&profile) {
+        return Err(format!("Expected the iCCP profile to be inflated, got {:?}.", png.icc_profile));
+    }
+
+    let image = Image::try_convert_from(png, ImageConvertData::default())?;
+
+    if image.metadata().icc_profile.as_ref() != Some(&profile) {
+        return Err(String::from("Expected the image to carry the profile of the png."));
+    }
+
+    //A profile which can't be read is skipped, since the pixels can be shown without it
+    for (name, method) in [(&b""[..], 0), (&b"Display"[..], 1)] {
+        let bytes = build(header(1, 1, 8, PngColorType::Rgb), &[iccp(name, method)], &[0, 1, 2, 3]);
+
+        if Png::try_from(bytes.as_slice())?.icc_profile.is_some() {
+            return Err(format!("Expected the iCCP chunk named {name:?} with method {method} to be skipped."));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests;
+
+use alloc::{format, vec::Vec};
+
+use crate::{image, convert::ConvertableFrom, error::ImageError};
+use image::Image;
+pub use image::buffer::PixelFormat;
+
+///
+/// A headerless buffer of pixels, such as an RGBA dump from a GPU or
+/// an array from scientific code. Nothing in the bytes says how they
+/// are laid out, so the layout is always given alongside them.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Raw {
+    pub layout: RawConvertData,
+    pub bytes: Vec<u8>
+}
+
+///
+/// The size and layout of the pixels of a raw buffer
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawConvertData {
+    pub width: usize,
+    pub height: usize,
+    ///
+    /// The order and size of the channels of each pixel
+    ///
+    pub format: PixelFormat,
+    ///
+    /// Bytes from the start of one row to the next. If not given, rows are packed.
+    ///
+    pub stride: Option<usize>
+}
+
+impl RawConvertData {
+    pub fn new(width: usize, height: usize, format: PixelFormat) -> Self {
+        Self {
+            width,
+            height,
+            format,
+            stride: None
+        }
+    }
+
+    pub fn with_stride(mut self, stride: usize) -> Self {
+        self.stride = Some(stride);
+        self
+    }
+
+    ///
+    /// Bytes from the start of one row to the next. Fails if packed rows
+    /// would be too large to fit in a usize.
+    ///
+    pub fn stride(&self) -> Result<usize, ImageError> {
+        self.stride.map_or_else(|| self.format.min_stride(self.width), Ok)
+    }
+
+    ///
+    /// Number of bytes a buffer with this layout needs. Fails if it
+    /// doesn't fit in a usize.
+    ///
+    pub fn buffer_size(&self) -> Result<usize, ImageError> {
+        self.format.buffer_size(self.width, self.height, self.stride()?)
+    }
+}
+
+impl Raw {
+    ///
+    /// Pair the bytes with their layout, checking that they are enough to hold it
+    ///
+    pub fn new(bytes: Vec<u8>, layout: RawConvertData) -> Result<Self, ImageError> {
+        let (stride, size) = (layout.stride()?, layout.buffer_size()?);
+
+        if bytes.len() < size {
+            return Err(ImageError::Decode(format!("A {}x{} {} buffer with stride {stride} needs {size} bytes, but has {}.",
+                layout.width, layout.height, layout.format.name(), bytes.len())));
+        }
+
+        Ok(Self {
+            layout,
+            bytes
+        })
+    }
+}
+
+impl From<Raw> for Vec<u8> {
+    fn from(value: Raw) -> Self {
+        value.bytes
+    }
+}
+
+///
+/// Write the pixels in the given layout, with any row padding left as zeros.
+/// The width and height of the layout are taken from the image.
+///
+impl ConvertableFrom<Image> for Raw {
+    type Options = RawConvertData;
+    type Error = ImageError;
+
+    fn try_convert_from(value: Image, options: Self::Options) -> Result<Self, Self::Error> {
+        let layout = RawConvertData {
+            width: value.width(),
+            height: value.height(),
+            ..options
+        };
+
+        Ok(Self {
+            bytes: value.to_raw_buffer(layout.stride()?, layout.format)?,
+            layout
+        })
+    }
+}
+
+impl ConvertableFrom<Raw> for Image {
+    type Options = ();
+    type Error = ImageError;
+
+    fn try_convert_from(value: Raw, _options: Self::Options) -> Result<Self, Self::Error> {
+        let layout = value.layout;
+        Image::from_raw_buffer(&value.bytes, layout.width, layout.height, layout.stride()?, layout.format)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,37 @@
+use super::*;
+use alloc::{string::{String, ToString}, vec};
+use crate::color;
+
+#[test]
+fn raw_round_trips_with_stride() -> Result<(), String> {
+    let pixels = (0..15_u8)
+        .map(|n| color::ARGB { alpha: 0xFF - n, red: n * 16, green: n, blue: 0x80 + n })
+        .collect();
+
+    let image = Image::new_pixels(5, 3, pixels);
+
+    //Rows padded to 32 bytes, as GPU readbacks often are
+    let layout = RawConvertData::new(0, 0, PixelFormat::Rgba8).with_stride(32);
+    let raw = Raw::try_convert_from(image.clone(), layout)?;
+
+    if raw.layout.width != 5 || raw.layout.height != 3 || raw.bytes.len() != 96 {
+        return Err(format!("Unexpected layout {:?} with {} bytes.", raw.layout, raw.bytes.len()));
+    }
+
+    if raw.bytes[32..36] != [80, 5, 0x85, 0xFA] || raw.bytes[20..32].iter().any(|byte| *byte != 0) {
+        return Err(format!("Unexpected bytes {:?}.", raw.bytes));
+    }
+
+    //Read back from bytes alone, as if from a file
+    let bytes: Vec<u8> = raw.into();
+    let read = Image::try_convert_from(Raw::new(bytes, RawConvertData::new(5, 3, PixelFormat::Rgba8).with_stride(32))?, ())?;
+
+    if read != image {
+        return Err(String::from("Reading back the raw buffer changed the image."));
+    }
+
+    match Raw::new(vec![0; 44], RawConvertData::new(5, 3, PixelFormat::Rgb8)) {
+        Err(err) if err.to_string().contains("needs 45 bytes") => Ok(()),
+        other => Err(format!("Expected a short buffer to fail, got {other:?}."))
+    }
+}
\ No newline at end of file
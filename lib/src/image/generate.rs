@@ -0,0 +1,288 @@
+use super::Image;
+use crate::color;
+use crate::utility::random::Random;
+
+#[cfg(test)]
+mod tests;
+
+///
+/// Full intensity of a color bar, as 75% bars are standard so that
+/// they stay within range of every video system
+///
+const BAR_LEVEL: u8 = 0xBF;
+
+///
+/// A standard image for checking codecs, renderers and operations by eye or by test
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TestPattern {
+    ///
+    /// Every pixel is the same color
+    ///
+    Solid(color::ARGB),
+    ///
+    /// A linear blend from one color to another, left to right or top to bottom
+    ///
+    Gradient {
+        from: color::ARGB,
+        to: color::ARGB,
+        horizontal: bool
+    },
+    ///
+    /// SMPTE-style color bars: 75% bars of gray, yellow, cyan, green, magenta, red and
+    /// blue, a strip of the reversed bars, then -I, white, +Q and near-black PLUGE bars
+    /// for setting black level. Approximated in full range sRGB.
+    ///
+    Bars,
+    ///
+    /// Squares of alternating colors, cell pixels wide, starting with the first at the top-left
+    ///
+    Checkerboard {
+        cell: usize,
+        colors: (color::ARGB, color::ARGB)
+    },
+    ///
+    /// Every channel of every opaque pixel is random, which is the hardest
+    /// content to compress. The same seed always generates the same image.
+    ///
+    Noise {
+        seed: u64
+    }
+}
+
+impl TestPattern {
+    pub fn generate(&self, width: usize, height: usize) -> Image {
+        match *self {
+            Self::Solid(color) => Image::new_pixels(width, height, vec![color; width * height]),
+            Self::Gradient { from, to, horizontal } => {
+                let steps = if horizontal { width } else { height };
+
+                Image::from_fn(width, height, |x, y| {
+                    let position = if horizontal { x } else { y };
+                    from.lerp(&to, position as f32 / (steps.max(2) - 1) as f32)
+                })
+            },
+            Self::Bars => bars(width, height),
+            Self::Checkerboard { cell, colors } => {
+                let cell = cell.max(1);
+
+                Image::from_fn(width, height, |x, y| {
+                    if (x / cell + y / cell) % 2 == 0 { colors.0 } else { colors.1 }
+                })
+            },
+            Self::Noise { seed } => ImageGenerator::new(seed)
+                .opaque(true)
+                .image_with(width, height, Fill::Noise)
+        }
+    }
+}
+
+fn bars(width: usize, height: usize) -> Image {
+    let rgb = |red: u8, green: u8, blue: u8| color::ARGB { alpha: 0xFF, red, green, blue };
+
+    let (level, black) = (BAR_LEVEL, rgb(0, 0, 0));
+
+    let top = [
+        rgb(level, level, level), rgb(level, level, 0), rgb(0, level, level), rgb(0, level, 0),
+        rgb(level, 0, level), rgb(level, 0, 0), rgb(0, 0, level)
+    ];
+
+    let middle = [rgb(0, 0, level), black, rgb(level, 0, level), black, rgb(0, level, level), black, rgb(level, level, level)];
+
+    //The bottom row is in 28ths of the width: -I, white, +Q and black under the first five bars,
+    //then PLUGE bars just below, at and just above black, then black under the last bar
+    let bottom = [
+        (5, rgb(0x00, 0x21, 0x4C)), (5, rgb(0xFF, 0xFF, 0xFF)), (5, rgb(0x32, 0x00, 0x6A)), (5, black),
+        (1, rgb(0x00, 0x00, 0x00)), (1, rgb(0x05, 0x05, 0x05)), (2, rgb(0x0A, 0x0A, 0x0A)), (4, black)
+    ];
+
+    Image::from_fn(width, height, |x, y| {
+        //Bars take the top 2/3, the reversed strip the next 1/12, and the rest is the bottom row
+        if y * 3 < height * 2 {
+            top[x * top.len() / width]
+        }
+        else if y * 12 < height * 9 {
+            middle[x * middle.len() / width]
+        }
+        else {
+            let position = x * 28 / width;
+
+            bottom.iter()
+                .scan(0, |end, (span, color)| {
+                    *end += span;
+                    Some((*end, *color))
+                })
+                .find(|(end, _)| position < *end)
+                .map_or(black, |(_, color)| color)
+        }
+    })
+}
+
+///
+/// How a generated image's pixels are filled
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fill {
+    ///
+    /// Every pixel is the same color
+    ///
+    Solid,
+    ///
+    /// A linear blend between two colors, horizontally or vertically
+    ///
+    Gradient,
+    ///
+    /// Every channel of every pixel is random
+    ///
+    Noise,
+    ///
+    /// Pixels are chosen at random from a palette of the given number of colors
+    ///
+    Palette(usize)
+}
+
+///
+/// Generates structured random images, i.e. for tests, so that codecs and operations
+/// can be checked against many sizes and kinds of content rather than a few fixed inputs.
+/// The same seed always generates the same images.
+///
+#[derive(Debug, Clone)]
+pub struct ImageGenerator {
+    random: Random,
+    max_size: usize,
+    opaque: bool
+}
+
+impl ImageGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            random: Random::new(seed),
+            max_size: 32,
+            opaque: false
+        }
+    }
+
+    ///
+    /// Limit the width and height of generated images to at most max_size
+    ///
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    ///
+    /// Only generate fully opaque pixels, for formats which don't store alpha
+    ///
+    pub fn opaque(mut self, opaque: bool) -> Self {
+        self.opaque = opaque;
+        self
+    }
+
+    ///
+    /// A random color, opaque unless the generator allows transparency
+    ///
+    pub fn color(&mut self) -> color::ARGB {
+        let value = self.random.next_u64();
+
+        color::ARGB {
+            alpha: if self.opaque { 0xFF } else { (value >> 24) as u8 },
+            red: (value >> 16) as u8,
+            green: (value >> 8) as u8,
+            blue: value as u8
+        }
+    }
+
+    ///
+    /// A random width and height, each in [1, max_size]
+    ///
+    pub fn size(&mut self) -> (usize, usize) {
+        let max_size = self.max_size.max(1);
+        (1 + self.random.next_below(max_size), 1 + self.random.next_below(max_size))
+    }
+
+    ///
+    /// A random fill, with palettes of up to max_colors colors
+    ///
+    pub fn fill(&mut self, max_colors: usize) -> Fill {
+        match self.random.next_below(4) {
+            0 => Fill::Solid,
+            1 => Fill::Gradient,
+            2 => Fill::Noise,
+            _ => Fill::Palette(1 + self.random.next_below(max_colors.max(1)))
+        }
+    }
+
+    ///
+    /// An image of the given size and fill
+    ///
+    pub fn image_with(&mut self, width: usize, height: usize, fill: Fill) -> Image {
+        let length = width * height;
+
+        let pixels: Vec<color::ARGB> = match fill {
+            Fill::Solid => vec![self.color(); length],
+            Fill::Gradient => {
+                let (from, to) = (self.color(), self.color());
+                let horizontal = self.random.next_below(2) == 0;
+                let steps = if horizontal { width } else { height };
+
+                (0..length)
+                    .map(|n| {
+                        let position = if horizontal { n % width } else { n / width };
+                        let t = position as f32 / (steps.max(2) - 1) as f32;
+                        from.lerp(&to, t)
+                    })
+                    .collect()
+            },
+            Fill::Noise => (0..length).map(|_| self.color()).collect(),
+            Fill::Palette(colors) => {
+                let palette = self.palette(colors);
+                (0..length).map(|_| palette[self.random.next_below(palette.len())]).collect()
+            }
+        };
+
+        Image::new_pixels(width, height, pixels)
+    }
+
+    ///
+    /// An image of random size and fill, with palettes of up to max_colors colors
+    ///
+    pub fn image(&mut self, max_colors: usize) -> Image {
+        let (width, height) = self.size();
+        let fill = self.fill(max_colors);
+        self.image_with(width, height, fill)
+    }
+
+    ///
+    /// count images of random size and fill, preceded by the degenerate
+    /// 0×0, 0×N and N×0 images which every codec and operation should handle
+    ///
+    pub fn images(&mut self, count: usize, max_colors: usize) -> Vec<Image> {
+        let (width, height) = self.size();
+
+        let mut images = vec![
+            Image::new(0, 0),
+            Image::new(0, height),
+            Image::new(width, 0)
+        ];
+
+        images.extend((0..count).map(|_| self.image(max_colors)));
+        images
+    }
+
+    ///
+    /// colors distinct random colors
+    ///
+    fn palette(&mut self, colors: usize) -> Vec<color::ARGB> {
+        let mut palette: Vec<color::ARGB> = Vec::with_capacity(colors);
+
+        while palette.len() < colors.max(1) {
+            let color = self.color();
+
+            if !palette.contains(&color) {
+                palette.push(color);
+            }
+        }
+
+        palette
+    }
+}
\ No newline at end of file
@@ -0,0 +1,80 @@
+use super::*;
+use std::collections::HashSet;
+
+#[test]
+fn generator_is_reproducible() -> Result<(), String> {
+    let first = ImageGenerator::new(7).images(20, 16);
+    let second = ImageGenerator::new(7).images(20, 16);
+
+    if first != second {
+        Err(String::from("Expected the same seed to generate the same images."))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn generated_images_respect_options() -> Result<(), String> {
+    let mut generator = ImageGenerator::new(11).max_size(8).opaque(true);
+    let images = generator.images(50, 4);
+
+    if images.iter().take(3).any(|image| image.length() != 0) {
+        return Err(String::from("Expected the first images to be degenerate."));
+    }
+
+    for (n, image) in images.iter().enumerate().skip(3) {
+        if image.width() == 0 || image.width() > 8 || image.height() == 0 || image.height() > 8 {
+            return Err(format!("Image {n} is {}x{}, which is outside [1, 8].", image.width(), image.height()));
+        }
+
+        if image.iter().flatten().any(|pixel| pixel.alpha != 0xFF) {
+            return Err(format!("Image {n} has a transparent pixel."));
+        }
+    }
+
+    let palette = generator.image_with(16, 16, Fill::Palette(3));
+    let colors: HashSet<color::ARGB> = palette.iter().flatten().copied().collect();
+
+    if colors.len() > 3 {
+        Err(format!("Expected at most 3 colors, got {}.", colors.len()))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_patterns() -> Result<(), String> {
+    let (black, white) = (color::ARGB { alpha: 0xFF, red: 0, green: 0, blue: 0 }, color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF });
+
+    let gradient = TestPattern::Gradient { from: black, to: white, horizontal: true }.generate(5, 2);
+
+    if gradient.row(1).iter().map(|pixel| pixel.red).collect::<Vec<_>>() != [0x00, 0x40, 0x80, 0xBF, 0xFF] {
+        return Err(format!("Unexpected gradient {:?}.", gradient.row(1)));
+    }
+
+    let checkerboard = TestPattern::Checkerboard { cell: 2, colors: (black, white) }.generate(4, 4);
+
+    if checkerboard.row(1)[1] != black || checkerboard.row(1)[2] != white || checkerboard.row(2)[0] != white || checkerboard.row(3)[3] != black {
+        return Err(String::from("Expected 2x2 cells of alternating colors."));
+    }
+
+    let bars = TestPattern::Bars.generate(28, 12);
+    let yellow = color::ARGB { alpha: 0xFF, red: BAR_LEVEL, green: BAR_LEVEL, blue: 0 };
+
+    if bars.row(0)[4] != yellow || bars.row(8)[4] != black || bars.row(11)[0] != (color::ARGB { alpha: 0xFF, red: 0x00, green: 0x21, blue: 0x4C }) {
+        return Err(String::from("Expected yellow bars, a reversed strip and -I at the bottom left."));
+    }
+
+    let noise = TestPattern::Noise { seed: 3 };
+
+    if noise.generate(8, 8) != noise.generate(8, 8) || noise.generate(8, 8).iter().flatten().any(|pixel| pixel.alpha != 0xFF) {
+        return Err(String::from("Expected noise to be opaque and reproducible."));
+    }
+
+    if TestPattern::Solid(white).generate(3, 0).length() != 0 || TestPattern::Bars.generate(0, 4).length() != 0 {
+        Err(String::from("Expected empty patterns to have no pixels."))
+    }
+    else {
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,247 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{color, convert::ConvertableFrom, error::ImageError};
+use super::Image;
+
+///
+/// Number of pixels with each value of each channel
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    pub alpha: [u32; 256],
+    pub red: [u32; 256],
+    pub green: [u32; 256],
+    pub blue: [u32; 256],
+    ///
+    /// Relative luminance of each pixel, ignoring alpha
+    ///
+    pub luminance: [u32; 256]
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            alpha: [0; 256],
+            red: [0; 256],
+            green: [0; 256],
+            blue: [0; 256],
+            luminance: [0; 256]
+        }
+    }
+}
+
+impl Histogram {
+    ///
+    /// Group the counts of a channel into the given number of
+    /// equally sized bins
+    ///
+    pub fn bin(channel: &[u32; 256], bins: usize) -> Vec<u32> {
+        let bins = bins.clamp(1, 256);
+        let mut binned = vec![0_u32; bins];
+
+        for (value, count) in channel.iter().enumerate() {
+            binned[value * bins / 256] += count;
+        }
+
+        binned
+    }
+}
+
+impl Image {
+    ///
+    /// Count the pixels with each value of each channel
+    ///
+    pub fn histogram(&self) -> Histogram {
+        let mut histogram = Histogram::default();
+
+        for pixel in self.pixels.iter() {
+            histogram.alpha[pixel.alpha as usize] += 1;
+            histogram.red[pixel.red as usize] += 1;
+            histogram.green[pixel.green as usize] += 1;
+            histogram.blue[pixel.blue as usize] += 1;
+            histogram.luminance[pixel.luminance() as usize] += 1;
+        }
+
+        histogram
+    }
+}
+
+///
+/// The color space a ColorHistogram divides into bins
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    ///
+    /// Red, green and blue, as stored
+    ///
+    #[default]
+    Rgb,
+    ///
+    /// Lightness, green-red and blue-yellow in OKLab, which is perceptually
+    /// uniform, so colors that look alike share bins more often
+    ///
+    Oklab
+}
+
+///
+/// Range of the a and b axes of OKLab which the bins cover; every sRGB color is within it
+///
+const OKLAB_AB_RANGE: f32 = 0.35;
+
+///
+/// The fraction of an image's pixels in each cell of a coarse grid over a 3D color
+/// space, for comparing the colors of images regardless of their layout or size.
+/// Pixels are weighted by alpha, so transparent pixels aren't counted.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorHistogram {
+    space: ColorSpace,
+    bins: usize,
+    weights: Vec<f32>
+}
+
+impl ColorHistogram {
+    pub fn space(&self) -> ColorSpace {
+        self.space
+    }
+
+    ///
+    /// Number of bins along each axis
+    ///
+    pub fn bins(&self) -> usize {
+        self.bins
+    }
+
+    ///
+    /// Fraction of the pixels in the bin with the given index along each axis
+    ///
+    pub fn get(&self, x: usize, y: usize, z: usize) -> f32 {
+        if x >= self.bins || y >= self.bins || z >= self.bins {
+            return 0_f32;
+        }
+
+        self.weights.get((x * self.bins + y) * self.bins + z).copied().unwrap_or_default()
+    }
+
+    ///
+    /// Histogram intersection: the fraction of pixels the histograms have in common,
+    /// from 0 for no colors in common to 1 for the same distribution of colors
+    ///
+    pub fn intersection(&self, other: &Self) -> Result<f32, ImageError> {
+        self.check_comparable(other)?;
+
+        Ok(self.weights.iter()
+            .zip(other.weights.iter())
+            .map(|(a, b)| a.min(*b))
+            .sum())
+    }
+
+    ///
+    /// Approximate earth mover's distance: how far, as a fraction of the space, pixels must
+    /// move to turn one distribution of colors into the other. Computed exactly along
+    /// each axis and averaged, from 0 for the same distribution up to 1.
+    /// Unlike intersection, similar but not identical colors count as close.
+    ///
+    pub fn earth_movers_distance(&self, other: &Self) -> Result<f32, ImageError> {
+        self.check_comparable(other)?;
+
+        if self.bins < 2 {
+            return Ok(0_f32);
+        }
+
+        let distance: f32 = (0..3)
+            .map(|axis| {
+                let (a, b) = (self.marginal(axis), other.marginal(axis));
+
+                //In 1D, the distance is the area between the cumulative distributions
+                a.iter()
+                    .zip(b.iter())
+                    .scan(0_f32, |difference, (a, b)| {
+                        *difference += a - b;
+                        Some(difference.abs())
+                    })
+                    .sum::<f32>()
+            })
+            .sum();
+
+        Ok((distance / (3 * (self.bins - 1)) as f32).clamp(0_f32, 1_f32))
+    }
+
+    ///
+    /// A single score of how alike the colors of two images are, from 0 to 1
+    ///
+    pub fn similarity(&self, other: &Self) -> Result<f32, ImageError> {
+        Ok(1_f32 - self.earth_movers_distance(other)?)
+    }
+
+    ///
+    /// Fraction of the pixels at each index along one axis
+    ///
+    fn marginal(&self, axis: usize) -> Vec<f32> {
+        let mut marginal = vec![0_f32; self.bins];
+
+        for (index, weight) in self.weights.iter().enumerate() {
+            let position = [index / (self.bins * self.bins), (index / self.bins) % self.bins, index % self.bins];
+            marginal[position[axis]] += weight;
+        }
+
+        marginal
+    }
+
+    fn check_comparable(&self, other: &Self) -> Result<(), ImageError> {
+        if self.space != other.space || self.bins != other.bins {
+            return Err(ImageError::InvalidArgument(format!(
+                "Can't compare a {:?} histogram with {} bins to a {:?} histogram with {} bins.",
+                self.space, self.bins, other.space, other.bins
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl Image {
+    ///
+    /// Divide the given color space into bins x bins x bins cells, and find
+    /// the fraction of the pixels in each. Bins are limited to [1, 32].
+    ///
+    pub fn color_histogram(&self, space: ColorSpace, bins: usize) -> ColorHistogram {
+        let bins = bins.clamp(1, 32);
+        let mut weights = vec![0_f32; bins * bins * bins];
+
+        //Position in [0, 1] of a color along each axis
+        let position = |pixel: &color::ARGB| -> [f32; 3] {
+            match space {
+                ColorSpace::Rgb => [pixel.red, pixel.green, pixel.blue].map(|channel| channel as f32 / 255_f32),
+                ColorSpace::Oklab => {
+                    let lab = color::AOKLAB::try_convert_from(*pixel, ()).unwrap_or_default();
+                    let ab = |value: f32| (value + OKLAB_AB_RANGE) / (2_f32 * OKLAB_AB_RANGE);
+                    [lab.l, ab(lab.a), ab(lab.b)]
+                }
+            }
+        };
+
+        let index = |value: f32| ((value.clamp(0_f32, 1_f32) * bins as f32) as usize).min(bins - 1);
+
+        let mut total = 0_f32;
+
+        for pixel in self.pixels.iter().filter(|pixel| pixel.alpha > 0) {
+            let [x, y, z] = position(pixel).map(index);
+            let weight = pixel.alpha as f32 / 255_f32;
+
+            weights[(x * bins + y) * bins + z] += weight;
+            total += weight;
+        }
+
+        if total > 0_f32 {
+            weights.iter_mut().for_each(|weight| *weight /= total);
+        }
+
+        ColorHistogram {
+            space,
+            bins,
+            weights
+        }
+    }
+}
\ No newline at end of file
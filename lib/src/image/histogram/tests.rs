@@ -0,0 +1,96 @@
+use super::*;
+use crate::color;
+
+#[test]
+fn histogram_counts() -> Result<(), String> {
+    let image = Image::new_pixels(3, 1, vec![
+        color::ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x00 },
+        color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF },
+        color::ARGB { alpha: 0x00, red: 0x00, green: 0x00, blue: 0x00 }
+    ]);
+
+    let histogram = image.histogram();
+
+    if histogram.red[0xFF] != 2 || histogram.red[0x00] != 1 {
+        return Err(format!("Unexpected red counts {} and {}.", histogram.red[0xFF], histogram.red[0x00]));
+    }
+
+    if histogram.luminance[0xFF] != 1 || histogram.luminance[0x00] != 1 {
+        return Err(String::from("Unexpected luminance counts."));
+    }
+
+    if histogram.alpha.iter().sum::<u32>() != 3 {
+        return Err(String::from("Every pixel should be counted once."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn bin_counts() -> Result<(), String> {
+    let mut channel = [0_u32; 256];
+    channel[0] = 1;
+    channel[127] = 2;
+    channel[128] = 3;
+    channel[255] = 4;
+
+    let binned = Histogram::bin(&channel, 2);
+
+    if binned != vec![3, 7] {
+        return Err(format!("Expected [3, 7], got {binned:?}."));
+    }
+
+    Ok(())
+}
+
+fn solid(pixel: color::ARGB) -> Image {
+    Image::new_pixels(2, 2, vec![pixel; 4])
+}
+
+#[test]
+fn color_histogram_similarity() -> Result<(), String> {
+    let red = solid(color::ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x00 });
+    let dark_red = solid(color::ARGB { alpha: 0xFF, red: 0xC0, green: 0x00, blue: 0x00 });
+    let blue = solid(color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0xFF });
+
+    for space in [ColorSpace::Rgb, ColorSpace::Oklab] {
+        let histograms = [&red, &dark_red, &blue].map(|image| image.color_histogram(space, 8));
+
+        let same = histograms[0].intersection(&histograms[0]).map_err(|e| e.to_string())?;
+
+        if (same - 1_f32).abs() > 0.001 {
+            return Err(format!("Expected a {space:?} histogram to fully intersect itself, got {same}."));
+        }
+
+        let near = histograms[0].similarity(&histograms[1]).map_err(|e| e.to_string())?;
+        let far = histograms[0].similarity(&histograms[2]).map_err(|e| e.to_string())?;
+
+        if near <= far || near >= 1_f32 {
+            return Err(format!("Expected red to be more like dark red ({near}) than blue ({far}) in {space:?}."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn color_histogram_weights() -> Result<(), String> {
+    let image = Image::new_pixels(3, 1, vec![
+        color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF },
+        color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0x00 },
+        color::ARGB { alpha: 0x00, red: 0xFF, green: 0x00, blue: 0x00 }
+    ]);
+
+    let histogram = image.color_histogram(ColorSpace::Rgb, 2);
+
+    if histogram.get(1, 1, 1) != 0.5 || histogram.get(0, 0, 0) != 0.5 || histogram.get(1, 0, 0) != 0_f32 {
+        return Err(format!("Expected transparent pixels to be ignored, got {histogram:?}."));
+    }
+
+    if histogram.intersection(&image.color_histogram(ColorSpace::Oklab, 2)).is_ok()
+        || histogram.intersection(&image.color_histogram(ColorSpace::Rgb, 4)).is_ok() {
+        return Err(String::from("Expected histograms of different spaces or bins not to be comparable."));
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,206 @@
+#[cfg(test)]
+mod tests;
+
+use crate::color;
+use crate::utility::random::{self, Random};
+use super::Image;
+
+///
+/// A kind of noise, which can either be generated as a standalone
+/// image or added on top of an existing one.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Noise {
+    ///
+    /// Independent, normally distributed offsets added to each channel
+    /// of each pixel.
+    ///
+    Gaussian {
+        sigma: f32
+    },
+    ///
+    /// Each pixel is replaced with black or white with the
+    /// given probability.
+    ///
+    SaltAndPepper {
+        p: f32
+    },
+    ///
+    /// Smoothly interpolated random values on a lattice with the given
+    /// cell size in pixels. Offsets range within +/- amplitude.
+    ///
+    Value {
+        scale: f32,
+        amplitude: f32
+    },
+    ///
+    /// Gradient (Perlin) noise on a lattice with the given cell size
+    /// in pixels. Offsets range within +/- amplitude.
+    ///
+    Perlin {
+        scale: f32,
+        amplitude: f32
+    }
+}
+
+///
+/// Gaussian noise with standard deviation sigma
+///
+pub fn gaussian(sigma: f32) -> Noise {
+    Noise::Gaussian { sigma }
+}
+
+///
+/// Salt and pepper noise, affecting each pixel with probability p
+///
+pub fn salt_and_pepper(p: f32) -> Noise {
+    Noise::SaltAndPepper { p }
+}
+
+///
+/// Value noise with the given lattice cell size, spanning the full
+/// range of intensities
+///
+pub fn value(scale: f32) -> Noise {
+    Noise::Value { scale, amplitude: 127.5 }
+}
+
+///
+/// Perlin noise with the given lattice cell size, spanning the full
+/// range of intensities
+///
+pub fn perlin(scale: f32) -> Noise {
+    Noise::Perlin { scale, amplitude: 127.5 }
+}
+
+impl Noise {
+    ///
+    /// Generate a grayscale image of the noise over a mid-gray background
+    ///
+    pub fn generate(&self, width: usize, height: usize, seed: u64) -> Image {
+        let gray = color::ARGB { alpha: 0xFF, red: 0x80, green: 0x80, blue: 0x80 };
+        let background = Image::new_pixels(width, height, vec![gray; width * height]);
+
+        //Generated noise should be gray, so use the same offset for each channel
+        self.apply_to(&background, seed, true)
+    }
+
+    ///
+    /// Add the noise to each pixel of the image. Alpha is left untouched.
+    ///
+    pub fn apply(&self, image: &Image, seed: u64) -> Image {
+        self.apply_to(image, seed, false)
+    }
+
+    fn apply_to(&self, image: &Image, seed: u64, monochrome: bool) -> Image {
+        let mut rng = Random::new(seed);
+        let mut pixels: Vec<color::ARGB> = Vec::with_capacity(image.length());
+
+        for j in 0..image.height() {
+            for (i, pixel) in image.row(j).iter().enumerate() {
+                let noisy = match self {
+                    Self::Gaussian { sigma } => {
+                        if monochrome {
+                            let offset = rng.next_gaussian() * sigma;
+                            pixel.offset(offset, offset, offset)
+                        }
+                        else {
+                            pixel.offset(
+                                rng.next_gaussian() * sigma,
+                                rng.next_gaussian() * sigma,
+                                rng.next_gaussian() * sigma
+                            )
+                        }
+                    },
+                    Self::SaltAndPepper { p } => {
+                        let roll = rng.next_f32();
+
+                        if roll < p / 2_f32 {
+                            color::ARGB { alpha: pixel.alpha, red: 0x00, green: 0x00, blue: 0x00 }
+                        }
+                        else if roll < *p {
+                            color::ARGB { alpha: pixel.alpha, red: 0xFF, green: 0xFF, blue: 0xFF }
+                        }
+                        else {
+                            *pixel
+                        }
+                    },
+                    Self::Value { scale, amplitude } => {
+                        let n = value_noise(i as f32 / scale, j as f32 / scale, seed);
+                        let offset = (2_f32 * n - 1_f32) * amplitude;
+                        pixel.offset(offset, offset, offset)
+                    },
+                    Self::Perlin { scale, amplitude } => {
+                        let n = perlin_noise(i as f32 / scale, j as f32 / scale, seed);
+                        let offset = (2_f32 * n - 1_f32) * amplitude;
+                        pixel.offset(offset, offset, offset)
+                    }
+                };
+
+                pixels.push(noisy);
+            }
+        }
+
+        Image::new_pixels(image.width(), image.height(), pixels)
+    }
+}
+
+impl Image {
+    ///
+    /// Add the given noise to this image. See Noise::apply.
+    ///
+    pub fn add_noise(&self, noise: Noise, seed: u64) -> Image {
+        noise.apply(self, seed)
+    }
+}
+
+///
+/// Quintic fade curve, so interpolation is smooth across lattice cells
+///
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6_f32 - 15_f32) + 10_f32)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+///
+/// Value noise at the given point, in [0, 1]
+///
+fn value_noise(x: f32, y: f32, seed: u64) -> f32 {
+    let lattice = |ix: i64, iy: i64| (random::hash(ix, iy, seed) >> 40) as f32 / ((1_u64 << 24) as f32);
+
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let (ix, iy) = (x0 as i64, y0 as i64);
+    let (tx, ty) = (fade(x - x0), fade(y - y0));
+
+    let top = lerp(lattice(ix, iy), lattice(ix + 1, iy), tx);
+    let bottom = lerp(lattice(ix, iy + 1), lattice(ix + 1, iy + 1), tx);
+
+    lerp(top, bottom, ty)
+}
+
+///
+/// Perlin noise at the given point, in [0, 1]
+///
+fn perlin_noise(x: f32, y: f32, seed: u64) -> f32 {
+    //Dot product of the offset from a lattice point with that point's gradient
+    let gradient = |ix: i64, iy: i64, dx: f32, dy: f32| {
+        let angle = (random::hash(ix, iy, seed) >> 40) as f32 / ((1_u64 << 24) as f32) * std::f32::consts::TAU;
+        angle.cos() * dx + angle.sin() * dy
+    };
+
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let (ix, iy) = (x0 as i64, y0 as i64);
+    let (fx, fy) = (x - x0, y - y0);
+    let (tx, ty) = (fade(fx), fade(fy));
+
+    let top = lerp(gradient(ix, iy, fx, fy), gradient(ix + 1, iy, fx - 1_f32, fy), tx);
+    let bottom = lerp(gradient(ix, iy + 1, fx, fy - 1_f32), gradient(ix + 1, iy + 1, fx - 1_f32, fy - 1_f32), tx);
+
+    //2D Perlin noise with unit gradients lies within +/- sqrt(1/2)
+    (lerp(top, bottom, ty) * std::f32::consts::SQRT_2 * 0.5_f32 + 0.5_f32).clamp(0_f32, 1_f32)
+}
\ No newline at end of file
@@ -0,0 +1,140 @@
+use super::*;
+
+///
+/// Seed of the noise, fixed so that any failure can be reproduced
+///
+const SEED: u64 = 0x5EED;
+
+const GRAY: color::ARGB = color::ARGB { alpha: 0xFF, red: 0x80, green: 0x80, blue: 0x80 };
+
+fn kinds() -> [Noise; 4] {
+    [gaussian(24_f32), salt_and_pepper(0.2_f32), value(4.5_f32), perlin(4.5_f32)]
+}
+
+///
+/// Each kind, strong enough that almost every offset runs past the ends of a channel
+///
+fn extreme_kinds() -> [Noise; 4] {
+    [
+        gaussian(1_000_000_f32),
+        salt_and_pepper(1_f32),
+        Noise::Value { scale: 4.5_f32, amplitude: 1_000_000_f32 },
+        Noise::Perlin { scale: 4.5_f32, amplitude: 1_000_000_f32 }
+    ]
+}
+
+fn translucent() -> Image {
+    let pixels = (0..64_u8)
+        .map(|n| color::ARGB { alpha: n * 4, red: n * 3, green: 255 - n * 2, blue: 0x80 })
+        .collect();
+
+    Image::new_pixels(8, 8, pixels)
+}
+
+#[test]
+fn noise_is_deterministic() -> Result<(), String> {
+    let image = translucent();
+
+    for noise in kinds() {
+        if noise.apply(&image, SEED) != noise.apply(&image, SEED) {
+            return Err(format!("{noise:?} differs between runs with the same seed."));
+        }
+
+        if noise.generate(16, 16, SEED) != noise.generate(16, 16, SEED) {
+            return Err(format!("{noise:?} generates differently with the same seed."));
+        }
+
+        if noise.generate(16, 16, SEED) == noise.generate(16, 16, SEED + 1) {
+            return Err(format!("{noise:?} generates the same image for different seeds."));
+        }
+
+        if image.add_noise(noise, SEED) != noise.apply(&image, SEED) {
+            return Err(format!("{noise:?} differs between Image::add_noise and Noise::apply."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn noise_without_strength_is_identity() -> Result<(), String> {
+    let image = translucent();
+
+    let none = [
+        gaussian(0_f32),
+        salt_and_pepper(0_f32),
+        Noise::Value { scale: 4.5_f32, amplitude: 0_f32 },
+        Noise::Perlin { scale: 4.5_f32, amplitude: 0_f32 }
+    ];
+
+    for noise in none {
+        if noise.apply(&image, SEED) != image {
+            return Err(format!("{noise:?} changed the image."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn noise_clamps_to_channel_range() -> Result<(), String> {
+    let image = Image::new_pixels(32, 32, vec![GRAY; 32 * 32]);
+
+    for noise in extreme_kinds() {
+        let noisy = noise.apply(&image, SEED);
+        let channels: Vec<u8> = noisy.pixels.iter().flat_map(|pixel| [pixel.red, pixel.green, pixel.blue]).collect();
+
+        //Offsets past either end saturate, rather than wrapping around
+        if !channels.contains(&0x00) || !channels.contains(&0xFF) {
+            return Err(format!("{noise:?} didn't reach both ends of the channel range."));
+        }
+
+        //Only offsets within a hair of zero, like those on lattice points, land in between
+        let between = channels.iter().filter(|&&channel| channel != 0x00 && channel != 0xFF).count();
+
+        if between * 20 > channels.len() {
+            return Err(format!("{noise:?} left {between} of {} channels unsaturated.", channels.len()));
+        }
+    }
+
+    //The lattice noise itself stays within [0, 1], which the amplitude is scaled from
+    for j in 0..64 {
+        for i in 0..64 {
+            let (x, y) = (i as f32 / 3.7_f32 - 8_f32, j as f32 / 3.7_f32 - 8_f32);
+
+            for (name, n) in [("Value", value_noise(x, y, SEED)), ("Perlin", perlin_noise(x, y, SEED))] {
+                if !(0_f32..=1_f32).contains(&n) {
+                    return Err(format!("{name} noise at ({x}, {y}) is {n}, outside [0, 1]."));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn noise_keeps_alpha() -> Result<(), String> {
+    let image = translucent();
+
+    for noise in kinds().into_iter().chain(extreme_kinds()) {
+        let noisy = noise.apply(&image, SEED);
+
+        if noisy.width() != image.width() || noisy.height() != image.height() {
+            return Err(format!("{noise:?} changed the size to {}x{}.", noisy.width(), noisy.height()));
+        }
+
+        for (index, (before, after)) in image.pixels.iter().zip(noisy.pixels.iter()).enumerate() {
+            if before.alpha != after.alpha {
+                return Err(format!("{noise:?} changed the alpha of pixel {index} from {} to {}.", before.alpha, after.alpha));
+            }
+        }
+
+        //Generated noise is always opaque, and gray
+        if let Some(pixel) = noise.generate(8, 8, SEED).pixels.iter().find(|pixel| pixel.alpha != 0xFF || pixel.red != pixel.green || pixel.green != pixel.blue) {
+            return Err(format!("{noise:?} generated {}, which isn't opaque gray.", pixel.to_hex()));
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,257 @@
+use super::Image;
+use super::transform::{Flip, Rotation};
+
+#[cfg(all(test, feature = "std"))]
+mod tests;
+
+///
+/// The order in which an image's pixels are stored. An Image is always stored
+/// top-down and unmirrored; other orientations describe how a format stores them.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Orientation {
+    ///
+    /// Rows are stored top to bottom, with columns left to right
+    ///
+    #[default]
+    TopDown,
+    ///
+    /// Rows are stored bottom to top, with columns left to right
+    ///
+    BottomUp,
+    ///
+    /// Rows are stored top to bottom, with columns right to left
+    ///
+    TopDownMirrored,
+    ///
+    /// Rows are stored bottom to top, with columns right to left
+    ///
+    BottomUpMirrored
+}
+
+impl Orientation {
+    ///
+    /// The orientation with the given row and column order
+    ///
+    pub fn new(top_down: bool, mirrored: bool) -> Self {
+        match (top_down, mirrored) {
+            (true, false) => Self::TopDown,
+            (false, false) => Self::BottomUp,
+            (true, true) => Self::TopDownMirrored,
+            (false, true) => Self::BottomUpMirrored
+        }
+    }
+
+    ///
+    /// The orientation encoded in the signs of a bmp's dimensions; rows are
+    /// stored bottom to top unless the height is negative, and columns
+    /// are stored right to left if the width is negative
+    ///
+    pub fn from_signs(width: i32, height: i32) -> Self {
+        Self::new(height < 0, width < 0)
+    }
+
+    pub fn is_top_down(&self) -> bool {
+        matches!(self, Self::TopDown | Self::TopDownMirrored)
+    }
+
+    pub fn is_mirrored(&self) -> bool {
+        matches!(self, Self::TopDownMirrored | Self::BottomUpMirrored)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::TopDown => "Top-down",
+            Self::BottomUp => "Bottom-up",
+            Self::TopDownMirrored => "Top-down, mirrored",
+            Self::BottomUpMirrored => "Bottom-up, mirrored"
+        }
+    }
+
+    ///
+    /// The orientation with the order of the rows reversed
+    ///
+    pub fn flipped_vertically(&self) -> Self {
+        Self::new(!self.is_top_down(), self.is_mirrored())
+    }
+
+    ///
+    /// The orientation with the order of the columns reversed
+    ///
+    pub fn flipped_horizontally(&self) -> Self {
+        Self::new(self.is_top_down(), !self.is_mirrored())
+    }
+
+    ///
+    /// The (column, row) at which the pixel (i, j) of a width x height
+    /// image is stored in this orientation. (i, j) must be within the image.
+    ///
+    pub fn stored_position(&self, i: usize, j: usize, width: usize, height: usize) -> (usize, usize) {
+        let column = if self.is_mirrored() { width - 1 - i } else { i };
+        let row = if self.is_top_down() { j } else { height - 1 - j };
+
+        (column, row)
+    }
+}
+
+impl Image {
+    ///
+    /// Reorder the pixels of an image stored in one orientation to be stored in another
+    ///
+    pub fn reoriented(&self, from: Orientation, to: Orientation) -> Image {
+        match (from.is_top_down() != to.is_top_down(), from.is_mirrored() != to.is_mirrored()) {
+            (false, false) => self.clone(),
+            (true, false) => self.flip(Flip::Vertical),
+            (false, true) => self.flip(Flip::Horizontal),
+            (true, true) => self.flip(Flip::Vertical).flip(Flip::Horizontal)
+        }
+    }
+
+    ///
+    /// Reorder the pixels of an image stored in the given orientation to be top-down and unmirrored
+    ///
+    pub fn normalized(&self, from: Orientation) -> Image {
+        self.reoriented(from, Orientation::TopDown)
+    }
+}
+
+///
+/// What a jpeg puts before its EXIF metadata
+///
+const EXIF_MARKER: [u8; 6] = *b"Exif\0\0";
+
+///
+/// The id of the orientation tag, and the type of its value, an unsigned 16 bit integer
+///
+const EXIF_ORIENTATION_TAG: u32 = 0x0112;
+const EXIF_SHORT: u32 = 3;
+
+///
+/// The orientation tag of EXIF metadata (0x0112), which tells how the stored
+/// image must be turned to be shown upright, as when a phone camera stores
+/// its sensor's rows as they are and notes how it was held
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ExifOrientation {
+    ///
+    /// Already upright
+    ///
+    #[default]
+    Normal,
+    FlipHorizontal,
+    Rotate180,
+    FlipVertical,
+    ///
+    /// Mirrored across the diagonal from the top-left corner
+    ///
+    Transpose,
+    ///
+    /// Rotate 90 degrees clockwise to show upright
+    ///
+    Rotate90,
+    ///
+    /// Mirrored across the diagonal from the top-right corner
+    ///
+    Transverse,
+    ///
+    /// Rotate 270 degrees clockwise to show upright
+    ///
+    Rotate270
+}
+
+impl ExifOrientation {
+    ///
+    /// The orientation with the given value of the tag, from 1 to 8
+    ///
+    pub fn from_tag(value: u16) -> Option<Self> {
+        match value {
+            1 => Some(Self::Normal),
+            2 => Some(Self::FlipHorizontal),
+            3 => Some(Self::Rotate180),
+            4 => Some(Self::FlipVertical),
+            5 => Some(Self::Transpose),
+            6 => Some(Self::Rotate90),
+            7 => Some(Self::Transverse),
+            8 => Some(Self::Rotate270),
+            _ => None
+        }
+    }
+
+    ///
+    /// The orientation tag of EXIF metadata, as stored in a png's eXIf chunk:
+    /// a TIFF header, then the tags of the first image. The "Exif" marker a
+    /// jpeg puts before it may be left on. None if it has no valid orientation.
+    ///
+    pub fn from_exif(exif: &[u8]) -> Option<Self> {
+        let exif = exif.strip_prefix(EXIF_MARKER.as_slice()).unwrap_or(exif);
+
+        let big_endian = match exif.get(..4)? {
+            b"MM\0*" => true,
+            b"II*\0" => false,
+            _ => return None
+        };
+
+        let read = |offset: usize, size: usize| -> Option<u32> {
+            let bytes = exif.get(offset..offset.checked_add(size)?)?;
+
+            let value = |value: u32, byte: &u8| (value << 8) | *byte as u32;
+
+            if big_endian {
+                Some(bytes.iter().fold(0, value))
+            }
+            else {
+                Some(bytes.iter().rev().fold(0, value))
+            }
+        };
+
+        //Each tag of the first directory is 12 bytes: its id, type, count, then its value
+        let directory = read(4, 4)? as usize;
+        let count = read(directory, 2)? as usize;
+
+        let entry = (0..count)
+            .map_while(|index| directory.checked_add(2 + 12 * index))
+            .find(|entry| read(*entry, 2) == Some(EXIF_ORIENTATION_TAG))?;
+
+        if read(entry + 2, 2)? != EXIF_SHORT || read(entry + 4, 4)? != 1 {
+            return None;
+        }
+
+        Self::from_tag(read(entry + 8, 2)? as u16)
+    }
+
+    pub fn tag(&self) -> u16 {
+        match self {
+            Self::Normal => 1,
+            Self::FlipHorizontal => 2,
+            Self::Rotate180 => 3,
+            Self::FlipVertical => 4,
+            Self::Transpose => 5,
+            Self::Rotate90 => 6,
+            Self::Transverse => 7,
+            Self::Rotate270 => 8
+        }
+    }
+
+    ///
+    /// Whether the width and height of the image are swapped once upright
+    ///
+    pub fn swaps_dimensions(&self) -> bool {
+        matches!(self, Self::Transpose | Self::Rotate90 | Self::Transverse | Self::Rotate270)
+    }
+
+    ///
+    /// Turn an image stored in this orientation to be upright
+    ///
+    pub fn apply(&self, image: &Image) -> Image {
+        match self {
+            Self::Normal => image.clone(),
+            Self::FlipHorizontal => image.flip(Flip::Horizontal),
+            Self::Rotate180 => image.rotate(Rotation::Rotate180),
+            Self::FlipVertical => image.flip(Flip::Vertical),
+            Self::Transpose => image.flip(Flip::Horizontal).rotate(Rotation::Rotate270),
+            Self::Rotate90 => image.rotate(Rotation::Rotate90),
+            Self::Transverse => image.flip(Flip::Horizontal).rotate(Rotation::Rotate90),
+            Self::Rotate270 => image.rotate(Rotation::Rotate270)
+        }
+    }
+}
\ No newline at end of file
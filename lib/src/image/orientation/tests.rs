@@ -0,0 +1,135 @@
+use super::*;
+use crate::image::generate::{Fill, ImageGenerator};
+
+///
+/// Seed of the generated test images, fixed so that any failure can be reproduced
+///
+const SEED: u64 = 0x5EED;
+
+#[test]
+fn orientation_from_signs() -> Result<(), String> {
+    let cases = [
+        ((4, 3), Orientation::BottomUp),
+        ((4, -3), Orientation::TopDown),
+        ((-4, 3), Orientation::BottomUpMirrored),
+        ((-4, -3), Orientation::TopDownMirrored)
+    ];
+
+    for ((width, height), expected) in cases {
+        let orientation = Orientation::from_signs(width, height);
+
+        if orientation != expected {
+            return Err(format!("Expected {width}x{height} to be {}, but got {}.", expected.name(), orientation.name()));
+        }
+
+        if orientation.flipped_vertically().flipped_horizontally() == orientation
+            || orientation.flipped_vertically().flipped_vertically() != orientation {
+            return Err(format!("Flipping {} is inconsistent.", orientation.name()));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn reoriented_matches_stored_position() -> Result<(), String> {
+    let image = ImageGenerator::new(SEED).image_with(5, 3, Fill::Noise);
+
+    for orientation in [Orientation::TopDown, Orientation::BottomUp, Orientation::TopDownMirrored, Orientation::BottomUpMirrored] {
+        let stored = image.reoriented(Orientation::TopDown, orientation);
+
+        for j in 0..image.height() {
+            for i in 0..image.width() {
+                let (column, row) = orientation.stored_position(i, j, image.width(), image.height());
+
+                if stored.get(column, row) != image.get(i, j) {
+                    return Err(format!("Pixel {i},{j} is not stored at {column},{row} in {} order.", orientation.name()));
+                }
+            }
+        }
+
+        if stored.normalized(orientation) != image {
+            return Err(format!("Normalizing {} pixels does not give back the image.", orientation.name()));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn exif_orientation_turns_upright() -> Result<(), String> {
+    let image = ImageGenerator::new(SEED).image_with(4, 3, Fill::Noise);
+    let (width, height) = (image.width(), image.height());
+
+    //Where the pixel (i, j) of the upright image is stored, for each tag
+    let stored_at = |tag: u16, i: usize, j: usize| match tag {
+        2 => (width - 1 - i, j),
+        3 => (width - 1 - i, height - 1 - j),
+        4 => (i, height - 1 - j),
+        5 => (j, i),
+        6 => (j, height - 1 - i),
+        7 => (width - 1 - j, height - 1 - i),
+        8 => (width - 1 - j, i),
+        _ => (i, j)
+    };
+
+    for tag in 1..=8 {
+        let orientation = ExifOrientation::from_tag(tag).ok_or(format!("Tag {tag} is not recognized."))?;
+        let upright = orientation.apply(&image);
+
+        if orientation.tag() != tag || orientation.swaps_dimensions() != (upright.width() != width) {
+            return Err(format!("Orientation {orientation:?} is inconsistent with tag {tag}."));
+        }
+
+        for j in 0..upright.height() {
+            for i in 0..upright.width() {
+                let (column, row) = stored_at(tag, i, j);
+
+                if upright.get(i, j) != image.get(column, row) {
+                    return Err(format!("Pixel {i},{j} of {orientation:?} should come from {column},{row}."));
+                }
+            }
+        }
+    }
+
+    if ExifOrientation::from_tag(9).is_some() {
+        return Err(String::from("Tag 9 should not be recognized."));
+    }
+
+    Ok(())
+}
+#[test]
+fn exif_orientation_from_metadata() -> Result<(), String> {
+    //A TIFF header, then a directory of a resolution tag and the orientation tag
+    let little_endian = |orientation: u16| {
+        let mut exif = b"II*\0".to_vec();
+        exif.extend(8_u32.to_le_bytes());
+        exif.extend(2_u16.to_le_bytes());
+        exif.extend([0x1A, 0x01, 5, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        exif.extend([0x12, 0x01, 3, 0, 1, 0, 0, 0]);
+        exif.extend(orientation.to_le_bytes());
+        exif.extend([0, 0]);
+        exif
+    };
+
+    let big_endian = b"Exif\0\0MM\0*\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01\0\x08\0\0".to_vec();
+
+    let cases = [
+        (little_endian(6), Some(ExifOrientation::Rotate90)),
+        (little_endian(2), Some(ExifOrientation::FlipHorizontal)),
+        (big_endian.clone(), Some(ExifOrientation::Rotate270)),
+        (little_endian(0), None),
+        (big_endian[..big_endian.len() - 4].to_vec(), None),
+        (b"XX*\0\x08\0\0\0".to_vec(), None)
+    ];
+
+    for (exif, expected) in cases {
+        let orientation = ExifOrientation::from_exif(&exif);
+
+        if orientation != expected {
+            return Err(format!("Expected {exif:?} to give {expected:?}, got {orientation:?}."));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+#[cfg(test)]
+mod tests;
+
+use crate::color;
+use crate::error::ImageError;
+use super::Image;
+
+///
+/// An operation which maps each pixel by its own value alone
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum PointOp {
+    ///
+    /// Add the amount to each color channel
+    ///
+    Brightness(f32),
+    ///
+    /// Raise each color channel, scaled to [0, 1], to the power 1 / gamma.
+    /// Gammas above 1 brighten, and below 1 darken.
+    ///
+    Gamma(f32),
+    ///
+    /// Invert each color channel
+    ///
+    Invert,
+    ///
+    /// Replace each color with its luminance (Rec. 709)
+    ///
+    Grayscale,
+    ///
+    /// Set each color channel to a weighted sum of the input channels. Each
+    /// row holds the red, green and blue weights of one output channel.
+    ///
+    Mix([[f32; 3]; 3])
+}
+
+impl PointOp {
+    ///
+    /// Whether each channel depends only on its own value, so that the
+    /// op can be folded into a lookup table
+    ///
+    fn is_per_channel(&self) -> bool {
+        !matches!(self, Self::Grayscale | Self::Mix(_))
+    }
+
+    fn validate(&self) -> Result<(), ImageError> {
+        match self {
+            Self::Brightness(amount) if !amount.is_finite() => Err(ImageError::InvalidArgument(format!("Brightness {amount} must be finite."))),
+            Self::Gamma(gamma) if !gamma.is_finite() || *gamma <= 0_f32 => Err(ImageError::InvalidArgument(format!("Gamma {gamma} must be positive."))),
+            Self::Mix(weights) if weights.iter().flatten().any(|weight| !weight.is_finite()) => {
+                Err(ImageError::InvalidArgument(String::from("Channel mix weights must be finite.")))
+            },
+            _ => Ok(())
+        }
+    }
+
+    fn channel(&self, value: u8) -> u8 {
+        match self {
+            Self::Brightness(amount) => round_channel(value as f32 + amount),
+            Self::Gamma(gamma) => round_channel((value as f32 / 255_f32).powf(1_f32 / gamma) * 255_f32),
+            Self::Invert => 255 - value,
+            Self::Grayscale | Self::Mix(_) => value
+        }
+    }
+}
+
+///
+/// A step of a compiled pipeline, applied to each pixel in turn
+///
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    ///
+    /// A run of per-channel ops, fused into one table per color channel
+    ///
+    Lookup(Box<[[u8; 256]; 3]>),
+    Mix([[f32; 3]; 3])
+}
+
+impl Step {
+    fn apply(&self, pixel: color::ARGB) -> color::ARGB {
+        match self {
+            Self::Lookup(tables) => color::ARGB {
+                alpha: pixel.alpha,
+                red: tables[0][pixel.red as usize],
+                green: tables[1][pixel.green as usize],
+                blue: tables[2][pixel.blue as usize]
+            },
+            Self::Mix(weights) => {
+                let input = [pixel.red as f32, pixel.green as f32, pixel.blue as f32];
+
+                let channel = |row: &[f32; 3]| round_channel(
+                    row[0] * input[0] + row[1] * input[1] + row[2] * input[2]
+                );
+
+                color::ARGB {
+                    alpha: pixel.alpha,
+                    red: channel(&weights[0]),
+                    green: channel(&weights[1]),
+                    blue: channel(&weights[2])
+                }
+            }
+        }
+    }
+}
+
+///
+/// A deferred chain of point operations. Nothing is applied until execute,
+/// which folds each run of per-channel ops into a single lookup table, and
+/// maps every pixel through all of the ops in a single pass over the image.
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct Pipeline {
+    ops: Vec<PointOp>
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ops(&self) -> &[PointOp] {
+        &self.ops
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    ///
+    /// Add any point op to the end of the pipeline
+    ///
+    pub fn then(mut self, op: PointOp) -> Self {
+        self.ops.push(op);
+        self
+    }
+
+    pub fn brightness(self, amount: f32) -> Self {
+        self.then(PointOp::Brightness(amount))
+    }
+
+    pub fn gamma(self, gamma: f32) -> Self {
+        self.then(PointOp::Gamma(gamma))
+    }
+
+    pub fn invert(self) -> Self {
+        self.then(PointOp::Invert)
+    }
+
+    pub fn grayscale(self) -> Self {
+        self.then(PointOp::Grayscale)
+    }
+
+    pub fn mix(self, weights: [[f32; 3]; 3]) -> Self {
+        self.then(PointOp::Mix(weights))
+    }
+
+    ///
+    /// Apply every op to the image, in a single pass
+    ///
+    pub fn execute(&self, image: &Image) -> Result<Image, ImageError> {
+        let steps = self.compile()?;
+
+        if steps.is_empty() {
+            return Ok(image.clone());
+        }
+
+        Ok(image.map_pixels(|pixel| steps.iter().fold(*pixel, |pixel, step| step.apply(pixel))))
+    }
+
+    ///
+    /// Validate the ops, and fuse each run of per-channel ops into one lookup table
+    ///
+    fn compile(&self) -> Result<Vec<Step>, ImageError> {
+        let mut steps = Vec::new();
+        let mut tables: Option<Box<[[u8; 256]; 3]>> = None;
+
+        for op in &self.ops {
+            op.validate()?;
+
+            if op.is_per_channel() {
+                let tables = tables.get_or_insert_with(|| Box::new([identity(); 3]));
+
+                for table in tables.iter_mut() {
+                    for value in table.iter_mut() {
+                        *value = op.channel(*value);
+                    }
+                }
+
+                continue;
+            }
+
+            if let Some(tables) = tables.take() {
+                steps.push(Step::Lookup(tables));
+            }
+
+            steps.push(match op {
+                PointOp::Mix(weights) => Step::Mix(*weights),
+                _ => Step::Mix([LUMINANCE; 3])
+            });
+        }
+
+        if let Some(tables) = tables {
+            steps.push(Step::Lookup(tables));
+        }
+
+        Ok(steps)
+    }
+}
+
+///
+/// Rec. 709 luma weights, as used by ARGB::luminance
+///
+const LUMINANCE: [f32; 3] = [0.2126_f32, 0.7152_f32, 0.0722_f32];
+
+fn identity() -> [u8; 256] {
+    core::array::from_fn(|value| value as u8)
+}
+
+fn round_channel(value: f32) -> u8 {
+    value.round().clamp(0_f32, 255_f32) as u8
+}
\ No newline at end of file
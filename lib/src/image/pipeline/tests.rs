@@ -0,0 +1,74 @@
+use super::*;
+
+fn gradient() -> Image {
+    let pixels = (0..16_u8)
+        .map(|n| color::ARGB { alpha: n * 16, red: n * 16, green: n * 8, blue: 255 - n * 16 })
+        .collect();
+
+    Image::new_pixels(4, 4, pixels)
+}
+
+#[test]
+fn pipeline_matches_eager_ops() -> Result<(), String> {
+    let image = gradient();
+
+    let fused = Pipeline::new()
+        .brightness(20_f32)
+        .invert()
+        .grayscale()
+        .brightness(-7.5_f32)
+        .execute(&image)?;
+
+    let eager = image.brightness(20_f32).invert().grayscale().brightness(-7.5_f32);
+
+    if fused != eager {
+        return Err(String::from("Fused pipeline differs from applying each op in turn."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn pipeline_fuses_per_channel_ops() -> Result<(), String> {
+    let pipeline = Pipeline::new()
+        .brightness(10_f32)
+        .gamma(2.2_f32)
+        .invert()
+        .mix([[0_f32, 0_f32, 1_f32], [0_f32, 1_f32, 0_f32], [1_f32, 0_f32, 0_f32]])
+        .gamma(0.5_f32);
+
+    let steps = pipeline.compile()?;
+
+    if !matches!(steps.as_slice(), [Step::Lookup(_), Step::Mix(_), Step::Lookup(_)]) {
+        return Err(format!("Expected a lookup, a mix and a lookup, got {steps:?}."));
+    }
+
+    let image = pipeline.execute(&gradient())?;
+    let pixel = gradient().row(0)[1];
+    let channel = |value: u8| 255 - round_channel((round_channel(value as f32 + 10_f32) as f32 / 255_f32).powf(1_f32 / 2.2_f32) * 255_f32);
+    let gamma = |value: u8| round_channel((value as f32 / 255_f32).powf(2_f32) * 255_f32);
+
+    let expected = color::ARGB {
+        alpha: pixel.alpha,
+        red: gamma(channel(pixel.blue)),
+        green: gamma(channel(pixel.green)),
+        blue: gamma(channel(pixel.red))
+    };
+
+    if image.row(0)[1] != expected {
+        return Err(format!("Expected {}, got {}.", expected.to_hex(), image.row(0)[1].to_hex()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn pipeline_rejects_invalid_ops() -> Result<(), String> {
+    for pipeline in [Pipeline::new().gamma(0_f32), Pipeline::new().brightness(f32::NAN)] {
+        if !matches!(pipeline.execute(&gradient()), Err(ImageError::InvalidArgument(_))) {
+            return Err(format!("Expected {:?} to be rejected.", pipeline.ops()));
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,54 @@
+use super::Image;
+
+#[cfg(test)]
+mod tests;
+
+///
+/// Keep the pixel buffers of images which are no longer needed, so they
+/// can be reused (e.g. by Bitmap::decode_into) instead of allocating new ones
+///
+#[derive(Debug, Default)]
+pub struct BufferPool {
+    images: Vec<Image>,
+    limit: Option<usize>
+}
+
+impl BufferPool {
+    ///
+    /// Create a pool which holds at most limit buffers
+    ///
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            images: Vec::new(),
+            limit: Some(limit)
+        }
+    }
+
+    ///
+    /// Take an image from the pool, or a new empty image if the pool is empty.
+    /// Its contents are unspecified; it is meant to be refilled.
+    ///
+    pub fn take(&mut self) -> Image {
+        self.images.pop().unwrap_or_default()
+    }
+
+    ///
+    /// Return an image to the pool, unless the pool is full or the image has no buffer worth keeping
+    ///
+    pub fn give(&mut self, image: Image) {
+        if image.pixels.capacity() > 0 && self.limit.is_none_or(|limit| self.images.len() < limit) {
+            self.images.push(image);
+        }
+    }
+
+    ///
+    /// The number of buffers in the pool
+    ///
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+}
\ No newline at end of file
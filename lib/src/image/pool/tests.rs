@@ -0,0 +1,46 @@
+use super::*;
+use crate::color;
+use crate::image::format::bitmap::{Bitmap, BitDepth, BitmapConvertData, DecodeOptions};
+use crate::convert::ConvertableFrom;
+
+#[test]
+fn pool_reuses_buffers() -> Result<(), String> {
+    let first = Image::new_pixels(4, 3, vec![color::ARGB { alpha: 0xFF, red: 0x10, green: 0x20, blue: 0x30 }; 12]);
+    let second = Image::new_pixels(2, 2, vec![color::ARGB { alpha: 0xFF, red: 0x40, green: 0x50, blue: 0x60 }; 4]);
+
+    let bytes = Vec::try_from(Bitmap::try_convert_from(second.clone(), BitmapConvertData {
+        bit_depth: BitDepth::Bits(24),
+        ..Default::default()
+    })?)?;
+
+    let mut pool = BufferPool::with_limit(1);
+    pool.give(first);
+    pool.give(Image::new(1, 1));
+
+    if pool.len() != 1 {
+        return Err(format!("Expected the pool to hold 1 buffer, but it held {}.", pool.len()));
+    }
+
+    let mut image = pool.take();
+    let buffer = image.pixels.as_ptr();
+
+    Bitmap::decode_into(&bytes, &mut image, DecodeOptions::default())?;
+
+    if image != second {
+        return Err(String::from("Decoded image does not match."));
+    }
+
+    if image.pixels.as_ptr() != buffer {
+        return Err(String::from("Expected the pooled buffer to be reused."));
+    }
+
+    //Point the pixel data into the info header
+    let mut malformed = bytes.clone();
+    malformed[10..14].copy_from_slice(&[0; 4]);
+
+    if Bitmap::decode_into(&malformed, &mut image, DecodeOptions::default()).is_ok() || image.length() != 0 {
+        return Err(String::from("Expected a failed decode to leave the image empty."));
+    }
+
+    Ok(())
+}
\ No newline at end of file
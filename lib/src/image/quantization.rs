@@ -0,0 +1,190 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+
+use crate::{color, convert::ConvertableFrom, error::ImageError};
+use color::conversion::LABSettings;
+use super::Image;
+
+///
+/// Color difference, in ΔE, shown as the hottest color of a heatmap.
+/// Around 2.3 is just noticeable, and past 20 colors are plainly different.
+///
+const HEATMAP_MAX_DELTA_E: f32 = 20_f32;
+
+///
+/// The error within one square region of a quantized image
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegionError {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub mean_delta_e: f32,
+    pub max_delta_e: f32
+}
+
+///
+/// How closely a palettized image matches the original, measured as the CIE76
+/// color difference (ΔE, the distance in CIELAB under D65) of each pixel. Alpha
+/// is not compared. Reports from the same original, e.g. after median-cut,
+/// octree or k-means, or at different bit depths, can be compared directly.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizationReport {
+    pub mean_delta_e: f32,
+    pub max_delta_e: f32,
+    ///
+    /// The position of the pixel with the largest error
+    ///
+    pub worst_pixel: (usize, usize),
+    ///
+    /// Each color of the quantized image and how many pixels have it, most common first
+    ///
+    pub population: Vec<(color::ARGB, usize)>,
+    ///
+    /// The error of each square region, row by row
+    ///
+    pub regions: Vec<RegionError>,
+    ///
+    /// Number of regions in each row
+    ///
+    pub region_columns: usize
+}
+
+impl QuantizationReport {
+    ///
+    /// Number of distinct colors in the quantized image
+    ///
+    pub fn colors(&self) -> usize {
+        self.population.len()
+    }
+
+    ///
+    /// Up to the given number of regions, with the largest mean error first
+    ///
+    pub fn worst_regions(&self, count: usize) -> Vec<RegionError> {
+        let mut regions = self.regions.clone();
+        regions.sort_by(|a, b| b.mean_delta_e.total_cmp(&a.mean_delta_e));
+        regions.truncate(count);
+        regions
+    }
+
+    ///
+    /// An image with one pixel per region, from black for no error through red
+    /// and yellow to white for errors of 20 ΔE or more
+    ///
+    pub fn heatmap(&self) -> Image {
+        let columns = self.region_columns.max(1);
+        let rows = self.regions.len().div_ceil(columns);
+
+        let pixels = self.regions.iter()
+            .map(|region| heat(region.mean_delta_e / HEATMAP_MAX_DELTA_E))
+            .collect();
+
+        Image::new_pixels(self.region_columns, rows, pixels)
+    }
+}
+
+impl Image {
+    ///
+    /// Measure how closely the quantized image matches this one, which must be
+    /// the same size, with the error also broken down into square regions of
+    /// the given size
+    ///
+    pub fn quantization_report(&self, quantized: &Image, region_size: usize) -> Result<QuantizationReport, ImageError> {
+        if self.width != quantized.width || self.height != quantized.height {
+            return Err(ImageError::InvalidArgument(format!("Cannot compare a {}x{} image to a {}x{} image.",
+                self.width, self.height, quantized.width, quantized.height)));
+        }
+
+        if region_size == 0 {
+            return Err(ImageError::InvalidArgument(String::from("Region size must be at least 1.")));
+        }
+
+        //Both images tend to repeat colors, so convert each only once
+        let mut lab: HashMap<color::ARGB, color::ALAB> = HashMap::new();
+
+        let mut to_lab = |pixel: color::ARGB| -> Result<color::ALAB, ImageError> {
+            if let Some(converted) = lab.get(&pixel) {
+                return Ok(*converted);
+            }
+
+            let converted = color::ALAB::try_convert_from(pixel.with_alpha(0xFF), LABSettings::D65)?;
+            lab.insert(pixel, converted);
+            Ok(converted)
+        };
+
+        let mut delta_e = Vec::with_capacity(self.length());
+        let mut population: HashMap<color::ARGB, usize> = HashMap::new();
+
+        for (original, reduced) in self.pixels.iter().zip(quantized.pixels.iter()) {
+            delta_e.push(to_lab(*original)?.distance_euclidean(&to_lab(*reduced)?));
+            *population.entry(*reduced).or_default() += 1;
+        }
+
+        let (worst_index, max_delta_e) = delta_e.iter()
+            .copied()
+            .enumerate()
+            .fold((0, 0_f32), |worst, (index, error)| if error > worst.1 { (index, error) } else { worst });
+
+        let mean_delta_e = if delta_e.is_empty() {
+            0_f32
+        }
+        else {
+            (delta_e.iter().map(|error| *error as f64).sum::<f64>() / delta_e.len() as f64) as f32
+        };
+
+        let mut population: Vec<(color::ARGB, usize)> = population.into_iter().collect();
+        population.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.as_u32(true).cmp(&b.0.as_u32(true))));
+
+        let region_columns = self.width.div_ceil(region_size);
+
+        let regions = (0..self.height.div_ceil(region_size))
+            .flat_map(|row| (0..region_columns).map(move |column| (column * region_size, row * region_size)))
+            .map(|(x, y)| {
+                let width = region_size.min(self.width - x);
+                let height = region_size.min(self.height - y);
+
+                let errors = (y..(y + height))
+                    .flat_map(|j| delta_e[(j * self.width + x)..(j * self.width + x + width)].iter().copied());
+
+                let (sum, max) = errors.fold((0_f64, 0_f32), |(sum, max), error| (sum + error as f64, max.max(error)));
+
+                RegionError {
+                    x,
+                    y,
+                    width,
+                    height,
+                    mean_delta_e: (sum / (width * height) as f64) as f32,
+                    max_delta_e: max
+                }
+            })
+            .collect();
+
+        Ok(QuantizationReport {
+            mean_delta_e,
+            max_delta_e,
+            worst_pixel: (worst_index % self.width.max(1), worst_index / self.width.max(1)),
+            population,
+            regions,
+            region_columns
+        })
+    }
+}
+
+///
+/// A color from black, through red and yellow, to white as t goes from 0 to 1
+///
+fn heat(t: f32) -> color::ARGB {
+    let channel = |offset: f32| ((t * 3_f32 - offset).clamp(0_f32, 1_f32) * 255_f32).round() as u8;
+
+    color::ARGB {
+        alpha: 0xFF,
+        red: channel(0_f32),
+        green: channel(1_f32),
+        blue: channel(2_f32)
+    }
+}
\ No newline at end of file
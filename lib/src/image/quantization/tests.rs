@@ -0,0 +1,61 @@
+use super::*;
+
+const BLACK: color::ARGB = color::ARGB { alpha: 0xFF, red: 0, green: 0, blue: 0 };
+const WHITE: color::ARGB = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF };
+
+fn gradient() -> Image {
+    let pixels = (0..32_u8)
+        .map(|n| color::ARGB { alpha: 0xFF, red: n * 8, green: n * 8, blue: n * 8 })
+        .collect();
+
+    Image::new_pixels(8, 4, pixels)
+}
+
+#[test]
+fn quantization_identical() -> Result<(), String> {
+    let report = gradient().quantization_report(&gradient(), 4)?;
+
+    if report.mean_delta_e != 0_f32 || report.max_delta_e != 0_f32 {
+        return Err(format!("Identical images should have no error, got {} and {}.", report.mean_delta_e, report.max_delta_e));
+    }
+
+    if report.colors() != 32 || report.regions.len() != 2 || report.region_columns != 2 {
+        return Err(format!("Expected 32 colors in 2 regions, got {} in {}.", report.colors(), report.regions.len()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn quantization_to_two_colors() -> Result<(), String> {
+    let image = gradient();
+    let quantized = image.map_pixels(|pixel| if pixel.red < 128 { BLACK } else { WHITE });
+    let report = image.quantization_report(&quantized, 3)?;
+
+    if report.population != [(BLACK, 16), (WHITE, 16)] {
+        return Err(format!("Unexpected population {:?}.", report.population));
+    }
+
+    //The worst pixels are those just below the middle gray, rounded down to black
+    if report.worst_pixel != (7, 1) {
+        return Err(format!("Expected the worst pixel at 7,1, got {:?}.", report.worst_pixel));
+    }
+
+    let worst = report.worst_regions(1);
+
+    if worst.first().map(|region| (region.x, region.y)) != Some((6, 0)) {
+        return Err(format!("Expected the worst region at 6,0, got {worst:?}."));
+    }
+
+    let heatmap = report.heatmap();
+
+    if (heatmap.width(), heatmap.height()) != (3, 2) {
+        return Err(format!("Expected a 3x2 heatmap, got {}x{}.", heatmap.width(), heatmap.height()));
+    }
+
+    if !matches!(image.quantization_report(&Image::new(2, 2), 4), Err(ImageError::InvalidArgument(_))) {
+        return Err(String::from("Images of different sizes should not be compared."));
+    }
+
+    Ok(())
+}
\ No newline at end of file
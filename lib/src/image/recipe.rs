@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error::ImageError;
+use super::Image;
+use super::builder::ImageBuilder;
+use super::filter::Filter;
+use super::pipeline::{Pipeline, PointOp};
+use super::transform::{Flip, ResizeFilter, Rotation};
+
+///
+/// One step of a recipe, with its parameters
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case", deny_unknown_fields))]
+pub enum Edit {
+    ///
+    /// Keep only the given rectangle, which must lie within the image
+    ///
+    Crop {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize
+    },
+    Resize {
+        width: usize,
+        height: usize,
+        #[cfg_attr(feature = "serde", serde(default))]
+        filter: ResizeFilter
+    },
+    Rotate(Rotation),
+    Flip(Flip),
+    Filter(Filter),
+    ///
+    /// A point op. Consecutive point ops are fused, and applied in one pass.
+    ///
+    Point(PointOp)
+}
+
+///
+/// A list of edits, applied in order. With the serde feature, a recipe can
+/// be saved, and applied again later to any number of images, with the
+/// same result each time.
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(deny_unknown_fields))]
+pub struct Recipe {
+    pub steps: Vec<Edit>
+}
+
+impl Recipe {
+    pub fn new(steps: Vec<Edit>) -> Self {
+        Self {
+            steps
+        }
+    }
+
+    ///
+    /// Apply each edit to the image, in order. Recipes may come from untrusted
+    /// files, so sizes and radii past the limits in constants::image are errors.
+    ///
+    pub fn apply(&self, image: Image) -> Result<Image, ImageError> {
+        ImageBuilder::new(image).recipe(self).build()
+    }
+}
+
+impl From<Vec<Edit>> for Recipe {
+    fn from(steps: Vec<Edit>) -> Self {
+        Self::new(steps)
+    }
+}
+
+impl ImageBuilder {
+    ///
+    /// Apply each edit of a recipe, in order
+    ///
+    pub fn recipe(self, recipe: &Recipe) -> Self {
+        let mut builder = self;
+        let mut points = Pipeline::new();
+
+        for edit in &recipe.steps {
+            if let Edit::Point(op) = edit {
+                points = points.then(*op);
+                continue;
+            }
+
+            if !points.is_empty() {
+                builder = builder.pipeline(&points);
+                points = Pipeline::new();
+            }
+
+            builder = match *edit {
+                Edit::Crop { x, y, width, height } => builder.crop(x, y, width, height),
+                Edit::Resize { width, height, filter } => builder.resize_with(width, height, filter),
+                Edit::Rotate(rotation) => builder.rotate(rotation),
+                Edit::Flip(flip) => builder.flip(flip),
+                Edit::Filter(filter) => builder.try_map(|image| filter.validate().map(|_| image.filter(filter))),
+                Edit::Point(_) => builder
+            };
+        }
+
+        if points.is_empty() {
+            builder
+        }
+        else {
+            builder.pipeline(&points)
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,102 @@
+use super::*;
+use crate::color;
+
+fn gradient() -> Image {
+    let pixels = (0..16_u8)
+        .map(|n| color::ARGB { alpha: 0xFF, red: n * 16, green: n * 8, blue: 255 - n * 16 })
+        .collect();
+
+    Image::new_pixels(4, 4, pixels)
+}
+
+fn grade() -> Recipe {
+    Recipe::new(vec![
+        Edit::Crop { x: 1, y: 0, width: 3, height: 4 },
+        Edit::Resize { width: 6, height: 8, filter: ResizeFilter::Bilinear },
+        Edit::Point(PointOp::Brightness(12_f32)),
+        Edit::Point(PointOp::Gamma(1.8_f32)),
+        Edit::Rotate(Rotation::Rotate270),
+        Edit::Filter(Filter::Blur { radius: 1 }),
+        Edit::Point(PointOp::Invert)
+    ])
+}
+
+#[test]
+fn recipe_matches_individual_ops() -> Result<(), String> {
+    let applied = grade().apply(gradient())?;
+
+    let expected = gradient()
+        .crop(1, 0, 3, 4)
+        .resize(6, 8, ResizeFilter::Bilinear);
+
+    let expected = Pipeline::new().brightness(12_f32).gamma(1.8_f32).execute(&expected)?
+        .rotate(Rotation::Rotate270)
+        .blur(1)
+        .invert();
+
+    if applied != expected {
+        return Err(String::from("Recipe result differs from applying each op in turn."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn recipe_reports_invalid_edit() -> Result<(), String> {
+    let recipe = Recipe::new(vec![Edit::Resize { width: 0, height: 4, filter: ResizeFilter::Box }]);
+
+    match recipe.apply(gradient()) {
+        Err(ImageError::InvalidArgument(_)) => Ok(()),
+        result => Err(format!("Expected an invalid argument error, got {result:?}."))
+    }
+}
+
+#[test]
+fn recipe_rejects_oversized_edits() -> Result<(), String> {
+    let edits = [
+        Edit::Resize { width: 100_000, height: 100_000, filter: ResizeFilter::Box },
+        Edit::Resize { width: usize::MAX, height: 2, filter: ResizeFilter::Box },
+        Edit::Filter(Filter::Blur { radius: usize::MAX }),
+        Edit::Filter(Filter::AnisotropicKuwahara { radius: 100_000 }),
+        Edit::Filter(Filter::Brightness { amount: f32::NAN })
+    ];
+
+    for edit in edits {
+        match Recipe::new(vec![edit]).apply(gradient()) {
+            Err(ImageError::InvalidArgument(_)) => (),
+            result => return Err(format!("Expected {edit:?} to be an invalid argument, got {result:?}."))
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn recipe_json_round_trip() -> Result<(), String> {
+    let json = serde_json::to_string(&grade()).map_err(|err| err.to_string())?;
+    let loaded: Recipe = serde_json::from_str(&json).map_err(|err| err.to_string())?;
+
+    if loaded != grade() {
+        return Err(format!("Recipe changed after a round trip through {json}."));
+    }
+
+    let written = r#"{"steps": [{"resize": {"width": 2, "height": 2}}, {"point": "invert"}, {"filter": "grayscale"}]}"#;
+    let loaded: Recipe = serde_json::from_str(written).map_err(|err| err.to_string())?;
+
+    let expected = Recipe::new(vec![
+        Edit::Resize { width: 2, height: 2, filter: ResizeFilter::default() },
+        Edit::Point(PointOp::Invert),
+        Edit::Filter(Filter::Grayscale)
+    ]);
+
+    if loaded != expected {
+        return Err(format!("Expected {expected:?}, got {loaded:?}."));
+    }
+
+    if serde_json::from_str::<Recipe>(r#"{"steps": [{"sharpen": 2}]}"#).is_ok() {
+        return Err(String::from("An unknown edit should not load."));
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,164 @@
+#[cfg(feature = "std")]
+use alloc::format;
+
+#[cfg(feature = "std")]
+use super::{Image, transform::ResizeFilter};
+#[cfg(feature = "std")]
+use crate::error::ImageError;
+
+#[cfg(all(test, feature = "std"))]
+mod tests;
+
+pub const MILLIMETERS_PER_INCH: f32 = 25.4;
+pub const METERS_PER_INCH: f32 = 0.0254;
+
+///
+/// Convert a resolution in dots (pixels) per inch to pixels per meter
+///
+pub fn dpi_to_pixels_per_meter(dpi: f32) -> f32 {
+    dpi / METERS_PER_INCH
+}
+
+///
+/// Convert a resolution in pixels per meter to dots (pixels) per inch
+///
+pub fn pixels_per_meter_to_dpi(pixels_per_meter: f32) -> f32 {
+    pixels_per_meter * METERS_PER_INCH
+}
+
+///
+/// The number of pixels spanning a length in millimeters at the given dpi
+///
+pub fn millimeters_to_pixels(millimeters: f32, dpi: f32) -> f32 {
+    millimeters / MILLIMETERS_PER_INCH * dpi
+}
+
+///
+/// The length in millimeters spanned by a number of pixels at the given dpi
+///
+pub fn pixels_to_millimeters(pixels: f32, dpi: f32) -> f32 {
+    pixels / dpi * MILLIMETERS_PER_INCH
+}
+
+///
+/// The physical density of an image's pixels, horizontally and vertically.
+/// It is stored in whole pixels per meter, as image formats store it; 0 means unknown.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Resolution {
+    x_pixels_per_meter: u32,
+    y_pixels_per_meter: u32
+}
+
+impl Resolution {
+    ///
+    /// A resolution in pixels per meter
+    ///
+    pub fn new(x_pixels_per_meter: u32, y_pixels_per_meter: u32) -> Self {
+        Self {
+            x_pixels_per_meter,
+            y_pixels_per_meter
+        }
+    }
+
+    ///
+    /// A resolution in dots (pixels) per inch, rounded to whole pixels per meter
+    ///
+    #[cfg(feature = "std")]
+    pub fn from_dpi(x_dpi: f32, y_dpi: f32) -> Self {
+        let to_pixels_per_meter = |dpi: f32| dpi_to_pixels_per_meter(dpi).round().max(0_f32) as u32;
+        Self::new(to_pixels_per_meter(x_dpi), to_pixels_per_meter(y_dpi))
+    }
+
+    ///
+    /// A resolution in dots (pixels) per centimeter, rounded to whole pixels per meter
+    ///
+    #[cfg(feature = "std")]
+    pub fn from_dpcm(x_dpcm: f32, y_dpcm: f32) -> Self {
+        let to_pixels_per_meter = |dpcm: f32| (dpcm * 100_f32).round().max(0_f32) as u32;
+        Self::new(to_pixels_per_meter(x_dpcm), to_pixels_per_meter(y_dpcm))
+    }
+
+    ///
+    /// A resolution as stored by a format which uses signed pixels per meter, such as bmp.
+    /// Negative values are meaningless, so they are treated as unknown.
+    ///
+    pub fn from_signed(x_pixels_per_meter: i32, y_pixels_per_meter: i32) -> Self {
+        Self::new(x_pixels_per_meter.max(0) as u32, y_pixels_per_meter.max(0) as u32)
+    }
+
+    pub fn x_pixels_per_meter(&self) -> u32 {
+        self.x_pixels_per_meter
+    }
+
+    pub fn y_pixels_per_meter(&self) -> u32 {
+        self.y_pixels_per_meter
+    }
+
+    ///
+    /// Horizontal and vertical dots (pixels) per inch
+    ///
+    pub fn dpi(&self) -> (f32, f32) {
+        (pixels_per_meter_to_dpi(self.x_pixels_per_meter as f32), pixels_per_meter_to_dpi(self.y_pixels_per_meter as f32))
+    }
+
+    ///
+    /// Horizontal and vertical dots (pixels) per centimeter
+    ///
+    pub fn dpcm(&self) -> (f32, f32) {
+        (self.x_pixels_per_meter as f32 / 100_f32, self.y_pixels_per_meter as f32 / 100_f32)
+    }
+
+    ///
+    /// Whether the resolution is known in both directions
+    ///
+    pub fn is_known(&self) -> bool {
+        self.x_pixels_per_meter > 0 && self.y_pixels_per_meter > 0
+    }
+
+    ///
+    /// The width and height, in millimeters, of an image of the given size
+    /// printed at this resolution, if the resolution is known
+    ///
+    pub fn physical_size(&self, width: usize, height: usize) -> Option<(f32, f32)> {
+        if !self.is_known() {
+            return None;
+        }
+
+        let (x_dpi, y_dpi) = self.dpi();
+        Some((pixels_to_millimeters(width as f32, x_dpi), pixels_to_millimeters(height as f32, y_dpi)))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Image {
+    ///
+    /// Scale the image so that it is width_mm millimeters wide when printed at
+    /// the given dpi, keeping its aspect ratio. The result should be stored with
+    /// Resolution::from_dpi(dpi, dpi) for the size to be kept.
+    ///
+    pub fn resize_to_physical(&self, width_mm: f32, dpi: f32) -> Result<Image, ImageError> {
+        if !(width_mm.is_finite() && width_mm > 0_f32 && dpi.is_finite() && dpi > 0_f32) {
+            return Err(ImageError::InvalidArgument(format!("Physical width {width_mm}mm and resolution {dpi} DPI must both be positive.")));
+        }
+
+        let target_width = millimeters_to_pixels(width_mm, dpi).round().max(1_f32) as usize;
+
+        let target_height = if self.width == 0 {
+            self.height
+        }
+        else {
+            ((self.height * target_width) as f32 / self.width as f32).round().max(1_f32) as usize
+        };
+
+        //Box averages away detail when shrinking, but blocks up when enlarging
+        let filter = if target_width < self.width {
+            ResizeFilter::Box
+        }
+        else {
+            ResizeFilter::Bilinear
+        };
+
+        Ok(self.resize(target_width, target_height, filter))
+    }
+}
\ No newline at end of file
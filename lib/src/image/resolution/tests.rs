@@ -0,0 +1,70 @@
+use super::*;
+
+#[test]
+fn units_convert() -> Result<(), String> {
+    let resolution = Resolution::from_dpi(300_f32, 72_f32);
+
+    if resolution != Resolution::new(11811, 2835) {
+        return Err(format!("Expected 300 x 72 DPI to be 11811 x 2835 pixels/meter, got {resolution:?}."));
+    }
+
+    let (x_dpi, y_dpi) = resolution.dpi();
+
+    if (x_dpi - 300_f32).abs() > 0.01 || (y_dpi - 72_f32).abs() > 0.01 {
+        return Err(format!("Expected 300 x 72 DPI back, got {x_dpi} x {y_dpi}."));
+    }
+
+    if Resolution::from_dpcm(118.11, 28.35) != resolution || resolution.dpcm() != (118.11, 28.35) {
+        return Err(format!("Expected {resolution:?} to be 118.11 x 28.35 DPCM, got {:?}.", resolution.dpcm()));
+    }
+
+    if Resolution::from_signed(-1, 3780) != Resolution::new(0, 3780) {
+        return Err(String::from("Expected a negative resolution to be unknown."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn physical_size() -> Result<(), String> {
+    if Resolution::new(0, 3780).physical_size(10, 10).is_some() {
+        return Err(String::from("Expected an unknown resolution to have no physical size."));
+    }
+
+    let (width, height) = Resolution::from_dpi(254_f32, 127_f32).physical_size(1000, 500)
+        .ok_or_else(|| String::from("Expected a known resolution to have a physical size."))?;
+
+    if (width - 100_f32).abs() > 0.1 || (height - 100_f32).abs() > 0.1 {
+        Err(format!("Expected 1000 x 500 pixels at 254 x 127 DPI to be 100 x 100 mm, got {width} x {height}."))
+    }
+    else {
+        Ok(())
+    }
+}
+
+#[test]
+fn resize_to_physical() -> Result<(), String> {
+    let image = Image::new(200, 100);
+
+    //50.8mm is 2 inches, or 600 pixels at 300 DPI
+    let resized = image.resize_to_physical(50.8, 300_f32).map_err(|e| e.to_string())?;
+
+    if (resized.width(), resized.height()) != (600, 300) {
+        return Err(format!("Expected 600x300, got {}x{}.", resized.width(), resized.height()));
+    }
+
+    let printed = Resolution::from_dpi(300_f32, 300_f32).physical_size(resized.width(), resized.height())
+        .ok_or_else(|| String::from("Expected a physical size."))?;
+
+    if (printed.0 - 50.8).abs() > 0.1 {
+        return Err(format!("Expected the resized image to print 50.8mm wide, got {}.", printed.0));
+    }
+
+    for (width_mm, dpi) in [(0_f32, 300_f32), (10_f32, -1_f32), (f32::NAN, 300_f32)] {
+        if image.resize_to_physical(width_mm, dpi).is_ok() {
+            return Err(format!("Expected {width_mm}mm at {dpi} DPI to be rejected."));
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
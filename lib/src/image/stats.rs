@@ -0,0 +1,291 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+
+use crate::color;
+use super::Image;
+use super::histogram::Histogram;
+
+///
+/// Summary of the values of one channel over every pixel of an image
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ChannelStats {
+    pub min: u8,
+    pub max: u8,
+    pub mean: f32,
+    ///
+    /// Population standard deviation
+    ///
+    pub stddev: f32,
+    ///
+    /// The lower median, so that it is always a value of the channel
+    ///
+    pub median: u8
+}
+
+impl ChannelStats {
+    ///
+    /// Summarize a channel from the number of pixels with each of its values
+    ///
+    pub fn from_counts(counts: &[u32; 256]) -> Self {
+        let total: u64 = counts.iter().map(|count| *count as u64).sum();
+
+        if total == 0 {
+            return Self::default();
+        }
+
+        let values = || counts.iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(value, count)| (value as u8, *count as u64));
+
+        let min = values().next().map_or(0, |(value, _)| value);
+        let max = values().next_back().map_or(0, |(value, _)| value);
+
+        let mean = values().map(|(value, count)| value as f64 * count as f64).sum::<f64>() / total as f64;
+
+        let variance = values()
+            .map(|(value, count)| (value as f64 - mean).powi(2) * count as f64)
+            .sum::<f64>() / total as f64;
+
+        //The first value with at least half of the pixels at or below it
+        let mut below = 0_u64;
+
+        let median = values()
+            .find(|(_, count)| {
+                below += count;
+                below * 2 >= total
+            })
+            .map_or(max, |(value, _)| value);
+
+        Self {
+            min,
+            max,
+            mean: mean as f32,
+            stddev: variance.sqrt() as f32,
+            median
+        }
+    }
+}
+
+///
+/// Summary of the values of each channel of an image
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ImageStats {
+    pub alpha: ChannelStats,
+    pub red: ChannelStats,
+    pub green: ChannelStats,
+    pub blue: ChannelStats,
+    ///
+    /// Relative luminance of each pixel, ignoring alpha
+    ///
+    pub luminance: ChannelStats,
+    ///
+    /// Shannon entropy of the luminance, in bits; from 0 for a single
+    /// value up to 8 when every value is equally common
+    ///
+    pub entropy: f32
+}
+
+impl From<&Histogram> for ImageStats {
+    fn from(value: &Histogram) -> Self {
+        let total: u64 = value.luminance.iter().map(|count| *count as u64).sum();
+
+        let entropy = value.luminance.iter()
+            .filter(|count| **count > 0)
+            .map(|count| {
+                let p = *count as f64 / total as f64;
+                -p * p.log2()
+            })
+            .sum::<f64>();
+
+        Self {
+            alpha: ChannelStats::from_counts(&value.alpha),
+            red: ChannelStats::from_counts(&value.red),
+            green: ChannelStats::from_counts(&value.green),
+            blue: ChannelStats::from_counts(&value.blue),
+            luminance: ChannelStats::from_counts(&value.luminance),
+            entropy: entropy as f32
+        }
+    }
+}
+
+///
+/// Mean and spread of the color of a rectangle of pixels, weighted by alpha
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RegionStats {
+    ///
+    /// Total alpha of the pixels, where each fully opaque pixel counts as 1
+    ///
+    pub weight: f32,
+    ///
+    /// Mean red, green and blue
+    ///
+    pub mean: [f32; 3],
+    ///
+    /// Sum of the variances of red, green and blue
+    ///
+    pub variance: f32
+}
+
+///
+/// Running sums of the colors of an image, from which the stats of any
+/// rectangle of it are found in constant time, however large it is
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct SummedAreaTable {
+    width: usize,
+    height: usize,
+    ///
+    /// Total alpha, alpha-weighted red, green and blue, and alpha-weighted sum of their
+    /// squares, of the pixels above and left of each point, with a row and column of 0s first
+    ///
+    sums: Vec<[f64; 5]>
+}
+
+impl SummedAreaTable {
+    pub fn new(image: &Image) -> Self {
+        let (width, height) = (image.width, image.height);
+        let stride = width + 1;
+        let mut sums = vec![[0_f64; 5]; stride * (height + 1)];
+
+        for y in 0..height {
+            let mut row = [0_f64; 5];
+
+            for (x, pixel) in image.row(y).iter().enumerate() {
+                let weight = pixel.alpha as f64 / 255_f64;
+                let [red, green, blue] = [pixel.red, pixel.green, pixel.blue].map(|channel| channel as f64);
+
+                let values = [weight, red * weight, green * weight, blue * weight, (red * red + green * green + blue * blue) * weight];
+
+                for (index, value) in values.into_iter().enumerate() {
+                    row[index] += value;
+                    sums[(y + 1) * stride + x + 1][index] = sums[y * stride + x + 1][index] + row[index];
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            sums
+        }
+    }
+
+    ///
+    /// Stats of the width x height rectangle with its top-left corner at (x, y),
+    /// clipped to the image. Default if none of it is within the image.
+    ///
+    pub fn region(&self, x: usize, y: usize, width: usize, height: usize) -> RegionStats {
+        let (left, top) = (x.min(self.width), y.min(self.height));
+        let (right, bottom) = (x.saturating_add(width).min(self.width), y.saturating_add(height).min(self.height));
+
+        let stride = self.width + 1;
+        let corner = |x: usize, y: usize| self.sums[y * stride + x];
+
+        let totals: [f64; 5] = core::array::from_fn(|index| {
+            corner(right, bottom)[index] - corner(left, bottom)[index] - corner(right, top)[index] + corner(left, top)[index]
+        });
+
+        let weight = totals[0];
+
+        //Rounding in the sums can leave a tiny weight for a transparent region
+        if weight < 0.5_f64 / 255_f64 {
+            return RegionStats::default();
+        }
+
+        let mean = [totals[1], totals[2], totals[3]].map(|total| total / weight);
+        let variance = totals[4] / weight - mean.iter().map(|mean| mean * mean).sum::<f64>();
+
+        RegionStats {
+            weight: weight as f32,
+            mean: mean.map(|mean| mean as f32),
+            variance: variance.max(0_f64) as f32
+        }
+    }
+}
+
+impl Image {
+    ///
+    /// Summarize the values of each channel over every pixel
+    ///
+    pub fn stats(&self) -> ImageStats {
+        ImageStats::from(&self.histogram())
+    }
+
+    ///
+    /// Running sums of the colors of the image, for the stats of many regions of it
+    ///
+    pub fn summed_area_table(&self) -> SummedAreaTable {
+        SummedAreaTable::new(self)
+    }
+
+    ///
+    /// The average color, with each pixel's color weighted by its alpha so that
+    /// transparent pixels don't tint it, or None for an empty image. Averaging in
+    /// linear light rather than sRGB gives the color the image blurs to when seen from afar.
+    ///
+    pub fn average_color(&self, linear_light: bool) -> Option<color::ARGB> {
+        if self.pixels.is_empty() {
+            return None;
+        }
+
+        //Each channel value's linear light, converted together rather than once per pixel
+        let mut linear = [0_f32; 256];
+        color::bulk::srgb_to_linear_all(&core::array::from_fn::<u8, 256, _>(|channel| channel as u8), &mut linear);
+
+        let to_linear = |channel: u8| if linear_light { linear[channel as usize] as f64 } else { channel as f64 };
+
+        let (mut red, mut green, mut blue, mut alpha) = (0_f64, 0_f64, 0_f64, 0_f64);
+
+        for pixel in self.pixels.iter() {
+            let weight = pixel.alpha as f64;
+
+            red += to_linear(pixel.red) * weight;
+            green += to_linear(pixel.green) * weight;
+            blue += to_linear(pixel.blue) * weight;
+            alpha += weight;
+        }
+
+        if alpha == 0_f64 {
+            return Some(color::ARGB::default());
+        }
+
+        let [red, green, blue] = if linear_light {
+            let mut channels = [0_u8; 3];
+            color::bulk::linear_to_srgb_all(&[red, green, blue].map(|channel| (channel / alpha) as f32), &mut channels);
+            channels
+        }
+        else {
+            [red, green, blue].map(|channel| (channel / alpha).round().clamp(0_f64, 255_f64) as u8)
+        };
+
+        Some(color::ARGB {
+            alpha: (alpha / self.pixels.len() as f64).round() as u8,
+            red,
+            green,
+            blue
+        })
+    }
+
+    ///
+    /// The most common color, ignoring fully transparent pixels, which have no color
+    /// to see. Ties go to the color found first. None if every pixel is transparent.
+    ///
+    pub fn mode_color(&self) -> Option<color::ARGB> {
+        //Number of pixels with each color, and where it was first found
+        let mut counts: HashMap<color::ARGB, (usize, usize)> = HashMap::new();
+
+        for (index, pixel) in self.pixels.iter().enumerate().filter(|(_, pixel)| pixel.alpha > 0) {
+            counts.entry(*pixel).or_insert((0, index)).0 += 1;
+        }
+
+        counts.into_iter()
+            .max_by_key(|(_, (count, first))| (*count, std::cmp::Reverse(*first)))
+            .map(|(color, _)| color)
+    }
+}
\ No newline at end of file
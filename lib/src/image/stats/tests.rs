@@ -0,0 +1,125 @@
+use super::*;
+use crate::color;
+
+fn gray(value: u8) -> color::ARGB {
+    color::ARGB { alpha: 0xFF, red: value, green: value, blue: value }
+}
+
+#[test]
+fn channel_stats() -> Result<(), String> {
+    let image = Image::new_pixels(4, 1, vec![gray(10), gray(20), gray(20), gray(70)]);
+    let stats = image.stats();
+
+    let expected = ChannelStats { min: 10, max: 70, mean: 30_f32, stddev: 550_f32.sqrt(), median: 20 };
+
+    for (name, channel) in [("red", stats.red), ("green", stats.green), ("blue", stats.blue), ("luminance", stats.luminance)] {
+        if channel.min != expected.min || channel.max != expected.max || channel.median != expected.median
+            || (channel.mean - expected.mean).abs() > 0.001 || (channel.stddev - expected.stddev).abs() > 0.001 {
+            return Err(format!("Expected {name} stats {expected:?}, got {channel:?}."));
+        }
+    }
+
+    if stats.alpha != (ChannelStats { min: 0xFF, max: 0xFF, mean: 255_f32, stddev: 0_f32, median: 0xFF }) {
+        return Err(format!("Expected constant alpha stats, got {:?}.", stats.alpha));
+    }
+
+    //Values with probabilities 1/4, 1/2 and 1/4
+    if (stats.entropy - 1.5).abs() > 0.001 {
+        return Err(format!("Expected an entropy of 1.5 bits, got {}.", stats.entropy));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn degenerate_stats() -> Result<(), String> {
+    if Image::new(0, 5).stats() != ImageStats::default() {
+        return Err(String::from("Expected an empty image to have default stats."));
+    }
+
+    let single = Image::new_pixels(3, 3, vec![gray(42); 9]).stats();
+
+    if single.entropy != 0_f32 || single.red.stddev != 0_f32 || single.red.median != 42 {
+        Err(format!("Expected a single color to have no spread or entropy, got {single:?}."))
+    }
+    else {
+        Ok(())
+    }
+}
+
+#[test]
+fn average_color() -> Result<(), String> {
+    let image = Image::new_pixels(3, 1, vec![
+        color::ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x00 },
+        color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0xFF },
+        color::ARGB { alpha: 0x00, red: 0x00, green: 0xFF, blue: 0x00 }
+    ]);
+
+    let average = image.average_color(false);
+    let expected = color::ARGB { alpha: 0xAA, red: 0x80, green: 0x00, blue: 0x80 };
+
+    if average != Some(expected) {
+        return Err(format!("Expected the transparent pixel not to tint {expected:?}, got {average:?}."));
+    }
+
+    //Half of full intensity is brighter than 0x80 when encoded in sRGB
+    let linear = image.average_color(true);
+    let expected = color::ARGB { alpha: 0xAA, red: 0xBC, green: 0x00, blue: 0xBC };
+
+    if linear != Some(expected) {
+        return Err(format!("Expected {expected:?} averaging in linear light, got {linear:?}."));
+    }
+
+    if Image::new(0, 0).average_color(false).is_some() || Image::new(2, 2).average_color(true) != Some(color::ARGB::default()) {
+        return Err(String::from("Expected no average for an empty image, and transparent black for a transparent one."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn mode_color() -> Result<(), String> {
+    let (first, second) = (gray(1), gray(2));
+    let transparent = color::ARGB::default();
+
+    let image = Image::new_pixels(7, 1, vec![transparent, transparent, transparent, second, first, first, second]);
+
+    if image.mode_color() != Some(second) {
+        return Err(format!("Expected the first of the most common visible colors, got {:?}.", image.mode_color()));
+    }
+
+    if Image::new(3, 3).mode_color().is_some() {
+        Err(String::from("Expected no mode for a transparent image."))
+    }
+    else {
+        Ok(())
+    }
+}
+
+#[test]
+fn region_stats() -> Result<(), String> {
+    let image = Image::new_pixels(3, 2, vec![
+        gray(10), gray(20), gray(30),
+        gray(40), gray(50), color::ARGB::default()
+    ]);
+
+    let table = image.summed_area_table();
+
+    let region = table.region(1, 0, 2, 2);
+
+    //The transparent pixel carries no weight
+    if (region.weight - 3_f32).abs() > 0.001 || (region.mean[0] - 100_f32 / 3_f32).abs() > 0.001 {
+        return Err(format!("Unexpected stats {region:?} for the right two columns."));
+    }
+
+    //Population variance of 20, 30, 50 is 1400 / 9, for each of the three channels
+    if (region.variance - 3_f32 * 1400_f32 / 9_f32).abs() > 0.01 {
+        return Err(format!("Expected a variance of {}, got {}.", 1400_f32 / 3_f32, region.variance));
+    }
+
+    if table.region(0, 0, 10, 10).weight != 5_f32 || table.region(2, 1, 1, 1) != RegionStats::default() || table.region(5, 5, 2, 2) != RegionStats::default() {
+        return Err(String::from("Expected regions to be clipped to the image, and transparent ones to be empty."));
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,211 @@
+#[cfg(test)]
+mod tests;
+
+use std::sync::OnceLock;
+
+use crate::color;
+use crate::utility::random::Random;
+use super::Image;
+
+///
+/// Width and height of the tile of blue noise which stippling repeats
+///
+const BLUE_NOISE_SIZE: usize = 64;
+
+///
+/// Spread of the gaussian which void-and-cluster uses to measure how crowded each pixel is
+///
+const BLUE_NOISE_SIGMA: f32 = 1.5;
+
+const INK: color::ARGB = color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0x00 };
+const PAPER: color::ARGB = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF };
+
+impl Image {
+    ///
+    /// Redraw the image as black lines on white, like a pen drawing: the darker
+    /// a pixel, the more layers of lines, spacing pixels apart, cross it. Each
+    /// pixel is fully ink or paper, which suits the braille and ASCII renderers.
+    /// Alpha is left untouched.
+    ///
+    pub fn hatch(&self, spacing: usize) -> Image {
+        let spacing = spacing.max(2);
+
+        Image::from_fn(self.width, self.height, |x, y| {
+            let pixel = self.row(y)[x];
+            let darkness = 1_f32 - pixel.luminance() as f32 / 255_f32;
+
+            //Diagonal, anti-diagonal, horizontal, then vertical lines
+            let layers = [
+                (x + y) % spacing == 0,
+                (x as isize - y as isize).rem_euclid(spacing as isize) == 0,
+                y % spacing == spacing / 2,
+                x % spacing == spacing / 2
+            ];
+
+            let inked = layers.iter()
+                .enumerate()
+                .any(|(layer, on_line)| *on_line && darkness > (layer + 1) as f32 / (layers.len() + 1) as f32);
+
+            color::ARGB {
+                alpha: pixel.alpha,
+                ..if inked { INK } else { PAPER }
+            }
+        })
+    }
+
+    ///
+    /// Redraw the image as black dots on white, with the fraction of dotted pixels
+    /// in each area matching its darkness. Dots are placed by a blue noise threshold,
+    /// which spreads them evenly without the grid of ordered dithering or the clumps
+    /// of white noise. Alpha is left untouched.
+    ///
+    pub fn stipple(&self) -> Image {
+        let thresholds = blue_noise();
+
+        Image::from_fn(self.width, self.height, |x, y| {
+            let pixel = self.row(y)[x];
+            let threshold = thresholds[(y % BLUE_NOISE_SIZE) * BLUE_NOISE_SIZE + x % BLUE_NOISE_SIZE];
+
+            color::ARGB {
+                alpha: pixel.alpha,
+                ..if (pixel.luminance() as f32 / 255_f32) < threshold { INK } else { PAPER }
+            }
+        })
+    }
+}
+
+///
+/// Tile of thresholds in (0, 1), with every value equally common and close
+/// values far apart, generated once by the void-and-cluster method
+///
+pub fn blue_noise() -> &'static [f32] {
+    static THRESHOLDS: OnceLock<Vec<f32>> = OnceLock::new();
+
+    THRESHOLDS.get_or_init(|| {
+        let count = BLUE_NOISE_SIZE * BLUE_NOISE_SIZE;
+
+        void_and_cluster(BLUE_NOISE_SIZE).into_iter()
+            .map(|rank| (rank as f32 + 0.5) / count as f32)
+            .collect()
+    })
+}
+
+///
+/// Pixels of a tile, with how crowded each is by the set pixels around it,
+/// wrapping around the edges so that the tile repeats seamlessly
+///
+struct Pattern<'a> {
+    size: usize,
+    kernel: &'a [f32],
+    set: Vec<bool>,
+    energy: Vec<f32>
+}
+
+impl Pattern<'_> {
+    fn toggle(&mut self, index: usize, set: bool) {
+        self.set[index] = set;
+
+        let (x, y) = (index % self.size, index / self.size);
+        let sign = if set { 1_f32 } else { -1_f32 };
+
+        for (other, energy) in self.energy.iter_mut().enumerate() {
+            let dx = (other % self.size + self.size - x) % self.size;
+            let dy = (other / self.size + self.size - y) % self.size;
+            *energy += sign * self.kernel[dy * self.size + dx];
+        }
+    }
+
+    ///
+    /// The set pixel with the most set pixels around it
+    ///
+    fn tightest_cluster(&self) -> usize {
+        self.extreme(true, |a, b| a > b)
+    }
+
+    ///
+    /// The unset pixel with the fewest set pixels around it
+    ///
+    fn largest_void(&self) -> usize {
+        self.extreme(false, |a, b| a < b)
+    }
+
+    fn extreme(&self, set: bool, better: impl Fn(f32, f32) -> bool) -> usize {
+        (0..self.energy.len())
+            .filter(|index| self.set[*index] == set)
+            .reduce(|best, index| if better(self.energy[index], self.energy[best]) { index } else { best })
+            .unwrap_or_default()
+    }
+}
+
+///
+/// Rank every pixel of a size x size tile, so that the pixels ranked
+/// below any threshold are as evenly spread as possible (Ulichney)
+///
+fn void_and_cluster(size: usize) -> Vec<usize> {
+    let count = size * size;
+
+    let kernel: Vec<f32> = (0..count)
+        .map(|index| {
+            let wrap = |offset: usize| offset.min(size - offset) as f32;
+            let (dx, dy) = (wrap(index % size), wrap(index / size));
+            (-(dx * dx + dy * dy) / (2_f32 * BLUE_NOISE_SIGMA * BLUE_NOISE_SIGMA)).exp()
+        })
+        .collect();
+
+    let mut pattern = Pattern {
+        size,
+        kernel: &kernel,
+        set: vec![false; count],
+        energy: vec![0_f32; count]
+    };
+
+    //Start from a tenth of the pixels set at random
+    let initial = (count / 10).max(1);
+    let mut random = Random::new(count as u64);
+
+    while pattern.set.iter().filter(|set| **set).count() < initial {
+        let index = random.next_below(count);
+
+        if !pattern.set[index] {
+            pattern.toggle(index, true);
+        }
+    }
+
+    //Move the most crowded pixel into the emptiest space, until it is already there
+    for _ in 0..count {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster, false);
+
+        let void = pattern.largest_void();
+        pattern.toggle(void, true);
+
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut ranks = vec![0_usize; count];
+
+    //Rank the initial pixels by removing the most crowded first, which ranks highest
+    let mut removing = Pattern {
+        set: pattern.set.clone(),
+        energy: pattern.energy.clone(),
+        ..pattern
+    };
+
+    for rank in (0..initial).rev() {
+        let cluster = removing.tightest_cluster();
+        removing.toggle(cluster, false);
+        ranks[cluster] = rank;
+    }
+
+    //Then rank the rest by filling the emptiest space first. Past half full this
+    //is also the tightest cluster of the unset pixels, so one rule covers both halves.
+    for rank in initial..count {
+        let void = pattern.largest_void();
+        pattern.toggle(void, true);
+        ranks[void] = rank;
+    }
+
+    ranks
+}
\ No newline at end of file
@@ -0,0 +1,75 @@
+use super::*;
+
+fn gray(width: usize, height: usize, value: u8) -> Image {
+    Image::new_pixels(width, height, vec![color::ARGB { alpha: 0xFF, red: value, green: value, blue: value }; width * height])
+}
+
+fn ink_fraction(image: &Image) -> f32 {
+    image.iter().flatten().filter(|pixel| **pixel == INK).count() as f32 / image.length() as f32
+}
+
+#[test]
+fn hatch_layers_by_darkness() -> Result<(), String> {
+    let fractions: Vec<f32> = [0xFF, 0xC0, 0x80, 0x40, 0x00]
+        .map(|value| ink_fraction(&gray(16, 16, value).hatch(4)))
+        .to_vec();
+
+    if fractions[0] != 0_f32 || fractions.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(format!("Expected darker images to have more lines, got ink fractions {fractions:?}."));
+    }
+
+    let hatched = gray(16, 16, 0x00).hatch(4);
+
+    if hatched.iter().flatten().any(|pixel| *pixel != INK && *pixel != PAPER) {
+        return Err(String::from("Expected every hatched pixel to be ink or paper."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn stipple_matches_darkness() -> Result<(), String> {
+    for value in [0x00, 0x40, 0x80, 0xC0, 0xFF] {
+        let expected = 1_f32 - value as f32 / 255_f32;
+        let actual = ink_fraction(&gray(BLUE_NOISE_SIZE, BLUE_NOISE_SIZE, value).stipple());
+
+        if (actual - expected).abs() > 0.01 {
+            return Err(format!("Expected {expected} of a {value:#X} image to be dotted, got {actual}."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blue_noise_is_even() -> Result<(), String> {
+    let thresholds = blue_noise();
+    let count = BLUE_NOISE_SIZE * BLUE_NOISE_SIZE;
+
+    //Every rank is used once
+    let mut ranks: Vec<usize> = thresholds.iter().map(|threshold| (threshold * count as f32) as usize).collect();
+    ranks.sort();
+
+    if ranks != (0..count).collect::<Vec<_>>() {
+        return Err(String::from("Expected every threshold to be distinct and evenly spaced."));
+    }
+
+    //No two of the lowest 10% of thresholds touch, which white noise would often do
+    let sparse: Vec<(usize, usize)> = (0..count)
+        .filter(|index| thresholds[*index] < 0.1)
+        .map(|index| (index % BLUE_NOISE_SIZE, index / BLUE_NOISE_SIZE))
+        .collect();
+
+    let touching = sparse.iter().any(|(x, y)| sparse.iter().any(|(u, v)| {
+        let dx = x.abs_diff(*u).min(BLUE_NOISE_SIZE - x.abs_diff(*u));
+        let dy = y.abs_diff(*v).min(BLUE_NOISE_SIZE - y.abs_diff(*v));
+        (dx, dy) != (0, 0) && dx <= 1 && dy <= 1
+    }));
+
+    if touching {
+        Err(String::from("Expected the lowest thresholds to be spread apart."))
+    }
+    else {
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,45 @@
+use super::*;
+use alloc::string::String;
+
+#[test]
+fn pixels_outside_of_image() -> Result<(), String> {
+    let white = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF };
+    let mut image = Image::new_pixels(3, 2, vec![white; 6]);
+
+    //(3, 0) would be (0, 1) if only the index were checked
+    if image.get(3, 0).is_some() || image.get(0, 2).is_some() || image.get(usize::MAX, 0).is_some() {
+        return Err(String::from("Expected pixels outside of the image to be None."));
+    }
+
+    if image.set(color::ARGB::default(), 3, 0).is_ok() || image.set(color::ARGB::default(), 0, 2).is_ok() {
+        return Err(String::from("Expected setting pixels outside of the image to be an error."));
+    }
+
+    if image.iter().flatten().any(|pixel| *pixel != white) {
+        return Err(String::from("Expected the image to be unchanged."));
+    }
+
+    image.set(color::ARGB::default(), 2, 1)?;
+
+    if image.get(2, 1) != Some(color::ARGB::default()) || !image.row(2).is_empty() {
+        Err(String::from("Expected the last pixel to be set, and rows outside of the image to be empty."))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn pixel_count_must_match_size() -> Result<(), String> {
+    let pixels = vec![color::ARGB::default(); 6];
+
+    for (width, height) in [(4, 2), (2, 2), (usize::MAX, 2)] {
+        if !matches!(Image::try_new_pixels(width, height, pixels.clone()), Err(ImageError::InvalidArgument(_))) {
+            return Err(format!("Expected {} pixels to be rejected for a {width}x{height} image.", pixels.len()));
+        }
+    }
+
+    match Image::try_new_pixels(3, 2, pixels) {
+        Ok(image) if image.length() == 6 => Ok(()),
+        other => Err(format!("Expected a 3x2 image, got {other:?}."))
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests;
+
+use crate::color;
+use super::Image;
+
+///
+/// Width, in pixels, of a glyph at scale 1
+///
+pub const GLYPH_WIDTH: usize = 3;
+
+///
+/// Height, in pixels, of a glyph at scale 1
+///
+pub const GLYPH_HEIGHT: usize = 5;
+
+///
+/// Space, in pixels, between glyphs at scale 1
+///
+pub const GLYPH_SPACING: usize = 1;
+
+///
+/// Get the rows of the built-in 3x5 glyph for the character, top to
+/// bottom. The 3 low bits of each row are its pixels, left to right.
+/// Letters are drawn as uppercase, and unsupported characters as '?'.
+///
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c.to_ascii_uppercase() {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        _ => [0b110, 0b001, 0b010, 0b000, 0b010]
+    }
+}
+
+///
+/// Size, in pixels, of the given text drawn with the built-in font at the given scale
+///
+pub fn text_size(text: &str, scale: usize) -> (usize, usize) {
+    let count = text.chars().count();
+
+    if count == 0 {
+        return (0, 0);
+    }
+
+    let width = (count * (GLYPH_WIDTH + GLYPH_SPACING) - GLYPH_SPACING) * scale;
+    (width, GLYPH_HEIGHT * scale)
+}
+
+impl Image {
+    ///
+    /// Draw text with the built-in font, with its top-left corner at (x, y).
+    /// Each font pixel is drawn as a scale x scale square. Anything falling
+    /// outside of the image is clipped.
+    ///
+    pub fn draw_text(&mut self, text: &str, x: usize, y: usize, color: color::ARGB, scale: usize) {
+        let scale = scale.max(1);
+
+        for (n, c) in text.chars().enumerate() {
+            let glyph_x = x + n * (GLYPH_WIDTH + GLYPH_SPACING) * scale;
+
+            if glyph_x >= self.width {
+                break;
+            }
+
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for column in (0..GLYPH_WIDTH).filter(|column| bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0) {
+                    for (dx, dy) in (0..scale).flat_map(|dy| (0..scale).map(move |dx| (dx, dy))) {
+                        let (i, j) = (glyph_x + column * scale + dx, y + row * scale + dy);
+
+                        if i < self.width && j < self.height {
+                            let index = self.calculate_index(i, j);
+                            self.pixels[index] = color.over(&self.pixels[index]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,40 @@
+use super::*;
+
+#[test]
+fn size_of_text() -> Result<(), String> {
+    let size = text_size("ab1", 2);
+
+    if size != (22, 10) {
+        return Err(format!("Expected 22x10, got {}x{}.", size.0, size.1));
+    }
+
+    if text_size("", 2) != (0, 0) {
+        return Err(String::from("Empty text should have no size."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn draw_text_pixels() -> Result<(), String> {
+    let white = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF };
+    let mut image = Image::new(4, 5);
+
+    //'L' is the left column plus the rest of the bottom row
+    image.draw_text("L", 0, 0, white, 1);
+
+    let drawn = (0..image.height())
+        .flat_map(|j| image.row(j).iter())
+        .filter(|pixel| **pixel == white)
+        .count();
+
+    if drawn != 7 {
+        return Err(format!("Expected 7 drawn pixels, got {drawn}."));
+    }
+
+    if image.row(4)[2] != white || image.row(0)[1] == white {
+        return Err(String::from("The drawn pixels are not in the expected positions."));
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,505 @@
+#[cfg(all(test, feature = "std"))]
+mod tests;
+
+use alloc::{format, string::String, vec::Vec};
+
+use crate::{color, error::ImageError};
+use super::Image;
+
+///
+/// How to decide which pixels along the border of an image
+/// are background, and can be trimmed away.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrimMode {
+    ///
+    /// Trim fully transparent pixels.
+    ///
+    Transparent,
+    ///
+    /// Trim pixels exactly matching the given color.
+    ///
+    Color(color::ARGB),
+    ///
+    /// Trim pixels whose distance from the top-left pixel
+    /// is no more than the given tolerance.
+    ///
+    Tolerance(f32)
+}
+
+///
+/// Distances, in pixels, from each edge of an image.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Insets {
+    pub left: usize,
+    pub top: usize,
+    pub right: usize,
+    pub bottom: usize
+}
+
+impl Insets {
+    pub fn new(left: usize, top: usize, right: usize, bottom: usize) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom
+        }
+    }
+
+    ///
+    /// The same inset on every edge
+    ///
+    pub fn uniform(inset: usize) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+}
+
+///
+/// How an image is repeated when tiling
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileMode {
+    ///
+    /// Each tile is an exact copy of the image
+    ///
+    #[default]
+    Repeat,
+    ///
+    /// Every other tile is mirrored, so adjacent tiles always
+    /// meet at matching edges
+    ///
+    Mirror
+}
+
+///
+/// How pixels are sampled when resizing an image
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum ResizeFilter {
+    ///
+    /// Use the closest source pixel. Fast, and preserves hard edges.
+    ///
+    Nearest,
+    ///
+    /// Interpolate between the four closest source pixels. Best
+    /// suited to enlarging.
+    ///
+    Bilinear,
+    ///
+    /// Average all source pixels covered by each target pixel. Best
+    /// suited to shrinking.
+    ///
+    #[default]
+    Box
+}
+
+///
+/// A clockwise rotation by a multiple of 90 degrees
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Rotation {
+    Rotate90,
+    Rotate180,
+    Rotate270
+}
+
+///
+/// The axis an image is mirrored across
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Flip {
+    ///
+    /// Swap the left and right sides of the image
+    ///
+    Horizontal,
+    ///
+    /// Swap the top and bottom of the image
+    ///
+    Vertical
+}
+
+#[cfg(feature = "std")]
+impl TrimMode {
+    fn is_background(&self, pixel: &color::ARGB, reference: &color::ARGB) -> bool {
+        match self {
+            Self::Transparent => pixel.alpha == 0,
+            Self::Color(c) => pixel == c,
+            Self::Tolerance(t) => {
+                let alpha_distance = (pixel.alpha as f32 - reference.alpha as f32).abs();
+                pixel.distance_euclidean(reference) <= *t && alpha_distance <= *t
+            }
+        }
+    }
+}
+
+impl Image {
+    ///
+    /// Copy the rectangle with top-left corner (x, y) and the given width
+    /// and height into a new image. The rectangle is clamped to the bounds
+    /// of this image.
+    ///
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Image {
+        let x = x.min(self.width);
+        let y = y.min(self.height);
+        let width = width.min(self.width - x);
+        let height = height.min(self.height - y);
+
+        let pixels: Vec<color::ARGB> = (y..(y + height))
+            .flat_map(|j| self.row(j)[x..(x + width)].iter().copied())
+            .collect();
+
+        Image::new_pixels(width, height, pixels)
+    }
+
+    ///
+    /// Find the tight bounding box (x, y, width, height) around all pixels that are
+    /// not background according to the given mode. Returns None if every pixel
+    /// is background.
+    ///
+    #[cfg(feature = "std")]
+    pub fn content_bounds(&self, mode: TrimMode) -> Option<(usize, usize, usize, usize)> {
+        let reference = self.pixels.first().copied().unwrap_or_default();
+
+        let mut min_x = usize::MAX;
+        let mut min_y = usize::MAX;
+        let mut max_x = 0_usize;
+        let mut max_y = 0_usize;
+
+        for j in 0..self.height {
+            for (i, pixel) in self.row(j).iter().enumerate() {
+                if !mode.is_background(pixel, &reference) {
+                    min_x = min_x.min(i);
+                    min_y = min_y.min(j);
+                    max_x = max_x.max(i);
+                    max_y = max_y.max(j);
+                }
+            }
+        }
+
+        if min_x == usize::MAX {
+            None
+        }
+        else {
+            Some((min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+        }
+    }
+
+    ///
+    /// Crop away the uniform or transparent border around the image,
+    /// leaving only the tight bounding box of the content. If the entire
+    /// image is background, the result is empty.
+    ///
+    #[cfg(feature = "std")]
+    pub fn trim(&self, mode: TrimMode) -> Image {
+        match self.content_bounds(mode) {
+            Some((x, y, width, height)) => self.crop(x, y, width, height),
+            None => Image::new(0, 0)
+        }
+    }
+
+    ///
+    /// Scale the image to the target size using 9-slice scaling: the image is
+    /// divided into a 3x3 grid by the given insets, the corners are copied
+    /// as-is, the edges are stretched along their length, and the center is
+    /// stretched in both directions.
+    ///
+    pub fn nine_slice(&self, insets: Insets, target_width: usize, target_height: usize) -> Result<Image, ImageError> {
+        let target_length = target_width.checked_mul(target_height)
+            .ok_or_else(|| ImageError::InvalidArgument(format!("Image {target_width}x{target_height} is too large.")))?;
+
+        if self.length() == 0 && target_length > 0 {
+            return Err(ImageError::InvalidArgument(String::from("Cannot 9-slice scale an empty image.")));
+        }
+
+        let (Some(horizontal), Some(vertical)) = (insets.left.checked_add(insets.right), insets.top.checked_add(insets.bottom)) else {
+            return Err(ImageError::InvalidArgument(format!("Insets {insets:?} are too large.")));
+        };
+
+        if horizontal > self.width || vertical > self.height {
+            return Err(ImageError::InvalidArgument(format!(
+                "Insets {:?} are larger than the {}x{} source image.",
+                insets, self.width, self.height
+            )));
+        }
+
+        if horizontal > target_width || vertical > target_height {
+            return Err(ImageError::InvalidArgument(format!(
+                "Insets {:?} are larger than the {}x{} target size.",
+                insets, target_width, target_height
+            )));
+        }
+
+        //For each column/row of the output, which column/row of the source it comes from
+        fn axis_map(source: usize, target: usize, start: usize, end: usize) -> Vec<usize> {
+            let source_center = source - start - end;
+            let target_center = target - start - end;
+
+            (0..target)
+                .map(|t| {
+                    if t < start {
+                        t
+                    }
+                    else if t >= target - end {
+                        source - (target - t)
+                    }
+                    else if source_center == 0 {
+                        //Nothing to stretch; reuse the closest edge
+                        start.min(source.saturating_sub(1))
+                    }
+                    else {
+                        //Sample from the center of the corresponding source pixel
+                        let offset = (((t - start) as f32 + 0.5_f32) * (source_center as f32) / (target_center as f32)) as usize;
+                        start + offset.min(source_center - 1)
+                    }
+                })
+                .collect()
+        }
+
+        let columns = axis_map(self.width, target_width, insets.left, insets.right);
+        let rows = axis_map(self.height, target_height, insets.top, insets.bottom);
+
+        let pixels: Vec<color::ARGB> = rows.iter()
+            .flat_map(|j| {
+                let row = self.row(*j);
+                columns.iter().map(move |i| row[*i])
+            })
+            .collect();
+
+        Ok(Image::new_pixels(target_width, target_height, pixels))
+    }
+
+    ///
+    /// Repeat the image across a canvas of the given size
+    ///
+    pub fn tile(&self, target_width: usize, target_height: usize) -> Image {
+        self.tile_with(target_width, target_height, TileMode::Repeat, (0, 0))
+    }
+
+    ///
+    /// Repeat the image across a canvas of the given size, using the given
+    /// mode. The pattern is shifted left and up by offset pixels.
+    ///
+    pub fn tile_with(&self, target_width: usize, target_height: usize, mode: TileMode, offset: (usize, usize)) -> Image {
+        if self.length() == 0 {
+            return Image::new(target_width, target_height);
+        }
+
+        //Which column/row of the source a given column/row of the output comes from
+        fn source_index(t: usize, source: usize, offset: usize, mode: TileMode) -> usize {
+            match mode {
+                TileMode::Repeat => (t + offset) % source,
+                TileMode::Mirror => {
+                    let period = (t + offset) % (2 * source);
+
+                    if period < source {
+                        period
+                    }
+                    else {
+                        2 * source - period - 1
+                    }
+                }
+            }
+        }
+
+        let pixels: Vec<color::ARGB> = (0..target_height)
+            .flat_map(|j| {
+                let row = self.row(source_index(j, self.height, offset.1, mode));
+                (0..target_width).map(move |i| row[source_index(i, self.width, offset.0, mode)])
+            })
+            .collect();
+
+        Image::new_pixels(target_width, target_height, pixels)
+    }
+
+    ///
+    /// Measure how visible the seams would be if this image were tiled,
+    /// as the mean distance between pixels on opposite edges. 0 means the
+    /// image wraps perfectly.
+    ///
+    #[cfg(feature = "std")]
+    pub fn seam_error(&self) -> f32 {
+        if self.length() == 0 {
+            return 0_f32;
+        }
+
+        let horizontal = (0..self.height)
+            .map(|j| {
+                let row = self.row(j);
+                row[0].distance_euclidean(&row[self.width - 1])
+            });
+
+        let vertical = self.row(0).iter()
+            .zip(self.row(self.height - 1).iter())
+            .map(|(top, bottom)| top.distance_euclidean(bottom));
+
+        let (total, count) = horizontal.chain(vertical)
+            .fold((0_f32, 0_usize), |(total, count), d| (total + d, count + 1));
+
+        total / (count as f32)
+    }
+
+    ///
+    /// Whether the image can be tiled without any seam exceeding
+    /// the given tolerance, on average
+    ///
+    #[cfg(feature = "std")]
+    pub fn is_seamless(&self, tolerance: f32) -> bool {
+        self.seam_error() <= tolerance
+    }
+
+    ///
+    /// Make the image tile seamlessly by cross-fading a band of the given
+    /// width from each edge into the opposite one. The result is smaller
+    /// than this image by blend pixels in each dimension.
+    ///
+    #[cfg(feature = "std")]
+    pub fn blend_edges(&self, blend: usize) -> Result<Image, ImageError> {
+        if blend >= self.width || blend >= self.height {
+            return Err(ImageError::InvalidArgument(format!(
+                "Blend width {} must be smaller than the {}x{} image.",
+                blend, self.width, self.height
+            )));
+        }
+
+        let width = self.width - blend;
+        let height = self.height - blend;
+
+        //Fade the last `blend` columns/rows into the first, placing the overlap at the start
+        let blended = |i: usize, j: usize| -> color::ARGB {
+            let column = |j: usize| -> color::ARGB {
+                let row = self.row(j);
+
+                if i < blend {
+                    let t = (i as f32 + 0.5_f32) / (blend as f32);
+                    row[width + i].lerp(&row[i], t)
+                }
+                else {
+                    row[i]
+                }
+            };
+
+            if j < blend {
+                let t = (j as f32 + 0.5_f32) / (blend as f32);
+                column(height + j).lerp(&column(j), t)
+            }
+            else {
+                column(j)
+            }
+        };
+
+        Ok(Image::from_fn(width, height, blended))
+    }
+
+    ///
+    /// Scale the image to the given size, using the given filter
+    ///
+    #[cfg(feature = "std")]
+    pub fn resize(&self, target_width: usize, target_height: usize, filter: ResizeFilter) -> Image {
+        if self.length() == 0 || target_width == 0 || target_height == 0 {
+            return Image::new(target_width, target_height);
+        }
+
+        let scale_x = self.width as f32 / target_width as f32;
+        let scale_y = self.height as f32 / target_height as f32;
+
+        Image::from_fn(target_width, target_height, |i, j| match filter {
+            ResizeFilter::Nearest => {
+                let x = (((i as f32 + 0.5_f32) * scale_x) as usize).min(self.width - 1);
+                let y = (((j as f32 + 0.5_f32) * scale_y) as usize).min(self.height - 1);
+                self.row(y)[x]
+            },
+            ResizeFilter::Bilinear => {
+                //Position of the target pixel's center in source pixel coordinates
+                let x = ((i as f32 + 0.5_f32) * scale_x - 0.5_f32).clamp(0_f32, (self.width - 1) as f32);
+                let y = ((j as f32 + 0.5_f32) * scale_y - 0.5_f32).clamp(0_f32, (self.height - 1) as f32);
+
+                let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+                let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+                let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+
+                let top = self.row(y0)[x0].lerp(&self.row(y0)[x1], tx);
+                let bottom = self.row(y1)[x0].lerp(&self.row(y1)[x1], tx);
+
+                top.lerp(&bottom, ty)
+            },
+            ResizeFilter::Box => {
+                //Range of source pixels covered by the target pixel, always covering at least one
+                let x0 = ((i as f32 * scale_x) as usize).min(self.width - 1);
+                let y0 = ((j as f32 * scale_y) as usize).min(self.height - 1);
+                let x1 = (((i + 1) as f32 * scale_x).ceil() as usize).clamp(x0 + 1, self.width);
+                let y1 = (((j + 1) as f32 * scale_y).ceil() as usize).clamp(y0 + 1, self.height);
+
+                average((y0..y1).flat_map(|y| self.row(y)[x0..x1].iter()))
+            }
+        })
+    }
+}
+
+impl Image {
+    ///
+    /// Rotate the image clockwise
+    ///
+    pub fn rotate(&self, rotation: Rotation) -> Image {
+        let (width, height) = match rotation {
+            Rotation::Rotate180 => (self.width, self.height),
+            Rotation::Rotate90 | Rotation::Rotate270 => (self.height, self.width)
+        };
+
+        Image::from_fn(width, height, |i, j| match rotation {
+            Rotation::Rotate90 => self.row(self.height - 1 - i)[j],
+            Rotation::Rotate180 => self.row(self.height - 1 - j)[self.width - 1 - i],
+            Rotation::Rotate270 => self.row(i)[self.width - 1 - j]
+        })
+    }
+
+    ///
+    /// Mirror the image across the given axis
+    ///
+    pub fn flip(&self, flip: Flip) -> Image {
+        let pixels: Vec<color::ARGB> = match flip {
+            Flip::Horizontal => (0..self.height)
+                .flat_map(|j| self.row(j).iter().rev().copied())
+                .collect(),
+            Flip::Vertical => (0..self.height).rev()
+                .flat_map(|j| self.row(j).iter().copied())
+                .collect()
+        };
+
+        Image::new_pixels(self.width, self.height, pixels)
+    }
+}
+
+///
+/// Average the given colors, weighting each color channel by alpha
+/// so transparent pixels don't bleed their color into the result
+///
+#[cfg(feature = "std")]
+fn average<'a, I>(colors: I) -> color::ARGB where I: Iterator<Item = &'a color::ARGB> {
+    let (mut red, mut green, mut blue, mut alpha, mut count) = (0_f32, 0_f32, 0_f32, 0_f32, 0_usize);
+
+    for color in colors {
+        let weight = color.alpha as f32;
+        red += color.red as f32 * weight;
+        green += color.green as f32 * weight;
+        blue += color.blue as f32 * weight;
+        alpha += weight;
+        count += 1;
+    }
+
+    if alpha == 0_f32 {
+        color::ARGB::default()
+    }
+    else {
+        color::ARGB {
+            alpha: (alpha / count as f32).round() as u8,
+            red: (red / alpha).round() as u8,
+            green: (green / alpha).round() as u8,
+            blue: (blue / alpha).round() as u8
+        }
+    }
+}
\ No newline at end of file
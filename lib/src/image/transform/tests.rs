@@ -0,0 +1,246 @@
+use super::*;
+
+///
+/// A 4x3 image with a transparent border around a 2x1 block of content
+///
+fn bordered() -> Image {
+    let clear = color::ARGB::default();
+    let red = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x00 };
+    let blue = color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0xFF };
+
+    Image::new_pixels(4, 3, vec![
+        clear, clear, clear, clear,
+        clear, red, blue, clear,
+        clear, clear, clear, clear
+    ])
+}
+
+#[test]
+fn trim_transparent() -> Result<(), String> {
+    let trimmed = bordered().trim(TrimMode::Transparent);
+
+    if trimmed.width() != 2 || trimmed.height() != 1 {
+        return Err(format!("Expected a 2x1 image, got {}x{}.", trimmed.width(), trimmed.height()));
+    }
+
+    if trimmed.row(0) != &bordered().row(1)[1..3] {
+        return Err(String::from("The trimmed pixels do not match the content of the original image."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn trim_color() -> Result<(), String> {
+    let white = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF };
+    let mut image = Image::new_pixels(3, 3, vec![white; 9]);
+    image.set(color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0x00 }, 2, 2)?;
+
+    let trimmed = image.trim(TrimMode::Color(white));
+
+    if trimmed.width() != 1 || trimmed.height() != 1 {
+        return Err(format!("Expected a 1x1 image, got {}x{}.", trimmed.width(), trimmed.height()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn trim_tolerance() -> Result<(), String> {
+    let background = color::ARGB { alpha: 0xFF, red: 0xF0, green: 0xF0, blue: 0xF0 };
+    let near = color::ARGB { alpha: 0xFF, red: 0xF2, green: 0xEE, blue: 0xF0 };
+    let content = color::ARGB { alpha: 0xFF, red: 0x10, green: 0x10, blue: 0x10 };
+
+    let image = Image::new_pixels(3, 2, vec![
+        background, near, background,
+        near, content, content
+    ]);
+
+    let trimmed = image.trim(TrimMode::Tolerance(5_f32));
+
+    if trimmed.width() != 2 || trimmed.height() != 1 {
+        return Err(format!("Expected a 2x1 image, got {}x{}.", trimmed.width(), trimmed.height()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn trim_all_background() -> Result<(), String> {
+    let trimmed = Image::new(5, 5).trim(TrimMode::Transparent);
+
+    if trimmed.length() != 0 {
+        return Err(String::from("Trimming an image that is entirely background should produce an empty image."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn nine_slice_keeps_corners() -> Result<(), String> {
+    let corner = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x00 };
+    let edge = color::ARGB { alpha: 0xFF, red: 0x00, green: 0xFF, blue: 0x00 };
+    let center = color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0xFF };
+
+    let image = Image::new_pixels(3, 3, vec![
+        corner, edge, corner,
+        edge, center, edge,
+        corner, edge, corner
+    ]);
+
+    let scaled = image.nine_slice(Insets::uniform(1), 6, 5)?;
+
+    for (i, j) in [(0, 0), (5, 0), (0, 4), (5, 4)] {
+        if scaled.get(i, j) != Some(corner) {
+            return Err(format!("Corner pixel ({i}, {j}) was not preserved."));
+        }
+    }
+
+    for i in 1..5 {
+        if scaled.get(i, 0) != Some(edge) || scaled.get(i, 4) != Some(edge) {
+            return Err(format!("Edge column {i} was not stretched from the source edge."));
+        }
+    }
+
+    for j in 1..4 {
+        for i in 1..5 {
+            if scaled.get(i, j) != Some(center) {
+                return Err(format!("Center pixel ({i}, {j}) was not stretched from the source center."));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn nine_slice_rejects_oversized_insets() {
+    let image = Image::new(4, 4);
+
+    assert!(image.nine_slice(Insets::uniform(3), 10, 10).is_err());
+    assert!(image.nine_slice(Insets::uniform(1), 1, 10).is_err());
+}
+
+#[test]
+fn nine_slice_rejects_overflowing_insets() {
+    let image = Image::new(4, 4);
+
+    assert!(image.nine_slice(Insets::uniform(usize::MAX), 10, 10).is_err());
+    assert!(image.nine_slice(Insets::new(usize::MAX, 0, 1, 0), 10, 10).is_err());
+    assert!(image.nine_slice(Insets::new(0, 1, 0, usize::MAX), 10, 10).is_err());
+}
+
+#[test]
+fn nine_slice_rejects_oversized_target() {
+    let image = Image::new(4, 4);
+
+    assert!(image.nine_slice(Insets::uniform(1), usize::MAX, 2).is_err());
+}
+
+#[test]
+fn tile_mirror() -> Result<(), String> {
+    let a = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0x00, blue: 0x00 };
+    let b = color::ARGB { alpha: 0xFF, red: 0x00, green: 0xFF, blue: 0x00 };
+    let image = Image::new_pixels(2, 1, vec![a, b]);
+
+    let repeated = image.tile(5, 2);
+    let mirrored = image.tile_with(5, 2, TileMode::Mirror, (0, 0));
+
+    if repeated.row(1) != [a, b, a, b, a] {
+        return Err(String::from("Repeated tiles do not match the source image."));
+    }
+
+    if mirrored.row(1) != [a, b, b, a, a] {
+        return Err(String::from("Mirrored tiles are not reflected at each boundary."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blend_edges_is_seamless() -> Result<(), String> {
+    let gradient: Vec<color::ARGB> = (0..64_u8)
+        .map(|n| color::ARGB { alpha: 0xFF, red: (n % 8) * 32, green: (n / 8) * 32, blue: 0x00 })
+        .collect();
+    let image = Image::new_pixels(8, 8, gradient);
+
+    let blended = image.blend_edges(4)?;
+
+    if blended.seam_error() >= image.seam_error() {
+        return Err(format!(
+            "Blending did not reduce the seam error ({} before, {} after).",
+            image.seam_error(), blended.seam_error()
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn resize_box_averages() -> Result<(), String> {
+    let black = color::ARGB { alpha: 0xFF, red: 0x00, green: 0x00, blue: 0x00 };
+    let white = color::ARGB { alpha: 0xFF, red: 0xFF, green: 0xFF, blue: 0xFF };
+    let image = Image::new_pixels(2, 2, vec![black, white, white, black]);
+
+    let resized = image.resize(1, 1, ResizeFilter::Box);
+    let expected = color::ARGB { alpha: 0xFF, red: 0x80, green: 0x80, blue: 0x80 };
+
+    if resized.get(0, 0) != Some(expected) {
+        return Err(format!("Expected {:?}, got {:?}.", expected, resized.get(0, 0)));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn resize_nearest_enlarges() -> Result<(), String> {
+    let image = bordered();
+    let resized = image.resize(8, 6, ResizeFilter::Nearest);
+
+    for j in 0..6 {
+        for i in 0..8 {
+            if resized.get(i, j) != image.get(i / 2, j / 2) {
+                return Err(format!("Pixel ({i}, {j}) does not match its nearest source pixel."));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn rotate_90() -> Result<(), String> {
+    let image = bordered();
+    let rotated = image.rotate(Rotation::Rotate90);
+
+    if rotated.width() != 3 || rotated.height() != 4 {
+        return Err(format!("Expected a 3x4 image, got {}x{}.", rotated.width(), rotated.height()));
+    }
+
+    //The content at (1, 1) and (2, 1) should move to (1, 1) and (1, 2)
+    if rotated.row(1)[1] != image.row(1)[1] || rotated.row(2)[1] != image.row(1)[2] {
+        return Err(String::from("The rotated pixels are not in the expected positions."));
+    }
+
+    if rotated.rotate(Rotation::Rotate270) != image {
+        return Err(String::from("Rotating back did not restore the original image."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn flip_horizontal() -> Result<(), String> {
+    let image = bordered();
+    let flipped = image.flip(Flip::Horizontal);
+
+    if flipped.row(1)[1] != image.row(1)[2] || flipped.row(1)[2] != image.row(1)[1] {
+        return Err(String::from("The flipped pixels are not in the expected positions."));
+    }
+
+    if flipped.flip(Flip::Horizontal) != image {
+        return Err(String::from("Flipping twice did not restore the original image."));
+    }
+
+    Ok(())
+}
\ No newline at end of file
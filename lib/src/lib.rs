@@ -1,5 +1,13 @@
+//Without the std feature, only the image and color types, the codecs and the lossless
+//transforms are built, on alloc alone, so that they can be used in firmware
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod image;
 pub mod color;
 pub mod utility;
 pub mod convert;
-pub mod constants;
\ No newline at end of file
+pub mod constants;
+pub mod log;
+pub mod error;
\ No newline at end of file
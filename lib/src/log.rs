@@ -0,0 +1,141 @@
+#[cfg(all(test, feature = "std"))]
+mod tests;
+
+use core::fmt;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+///
+/// How important a log message is
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    ///
+    /// An operation failed
+    ///
+    Error = 1,
+    ///
+    /// Something unexpected happened, but was recovered from,
+    /// i.e. a malformed file was decoded anyway
+    ///
+    Warn = 2,
+    ///
+    /// Progress the user may want to know about
+    ///
+    Info = 3,
+    ///
+    /// Details for diagnosing problems, i.e. how long each stage took
+    ///
+    Debug = 4
+}
+
+impl Level {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug"
+        }
+    }
+}
+
+///
+/// Destination for log messages. The library never prints on its own;
+/// applications install a logger to see what it reports.
+///
+pub trait Logger: Send + Sync {
+    fn log(&self, level: Level, message: &fmt::Arguments);
+}
+
+//Without std there is nowhere to install a logger, so messages are dropped
+#[cfg(feature = "std")]
+static LOGGER: OnceLock<Box<dyn Logger>> = OnceLock::new();
+
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Warn as u8);
+
+///
+/// Install the logger messages are sent to. Can only be done once.
+///
+#[cfg(feature = "std")]
+pub fn set_logger(logger: Box<dyn Logger>) -> Result<(), String> {
+    LOGGER.set(logger)
+        .map_err(|_| String::from("A logger has already been set."))
+}
+
+///
+/// Set the least important level of message that is logged
+///
+pub fn set_max_level(level: Level) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+///
+/// Whether messages of the given level would be logged
+///
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed) && logger().is_some()
+}
+
+fn logger() -> Option<&'static dyn Logger> {
+    #[cfg(feature = "std")]
+    return LOGGER.get().map(|logger| logger.as_ref());
+
+    #[cfg(not(feature = "std"))]
+    None
+}
+
+pub fn log(level: Level, message: fmt::Arguments) {
+    if enabled(level) {
+        if let Some(logger) = logger() {
+            logger.log(level, &message);
+        }
+    }
+}
+
+pub fn error(message: fmt::Arguments) {
+    log(Level::Error, message);
+}
+
+pub fn warn(message: fmt::Arguments) {
+    log(Level::Warn, message);
+}
+
+pub fn info(message: fmt::Arguments) {
+    log(Level::Info, message);
+}
+
+pub fn debug(message: fmt::Arguments) {
+    log(Level::Debug, message);
+}
+
+///
+/// Logs how long a stage took, at debug level, when dropped
+///
+#[cfg(feature = "std")]
+pub struct StageTimer {
+    stage: &'static str,
+    start: Instant
+}
+
+#[cfg(feature = "std")]
+impl Drop for StageTimer {
+    fn drop(&mut self) {
+        debug(format_args!("{} took {:.3} ms", self.stage, self.start.elapsed().as_secs_f64() * 1000_f64));
+    }
+}
+
+///
+/// Start timing a stage, which is logged when the returned timer is dropped
+///
+#[cfg(feature = "std")]
+pub fn time(stage: &'static str) -> StageTimer {
+    StageTimer {
+        stage,
+        start: Instant::now()
+    }
+}
\ No newline at end of file
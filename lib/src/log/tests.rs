@@ -0,0 +1,116 @@
+use std::sync::{Mutex, MutexGuard};
+use std::thread::{self, ThreadId};
+
+use super::*;
+
+///
+/// Messages logged by every thread, so each test can pick out its own
+///
+static MESSAGES: Mutex<Vec<(ThreadId, Level, String)>> = Mutex::new(Vec::new());
+
+///
+/// The max level is shared by every test, so tests which set it take turns
+///
+static LEVEL: Mutex<()> = Mutex::new(());
+
+struct TestLogger;
+
+impl Logger for TestLogger {
+    fn log(&self, level: Level, message: &fmt::Arguments) {
+        MESSAGES.lock().unwrap_or_else(|err| err.into_inner())
+            .push((thread::current().id(), level, message.to_string()));
+    }
+}
+
+///
+/// Install the test logger if it isn't already, and set the max level until the guard is dropped
+///
+fn max_level(level: Level) -> MutexGuard<'static, ()> {
+    let guard = LEVEL.lock().unwrap_or_else(|err| err.into_inner());
+    let _ = set_logger(Box::new(TestLogger));
+    set_max_level(level);
+    guard
+}
+
+///
+/// Take the messages logged by this thread
+///
+fn logged() -> Vec<(Level, String)> {
+    let thread = thread::current().id();
+    let mut messages = MESSAGES.lock().unwrap_or_else(|err| err.into_inner());
+
+    let (mine, others): (Vec<_>, Vec<_>) = messages.drain(..).partition(|(id, ..)| *id == thread);
+    *messages = others;
+
+    mine.into_iter().map(|(_, level, message)| (level, message)).collect()
+}
+
+fn log_each_level() {
+    error(format_args!("e"));
+    warn(format_args!("w"));
+    info(format_args!("i"));
+    debug(format_args!("d"));
+}
+
+#[test]
+fn levels_are_filtered() -> Result<(), String> {
+    let _guard = max_level(Level::Warn);
+    logged();
+
+    log_each_level();
+
+    let levels: Vec<Level> = logged().into_iter().map(|(level, _)| level).collect();
+
+    if levels != [Level::Error, Level::Warn] {
+        return Err(format!("Expected only errors and warnings to be logged, got {levels:?}."));
+    }
+
+    if !enabled(Level::Warn) || enabled(Level::Info) {
+        return Err(String::from("Expected warnings to be enabled, but not info."));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn quiet_and_verbose_levels() -> Result<(), String> {
+    //Quiet logs only errors, and verbose everything, as the console sets them
+    for (level, expected) in [(Level::Error, vec!["e"]), (Level::Debug, vec!["e", "w", "i", "d"])] {
+        let _guard = max_level(level);
+        logged();
+
+        log_each_level();
+
+        let messages: Vec<String> = logged().into_iter().map(|(_, message)| message).collect();
+
+        if messages != expected {
+            return Err(format!("Expected {expected:?} to be logged at {}, got {messages:?}.", level.name()));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn timer_logs_at_debug() -> Result<(), String> {
+    let guard = max_level(Level::Debug);
+    logged();
+
+    drop(time("Decoding"));
+
+    match logged().as_slice() {
+        [(Level::Debug, message)] if message.starts_with("Decoding took ") && message.ends_with(" ms") => (),
+        other => return Err(format!("Expected one debug message with the stage's time, got {other:?}."))
+    }
+
+    drop(guard);
+
+    //Timers say nothing unless debug messages are logged
+    let _guard = max_level(Level::Info);
+    drop(time("Decoding"));
+
+    match logged().as_slice() {
+        [] => Ok(()),
+        other => Err(format!("Expected nothing to be logged below debug, got {other:?}."))
+    }
+}
@@ -1,34 +1,43 @@
-pub trait FromBitSlice {
-    fn reduce_bit_slice(slice: &[u8]) -> Self; 
+//Bitmaps are decoded with these, so they must not panic on any input
+#![deny(clippy::indexing_slicing, clippy::unwrap_used, clippy::expect_used)]
+
+use alloc::{format, string::String, vec::Vec};
+
+pub trait FromBitSlice: Sized {
+    ///
+    /// Combine little-endian bytes into a value, or None if
+    /// there are no bytes or more than the value can hold
+    ///
+    fn reduce_bit_slice(slice: &[u8]) -> Option<Self>;
 }
 
 impl FromBitSlice for u16 {
-    fn reduce_bit_slice(slice: &[u8]) -> Self {
-        slice.iter()
-            .enumerate()
-            .map(|(index, byte)| Self::from(*byte) << (8 * index))
-            .reduce(|a, b| a + b)
-            .unwrap()
+    fn reduce_bit_slice(slice: &[u8]) -> Option<Self> {
+        if slice.is_empty() || slice.len() > size_of::<Self>() {
+            return None;
+        }
+
+        Some(slice.iter().rev().fold(0, |value, byte| (value << 8) | Self::from(*byte)))
     }
 }
 
 impl FromBitSlice for u32 {
-    fn reduce_bit_slice(slice: &[u8]) -> Self {
-        slice.iter()
-            .enumerate()
-            .map(|(index, byte)| Self::from(*byte) << (8 * index))
-            .reduce(|a, b| a + b)
-            .unwrap()
+    fn reduce_bit_slice(slice: &[u8]) -> Option<Self> {
+        if slice.is_empty() || slice.len() > size_of::<Self>() {
+            return None;
+        }
+
+        Some(slice.iter().rev().fold(0, |value, byte| (value << 8) | Self::from(*byte)))
     }
 }
 
 impl FromBitSlice for i32 {
-    fn reduce_bit_slice(slice: &[u8]) -> Self {
-        slice.iter()
-            .enumerate()
-            .map(|(index, byte)| Self::from(*byte) << (8 * index))
-            .reduce(|a, b| a + b)
-            .unwrap()
+    fn reduce_bit_slice(slice: &[u8]) -> Option<Self> {
+        if slice.is_empty() || slice.len() > size_of::<Self>() {
+            return None;
+        }
+
+        Some(slice.iter().rev().fold(0, |value, byte| (value << 8) | Self::from(*byte)))
     }
 }
 
@@ -40,13 +49,44 @@ pub fn round_to_next_multiple_of_4(value: i32) -> usize {
     ((value + 4 - 1) & -4) as usize
 }
 
+///
+/// Format bytes as a plain hexdump, with the offset of each line in the left
+/// gutter and the printable ASCII characters in the right. Offsets start from
+/// the given value, for dumping a slice from the middle of a file.
+/// 
+pub fn hexdump(bytes: &[u8], bytes_per_line: usize, start_offset: usize) -> String {
+    let bytes_per_line = bytes_per_line.max(1);
+
+    bytes.chunks(bytes_per_line)
+        .enumerate()
+        .map(|(line, chunk)| {
+            let hex = chunk.iter()
+                .map(|b| format!("{b:02X}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            let ascii: String = chunk.iter()
+                .map(|b| if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' })
+                .collect();
+
+            format!("{:08X}  {hex: <width$}  |{ascii}|", start_offset.saturating_add(line.saturating_mul(bytes_per_line)), width = bytes_per_line.saturating_mul(3) - 1)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub mod zlib;
+pub mod datauri;
+
+#[cfg(feature = "std")]
 pub mod file {
     use std::fs;
     use fs::File;
     use std::path;
     use std::io::{Read, BufReader, Write};
+    use crate::error;
 
-    pub fn get_file_bytes(path: &str) -> Result<Vec<u8>, std::io::Error> {
+    pub fn get_file_bytes(path: &str) -> error::Result<Vec<u8>> {
         let file_path = path::Path::new(path);
 
         //Open the file
@@ -61,7 +101,26 @@ pub mod file {
         Ok(buffer)
     }
 
-    pub fn write_file_bytes(path: &str, bytes: &[u8]) -> Result<(), std::io::Error> {
+    ///
+    /// Memory map the file at the given path, so its bytes are paged in
+    /// as they are read instead of being copied into memory up front.
+    /// Fails for files which can't be mapped, such as pipes.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be truncated or modified while the mapping is alive.
+    /// Reading a page which was cut off the end of the file raises SIGBUS,
+    /// and the bytes can otherwise change underneath the slice.
+    ///
+    #[cfg(feature = "mmap")]
+    pub unsafe fn map_file_bytes(path: &str) -> error::Result<memmap2::Mmap> {
+        let file = File::open(path::Path::new(path))?;
+
+        //Safety: upheld by the caller
+        Ok(unsafe { memmap2::Mmap::map(&file) }?)
+    }
+
+    pub fn write_file_bytes(path: &str, bytes: &[u8]) -> error::Result<()> {
         let file_path = path::Path::new(path);
 
         //Create directory if necessary
@@ -70,6 +129,82 @@ pub mod file {
         }
         
         let mut file = File::create(path)?;
-        file.write_all(bytes)
+        file.write_all(bytes)?;
+
+        Ok(())
+    }
+}
+
+pub mod random {
+    ///
+    /// A small, seedable pseudo-random number generator (SplitMix64).
+    /// Not suitable for cryptography; intended for reproducible
+    /// noise and test data.
+    ///
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Random {
+        state: u64
+    }
+
+    impl Random {
+        pub fn new(seed: u64) -> Self {
+            Self {
+                state: seed
+            }
+        }
+
+        pub fn next_u64(&mut self) -> u64 {
+            self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+            mix(self.state)
+        }
+
+        ///
+        /// Uniformly distributed value in [0, 1)
+        ///
+        pub fn next_f32(&mut self) -> f32 {
+            //Use the top 24 bits, which is all of the precision an f32 mantissa has
+            ((self.next_u64() >> 40) as f32) / ((1_u64 << 24) as f32)
+        }
+
+        ///
+        /// Uniformly distributed value in [0, n). Returns 0 if n is 0.
+        ///
+        pub fn next_below(&mut self, n: usize) -> usize {
+            if n == 0 {
+                0
+            }
+            else {
+                (self.next_u64() % (n as u64)) as usize
+            }
+        }
+
+        ///
+        /// Normally distributed value with mean 0 and standard deviation 1,
+        /// using the Box-Muller transform
+        ///
+        #[cfg(feature = "std")]
+        pub fn next_gaussian(&mut self) -> f32 {
+            //Avoid ln(0)
+            let u1 = f32::max(self.next_f32(), f32::MIN_POSITIVE);
+            let u2 = self.next_f32();
+
+            f32::sqrt(-2_f32 * u1.ln()) * f32::cos(2_f32 * std::f32::consts::PI * u2)
+        }
+    }
+
+    ///
+    /// Deterministically hash a lattice coordinate and seed to a 64-bit value
+    ///
+    pub fn hash(x: i64, y: i64, seed: u64) -> u64 {
+        mix(seed
+            ^ mix((x as u64).wrapping_mul(0x9E3779B97F4A7C15))
+            ^ mix((y as u64).wrapping_mul(0xC2B2AE3D27D4EB4F)))
+    }
+
+    fn mix(n: u64) -> u64 {
+        let mut z = n;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
     }
 }
\ No newline at end of file
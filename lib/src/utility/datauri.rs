@@ -0,0 +1,184 @@
+#[cfg(test)]
+#[allow(clippy::indexing_slicing, clippy::unwrap_used)]
+mod tests;
+
+use alloc::{format, string::String, vec::Vec};
+
+use crate::error::ImageError;
+use crate::image::format::bitmap::Bitmap;
+#[cfg(feature = "std")]
+use crate::image::{Image, format::{ImageFormat, codec}};
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+///
+/// The media type and bytes of a data URI
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DataUri {
+    ///
+    /// The media type, without parameters, e.g. image/png. Empty if the URI gave none.
+    ///
+    pub mime_type: String,
+    pub bytes: Vec<u8>
+}
+
+///
+/// Encode bytes as standard base64, padded with '='
+///
+pub fn encode_base64(bytes: &[u8]) -> String {
+    let sextet = |value: u32, shift: u32| BASE64_ALPHABET.get(((value >> shift) & 0x3F) as usize).map_or('=', |c| *c as char);
+
+    bytes.chunks(3)
+        .flat_map(|chunk| {
+            let value = chunk.iter().enumerate().fold(0_u32, |value, (i, byte)| value | (*byte as u32) << (16 - 8 * i));
+
+            [18, 12, 6, 0].into_iter()
+                .enumerate()
+                .map(move |(i, shift)| if i <= chunk.len() { sextet(value, shift) } else { '=' })
+        })
+        .collect()
+}
+
+///
+/// Decode standard base64. Whitespace is skipped, as is padding, which may be left out.
+///
+pub fn decode_base64(text: &str) -> Result<Vec<u8>, ImageError> {
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3);
+    let mut value: u32 = 0;
+    let mut bits = 0;
+    let mut padded = false;
+
+    for (position, c) in text.bytes().enumerate() {
+        let sextet = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => {
+                padded = true;
+                continue;
+            },
+            _ if c.is_ascii_whitespace() => continue,
+            _ => return Err(ImageError::Decode(format!("Invalid base64 character '{}' at {position}.", c as char)))
+        };
+
+        if padded {
+            return Err(ImageError::Decode(format!("Base64 continues after padding at {position}.")));
+        }
+
+        value = (value << 6) | sextet as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((value >> bits) as u8);
+        }
+    }
+
+    //A lone trailing character can't make up a byte
+    if bits == 6 {
+        return Err(ImageError::Decode(String::from("Base64 is truncated.")));
+    }
+
+    Ok(bytes)
+}
+
+///
+/// Make a data URI of the bytes, e.g. to embed an image in an HTML report
+///
+pub fn encode(mime_type: &str, bytes: &[u8]) -> String {
+    format!("data:{mime_type};base64,{}", encode_base64(bytes))
+}
+
+///
+/// Read the media type and bytes of a data URI, which may be base64 or percent-encoded
+///
+pub fn decode(uri: &str) -> Result<DataUri, ImageError> {
+    let rest = uri.trim()
+        .get(..5)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("data:"))
+        .and_then(|_| uri.trim().get(5..))
+        .ok_or_else(|| ImageError::Decode(String::from("Data URI must start with 'data:'.")))?;
+
+    let (header, data) = rest.split_once(',')
+        .ok_or_else(|| ImageError::Decode(String::from("Data URI has no ',' before its data.")))?;
+
+    let mut parameters = header.split(';');
+    let mime_type = parameters.next().unwrap_or_default().trim().to_ascii_lowercase();
+    let is_base64 = parameters.any(|parameter| parameter.trim().eq_ignore_ascii_case("base64"));
+
+    let bytes = if is_base64 {
+        decode_base64(data)?
+    }
+    else {
+        decode_percent(data)?
+    };
+
+    Ok(DataUri {
+        mime_type,
+        bytes
+    })
+}
+
+fn decode_percent(data: &str) -> Result<Vec<u8>, ImageError> {
+    let mut bytes = Vec::with_capacity(data.len());
+    let mut iter = data.bytes();
+
+    while let Some(byte) = iter.next() {
+        if byte != b'%' {
+            bytes.push(byte);
+            continue;
+        }
+
+        let hex = [iter.next(), iter.next()];
+
+        let value = match hex {
+            [Some(high), Some(low)] => core::str::from_utf8(&[high, low]).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None
+        };
+
+        bytes.push(value.ok_or_else(|| ImageError::Decode(String::from("Data URI has an invalid percent escape.")))?);
+    }
+
+    Ok(bytes)
+}
+
+///
+/// Make a data URI of the bitmap, encoded as is
+///
+pub fn from_bitmap(bitmap: Bitmap) -> Result<String, ImageError> {
+    Ok(encode("image/bmp", &Vec::try_from(bitmap)?))
+}
+
+///
+/// Make a data URI of the image, encoded in the given format
+///
+#[cfg(feature = "std")]
+pub fn from_image(image: &Image, format: ImageFormat) -> Result<String, ImageError> {
+    let codec = codec::find(format.extension())
+        .filter(|codec| codec.can_encode())
+        .ok_or_else(|| ImageError::Unsupported(format!("Writing {} images is not supported.", format.name())))?;
+
+    Ok(encode(format.mime_type(), &codec.encode(image)?))
+}
+
+///
+/// Decode the image in a data URI with the format its media type names. If
+/// the type is unknown, or doesn't match the bytes, the format is detected.
+///
+#[cfg(feature = "std")]
+pub fn to_image(uri: &str) -> Result<Image, ImageError> {
+    let data = decode(uri)?;
+
+    //Registered formats are found by the subtype, e.g. image/qoi for qoi
+    let extension = ImageFormat::from_mime_type(&data.mime_type)
+        .map(|format| String::from(format.extension()))
+        .or_else(|| data.mime_type.strip_prefix("image/").map(|subtype| subtype.trim_start_matches("x-").into()));
+
+    match extension.and_then(|extension| codec::find(&extension)) {
+        Some(codec) if codec.detect(&data.bytes) => codec.decode(&data.bytes),
+        _ => codec::load(&data.bytes)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,101 @@
+use super::*;
+
+#[test]
+fn base64_round_trips() -> Result<(), String> {
+    //The examples of RFC 4648
+    let cases = [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v"), ("foob", "Zm9vYg=="), ("fooba", "Zm9vYmE="), ("foobar", "Zm9vYmFy")];
+
+    for (plain, encoded) in cases {
+        if encode_base64(plain.as_bytes()) != encoded {
+            return Err(format!("Expected '{plain}' to encode as '{encoded}', got '{}'.", encode_base64(plain.as_bytes())));
+        }
+
+        if decode_base64(encoded)? != plain.as_bytes() {
+            return Err(format!("Expected '{encoded}' to decode as '{plain}'."));
+        }
+    }
+
+    let bytes: Vec<u8> = (0..=255).collect();
+
+    //Wrapped and without padding, as found in the wild
+    let wrapped: String = encode_base64(&bytes).trim_end_matches('=').chars()
+        .enumerate()
+        .flat_map(|(i, c)| (i > 0 && i % 76 == 0).then_some('\n').into_iter().chain([c]))
+        .collect();
+
+    if decode_base64(&wrapped)? != bytes {
+        return Err(String::from("Wrapped base64 did not decode to the original bytes."));
+    }
+
+    for bad in ["Zm9v!", "Zg==Zg", "Z"] {
+        if decode_base64(bad).is_ok() {
+            return Err(format!("Expected '{bad}' to fail to decode."));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn data_uri_decodes() -> Result<(), String> {
+    let uri = encode("image/png", b"\x89PNG");
+
+    if uri != "data:image/png;base64,iVBORw==" {
+        return Err(format!("Unexpected data URI '{uri}'."));
+    }
+
+    let cases = [
+        (uri.as_str(), "image/png", b"\x89PNG".as_slice()),
+        ("DATA:Image/GIF;charset=x;base64,R0lG", "image/gif", b"GIF"),
+        ("data:,A%20b%2c", "", b"A b,")
+    ];
+
+    for (uri, mime_type, bytes) in cases {
+        let data = decode(uri)?;
+
+        if data.mime_type != mime_type || data.bytes != bytes {
+            return Err(format!("Expected '{uri}' to decode as {mime_type} {bytes:?}, got {data:?}."));
+        }
+    }
+
+    for bad in ["image/png;base64,AAAA", "data:image/png;base64", "data:,%4"] {
+        if decode(bad).is_ok() {
+            return Err(format!("Expected '{bad}' to fail to decode."));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn data_uri_round_trips_images() -> Result<(), String> {
+    use crate::{color, convert::ConvertableFrom};
+    use crate::image::format::bitmap::{BitDepth, BitmapConvertData, ImageConvertData};
+
+    let pixels = (0..6_u8).map(|n| color::ARGB { alpha: 0xFF, red: n * 40, green: 0x10, blue: 0xFF - n }).collect();
+    let image = Image::new_pixels(3, 2, pixels);
+
+    for format in [ImageFormat::Bitmap, ImageFormat::Gif, ImageFormat::Netpbm] {
+        let uri = from_image(&image, format)?;
+
+        if !uri.starts_with(&format!("data:{};base64,", format.mime_type())) || to_image(&uri)? != image {
+            return Err(format!("{} data URI did not round trip.", format.name()));
+        }
+    }
+
+    if from_image(&image, ImageFormat::Png).is_ok() {
+        return Err(String::from("Expected writing a png data URI to fail."));
+    }
+
+    //A media type that doesn't match the bytes falls back to detecting the format
+    let bitmap = Bitmap::try_convert_from(image.clone(), BitmapConvertData { bit_depth: BitDepth::Bits(24), ..Default::default() })?;
+    let mislabeled = from_bitmap(bitmap)?.replacen("image/bmp", "image/png", 1);
+    let decoded = to_image(&mislabeled)?;
+
+    if Image::try_convert_from(Bitmap::try_from(decode(&mislabeled)?.bytes)?, ImageConvertData::default())? != decoded {
+        return Err(String::from("Mislabeled data URI did not decode as a bitmap."));
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,400 @@
+//Images are inflated with this, so it must not panic on any input
+#![deny(clippy::indexing_slicing, clippy::unwrap_used, clippy::expect_used)]
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing, clippy::unwrap_used)]
+mod tests;
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+use crate::error::ImageError;
+
+///
+/// Base lengths of the length codes 257 to 285, and the number of extra bits to add to each
+///
+const LENGTH_BASES: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA_BITS: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+
+///
+/// Base distances of the distance codes 0 to 29, and the number of extra bits to add to each
+///
+const DISTANCE_BASES: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+///
+/// The order in which the lengths of the code length code are stored
+///
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+const MAX_CODE_LENGTH: usize = 15;
+const END_OF_BLOCK: u16 = 256;
+
+///
+/// Decompress a zlib stream (RFC 1950), checking its checksum. Fails if
+/// it would decompress to more than limit bytes.
+///
+pub fn decompress(bytes: &[u8], limit: usize) -> Result<Vec<u8>, ImageError> {
+    let [cmf, flg, ..] = *bytes else {
+        return Err(ImageError::Decode(String::from("Zlib stream is truncated.")));
+    };
+
+    if cmf & 0x0F != 8 || cmf >> 4 > 7 {
+        return Err(ImageError::Decode(format!("Zlib compression method {:#04x} is not deflate.", cmf)));
+    }
+
+    if !(((cmf as u16) << 8) | flg as u16).is_multiple_of(31) {
+        return Err(ImageError::Decode(String::from("Zlib header check bits are wrong.")));
+    }
+
+    if flg & 0x20 != 0 {
+        return Err(ImageError::Unsupported(String::from("Zlib streams with a preset dictionary are not supported.")));
+    }
+
+    let mut reader = BitReader::new(bytes.get(2..).unwrap_or_default());
+    let data = inflate_from(&mut reader, limit)?;
+
+    let checksum = reader.aligned_bytes(4)
+        .map(|bytes| bytes.iter().fold(0_u32, |value, byte| (value << 8) | *byte as u32))
+        .ok_or_else(|| ImageError::Decode(String::from("Zlib stream is missing its checksum.")))?;
+
+    if checksum != adler32(&data) {
+        return Err(ImageError::Decode(String::from("Zlib checksum does not match the data.")));
+    }
+
+    Ok(data)
+}
+
+///
+/// Decompress a raw deflate stream (RFC 1951). Fails if it would
+/// decompress to more than limit bytes.
+///
+pub fn inflate(bytes: &[u8], limit: usize) -> Result<Vec<u8>, ImageError> {
+    inflate_from(&mut BitReader::new(bytes), limit)
+}
+
+///
+/// The Adler-32 checksum of the bytes, as used by zlib
+///
+pub fn adler32(bytes: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+
+    //Sums can go this many bytes before they could overflow
+    let (mut a, mut b) = (1_u32, 0_u32);
+
+    for chunk in bytes.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+
+        a %= MODULUS;
+        b %= MODULUS;
+    }
+
+    (b << 16) | a
+}
+
+fn inflate_from(reader: &mut BitReader, limit: usize) -> Result<Vec<u8>, ImageError> {
+    let mut out = Vec::new();
+
+    loop {
+        let last = reader.bits(1)? == 1;
+
+        match reader.bits(2)? {
+            0 => stored(reader, &mut out, limit)?,
+            1 => {
+                let (literals, distances) = fixed_codes()?;
+                compressed(reader, &mut out, limit, &literals, &distances)?
+            },
+            2 => {
+                let (literals, distances) = dynamic_codes(reader)?;
+                compressed(reader, &mut out, limit, &literals, &distances)?
+            },
+            _ => return Err(ImageError::Decode(String::from("Deflate block type 3 is reserved.")))
+        }
+
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+fn stored(reader: &mut BitReader, out: &mut Vec<u8>, limit: usize) -> Result<(), ImageError> {
+    let header = reader.aligned_bytes(4)
+        .ok_or_else(|| ImageError::Decode(String::from("Deflate stored block is truncated.")))?;
+
+    let (length, complement) = match *header {
+        [a, b, c, d] => (u16::from_le_bytes([a, b]), u16::from_le_bytes([c, d])),
+        _ => (0, 0)
+    };
+
+    if length != !complement {
+        return Err(ImageError::Decode(String::from("Deflate stored block length does not match its complement.")));
+    }
+
+    let data = reader.aligned_bytes(length as usize)
+        .ok_or_else(|| ImageError::Decode(String::from("Deflate stored block is truncated.")))?;
+
+    check_limit(out.len() + data.len(), limit)?;
+    out.extend_from_slice(data);
+
+    Ok(())
+}
+
+fn compressed(reader: &mut BitReader, out: &mut Vec<u8>, limit: usize, literals: &Huffman, distances: &Huffman) -> Result<(), ImageError> {
+    loop {
+        let symbol = literals.decode(reader)?;
+
+        if symbol < END_OF_BLOCK {
+            check_limit(out.len() + 1, limit)?;
+            out.push(symbol as u8);
+            continue;
+        }
+
+        if symbol == END_OF_BLOCK {
+            return Ok(());
+        }
+
+        let code = (symbol - END_OF_BLOCK - 1) as usize;
+
+        let length = match (LENGTH_BASES.get(code), LENGTH_EXTRA_BITS.get(code)) {
+            (Some(base), Some(extra)) => *base as usize + reader.bits(*extra)? as usize,
+            _ => return Err(ImageError::Decode(format!("Deflate length code {symbol} is invalid.")))
+        };
+
+        let code = distances.decode(reader)? as usize;
+
+        let distance = match (DISTANCE_BASES.get(code), DISTANCE_EXTRA_BITS.get(code)) {
+            (Some(base), Some(extra)) => *base as usize + reader.bits(*extra)? as usize,
+            _ => return Err(ImageError::Decode(format!("Deflate distance code {code} is invalid.")))
+        };
+
+        if distance > out.len() {
+            return Err(ImageError::Decode(format!("Deflate distance {distance} is before the start of the data.")));
+        }
+
+        check_limit(out.len() + length, limit)?;
+
+        //The copy may overlap what it is copying, repeating it, so go a byte at a time
+        let start = out.len() - distance;
+
+        for index in start..(start + length) {
+            let byte = out.get(index).copied().unwrap_or_default();
+            out.push(byte);
+        }
+    }
+}
+
+fn check_limit(length: usize, limit: usize) -> Result<(), ImageError> {
+    if length > limit {
+        Err(ImageError::Decode(format!("Compressed data is larger than the expected {limit} bytes.")))
+    }
+    else {
+        Ok(())
+    }
+}
+
+fn fixed_codes() -> Result<(Huffman, Huffman), ImageError> {
+    let literals: Vec<u8> = (0..288)
+        .map(|symbol| match symbol {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8
+        })
+        .collect();
+
+    Ok((Huffman::new(&literals)?, Huffman::new(&[5; 30])?))
+}
+
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), ImageError> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0_u8; 19];
+
+    for position in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        if let Some(length) = code_lengths.get_mut(*position) {
+            *length = reader.bits(3)? as u8;
+        }
+    }
+
+    let code_length_code = Huffman::new(&code_lengths)?;
+
+    //The literal and distance lengths are one sequence, and repeats may cross from one to the other
+    let mut lengths: Vec<u8> = Vec::with_capacity(literal_count + distance_count);
+
+    while lengths.len() < literal_count + distance_count {
+        let (length, repeat) = match code_length_code.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = lengths.last().copied()
+                    .ok_or_else(|| ImageError::Decode(String::from("Deflate code lengths repeat before the first length.")))?;
+
+                (previous, 3 + reader.bits(2)? as usize)
+            },
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize)
+        };
+
+        if lengths.len() + repeat > literal_count + distance_count {
+            return Err(ImageError::Decode(String::from("Deflate code lengths repeat past the end.")));
+        }
+
+        lengths.extend(core::iter::repeat_n(length, repeat));
+    }
+
+    let (literals, distances) = lengths.split_at(literal_count);
+
+    if literals.get(END_OF_BLOCK as usize).copied().unwrap_or_default() == 0 {
+        return Err(ImageError::Decode(String::from("Deflate block has no end of block code.")));
+    }
+
+    Ok((Huffman::new(literals)?, Huffman::new(distances)?))
+}
+
+///
+/// A canonical Huffman code, as the number of codes of each length and
+/// the symbols in order of their codes
+///
+struct Huffman {
+    counts: [u16; MAX_CODE_LENGTH + 1],
+    symbols: Vec<u16>
+}
+
+impl Huffman {
+    ///
+    /// The code with the given length for each symbol, where 0 means unused
+    ///
+    fn new(lengths: &[u8]) -> Result<Self, ImageError> {
+        let mut counts = [0_u16; MAX_CODE_LENGTH + 1];
+
+        for length in lengths {
+            match counts.get_mut(*length as usize) {
+                Some(count) => *count += 1,
+                None => return Err(ImageError::Decode(format!("Deflate code length {length} is too long.")))
+            }
+        }
+
+        //Offsets of the first symbol of each length in the symbols
+        let mut offsets = [0_u16; MAX_CODE_LENGTH + 2];
+        let mut left = 1_i32;
+
+        for length in 1..=MAX_CODE_LENGTH {
+            let count = counts.get(length).copied().unwrap_or_default();
+
+            //More codes than there are bits for; fewer is allowed, i.e. for a single distance code
+            left = (left << 1) - count as i32;
+
+            if left < 0 {
+                return Err(ImageError::Decode(String::from("Deflate code lengths are oversubscribed.")));
+            }
+
+            let next = offsets.get(length).copied().unwrap_or_default() + count;
+
+            if let Some(offset) = offsets.get_mut(length + 1) {
+                *offset = next;
+            }
+        }
+
+        let mut symbols = vec![0_u16; lengths.iter().filter(|length| **length != 0).count()];
+
+        for (symbol, length) in lengths.iter().enumerate().filter(|(_, length)| **length != 0) {
+            if let Some(offset) = offsets.get_mut(*length as usize) {
+                if let Some(slot) = symbols.get_mut(*offset as usize) {
+                    *slot = symbol as u16;
+                }
+
+                *offset += 1;
+            }
+        }
+
+        if let Some(count) = counts.first_mut() {
+            *count = 0;
+        }
+
+        Ok(Self {
+            counts,
+            symbols
+        })
+    }
+
+    ///
+    /// Read one code, a bit at a time
+    ///
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, ImageError> {
+        let (mut code, mut first, mut index) = (0_i32, 0_i32, 0_i32);
+
+        for count in self.counts.iter().skip(1) {
+            code |= reader.bits(1)? as i32;
+            let count = *count as i32;
+
+            if code - first < count {
+                return self.symbols.get((index + code - first) as usize)
+                    .copied()
+                    .ok_or_else(|| ImageError::Decode(String::from("Deflate code is invalid.")));
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(ImageError::Decode(String::from("Deflate code is invalid.")))
+    }
+}
+
+///
+/// Reads bits from the bytes, least significant first, as deflate stores them
+///
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    bit: u8
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            position: 0,
+            bit: 0
+        }
+    }
+
+    fn bits(&mut self, count: u8) -> Result<u32, ImageError> {
+        let mut value = 0_u32;
+
+        for shift in 0..count {
+            let byte = self.bytes.get(self.position)
+                .ok_or_else(|| ImageError::Decode(String::from("Deflate stream is truncated.")))?;
+
+            value |= (((byte >> self.bit) & 1) as u32) << shift;
+
+            self.bit += 1;
+
+            if self.bit == 8 {
+                self.bit = 0;
+                self.position += 1;
+            }
+        }
+
+        Ok(value)
+    }
+
+    ///
+    /// Skip to the next whole byte, then read the given number of bytes
+    ///
+    fn aligned_bytes(&mut self, count: usize) -> Option<&'a [u8]> {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.position += 1;
+        }
+
+        let bytes = self.bytes.get(self.position..(self.position + count))?;
+        self.position += count;
+
+        Some(bytes)
+    }
+}
\ No newline at end of file
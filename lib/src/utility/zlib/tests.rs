@@ -0,0 +1,57 @@
+use super::*;
+use alloc::string::ToString;
+
+#[test]
+fn decompress_each_block_type() -> Result<(), String> {
+    let cases: [(&[u8], &[u8]); 3] = [
+        //Stored
+        (&[0x78, 0x01, 0x01, 0x03, 0x00, 0xfc, 0xff, 0x61, 0x62, 0x63, 0x02, 0x4d, 0x01, 0x27], b"abc"),
+        //Fixed codes, with back references
+        (&[0x78, 0xda, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x22, 0xcb, 0xf3, 0x8b, 0x72, 0x52, 0x00, 0x68, 0x7d, 0x08, 0xc5],
+            b"hello hello hello world"),
+        //Dynamic codes
+        (&[0x78, 0xda, 0x25, 0x8a, 0x81, 0x09, 0x00, 0x00, 0x08, 0xc2, 0x6e, 0x9d, 0xd5, 0xff, 0x2f, 0x94, 0x56, 0xa0, 0x38, 0x65, 0x40,
+            0x50, 0xb2, 0xf5, 0xe9, 0xdf, 0xa3, 0x41, 0x92, 0x25, 0xa8, 0xbe, 0xb8, 0xcf, 0x3a, 0x54, 0xc9, 0xf1, 0xab, 0xd5, 0x9e, 0x54,
+            0xa6, 0xc6, 0x9d, 0x8a, 0x05, 0x87, 0xb0, 0x26, 0x27],
+            b"aeaabaacbaabadbaaaaaaaabaacbaeabbbeabbaaaabcaaaabababcbaabbbaaabbababaabbacaabdaaaacabcaababadabaaba")
+    ];
+
+    for (compressed, expected) in cases {
+        let data = decompress(compressed, expected.len())?;
+
+        if data != expected {
+            return Err(format!("Expected {:?}, got {:?}.", String::from_utf8_lossy(expected), String::from_utf8_lossy(&data)));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn decompress_rejects_bad_streams() -> Result<(), String> {
+    let valid = [0x78, 0xda, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x22, 0xcb, 0xf3, 0x8b, 0x72, 0x52, 0x00, 0x68, 0x7d, 0x08, 0xc5];
+
+    let mut corrupt = valid;
+    corrupt[20] ^= 1;
+
+    let cases: [(&[u8], usize, &str); 4] = [
+        (&valid, 10, "larger than"),
+        (&corrupt, 100, "checksum"),
+        (&valid[..12], 100, "truncated"),
+        (&[0x78, 0x9c, 0x07], 100, "reserved")
+    ];
+
+    for (bytes, limit, expected) in cases {
+        match decompress(bytes, limit) {
+            Ok(_) => return Err(format!("Expected an error mentioning {expected}.")),
+            Err(err) if !err.to_string().contains(expected) => return Err(format!("Expected an error mentioning {expected}, got '{err}'.")),
+            Err(_) => ()
+        }
+    }
+
+    if adler32(b"Wikipedia") != 0x11E60398 {
+        return Err(format!("Unexpected checksum {:#010x}.", adler32(b"Wikipedia")));
+    }
+
+    Ok(())
+}
\ No newline at end of file